@@ -12,13 +12,14 @@ use blockstack_lib::clarity_vm::database::MemoryBackingStore;
 use blockstack_lib::core::StacksEpochId;
 use blockstack_lib::types::chainstate::BlockHeaderHash;
 use blockstack_lib::types::chainstate::BurnchainHeaderHash;
+use blockstack_lib::types::chainstate::ConsensusHash;
 use blockstack_lib::types::chainstate::VRFSeed;
 use blockstack_lib::types::proof::ClarityMarfTrieId;
 use blockstack_lib::vm::ast::build_ast;
 use blockstack_lib::vm::contexts::GlobalContext;
 use blockstack_lib::vm::costs::LimitedCostTracker;
 use blockstack_lib::vm::errors::InterpreterResult;
-use blockstack_lib::vm::{eval_all, ContractContext};
+use blockstack_lib::vm::{eval_all, ClarityVersion, ContractContext};
 use rand::Rng;
 
 use blockstack_lib::clarity_vm::database::marf::MarfedKV;
@@ -72,6 +73,10 @@ impl HeadersDB for TestHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        ConsensusHash::from_bytes(&id_bhh.0[0..20])
+    }
 }
 
 fn as_hash160(inp: u32) -> [u8; 20] {
@@ -492,12 +497,13 @@ fn expensive_contract_test(scaling: u32, buildup_count: u32, genesis_size: u32)
 
 pub fn execute_in_epoch(program: &str, epoch: StacksEpochId) -> InterpreterResult<Option<Value>> {
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let clarity_version = ClarityVersion::default_for_epoch(epoch);
+    let mut contract_context = ContractContext::new(contract_id.clone(), clarity_version);
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context = GlobalContext::new(false, conn, LimitedCostTracker::new_free(), epoch);
     global_context.execute(|g| {
-        let parsed = build_ast(&contract_id, program, &mut ())?.expressions;
+        let parsed = build_ast(&contract_id, program, &mut (), clarity_version)?.expressions;
         eval_all(&parsed, &mut contract_context, g)
     })
 }
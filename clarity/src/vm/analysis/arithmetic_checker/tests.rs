@@ -24,6 +24,7 @@ use crate::vm::functions::define::DefineFunctions;
 use crate::vm::functions::NativeFunctions;
 use crate::vm::types::QualifiedContractIdentifier;
 use crate::vm::variables::NativeVariables;
+use crate::vm::ClarityVersion;
 
 fn arithmetic_check(contract: &str) -> Result<(), Error> {
     let contract_identifier = QualifiedContractIdentifier::transient();
@@ -33,6 +34,7 @@ fn arithmetic_check(contract: &str) -> Result<(), Error> {
         contract_identifier,
         expressions,
         LimitedCostTracker::new_free(),
+        ClarityVersion::Clarity1,
     );
 
     ArithmeticOnlyChecker::run(&analysis)
@@ -234,6 +236,8 @@ fn test_functions() {
            (try! (some 4))
            (some 5)))
          (define-read-only (bar) (foo))",
+        "(define-private (foo (a uint) (b uint))
+           (bit-or (bit-and a b u255) (bit-not (bit-shift-left a u4)) (bit-shift-right b u2)))",
     ];
 
     for contract in good_tests.iter() {
@@ -31,6 +31,7 @@ use crate::vm::costs::LimitedCostTracker;
 use crate::vm::database::STORE_CONTRACT_SRC_INTERFACE;
 use crate::vm::representations::SymbolicExpression;
 use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
+use crate::vm::ClarityVersion;
 
 pub use self::analysis_db::AnalysisDatabase;
 pub use self::errors::{CheckError, CheckErrors, CheckResult};
@@ -43,9 +44,19 @@ use self::type_checker::TypeChecker;
 
 /// Used by CLI tools like the docs generator. Not used in production
 pub fn mem_type_check(snippet: &str) -> CheckResult<(Option<TypeSignature>, ContractAnalysis)> {
-    use crate::vm::ast::parse;
+    mem_type_check_version(snippet, ClarityVersion::latest())
+}
+
+/// Type-check a snippet at the given Clarity version
+pub fn mem_type_check_version(
+    snippet: &str,
+    clarity_version: ClarityVersion,
+) -> CheckResult<(Option<TypeSignature>, ContractAnalysis)> {
+    use crate::vm::ast::build_ast;
     let contract_identifier = QualifiedContractIdentifier::transient();
-    let mut contract = parse(&contract_identifier, snippet).unwrap();
+    let mut contract = build_ast(&contract_identifier, snippet, &mut (), clarity_version)
+        .unwrap()
+        .expressions;
     let mut marf = MemoryBackingStore::new();
     let mut analysis_db = marf.as_analysis_db();
     let cost_tracker = LimitedCostTracker::new_free();
@@ -55,6 +66,7 @@ pub fn mem_type_check(snippet: &str) -> CheckResult<(Option<TypeSignature>, Cont
         &mut analysis_db,
         false,
         cost_tracker,
+        clarity_version,
     ) {
         Ok(x) => {
             // return the first type result of the type checker
@@ -78,6 +90,23 @@ pub fn type_check(
     expressions: &mut [SymbolicExpression],
     analysis_db: &mut AnalysisDatabase,
     insert_contract: bool,
+) -> CheckResult<ContractAnalysis> {
+    type_check_version(
+        contract_identifier,
+        expressions,
+        analysis_db,
+        insert_contract,
+        ClarityVersion::Clarity1,
+    )
+}
+
+#[cfg(test)]
+pub fn type_check_version(
+    contract_identifier: &QualifiedContractIdentifier,
+    expressions: &mut [SymbolicExpression],
+    analysis_db: &mut AnalysisDatabase,
+    insert_contract: bool,
+    clarity_version: ClarityVersion,
 ) -> CheckResult<ContractAnalysis> {
     run_analysis(
         &contract_identifier,
@@ -87,6 +116,7 @@ pub fn type_check(
         // for the type check tests, the cost tracker's epoch doesn't
         //  matter: the costs in those tests are all free anyways.
         LimitedCostTracker::new_free(),
+        clarity_version,
     )
     .map_err(|(e, _cost_tracker)| e)
 }
@@ -97,11 +127,13 @@ pub fn run_analysis(
     analysis_db: &mut AnalysisDatabase,
     save_contract: bool,
     cost_tracker: LimitedCostTracker,
+    clarity_version: ClarityVersion,
 ) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
     let mut contract_analysis = ContractAnalysis::new(
         contract_identifier.clone(),
         expressions.to_vec(),
         cost_tracker,
+        clarity_version,
     );
     let result = analysis_db.execute(|db| {
         ReadOnlyChecker::run_pass(&mut contract_analysis, db)?;
@@ -22,6 +22,7 @@ use crate::vm::ast::errors::ParseErrors;
 use crate::vm::ast::{build_ast, parse};
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
+use crate::vm::ClarityVersion;
 
 #[test]
 fn test_dynamic_dispatch_by_defining_trait() {
@@ -291,7 +292,13 @@ fn test_define_map_storing_trait_references() {
     let dispatching_contract_id =
         QualifiedContractIdentifier::local("dispatching-contract").unwrap();
 
-    let err = build_ast(&dispatching_contract_id, dispatching_contract_src, &mut ()).unwrap_err();
+    let err = build_ast(
+        &dispatching_contract_id,
+        dispatching_contract_src,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )
+    .unwrap_err();
 
     match err.err {
         ParseErrors::TraitReferenceNotAllowed => {}
@@ -309,7 +316,13 @@ fn test_cycle_in_traits_1_contract() {
     let dispatching_contract_id =
         QualifiedContractIdentifier::local("dispatching-contract").unwrap();
 
-    let err = build_ast(&dispatching_contract_id, dispatching_contract_src, &mut ()).unwrap_err();
+    let err = build_ast(
+        &dispatching_contract_id,
+        dispatching_contract_src,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )
+    .unwrap_err();
     match err.err {
         ParseErrors::CircularReference(_) => {}
         _ => panic!("{:?}", err),
@@ -504,7 +517,13 @@ fn test_dynamic_dispatch_collision_trait() {
 
     let _contract_defining_trait =
         parse(&contract_defining_trait_id, contract_defining_trait_src).unwrap();
-    let err = build_ast(&dispatching_contract_id, dispatching_contract_src, &mut ()).unwrap_err();
+    let err = build_ast(
+        &dispatching_contract_id,
+        dispatching_contract_src,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )
+    .unwrap_err();
     match err.err {
         ParseErrors::NameAlreadyUsed(_) => {}
         _ => panic!("{:?}", err),
@@ -523,7 +542,13 @@ fn test_dynamic_dispatch_collision_defined_trait() {
     let dispatching_contract_id =
         QualifiedContractIdentifier::local("dispatching-contract").unwrap();
 
-    let err = build_ast(&dispatching_contract_id, dispatching_contract_src, &mut ()).unwrap_err();
+    let err = build_ast(
+        &dispatching_contract_id,
+        dispatching_contract_src,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )
+    .unwrap_err();
     match err.err {
         ParseErrors::NameAlreadyUsed(_) => {}
         _ => panic!("{:?}", err),
@@ -548,7 +573,13 @@ fn test_dynamic_dispatch_collision_imported_trait() {
 
     let _contract_defining_trait =
         parse(&contract_defining_trait_id, contract_defining_trait_src).unwrap();
-    let err = build_ast(&dispatching_contract_id, dispatching_contract_src, &mut ()).unwrap_err();
+    let err = build_ast(
+        &dispatching_contract_id,
+        dispatching_contract_src,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )
+    .unwrap_err();
     match err.err {
         ParseErrors::NameAlreadyUsed(_) => {}
         _ => panic!("{:?}", err),
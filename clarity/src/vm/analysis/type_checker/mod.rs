@@ -34,6 +34,7 @@ use crate::vm::types::{
     QualifiedContractIdentifier, TupleTypeSignature, TypeSignature, Value,
 };
 use crate::vm::variables::NativeVariables;
+use crate::vm::ClarityVersion;
 use std::collections::{BTreeMap, HashMap};
 use std::convert::TryInto;
 
@@ -75,6 +76,7 @@ pub struct TypeChecker<'a, 'b> {
     function_return_tracker: Option<Option<TypeSignature>>,
     db: &'a mut AnalysisDatabase<'b>,
     pub cost_track: LimitedCostTracker,
+    clarity_version: ClarityVersion,
 }
 
 impl CostTracker for TypeChecker<'_, '_> {
@@ -115,7 +117,8 @@ impl AnalysisPass for TypeChecker<'_, '_> {
         analysis_db: &mut AnalysisDatabase,
     ) -> CheckResult<()> {
         let cost_track = contract_analysis.take_contract_cost_tracker();
-        let mut command = TypeChecker::new(analysis_db, cost_track);
+        let mut command =
+            TypeChecker::new(analysis_db, cost_track, contract_analysis.clarity_version);
         // run the analysis, and replace the cost tracker whether or not the
         //   analysis succeeded.
         match command.run(contract_analysis) {
@@ -234,6 +237,28 @@ impl FunctionType {
 
                 Ok(TypeSignature::BoolType)
             }
+            FunctionType::BitShift => {
+                check_argument_count(2, args)?;
+                let (input, shamt) = (&args[0], &args[1]);
+                analysis_typecheck_cost(accounting, &TypeSignature::IntType, input)?;
+                analysis_typecheck_cost(accounting, &TypeSignature::UIntType, shamt)?;
+
+                if input != &TypeSignature::IntType && input != &TypeSignature::UIntType {
+                    return Err(CheckErrors::UnionTypeError(
+                        vec![TypeSignature::IntType, TypeSignature::UIntType],
+                        input.clone(),
+                    )
+                    .into());
+                }
+
+                if shamt != &TypeSignature::UIntType {
+                    return Err(
+                        CheckErrors::TypeError(TypeSignature::UIntType, shamt.clone()).into(),
+                    );
+                }
+
+                Ok(input.clone())
+            }
         }
     }
 
@@ -287,8 +312,8 @@ fn trait_type_size(trait_sig: &BTreeMap<ClarityName, FunctionSignature>) -> Chec
     Ok(total_size)
 }
 
-fn type_reserved_variable(variable_name: &str) -> Option<TypeSignature> {
-    if let Some(variable) = NativeVariables::lookup_by_name(variable_name) {
+fn type_reserved_variable(variable_name: &str, version: &ClarityVersion) -> Option<TypeSignature> {
+    if let Some(variable) = NativeVariables::lookup_by_name_at_version(variable_name, version) {
         use crate::vm::variables::NativeVariables::*;
         let var_type = match variable {
             TxSender => TypeSignature::PrincipalType,
@@ -315,10 +340,12 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
     fn new(
         db: &'a mut AnalysisDatabase<'b>,
         cost_track: LimitedCostTracker,
+        clarity_version: ClarityVersion,
     ) -> TypeChecker<'a, 'b> {
         Self {
             db,
             cost_track,
+            clarity_version,
             contract_context: ContractContext::new(),
             function_return_tracker: None,
             type_map: TypeMap::new(),
@@ -611,7 +638,9 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
         args: &[SymbolicExpression],
         context: &TypingContext,
     ) -> Option<TypeResult> {
-        if let Some(ref native_function) = NativeFunctions::lookup_by_name(function) {
+        if let Some(ref native_function) =
+            NativeFunctions::lookup_by_name_at_version(function, &self.clarity_version)
+        {
             let typed_function = TypedNativeFunction::type_native_function(native_function);
             Some(typed_function.type_check_appliction(self, args, context))
         } else {
@@ -653,7 +682,7 @@ impl<'a, 'b> TypeChecker<'a, 'b> {
     fn lookup_variable(&mut self, name: &str, context: &TypingContext) -> TypeResult {
         runtime_cost(ClarityCostFunction::AnalysisLookupVariableConst, self, 0)?;
 
-        if let Some(type_result) = type_reserved_variable(name) {
+        if let Some(type_result) = type_reserved_variable(name, &self.clarity_version) {
             Ok(type_result)
         } else if let Some(type_result) = self.contract_context.get_variable_type(name) {
             Ok(type_result.clone())
@@ -0,0 +1,59 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::TryFrom;
+
+use crate::vm::representations::SymbolicExpression;
+use crate::vm::types::{BufferLength, SequenceSubtype, TypeSignature, MAX_VALUE_SIZE};
+
+use crate::vm::analysis::type_checker::{
+    check_argument_count, CheckErrors, TypeChecker, TypeResult, TypingContext,
+};
+
+/// `to-consensus-buff` returns a buffer large enough to hold the serialization
+///  of any value of its input type.
+pub fn check_special_to_consensus_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(1, args)?;
+
+    let input_type = checker.type_check(&args[0], context)?;
+    let buffer_max_len = input_type
+        .max_serialized_size()
+        .filter(|max_len| *max_len <= MAX_VALUE_SIZE)
+        .ok_or(CheckErrors::ValueTooLarge)?;
+
+    Ok(TypeSignature::SequenceType(SequenceSubtype::BufferType(
+        BufferLength::try_from(buffer_max_len)?,
+    )))
+}
+
+/// `from-consensus-buff` takes a type signature and a buffer, and returns an
+///  optional of that type.
+pub fn check_special_from_consensus_buff(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let result_type = TypeSignature::parse_type_repr(&args[0], checker)?;
+    checker.type_check_expects(&args[1], context, &TypeSignature::max_buffer())?;
+
+    Ok(TypeSignature::new_option(result_type)?)
+}
@@ -21,9 +21,10 @@ use crate::vm::analysis::errors::{CheckError, CheckErrors, CheckResult};
 use crate::vm::errors::{Error as InterpError, RuntimeErrorType};
 use crate::vm::functions::{handle_binding_list, NativeFunctions};
 use crate::vm::types::{
-    BlockInfoProperty, FixedFunction, FunctionArg, FunctionSignature, FunctionType, PrincipalData,
-    TupleTypeSignature, TypeSignature, Value, BUFF_20, BUFF_32, BUFF_33, BUFF_64, BUFF_65,
-    MAX_VALUE_SIZE,
+    BlockInfoProperty, BufferLength, BurnBlockInfoProperty, FixedFunction, FunctionArg,
+    FunctionSignature, FunctionType, PrincipalData, SequenceSubtype, StringSubtype,
+    TupleTypeSignature, TypeSignature, Value, BUFF_1, BUFF_16, BUFF_20, BUFF_32, BUFF_33, BUFF_64,
+    BUFF_65, MAX_VALUE_SIZE,
 };
 use crate::vm::{ClarityName, SymbolicExpression, SymbolicExpressionType};
 use std::convert::TryFrom;
@@ -34,6 +35,7 @@ use crate::vm::costs::{
 };
 
 mod assets;
+mod conversions;
 mod maps;
 mod options;
 mod sequences;
@@ -476,6 +478,43 @@ fn check_principal_of(
     Ok(TypeSignature::new_response(TypeSignature::PrincipalType, TypeSignature::UIntType).unwrap())
 }
 
+fn check_principal_construct(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_arguments_at_least(2, args)?;
+    if args.len() > 3 {
+        return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+    }
+    checker.type_check_expects(&args[0], context, &BUFF_1)?;
+    checker.type_check_expects(&args[1], context, &BUFF_20)?;
+    if args.len() > 2 {
+        checker.type_check_expects(
+            &args[2],
+            context,
+            &TypeSignature::contract_name_string_ascii_type(),
+        )?;
+    }
+    Ok(TypeSignature::new_response(TypeSignature::PrincipalType, TypeSignature::UIntType).unwrap())
+}
+
+/// The tuple type returned by `principal-destruct`.
+fn principal_destruct_type() -> TypeSignature {
+    TypeSignature::TupleType(
+        TupleTypeSignature::try_from(vec![
+            ("version".into(), BUFF_1.clone()),
+            ("hash-bytes".into(), BUFF_20.clone()),
+            (
+                "name".into(),
+                TypeSignature::new_option(TypeSignature::contract_name_string_ascii_type())
+                    .expect("FAIL: optional contract name type is too large"),
+            ),
+        ])
+        .expect("FAIL: PrincipalDestruct failed to initialize type signature"),
+    )
+}
+
 fn check_secp256k1_recover(
     checker: &mut TypeChecker,
     args: &[SymbolicExpression],
@@ -520,6 +559,27 @@ fn check_get_block_info(
     Ok(TypeSignature::new_option(block_info_prop.type_result())?)
 }
 
+fn check_get_burn_block_info(
+    checker: &mut TypeChecker,
+    args: &[SymbolicExpression],
+    context: &TypingContext,
+) -> TypeResult {
+    check_argument_count(2, args)?;
+
+    let block_info_prop_str = args[0].match_atom().ok_or(CheckError::new(
+        CheckErrors::GetBurnBlockInfoExpectPropertyName,
+    ))?;
+
+    let block_info_prop =
+        BurnBlockInfoProperty::lookup_by_name(block_info_prop_str).ok_or(CheckError::new(
+            CheckErrors::NoSuchBurnBlockInfoProperty(block_info_prop_str.to_string()),
+        ))?;
+
+    checker.type_check_expects(&args[1], &context, &TypeSignature::UIntType)?;
+
+    Ok(TypeSignature::new_option(block_info_prop.type_result())?)
+}
+
 impl TypedNativeFunction {
     pub fn type_check_appliction(
         &self,
@@ -546,10 +606,16 @@ impl TypedNativeFunction {
             CmpGeq | CmpLeq | CmpLess | CmpGreater => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticComparison))
             }
-            Sqrti | Log2 => Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary)),
+            Sqrti | Log2 | BitwiseNot => {
+                Simple(SimpleNativeFunction(FunctionType::ArithmeticUnary))
+            }
             Modulo | Power | BitwiseXOR => {
                 Simple(SimpleNativeFunction(FunctionType::ArithmeticBinary))
             }
+            BitwiseAnd | BitwiseOr => {
+                Simple(SimpleNativeFunction(FunctionType::ArithmeticVariadic))
+            }
+            BitwiseLShift | BitwiseRShift => Simple(SimpleNativeFunction(FunctionType::BitShift)),
             And | Or => Simple(SimpleNativeFunction(FunctionType::Variadic(
                 TypeSignature::BoolType,
                 TypeSignature::BoolType,
@@ -711,6 +777,7 @@ impl TypedNativeFunction {
             ContractOf => Special(SpecialNativeFunction(&check_contract_of)),
             PrincipalOf => Special(SpecialNativeFunction(&check_principal_of)),
             GetBlockInfo => Special(SpecialNativeFunction(&check_get_block_info)),
+            GetBurnBlockInfo => Special(SpecialNativeFunction(&check_get_burn_block_info)),
             ConsSome => Special(SpecialNativeFunction(&options::check_special_some)),
             ConsOkay => Special(SpecialNativeFunction(&options::check_special_okay)),
             ConsError => Special(SpecialNativeFunction(&options::check_special_error)),
@@ -729,6 +796,72 @@ impl TypedNativeFunction {
             IsNone => Special(SpecialNativeFunction(&options::check_special_is_optional)),
             IsSome => Special(SpecialNativeFunction(&options::check_special_is_optional)),
             AtBlock => Special(SpecialNativeFunction(&check_special_at_block)),
+            ToConsensusBuff => Special(SpecialNativeFunction(
+                &conversions::check_special_to_consensus_buff,
+            )),
+            FromConsensusBuff => Special(SpecialNativeFunction(
+                &conversions::check_special_from_consensus_buff,
+            )),
+            Slice => Special(SpecialNativeFunction(&sequences::check_special_slice)),
+            ReplaceAt => Special(SpecialNativeFunction(&sequences::check_special_replace_at)),
+            BuffToIntLe | BuffToIntBe => {
+                Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                    args: vec![FunctionArg::new(
+                        BUFF_16.clone(),
+                        ClarityName::try_from("value".to_owned())
+                            .expect("FAIL: ClarityName failed to accept default arg name"),
+                    )],
+                    returns: TypeSignature::IntType,
+                })))
+            }
+            BuffToUIntLe | BuffToUIntBe => {
+                Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                    args: vec![FunctionArg::new(
+                        BUFF_16.clone(),
+                        ClarityName::try_from("value".to_owned())
+                            .expect("FAIL: ClarityName failed to accept default arg name"),
+                    )],
+                    returns: TypeSignature::UIntType,
+                })))
+            }
+            IntToAscii => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                TypeSignature::SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(
+                    BufferLength::try_from(40u32)
+                        .expect("FAIL: legal Clarity string length marked invalid"),
+                ))),
+            ))),
+            StringToInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::OptionalType(Box::new(TypeSignature::IntType)),
+            ))),
+            StringToUInt => Simple(SimpleNativeFunction(FunctionType::UnionArgs(
+                vec![
+                    TypeSignature::max_string_ascii(),
+                    TypeSignature::max_string_utf8(),
+                ],
+                TypeSignature::OptionalType(Box::new(TypeSignature::UIntType)),
+            ))),
+            PrincipalDestruct => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("principal".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: principal_destruct_type(),
+            }))),
+            PrincipalConstruct => Special(SpecialNativeFunction(&check_principal_construct)),
+            IsStandard => Simple(SimpleNativeFunction(FunctionType::Fixed(FixedFunction {
+                args: vec![FunctionArg::new(
+                    TypeSignature::PrincipalType,
+                    ClarityName::try_from("principal".to_owned())
+                        .expect("FAIL: ClarityName failed to accept default arg name"),
+                )],
+                returns: TypeSignature::BoolType,
+            }))),
         }
     }
 }
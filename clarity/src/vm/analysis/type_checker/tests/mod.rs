@@ -16,6 +16,7 @@
 
 use crate::vm::analysis::errors::CheckErrors;
 use crate::vm::analysis::mem_type_check;
+use crate::vm::analysis::mem_type_check_version;
 use crate::vm::analysis::type_check;
 use crate::vm::analysis::type_checker::{TypeChecker, TypeResult, TypingContext};
 use crate::vm::analysis::types::ContractAnalysis;
@@ -23,15 +24,17 @@ use crate::vm::analysis::AnalysisDatabase;
 use crate::vm::ast::errors::ParseErrors;
 use crate::vm::ast::{build_ast, parse};
 use crate::vm::contexts::OwnedEnvironment;
+use crate::vm::execute_v2;
 use crate::vm::representations::SymbolicExpression;
 use crate::vm::types::{
     FixedFunction, FunctionType, PrincipalData, QualifiedContractIdentifier, TypeSignature, Value,
-    BUFF_32, BUFF_64,
+    BUFF_1, BUFF_16, BUFF_20, BUFF_32, BUFF_64,
 };
 
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::types::TypeSignature::{BoolType, IntType, PrincipalType, SequenceType, UIntType};
 use crate::vm::types::{SequenceSubtype::*, StringSubtype::*};
+use crate::vm::ClarityVersion;
 
 use std::convert::TryInto;
 
@@ -50,6 +53,10 @@ fn ascii_type(size: u32) -> TypeSignature {
     TypeSignature::SequenceType(StringType(ASCII(size.try_into().unwrap()))).into()
 }
 
+fn utf8_type(size: u32) -> TypeSignature {
+    TypeSignature::SequenceType(StringType(UTF8(size.try_into().unwrap()))).into()
+}
+
 #[test]
 fn test_get_block_info() {
     let good = [
@@ -94,6 +101,320 @@ fn test_get_block_info() {
     }
 }
 
+#[test]
+fn test_get_burn_block_info() {
+    let good = [
+        "(get-burn-block-info? header-hash u1)",
+        "(get-burn-block-info? header-hash (+ u1 u2))",
+        "(get-burn-block-info? pox-addrs u1)",
+    ];
+    let expected = [
+        "(optional (buff 32))",
+        "(optional (buff 32))",
+        "(optional (tuple (addrs (list 2 (tuple (hashbytes (buff 20)) (version (buff 1))))) (payout uint)))",
+    ];
+
+    let bad = [
+        "(get-burn-block-info? none u1)",
+        "(get-burn-block-info? header-hash true)",
+        "(get-burn-block-info? header-hash 1)",
+        "(get-burn-block-info? header-hash)",
+    ];
+    let bad_expected = [
+        CheckErrors::NoSuchBurnBlockInfoProperty("none".to_string()),
+        CheckErrors::TypeError(UIntType, BoolType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_to_consensus_buff() {
+    let good = [
+        "(to-consensus-buff 1)",
+        "(to-consensus-buff u1)",
+        "(to-consensus-buff true)",
+        "(to-consensus-buff (some 1))",
+        "(to-consensus-buff 'S1G2081040G2081040G2081040G208105NK8PE5)",
+        "(to-consensus-buff \"abc\")",
+        "(to-consensus-buff (list 1 2 3))",
+        "(to-consensus-buff { a: 1, bc: u2 })",
+        "(to-consensus-buff (if true (ok 1) (err u1)))",
+    ];
+    let expected = [
+        "(buff 17)",
+        "(buff 17)",
+        "(buff 1)",
+        "(buff 18)",
+        "(buff 151)",
+        "(buff 8)",
+        "(buff 56)",
+        "(buff 44)",
+        "(buff 18)",
+    ];
+
+    let bad = [
+        "(to-consensus-buff)",
+        "(to-consensus-buff 1 2)",
+        "(define-private (serialize-max (a (buff 1048576))) (to-consensus-buff a))",
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(1, 0),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::ValueTooLarge,
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &mem_type_check(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_from_consensus_buff() {
+    let good = [
+        "(from-consensus-buff int 0x00)",
+        "(from-consensus-buff (list 3 uint) 0x00)",
+        "(from-consensus-buff { a: int } 0x00)",
+        "(from-consensus-buff (response bool (string-ascii 4)) (to-consensus-buff 1))",
+    ];
+    let expected = [
+        "(optional int)",
+        "(optional (list 3 uint))",
+        "(optional (tuple (a int)))",
+        "(optional (response bool (string-ascii 4)))",
+    ];
+
+    let bad = [
+        "(from-consensus-buff int)",
+        "(from-consensus-buff int u1)",
+        "(from-consensus-buff 1 0x00)",
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(2, 1),
+        CheckErrors::TypeError(TypeSignature::max_buffer(), UIntType),
+        CheckErrors::InvalidTypeDescription,
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_slice() {
+    let good = [
+        "(slice (list 1 2 3 4 5) u1 u3)",
+        "(slice \"abcde\" u1 u3)",
+        "(slice u\"abcde\" u1 u3)",
+        "(slice 0x0102 u0 u1)",
+    ];
+    let expected = [
+        "(optional (list 5 int))",
+        "(optional (string-ascii 5))",
+        "(optional (string-utf8 5))",
+        "(optional (buff 2))",
+    ];
+
+    let bad = [
+        "(slice (list 1 2 3))",
+        "(slice 1 u1 u2)",
+        "(slice (list 1 2 3) 1 u2)",
+        "(slice (list 1 2 3) u1 2)",
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(3, 1),
+        CheckErrors::ExpectedSequence(IntType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::TypeError(UIntType, IntType),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_replace_at() {
+    let good = [
+        "(replace-at (list 1 2 3) u1 4)",
+        "(replace-at \"abc\" u1 \"d\")",
+        "(replace-at u\"abc\" u1 u\"d\")",
+        "(replace-at 0x0102 u0 0x03)",
+    ];
+    let expected = [
+        "(optional (list 3 int))",
+        "(optional (string-ascii 3))",
+        "(optional (string-utf8 3))",
+        "(optional (buff 2))",
+    ];
+
+    let bad = [
+        "(replace-at (list 1 2 3) u1)",
+        "(replace-at 1 u1 2)",
+        "(replace-at (list 1 2 3) 1 4)",
+        "(replace-at (list 1 2 3) u1 u4)",
+        "(replace-at \"abc\" u1 \"de\")",
+    ];
+    let bad_expected = [
+        CheckErrors::IncorrectArgumentCount(3, 2),
+        CheckErrors::ExpectedSequence(IntType),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::TypeError(IntType, UIntType),
+        CheckErrors::TypeError(TypeSignature::min_string_ascii(), ascii_type(2)),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_int_conversions() {
+    let good = [
+        "(buff-to-int-le 0x01)",
+        "(buff-to-int-be 0x00000000000000000000000000000001)",
+        "(buff-to-uint-le 0x01)",
+        "(buff-to-uint-be 0x01)",
+        "(int-to-ascii 1)",
+        "(int-to-ascii u1)",
+        "(string-to-int \"1\")",
+        "(string-to-int u\"1\")",
+        "(string-to-uint \"1\")",
+        "(string-to-uint u\"1\")",
+    ];
+    let expected = [
+        "int",
+        "int",
+        "uint",
+        "uint",
+        "(string-ascii 40)",
+        "(string-ascii 40)",
+        "(optional int)",
+        "(optional int)",
+        "(optional uint)",
+        "(optional uint)",
+    ];
+
+    let bad = [
+        "(buff-to-int-le 0x0000000000000000000000000000000000)",
+        "(buff-to-uint-be \"a\")",
+        "(int-to-ascii 0x01)",
+        "(string-to-int 1)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(BUFF_16.clone(), buff_type(17)),
+        CheckErrors::TypeError(BUFF_16.clone(), ascii_type(1)),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], buff_type(1)),
+        CheckErrors::UnionTypeError(
+            vec![
+                TypeSignature::max_string_ascii(),
+                TypeSignature::max_string_utf8(),
+            ],
+            IntType,
+        ),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
+#[test]
+fn test_principal_natives() {
+    let good = [
+        "(principal-destruct 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)",
+        "(principal-destruct 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6.foo)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 \"foo\")",
+        "(is-standard 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)",
+        "(is-standard tx-sender)",
+    ];
+    let expected = [
+        "(tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1)))",
+        "(tuple (hash-bytes (buff 20)) (name (optional (string-ascii 40))) (version (buff 1)))",
+        "(response principal uint)",
+        "(response principal uint)",
+        "bool",
+        "bool",
+    ];
+
+    let bad = [
+        "(principal-destruct 0x00)",
+        "(principal-construct 0x1a)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 \"foo\" \"bar\")",
+        "(principal-construct 0x1a1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b000)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 u\"foo\")",
+        "(is-standard u1)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(PrincipalType, buff_type(1)),
+        CheckErrors::RequiresAtLeastArguments(2, 1),
+        CheckErrors::IncorrectArgumentCount(3, 4),
+        CheckErrors::TypeError(BUFF_1.clone(), buff_type(2)),
+        CheckErrors::TypeError(BUFF_20.clone(), buff_type(21)),
+        CheckErrors::TypeError(ascii_type(40), utf8_type(3)),
+        CheckErrors::TypeError(PrincipalType, UIntType),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_define_trait() {
     let good = [
@@ -133,7 +454,13 @@ fn test_define_trait() {
 
     let contract_identifier = QualifiedContractIdentifier::transient();
     for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
-        let res = build_ast(&contract_identifier, bad_test, &mut ()).unwrap_err();
+        let res = build_ast(
+            &contract_identifier,
+            bad_test,
+            &mut (),
+            ClarityVersion::Clarity1,
+        )
+        .unwrap_err();
         assert_eq!(expected, &res.err);
     }
 }
@@ -155,7 +482,13 @@ fn test_use_trait() {
 
     let contract_identifier = QualifiedContractIdentifier::transient();
     for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
-        let res = build_ast(&contract_identifier, bad_test, &mut ()).unwrap_err();
+        let res = build_ast(
+            &contract_identifier,
+            bad_test,
+            &mut (),
+            ClarityVersion::Clarity1,
+        )
+        .unwrap_err();
         assert_eq!(expected, &res.err);
     }
 }
@@ -170,7 +503,13 @@ fn test_impl_trait() {
 
     let contract_identifier = QualifiedContractIdentifier::transient();
     for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
-        let res = build_ast(&contract_identifier, bad_test, &mut ()).unwrap_err();
+        let res = build_ast(
+            &contract_identifier,
+            bad_test,
+            &mut (),
+            ClarityVersion::Clarity1,
+        )
+        .unwrap_err();
         assert_eq!(expected, &res.err);
     }
 }
@@ -440,7 +779,13 @@ fn test_trait_reference_unknown() {
 
     let contract_identifier = QualifiedContractIdentifier::transient();
     for (bad_test, expected) in bad.iter() {
-        let res = build_ast(&contract_identifier, bad_test, &mut ()).unwrap_err();
+        let res = build_ast(
+            &contract_identifier,
+            bad_test,
+            &mut (),
+            ClarityVersion::Clarity1,
+        )
+        .unwrap_err();
         assert_eq!(expected, &res.err);
     }
 }
@@ -494,6 +839,48 @@ fn test_simple_arithmetic_checks() {
     }
 }
 
+#[test]
+fn test_bitwise_checks() {
+    let good = [
+        "(bit-and 1 2 3)",
+        "(bit-or u1 u2)",
+        "(bit-not 1)",
+        "(bit-not u1)",
+        "(bit-shift-left 1 u2)",
+        "(bit-shift-right u1 u2)",
+    ];
+    let expected = ["int", "uint", "int", "uint", "int", "uint"];
+    let bad = [
+        "(bit-and 1 u2)",
+        "(bit-or)",
+        "(bit-not true)",
+        "(bit-not 1 2)",
+        "(bit-shift-left 1 2)",
+        "(bit-shift-right true u2)",
+        "(bit-shift-right u1)",
+    ];
+    let bad_expected = [
+        CheckErrors::TypeError(IntType, UIntType),
+        CheckErrors::RequiresAtLeastArguments(1, 0),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
+        CheckErrors::IncorrectArgumentCount(1, 2),
+        CheckErrors::TypeError(UIntType, IntType),
+        CheckErrors::UnionTypeError(vec![IntType, UIntType], BoolType),
+        CheckErrors::IncorrectArgumentCount(2, 1),
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", type_check_helper(&good_test).unwrap())
+        );
+    }
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        assert_eq!(expected, &type_check_helper(&bad_test).unwrap_err().err);
+    }
+}
+
 #[test]
 fn test_simple_hash_checks() {
     let good = [
@@ -933,11 +1320,38 @@ fn test_buff_fold() {
     ];
 
     for (good_test, expected) in good.iter().zip(expected.iter()) {
-        let type_sig = mem_type_check(good_test).unwrap().0.unwrap();
+        // `slice` is a reserved name from Clarity 2 on, but deployed Clarity 1 contracts that
+        //  define it must still type-check
+        let type_sig = mem_type_check_version(good_test, ClarityVersion::Clarity1)
+            .unwrap()
+            .0
+            .unwrap();
         assert_eq!(expected, &type_sig.to_string());
     }
 }
 
+#[test]
+fn test_define_reserved_slice() {
+    let snippet = "(define-private (slice (x (buff 1)) (acc uint)) (+ acc u1))
+        (fold slice 0x000102030405 u0)";
+
+    // Clarity 1 contracts may define `slice`
+    mem_type_check_version(snippet, ClarityVersion::Clarity1).unwrap();
+
+    // but in Clarity 2 it names the native function, so the contract doesn't type-check
+    let err = mem_type_check_version(snippet, ClarityVersion::Clarity2).unwrap_err();
+    assert_eq!(
+        err.err,
+        CheckErrors::IllegalOrUnknownFunctionApplication("slice".to_string())
+    );
+
+    // and the definition itself is rejected
+    assert_eq!(
+        execute_v2(snippet).unwrap_err(),
+        CheckErrors::NameAlreadyUsed("slice".to_string()).into()
+    );
+}
+
 #[test]
 fn test_buff_map() {
     let good = ["(map hash160 0x0102030405)"];
@@ -2101,7 +2515,12 @@ fn test_string_ascii_fold() {
     ];
 
     for (good_test, expected) in good.iter().zip(expected.iter()) {
-        let type_sig = mem_type_check(good_test).unwrap().0.unwrap();
+        // `slice` is a reserved name from Clarity 2 on, but deployed Clarity 1 contracts that
+        //  define it must still type-check
+        let type_sig = mem_type_check_version(good_test, ClarityVersion::Clarity1)
+            .unwrap()
+            .0
+            .unwrap();
         assert_eq!(expected, &type_sig.to_string());
     }
 }
@@ -2154,7 +2573,12 @@ fn test_string_utf8_fold() {
     ];
 
     for (good_test, expected) in good.iter().zip(expected.iter()) {
-        let type_sig = mem_type_check(good_test).unwrap().0.unwrap();
+        // `slice` is a reserved name from Clarity 2 on, but deployed Clarity 1 contracts that
+        //  define it must still type-check
+        let type_sig = mem_type_check_version(good_test, ClarityVersion::Clarity1)
+            .unwrap()
+            .0
+            .unwrap();
         assert_eq!(expected, &type_sig.to_string());
     }
 }
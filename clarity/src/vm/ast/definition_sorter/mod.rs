@@ -26,6 +26,7 @@ use crate::vm::representations::PreSymbolicExpressionType::{
 };
 use crate::vm::representations::{ClarityName, PreSymbolicExpression};
 use crate::vm::types::Value;
+use crate::vm::ClarityVersion;
 use std::collections::{HashMap, HashSet};
 use std::iter::FromIterator;
 
@@ -35,13 +36,15 @@ mod tests;
 pub struct DefinitionSorter {
     graph: Graph,
     top_level_expressions_map: HashMap<ClarityName, TopLevelExpressionIndex>,
+    clarity_version: ClarityVersion,
 }
 
 impl<'a> DefinitionSorter {
-    fn new() -> Self {
+    fn new(clarity_version: ClarityVersion) -> Self {
         Self {
             top_level_expressions_map: HashMap::new(),
             graph: Graph::new(),
+            clarity_version,
         }
     }
 
@@ -49,7 +52,7 @@ impl<'a> DefinitionSorter {
         contract_ast: &mut ContractAST,
         accounting: &mut T,
     ) -> ParseResult<()> {
-        let mut pass = DefinitionSorter::new();
+        let mut pass = DefinitionSorter::new(contract_ast.clarity_version);
         pass.run(contract_ast, accounting)?;
         Ok(())
     }
@@ -204,7 +207,10 @@ impl<'a> DefinitionSorter {
                                 }
                             }
                         } else if let Some(native_function) =
-                            NativeFunctions::lookup_by_name(function_name)
+                            NativeFunctions::lookup_by_name_at_version(
+                                function_name,
+                                &self.clarity_version,
+                            )
                         {
                             match native_function {
                                 NativeFunctions::ContractCall => {
@@ -23,11 +23,16 @@ use crate::vm::ast::parser;
 use crate::vm::ast::types::{BuildASTPass, ContractAST};
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::ClarityVersion;
 
 fn run_scoped_parsing_helper(contract: &str) -> ParseResult<ContractAST> {
     let contract_identifier = QualifiedContractIdentifier::transient();
     let pre_expressions = parser::parse(contract)?;
-    let mut contract_ast = ContractAST::new(contract_identifier.clone(), pre_expressions);
+    let mut contract_ast = ContractAST::new(
+        contract_identifier.clone(),
+        pre_expressions,
+        ClarityVersion::Clarity1,
+    );
     ExpressionIdentifier::run_pre_expression_pass(&mut contract_ast)?;
     DefinitionSorter::run_pass(&mut contract_ast, &mut ())?;
     Ok(contract_ast)
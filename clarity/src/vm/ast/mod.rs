@@ -28,6 +28,7 @@ use crate::vm::errors::{Error, RuntimeErrorType};
 
 use crate::vm::representations::SymbolicExpression;
 use crate::vm::types::QualifiedContractIdentifier;
+use crate::vm::ClarityVersion;
 
 use self::definition_sorter::DefinitionSorter;
 use self::errors::ParseResult;
@@ -39,19 +40,27 @@ use self::types::BuildASTPass;
 pub use self::types::ContractAST;
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 
-/// Legacy function
+/// Legacy function: parses `source_code` as a Clarity 1 contract
 pub fn parse(
     contract_identifier: &QualifiedContractIdentifier,
     source_code: &str,
 ) -> Result<Vec<SymbolicExpression>, Error> {
-    let ast = build_ast(contract_identifier, source_code, &mut ())?;
+    let ast = build_ast(
+        contract_identifier,
+        source_code,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )?;
     Ok(ast.expressions)
 }
 
+/// Parse a contract written in Clarity version `clarity_version`. The version
+///  determines which native function names the parser treats as natives.
 pub fn build_ast<T: CostTracker>(
     contract_identifier: &QualifiedContractIdentifier,
     source_code: &str,
     cost_track: &mut T,
+    clarity_version: ClarityVersion,
 ) -> ParseResult<ContractAST> {
     runtime_cost(
         ClarityCostFunction::AstParse,
@@ -59,7 +68,11 @@ pub fn build_ast<T: CostTracker>(
         source_code.len() as u64,
     )?;
     let pre_expressions = parser::parse(source_code)?;
-    let mut contract_ast = ContractAST::new(contract_identifier.clone(), pre_expressions);
+    let mut contract_ast = ContractAST::new(
+        contract_identifier.clone(),
+        pre_expressions,
+        clarity_version,
+    );
     StackDepthChecker::run_pass(&mut contract_ast)?;
     ExpressionIdentifier::run_pre_expression_pass(&mut contract_ast)?;
     DefinitionSorter::run_pass(&mut contract_ast, cost_track)?;
@@ -77,6 +90,7 @@ mod test {
     use crate::vm::costs::LimitedCostTracker;
     use crate::vm::representations::depth_traverse;
     use crate::vm::types::QualifiedContractIdentifier;
+    use crate::vm::ClarityVersion;
 
     #[test]
     fn test_expression_identification_tuples() {
@@ -89,6 +103,7 @@ mod test {
             &QualifiedContractIdentifier::transient(),
             &progn,
             &mut cost_track,
+            ClarityVersion::Clarity1,
         )
         .unwrap()
         .expressions;
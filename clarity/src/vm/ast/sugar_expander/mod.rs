@@ -130,7 +130,7 @@ mod test {
     use crate::vm::ast::types::ContractAST;
     use crate::vm::representations::{ContractName, PreSymbolicExpression, SymbolicExpression};
     use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
-    use crate::vm::{ast, Value};
+    use crate::vm::{ast, ClarityVersion, Value};
 
     fn make_pre_atom(
         x: &str,
@@ -426,7 +426,8 @@ mod test {
             "S1G2081040G2081040G2081040G208105NK8PE5.contract-a",
         )
         .unwrap();
-        let mut contract_ast = ContractAST::new(contract_id.clone(), pre_ast);
+        let mut contract_ast =
+            ContractAST::new(contract_id.clone(), pre_ast, ClarityVersion::Clarity1);
         let expander = SugarExpander::new(contract_id.issuer);
         expander.run(&mut contract_ast).unwrap();
         assert_eq!(
@@ -470,7 +471,8 @@ mod test {
             "S1G2081040G2081040G2081040G208105NK8PE5.contract-a",
         )
         .unwrap();
-        let mut contract_ast = ContractAST::new(contract_id.clone(), pre_ast);
+        let mut contract_ast =
+            ContractAST::new(contract_id.clone(), pre_ast, ClarityVersion::Clarity1);
         let expander = SugarExpander::new(contract_id.issuer);
         expander.run(&mut contract_ast).unwrap();
         assert_eq!(
@@ -497,7 +499,8 @@ mod test {
             "S1G2081040G2081040G2081040G208105NK8PE5.contract-a",
         )
         .unwrap();
-        let mut contract_ast = ContractAST::new(contract_id.clone(), pre_ast);
+        let mut contract_ast =
+            ContractAST::new(contract_id.clone(), pre_ast, ClarityVersion::Clarity1);
         let expander = SugarExpander::new(contract_id.issuer);
         expander.run(&mut contract_ast).unwrap();
         assert_eq!(
@@ -18,7 +18,7 @@ use crate::vm::ast::errors::ParseResult;
 use crate::vm::representations::{PreSymbolicExpression, SymbolicExpression, TraitDefinition};
 use crate::vm::types::signatures::FunctionSignature;
 use crate::vm::types::{QualifiedContractIdentifier, TraitIdentifier};
-use crate::vm::ClarityName;
+use crate::vm::{ClarityName, ClarityVersion};
 use std::collections::{HashMap, HashSet};
 use std::vec::Drain;
 
@@ -34,16 +34,19 @@ pub struct ContractAST {
     pub top_level_expression_sorting: Option<Vec<usize>>,
     pub referenced_traits: HashMap<ClarityName, TraitDefinition>,
     pub implemented_traits: HashSet<TraitIdentifier>,
+    pub clarity_version: ClarityVersion,
 }
 
 impl ContractAST {
     pub fn new(
         contract_identifier: QualifiedContractIdentifier,
         pre_expressions: Vec<PreSymbolicExpression>,
+        clarity_version: ClarityVersion,
     ) -> ContractAST {
         ContractAST {
             contract_identifier,
             pre_expressions,
+            clarity_version,
             expressions: Vec::new(),
             top_level_expression_sorting: Some(Vec::new()),
             referenced_traits: HashMap::new(),
@@ -11,7 +11,7 @@ use crate::vm::database::ClarityDatabase;
 use crate::vm::errors::Error as InterpreterError;
 use crate::vm::events::StacksTransactionEvent;
 use crate::vm::types::{PrincipalData, QualifiedContractIdentifier};
-use crate::vm::{ast, SymbolicExpression, Value};
+use crate::vm::{ast, ClarityVersion, SymbolicExpression, Value};
 use stacks_common::types::StacksEpochId;
 use std::fmt;
 
@@ -159,14 +159,33 @@ pub trait TransactionConnection: ClarityConnection {
     where
         F: FnOnce(&mut AnalysisDatabase, LimitedCostTracker) -> (LimitedCostTracker, R);
 
-    /// Analyze a provided smart contract, but do not write the analysis to the AnalysisDatabase
+    /// Analyze a provided smart contract at the default Clarity version of the current epoch,
+    ///  but do not write the analysis to the AnalysisDatabase
     fn analyze_smart_contract(
         &mut self,
         identifier: &QualifiedContractIdentifier,
         contract_content: &str,
+    ) -> Result<(ContractAST, ContractAnalysis), Error> {
+        let clarity_version = ClarityVersion::default_for_epoch(self.get_epoch());
+        self.analyze_versioned_smart_contract(identifier, clarity_version, contract_content)
+    }
+
+    /// Analyze a provided smart contract written in `clarity_version`, but do not write the
+    ///  analysis to the AnalysisDatabase. The returned AST carries the version, so a contract
+    ///  initialized from it executes at the same version.
+    fn analyze_versioned_smart_contract(
+        &mut self,
+        identifier: &QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+        contract_content: &str,
     ) -> Result<(ContractAST, ContractAnalysis), Error> {
         self.with_analysis_db(|db, mut cost_track| {
-            let ast_result = ast::build_ast(identifier, contract_content, &mut cost_track);
+            let ast_result = ast::build_ast(
+                identifier,
+                contract_content,
+                &mut cost_track,
+                clarity_version,
+            );
 
             let mut contract_ast = match ast_result {
                 Ok(x) => x,
@@ -179,6 +198,7 @@ pub trait TransactionConnection: ClarityConnection {
                 db,
                 false,
                 cost_track,
+                clarity_version,
             );
 
             match result {
@@ -42,7 +42,7 @@ use crate::vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, TraitIdentifier, TypeSignature,
     Value,
 };
-use crate::vm::{eval, is_reserved};
+use crate::vm::{eval, is_reserved, ClarityVersion};
 
 use crate::{types::chainstate::StacksBlockId, types::StacksEpochId};
 
@@ -211,6 +211,10 @@ pub struct ContractContext {
     pub meta_nft: HashMap<ClarityName, NonFungibleTokenMetadata>,
     pub meta_ft: HashMap<ClarityName, FungibleTokenMetadata>,
     pub data_size: u64,
+    /// the Clarity version of the contract -- contracts stored before versioning
+    ///  existed deserialize as Clarity 1.
+    #[serde(default)]
+    pub clarity_version: ClarityVersion,
 }
 
 pub struct LocalContext<'a> {
@@ -531,7 +535,10 @@ impl<'a> OwnedEnvironment<'a> {
         let epoch = StacksEpochId::Epoch2_05;
         OwnedEnvironment {
             context: GlobalContext::new(false, database, LimitedCostTracker::new_free(), epoch),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::default_for_epoch(epoch),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -546,7 +553,10 @@ impl<'a> OwnedEnvironment<'a> {
             .expect("FAIL: problem instantiating cost tracking");
         OwnedEnvironment {
             context: GlobalContext::new(use_mainnet, database, cost_track, epoch),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::default_for_epoch(epoch),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -571,7 +581,10 @@ impl<'a> OwnedEnvironment<'a> {
                 LimitedCostTracker::new_free(),
                 epoch_id,
             ),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::default_for_epoch(epoch_id),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -584,7 +597,10 @@ impl<'a> OwnedEnvironment<'a> {
     ) -> OwnedEnvironment<'a> {
         OwnedEnvironment {
             context: GlobalContext::new(mainnet, database, cost_tracker, epoch_id),
-            default_contract: ContractContext::new(QualifiedContractIdentifier::transient()),
+            default_contract: ContractContext::new(
+                QualifiedContractIdentifier::transient(),
+                ClarityVersion::default_for_epoch(epoch_id),
+            ),
             call_stack: CallStack::new(),
         }
     }
@@ -641,6 +657,21 @@ impl<'a> OwnedEnvironment<'a> {
         })
     }
 
+    pub fn initialize_versioned_contract(
+        &mut self,
+        contract_identifier: QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+        contract_content: &str,
+    ) -> Result<((), AssetMap, Vec<StacksTransactionEvent>)> {
+        self.execute_in_env(contract_identifier.issuer.clone().into(), |exec_env| {
+            exec_env.initialize_versioned_contract(
+                contract_identifier,
+                clarity_version,
+                contract_content,
+            )
+        })
+    }
+
     pub fn initialize_contract_from_ast(
         &mut self,
         contract_identifier: QualifiedContractIdentifier,
@@ -862,7 +893,17 @@ impl<'a, 'b> Environment<'a, 'b> {
         contract_identifier: &QualifiedContractIdentifier,
         program: &str,
     ) -> Result<Value> {
-        let parsed = ast::build_ast(contract_identifier, program, self)?.expressions;
+        // the snippet is evaluated in the contract's context, so it is parsed at the
+        //  contract's Clarity version.
+        self.global_context.begin();
+        let clarity_version = self
+            .global_context
+            .database
+            .get_contract_clarity_version(contract_identifier);
+        self.global_context.roll_back();
+        let clarity_version = clarity_version?;
+        let parsed =
+            ast::build_ast(contract_identifier, program, self, clarity_version)?.expressions;
 
         if parsed.len() < 1 {
             return Err(RuntimeErrorType::ParseError(
@@ -898,7 +939,8 @@ impl<'a, 'b> Environment<'a, 'b> {
     pub fn eval_raw(&mut self, program: &str) -> Result<Value> {
         let contract_id = QualifiedContractIdentifier::transient();
 
-        let parsed = ast::build_ast(&contract_id, program, self)?.expressions;
+        let clarity_version = *self.contract_context.get_clarity_version();
+        let parsed = ast::build_ast(&contract_id, program, self, clarity_version)?.expressions;
         if parsed.len() < 1 {
             return Err(RuntimeErrorType::ParseError(
                 "Expected a program of at least length 1".to_string(),
@@ -1057,12 +1099,28 @@ impl<'a, 'b> Environment<'a, 'b> {
         result
     }
 
+    /// Initialize a contract at the default Clarity version of the current epoch.
     pub fn initialize_contract(
         &mut self,
         contract_identifier: QualifiedContractIdentifier,
         contract_content: &str,
     ) -> Result<()> {
-        let contract_ast = ast::build_ast(&contract_identifier, contract_content, self)?;
+        let clarity_version = ClarityVersion::default_for_epoch(*self.epoch());
+        self.initialize_versioned_contract(contract_identifier, clarity_version, contract_content)
+    }
+
+    pub fn initialize_versioned_contract(
+        &mut self,
+        contract_identifier: QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+        contract_content: &str,
+    ) -> Result<()> {
+        let contract_ast = ast::build_ast(
+            &contract_identifier,
+            contract_content,
+            self,
+            clarity_version,
+        )?;
         self.initialize_contract_from_ast(contract_identifier, &contract_ast, &contract_content)
     }
 
@@ -1099,6 +1157,10 @@ impl<'a, 'b> Environment<'a, 'b> {
             self.global_context
                 .database
                 .insert_contract_hash(&contract_identifier, contract_string)?;
+            self.global_context.database.set_contract_clarity_version(
+                &contract_identifier,
+                contract_content.clarity_version,
+            );
             let memory_use = contract_string.len() as u64;
             self.add_memory(memory_use)?;
 
@@ -1538,7 +1600,10 @@ impl<'a> GlobalContext<'a> {
 }
 
 impl ContractContext {
-    pub fn new(contract_identifier: QualifiedContractIdentifier) -> Self {
+    pub fn new(
+        contract_identifier: QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+    ) -> Self {
         Self {
             contract_identifier,
             variables: HashMap::new(),
@@ -1551,6 +1616,7 @@ impl ContractContext {
             meta_data_var: HashMap::new(),
             meta_nft: HashMap::new(),
             meta_ft: HashMap::new(),
+            clarity_version,
         }
     }
 
@@ -1573,8 +1639,12 @@ impl ContractContext {
         self.implemented_traits.contains(trait_identifier)
     }
 
+    pub fn get_clarity_version(&self) -> &ClarityVersion {
+        &self.clarity_version
+    }
+
     pub fn is_name_used(&self, name: &str) -> bool {
-        is_reserved(name)
+        is_reserved(name, &self.clarity_version)
             || self.variables.contains_key(name)
             || self.functions.contains_key(name)
             || self.persisted_names.contains(name)
@@ -130,4 +130,24 @@ define_named_enum!(ClarityCostFunction {
     NftOwner("cost_nft_owner"),
     NftBurn("cost_nft_burn"),
     PoisonMicroblock("poison_microblock"),
+    ToConsensusBuff("cost_to_consensus_buff"),
+    FromConsensusBuff("cost_from_consensus_buff"),
+    Slice("cost_slice"),
+    ReplaceAt("cost_replace_at"),
+    BuffToIntLe("cost_buff_to_int_le"),
+    BuffToUIntLe("cost_buff_to_uint_le"),
+    BuffToIntBe("cost_buff_to_int_be"),
+    BuffToUIntBe("cost_buff_to_uint_be"),
+    IntToAscii("cost_int_to_ascii"),
+    StringToInt("cost_string_to_int"),
+    StringToUInt("cost_string_to_uint"),
+    PrincipalDestruct("cost_principal_destruct"),
+    PrincipalConstruct("cost_principal_construct"),
+    IsStandard("cost_is_standard"),
+    GetBurnBlockInfo("cost_burn_block_info"),
+    BitwiseAnd("cost_bitwise_and"),
+    BitwiseOr("cost_bitwise_or"),
+    BitwiseNot("cost_bitwise_not"),
+    BitwiseLShift("cost_bitwise_left_shift"),
+    BitwiseRShift("cost_bitwise_right_shift"),
 });
@@ -48,6 +48,7 @@ pub const CLARITY_MEMORY_LIMIT: u64 = 100 * 1000 * 1000;
 // TODO: factor out into a boot lib?
 pub const COSTS_1_NAME: &'static str = "costs";
 pub const COSTS_2_NAME: &'static str = "costs-2";
+pub const COSTS_3_NAME: &'static str = "costs-3";
 
 lazy_static! {
     static ref COST_TUPLE_TYPE_SIGNATURE: TypeSignature = TypeSignature::TupleType(
@@ -614,6 +615,41 @@ fn load_cost_functions(
     Ok(state_summary)
 }
 
+/// Load the cost functions that a block built on the currently open chain tip would run under.
+/// This applies any proposals confirmed in the cost-voting contract since the cost state was last
+/// computed in this fork, without persisting the result, and maps every `ClarityCostFunction`
+/// that is not overridden to the epoch's default boot cost contract.
+pub fn load_effective_cost_functions(
+    mainnet: bool,
+    clarity_db: &mut ClarityDatabase,
+) -> Result<CostStateSummary> {
+    clarity_db.begin();
+    let loaded = load_cost_functions(mainnet, clarity_db, true);
+    clarity_db.roll_back();
+    let CostStateSummary {
+        contract_call_circuits,
+        mut cost_function_references,
+    } = loaded?;
+
+    let epoch_id = clarity_db.get_clarity_epoch_version();
+    let boot_costs_id = boot_code_id(
+        &LimitedCostTracker::default_cost_contract_for_epoch(epoch_id),
+        mainnet,
+    );
+    for f in ClarityCostFunction::ALL.iter() {
+        cost_function_references
+            .entry(f.clone())
+            .or_insert_with(|| {
+                ClarityCostFunctionReference::new(boot_costs_id.clone(), f.get_name())
+            });
+    }
+
+    Ok(CostStateSummary {
+        contract_call_circuits,
+        cost_function_references,
+    })
+}
+
 impl LimitedCostTracker {
     pub fn new(
         mainnet: bool,
@@ -679,6 +715,7 @@ impl LimitedCostTracker {
             }
             StacksEpochId::Epoch20 => COSTS_1_NAME.to_string(),
             StacksEpochId::Epoch2_05 => COSTS_2_NAME.to_string(),
+            StacksEpochId::Epoch21 => COSTS_3_NAME.to_string(),
         }
     }
 }
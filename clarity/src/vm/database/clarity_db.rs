@@ -38,10 +38,12 @@ use crate::vm::types::{
     QualifiedContractIdentifier, StandardPrincipalData, TupleData, TupleTypeSignature,
     TypeSignature, Value, NONE,
 };
+use crate::vm::ClarityVersion;
 use stacks_common::util::hash::{to_hex, Hash160, Sha256Sum, Sha512Trunc256Sum};
 
 use crate::types::chainstate::{
-    BlockHeaderHash, BurnchainHeaderHash, SortitionId, StacksAddress, StacksBlockId, VRFSeed,
+    BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, SortitionId, StacksAddress, StacksBlockId,
+    VRFSeed,
 };
 use crate::vm::types::byte_len_of_serialization;
 
@@ -96,6 +98,7 @@ pub trait HeadersDB {
     fn get_burn_block_time_for_block(&self, id_bhh: &StacksBlockId) -> Option<u64>;
     fn get_burn_block_height_for_block(&self, id_bhh: &StacksBlockId) -> Option<u32>;
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress>;
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash>;
 }
 
 pub trait BurnStateDB {
@@ -107,6 +110,19 @@ pub trait BurnStateDB {
     ) -> Option<BurnchainHeaderHash>;
     fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch>;
     fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch>;
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId>;
+
+    /// Get the PoX reward addresses paid by the burnchain block at `height` in the fork
+    ///  identified by `sortition_id`, as Clarity PoX address tuples, along with the amount
+    ///  paid to each address.
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)>;
 }
 
 impl HeadersDB for &dyn HeadersDB {
@@ -131,6 +147,9 @@ impl HeadersDB for &dyn HeadersDB {
     fn get_miner_address(&self, bhh: &StacksBlockId) -> Option<StacksAddress> {
         (*self).get_miner_address(bhh)
     }
+    fn get_consensus_hash_for_block(&self, bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        (*self).get_consensus_hash_for_block(bhh)
+    }
 }
 
 impl BurnStateDB for &dyn BurnStateDB {
@@ -153,6 +172,21 @@ impl BurnStateDB for &dyn BurnStateDB {
     fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
         (*self).get_stacks_epoch_by_epoch_id(epoch_id)
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        (*self).get_sortition_id_from_consensus_hash(consensus_hash)
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        (*self).get_pox_payout_addrs(height, sortition_id)
+    }
 }
 
 pub struct NullHeadersDB {}
@@ -208,6 +242,14 @@ impl HeadersDB for NullHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        if *id_bhh == StacksBlockId::new(&FIRST_BURNCHAIN_CONSENSUS_HASH, &FIRST_STACKS_BLOCK_HASH)
+        {
+            Some(FIRST_BURNCHAIN_CONSENSUS_HASH)
+        } else {
+            None
+        }
+    }
 }
 
 impl BurnStateDB for NullBurnStateDB {
@@ -236,6 +278,21 @@ impl BurnStateDB for NullBurnStateDB {
     fn get_stacks_epoch_by_epoch_id(&self, _epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
         self.get_stacks_epoch(0)
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        _consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        None
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        _height: u32,
+        _sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        None
+    }
 }
 
 impl<'a> ClarityDatabase<'a> {
@@ -482,6 +539,30 @@ impl<'a> ClarityDatabase<'a> {
         Ok(())
     }
 
+    /// Record the Clarity version that a contract was deployed with.
+    pub fn set_contract_clarity_version(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+        clarity_version: ClarityVersion,
+    ) {
+        let key =
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-clarity-version");
+        self.insert_metadata(contract_identifier, &key, &clarity_version);
+    }
+
+    /// Get the Clarity version of a contract. Contracts deployed before versioning existed
+    ///  have no recorded version, and are Clarity 1 contracts.
+    pub fn get_contract_clarity_version(
+        &mut self,
+        contract_identifier: &QualifiedContractIdentifier,
+    ) -> Result<ClarityVersion> {
+        let key =
+            ClarityDatabase::make_metadata_key(StoreType::Contract, "contract-clarity-version");
+        Ok(self
+            .fetch_metadata(contract_identifier, &key)?
+            .unwrap_or_default())
+    }
+
     pub fn insert_contract(
         &mut self,
         contract_identifier: &QualifiedContractIdentifier,
@@ -1545,6 +1626,47 @@ impl<'a> ClarityDatabase<'a> {
             .get_burn_header_hash(height, sortition_id)
     }
 
+    /// Get the sortition in which the parent of the current Stacks block was mined. Burnchain
+    ///  state queries are answered from this sortition's fork.
+    /// Returns None if the current block is the boot block, or if the sortition is unknown.
+    pub fn get_sortition_id_for_stacks_tip(&mut self) -> Option<SortitionId> {
+        let current_stacks_height = self.get_current_block_height();
+        if current_stacks_height < 1 {
+            return None;
+        }
+
+        let parent_id_bhh = self.get_index_block_header_hash(current_stacks_height - 1);
+        let consensus_hash = self
+            .headers_db
+            .get_consensus_hash_for_block(&parent_id_bhh)
+            .expect("FATAL: no consensus hash found for StacksBlockId");
+
+        self.burn_state_db
+            .get_sortition_id_from_consensus_hash(&consensus_hash)
+    }
+
+    /// Get the burnchain header hash of the burnchain block at `burnchain_block_height`,
+    ///  in the burnchain fork that the current Stacks block builds on.
+    pub fn get_burnchain_block_header_hash_for_burnchain_height(
+        &mut self,
+        burnchain_block_height: u32,
+    ) -> Option<BurnchainHeaderHash> {
+        let sortition_id = self.get_sortition_id_for_stacks_tip()?;
+        self.burn_state_db
+            .get_burn_header_hash(burnchain_block_height, &sortition_id)
+    }
+
+    /// Get the PoX reward addresses and per-address payout of the burnchain block at
+    ///  `burnchain_block_height`, in the burnchain fork that the current Stacks block builds on.
+    pub fn get_pox_payout_addrs_for_burnchain_height(
+        &mut self,
+        burnchain_block_height: u32,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let sortition_id = self.get_sortition_id_for_stacks_tip()?;
+        self.burn_state_db
+            .get_pox_payout_addrs(burnchain_block_height, &sortition_id)
+    }
+
     /// This function obtains the stacks epoch version, which is based on the burn block height.
     /// Valid epochs include stacks 1.0, 2.0, 2.05, and so on.
     pub fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
@@ -23,6 +23,7 @@ use crate::vm::errors::{
 use crate::vm::types::{
     OptionalData, PrincipalData, TupleTypeSignature, TypeSignature, Value, NONE,
 };
+use crate::vm::ClarityVersion;
 use serde::Deserialize;
 use stacks_common::util::hash::{hex_bytes, to_hex};
 use std::convert::TryInto;
@@ -123,6 +124,7 @@ clarity_serializable!(u128);
 clarity_serializable!(u64);
 clarity_serializable!(Contract);
 clarity_serializable!(ContractAnalysis);
+clarity_serializable!(ClarityVersion);
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct STXBalance {
@@ -315,6 +317,54 @@ impl<'db, 'conn> STXBalanceSnapshot<'db, 'conn> {
         self.balance.amount_locked = amount_to_lock;
     }
 
+    /// Move the unlock height of an existing lock later.  The amount locked is unchanged.
+    pub fn extend_lock(&mut self, unlock_burn_height: u64) {
+        let unlocked = self.unlock_available_tokens_if_any();
+        if unlocked > 0 {
+            debug!("Consolidated after extend-token-lock");
+        }
+
+        if !self.has_locked_tokens() {
+            // caller needs to have checked this
+            panic!("FATAL: account does not have locked tokens");
+        }
+
+        if unlock_burn_height <= self.balance.unlock_height {
+            // caller needs to have checked this
+            panic!("FATAL: cannot extend a lock to an earlier unlock burn height");
+        }
+
+        self.balance.unlock_height = unlock_burn_height;
+    }
+
+    /// Raise the amount locked by an existing lock to `new_total_locked`.  The unlock height
+    /// is unchanged.
+    pub fn increase_lock(&mut self, new_total_locked: u128) {
+        let unlocked = self.unlock_available_tokens_if_any();
+        if unlocked > 0 {
+            debug!("Consolidated after increase-token-lock");
+        }
+
+        if !self.has_locked_tokens() {
+            // caller needs to have checked this
+            panic!("FATAL: account does not have locked tokens");
+        }
+
+        if new_total_locked <= self.balance.amount_locked {
+            // caller needs to have checked this
+            panic!("FATAL: cannot increase a lock to a smaller amount");
+        }
+
+        let increase_by = new_total_locked - self.balance.amount_locked;
+        self.balance.amount_unlocked = self
+            .balance
+            .amount_unlocked
+            .checked_sub(increase_by)
+            .expect("STX underflow");
+
+        self.balance.amount_locked = new_total_locked;
+    }
+
     fn unlock_available_tokens_if_any(&mut self) -> u128 {
         if !self
             .balance
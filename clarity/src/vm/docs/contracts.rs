@@ -10,7 +10,7 @@ use crate::vm::contexts::GlobalContext;
 use crate::vm::costs::LimitedCostTracker;
 use crate::vm::database::MemoryBackingStore;
 use crate::vm::types::QualifiedContractIdentifier;
-use crate::vm::{self, ContractContext};
+use crate::vm::{self, ClarityVersion, ContractContext};
 
 const DOCS_GENERATION_EPOCH: StacksEpochId = StacksEpochId::Epoch2_05;
 
@@ -66,7 +66,8 @@ fn get_constant_value(var_name: &str, contract_content: &str) -> Value {
 
 fn doc_execute(program: &str) -> Result<Option<Value>, vm::Error> {
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let clarity_version = ClarityVersion::default_for_epoch(DOCS_GENERATION_EPOCH);
+    let mut contract_context = ContractContext::new(contract_id.clone(), clarity_version);
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context = GlobalContext::new(
@@ -76,7 +77,8 @@ fn doc_execute(program: &str) -> Result<Option<Value>, vm::Error> {
         DOCS_GENERATION_EPOCH,
     );
     global_context.execute(|g| {
-        let parsed = vm::ast::build_ast(&contract_id, program, &mut ())?.expressions;
+        let parsed =
+            vm::ast::build_ast(&contract_id, program, &mut (), clarity_version)?.expressions;
         vm::eval_all(&parsed, &mut contract_context, g)
     })
 }
@@ -254,6 +254,74 @@ const XOR_API: SimpleFunctionAPI = SimpleFunctionAPI {
 ",
 };
 
+const BITWISE_AND_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-and i1 i2...)",
+    description: "Returns the result of bitwise and'ing a variable number of integer inputs.",
+    example: "(bit-and 24 16) ;; Returns 16
+(bit-and 28 24 -1) ;; Returns 24
+(bit-and u24 u16) ;; Returns u16
+(bit-and -128 -64) ;; Returns -128
+",
+};
+
+const BITWISE_OR_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-or i1 i2...)",
+    description:
+        "Returns the result of bitwise inclusive or'ing a variable number of integer inputs.",
+    example: "(bit-or 4 8) ;; Returns 12
+(bit-or 1 2 4) ;; Returns 7
+(bit-or 64 -32 -16) ;; Returns -16
+(bit-or u2 u4 u32) ;; Returns u38
+",
+};
+
+const BITWISE_NOT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-not i1)",
+    description: "Returns the one's complement (the bitwise not) of `i1`: every bit that is `1` in `i1` is `0` in the result, and
+every bit that is `0` in `i1` is `1` in the result.",
+    example: "(bit-not 3) ;; Returns -4
+(bit-not u128) ;; Returns u340282366920938463463374607431768211327
+(bit-not 128) ;; Returns -129
+(bit-not -128) ;; Returns 127
+",
+};
+
+const BITWISE_LEFT_SHIFT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-shift-left i1 shamt)",
+    description: "Shifts all the bits in `i1` to the left by the number of places specified in `shamt` modulo 128 (the bit width of Clarity integers).
+Bits shifted past the most significant bit are discarded, so unlike multiplication by a power of two, `bit-shift-left` never
+fails with an overflow error.",
+    example: "(bit-shift-left 2 u1) ;; Returns 4
+(bit-shift-left 16 u2) ;; Returns 64
+(bit-shift-left -64 u1) ;; Returns -128
+(bit-shift-left u4 u2) ;; Returns u16
+(bit-shift-left 123 u9999999999) ;; Returns -170141183460469231731687303715884105728
+(bit-shift-left u123 u9999999999) ;; Returns u170141183460469231731687303715884105728
+(bit-shift-left 1 u128) ;; Returns 1
+",
+};
+
+const BITWISE_RIGHT_SHIFT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(bit-shift-right i1 shamt)",
+    description: "Shifts all the bits in `i1` to the right by the number of places specified in `shamt` modulo 128 (the bit width of Clarity integers).
+When `i1` is a `uint` (unsigned), new bits are filled with zeros. When `i1` is an `int` (signed), the sign is preserved, meaning that new bits
+are filled with the value of the previous sign-bit.",
+    example: "(bit-shift-right 2 u1) ;; Returns 1
+(bit-shift-right 128 u2) ;; Returns 32
+(bit-shift-right -64 u1) ;; Returns -32
+(bit-shift-right u128 u2) ;; Returns u32
+(bit-shift-right 123 u9999999999) ;; Returns 0
+(bit-shift-right u123 u9999999999) ;; Returns u0
+(bit-shift-right -128 u7) ;; Returns -1
+(bit-shift-right -256 u1) ;; Returns -128
+",
+};
+
 const AND_API: SimpleFunctionAPI = SimpleFunctionAPI {
     name: None,
     signature: "(and b1 b2 ...)",
@@ -338,6 +406,7 @@ pub fn get_input_type_string(function_type: &FunctionType) -> String {
         FunctionType::ArithmeticBinary | FunctionType::ArithmeticComparison => {
             "int, int | uint, uint".to_string()
         }
+        FunctionType::BitShift => "int, uint | uint, uint".to_string(),
     }
 }
 
@@ -348,7 +417,8 @@ pub fn get_output_type_string(function_type: &FunctionType) -> String {
         FunctionType::UnionArgs(_, ref out_type) => format!("{}", out_type),
         FunctionType::ArithmeticVariadic
         | FunctionType::ArithmeticUnary
-        | FunctionType::ArithmeticBinary => "int | uint".to_string(),
+        | FunctionType::ArithmeticBinary
+        | FunctionType::BitShift => "int | uint".to_string(),
         FunctionType::ArithmeticComparison => "bool".to_string(),
     }
 }
@@ -1170,6 +1240,29 @@ The `id-header-hash` is the block identifier value that must be used as input to
 "
 };
 
+const GET_BURN_BLOCK_INFO_API: SpecialAPI = SpecialAPI {
+    input_type: "BurnBlockInfoPropertyName, BlockHeightInt",
+    output_type: "(optional buff) | (optional (tuple (addrs (list 2 (tuple (hashbytes (buff 20)) (version (buff 1))))) (payout uint)))",
+    signature: "(get-burn-block-info? prop-name block-height-expr)",
+    description: "The `get-burn-block-info?` function fetches data for a block of the given *burnchain* block height. The
+value and type returned are determined by the specified `BurnBlockInfoPropertyName`. Valid values for `block-height-expr` are
+burnchain block heights up to the burnchain block that the current Stacks block's parent was mined in. If the provided
+`BlockHeightInt` does not correspond to such a burnchain block, the function returns `none`. The currently available property
+names are `header-hash` and `pox-addrs`.
+
+The `header-hash` property returns a 32-byte buffer representing the header hash of the burnchain block at
+burnchain height `block-height-expr`.
+
+The `pox-addrs` property returns a tuple with two items: a list of up to two PoX addresses that received a PoX payout at that
+burnchain block height, and the amount of the burnchain currency paid to each address. Each PoX address is a tuple of its
+`version` byte and its 20 `hashbytes`. Note that the list may be empty if the burnchain block fell in a prepare phase, or if
+no addresses were stacked for the reward cycle.
+",
+    example: "(get-burn-block-info? header-hash u677050) ;; Returns (some 0xe67141016c88a7f1203eca0b4312f2ed141531f59303a1c267d7d83ab6b977d8)
+(get-burn-block-info? pox-addrs u677050) ;; Returns (some (tuple (addrs ((tuple (hashbytes 0x395f3643cea07ec4eec73b4d9a973dcce56b9bf1) (version 0x00)) (tuple (hashbytes 0x7c6775e20e3e938d2d7e9d79ac310108ba501ddb) (version 0x01)))) (payout u123)))
+"
+};
+
 const DEFINE_TOKEN_API: DefineAPI = DefineAPI {
     input_type: "TokenName, <uint>",
     output_type: "Not Applicable",
@@ -1616,6 +1709,253 @@ one of the following error codes:
 "
 };
 
+const TO_CONSENSUS_BUFF: SpecialAPI = SpecialAPI {
+    input_type: "any",
+    output_type: "buff",
+    signature: "(to-consensus-buff value)",
+    description: "`to-consensus-buff` is a special function that will serialize any
+Clarity value into a buffer, using the SIP-005 serialization of the
+Clarity value. This is the same serialization used to store values in the
+Clarity database and to encode them in transactions.
+
+The type checker computes the length of the returned buffer from the largest
+possible serialization of the input type. If that length exceeds the maximum
+value size, the contract fails to type check.",
+    example: r#"
+(to-consensus-buff 1) ;; Returns 0x0000000000000000000000000000000001
+(to-consensus-buff u1) ;; Returns 0x0100000000000000000000000000000001
+(to-consensus-buff true) ;; Returns 0x03
+(to-consensus-buff none) ;; Returns 0x09
+(to-consensus-buff (some u1)) ;; Returns 0x0a0100000000000000000000000000000001
+(to-consensus-buff "abc") ;; Returns 0x0d00000003616263
+(to-consensus-buff { a: 1 }) ;; Returns 0x0c0000000101610000000000000000000000000000000001
+"#,
+};
+
+const SLICE_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, uint, uint",
+    output_type: "(optional sequence_A)",
+    signature: "(slice sequence left-position right-position)",
+    description: "The `slice` function returns the sub-sequence of `sequence` that starts at
+`left-position` and ends just before `right-position`. Applicable sequence types are `(list A)`,
+`buff`, `string-ascii` and `string-utf8`. If `left-position` is greater than `right-position`, or
+`right-position` is greater than the length of `sequence`, the function returns `none`.
+",
+    example: r#"
+(slice "blockstack" u5 u10) ;; Returns (some "stack")
+(slice (list 1 2 3 4 5) u5 u9) ;; Returns none
+(slice (list 1 2 3 4 5) u3 u4) ;; Returns (some (4))
+(slice "abcd" u1 u3) ;; Returns (some "bc")
+(slice "abcd" u2 u2) ;; Returns (some "")
+(slice "abcd" u3 u1) ;; Returns none
+"#,
+};
+
+const REPLACE_AT_API: SpecialAPI = SpecialAPI {
+    input_type: "sequence_A, uint, A",
+    output_type: "(optional sequence_A)",
+    signature: "(replace-at sequence index element)",
+    description: "The `replace-at` function returns a copy of `sequence` in which the element at
+`index` is replaced by `element`. Applicable sequence types are `(list A)`, `buff`, `string-ascii`
+and `string-utf8`, for which the corresponding element types are, respectively, `A`, `(buff 1)`,
+`(string-ascii 1)` and `(string-utf8 1)`. If `index` is out of bounds, or if a `buff` or string
+`element` does not contain exactly one element, the function returns `none`.
+",
+    example: r#"
+(replace-at u"ab" u1 u"c") ;; Returns (some u"ac")
+(replace-at 0x00112233 u2 0x44) ;; Returns (some 0x00114433)
+(replace-at "abcd" u3 "e") ;; Returns (some "abce")
+(replace-at (list 1) u0 10) ;; Returns (some (10))
+(replace-at (list (list 1) (list 2)) u0 (list 33)) ;; Returns (some ((33) (2)))
+(replace-at (list 1 2) u3 4) ;; Returns none
+"#,
+};
+
+const BUFF_TO_INT_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-le buffer)",
+    description: "Converts a byte buffer to a signed integer, reading the buffer as the
+little-endian two's complement encoding of the integer. Buffers shorter than 16 bytes are
+padded with zero bytes at their most significant end, so they always produce a non-negative
+integer.",
+    example: r#"
+(buff-to-int-le 0x01) ;; Returns 1
+(buff-to-int-le 0x01000000000000000000000000000000) ;; Returns 1
+(buff-to-int-le 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-le 0xff) ;; Returns 255
+"#,
+};
+
+const BUFF_TO_UINT_LE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-le buffer)",
+    description: "Converts a byte buffer to an unsigned integer, reading the buffer as the
+little-endian encoding of the integer. Buffers shorter than 16 bytes are padded with zero bytes
+at their most significant end.",
+    example: r#"
+(buff-to-uint-le 0x01) ;; Returns u1
+(buff-to-uint-le 0x0001) ;; Returns u256
+(buff-to-uint-le 0xffffffffffffffffffffffffffffffff) ;; Returns u340282366920938463463374607431768211455
+"#,
+};
+
+const BUFF_TO_INT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-int-be buffer)",
+    description: "Converts a byte buffer to a signed integer, reading the buffer as the
+big-endian two's complement encoding of the integer. Buffers shorter than 16 bytes are
+padded with zero bytes at their most significant end, so they always produce a non-negative
+integer.",
+    example: r#"
+(buff-to-int-be 0x01) ;; Returns 1
+(buff-to-int-be 0x00000000000000000000000000000001) ;; Returns 1
+(buff-to-int-be 0xffffffffffffffffffffffffffffffff) ;; Returns -1
+(buff-to-int-be 0xff) ;; Returns 255
+"#,
+};
+
+const BUFF_TO_UINT_BE_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(buff-to-uint-be buffer)",
+    description: "Converts a byte buffer to an unsigned integer, reading the buffer as the
+big-endian encoding of the integer. Buffers shorter than 16 bytes are padded with zero bytes
+at their most significant end.",
+    example: r#"
+(buff-to-uint-be 0x01) ;; Returns u1
+(buff-to-uint-be 0x0100) ;; Returns u256
+(buff-to-uint-be 0xffffffffffffffffffffffffffffffff) ;; Returns u340282366920938463463374607431768211455
+"#,
+};
+
+const INT_TO_ASCII_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(int-to-ascii value)",
+    description: "Converts an integer, either `int` or `uint`, to its decimal representation
+as a `string-ascii`.",
+    example: r#"
+(int-to-ascii 1) ;; Returns "1"
+(int-to-ascii u1) ;; Returns "1"
+(int-to-ascii -1) ;; Returns "-1"
+"#,
+};
+
+const STRING_TO_INT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-int string)",
+    description: "Converts a decimal string, either `string-ascii` or `string-utf8`, to an
+optional-wrapped signed integer. If the input string does not represent a valid 128-bit
+signed integer, then the function returns `none`. Otherwise it returns the integer wrapped
+in `some`.",
+    example: r#"
+(string-to-int "1") ;; Returns (some 1)
+(string-to-int u"-1") ;; Returns (some -1)
+(string-to-int "a") ;; Returns none
+"#,
+};
+
+const STRING_TO_UINT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(string-to-uint string)",
+    description: "Converts a decimal string, either `string-ascii` or `string-utf8`, to an
+optional-wrapped unsigned integer. If the input string does not represent a valid 128-bit
+unsigned integer, then the function returns `none`. Otherwise it returns the integer wrapped
+in `some`.",
+    example: r#"
+(string-to-uint "1") ;; Returns (some u1)
+(string-to-uint u"1") ;; Returns (some u1)
+(string-to-uint "-1") ;; Returns none
+"#,
+};
+
+const PRINCIPAL_DESTRUCT_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(principal-destruct principal-address)",
+    description: "A principal value represents either a set of keys, or a smart contract.
+The former, called a _standard principal_, is encoded as a `(buff 1)` *version byte*,
+indicating the type of account and the type of network that this principal can spend tokens on,
+and a `(buff 20)` *public key hash*, characterizing the principal's unique identity.
+The latter, a _contract principal_, is encoded as a standard principal concatenated with
+a `(string-ascii 40)` *contract name* that identifies the code body.
+
+`principal-destruct` decomposes a principal into its component parts and returns them
+in a tuple of the form `{ version: (buff 1), hash-bytes: (buff 20), name: (optional (string-ascii 40)) }`.
+The `name` is `none` for a standard principal.
+
+Note that this function does not check whether the principal belongs to the network this
+blockchain is running on; use `is-standard` for that.",
+    example: r#"
+(principal-destruct 'ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP) ;; Returns (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x1a))
+(principal-destruct 'ST1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582VF9HSCP.foo) ;; Returns (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name (some "foo")) (version 0x1a))
+(principal-destruct 'SP1AW6EKPGT61SQ9FNVDS17RKNWT8ZP582RS0JSRJ) ;; Returns (tuple (hash-bytes 0x55c33a76868c1cdd2faedb909f13af348fd8a816) (name none) (version 0x16))
+"#,
+};
+
+const PRINCIPAL_CONSTRUCT_API: SpecialAPI = SpecialAPI {
+    input_type: "(buff 1), (buff 20), [(string-ascii 40)]",
+    output_type: "(response principal uint)",
+    signature: "(principal-construct version-byte hash-bytes [contract-name])",
+    description:
+        "`principal-construct` takes a `(buff 1)` *version byte*, a `(buff 20)` *public key hash*,
+and optionally a `(string-ascii 40)` *contract name*, and builds a standard principal, or a contract
+principal if a contract name is given. These are the parts returned by `principal-destruct`.
+
+If the parts do not make up a valid principal, the function returns an error code:
+
+* `(err u1)` if the version byte is missing or is not a valid address version (greater than 31).
+* `(err u2)` if the public key hash is not exactly 20 bytes long.
+* `(err u3)` if the contract name is not a valid contract name.
+
+Note that this function does not check whether the principal belongs to the network this
+blockchain is running on; use `is-standard` on the result for that.",
+    example: r#"
+(principal-construct 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320) ;; Returns (ok ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK)
+(principal-construct 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 "foo") ;; Returns (ok ST3X6QWWETNBZWGBK6DRGTR1KX50S74D3425Q1TPK.foo)
+(principal-construct 0x20 0xfa6bf38ed557fe417333710d6033e9419391a320) ;; Returns (err u1)
+(principal-construct 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a3) ;; Returns (err u2)
+(principal-construct 0x1a 0xfa6bf38ed557fe417333710d6033e9419391a320 "") ;; Returns (err u3)
+"#,
+};
+
+const IS_STANDARD_API: SimpleFunctionAPI = SimpleFunctionAPI {
+    name: None,
+    signature: "(is-standard standard-or-contract-principal)",
+    description: "Tests whether `standard-or-contract-principal` _matches_ the current network
+type, and therefore represents a principal that can spend tokens on the current
+network type. That is, the network is either of type `mainnet`, or `testnet`.
+Only `SPxxxx` and `SMxxxx` _c32check form_ addresses can spend tokens on
+a mainnet, whereas only `STxxxx` and `SNxxxx` _c32check forms_ addresses can spend
+tokens on a testnet. All other addresses can be used, but cannot spend tokens.
+
+For contract principals, `is-standard` applies the same test to the address of the
+contract's deployer.",
+    example: r#"
+(is-standard 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6) ;; Returns true
+(is-standard 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6.foo) ;; Returns true
+(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY) ;; Returns false
+(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.foo) ;; Returns false
+(is-standard 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR) ;; Returns false
+"#,
+};
+
+const FROM_CONSENSUS_BUFF: SpecialAPI = SpecialAPI {
+    input_type: "type-signature(t), buff",
+    output_type: "(optional t)",
+    signature: "(from-consensus-buff type-signature buffer)",
+    description: "`from-consensus-buff` is a special function that will deserialize a
+buffer into a Clarity value, using the SIP-005 serialization of the
+Clarity value. The type that `from-consensus-buff` tries to deserialize
+into is provided by the first parameter to the function. If it fails
+to deserialize the type, or if the buffer contains any bytes after the
+serialized value, the method returns `none`.",
+    example: r#"
+(from-consensus-buff int 0x0000000000000000000000000000000001) ;; Returns (some 1)
+(from-consensus-buff uint 0x0000000000000000000000000000000001) ;; Returns none
+(from-consensus-buff bool 0x0300) ;; Returns none
+(from-consensus-buff (string-ascii 8) 0x0d00000003616263) ;; Returns (some "abc")
+(from-consensus-buff { a: int } 0x0c0000000101610000000000000000000000000000000001) ;; Returns (some (tuple (a 1)))
+"#,
+};
+
 fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
     use crate::vm::functions::NativeFunctions::*;
     let name = function.get_name();
@@ -1635,6 +1975,11 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         Sqrti => make_for_simple_native(&SQRTI_API, &Sqrti, name),
         Log2 => make_for_simple_native(&LOG2_API, &Log2, name),
         BitwiseXOR => make_for_simple_native(&XOR_API, &BitwiseXOR, name),
+        BitwiseAnd => make_for_simple_native(&BITWISE_AND_API, &BitwiseAnd, name),
+        BitwiseOr => make_for_simple_native(&BITWISE_OR_API, &BitwiseOr, name),
+        BitwiseNot => make_for_simple_native(&BITWISE_NOT_API, &BitwiseNot, name),
+        BitwiseLShift => make_for_simple_native(&BITWISE_LEFT_SHIFT_API, &BitwiseLShift, name),
+        BitwiseRShift => make_for_simple_native(&BITWISE_RIGHT_SHIFT_API, &BitwiseRShift, name),
         And => make_for_simple_native(&AND_API, &And, name),
         Or => make_for_simple_native(&OR_API, &Or, name),
         Not => make_for_simple_native(&NOT_API, &Not, name),
@@ -1674,6 +2019,7 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         PrincipalOf => make_for_special(&PRINCIPAL_OF_API, name),
         AsContract => make_for_special(&AS_CONTRACT_API, name),
         GetBlockInfo => make_for_special(&GET_BLOCK_INFO_API, name),
+        GetBurnBlockInfo => make_for_special(&GET_BURN_BLOCK_INFO_API, name),
         ConsOkay => make_for_special(&CONS_OK_API, name),
         ConsError => make_for_special(&CONS_ERR_API, name),
         ConsSome => make_for_special(&CONS_SOME_API, name),
@@ -1702,6 +2048,22 @@ fn make_api_reference(function: &NativeFunctions) -> FunctionAPI {
         GetStxBalance => make_for_simple_native(&STX_GET_BALANCE, &GetStxBalance, name),
         StxTransfer => make_for_simple_native(&STX_TRANSFER, &StxTransfer, name),
         StxBurn => make_for_simple_native(&STX_BURN, &StxBurn, name),
+        ToConsensusBuff => make_for_special(&TO_CONSENSUS_BUFF, name),
+        FromConsensusBuff => make_for_special(&FROM_CONSENSUS_BUFF, name),
+        Slice => make_for_special(&SLICE_API, name),
+        ReplaceAt => make_for_special(&REPLACE_AT_API, name),
+        BuffToIntLe => make_for_simple_native(&BUFF_TO_INT_LE_API, &BuffToIntLe, name),
+        BuffToUIntLe => make_for_simple_native(&BUFF_TO_UINT_LE_API, &BuffToUIntLe, name),
+        BuffToIntBe => make_for_simple_native(&BUFF_TO_INT_BE_API, &BuffToIntBe, name),
+        BuffToUIntBe => make_for_simple_native(&BUFF_TO_UINT_BE_API, &BuffToUIntBe, name),
+        IntToAscii => make_for_simple_native(&INT_TO_ASCII_API, &IntToAscii, name),
+        StringToInt => make_for_simple_native(&STRING_TO_INT_API, &StringToInt, name),
+        StringToUInt => make_for_simple_native(&STRING_TO_UINT_API, &StringToUInt, name),
+        PrincipalDestruct => {
+            make_for_simple_native(&PRINCIPAL_DESTRUCT_API, &PrincipalDestruct, name)
+        }
+        PrincipalConstruct => make_for_special(&PRINCIPAL_CONSTRUCT_API, name),
+        IsStandard => make_for_simple_native(&IS_STANDARD_API, &IsStandard, name),
     }
 }
 
@@ -1798,16 +2160,16 @@ mod test {
         contexts::OwnedEnvironment,
         database::{BurnStateDB, HeadersDB, STXBalance},
         eval_all, execute,
-        types::PrincipalData,
-        ContractContext, Error, GlobalContext, LimitedCostTracker, QualifiedContractIdentifier,
-        Value,
+        types::{PrincipalData, TupleData},
+        ClarityVersion, ContractContext, Error, GlobalContext, LimitedCostTracker,
+        QualifiedContractIdentifier, Value,
     };
     use stacks_common::types::{StacksEpochId, PEER_VERSION_EPOCH_2_0};
 
     use super::make_all_api_reference;
     use super::make_json_api_reference;
-    use crate::types::chainstate::{SortitionId, StacksAddress, StacksBlockId};
-    use crate::vm::analysis::type_check;
+    use crate::types::chainstate::{ConsensusHash, SortitionId, StacksAddress, StacksBlockId};
+    use crate::vm::analysis::type_check_version;
     use crate::{types::chainstate::VRFSeed, vm::StacksEpoch};
     use crate::{
         types::chainstate::{BlockHeaderHash, BurnchainHeaderHash},
@@ -1860,6 +2222,9 @@ mod test {
         fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
             None
         }
+        fn get_consensus_hash_for_block(&self, _bhh: &StacksBlockId) -> Option<ConsensusHash> {
+            None
+        }
     }
 
     struct DocBurnStateDB {}
@@ -1893,6 +2258,19 @@ mod test {
         fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
             self.get_stacks_epoch(0)
         }
+        fn get_sortition_id_from_consensus_hash(
+            &self,
+            _consensus_hash: &ConsensusHash,
+        ) -> Option<SortitionId> {
+            None
+        }
+        fn get_pox_payout_addrs(
+            &self,
+            _height: u32,
+            _sortition_id: &SortitionId,
+        ) -> Option<(Vec<TupleData>, u128)> {
+            None
+        }
     }
 
     fn docs_execute(store: &mut MemoryBackingStore, program: &str) {
@@ -1918,16 +2296,28 @@ mod test {
             let mut analysis_db = store.as_analysis_db();
             let whole_contract = segments.join("\n");
             eprintln!("{}", whole_contract);
-            let mut parsed = ast::build_ast(&contract_id, &whole_contract, &mut ())
-                .unwrap()
-                .expressions;
-
-            type_check(&contract_id, &mut parsed, &mut analysis_db, false)
-                .expect("Failed to type check");
+            let mut parsed = ast::build_ast(
+                &contract_id,
+                &whole_contract,
+                &mut (),
+                ClarityVersion::latest(),
+            )
+            .unwrap()
+            .expressions;
+
+            type_check_version(
+                &contract_id,
+                &mut parsed,
+                &mut analysis_db,
+                false,
+                ClarityVersion::latest(),
+            )
+            .expect("Failed to type check");
         }
 
         let conn = store.as_docs_clarity_db();
-        let mut contract_context = ContractContext::new(contract_id.clone());
+        let mut contract_context =
+            ContractContext::new(contract_id.clone(), ClarityVersion::latest());
         let mut global_context = GlobalContext::new(
             false,
             conn,
@@ -1948,9 +2338,14 @@ mod test {
                     eprintln!("{}", segment);
 
                     let result = {
-                        let parsed = ast::build_ast(&contract_id, segment, &mut ())
-                            .unwrap()
-                            .expressions;
+                        let parsed = ast::build_ast(
+                            &contract_id,
+                            segment,
+                            &mut (),
+                            ClarityVersion::latest(),
+                        )
+                        .unwrap()
+                        .expressions;
                         eval_all(&parsed, &mut contract_context, g).unwrap()
                     };
 
@@ -1985,6 +2380,12 @@ mod test {
                 );
                 continue;
             }
+            if func_api.name == "get-burn-block-info?" {
+                eprintln!(
+                    "Skipping get-burn-block-info?, because it cannot be evaluated without a burnchain"
+                );
+                continue;
+            }
 
             let mut store = MemoryBackingStore::new();
             // first, load the samples for contract-call
@@ -1998,23 +2399,44 @@ mod test {
 
                 {
                     let mut analysis_db = store.as_analysis_db();
-                    let mut parsed = ast::build_ast(&contract_id, &token_contract_content, &mut ())
-                        .unwrap()
-                        .expressions;
-
-                    type_check(&contract_id, &mut parsed, &mut analysis_db, true)
-                        .expect("Failed to type check sample-contracts/tokens");
+                    let mut parsed = ast::build_ast(
+                        &contract_id,
+                        &token_contract_content,
+                        &mut (),
+                        ClarityVersion::latest(),
+                    )
+                    .unwrap()
+                    .expressions;
+
+                    type_check_version(
+                        &contract_id,
+                        &mut parsed,
+                        &mut analysis_db,
+                        true,
+                        ClarityVersion::latest(),
+                    )
+                    .expect("Failed to type check sample-contracts/tokens");
                 }
 
                 {
                     let mut analysis_db = store.as_analysis_db();
-                    let mut parsed =
-                        ast::build_ast(&trait_def_id, super::DEFINE_TRAIT_API.example, &mut ())
-                            .unwrap()
-                            .expressions;
-
-                    type_check(&trait_def_id, &mut parsed, &mut analysis_db, true)
-                        .expect("Failed to type check sample-contracts/tokens");
+                    let mut parsed = ast::build_ast(
+                        &trait_def_id,
+                        super::DEFINE_TRAIT_API.example,
+                        &mut (),
+                        ClarityVersion::latest(),
+                    )
+                    .unwrap()
+                    .expressions;
+
+                    type_check_version(
+                        &trait_def_id,
+                        &mut parsed,
+                        &mut analysis_db,
+                        true,
+                        ClarityVersion::latest(),
+                    )
+                    .expect("Failed to type check sample-contracts/tokens");
                 }
 
                 let conn = store.as_docs_clarity_db();
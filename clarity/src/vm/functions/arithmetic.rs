@@ -52,6 +52,25 @@ macro_rules! type_force_binary_arithmetic {
     }};
 }
 
+// This macro checks that the shift amount is a uint and then dispatches the evaluation
+//   to the correct arithmetic type handler based on the type of the shifted value.
+macro_rules! type_force_shift_arithmetic {
+    ($function: ident, $x: expr, $shamt: expr) => {{
+        match ($x, $shamt) {
+            (Value::Int(x), Value::UInt(shamt)) => I128Ops::$function(x, shamt),
+            (Value::UInt(x), Value::UInt(shamt)) => U128Ops::$function(x, shamt),
+            (Value::Int(_), shamt) | (Value::UInt(_), shamt) => {
+                Err(CheckErrors::TypeValueError(TypeSignature::UIntType, shamt).into())
+            }
+            (x, _) => Err(CheckErrors::UnionTypeValueError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                x,
+            )
+            .into()),
+        }
+    }};
+}
+
 macro_rules! type_force_unary_arithmetic {
     ($function: ident, $x: expr) => {{
         match $x {
@@ -122,6 +141,32 @@ macro_rules! make_arithmetic_ops {
             fn xor(x: $type, y: $type) -> InterpreterResult<Value> {
                 Self::make_value(x ^ y)
             }
+            fn bitwise_and(args: &[$type]) -> InterpreterResult<Value> {
+                let (first, rest) = args
+                    .split_first()
+                    .ok_or(CheckErrors::IncorrectArgumentCount(1, 0))?;
+                let result = rest.iter().fold(*first, |acc: $type, x: &$type| acc & *x);
+                Self::make_value(result)
+            }
+            fn bitwise_or(args: &[$type]) -> InterpreterResult<Value> {
+                let (first, rest) = args
+                    .split_first()
+                    .ok_or(CheckErrors::IncorrectArgumentCount(1, 0))?;
+                let result = rest.iter().fold(*first, |acc: $type, x: &$type| acc | *x);
+                Self::make_value(result)
+            }
+            fn bitwise_not(x: $type) -> InterpreterResult<Value> {
+                Self::make_value(!x)
+            }
+            // shift amounts are taken modulo the integer width, and bits shifted past
+            //  either end are discarded rather than raising an overflow.
+            fn bitwise_left_shift(x: $type, shamt: u128) -> InterpreterResult<Value> {
+                Self::make_value(x.wrapping_shl((shamt % 128) as u32))
+            }
+            // right shifts of `int` are arithmetic: the sign bit is carried in.
+            fn bitwise_right_shift(x: $type, shamt: u128) -> InterpreterResult<Value> {
+                Self::make_value(x.wrapping_shr((shamt % 128) as u32))
+            }
             fn leq(x: $type, y: $type) -> InterpreterResult<Value> {
                 Ok(Value::Bool(x <= y))
             }
@@ -246,6 +291,21 @@ make_arithmetic_ops!(I128Ops, i128);
 pub fn native_xor(a: Value, b: Value) -> InterpreterResult<Value> {
     type_force_binary_arithmetic!(xor, a, b)
 }
+pub fn native_bitwise_and(mut args: Vec<Value>) -> InterpreterResult<Value> {
+    type_force_variadic_arithmetic!(bitwise_and, args)
+}
+pub fn native_bitwise_or(mut args: Vec<Value>) -> InterpreterResult<Value> {
+    type_force_variadic_arithmetic!(bitwise_or, args)
+}
+pub fn native_bitwise_not(a: Value) -> InterpreterResult<Value> {
+    type_force_unary_arithmetic!(bitwise_not, a)
+}
+pub fn native_bitwise_left_shift(input: Value, shamt: Value) -> InterpreterResult<Value> {
+    type_force_shift_arithmetic!(bitwise_left_shift, input, shamt)
+}
+pub fn native_bitwise_right_shift(input: Value, shamt: Value) -> InterpreterResult<Value> {
+    type_force_shift_arithmetic!(bitwise_right_shift, input, shamt)
+}
 pub fn native_geq(a: Value, b: Value) -> InterpreterResult<Value> {
     type_force_binary_arithmetic!(geq, a, b)
 }
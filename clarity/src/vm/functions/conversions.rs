@@ -0,0 +1,174 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::runtime_cost;
+use crate::vm::errors::{
+    check_argument_count, CheckErrors, InterpreterError, InterpreterResult as Result,
+};
+use crate::vm::representations::SymbolicExpression;
+use crate::vm::types::{
+    ASCIIData, BuffData, CharType, SequenceData, TypeSignature, UTF8Data, Value, BUFF_16,
+};
+use crate::vm::{eval, Environment, LocalContext};
+
+/// Serialize a Clarity value into a buffer, using the same consensus
+///  serialization that is used for storing values and for transaction payloads.
+pub fn native_to_consensus_buff(value: Value) -> Result<Value> {
+    let mut serialized = vec![];
+    value
+        .serialize_write(&mut serialized)
+        .map_err(|_| InterpreterError::InterpreterError("IOError filling byte buffer.".into()))?;
+    Value::buff_from(serialized)
+}
+
+/// Deserialize a buffer into a Clarity value of the given type signature.
+///  Evaluates to `none` if the buffer is not the consensus serialization of
+///  exactly one value admitted by the type signature.
+pub fn special_from_consensus_buff(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(2, args)?;
+
+    let expected_type = TypeSignature::parse_type_repr(&args[0], env)?;
+
+    let input = eval(&args[1], env, context)?;
+    let input_bytes = match input {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) => data,
+        x => return Err(CheckErrors::TypeValueError(TypeSignature::max_buffer(), x).into()),
+    };
+
+    runtime_cost(
+        ClarityCostFunction::FromConsensusBuff,
+        env,
+        input_bytes.len(),
+    )?;
+
+    let value = match Value::try_deserialize_bytes(&input_bytes, &expected_type) {
+        Ok(value) => value,
+        Err(_) => return Ok(Value::none()),
+    };
+
+    // reject trailing bytes: the buffer must hold exactly one serialized value
+    if value.serialized_size() as usize != input_bytes.len() {
+        return Ok(Value::none());
+    }
+
+    Value::some(value)
+}
+
+/// The byte order in which a buffer encodes an integer.
+enum EndianDirection {
+    LittleEndian,
+    BigEndian,
+}
+
+/// Read a buffer of at most 16 bytes as the two's complement encoding of a 128-bit
+///  integer. Buffers shorter than 16 bytes are zero-extended at their most
+///  significant end.
+fn buff_to_int_bytes(value: Value, direction: EndianDirection) -> Result<[u8; 16]> {
+    match value {
+        Value::Sequence(SequenceData::Buffer(BuffData { data })) if data.len() <= 16 => {
+            let mut bytes = [0u8; 16];
+            match direction {
+                EndianDirection::LittleEndian => bytes[..data.len()].copy_from_slice(&data),
+                EndianDirection::BigEndian => bytes[16 - data.len()..].copy_from_slice(&data),
+            }
+            Ok(bytes)
+        }
+        _ => Err(CheckErrors::TypeValueError(BUFF_16.clone(), value).into()),
+    }
+}
+
+pub fn native_buff_to_int_le(value: Value) -> Result<Value> {
+    let bytes = buff_to_int_bytes(value, EndianDirection::LittleEndian)?;
+    Ok(Value::Int(i128::from_le_bytes(bytes)))
+}
+
+pub fn native_buff_to_uint_le(value: Value) -> Result<Value> {
+    let bytes = buff_to_int_bytes(value, EndianDirection::LittleEndian)?;
+    Ok(Value::UInt(u128::from_le_bytes(bytes)))
+}
+
+pub fn native_buff_to_int_be(value: Value) -> Result<Value> {
+    let bytes = buff_to_int_bytes(value, EndianDirection::BigEndian)?;
+    Ok(Value::Int(i128::from_be_bytes(bytes)))
+}
+
+pub fn native_buff_to_uint_be(value: Value) -> Result<Value> {
+    let bytes = buff_to_int_bytes(value, EndianDirection::BigEndian)?;
+    Ok(Value::UInt(u128::from_be_bytes(bytes)))
+}
+
+/// Render an integer as its decimal representation in an ASCII string.
+pub fn native_int_to_ascii(value: Value) -> Result<Value> {
+    let digits = match value {
+        Value::Int(int_value) => int_value.to_string(),
+        Value::UInt(uint_value) => uint_value.to_string(),
+        _ => {
+            return Err(CheckErrors::UnionTypeValueError(
+                vec![TypeSignature::IntType, TypeSignature::UIntType],
+                value,
+            )
+            .into())
+        }
+    };
+    Value::string_ascii_from_bytes(digits.into_bytes())
+}
+
+/// Get the characters of an ASCII or UTF-8 string, or `None` if a UTF-8 string
+///  contains characters outside of the ASCII range (which can never be digits).
+fn string_to_ascii_digits(value: Value) -> Result<Option<String>> {
+    match value {
+        Value::Sequence(SequenceData::String(CharType::ASCII(ASCIIData { data }))) => {
+            Ok(String::from_utf8(data).ok())
+        }
+        Value::Sequence(SequenceData::String(CharType::UTF8(UTF8Data { data }))) => {
+            if data.iter().any(|c| c.len() != 1) {
+                return Ok(None);
+            }
+            Ok(String::from_utf8(data.into_iter().flatten().collect()).ok())
+        }
+        _ => Err(CheckErrors::UnionTypeValueError(
+            vec![
+                TypeSignature::max_string_ascii(),
+                TypeSignature::max_string_utf8(),
+            ],
+            value,
+        )
+        .into()),
+    }
+}
+
+/// Parse a decimal string into an int, evaluating to `none` if it is not a valid
+///  decimal representation of a 128-bit signed integer.
+pub fn native_string_to_int(value: Value) -> Result<Value> {
+    match string_to_ascii_digits(value)?.and_then(|digits| digits.parse::<i128>().ok()) {
+        Some(int_value) => Value::some(Value::Int(int_value)),
+        None => Ok(Value::none()),
+    }
+}
+
+/// Parse a decimal string into a uint, evaluating to `none` if it is not a valid
+///  decimal representation of a 128-bit unsigned integer.
+pub fn native_string_to_uint(value: Value) -> Result<Value> {
+    match string_to_ascii_digits(value)?.and_then(|digits| digits.parse::<u128>().ok()) {
+        Some(uint_value) => Value::some(Value::UInt(uint_value)),
+        None => Ok(Value::none()),
+    }
+}
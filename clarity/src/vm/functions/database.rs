@@ -29,8 +29,8 @@ use crate::vm::errors::{
 };
 use crate::vm::representations::{SymbolicExpression, SymbolicExpressionType};
 use crate::vm::types::{
-    BlockInfoProperty, BuffData, OptionalData, PrincipalData, SequenceData, TypeSignature, Value,
-    BUFF_32,
+    BlockInfoProperty, BuffData, BurnBlockInfoProperty, OptionalData, PrincipalData, SequenceData,
+    TupleData, TypeSignature, Value, BUFF_32,
 };
 use crate::vm::{eval, Environment, LocalContext};
 use stacks_common::types::chainstate::StacksBlockId;
@@ -768,3 +768,71 @@ pub fn special_get_block_info(
 
     Ok(Value::some(result)?)
 }
+
+pub fn special_get_burn_block_info(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    // (get-burn-block-info? property-name burn-block-height-int)
+    runtime_cost(ClarityCostFunction::GetBurnBlockInfo, env, 0)?;
+
+    check_argument_count(2, args)?;
+
+    // Handle the block property name input arg.
+    let property_name = args[0]
+        .match_atom()
+        .ok_or(CheckErrors::GetBurnBlockInfoExpectPropertyName)?;
+
+    let block_info_prop = BurnBlockInfoProperty::lookup_by_name(property_name)
+        .ok_or(CheckErrors::GetBurnBlockInfoExpectPropertyName)?;
+
+    // Handle the block-height input arg clause.
+    let height_eval = eval(&args[1], env, context)?;
+    let height_value = match height_eval {
+        Value::UInt(result) => Ok(result),
+        x => Err(CheckErrors::TypeValueError(TypeSignature::UIntType, x)),
+    }?;
+
+    let height_value = match u32::try_from(height_value) {
+        Ok(result) => result,
+        _ => return Ok(Value::none()),
+    };
+
+    // burnchain blocks are looked up in the fork that the current Stacks block's
+    //  parent was mined on, so heights past that fork's tip evaluate to `none`.
+    match block_info_prop {
+        BurnBlockInfoProperty::HeaderHash => {
+            let burnchain_header_hash_opt = env
+                .global_context
+                .database
+                .get_burnchain_block_header_hash_for_burnchain_height(height_value);
+
+            match burnchain_header_hash_opt {
+                Some(burnchain_header_hash) => {
+                    Value::some(Value::Sequence(SequenceData::Buffer(BuffData {
+                        data: burnchain_header_hash.as_bytes().to_vec(),
+                    })))
+                }
+                None => Ok(Value::none()),
+            }
+        }
+        BurnBlockInfoProperty::PoxAddrs => {
+            let pox_addrs_and_payout = env
+                .global_context
+                .database
+                .get_pox_payout_addrs_for_burnchain_height(height_value);
+
+            match pox_addrs_and_payout {
+                Some((addrs, payout)) => Value::some(Value::Tuple(TupleData::from_data(vec![
+                    (
+                        "addrs".into(),
+                        Value::list_from(addrs.into_iter().map(Value::Tuple).collect())?,
+                    ),
+                    ("payout".into(), Value::UInt(payout)),
+                ])?)),
+                None => Ok(Value::none()),
+            }
+        }
+    }
+}
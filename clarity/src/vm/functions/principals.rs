@@ -0,0 +1,175 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::convert::TryFrom;
+
+use crate::vm::ast::parser::CONTRACT_MAX_NAME_LENGTH;
+use crate::vm::costs::cost_functions::ClarityCostFunction;
+use crate::vm::costs::runtime_cost;
+use crate::vm::errors::{
+    check_argument_count, check_arguments_at_least, CheckErrors, InterpreterResult as Result,
+};
+use crate::vm::representations::{ContractName, SymbolicExpression};
+use crate::vm::types::{
+    ASCIIData, BuffData, CharType, PrincipalData, QualifiedContractIdentifier, SequenceData,
+    StandardPrincipalData, TupleData, TypeSignature, Value, BUFF_1, BUFF_20,
+};
+use crate::vm::{eval, Environment, LocalContext};
+use stacks_common::address::{
+    C32_ADDRESS_VERSION_MAINNET_MULTISIG, C32_ADDRESS_VERSION_MAINNET_SINGLESIG,
+    C32_ADDRESS_VERSION_TESTNET_MULTISIG, C32_ADDRESS_VERSION_TESTNET_SINGLESIG,
+};
+
+/// Error code returned by `principal-construct` if the version byte is not a valid
+///  c32 address version.
+const PRINCIPAL_CONSTRUCT_BAD_VERSION: u128 = 1;
+/// Error code returned by `principal-construct` if the hash bytes are not 20 bytes long.
+const PRINCIPAL_CONSTRUCT_BAD_HASH_BYTES: u128 = 2;
+/// Error code returned by `principal-construct` if the contract name is not valid.
+const PRINCIPAL_CONSTRUCT_BAD_CONTRACT_NAME: u128 = 3;
+
+/// c32 address versions are 5-bit values.
+const MAX_ADDRESS_VERSION: u8 = 31;
+
+/// Does `version` belong to the network this chain runs on? The chain's
+///  `TransactionVersion` is surfaced to the VM as the global context's `mainnet` flag.
+fn version_matches_network(version: u8, mainnet: bool) -> bool {
+    if mainnet {
+        version == C32_ADDRESS_VERSION_MAINNET_SINGLESIG
+            || version == C32_ADDRESS_VERSION_MAINNET_MULTISIG
+    } else {
+        version == C32_ADDRESS_VERSION_TESTNET_SINGLESIG
+            || version == C32_ADDRESS_VERSION_TESTNET_MULTISIG
+    }
+}
+
+pub fn special_is_standard(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(1, args)?;
+    runtime_cost(ClarityCostFunction::IsStandard, env, 0)?;
+
+    let owner = eval(&args[0], env, context)?;
+    let version = match owner {
+        Value::Principal(PrincipalData::Standard(StandardPrincipalData(version, _))) => version,
+        Value::Principal(PrincipalData::Contract(QualifiedContractIdentifier {
+            issuer: StandardPrincipalData(version, _),
+            ..
+        })) => version,
+        _ => return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, owner).into()),
+    };
+
+    Ok(Value::Bool(version_matches_network(
+        version,
+        env.global_context.mainnet,
+    )))
+}
+
+/// Split a principal into its version byte, its hash bytes, and (for contract
+///  principals) its contract name.
+pub fn native_principal_destruct(principal: Value) -> Result<Value> {
+    let (issuer, name) = match principal {
+        Value::Principal(PrincipalData::Standard(issuer)) => (issuer, None),
+        Value::Principal(PrincipalData::Contract(QualifiedContractIdentifier { issuer, name })) => {
+            (issuer, Some(name))
+        }
+        _ => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::PrincipalType, principal).into())
+        }
+    };
+
+    let StandardPrincipalData(version, hash_bytes) = issuer;
+    let name = match name {
+        Some(name) => Value::some(Value::string_ascii_from_bytes(name.as_bytes().to_vec())?)?,
+        None => Value::none(),
+    };
+
+    TupleData::from_data(vec![
+        ("version".into(), Value::buff_from_byte(version)),
+        ("hash-bytes".into(), Value::buff_from(hash_bytes.to_vec())?),
+        ("name".into(), name),
+    ])
+    .map(Value::from)
+}
+
+/// Build a principal from a version byte, hash bytes, and an optional contract name.
+///  Evaluates to an `err` code if the parts do not make up a valid principal.
+pub fn special_principal_construct(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_arguments_at_least(2, args)?;
+    if args.len() > 3 {
+        return Err(CheckErrors::IncorrectArgumentCount(3, args.len()).into());
+    }
+    runtime_cost(ClarityCostFunction::PrincipalConstruct, env, 0)?;
+
+    let version = eval(&args[0], env, context)?;
+    let version = match version {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) if data.len() <= 1 => {
+            match data.first() {
+                Some(version) if *version <= MAX_ADDRESS_VERSION => *version,
+                _ => return Ok(Value::err_uint(PRINCIPAL_CONSTRUCT_BAD_VERSION)),
+            }
+        }
+        _ => return Err(CheckErrors::TypeValueError(BUFF_1.clone(), version).into()),
+    };
+
+    let hash_bytes = eval(&args[1], env, context)?;
+    let hash_bytes = match hash_bytes {
+        Value::Sequence(SequenceData::Buffer(BuffData { ref data })) if data.len() <= 20 => {
+            if data.len() != 20 {
+                return Ok(Value::err_uint(PRINCIPAL_CONSTRUCT_BAD_HASH_BYTES));
+            }
+            let mut hash_bytes = [0u8; 20];
+            hash_bytes.copy_from_slice(data);
+            hash_bytes
+        }
+        _ => return Err(CheckErrors::TypeValueError(BUFF_20.clone(), hash_bytes).into()),
+    };
+
+    let issuer = StandardPrincipalData(version, hash_bytes);
+    if args.len() < 3 {
+        return Value::okay(Value::Principal(PrincipalData::Standard(issuer)));
+    }
+
+    let name = eval(&args[2], env, context)?;
+    let name = match name {
+        Value::Sequence(SequenceData::String(CharType::ASCII(ASCIIData { ref data })))
+            if data.len() <= CONTRACT_MAX_NAME_LENGTH =>
+        {
+            let name = String::from_utf8(data.clone()).ok();
+            match name.and_then(|name| ContractName::try_from(name).ok()) {
+                Some(name) => name,
+                None => return Ok(Value::err_uint(PRINCIPAL_CONSTRUCT_BAD_CONTRACT_NAME)),
+            }
+        }
+        _ => {
+            return Err(CheckErrors::TypeValueError(
+                TypeSignature::contract_name_string_ascii_type(),
+                name,
+            )
+            .into())
+        }
+    };
+
+    Value::okay(Value::Principal(PrincipalData::Contract(
+        QualifiedContractIdentifier { issuer, name },
+    )))
+}
@@ -343,3 +343,78 @@ pub fn native_element_at(sequence: Value, index: Value) -> Result<Value> {
         Ok(Value::none())
     }
 }
+
+pub fn special_slice(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let left_position = eval(&args[1], env, context)?;
+    let right_position = eval(&args[2], env, context)?;
+
+    let sequence_data = if let Value::Sequence(sequence_data) = sequence {
+        sequence_data
+    } else {
+        return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into());
+    };
+
+    runtime_cost(ClarityCostFunction::Slice, env, sequence_data.len())?;
+
+    let (left_position, right_position) = match (left_position, right_position) {
+        (Value::UInt(left_position), Value::UInt(right_position)) => {
+            match (
+                usize::try_from(left_position),
+                usize::try_from(right_position),
+            ) {
+                (Ok(left_position), Ok(right_position)) => (left_position, right_position),
+                _ => return Ok(Value::none()),
+            }
+        }
+        (Value::UInt(_), position) | (position, _) => {
+            return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, position).into())
+        }
+    };
+
+    match sequence_data.slice(left_position, right_position) {
+        Some(result) => Value::some(Value::Sequence(result)),
+        None => Ok(Value::none()),
+    }
+}
+
+pub fn special_replace_at(
+    args: &[SymbolicExpression],
+    env: &mut Environment,
+    context: &LocalContext,
+) -> Result<Value> {
+    check_argument_count(3, args)?;
+
+    let sequence = eval(&args[0], env, context)?;
+    let index = eval(&args[1], env, context)?;
+    let element = eval(&args[2], env, context)?;
+
+    let sequence_data = if let Value::Sequence(sequence_data) = sequence {
+        sequence_data
+    } else {
+        return Err(CheckErrors::ExpectedSequence(TypeSignature::type_of(&sequence)).into());
+    };
+
+    runtime_cost(ClarityCostFunction::ReplaceAt, env, sequence_data.len())?;
+
+    let index = if let Value::UInt(index_u128) = index {
+        if let Ok(index_usize) = usize::try_from(index_u128) {
+            index_usize
+        } else {
+            return Ok(Value::none());
+        }
+    } else {
+        return Err(CheckErrors::TypeValueError(TypeSignature::UIntType, index).into());
+    };
+
+    match sequence_data.replace_at(index, element)? {
+        Some(result) => Value::some(Value::Sequence(result)),
+        None => Ok(Value::none()),
+    }
+}
@@ -50,6 +50,8 @@ pub mod test_util;
 
 pub mod clarity;
 
+pub mod version;
+
 // publish the non-generic StacksEpoch form for use throughout module
 use crate::types::StacksEpochId;
 pub use crate::vm::database::clarity_db::StacksEpoch;
@@ -77,6 +79,7 @@ pub use crate::vm::representations::{
 pub use crate::vm::contexts::MAX_CONTEXT_DEPTH;
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 pub use crate::vm::functions::stx_transfer_consolidated;
+pub use crate::vm::version::ClarityVersion;
 use std::convert::{TryFrom, TryInto};
 
 const MAX_CALL_STACK_DEPTH: usize = 64;
@@ -118,7 +121,9 @@ fn lookup_variable(name: &str, context: &LocalContext, env: &mut Environment) ->
 pub fn lookup_function(name: &str, env: &mut Environment) -> Result<CallableType> {
     runtime_cost(ClarityCostFunction::LookupFunction, env, 0)?;
 
-    if let Some(result) = functions::lookup_reserved_functions(name) {
+    if let Some(result) =
+        functions::lookup_reserved_functions(name, env.contract_context.get_clarity_version())
+    {
         Ok(result)
     } else {
         let user_function = env
@@ -259,10 +264,10 @@ pub fn eval<'a>(
     }
 }
 
-pub fn is_reserved(name: &str) -> bool {
-    if let Some(_result) = functions::lookup_reserved_functions(name) {
+pub fn is_reserved(name: &str, version: &ClarityVersion) -> bool {
+    if let Some(_result) = functions::lookup_reserved_functions(name, version) {
         true
-    } else if variables::is_reserved_name(name) {
+    } else if variables::is_reserved_name(name, version) {
         true
     } else {
         false
@@ -405,22 +410,48 @@ pub fn execute(program: &str) -> Result<Option<Value>> {
     execute_on_network(program, false)
 }
 
+/// Execute `program` as a Clarity 2 contract in Epoch 2.1, on the `Testnet`.
+#[cfg(any(test, feature = "testing"))]
+pub fn execute_v2(program: &str) -> Result<Option<Value>> {
+    execute_with_parameters(
+        program,
+        ClarityVersion::Clarity2,
+        StacksEpochId::Epoch21,
+        false,
+    )
+}
+
 #[cfg(any(test, feature = "testing"))]
 pub fn execute_in_epoch(
     program: &str,
     epoch: StacksEpochId,
     use_mainnet: bool,
+) -> Result<Option<Value>> {
+    execute_with_parameters(
+        program,
+        ClarityVersion::default_for_epoch(epoch),
+        epoch,
+        use_mainnet,
+    )
+}
+
+#[cfg(any(test, feature = "testing"))]
+pub fn execute_with_parameters(
+    program: &str,
+    clarity_version: ClarityVersion,
+    epoch: StacksEpochId,
+    use_mainnet: bool,
 ) -> Result<Option<Value>> {
     use crate::vm::database::MemoryBackingStore;
 
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let mut contract_context = ContractContext::new(contract_id.clone(), clarity_version);
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context =
         GlobalContext::new(use_mainnet, conn, LimitedCostTracker::new_free(), epoch);
     global_context.execute(|g| {
-        let parsed = ast::build_ast(&contract_id, program, &mut ())?.expressions;
+        let parsed = ast::build_ast(&contract_id, program, &mut (), clarity_version)?.expressions;
         eval_all(&parsed, &mut contract_context, g)
     })
 }
@@ -436,8 +467,8 @@ mod test {
     use crate::vm::execute;
     use crate::vm::types::{QualifiedContractIdentifier, TypeSignature};
     use crate::vm::{
-        CallStack, ContractContext, Environment, GlobalContext, LocalContext, SymbolicExpression,
-        Value,
+        CallStack, ClarityVersion, ContractContext, Environment, GlobalContext, LocalContext,
+        SymbolicExpression, Value,
     };
     use std::collections::HashMap;
 
@@ -470,7 +501,10 @@ mod test {
         );
 
         let context = LocalContext::new();
-        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        let mut contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            ClarityVersion::Clarity1,
+        );
 
         let mut marf = MemoryBackingStore::new();
         let mut global_context = GlobalContext::new(
@@ -33,6 +33,7 @@ use crate::vm::types::{
     OptionalData, PrincipalData, QualifiedContractIdentifier, ResponseData, StandardPrincipalData,
     TypeSignature, Value,
 };
+use crate::vm::version::ClarityVersion;
 use stacks_common::util::hash::hex_bytes;
 
 use crate::vm::database::MemoryBackingStore;
@@ -811,6 +812,52 @@ fn test_as_max_len() {
     with_memory_environment(test, true);
 }
 
+#[test]
+fn test_contract_clarity_version() {
+    fn test(owned_env: &mut OwnedEnvironment) {
+        let contract = "(define-read-only (get-one) 1)";
+        let default_id = QualifiedContractIdentifier::local("default-version").unwrap();
+        let versioned_id = QualifiedContractIdentifier::local("versioned").unwrap();
+        let missing_id = QualifiedContractIdentifier::local("missing").unwrap();
+
+        owned_env
+            .initialize_contract(default_id.clone(), &contract)
+            .unwrap();
+        owned_env
+            .initialize_versioned_contract(
+                versioned_id.clone(),
+                ClarityVersion::Clarity2,
+                &contract,
+            )
+            .unwrap();
+
+        let mut env = owned_env.get_exec_environment(None);
+        let db = &mut env.global_context.database;
+        db.begin();
+
+        // the test environment is in epoch 2.05, which defaults to Clarity 1
+        assert_eq!(
+            db.get_contract_clarity_version(&default_id).unwrap(),
+            ClarityVersion::Clarity1
+        );
+        assert_eq!(
+            db.get_contract_clarity_version(&versioned_id).unwrap(),
+            ClarityVersion::Clarity2
+        );
+        // there is no version for a contract that was never deployed
+        assert!(db.get_contract_clarity_version(&missing_id).is_err());
+
+        let stored = db.get_contract(&versioned_id).unwrap();
+        assert_eq!(
+            stored.contract_context.get_clarity_version(),
+            &ClarityVersion::Clarity2
+        );
+        db.commit();
+    }
+
+    with_memory_environment(test, true);
+}
+
 #[test]
 fn test_ast_stack_depth() {
     let program = "(+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ (+ 
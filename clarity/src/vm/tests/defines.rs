@@ -19,6 +19,7 @@ use crate::vm::ast::errors::ParseErrors;
 use crate::vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use crate::vm::execute;
 use crate::vm::types::{QualifiedContractIdentifier, TypeSignature, Value};
+use crate::vm::ClarityVersion;
 
 fn assert_eq_err(e1: CheckErrors, e2: Error) {
     let e1: Error = e1.into();
@@ -188,7 +189,13 @@ fn test_recursive_panic() {
               (* a (factorial (- a 1)))))
          (factorial 10)";
 
-    let err = build_ast(&QualifiedContractIdentifier::transient(), tests, &mut ()).unwrap_err();
+    let err = build_ast(
+        &QualifiedContractIdentifier::transient(),
+        tests,
+        &mut (),
+        ClarityVersion::Clarity1,
+    )
+    .unwrap_err();
     match err.err {
         ParseErrors::CircularReference(_) => {}
         _ => panic!("{:?}", err),
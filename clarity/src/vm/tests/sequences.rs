@@ -20,7 +20,7 @@ use crate::vm::types::{TypeSignature, Value};
 
 use crate::vm::analysis::errors::CheckError;
 use crate::vm::errors::{CheckErrors, Error, RuntimeErrorType};
-use crate::vm::execute;
+use crate::vm::{execute, execute_v2};
 use std::convert::TryInto;
 
 #[test]
@@ -174,6 +174,114 @@ fn test_element_at() {
     }
 }
 
+#[test]
+fn test_slice() {
+    let good = [
+        "(slice (list 1 2 3 4 5) u1 u3)",
+        "(slice (list 1 2 3 4 5) u0 u5)",
+        "(slice (list 1 2 3 4 5) u2 u2)",
+        "(slice \"blockstack\" u5 u10)",
+        "(slice 0x00112233 u1 u2)",
+        "(slice u\"abcd\" u2 u4)",
+        "(slice (list 1 2 3 4 5) u3 u6)",
+        "(slice (list 1 2 3 4 5) u3 u2)",
+        "(slice \"abcd\" u4 u4)",
+        "(slice \"abcd\" u5 u5)",
+    ];
+
+    let expected = [
+        "(some (2 3))",
+        "(some (1 2 3 4 5))",
+        "(some ())",
+        "(some \"stack\")",
+        "(some 0x11)",
+        "(some u\"cd\")",
+        "none",
+        "none",
+        "(some \"\")",
+        "none",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute_v2(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = ["(slice 3 u1 u2)", "(slice (list 1 2 3) 1 u2)"];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(1)),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        match execute_v2(&bad_test).unwrap_err() {
+            Error::Unchecked(check_error) => {
+                assert_eq!(&check_error, expected);
+            }
+            _ => unreachable!("Should have raised unchecked errors"),
+        }
+    }
+}
+
+#[test]
+fn test_replace_at() {
+    let good = [
+        "(replace-at (list 1 2 3) u1 5)",
+        "(replace-at (list (some 1) none) u1 (some 2))",
+        "(replace-at \"abcd\" u0 \"z\")",
+        "(replace-at 0x0011 u1 0xff)",
+        "(replace-at u\"abcd\" u3 u\"z\")",
+        "(replace-at (list 1 2 3) u3 5)",
+        "(replace-at \"abcd\" u4 \"z\")",
+        "(replace-at 0x0011 u0 0x)",
+    ];
+
+    let expected = [
+        "(some (1 5 3))",
+        "(some ((some 1) (some 2)))",
+        "(some \"zbcd\")",
+        "(some 0x00ff)",
+        "(some u\"abcz\")",
+        "none",
+        "none",
+        "none",
+    ];
+
+    for (good_test, expected) in good.iter().zip(expected.iter()) {
+        assert_eq!(
+            expected,
+            &format!("{}", execute_v2(&good_test).unwrap().unwrap())
+        );
+    }
+
+    let bad = [
+        "(replace-at 3 u1 1)",
+        "(replace-at (list 1 2 3) 1 1)",
+        "(replace-at \"abcd\" u1 0x00)",
+    ];
+
+    let bad_expected = [
+        CheckErrors::ExpectedSequence(TypeSignature::IntType),
+        CheckErrors::TypeValueError(TypeSignature::UIntType, Value::Int(1)),
+        CheckErrors::TypeValueError(
+            TypeSignature::min_string_ascii(),
+            Value::buff_from_byte(0x00),
+        ),
+    ];
+
+    for (bad_test, expected) in bad.iter().zip(bad_expected.iter()) {
+        match execute_v2(&bad_test).unwrap_err() {
+            Error::Unchecked(check_error) => {
+                assert_eq!(&check_error, expected);
+            }
+            _ => unreachable!("Should have raised unchecked errors"),
+        }
+    }
+}
+
 #[test]
 fn test_string_ascii_admission() {
     let defines = "(define-private (set-name (x (string-ascii 11))) x)";
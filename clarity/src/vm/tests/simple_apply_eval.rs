@@ -23,10 +23,12 @@ use crate::vm::costs::LimitedCostTracker;
 use crate::vm::errors::{CheckErrors, Error, RuntimeErrorType, ShortReturnType};
 use crate::vm::tests::execute;
 use crate::vm::types::signatures::*;
-use crate::vm::types::{BuffData, QualifiedContractIdentifier, TypeSignature};
+use crate::vm::types::{BuffData, QualifiedContractIdentifier, TupleData, TypeSignature};
 use crate::vm::types::{PrincipalData, ResponseData, SequenceData, SequenceSubtype};
-use crate::vm::{eval, execute as vm_execute};
-use crate::vm::{CallStack, ContractContext, Environment, GlobalContext, LocalContext, Value};
+use crate::vm::{eval, execute as vm_execute, execute_v2, execute_with_parameters};
+use crate::vm::{
+    CallStack, ClarityVersion, ContractContext, Environment, GlobalContext, LocalContext, Value,
+};
 use stacks_common::address::c32;
 use stacks_common::address::AddressHashMode;
 use stacks_common::address::C32_ADDRESS_VERSION_MAINNET_SINGLESIG;
@@ -383,7 +385,10 @@ fn test_simple_if_functions() {
         );
 
         let context = LocalContext::new();
-        let mut contract_context = ContractContext::new(QualifiedContractIdentifier::transient());
+        let mut contract_context = ContractContext::new(
+            QualifiedContractIdentifier::transient(),
+            ClarityVersion::Clarity1,
+        );
         let mut marf = MemoryBackingStore::new();
         let mut global_context = GlobalContext::new(
             false,
@@ -1025,3 +1030,425 @@ fn test_asserts_short_circuit() {
             assert_eq!((*expectation), vm_execute(program).unwrap_err())
         });
 }
+
+#[test]
+fn test_consensus_buff_round_trip() {
+    let tests = [
+        "(from-consensus-buff int (to-consensus-buff 1))",
+        "(from-consensus-buff uint (to-consensus-buff u1))",
+        "(from-consensus-buff (string-ascii 8) (to-consensus-buff \"abc\"))",
+        "(from-consensus-buff { a: int, b: (optional uint) } (to-consensus-buff { a: -1, b: (some u2) }))",
+        "(from-consensus-buff (list 4 int) (to-consensus-buff (list 1 2 3)))",
+    ];
+
+    let expectations = [
+        Value::some(Value::Int(1)).unwrap(),
+        Value::some(Value::UInt(1)).unwrap(),
+        Value::some(Value::string_ascii_from_bytes("abc".as_bytes().to_vec()).unwrap()).unwrap(),
+        Value::some(
+            TupleData::from_data(vec![
+                ("a".into(), Value::Int(-1)),
+                ("b".into(), Value::some(Value::UInt(2)).unwrap()),
+            ])
+            .unwrap()
+            .into(),
+        )
+        .unwrap(),
+        Value::some(Value::list_from(vec![Value::Int(1), Value::Int(2), Value::Int(3)]).unwrap())
+            .unwrap(),
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(expectation.clone(), execute_v2(program).unwrap().unwrap())
+        });
+}
+
+#[test]
+fn test_to_consensus_buff() {
+    let tests = [
+        "(to-consensus-buff 1)",
+        "(to-consensus-buff u1)",
+        "(to-consensus-buff false)",
+        "(to-consensus-buff none)",
+        "(to-consensus-buff (err u1))",
+    ];
+
+    let expectations = [
+        "0000000000000000000000000000000001",
+        "0100000000000000000000000000000001",
+        "04",
+        "09",
+        "080100000000000000000000000000000001",
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(
+                Value::buff_from(hex_bytes(expectation).unwrap()).unwrap(),
+                execute_v2(program).unwrap().unwrap()
+            )
+        });
+}
+
+#[test]
+fn test_from_consensus_buff_invalid() {
+    let tests = [
+        // wrong type prefix
+        "(from-consensus-buff uint 0x0000000000000000000000000000000001)",
+        // truncated value
+        "(from-consensus-buff int 0x00000000000000000000000000000001)",
+        // trailing bytes
+        "(from-consensus-buff int 0x000000000000000000000000000000000100)",
+        // list longer than the expected type admits
+        "(from-consensus-buff (list 1 int) (to-consensus-buff (list 1 2)))",
+        // string longer than the expected type admits
+        "(from-consensus-buff (string-ascii 2) (to-consensus-buff \"abc\"))",
+    ];
+
+    for program in tests.iter() {
+        assert_eq!(Value::none(), execute_v2(program).unwrap().unwrap());
+    }
+}
+
+#[test]
+fn test_consensus_buff_not_in_clarity1() {
+    assert_eq!(
+        Error::from(CheckErrors::UndefinedFunction(
+            "to-consensus-buff".to_string()
+        )),
+        vm_execute("(to-consensus-buff 1)").unwrap_err()
+    );
+}
+
+#[test]
+fn test_bitwise_functions() {
+    let tests = [
+        "(bit-and 24 16)",
+        "(bit-and 28 24 -1)",
+        "(bit-and u24 u16)",
+        "(bit-and -128 -64)",
+        "(bit-or 4 8)",
+        "(bit-or 1 2 4)",
+        "(bit-or 64 -32 -16)",
+        "(bit-or u2 u4 u32)",
+        "(bit-not 3)",
+        "(bit-not u128)",
+        "(bit-not -128)",
+        "(bit-shift-left 2 u1)",
+        "(bit-shift-left -64 u1)",
+        "(bit-shift-left u4 u2)",
+        "(bit-shift-left 123 u9999999999)",
+        "(bit-shift-left u123 u9999999999)",
+        "(bit-shift-left 1 u128)",
+        "(bit-shift-right 128 u2)",
+        "(bit-shift-right -64 u1)",
+        "(bit-shift-right u128 u2)",
+        "(bit-shift-right u340282366920938463463374607431768211455 u127)",
+        "(bit-shift-right -128 u7)",
+        "(bit-shift-right -256 u1)",
+        "(bit-shift-right 123 u9999999999)",
+    ];
+
+    let expectations = [
+        Value::Int(16),
+        Value::Int(24),
+        Value::UInt(16),
+        Value::Int(-128),
+        Value::Int(12),
+        Value::Int(7),
+        Value::Int(-16),
+        Value::UInt(38),
+        Value::Int(-4),
+        Value::UInt(u128::MAX - 128),
+        Value::Int(127),
+        Value::Int(4),
+        Value::Int(-128),
+        Value::UInt(16),
+        Value::Int(i128::MIN),
+        Value::UInt(1 << 127),
+        Value::Int(1),
+        Value::Int(32),
+        Value::Int(-32),
+        Value::UInt(32),
+        Value::UInt(1),
+        Value::Int(-1),
+        Value::Int(-128),
+        Value::Int(0),
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(expectation.clone(), execute_v2(program).unwrap().unwrap())
+        });
+
+    let bad_shift = "(bit-shift-left 1 1)";
+    assert_eq!(
+        Error::from(CheckErrors::TypeValueError(
+            TypeSignature::UIntType,
+            Value::Int(1)
+        )),
+        execute_v2(bad_shift).unwrap_err()
+    );
+
+    let mixed_types = "(bit-and 1 u1)";
+    assert_eq!(
+        Error::from(CheckErrors::TypeValueError(
+            TypeSignature::IntType,
+            Value::UInt(1)
+        )),
+        execute_v2(mixed_types).unwrap_err()
+    );
+}
+
+#[test]
+fn test_buff_to_int() {
+    let tests = [
+        "(buff-to-int-le 0x01)",
+        "(buff-to-int-le 0x01000000000000000000000000000000)",
+        "(buff-to-int-le 0xffffffffffffffffffffffffffffffff)",
+        "(buff-to-int-le 0xff)",
+        "(buff-to-int-le 0x)",
+        "(buff-to-uint-le 0x0001)",
+        "(buff-to-uint-le 0xffffffffffffffffffffffffffffffff)",
+        "(buff-to-int-be 0x01)",
+        "(buff-to-int-be 0x00000000000000000000000000000001)",
+        "(buff-to-int-be 0xffffffffffffffffffffffffffffffff)",
+        "(buff-to-int-be 0x80000000000000000000000000000000)",
+        "(buff-to-uint-be 0x0100)",
+        "(buff-to-uint-be 0x80000000000000000000000000000000)",
+    ];
+
+    let expectations = [
+        Value::Int(1),
+        Value::Int(1),
+        Value::Int(-1),
+        Value::Int(255),
+        Value::Int(0),
+        Value::UInt(256),
+        Value::UInt(u128::MAX),
+        Value::Int(1),
+        Value::Int(1),
+        Value::Int(-1),
+        Value::Int(i128::MIN),
+        Value::UInt(256),
+        Value::UInt(1 << 127),
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(expectation.clone(), execute_v2(program).unwrap().unwrap())
+        });
+
+    let too_long = "(buff-to-int-le 0x0000000000000000000000000000000000)";
+    assert_eq!(
+        Error::from(CheckErrors::TypeValueError(
+            BUFF_16.clone(),
+            Value::buff_from(vec![0; 17]).unwrap()
+        )),
+        execute_v2(too_long).unwrap_err()
+    );
+}
+
+#[test]
+fn test_int_to_ascii() {
+    let tests = [
+        "(int-to-ascii 1)",
+        "(int-to-ascii u1)",
+        "(int-to-ascii -1)",
+        "(int-to-ascii -170141183460469231731687303715884105728)",
+        "(int-to-ascii u340282366920938463463374607431768211455)",
+    ];
+
+    let expectations = [
+        "1",
+        "1",
+        "-1",
+        "-170141183460469231731687303715884105728",
+        "340282366920938463463374607431768211455",
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(
+                Value::string_ascii_from_bytes(expectation.as_bytes().to_vec()).unwrap(),
+                execute_v2(program).unwrap().unwrap()
+            )
+        });
+}
+
+#[test]
+fn test_string_to_int() {
+    let tests = [
+        "(string-to-int \"1\")",
+        "(string-to-int \"-1\")",
+        "(string-to-int u\"-170141183460469231731687303715884105728\")",
+        "(string-to-int \"170141183460469231731687303715884105728\")",
+        "(string-to-int \"a\")",
+        "(string-to-int \"\")",
+        "(string-to-int u\"1\\u{0663}\")",
+        "(string-to-uint \"1\")",
+        "(string-to-uint u\"340282366920938463463374607431768211455\")",
+        "(string-to-uint \"340282366920938463463374607431768211456\")",
+        "(string-to-uint \"-1\")",
+        "(string-to-uint \"1.0\")",
+    ];
+
+    let expectations = [
+        Value::some(Value::Int(1)).unwrap(),
+        Value::some(Value::Int(-1)).unwrap(),
+        Value::some(Value::Int(i128::MIN)).unwrap(),
+        Value::none(),
+        Value::none(),
+        Value::none(),
+        Value::none(),
+        Value::some(Value::UInt(1)).unwrap(),
+        Value::some(Value::UInt(u128::MAX)).unwrap(),
+        Value::none(),
+        Value::none(),
+        Value::none(),
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(expectation.clone(), execute_v2(program).unwrap().unwrap())
+        });
+}
+
+#[test]
+fn test_principal_destruct() {
+    let hash_bytes =
+        Value::buff_from(hex_bytes("164247d6f2b425ac5771423ae6c80c754f7172b0").unwrap()).unwrap();
+
+    let standard = execute_v2("(principal-destruct 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)")
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        Value::from(
+            TupleData::from_data(vec![
+                ("version".into(), Value::buff_from_byte(0x1a)),
+                ("hash-bytes".into(), hash_bytes.clone()),
+                ("name".into(), Value::none()),
+            ])
+            .unwrap()
+        ),
+        standard
+    );
+
+    let contract =
+        execute_v2("(principal-destruct 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.foo-bar)")
+            .unwrap()
+            .unwrap();
+    assert_eq!(
+        Value::from(
+            TupleData::from_data(vec![
+                ("version".into(), Value::buff_from_byte(0x16)),
+                (
+                    "hash-bytes".into(),
+                    Value::buff_from(
+                        hex_bytes("fa6bf38ed557fe417333710d6033e9419391a320").unwrap()
+                    )
+                    .unwrap()
+                ),
+                (
+                    "name".into(),
+                    Value::some(Value::string_ascii_from_bytes(b"foo-bar".to_vec()).unwrap())
+                        .unwrap()
+                ),
+            ])
+            .unwrap()
+        ),
+        contract
+    );
+}
+
+#[test]
+fn test_principal_construct() {
+    let tests = [
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 \"foo-bar\")",
+        "(principal-construct 0x1f 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        "(principal-construct 0x20 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        "(principal-construct 0x 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172)",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 \"\")",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 \"1foo\")",
+        "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0 \"foo.bar\")",
+    ];
+
+    let expectations = [
+        "(ok STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)",
+        "(ok STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6.foo-bar)",
+        "(ok SZB44HYPYAT2BB2QE513NSP81HTMYWBJP39F2EX9)",
+        "(err u1)",
+        "(err u1)",
+        "(err u2)",
+        "(err u3)",
+        "(err u3)",
+        "(err u3)",
+    ];
+
+    tests
+        .iter()
+        .zip(expectations.iter())
+        .for_each(|(program, expectation)| {
+            assert_eq!(
+                expectation.to_string(),
+                execute_v2(program).unwrap().unwrap().to_string()
+            )
+        });
+
+    // destructuring and reconstructing a principal is the identity
+    let round_trip = "(let ((parts (principal-destruct 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6.foo)))
+         (principal-construct (get version parts) (get hash-bytes parts) (unwrap-panic (get name parts))))";
+    assert_eq!(
+        "(ok STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6.foo)",
+        execute_v2(round_trip).unwrap().unwrap().to_string()
+    );
+}
+
+#[test]
+fn test_is_standard() {
+    let tests = [
+        "(is-standard 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)",
+        "(is-standard 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6.foo)",
+        "(is-standard 'SNB44HYPYAT2BB2QE513NSP81HTMYWBJP1EWMF5Z)",
+        "(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY)",
+        "(is-standard 'SP3X6QWWETNBZWGBK6DRGTR1KX50S74D3433WDGJY.foo)",
+        "(is-standard 'SM3X6QWWETNBZWGBK6DRGTR1KX50S74D341M9C5X7)",
+        "(is-standard 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+    ];
+    let testnet_expectations = [true, true, true, false, false, false, false];
+    let mainnet_expectations = [false, false, false, true, true, true, false];
+
+    for (program, (testnet, mainnet)) in tests
+        .iter()
+        .zip(testnet_expectations.iter().zip(mainnet_expectations.iter()))
+    {
+        for (use_mainnet, expectation) in [(false, testnet), (true, mainnet)].iter() {
+            assert_eq!(
+                Value::Bool(**expectation),
+                execute_with_parameters(
+                    program,
+                    ClarityVersion::Clarity2,
+                    StacksEpochId::Epoch21,
+                    *use_mainnet,
+                )
+                .unwrap()
+                .unwrap()
+            );
+        }
+    }
+}
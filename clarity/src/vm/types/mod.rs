@@ -39,8 +39,8 @@ use crate::vm::representations::{
 pub use crate::vm::types::signatures::{
     parse_name_type_pairs, AssetIdentifier, BufferLength, FixedFunction, FunctionArg,
     FunctionSignature, FunctionType, ListTypeData, SequenceSubtype, StringSubtype,
-    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_20, BUFF_32, BUFF_33,
-    BUFF_64, BUFF_65,
+    StringUTF8Length, TupleTypeSignature, TypeSignature, BUFF_1, BUFF_16, BUFF_20, BUFF_32,
+    BUFF_33, BUFF_64, BUFF_65,
 };
 
 pub const MAX_VALUE_SIZE: u32 = 1024 * 1024; // 1MB
@@ -280,6 +280,99 @@ impl SequenceData {
         Some(result)
     }
 
+    /// Returns the sub-sequence covering `[left_position, right_position)`, or `None` if
+    ///  `right_position` is before `left_position` or past the end of the sequence.
+    pub fn slice(self, left_position: usize, right_position: usize) -> Option<SequenceData> {
+        if left_position > right_position || right_position > self.len() {
+            return None;
+        }
+        let result = match self {
+            SequenceData::Buffer(data) => SequenceData::Buffer(BuffData {
+                data: data.data[left_position..right_position].to_vec(),
+            }),
+            SequenceData::List(data) => SequenceData::List(ListData {
+                data: data.data[left_position..right_position].to_vec(),
+                type_signature: data.type_signature,
+            }),
+            SequenceData::String(CharType::ASCII(data)) => {
+                SequenceData::String(CharType::ASCII(ASCIIData {
+                    data: data.data[left_position..right_position].to_vec(),
+                }))
+            }
+            SequenceData::String(CharType::UTF8(data)) => {
+                SequenceData::String(CharType::UTF8(UTF8Data {
+                    data: data.data[left_position..right_position].to_vec(),
+                }))
+            }
+        };
+
+        Some(result)
+    }
+
+    /// Replaces the element at `index` with `element`, which must be a single-unit
+    ///  sequence for buffers and strings. Returns `None` if `index` is out of range or a
+    ///  buffer or string element does not hold exactly one unit.
+    pub fn replace_at(mut self, index: usize, element: Value) -> Result<Option<SequenceData>> {
+        if index >= self.len() {
+            return Ok(None);
+        }
+        match (&mut self, element) {
+            (SequenceData::List(ref mut data), element) => {
+                // rebuild the list so that its type signature admits the new element
+                let mut items = std::mem::replace(&mut data.data, vec![]);
+                items[index] = element;
+                return match Value::list_from(items)? {
+                    Value::Sequence(list) => Ok(Some(list)),
+                    _ => Err(InterpreterError::FailureConstructingListWithType.into()),
+                };
+            }
+            (
+                SequenceData::Buffer(ref mut data),
+                Value::Sequence(SequenceData::Buffer(element)),
+            ) => {
+                if element.data.len() != 1 {
+                    return Ok(None);
+                }
+                data.data[index] = element.data[0];
+            }
+            (
+                SequenceData::String(CharType::ASCII(ref mut data)),
+                Value::Sequence(SequenceData::String(CharType::ASCII(element))),
+            ) => {
+                if element.data.len() != 1 {
+                    return Ok(None);
+                }
+                data.data[index] = element.data[0];
+            }
+            (
+                SequenceData::String(CharType::UTF8(ref mut data)),
+                Value::Sequence(SequenceData::String(CharType::UTF8(mut element))),
+            ) => {
+                if element.data.len() != 1 {
+                    return Ok(None);
+                }
+                data.data[index] = element.data.remove(0);
+            }
+            (SequenceData::Buffer(_), element) => {
+                return Err(
+                    CheckErrors::TypeValueError(TypeSignature::min_buffer(), element).into(),
+                )
+            }
+            (SequenceData::String(CharType::ASCII(_)), element) => {
+                return Err(
+                    CheckErrors::TypeValueError(TypeSignature::min_string_ascii(), element).into(),
+                )
+            }
+            (SequenceData::String(CharType::UTF8(_)), element) => {
+                return Err(
+                    CheckErrors::TypeValueError(TypeSignature::min_string_utf8(), element).into(),
+                )
+            }
+        }
+
+        Ok(Some(self))
+    }
+
     pub fn contains(&self, to_find: Value) -> Result<Option<usize>> {
         match self {
             SequenceData::Buffer(ref data) => {
@@ -583,6 +676,11 @@ define_named_enum!(BlockInfoProperty {
     MinerAddress("miner-address"),
 });
 
+define_named_enum!(BurnBlockInfoProperty {
+    HeaderHash("header-hash"),
+    PoxAddrs("pox-addrs"),
+});
+
 impl OptionalData {
     pub fn type_signature(&self) -> TypeSignature {
         let type_result = match self.data {
@@ -620,6 +718,34 @@ impl BlockInfoProperty {
     }
 }
 
+impl BurnBlockInfoProperty {
+    pub fn type_result(&self) -> TypeSignature {
+        use self::BurnBlockInfoProperty::*;
+        match self {
+            HeaderHash => BUFF_32.clone(),
+            PoxAddrs => TupleTypeSignature::try_from(vec![
+                (
+                    "addrs".into(),
+                    TypeSignature::list_of(
+                        TypeSignature::TupleType(
+                            TupleTypeSignature::try_from(vec![
+                                ("version".into(), BUFF_1.clone()),
+                                ("hashbytes".into(), BUFF_20.clone()),
+                            ])
+                            .expect("FAIL: PoxAddrs failed to initialize address type signature"),
+                        ),
+                        2,
+                    )
+                    .expect("FAIL: PoxAddrs failed to initialize list type signature"),
+                ),
+                ("payout".into(), TypeSignature::UIntType),
+            ])
+            .expect("FAIL: PoxAddrs failed to initialize type signature")
+            .into(),
+        }
+    }
+}
+
 impl PartialEq for ListData {
     fn eq(&self, other: &ListData) -> bool {
         self.data == other.data
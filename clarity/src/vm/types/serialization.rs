@@ -660,6 +660,55 @@ impl Value {
     }
 }
 
+impl TypeSignature {
+    /// Upper bound on the number of bytes `Value::serialize_write` can produce for any value
+    ///  admitted by this type, or `None` if the bound does not fit in a u32.
+    pub fn max_serialized_size(&self) -> Option<u32> {
+        use super::TypeSignature::*;
+        // every serialized value begins with a one-byte type prefix
+        let prefix_len = 1u32;
+        // sequences and tuples write their length as a u32
+        let length_prefix_len = 4u32;
+        match self {
+            // `NoType` only appears in the unused branch of an optional or response,
+            //  so it never contributes more than a prefix.
+            NoType | BoolType => Some(prefix_len),
+            IntType | UIntType => prefix_len.checked_add(16),
+            // issuer version + hash160, then a one-byte length and the contract name
+            PrincipalType | TraitReferenceType(_) => {
+                prefix_len.checked_add(1 + 20 + 1 + MAX_STRING_LEN as u32)
+            }
+            SequenceType(SequenceSubtype::BufferType(len))
+            | SequenceType(SequenceSubtype::StringType(StringSubtype::ASCII(len))) => prefix_len
+                .checked_add(length_prefix_len)?
+                .checked_add(u32::from(len)),
+            SequenceType(SequenceSubtype::StringType(StringSubtype::UTF8(len))) => prefix_len
+                .checked_add(length_prefix_len)?
+                .checked_add(u32::from(len).checked_mul(4)?),
+            SequenceType(SequenceSubtype::ListType(list_type)) => {
+                let item_size = list_type.get_list_item_type().max_serialized_size()?;
+                prefix_len
+                    .checked_add(length_prefix_len)?
+                    .checked_add(list_type.get_max_len().checked_mul(item_size)?)
+            }
+            TupleType(tuple_type) => tuple_type.get_type_map().iter().try_fold(
+                prefix_len.checked_add(length_prefix_len)?,
+                |size, (name, field_type)| {
+                    size.checked_add(1)?
+                        .checked_add(name.len() as u32)?
+                        .checked_add(field_type.max_serialized_size()?)
+                },
+            ),
+            OptionalType(inner_type) => prefix_len.checked_add(inner_type.max_serialized_size()?),
+            ResponseType(response_types) => {
+                let ok_size = response_types.0.max_serialized_size()?;
+                let err_size = response_types.1.max_serialized_size()?;
+                prefix_len.checked_add(std::cmp::max(ok_size, err_size))
+            }
+        }
+    }
+}
+
 /// A writer that just counts the bytes written
 struct WriteCounter {
     count: u32,
@@ -18,21 +18,42 @@ use crate::vm::contexts::{Environment, LocalContext};
 use crate::vm::errors::{InterpreterResult as Result, RuntimeErrorType};
 use crate::vm::types::BuffData;
 use crate::vm::types::Value;
+use crate::vm::ClarityVersion;
 use std::convert::TryFrom;
 
 use crate::vm::costs::cost_functions::ClarityCostFunction;
 use crate::vm::costs::runtime_cost;
 
-define_named_enum!(NativeVariables {
-    ContractCaller("contract-caller"), TxSender("tx-sender"), BlockHeight("block-height"),
-    BurnBlockHeight("burn-block-height"), NativeNone("none"),
-    NativeTrue("true"), NativeFalse("false"),
-    TotalLiquidMicroSTX("stx-liquid-supply"),
-    Regtest("is-in-regtest"),
+define_versioned_named_enum!(NativeVariables(ClarityVersion) {
+    ContractCaller("contract-caller", ClarityVersion::Clarity1),
+    TxSender("tx-sender", ClarityVersion::Clarity1),
+    BlockHeight("block-height", ClarityVersion::Clarity1),
+    BurnBlockHeight("burn-block-height", ClarityVersion::Clarity1),
+    NativeNone("none", ClarityVersion::Clarity1),
+    NativeTrue("true", ClarityVersion::Clarity1),
+    NativeFalse("false", ClarityVersion::Clarity1),
+    TotalLiquidMicroSTX("stx-liquid-supply", ClarityVersion::Clarity1),
+    Regtest("is-in-regtest", ClarityVersion::Clarity1),
 });
 
-pub fn is_reserved_name(name: &str) -> bool {
-    NativeVariables::lookup_by_name(name).is_some()
+impl NativeVariables {
+    /// Look up a native variable that is visible to contracts of `version`.
+    pub fn lookup_by_name_at_version(
+        name: &str,
+        version: &ClarityVersion,
+    ) -> Option<NativeVariables> {
+        NativeVariables::lookup_by_name(name).and_then(|native_variable| {
+            if &native_variable.get_version() <= version {
+                Some(native_variable)
+            } else {
+                None
+            }
+        })
+    }
+}
+
+pub fn is_reserved_name(name: &str, version: &ClarityVersion) -> bool {
+    NativeVariables::lookup_by_name_at_version(name, version).is_some()
 }
 
 pub fn lookup_reserved_variable(
@@ -40,7 +61,9 @@ pub fn lookup_reserved_variable(
     _context: &LocalContext,
     env: &mut Environment,
 ) -> Result<Option<Value>> {
-    if let Some(variable) = NativeVariables::lookup_by_name(name) {
+    if let Some(variable) =
+        NativeVariables::lookup_by_name_at_version(name, env.contract_context.get_clarity_version())
+    {
         match variable {
             NativeVariables::TxSender => {
                 let sender = env
@@ -0,0 +1,100 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::types::StacksEpochId;
+use crate::vm::errors::{Error, RuntimeErrorType};
+
+/// The version of the Clarity language that a contract is written in.
+///
+/// The version is chosen when the contract is deployed, and is stored alongside the contract
+///  and its analysis. It determines which native functions and variables are visible to the
+///  contract, so that adding natives in a new version never changes the meaning of a contract
+///  deployed under an older one.
+#[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ClarityVersion {
+    Clarity1 = 1,
+    Clarity2 = 2,
+}
+
+impl Default for ClarityVersion {
+    /// Contracts that were stored before versioning existed are Clarity 1 contracts.
+    fn default() -> ClarityVersion {
+        ClarityVersion::Clarity1
+    }
+}
+
+impl fmt::Display for ClarityVersion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ClarityVersion::Clarity1 => write!(f, "Clarity 1"),
+            ClarityVersion::Clarity2 => write!(f, "Clarity 2"),
+        }
+    }
+}
+
+impl ClarityVersion {
+    pub fn latest() -> ClarityVersion {
+        ClarityVersion::Clarity2
+    }
+
+    /// The version used for contracts that are deployed without specifying one.
+    pub fn default_for_epoch(epoch_id: StacksEpochId) -> ClarityVersion {
+        match epoch_id {
+            StacksEpochId::Epoch10 => {
+                warn!("Attempted to get default Clarity version in Epoch 1.0 where Clarity does not exist");
+                ClarityVersion::Clarity1
+            }
+            StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => ClarityVersion::Clarity1,
+            StacksEpochId::Epoch21 => ClarityVersion::Clarity2,
+        }
+    }
+
+    /// Can a contract of this version be deployed in `epoch_id`?
+    pub fn is_supported_in_epoch(&self, epoch_id: StacksEpochId) -> bool {
+        match self {
+            ClarityVersion::Clarity1 => epoch_id >= StacksEpochId::Epoch20,
+            ClarityVersion::Clarity2 => epoch_id >= StacksEpochId::Epoch21,
+        }
+    }
+
+    pub fn from_u8(version: u8) -> Option<ClarityVersion> {
+        match version {
+            x if x == ClarityVersion::Clarity1 as u8 => Some(ClarityVersion::Clarity1),
+            x if x == ClarityVersion::Clarity2 as u8 => Some(ClarityVersion::Clarity2),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for ClarityVersion {
+    type Err = Error;
+
+    fn from_str(version: &str) -> Result<ClarityVersion, Error> {
+        match version.to_lowercase().as_str() {
+            "clarity1" => Ok(ClarityVersion::Clarity1),
+            "clarity2" => Ok(ClarityVersion::Clarity2),
+            _ => Err(RuntimeErrorType::ParseError(format!(
+                "Invalid Clarity version '{}': expected clarity1 or clarity2",
+                version
+            ))
+            .into()),
+        }
+    }
+}
@@ -260,7 +260,11 @@ fn make_contract_publish(
     let name = ContractName::try_from(contract_name)?;
     let code_body = StacksString::from_string(&contract_content)
         .ok_or("Non-legal characters in contract-content")?;
-    Ok(TransactionSmartContract { name, code_body })
+    Ok(TransactionSmartContract {
+        name,
+        code_body,
+        clarity_version: None,
+    })
 }
 
 fn make_contract_call(
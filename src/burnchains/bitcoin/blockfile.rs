@@ -0,0 +1,1180 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+// This module implements a BurnchainIndexer that reads Bitcoin blocks from the local
+// filesystem instead of from a bitcoind peer.  It understands two layouts:
+// * bitcoind's own blocks directory, whose `blk*.dat` files are sequences of
+//   <magic><length><block> records, obfuscated with the key in `xor.dat` if there is one.
+//   Everything else in it (undo data, the block index) is ignored.
+// * a directory without `blk*.dat` files, where each file holds either a single
+//   consensus-serialized block or a bare 80-byte block header.
+//
+// Header-only files let a caller supply the (large) header chain below the first Stacks
+// block without also having to supply every block body.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::burnchains::bitcoin::blocks::{BitcoinBlockIPC, BitcoinBlockParser, BitcoinHeaderIPC};
+use crate::burnchains::bitcoin::indexer::{get_bitcoin_stacks_epochs, network_id_to_bytes};
+use crate::burnchains::bitcoin::spv::{SpvClient, BLOCK_DIFFICULTY_CHUNK_SIZE};
+use crate::burnchains::bitcoin::BitcoinNetworkType;
+use crate::burnchains::bitcoin::Error as btc_error;
+use crate::burnchains::indexer::{
+    BurnchainBlockDownloader, BurnchainBlockParser, BurnchainIndexer,
+};
+use crate::burnchains::BurnchainBlock;
+use crate::burnchains::Error as burnchain_error;
+use crate::burnchains::MagicBytes;
+use crate::core::StacksEpoch;
+use crate::types::chainstate::BurnchainHeaderHash;
+
+use stacks_common::deps_common::bitcoin::blockdata::block::{Block, BlockHeader, LoneBlockHeader};
+use stacks_common::deps_common::bitcoin::blockdata::constants::genesis_block;
+use stacks_common::deps_common::bitcoin::network::constants::Network;
+use stacks_common::deps_common::bitcoin::network::encodable::VarInt;
+use stacks_common::deps_common::bitcoin::network::message::NetworkMessage;
+use stacks_common::deps_common::bitcoin::network::serialize::{deserialize, BitcoinHash};
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
+use stacks_common::util::uint::Uint256;
+
+/// Size of a consensus-serialized Bitcoin block header
+pub const BITCOIN_BLOCK_HEADER_LEN: u64 = 80;
+
+/// File in bitcoind's blocks directory that holds the key its block files are obfuscated with
+pub const XOR_KEY_FILE_NAME: &str = "xor.dat";
+
+/// Length of bitcoind's block file obfuscation key
+pub const XOR_KEY_LEN: usize = 8;
+
+/// Where a block (or bare header) lives on disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFileLocation {
+    pub path: PathBuf,
+    pub offset: u64,
+    pub len: u64,
+    /// The key bitcoind obfuscated the file with, if any
+    pub xor_key: Option<[u8; XOR_KEY_LEN]>,
+}
+
+impl BlockFileLocation {
+    /// Does this location hold a full block, or just a header?
+    pub fn has_block(&self) -> bool {
+        self.len > BITCOIN_BLOCK_HEADER_LEN
+    }
+}
+
+/// A header in the block tree, with its height and the cumulative work of the chain it ends
+#[derive(Debug, Clone, PartialEq)]
+struct IndexedHeader {
+    header: BlockHeader,
+    height: u64,
+    work: Uint256,
+}
+
+/// In-memory index over a directory of block files.
+/// `chain` is the most-work chain that descends from the network's genesis block, where
+/// `chain[i]` is the header at height `i`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlockFileIndex {
+    pub locations: HashMap<Sha256dHash, BlockFileLocation>,
+    pub chain: Vec<LoneBlockHeader>,
+    network_id: BitcoinNetworkType,
+    /// Every header that descends from genesis
+    headers: HashMap<Sha256dHash, IndexedHeader>,
+    /// Headers whose parents have not been seen yet, by parent hash
+    orphans: HashMap<Sha256dHash, Vec<BlockHeader>>,
+    best_tip: Sha256dHash,
+    best_work: Uint256,
+    /// How far each file has been read
+    scanned: HashMap<PathBuf, u64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BitcoinBlockFileIndexerConfig {
+    /// Directory that holds `blk*.dat` files and/or serialized blocks and headers
+    pub blocks_path: String,
+    pub spv_headers_path: String,
+    pub first_block: u64,
+    pub magic_bytes: MagicBytes,
+    pub epochs: Option<Vec<StacksEpoch>>,
+}
+
+pub struct BitcoinBlockFileIndexer {
+    pub config: BitcoinBlockFileIndexerConfig,
+    pub network_id: BitcoinNetworkType,
+    index: Option<Arc<BlockFileIndex>>,
+}
+
+pub struct BitcoinBlockFileDownloader {
+    index: Arc<BlockFileIndex>,
+}
+
+pub struct BitcoinBlockFileParser {
+    parser: BitcoinBlockParser,
+}
+
+fn network_id_to_network(network_id: BitcoinNetworkType) -> Network {
+    match network_id {
+        BitcoinNetworkType::Mainnet => Network::Bitcoin,
+        BitcoinNetworkType::Testnet => Network::Testnet,
+        BitcoinNetworkType::Regtest => Network::Regtest,
+    }
+}
+
+/// Is this the name of one of bitcoind's block files?
+fn is_blk_file_name(name: &str) -> bool {
+    name.starts_with("blk") && name.ends_with(".dat")
+}
+
+/// Name of a file, or "" if it has none we can read
+fn file_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("")
+}
+
+/// Read bitcoind's block file obfuscation key from the blocks directory, if it has one.
+/// bitcoind writes an all-zero key when it does not obfuscate its files.
+fn read_xor_key(blocks_path: &str) -> Result<Option<[u8; XOR_KEY_LEN]>, btc_error> {
+    let path = Path::new(blocks_path).join(XOR_KEY_FILE_NAME);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            return Ok(None);
+        }
+        Err(e) => {
+            return Err(btc_error::FilesystemError(e));
+        }
+    };
+    if bytes.len() != XOR_KEY_LEN {
+        warn!(
+            "Obfuscation key in {} is {} bytes, not {}",
+            path.display(),
+            bytes.len(),
+            XOR_KEY_LEN
+        );
+        return Err(btc_error::InvalidByteSequence);
+    }
+
+    let mut xor_key = [0u8; XOR_KEY_LEN];
+    xor_key.copy_from_slice(&bytes);
+    if xor_key == [0u8; XOR_KEY_LEN] {
+        return Ok(None);
+    }
+    Ok(Some(xor_key))
+}
+
+/// Undo bitcoind's obfuscation of bytes read from `offset` in a block file: each byte on disk
+/// is XORed with the key byte at its file offset modulo the key length.
+fn deobfuscate(bytes: &mut [u8], offset: u64, xor_key: &Option<[u8; XOR_KEY_LEN]>) {
+    if let Some(xor_key) = xor_key {
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte ^= xor_key[((offset + (i as u64)) % (XOR_KEY_LEN as u64)) as usize];
+        }
+    }
+}
+
+fn read_exact_at(fd: &mut fs::File, offset: u64, bytes: &mut [u8]) -> Result<(), btc_error> {
+    fd.seek(SeekFrom::Start(offset))
+        .map_err(btc_error::FilesystemError)?;
+    fd.read_exact(bytes).map_err(btc_error::FilesystemError)
+}
+
+fn read_header_at(
+    fd: &mut fs::File,
+    offset: u64,
+    xor_key: &Option<[u8; XOR_KEY_LEN]>,
+) -> Result<BlockHeader, btc_error> {
+    let mut bytes = [0u8; BITCOIN_BLOCK_HEADER_LEN as usize];
+    read_exact_at(fd, offset, &mut bytes)?;
+    deobfuscate(&mut bytes, offset, xor_key);
+    deserialize(&bytes).map_err(btc_error::SerializationError)
+}
+
+impl BlockFileIndex {
+    /// An index that holds only the network's genesis block
+    pub fn new(network_id: BitcoinNetworkType) -> BlockFileIndex {
+        let genesis_header = genesis_block(network_id_to_network(network_id)).header;
+        let genesis_hash = genesis_header.bitcoin_hash();
+        let genesis_work = genesis_header.work();
+
+        let mut headers = HashMap::new();
+        headers.insert(
+            genesis_hash,
+            IndexedHeader {
+                header: genesis_header,
+                height: 0,
+                work: genesis_work,
+            },
+        );
+
+        BlockFileIndex {
+            locations: HashMap::new(),
+            chain: vec![LoneBlockHeader {
+                header: genesis_header,
+                tx_count: VarInt(0),
+            }],
+            network_id,
+            headers,
+            orphans: HashMap::new(),
+            best_tip: genesis_hash,
+            best_work: genesis_work,
+            scanned: HashMap::new(),
+        }
+    }
+
+    /// Scan a bitcoind `blk*.dat` file from `start_offset`, and record where each block is.
+    /// bitcoind pre-allocates these files with zeros (which it does not obfuscate), so a zero
+    /// magic value marks the end of the data.  Returns the offset to resume scanning from once
+    /// bitcoind has written more blocks.
+    fn scan_blk_file(
+        path: &Path,
+        start_offset: u64,
+        magic: u32,
+        xor_key: &Option<[u8; XOR_KEY_LEN]>,
+        locations: &mut Vec<(BlockHeader, BlockFileLocation)>,
+    ) -> Result<u64, btc_error> {
+        let mut fd = fs::File::open(path).map_err(btc_error::FilesystemError)?;
+        let file_len = fd.metadata().map_err(btc_error::FilesystemError)?.len();
+        let mut offset = start_offset;
+        while offset < file_len {
+            let mut prefix = [0u8; 8];
+            let prefix_len = (file_len - offset).min(prefix.len() as u64) as usize;
+            read_exact_at(&mut fd, offset, &mut prefix[..prefix_len])?;
+            if prefix_len >= 4 && prefix[..4] == [0u8; 4] {
+                break;
+            }
+            if prefix_len < prefix.len() {
+                warn!(
+                    "Truncated block record at offset {} in {}",
+                    offset,
+                    path.display()
+                );
+                return Err(btc_error::InvalidByteSequence);
+            }
+            deobfuscate(&mut prefix, offset, xor_key);
+
+            let record_magic = u32::from_le_bytes([prefix[0], prefix[1], prefix[2], prefix[3]]);
+            if record_magic != magic {
+                warn!(
+                    "Invalid magic {:08x} at offset {} in {}",
+                    record_magic,
+                    offset,
+                    path.display()
+                );
+                return Err(btc_error::InvalidMagic);
+            }
+            let len = u32::from_le_bytes([prefix[4], prefix[5], prefix[6], prefix[7]]) as u64;
+            let block_offset = offset + 8;
+            if len < BITCOIN_BLOCK_HEADER_LEN || block_offset + len > file_len {
+                warn!(
+                    "Truncated block record at offset {} in {}",
+                    offset,
+                    path.display()
+                );
+                return Err(btc_error::InvalidByteSequence);
+            }
+
+            let header = read_header_at(&mut fd, block_offset, xor_key)?;
+            locations.push((
+                header,
+                BlockFileLocation {
+                    path: path.to_path_buf(),
+                    offset: block_offset,
+                    len,
+                    xor_key: *xor_key,
+                },
+            ));
+            offset = block_offset + len;
+        }
+        Ok(offset)
+    }
+
+    /// Scan a file that holds a single serialized block or a bare header.  Returns its length.
+    fn scan_block_file(
+        path: &Path,
+        locations: &mut Vec<(BlockHeader, BlockFileLocation)>,
+    ) -> Result<u64, btc_error> {
+        let mut fd = fs::File::open(path).map_err(btc_error::FilesystemError)?;
+        let len = fd.metadata().map_err(btc_error::FilesystemError)?.len();
+        if len < BITCOIN_BLOCK_HEADER_LEN {
+            warn!("File {} is too short to hold a block", path.display());
+            return Err(btc_error::InvalidByteSequence);
+        }
+        let header = read_header_at(&mut fd, 0, &None)?;
+        locations.push((
+            header,
+            BlockFileLocation {
+                path: path.to_path_buf(),
+                offset: 0,
+                len,
+                xor_key: None,
+            },
+        ));
+        Ok(len)
+    }
+
+    /// Build an index over all block files in the given directory.
+    pub fn scan(
+        blocks_path: &str,
+        network_id: BitcoinNetworkType,
+    ) -> Result<BlockFileIndex, btc_error> {
+        let mut index = BlockFileIndex::new(network_id);
+        index.update(blocks_path)?;
+        Ok(index)
+    }
+
+    /// Index whatever was added to the blocks directory since it was last scanned.
+    /// bitcoind only appends to its block files, so each `blk*.dat` file is read from where the
+    /// last scan stopped.  If the directory has any `blk*.dat` files, it is bitcoind's, and
+    /// nothing else in it holds blocks; otherwise each file not yet seen is read as a
+    /// serialized block or header.  Files are visited in lexicographic order, so the index is
+    /// deterministic.
+    pub fn update(&mut self, blocks_path: &str) -> Result<(), btc_error> {
+        let mut paths = vec![];
+        for entry in fs::read_dir(blocks_path).map_err(btc_error::FilesystemError)? {
+            let entry = entry.map_err(btc_error::FilesystemError)?;
+            if entry
+                .file_type()
+                .map_err(btc_error::FilesystemError)?
+                .is_file()
+            {
+                paths.push(entry.path());
+            }
+        }
+        paths.sort();
+
+        let is_bitcoind_dir = paths.iter().any(|path| is_blk_file_name(file_name(path)));
+        let xor_key = if is_bitcoind_dir {
+            read_xor_key(blocks_path)?
+        } else {
+            None
+        };
+
+        let magic = network_id_to_bytes(self.network_id);
+        let mut found = vec![];
+        let mut scanned = vec![];
+        for path in paths.into_iter() {
+            let last_offset = self.scanned.get(&path).cloned();
+            if is_blk_file_name(file_name(&path)) {
+                let next_offset = BlockFileIndex::scan_blk_file(
+                    &path,
+                    last_offset.unwrap_or(0),
+                    magic,
+                    &xor_key,
+                    &mut found,
+                )?;
+                scanned.push((path, next_offset));
+            } else if !is_bitcoind_dir && last_offset.is_none() {
+                let len = BlockFileIndex::scan_block_file(&path, &mut found)?;
+                scanned.push((path, len));
+            }
+        }
+
+        debug!(
+            "Found {} new blocks and headers in {}",
+            found.len(),
+            blocks_path
+        );
+        self.scanned.extend(scanned);
+        self.extend(found);
+        Ok(())
+    }
+
+    /// Build up the index from a list of headers and their locations.
+    pub fn from_locations(
+        found: Vec<(BlockHeader, BlockFileLocation)>,
+        network_id: BitcoinNetworkType,
+    ) -> BlockFileIndex {
+        let mut index = BlockFileIndex::new(network_id);
+        index.extend(found);
+        index
+    }
+
+    /// Add headers and their locations to the index, and move `chain` to the new best chain.
+    /// If the same block appears more than once, the copy with the body wins (and then the
+    /// first one seen).
+    pub fn extend(&mut self, found: Vec<(BlockHeader, BlockFileLocation)>) {
+        for (header, location) in found.into_iter() {
+            let block_hash = header.bitcoin_hash();
+            if let Some(existing) = self.locations.get(&block_hash) {
+                if !existing.has_block() && location.has_block() {
+                    self.locations.insert(block_hash, location);
+                }
+                continue;
+            }
+            self.locations.insert(block_hash, location);
+            self.connect_header(block_hash, header);
+        }
+        self.update_chain();
+    }
+
+    /// Attach a header to the block tree, along with any headers that were waiting on it, and
+    /// track the block with the most cumulative work.  Ties go to the block attached first.
+    fn connect_header(&mut self, block_hash: Sha256dHash, header: BlockHeader) {
+        if self.headers.contains_key(&block_hash) {
+            return;
+        }
+        if !self.headers.contains_key(&header.prev_blockhash) {
+            self.orphans
+                .entry(header.prev_blockhash)
+                .or_insert_with(|| vec![])
+                .push(header);
+            return;
+        }
+
+        let mut pending = vec![(block_hash, header)];
+        while let Some((block_hash, header)) = pending.pop() {
+            let (height, work) = {
+                let parent = &self.headers[&header.prev_blockhash];
+                (parent.height + 1, parent.work + header.work())
+            };
+            if work > self.best_work {
+                self.best_tip = block_hash;
+                self.best_work = work;
+            }
+            self.headers.insert(
+                block_hash,
+                IndexedHeader {
+                    header,
+                    height,
+                    work,
+                },
+            );
+            if let Some(children) = self.orphans.remove(&block_hash) {
+                for child in children.into_iter().rev() {
+                    pending.push((child.bitcoin_hash(), child));
+                }
+            }
+        }
+    }
+
+    /// Make `chain` end at the best tip, replacing only the headers above the fork point
+    fn update_chain(&mut self) {
+        let mut new_headers = vec![];
+        let mut cursor = self.best_tip;
+        loop {
+            let indexed = &self.headers[&cursor];
+            let height = indexed.height as usize;
+            if height < self.chain.len() && self.chain[height].header.bitcoin_hash() == cursor {
+                self.chain.truncate(height + 1);
+                break;
+            }
+            new_headers.push(indexed.header);
+            cursor = indexed.header.prev_blockhash;
+        }
+
+        for header in new_headers.into_iter().rev() {
+            self.chain.push(LoneBlockHeader {
+                header,
+                tx_count: VarInt(0),
+            });
+        }
+    }
+
+    /// Height of the best chain's tip
+    pub fn get_chain_height(&self) -> u64 {
+        (self.chain.len() as u64).saturating_sub(1)
+    }
+
+    /// Load up a block with the given hash
+    pub fn read_block(&self, block_hash: &Sha256dHash) -> Result<Option<Block>, btc_error> {
+        let location = match self.locations.get(block_hash) {
+            Some(location) if location.has_block() => location,
+            _ => {
+                return Ok(None);
+            }
+        };
+
+        let mut fd = fs::File::open(&location.path).map_err(btc_error::FilesystemError)?;
+        let mut bytes = vec![0u8; location.len as usize];
+        read_exact_at(&mut fd, location.offset, &mut bytes)?;
+        deobfuscate(&mut bytes, location.offset, &location.xor_key);
+
+        let block: Block = deserialize(&bytes).map_err(btc_error::SerializationError)?;
+        Ok(Some(block))
+    }
+}
+
+impl BitcoinBlockFileIndexer {
+    pub fn new(
+        config: BitcoinBlockFileIndexerConfig,
+        network_id: BitcoinNetworkType,
+    ) -> BitcoinBlockFileIndexer {
+        BitcoinBlockFileIndexer {
+            config,
+            network_id,
+            index: None,
+        }
+    }
+
+    /// Scan the blocks directory from scratch
+    pub fn rescan(&mut self) -> Result<Arc<BlockFileIndex>, btc_error> {
+        let index = Arc::new(BlockFileIndex::scan(
+            &self.config.blocks_path,
+            self.network_id,
+        )?);
+        self.index = Some(index.clone());
+        Ok(index)
+    }
+
+    /// Index whatever was added to the blocks directory since it was last scanned, or scan it
+    /// if we haven't done so yet
+    pub fn update_index(&mut self) -> Result<Arc<BlockFileIndex>, btc_error> {
+        match self.index {
+            Some(ref mut index) => {
+                Arc::make_mut(index).update(&self.config.blocks_path)?;
+                Ok(index.clone())
+            }
+            None => self.rescan(),
+        }
+    }
+
+    /// Get the block file index, scanning the blocks directory if we haven't done so yet
+    fn get_index(&mut self) -> Result<Arc<BlockFileIndex>, btc_error> {
+        match self.index {
+            Some(ref index) => Ok(index.clone()),
+            None => self.rescan(),
+        }
+    }
+
+    fn open_spv_client(&self, readwrite: bool) -> Result<SpvClient, btc_error> {
+        SpvClient::new(
+            &self.config.spv_headers_path,
+            0,
+            None,
+            self.network_id,
+            readwrite,
+            false,
+        )
+    }
+
+    /// Copy headers from the best chain in the block files into the SPV headers DB.
+    /// Headers are stored one difficulty interval at a time, so each interval's proof-of-work
+    /// gets checked just like it would for headers received from a peer.
+    /// Returns the height of the last header stored.
+    pub fn sync_file_headers(
+        &mut self,
+        start_block: u64,
+        end_block: Option<u64>,
+    ) -> Result<u64, btc_error> {
+        let index = self.get_index()?;
+        let mut spv_client = self.open_spv_client(true)?;
+
+        let chain_height = index.get_chain_height();
+        let last_block = match end_block {
+            Some(end_block) => end_block.min(chain_height),
+            None => chain_height,
+        };
+
+        let mut height = start_block;
+        while height < last_block {
+            let batch_end = (height + BLOCK_DIFFICULTY_CHUNK_SIZE).min(last_block);
+            let headers = index.chain[((height + 1) as usize)..=(batch_end as usize)].to_vec();
+            spv_client.handle_headers(height, headers)?;
+            height = batch_end;
+        }
+
+        debug!(
+            "Synced headers {}-{} from {}",
+            start_block, last_block, &self.config.blocks_path
+        );
+        spv_client.get_highest_header_height()
+    }
+}
+
+impl BurnchainIndexer for BitcoinBlockFileIndexer {
+    type P = BitcoinBlockFileParser;
+
+    /// "Connect" to the block files by indexing them.
+    fn connect(&mut self) -> Result<(), burnchain_error> {
+        self.update_index()
+            .map(|_| ())
+            .map_err(burnchain_error::Bitcoin)
+    }
+
+    fn get_first_block_height(&self) -> u64 {
+        self.config.first_block
+    }
+
+    fn get_first_block_header_hash(&self) -> Result<BurnchainHeaderHash, burnchain_error> {
+        let spv_client = self.open_spv_client(false)?;
+        let first_header = spv_client
+            .read_block_header(self.config.first_block)?
+            .expect("BUG: no first block header hash");
+
+        Ok(BurnchainHeaderHash::from_bitcoin_hash(
+            &first_header.header.bitcoin_hash(),
+        ))
+    }
+
+    fn get_first_block_header_timestamp(&self) -> Result<u64, burnchain_error> {
+        let spv_client = self.open_spv_client(false)?;
+        let first_header = spv_client
+            .read_block_header(self.config.first_block)?
+            .expect("BUG: no first block header timestamp");
+
+        Ok(first_header.header.time as u64)
+    }
+
+    /// Same rules as the BitcoinIndexer: use the configured epochs if given (and not on
+    /// mainnet), or the network's defaults.
+    fn get_stacks_epochs(&self) -> Vec<StacksEpoch> {
+        match self.config.epochs {
+            Some(ref epochs) => {
+                assert!(self.network_id != BitcoinNetworkType::Mainnet);
+                epochs.clone()
+            }
+            None => get_bitcoin_stacks_epochs(self.network_id),
+        }
+    }
+
+    fn get_headers_path(&self) -> String {
+        self.config.spv_headers_path.clone()
+    }
+
+    fn get_headers_height(&self) -> Result<u64, burnchain_error> {
+        self.open_spv_client(false)
+            .and_then(|spv_client| spv_client.get_headers_height())
+            .map_err(burnchain_error::Bitcoin)
+    }
+
+    fn get_highest_header_height(&self) -> Result<u64, burnchain_error> {
+        self.open_spv_client(false)
+            .and_then(|spv_client| spv_client.get_highest_header_height())
+            .map_err(burnchain_error::Bitcoin)
+    }
+
+    /// Find the highest header we have stored that is also on the best chain in the block
+    /// files.  Blocks written since the last scan are indexed first, so new blocks (e.g. a
+    /// longer fork) get picked up.
+    fn find_chain_reorg(&mut self) -> Result<u64, burnchain_error> {
+        let index = self.update_index().map_err(burnchain_error::Bitcoin)?;
+        let spv_client = self.open_spv_client(false)?;
+
+        let highest_header = spv_client.get_highest_header_height()?;
+        let mut height = highest_header.min(index.get_chain_height());
+        while height > 0 {
+            let batch_start = height.saturating_sub(BLOCK_DIFFICULTY_CHUNK_SIZE);
+            let headers = spv_client.read_block_headers(batch_start, height + 1)?;
+            for (i, header) in headers.iter().enumerate().rev() {
+                let header_height = batch_start + (i as u64);
+                if index.chain[header_height as usize].header == header.header {
+                    if header_height < highest_header {
+                        debug!(
+                            "Block files diverge from stored headers after height {}",
+                            header_height
+                        );
+                    }
+                    return Ok(header_height);
+                }
+            }
+            height = batch_start;
+        }
+        Ok(0)
+    }
+
+    fn sync_headers(
+        &mut self,
+        start_height: u64,
+        end_height: Option<u64>,
+    ) -> Result<u64, burnchain_error> {
+        if end_height.is_some() && end_height <= Some(start_height) {
+            return Ok(end_height.unwrap());
+        }
+
+        self.sync_file_headers(start_height, end_height)
+            .map_err(burnchain_error::Bitcoin)
+    }
+
+    fn drop_headers(&mut self, new_height: u64) -> Result<(), burnchain_error> {
+        self.open_spv_client(true)
+            .and_then(|mut spv_client| spv_client.drop_headers(new_height))
+            .map_err(burnchain_error::Bitcoin)
+    }
+
+    fn read_headers(
+        &self,
+        start_block: u64,
+        end_block: u64,
+    ) -> Result<Vec<BitcoinHeaderIPC>, burnchain_error> {
+        let spv_client = self.open_spv_client(false)?;
+        let headers = spv_client.read_block_headers(start_block, end_block)?;
+        Ok(headers
+            .into_iter()
+            .enumerate()
+            .map(|(i, block_header)| BitcoinHeaderIPC {
+                block_header,
+                block_height: start_block + (i as u64),
+            })
+            .collect())
+    }
+
+    fn downloader(&self) -> BitcoinBlockFileDownloader {
+        let index = self
+            .index
+            .clone()
+            .expect("BUG: block files have not been indexed");
+        BitcoinBlockFileDownloader { index }
+    }
+
+    fn parser(&self) -> BitcoinBlockFileParser {
+        BitcoinBlockFileParser {
+            parser: BitcoinBlockParser::new(self.network_id, self.config.magic_bytes),
+        }
+    }
+}
+
+impl BurnchainBlockDownloader for BitcoinBlockFileDownloader {
+    type H = BitcoinHeaderIPC;
+    type B = BitcoinBlockIPC;
+
+    /// "Download" a block by reading it from its block file
+    fn download(&mut self, header: &BitcoinHeaderIPC) -> Result<BitcoinBlockIPC, burnchain_error> {
+        let block_hash = header.block_header.header.bitcoin_hash();
+        let block = match self
+            .index
+            .read_block(&block_hash)
+            .map_err(burnchain_error::DownloadError)?
+        {
+            Some(block) => block,
+            None => {
+                warn!(
+                    "No block data for {} at height {}",
+                    &block_hash, header.block_height
+                );
+                return Err(burnchain_error::UnknownBlock(
+                    BurnchainHeaderHash::from_bitcoin_hash(&block_hash),
+                ));
+            }
+        };
+
+        if !BitcoinBlockParser::check_block(&block, &header.block_header) {
+            warn!(
+                "Block data for {} at height {} does not match its header",
+                &block_hash, header.block_height
+            );
+            return Err(burnchain_error::DownloadError(btc_error::InvalidReply));
+        }
+
+        Ok(BitcoinBlockIPC {
+            header_data: header.clone(),
+            block_message: NetworkMessage::Block(block),
+        })
+    }
+}
+
+impl BurnchainBlockParser for BitcoinBlockFileParser {
+    type D = BitcoinBlockFileDownloader;
+
+    fn parse(&mut self, ipc_block: &BitcoinBlockIPC) -> Result<BurnchainBlock, burnchain_error> {
+        match ipc_block.block_message {
+            NetworkMessage::Block(ref block) => {
+                match self.parser.process_block(
+                    block,
+                    &ipc_block.header_data.block_header,
+                    ipc_block.header_data.block_height,
+                ) {
+                    None => Err(burnchain_error::ParseError),
+                    Some(block_data) => Ok(BurnchainBlock::Bitcoin(block_data)),
+                }
+            }
+            _ => {
+                panic!("Did not receive a Block message"); // should never happen
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::burnchains::db::BurnchainDB;
+    use crate::burnchains::indexer::{BurnBlockIPC, BurnHeaderIPC};
+    use crate::burnchains::{Burnchain, BLOCKSTACK_MAGIC_MAINNET};
+    use crate::chainstate::coordinator::comm::CoordinatorCommunication;
+
+    use stacks_common::deps_common::bitcoin::blockdata::script::Script;
+    use stacks_common::deps_common::bitcoin::blockdata::transaction::{
+        OutPoint, Transaction, TxIn, TxOut,
+    };
+    use stacks_common::deps_common::bitcoin::network::serialize::serialize;
+    use stacks_common::deps_common::bitcoin::util::hash::MerkleRoot;
+
+    use std::io::Write;
+
+    fn setup_dir(name: &str) -> (String, String) {
+        let blocks_path = format!("/tmp/test-blockfile-indexer-{}", name);
+        let headers_path = format!("/tmp/test-blockfile-indexer-{}.headers", name);
+        if fs::metadata(&blocks_path).is_ok() {
+            fs::remove_dir_all(&blocks_path).unwrap();
+        }
+        if fs::metadata(&headers_path).is_ok() {
+            fs::remove_file(&headers_path).unwrap();
+        }
+        fs::create_dir_all(&blocks_path).unwrap();
+        (blocks_path, headers_path)
+    }
+
+    /// Make a regtest block on top of `parent`, with a coinbase tagged by `tag` so sibling
+    /// blocks get distinct hashes.  Grinds the nonce until the block meets the regtest target.
+    fn make_block(parent: &BlockHeader, height: u32, tag: u8) -> Block {
+        let coinbase = Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig: Script::from(vec![0x03, height as u8, (height >> 8) as u8, tag]),
+                sequence: 0xffffffff,
+                witness: vec![],
+            }],
+            output: vec![TxOut {
+                value: 5000000000,
+                script_pubkey: Script::from(vec![0x6a]),
+            }],
+        };
+        let txdata = vec![coinbase];
+        let mut header = BlockHeader {
+            version: 0x20000000,
+            prev_blockhash: parent.bitcoin_hash(),
+            merkle_root: txdata.merkle_root(),
+            time: parent.time + 1,
+            bits: parent.bits,
+            nonce: 0,
+        };
+        while header.spv_validate(&header.target()).is_err() {
+            header.nonce += 1;
+        }
+        Block { header, txdata }
+    }
+
+    fn make_chain(parent: &BlockHeader, start_height: u32, num: u32, tag: u8) -> Vec<Block> {
+        let mut blocks: Vec<Block> = vec![];
+        for i in 0..num {
+            let parent_header = blocks.last().map(|b| b.header.clone()).unwrap_or(*parent);
+            blocks.push(make_block(&parent_header, start_height + i, tag));
+        }
+        blocks
+    }
+
+    fn write_blk_file(path: &str, blocks: &[Block]) {
+        write_blk_file_at(path, 0, blocks, None);
+    }
+
+    /// Write `blocks` as block records at `offset` in the blk file at `path`, obfuscated with
+    /// `xor_key` if given.  Returns the offset just past them.
+    fn write_blk_file_at(
+        path: &str,
+        offset: u64,
+        blocks: &[Block],
+        xor_key: Option<[u8; XOR_KEY_LEN]>,
+    ) -> u64 {
+        let mut records = vec![];
+        for block in blocks.iter() {
+            let bytes = serialize(block).unwrap();
+            records
+                .extend_from_slice(&network_id_to_bytes(BitcoinNetworkType::Regtest).to_le_bytes());
+            records.extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+            records.extend_from_slice(&bytes);
+        }
+        // obfuscating is the same as de-obfuscating
+        deobfuscate(&mut records, offset, &xor_key);
+
+        let mut fd = fs::OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(path)
+            .unwrap();
+        fd.seek(SeekFrom::Start(offset)).unwrap();
+        fd.write_all(&records).unwrap();
+        // bitcoind pre-allocates block files with zeros
+        fd.write_all(&[0u8; 64]).unwrap();
+        offset + records.len() as u64
+    }
+
+    fn make_indexer(blocks_path: &str, headers_path: &str) -> BitcoinBlockFileIndexer {
+        BitcoinBlockFileIndexer::new(
+            BitcoinBlockFileIndexerConfig {
+                blocks_path: blocks_path.to_string(),
+                spv_headers_path: headers_path.to_string(),
+                first_block: 0,
+                magic_bytes: BLOCKSTACK_MAGIC_MAINNET.clone(),
+                epochs: None,
+            },
+            BitcoinNetworkType::Regtest,
+        )
+    }
+
+    #[test]
+    fn test_blockfile_index_blk_files() {
+        let (blocks_path, headers_path) = setup_dir("blk-files");
+        let genesis = genesis_block(Network::Regtest).header;
+
+        // main chain of 5 blocks, and a stale fork of 2 blocks off of block 2
+        let main_chain = make_chain(&genesis, 1, 5, 0);
+        let fork = make_chain(&main_chain[1].header, 3, 2, 1);
+
+        // write them out of order, across two files
+        let mut first_file = vec![main_chain[0].clone(), fork[0].clone()];
+        first_file.push(main_chain[2].clone());
+        write_blk_file(&format!("{}/blk00000.dat", &blocks_path), &first_file);
+        write_blk_file(
+            &format!("{}/blk00001.dat", &blocks_path),
+            &[
+                main_chain[1].clone(),
+                fork[1].clone(),
+                main_chain[3].clone(),
+                main_chain[4].clone(),
+            ],
+        );
+
+        let index = BlockFileIndex::scan(&blocks_path, BitcoinNetworkType::Regtest).unwrap();
+        assert_eq!(index.locations.len(), 7);
+        assert_eq!(index.get_chain_height(), 5);
+        assert_eq!(index.chain[0].header, genesis);
+        for (i, block) in main_chain.iter().enumerate() {
+            assert_eq!(index.chain[i + 1].header, block.header);
+            assert_eq!(
+                index.read_block(&block.bitcoin_hash()).unwrap().unwrap(),
+                *block
+            );
+        }
+
+        let mut indexer = make_indexer(&blocks_path, &headers_path);
+        indexer.connect().unwrap();
+        assert_eq!(indexer.sync_headers(0, None).unwrap(), 5);
+        assert_eq!(indexer.get_highest_header_height().unwrap(), 5);
+
+        let headers = indexer.read_headers(1, 6).unwrap();
+        assert_eq!(headers.len(), 5);
+
+        let mut downloader = indexer.downloader();
+        let mut parser = indexer.parser();
+        for (i, header) in headers.iter().enumerate() {
+            let ipc_block = downloader.download(header).unwrap();
+            assert_eq!(ipc_block.height(), (i as u64) + 1);
+            assert_eq!(
+                ipc_block.header().header_hash(),
+                main_chain[i].bitcoin_hash().0
+            );
+
+            let burnchain_block = parser.parse(&ipc_block).unwrap();
+            assert_eq!(burnchain_block.block_height(), (i as u64) + 1);
+            assert_eq!(
+                burnchain_block.block_hash(),
+                BurnchainHeaderHash::from_bitcoin_hash(&main_chain[i].bitcoin_hash())
+            );
+        }
+
+        // no reorg
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_blockfile_index_serialized_blocks_and_headers() {
+        let (blocks_path, headers_path) = setup_dir("serialized");
+        let genesis = genesis_block(Network::Regtest).header;
+        let chain = make_chain(&genesis, 1, 4, 0);
+
+        // first two are bare headers, last two are full blocks
+        for (i, block) in chain.iter().enumerate() {
+            let bytes = if i < 2 {
+                serialize(&block.header).unwrap()
+            } else {
+                serialize(block).unwrap()
+            };
+            let mut fd = fs::File::create(format!("{}/{:08}.bin", &blocks_path, i + 1)).unwrap();
+            fd.write_all(&bytes).unwrap();
+        }
+
+        let mut indexer = make_indexer(&blocks_path, &headers_path);
+        indexer.connect().unwrap();
+        assert_eq!(indexer.sync_headers(0, None).unwrap(), 4);
+
+        let headers = indexer.read_headers(1, 5).unwrap();
+        let mut downloader = indexer.downloader();
+
+        // headers-only entries can't be downloaded
+        match downloader.download(&headers[0]) {
+            Err(burnchain_error::UnknownBlock(bhh)) => {
+                assert_eq!(
+                    bhh,
+                    BurnchainHeaderHash::from_bitcoin_hash(&chain[0].bitcoin_hash())
+                );
+            }
+            _ => panic!("Expected UnknownBlock"),
+        }
+
+        for i in 2..4 {
+            let ipc_block = downloader.download(&headers[i]).unwrap();
+            assert_eq!(ipc_block.block(), NetworkMessage::Block(chain[i].clone()));
+        }
+    }
+
+    #[test]
+    fn test_blockfile_indexer_reorg() {
+        let (blocks_path, headers_path) = setup_dir("reorg");
+        let genesis = genesis_block(Network::Regtest).header;
+
+        let main_chain = make_chain(&genesis, 1, 4, 0);
+        write_blk_file(&format!("{}/blk00000.dat", &blocks_path), &main_chain);
+
+        let mut indexer = make_indexer(&blocks_path, &headers_path);
+        indexer.connect().unwrap();
+        assert_eq!(indexer.sync_headers(0, None).unwrap(), 4);
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 4);
+
+        // a longer fork off of block 2 shows up in a new file
+        let fork = make_chain(&main_chain[1].header, 3, 3, 1);
+        write_blk_file(&format!("{}/blk00001.dat", &blocks_path), &fork);
+
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 2);
+        indexer.drop_headers(2).unwrap();
+        assert_eq!(indexer.sync_headers(2, None).unwrap(), 5);
+
+        let headers = indexer.read_headers(3, 6).unwrap();
+        assert_eq!(headers.len(), 3);
+        for (i, header) in headers.iter().enumerate() {
+            assert_eq!(header.block_header.header, fork[i].header);
+        }
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 5);
+    }
+
+    #[test]
+    fn test_blockfile_indexer_sync_burnchain_db() {
+        let (blocks_path, headers_path) = setup_dir("sync-burnchain-db");
+        let working_dir = format!("{}.burnchain", &blocks_path);
+        if fs::metadata(&working_dir).is_ok() {
+            fs::remove_dir_all(&working_dir).unwrap();
+        }
+
+        let genesis = genesis_block(Network::Regtest).header;
+        let chain = make_chain(&genesis, 1, 7, 0);
+        write_blk_file(&format!("{}/blk00000.dat", &blocks_path), &chain[0..5]);
+
+        let mut indexer = make_indexer(&blocks_path, &headers_path);
+        let mut burnchain = Burnchain::regtest(&working_dir);
+        let (_coord_receivers, coord_comms) = CoordinatorCommunication::instantiate();
+
+        let tip = burnchain
+            .sync_with_indexer(&mut indexer, coord_comms.clone(), None, None, None)
+            .unwrap();
+        assert_eq!(tip.block_height, 5);
+        assert_eq!(
+            tip.block_hash,
+            BurnchainHeaderHash::from_bitcoin_hash(&chain[4].bitcoin_hash())
+        );
+
+        // blocks written since the last sync are picked up by the next one
+        write_blk_file(&format!("{}/blk00001.dat", &blocks_path), &chain[5..7]);
+        let tip = burnchain
+            .sync_with_indexer(&mut indexer, coord_comms, None, None, None)
+            .unwrap();
+        assert_eq!(tip.block_height, 7);
+
+        let burnchain_db = BurnchainDB::open(&burnchain.get_burnchaindb_path(), false).unwrap();
+        for (i, block) in chain.iter().enumerate() {
+            let block_hash = BurnchainHeaderHash::from_bitcoin_hash(&block.bitcoin_hash());
+            let stored = burnchain_db.get_burnchain_block(&block_hash).unwrap();
+            assert_eq!(stored.header.block_height, (i as u64) + 1);
+            assert_eq!(
+                stored.header.parent_block_hash,
+                BurnchainHeaderHash::from_bitcoin_hash(&block.header.prev_blockhash)
+            );
+        }
+        assert_eq!(
+            burnchain_db.get_canonical_chain_tip().unwrap().block_hash,
+            BurnchainHeaderHash::from_bitcoin_hash(&chain[6].bitcoin_hash())
+        );
+    }
+
+    #[test]
+    fn test_blockfile_index_bad_magic() {
+        let (blocks_path, _) = setup_dir("bad-magic");
+        let genesis = genesis_block(Network::Regtest).header;
+        let chain = make_chain(&genesis, 1, 1, 0);
+        write_blk_file(&format!("{}/blk00000.dat", &blocks_path), &chain);
+
+        match BlockFileIndex::scan(&blocks_path, BitcoinNetworkType::Mainnet) {
+            Err(btc_error::InvalidMagic) => {}
+            _ => panic!("Expected InvalidMagic"),
+        }
+    }
+
+    #[test]
+    fn test_blockfile_index_obfuscated_and_appended() {
+        let (blocks_path, headers_path) = setup_dir("obfuscated");
+        let genesis = genesis_block(Network::Regtest).header;
+        let chain = make_chain(&genesis, 1, 4, 0);
+
+        let xor_key = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+        fs::write(format!("{}/{}", &blocks_path, XOR_KEY_FILE_NAME), &xor_key).unwrap();
+
+        let blk_path = format!("{}/blk00000.dat", &blocks_path);
+        let next_offset = write_blk_file_at(&blk_path, 0, &chain[0..2], Some(xor_key));
+
+        let mut indexer = make_indexer(&blocks_path, &headers_path);
+        indexer.connect().unwrap();
+        assert_eq!(indexer.sync_headers(0, None).unwrap(), 2);
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 2);
+
+        // bitcoind writes the next blocks over the zeros it pre-allocated
+        write_blk_file_at(&blk_path, next_offset, &chain[2..4], Some(xor_key));
+        assert_eq!(indexer.find_chain_reorg().unwrap(), 2);
+
+        // only the new records were read
+        let index = indexer.get_index().unwrap();
+        assert_eq!(index.get_chain_height(), 4);
+        assert!(index.scanned[&PathBuf::from(&blk_path)] > next_offset);
+        assert_eq!(indexer.sync_headers(2, None).unwrap(), 4);
+
+        let headers = indexer.read_headers(1, 5).unwrap();
+        let mut downloader = indexer.downloader();
+        for (i, header) in headers.iter().enumerate() {
+            let ipc_block = downloader.download(header).unwrap();
+            assert_eq!(ipc_block.block(), NetworkMessage::Block(chain[i].clone()));
+        }
+    }
+
+    #[test]
+    fn test_blockfile_index_ignores_other_bitcoind_files() {
+        let (blocks_path, _) = setup_dir("other-bitcoind-files");
+        let genesis = genesis_block(Network::Regtest).header;
+        let chain = make_chain(&genesis, 1, 2, 0);
+        write_blk_file(&format!("{}/blk00000.dat", &blocks_path), &chain);
+
+        // undo data and an all-zero (i.e. disabled) obfuscation key
+        fs::write(format!("{}/rev00000.dat", &blocks_path), &[0xffu8; 200]).unwrap();
+        fs::write(
+            format!("{}/{}", &blocks_path, XOR_KEY_FILE_NAME),
+            &[0u8; XOR_KEY_LEN],
+        )
+        .unwrap();
+        fs::create_dir_all(format!("{}/index", &blocks_path)).unwrap();
+
+        let index = BlockFileIndex::scan(&blocks_path, BitcoinNetworkType::Regtest).unwrap();
+        assert_eq!(index.locations.len(), 2);
+        assert_eq!(index.get_chain_height(), 2);
+        for (i, block) in chain.iter().enumerate() {
+            assert_eq!(index.chain[i + 1].header, block.header);
+        }
+    }
+}
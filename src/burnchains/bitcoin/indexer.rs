@@ -91,9 +91,9 @@ impl TryFrom<u32> for BitcoinNetworkType {
 }
 
 /// Get the default epochs definitions for the given BitcoinNetworkType.
-/// Should *not* be used except by the BitcoinIndexer when no epochs vector
+/// Should *not* be used except by the Bitcoin indexers when no epochs vector
 /// was specified.
-fn get_bitcoin_stacks_epochs(network_id: BitcoinNetworkType) -> Vec<StacksEpoch> {
+pub(crate) fn get_bitcoin_stacks_epochs(network_id: BitcoinNetworkType) -> Vec<StacksEpoch> {
     match network_id {
         BitcoinNetworkType::Mainnet => STACKS_EPOCHS_MAINNET.to_vec(),
         BitcoinNetworkType::Testnet => STACKS_EPOCHS_TESTNET.to_vec(),
@@ -35,6 +35,7 @@ use crate::types::chainstate::BurnchainHeaderHash;
 
 pub mod address;
 pub mod bits;
+pub mod blockfile;
 pub mod blocks;
 pub mod indexer;
 pub mod keys;
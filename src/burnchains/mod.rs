@@ -1021,6 +1021,8 @@ pub mod test {
                 .expect(&format!("BUG: no epoch for height {}", &txop.block_height));
             if epoch.epoch_id == StacksEpochId::Epoch2_05 {
                 txop.memo = vec![STACKS_EPOCH_2_05_MARKER];
+            } else if epoch.epoch_id == StacksEpochId::Epoch21 {
+                txop.memo = vec![STACKS_EPOCH_2_1_MARKER];
             }
 
             self.txs
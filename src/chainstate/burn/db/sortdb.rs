@@ -1596,6 +1596,33 @@ impl<'a> SortitionHandleConn<'a> {
         SortitionDB::get_ancestor_snapshot(self, block_height, &self.context.chain_tip)
     }
 
+    /// Get the PoX reward addresses paid by the block-commits in the given sortition, and the
+    ///  total amount paid to each of them.  Each block-commit splits its burn fee evenly across
+    ///  its outputs, and every valid block-commit in a sortition pays the same outputs.
+    /// Returns no addresses and a zero payout if the sortition's block-commits paid no outputs.
+    pub fn get_reward_set_payouts_at(
+        &self,
+        sortition_id: &SortitionId,
+    ) -> Result<(Vec<StacksAddress>, u128), db_error> {
+        let block_commits = SortitionDB::get_block_commits_by_block(self.conn(), sortition_id)?;
+        let mut pox_addrs = vec![];
+        let mut payout: u128 = 0;
+        for block_commit in block_commits.into_iter() {
+            if block_commit.commit_outs.is_empty() {
+                continue;
+            }
+            let per_output_payout =
+                (block_commit.burn_fee as u128) / (block_commit.commit_outs.len() as u128);
+            payout = payout
+                .checked_add(per_output_payout)
+                .ok_or(db_error::Overflow)?;
+            if pox_addrs.is_empty() {
+                pox_addrs = block_commit.commit_outs;
+            }
+        }
+        Ok((pox_addrs, payout))
+    }
+
     /// Get all user burns that burned for the winning block in the chain_tip sortition
     /// Returns list of user burns in order by vtxindex.
     pub fn get_winning_user_burns_by_block(&self) -> Result<Vec<UserBurnSupportOp>, db_error> {
@@ -2463,6 +2490,7 @@ impl SortitionDB {
             StacksEpochId::Epoch10 => false,
             StacksEpochId::Epoch20 => (version == "1" || version == "2" || version == "3"),
             StacksEpochId::Epoch2_05 => (version == "2" || version == "3"),
+            StacksEpochId::Epoch21 => (version == "2" || version == "3"),
         }
     }
 
@@ -35,7 +35,7 @@ use crate::chainstate::burn::SortitionId;
 use crate::chainstate::stacks::index::storage::TrieFileStorage;
 use crate::chainstate::stacks::{StacksPrivateKey, StacksPublicKey};
 use crate::codec::{write_next, Error as codec_error, StacksMessageCodec};
-use crate::core::STACKS_EPOCH_2_05_MARKER;
+use crate::core::{STACKS_EPOCH_2_05_MARKER, STACKS_EPOCH_2_1_MARKER};
 use crate::core::{StacksEpoch, StacksEpochId};
 use crate::net::Error as net_error;
 use crate::types::chainstate::TrieHash;
@@ -850,6 +850,24 @@ impl LeaderBlockCommitOp {
                     return Err(op_error::BlockCommitBadEpoch);
                 }
             }
+            StacksEpochId::Epoch21 => {
+                if self.memo.len() < 1 {
+                    debug!(
+                        "Invalid block commit";
+                        "reason" => "no epoch marker byte given",
+                    );
+                    return Err(op_error::BlockCommitBadEpoch);
+                }
+                if self.memo[0] < STACKS_EPOCH_2_1_MARKER {
+                    debug!(
+                        "Invalid block commit";
+                        "reason" => "invalid epoch marker byte",
+                        "marker_byte" => self.memo[0],
+                        "expected_marker_byte" => STACKS_EPOCH_2_1_MARKER
+                    );
+                    return Err(op_error::BlockCommitBadEpoch);
+                }
+            }
         }
 
         // good to go!
@@ -62,8 +62,10 @@ use clarity::vm::database::BurnStateDB;
 use crate::chainstate::stacks::index::marf::MARFOpenOpts;
 
 pub use self::comm::CoordinatorCommunication;
+pub use self::reorg::ChainReorg;
 
 pub mod comm;
+pub mod reorg;
 #[cfg(test)]
 pub mod tests;
 
@@ -145,6 +147,11 @@ pub trait BlockEventDispatcher {
     );
 
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>);
+
+    /// called whenever processing new burnchain or Stacks blocks
+    ///  moved the canonical chain tip onto a different fork,
+    ///  rolling back burnchain blocks, Stacks blocks or microblocks.
+    fn announce_reorg(&self, reorg: &ChainReorg);
 }
 
 pub struct ChainsCoordinator<
@@ -210,6 +217,24 @@ pub trait RewardSetProvider {
         sortdb: &SortitionDB,
         block_id: &StacksBlockId,
     ) -> Result<Vec<StacksAddress>, Error>;
+
+    /// Is the given PoX anchor block known to this node?  Full nodes know an anchor block once
+    /// they have processed it; providers that do not process Stacks blocks (e.g. light clients)
+    /// can override this.
+    fn is_anchor_block_known(
+        &self,
+        chainstate: &StacksChainState,
+        _sortdb: &SortitionDB,
+        consensus_hash: &ConsensusHash,
+        stacks_block_hash: &BlockHeaderHash,
+    ) -> Result<bool, Error> {
+        let known = StacksChainState::is_stacks_block_processed(
+            &chainstate.db(),
+            consensus_hash,
+            stacks_block_hash,
+        )?;
+        Ok(known)
+    }
 }
 
 pub struct OnChainRewardSetProvider();
@@ -228,36 +253,50 @@ impl RewardSetProvider for OnChainRewardSetProvider {
 
         let liquid_ustx = chainstate.get_liquid_ustx(block_id);
 
-        let (threshold, participation) = StacksChainState::get_reward_threshold_and_participation(
-            &burnchain.pox_constants,
-            &registered_addrs,
+        Ok(make_reward_set_from_registrations(
+            burnchain,
+            current_burn_height,
+            registered_addrs,
             liquid_ustx,
-        );
+        ))
+    }
+}
 
-        if !burnchain
-            .pox_constants
-            .enough_participation(participation, liquid_ustx)
-        {
-            info!("PoX reward cycle did not have enough participation. Defaulting to burn";
-                  "burn_height" => current_burn_height,
-                  "participation" => participation,
-                  "liquid_ustx" => liquid_ustx,
-                  "registered_addrs" => registered_addrs.len());
-            return Ok(vec![]);
-        } else {
-            info!("PoX reward cycle threshold computed";
-                  "burn_height" => current_burn_height,
-                  "threshold" => threshold,
-                  "participation" => participation,
-                  "liquid_ustx" => liquid_ustx,
-                  "registered_addrs" => registered_addrs.len());
-        }
+/// Compute the reward set for the reward cycle starting at `current_burn_height`, given the PoX
+/// addresses registered for it and the liquid uSTX supply.  The reward set is empty if there
+/// was not enough participation.
+pub fn make_reward_set_from_registrations(
+    burnchain: &Burnchain,
+    current_burn_height: u64,
+    registered_addrs: Vec<(StacksAddress, u128)>,
+    liquid_ustx: u128,
+) -> Vec<StacksAddress> {
+    let (threshold, participation) = StacksChainState::get_reward_threshold_and_participation(
+        &burnchain.pox_constants,
+        &registered_addrs,
+        liquid_ustx,
+    );
 
-        Ok(StacksChainState::make_reward_set(
-            threshold,
-            registered_addrs,
-        ))
+    if !burnchain
+        .pox_constants
+        .enough_participation(participation, liquid_ustx)
+    {
+        info!("PoX reward cycle did not have enough participation. Defaulting to burn";
+              "burn_height" => current_burn_height,
+              "participation" => participation,
+              "liquid_ustx" => liquid_ustx,
+              "registered_addrs" => registered_addrs.len());
+        return vec![];
+    } else {
+        info!("PoX reward cycle threshold computed";
+              "burn_height" => current_burn_height,
+              "threshold" => threshold,
+              "participation" => participation,
+              "liquid_ustx" => liquid_ustx,
+              "registered_addrs" => registered_addrs.len());
     }
+
+    StacksChainState::make_reward_set(threshold, registered_addrs)
 }
 
 impl<'a, T: BlockEventDispatcher, CE: CostEstimator + ?Sized, FE: FeeEstimator + ?Sized>
@@ -274,6 +313,42 @@ impl<'a, T: BlockEventDispatcher, CE: CostEstimator + ?Sized, FE: FeeEstimator +
         fee_estimator: Option<&mut FE>,
     ) where
         T: BlockEventDispatcher,
+    {
+        ChainsCoordinator::run_with_reward_set_provider(
+            chain_state_db,
+            burnchain,
+            attachments_tx,
+            dispatcher,
+            comms,
+            atlas_config,
+            cost_estimator,
+            fee_estimator,
+            OnChainRewardSetProvider(),
+        )
+    }
+}
+
+impl<
+        'a,
+        T: BlockEventDispatcher,
+        U: RewardSetProvider,
+        CE: CostEstimator + ?Sized,
+        FE: FeeEstimator + ?Sized,
+    > ChainsCoordinator<'a, T, ArcCounterCoordinatorNotices, U, CE, FE>
+{
+    /// Run the coordinator, using the given reward set provider to compute PoX reward sets
+    pub fn run_with_reward_set_provider(
+        chain_state_db: StacksChainState,
+        burnchain: Burnchain,
+        attachments_tx: SyncSender<HashSet<AttachmentInstance>>,
+        dispatcher: &'a mut T,
+        comms: CoordinatorReceivers,
+        atlas_config: AtlasConfig,
+        cost_estimator: Option<&mut CE>,
+        fee_estimator: Option<&mut FE>,
+        reward_set_provider: U,
+    ) where
+        T: BlockEventDispatcher,
     {
         let stacks_blocks_processed = comms.stacks_blocks_processed.clone();
         let sortitions_processed = comms.sortitions_processed.clone();
@@ -301,7 +376,7 @@ impl<'a, T: BlockEventDispatcher, CE: CostEstimator + ?Sized, FE: FeeEstimator +
             attachments_tx,
             dispatcher: Some(dispatcher),
             notifier: arc_notices,
-            reward_set_provider: OnChainRewardSetProvider(),
+            reward_set_provider,
             cost_estimator,
             fee_estimator,
             atlas_config,
@@ -450,8 +525,9 @@ pub fn get_reward_cycle_info<U: RewardSetProvider>(
         }?;
         if let Some((consensus_hash, stacks_block_hash)) = reward_cycle_info {
             info!("Anchor block selected: {}", stacks_block_hash);
-            let anchor_block_known = StacksChainState::is_stacks_block_processed(
-                &chain_state.db(),
+            let anchor_block_known = provider.is_anchor_block_known(
+                chain_state,
+                sort_db,
                 &consensus_hash,
                 &stacks_block_hash,
             )?;
@@ -547,6 +623,80 @@ impl<
     > ChainsCoordinator<'a, T, N, U, CE, FE>
 {
     pub fn handle_new_stacks_block(&mut self) -> Result<(), Error> {
+        let prior_tip = self.get_reorg_base();
+        let result = self.inner_handle_new_stacks_block();
+        self.announce_reorg_since(prior_tip);
+        result
+    }
+
+    pub fn handle_new_burnchain_block(&mut self) -> Result<(), Error> {
+        let prior_tip = self.get_reorg_base();
+        let result = self.inner_handle_new_burnchain_block();
+        self.announce_reorg_since(prior_tip);
+        result
+    }
+
+    /// If there is a dispatcher to tell about reorgs, load the current canonical sortition so
+    /// that it can later be compared against the new one.  This must happen before processing,
+    /// since the sortition DB moves a snapshot's canonical Stacks tip in place.
+    fn get_reorg_base(&self) -> Option<BlockSnapshot> {
+        if self.dispatcher.is_none() {
+            return None;
+        }
+        let sortition_id = self.canonical_sortition_tip.as_ref()?;
+        match SortitionDB::get_block_snapshot(self.sortition_db.conn(), sortition_id) {
+            Ok(sn_opt) => sn_opt,
+            Err(e) => {
+                warn!(
+                    "Failed to load canonical sortition {}: {:?}",
+                    sortition_id, &e
+                );
+                None
+            }
+        }
+    }
+
+    /// Tell the dispatcher if the canonical chain tip has moved to a different fork since
+    /// `prior_tip` was loaded.
+    fn announce_reorg_since(&self, prior_tip: Option<BlockSnapshot>) {
+        let (dispatcher, prior_tip) = match (self.dispatcher, prior_tip) {
+            (Some(dispatcher), Some(prior_tip)) => (dispatcher, prior_tip),
+            _ => {
+                return;
+            }
+        };
+        let new_tip = match self.get_reorg_base() {
+            Some(new_tip) => new_tip,
+            None => {
+                return;
+            }
+        };
+
+        match ChainReorg::find(
+            &self.chain_state_db,
+            &self.sortition_db,
+            &prior_tip,
+            &new_tip,
+        ) {
+            Ok(Some(reorg)) => {
+                info!("Canonical chain tip moved to a different fork";
+                      "old_stacks_tip" => %reorg.old_stacks_tip,
+                      "new_stacks_tip" => %reorg.new_stacks_tip,
+                      "old_burn_tip" => %reorg.old_burn_tip,
+                      "new_burn_tip" => %reorg.new_burn_tip,
+                      "rolled_back_burn_blocks" => reorg.rolled_back_burn_blocks.len(),
+                      "rolled_back_blocks" => reorg.rolled_back_blocks.len(),
+                      "rolled_back_microblocks" => reorg.rolled_back_microblocks.len());
+                dispatcher.announce_reorg(&reorg);
+            }
+            Ok(None) => {}
+            Err(e) => {
+                warn!("Failed to check for a chain reorg: {:?}", &e);
+            }
+        }
+    }
+
+    fn inner_handle_new_stacks_block(&mut self) -> Result<(), Error> {
         if let Some(pox_anchor) = self.process_ready_blocks()? {
             self.process_new_pox_anchor(pox_anchor)
         } else {
@@ -554,7 +704,7 @@ impl<
         }
     }
 
-    pub fn handle_new_burnchain_block(&mut self) -> Result<(), Error> {
+    fn inner_handle_new_burnchain_block(&mut self) -> Result<(), Error> {
         // Retrieve canonical burnchain chain tip from the BurnchainBlocksDB
         let canonical_burnchain_tip = self.burnchain_blocks_db.get_canonical_chain_tip()?;
         debug!("Handle new canonical burnchain tip";
@@ -881,7 +1031,7 @@ impl<
         self.canonical_pox_id = Some(pox_id);
 
         // Start processing from the beginning of the new PoX reward set
-        self.handle_new_burnchain_block()
+        self.inner_handle_new_burnchain_block()
     }
 }
 
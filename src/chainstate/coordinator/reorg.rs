@@ -0,0 +1,370 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+use std::collections::HashSet;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::{BlockSnapshot, ConsensusHash};
+use crate::chainstate::coordinator::Error;
+use crate::chainstate::stacks::db::{StacksChainState, StacksHeaderInfo};
+use crate::chainstate::stacks::StacksBlockHeader;
+use crate::core::EMPTY_MICROBLOCK_PARENT_HASH;
+use crate::util_lib::db::Error as DBError;
+
+use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, StacksBlockId};
+
+/// A Stacks block that left or joined the canonical Stacks fork
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorgStacksBlock {
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub consensus_hash: ConsensusHash,
+    pub block_height: u64,
+}
+
+/// A microblock that left or joined the canonical Stacks fork
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorgMicroblock {
+    pub parent_index_block_hash: StacksBlockId,
+    pub microblock_hash: BlockHeaderHash,
+    pub sequence: u16,
+}
+
+/// A burnchain block whose sortition left or joined the canonical sortition fork
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReorgBurnBlock {
+    pub burn_header_hash: BurnchainHeaderHash,
+    pub block_height: u64,
+    pub consensus_hash: ConsensusHash,
+}
+
+/// A move of the canonical chain tip that rolled back at least one burnchain block, Stacks
+/// block, or microblock.  Rolled-back items are listed from the old tip down to the fork point,
+/// and applied items from the fork point up to the new tip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainReorg {
+    pub old_stacks_tip: StacksBlockId,
+    pub new_stacks_tip: StacksBlockId,
+    pub old_burn_tip: BurnchainHeaderHash,
+    pub new_burn_tip: BurnchainHeaderHash,
+    pub rolled_back_burn_blocks: Vec<ReorgBurnBlock>,
+    pub applied_burn_blocks: Vec<ReorgBurnBlock>,
+    pub rolled_back_blocks: Vec<ReorgStacksBlock>,
+    pub applied_blocks: Vec<ReorgStacksBlock>,
+    /// Microblocks confirmed by the rolled-back blocks, plus the old tip's unconfirmed stream,
+    /// that the new fork does not confirm
+    pub rolled_back_microblocks: Vec<ReorgMicroblock>,
+    /// Microblocks confirmed by the applied blocks that the old fork had not seen
+    pub applied_microblocks: Vec<ReorgMicroblock>,
+}
+
+impl ReorgStacksBlock {
+    fn from_header_info(header_info: &StacksHeaderInfo) -> ReorgStacksBlock {
+        ReorgStacksBlock {
+            index_block_hash: header_info.index_block_hash(),
+            block_hash: header_info.anchored_header.block_hash(),
+            consensus_hash: header_info.consensus_hash.clone(),
+            block_height: header_info.stacks_block_height,
+        }
+    }
+}
+
+impl ReorgBurnBlock {
+    fn from_snapshot(sn: &BlockSnapshot) -> ReorgBurnBlock {
+        ReorgBurnBlock {
+            burn_header_hash: sn.burn_header_hash.clone(),
+            block_height: sn.block_height,
+            consensus_hash: sn.consensus_hash.clone(),
+        }
+    }
+}
+
+impl ChainReorg {
+    /// Work out what changed between the canonical tip in `old_sn` and the one in `new_sn`.
+    /// `old_sn` must have been loaded before the new tip was processed, since the sortition DB
+    /// updates a snapshot's Stacks tip in place.  Returns None if nothing was rolled back.
+    pub fn find(
+        chainstate: &StacksChainState,
+        sortdb: &SortitionDB,
+        old_sn: &BlockSnapshot,
+        new_sn: &BlockSnapshot,
+    ) -> Result<Option<ChainReorg>, Error> {
+        let (rolled_back_burn_blocks, applied_burn_blocks) =
+            if old_sn.sortition_id != new_sn.sortition_id {
+                ChainReorg::find_sortition_fork(sortdb, old_sn, new_sn)?
+            } else {
+                (vec![], vec![])
+            };
+
+        let old_stacks_tip = StacksBlockHeader::make_index_block_hash(
+            &old_sn.canonical_stacks_tip_consensus_hash,
+            &old_sn.canonical_stacks_tip_hash,
+        );
+        let new_stacks_tip = StacksBlockHeader::make_index_block_hash(
+            &new_sn.canonical_stacks_tip_consensus_hash,
+            &new_sn.canonical_stacks_tip_hash,
+        );
+
+        let (rolled_back_headers, applied_headers, fork_point) = if old_stacks_tip != new_stacks_tip
+        {
+            ChainReorg::find_stacks_fork(chainstate, &old_stacks_tip, &new_stacks_tip)?
+        } else {
+            (vec![], vec![], None)
+        };
+
+        let (rolled_back_microblocks, applied_microblocks) = match fork_point {
+            Some(fork_point) => ChainReorg::find_microblock_changes(
+                chainstate,
+                &old_stacks_tip,
+                &fork_point,
+                &rolled_back_headers,
+                &applied_headers,
+            )?,
+            None => (vec![], vec![]),
+        };
+
+        if rolled_back_burn_blocks.is_empty()
+            && rolled_back_headers.is_empty()
+            && rolled_back_microblocks.is_empty()
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(ChainReorg {
+            old_stacks_tip,
+            new_stacks_tip,
+            old_burn_tip: old_sn.burn_header_hash.clone(),
+            new_burn_tip: new_sn.burn_header_hash.clone(),
+            rolled_back_burn_blocks,
+            applied_burn_blocks,
+            rolled_back_blocks: rolled_back_headers
+                .iter()
+                .map(ReorgStacksBlock::from_header_info)
+                .collect(),
+            applied_blocks: applied_headers
+                .iter()
+                .map(ReorgStacksBlock::from_header_info)
+                .collect(),
+            rolled_back_microblocks,
+            applied_microblocks,
+        }))
+    }
+
+    /// Walk both sortition tips back to their common ancestor.  Returns the sortitions only
+    /// on the old fork (highest first) and only on the new fork (lowest first).
+    fn find_sortition_fork(
+        sortdb: &SortitionDB,
+        old_sn: &BlockSnapshot,
+        new_sn: &BlockSnapshot,
+    ) -> Result<(Vec<ReorgBurnBlock>, Vec<ReorgBurnBlock>), Error> {
+        let load_parent = |sn: &BlockSnapshot| -> Result<BlockSnapshot, Error> {
+            SortitionDB::get_block_snapshot(sortdb.conn(), &sn.parent_sortition_id)?
+                .ok_or(Error::DBError(DBError::NotFoundError))
+        };
+
+        let mut rolled_back = vec![];
+        let mut applied = vec![];
+        let mut old_cursor = old_sn.clone();
+        let mut new_cursor = new_sn.clone();
+
+        while old_cursor.block_height > new_cursor.block_height {
+            rolled_back.push(ReorgBurnBlock::from_snapshot(&old_cursor));
+            old_cursor = load_parent(&old_cursor)?;
+        }
+        while new_cursor.block_height > old_cursor.block_height {
+            applied.push(ReorgBurnBlock::from_snapshot(&new_cursor));
+            new_cursor = load_parent(&new_cursor)?;
+        }
+        while old_cursor.sortition_id != new_cursor.sortition_id {
+            rolled_back.push(ReorgBurnBlock::from_snapshot(&old_cursor));
+            applied.push(ReorgBurnBlock::from_snapshot(&new_cursor));
+            old_cursor = load_parent(&old_cursor)?;
+            new_cursor = load_parent(&new_cursor)?;
+        }
+
+        if rolled_back.is_empty() {
+            // the new tip simply extends the old one
+            return Ok((vec![], vec![]));
+        }
+
+        applied.reverse();
+        Ok((rolled_back, applied))
+    }
+
+    /// Walk both Stacks tips back to their common ancestor.  Returns the headers only on the old
+    /// fork (highest first), the headers only on the new fork (lowest first), and the common
+    /// ancestor.  Returns no fork point if either tip's header is not stored.
+    fn find_stacks_fork(
+        chainstate: &StacksChainState,
+        old_tip: &StacksBlockId,
+        new_tip: &StacksBlockId,
+    ) -> Result<
+        (
+            Vec<StacksHeaderInfo>,
+            Vec<StacksHeaderInfo>,
+            Option<StacksHeaderInfo>,
+        ),
+        Error,
+    > {
+        let load_header = |block_id: &StacksBlockId| -> Result<Option<StacksHeaderInfo>, Error> {
+            Ok(
+                StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    chainstate.db(),
+                    block_id,
+                )?,
+            )
+        };
+        let load_parent = |header_info: &StacksHeaderInfo| -> Result<StacksHeaderInfo, Error> {
+            let parent_id = StacksChainState::get_parent_block_id(
+                chainstate.db(),
+                &header_info.index_block_hash(),
+            )?
+            .ok_or(Error::DBError(DBError::NotFoundError))?;
+            load_header(&parent_id)?.ok_or(Error::DBError(DBError::NotFoundError))
+        };
+
+        let (mut old_cursor, mut new_cursor) = match (load_header(old_tip)?, load_header(new_tip)?)
+        {
+            (Some(old_header), Some(new_header)) => (old_header, new_header),
+            _ => {
+                debug!(
+                    "Not checking for a Stacks reorg from {} to {}: header is not stored",
+                    old_tip, new_tip
+                );
+                return Ok((vec![], vec![], None));
+            }
+        };
+
+        let mut rolled_back = vec![];
+        let mut applied = vec![];
+
+        while old_cursor.stacks_block_height > new_cursor.stacks_block_height {
+            let parent = load_parent(&old_cursor)?;
+            rolled_back.push(old_cursor);
+            old_cursor = parent;
+        }
+        while new_cursor.stacks_block_height > old_cursor.stacks_block_height {
+            let parent = load_parent(&new_cursor)?;
+            applied.push(new_cursor);
+            new_cursor = parent;
+        }
+        while old_cursor.index_block_hash() != new_cursor.index_block_hash() {
+            let old_parent = load_parent(&old_cursor)?;
+            let new_parent = load_parent(&new_cursor)?;
+            rolled_back.push(old_cursor);
+            applied.push(new_cursor);
+            old_cursor = old_parent;
+            new_cursor = new_parent;
+        }
+
+        applied.reverse();
+        Ok((rolled_back, applied, Some(old_cursor)))
+    }
+
+    /// The microblocks that `child` confirms from `parent`'s stream
+    fn load_confirmed_microblocks(
+        chainstate: &StacksChainState,
+        parent: &StacksHeaderInfo,
+        child: &StacksHeaderInfo,
+    ) -> Result<Vec<ReorgMicroblock>, Error> {
+        let header = &child.anchored_header;
+        if header.parent_microblock == EMPTY_MICROBLOCK_PARENT_HASH
+            && header.parent_microblock_sequence == 0
+        {
+            return Ok(vec![]);
+        }
+
+        let parent_index_block_hash = parent.index_block_hash();
+        let microblocks = StacksChainState::load_microblock_stream_fork(
+            chainstate.db(),
+            &parent.consensus_hash,
+            &parent.anchored_header.block_hash(),
+            &header.parent_microblock,
+        )?
+        .unwrap_or(vec![]);
+
+        Ok(microblocks
+            .iter()
+            .map(|microblock| ReorgMicroblock {
+                parent_index_block_hash: parent_index_block_hash.clone(),
+                microblock_hash: microblock.block_hash(),
+                sequence: microblock.header.sequence,
+            })
+            .collect())
+    }
+
+    /// The old fork's microblocks are those confirmed by the rolled-back blocks plus the old
+    /// tip's unconfirmed stream; the new fork's are those confirmed by the applied blocks.
+    /// Returns the microblocks only in the old fork, and those only in the new fork.
+    fn find_microblock_changes(
+        chainstate: &StacksChainState,
+        old_tip: &StacksBlockId,
+        fork_point: &StacksHeaderInfo,
+        rolled_back: &[StacksHeaderInfo],
+        applied: &[StacksHeaderInfo],
+    ) -> Result<(Vec<ReorgMicroblock>, Vec<ReorgMicroblock>), Error> {
+        let mut old_microblocks = vec![];
+        if let Some(unconfirmed) = StacksChainState::load_descendant_staging_microblock_stream(
+            chainstate.db(),
+            old_tip,
+            0,
+            u16::MAX,
+        )? {
+            for microblock in unconfirmed.iter().rev() {
+                old_microblocks.push(ReorgMicroblock {
+                    parent_index_block_hash: old_tip.clone(),
+                    microblock_hash: microblock.block_hash(),
+                    sequence: microblock.header.sequence,
+                });
+            }
+        }
+        for (i, header_info) in rolled_back.iter().enumerate() {
+            let parent = rolled_back.get(i + 1).unwrap_or(fork_point);
+            let mut confirmed =
+                ChainReorg::load_confirmed_microblocks(chainstate, parent, header_info)?;
+            confirmed.reverse();
+            old_microblocks.append(&mut confirmed);
+        }
+
+        let mut new_microblocks = vec![];
+        for (i, header_info) in applied.iter().enumerate() {
+            let parent = if i == 0 { fork_point } else { &applied[i - 1] };
+            let mut confirmed =
+                ChainReorg::load_confirmed_microblocks(chainstate, parent, header_info)?;
+            new_microblocks.append(&mut confirmed);
+        }
+
+        let old_hashes: HashSet<_> = old_microblocks
+            .iter()
+            .map(|mblock| mblock.microblock_hash.clone())
+            .collect();
+        let new_hashes: HashSet<_> = new_microblocks
+            .iter()
+            .map(|mblock| mblock.microblock_hash.clone())
+            .collect();
+
+        Ok((
+            old_microblocks
+                .into_iter()
+                .filter(|mblock| !new_hashes.contains(&mblock.microblock_hash))
+                .collect(),
+            new_microblocks
+                .into_iter()
+                .filter(|mblock| !old_hashes.contains(&mblock.microblock_hash))
+                .collect(),
+        ))
+    }
+}
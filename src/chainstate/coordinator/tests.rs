@@ -41,6 +41,7 @@ use crate::clarity_vm::clarity::ClarityConnection;
 use crate::core;
 use crate::core::*;
 use crate::monitoring::increment_stx_blocks_processed_counter;
+use crate::net::test::TestEventObserver;
 use clarity::vm::{
     costs::{ExecutionCost, LimitedCostTracker},
     types::PrincipalData,
@@ -333,6 +334,8 @@ impl BlockEventDispatcher for NullEventDispatcher {
     }
 
     fn dispatch_boot_receipts(&mut self, _receipts: Vec<StacksTransactionReceipt>) {}
+
+    fn announce_reorg(&self, _reorg: &ChainReorg) {}
 }
 
 pub fn make_coordinator<'a>(
@@ -3869,6 +3872,162 @@ fn preprocess_block(
         .unwrap();
 }
 
+#[test]
+fn test_announce_reorgs() {
+    let path = "/tmp/stacks-blockchain-announce-reorgs";
+    let _r = std::fs::remove_dir_all(path);
+
+    let vrf_keys: Vec<_> = (0..4).map(|_| VRFPrivateKey::new()).collect();
+    let committers: Vec<_> = (0..4).map(|_| StacksPrivateKey::new()).collect();
+
+    setup_states(
+        &[path],
+        &vrf_keys,
+        &committers,
+        None,
+        None,
+        StacksEpochId::Epoch20,
+    );
+
+    let observer = TestEventObserver::new();
+    let (tx, _) = sync_channel(100000);
+    let mut coord = ChainsCoordinator::test_new_with_observer(
+        &get_burnchain(path, None),
+        0x80000000,
+        path,
+        OnChainRewardSetProvider(),
+        tx,
+        Some(&observer),
+    );
+    coord.handle_new_burnchain_block().unwrap();
+
+    let sort_db = get_sortition_db(path, None);
+    let b = get_burnchain(path, None);
+
+    // mine g, then b1 on g, then c1 on g, then c2 on c1.  c1 ties b1, so only c2 reorgs the
+    // Stacks chain.
+    let parents = [None, Some(0), Some(0), Some(2)];
+    let mut blocks: Vec<StacksBlock> = vec![];
+    let mut block_ids = vec![];
+    let mut burn_hashes = vec![];
+    for (ix, parent_ix) in parents.iter().enumerate() {
+        let mut burnchain = get_burnchain_db(path, None);
+        let mut chainstate = get_chainstate(path);
+        let burnchain_tip = burnchain.get_canonical_chain_tip().unwrap();
+
+        let (op, block) = match parent_ix {
+            None => make_genesis_block(
+                &sort_db,
+                &mut chainstate,
+                &BlockHeaderHash([0; 32]),
+                &committers[ix],
+                10000,
+                &vrf_keys[ix],
+                ix as u32,
+            ),
+            Some(parent_ix) => make_stacks_block(
+                &sort_db,
+                &mut chainstate,
+                &b,
+                &blocks[*parent_ix].block_hash(),
+                burnchain_tip.block_height,
+                &committers[ix],
+                10000,
+                &vrf_keys[ix],
+                ix as u32,
+            ),
+        };
+
+        let burn_hash = produce_burn_block(
+            &mut burnchain,
+            &burnchain_tip.block_hash,
+            vec![op],
+            vec![].iter_mut(),
+        );
+        coord.handle_new_burnchain_block().unwrap();
+
+        let tip = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn()).unwrap();
+        assert_eq!(tip.winning_stacks_block_hash, block.block_hash());
+        preprocess_block(&mut chainstate, &sort_db, &tip, block.clone());
+        coord.handle_new_stacks_block().unwrap();
+
+        block_ids.push(StacksBlockId::new(&tip.consensus_hash, &block.block_hash()));
+        burn_hashes.push(burn_hash);
+        blocks.push(block);
+    }
+
+    let reorgs = observer.get_reorgs();
+    assert_eq!(reorgs.len(), 1);
+    let reorg = &reorgs[0];
+    assert_eq!(reorg.old_stacks_tip, block_ids[1]);
+    assert_eq!(reorg.new_stacks_tip, block_ids[3]);
+    assert_eq!(
+        reorg
+            .rolled_back_blocks
+            .iter()
+            .map(|block| block.index_block_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![block_ids[1].clone()]
+    );
+    assert_eq!(
+        reorg
+            .applied_blocks
+            .iter()
+            .map(|block| block.index_block_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![block_ids[2].clone(), block_ids[3].clone()]
+    );
+    assert!(reorg.rolled_back_burn_blocks.is_empty());
+    assert!(reorg.applied_burn_blocks.is_empty());
+    assert!(reorg.rolled_back_microblocks.is_empty());
+
+    // fork the burnchain below c2's sortition with two empty burn blocks.  The new sortition
+    // fork never saw c2, so b1 becomes the canonical Stacks tip again.
+    let mut burnchain = get_burnchain_db(path, None);
+    let fork_hash_1 =
+        produce_burn_block(&mut burnchain, &burn_hashes[2], vec![], vec![].iter_mut());
+    let fork_hash_2 = produce_burn_block(&mut burnchain, &fork_hash_1, vec![], vec![].iter_mut());
+    coord.handle_new_burnchain_block().unwrap();
+
+    let reorgs = observer.get_reorgs();
+    assert_eq!(reorgs.len(), 2);
+    let reorg = &reorgs[1];
+    assert_eq!(reorg.old_burn_tip, burn_hashes[3]);
+    assert_eq!(reorg.new_burn_tip, fork_hash_2);
+    assert_eq!(
+        reorg
+            .rolled_back_burn_blocks
+            .iter()
+            .map(|burn_block| burn_block.burn_header_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![burn_hashes[3].clone()]
+    );
+    assert_eq!(
+        reorg
+            .applied_burn_blocks
+            .iter()
+            .map(|burn_block| burn_block.burn_header_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![fork_hash_1, fork_hash_2]
+    );
+    assert_eq!(
+        reorg
+            .rolled_back_blocks
+            .iter()
+            .map(|block| block.index_block_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![block_ids[3].clone(), block_ids[2].clone()]
+    );
+    assert_eq!(
+        reorg
+            .applied_blocks
+            .iter()
+            .map(|block| block.index_block_hash.clone())
+            .collect::<Vec<_>>(),
+        vec![block_ids[1].clone()]
+    );
+}
+
 #[test]
 fn test_check_chainstate_db_versions() {
     let path = "/tmp/stacks-blockchain-check_chainstate_db_versions";
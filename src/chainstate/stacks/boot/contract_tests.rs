@@ -4,7 +4,8 @@ use std::convert::TryInto;
 
 use crate::chainstate::burn::ConsensusHash;
 use crate::chainstate::stacks::boot::{
-    BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING, BOOT_CODE_POX_TESTNET,
+    BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING, BOOT_CODE_POX_2_TESTNET,
+    BOOT_CODE_POX_TESTNET,
 };
 use crate::chainstate::stacks::db::{MinerPaymentSchedule, StacksHeaderInfo};
 use crate::chainstate::stacks::index::MarfTrieId;
@@ -65,6 +66,7 @@ lazy_static! {
         &FIRST_STACKS_BLOCK_HASH
     );
     static ref POX_CONTRACT_TESTNET: QualifiedContractIdentifier = boot_code_id("pox", false);
+    static ref POX_2_CONTRACT_TESTNET: QualifiedContractIdentifier = boot_code_id("pox-2", false);
     static ref COST_VOTING_CONTRACT_TESTNET: QualifiedContractIdentifier =
         boot_code_id("cost-voting", false);
     static ref USER_KEYS: Vec<StacksPrivateKey> =
@@ -213,6 +215,12 @@ fn cost_2_contract_is_arithmetic_only() {
     check_arithmetic_only(BOOT_CODE_COSTS_2);
 }
 
+#[test]
+fn cost_3_contract_is_arithmetic_only() {
+    use crate::chainstate::stacks::boot::BOOT_CODE_COSTS_3;
+    check_arithmetic_only(BOOT_CODE_COSTS_3);
+}
+
 impl HeadersDB for TestSimHeadersDB {
     fn get_burn_header_hash_for_block(
         &self,
@@ -273,6 +281,14 @@ impl HeadersDB for TestSimHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         Some(MINER_ADDR.clone())
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        if *id_bhh == *FIRST_INDEX_BLOCK_HASH {
+            Some(FIRST_BURNCHAIN_CONSENSUS_HASH)
+        } else {
+            self.get_burn_block_height_for_block(id_bhh)?;
+            ConsensusHash::from_bytes(&id_bhh.0[0..20])
+        }
+    }
 }
 
 #[test]
@@ -894,6 +910,323 @@ fn delegation_tests() {
     });
 }
 
+#[test]
+fn pox_2_extend_and_increase_tests() {
+    let mut sim = ClarityTestSim::new();
+    let delegator = StacksPrivateKey::new();
+    const REWARD_CYCLE_LENGTH: u128 = 1050;
+
+    sim.execute_next_block(|env| {
+        env.initialize_contract(POX_CONTRACT_TESTNET.clone(), &BOOT_CODE_POX_TESTNET)
+            .unwrap();
+        env.initialize_contract(POX_2_CONTRACT_TESTNET.clone(), &BOOT_CODE_POX_2_TESTNET)
+            .unwrap()
+    });
+    sim.execute_next_block(|env| {
+        let burn_height = env.eval_raw("burn-block-height").unwrap().0;
+
+        // can't extend or increase without stacking first
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-extend",
+                &symbols_from_values(vec![Value::UInt(1), POX_ADDRS[0].clone()])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 26)".to_string()
+        );
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-increase",
+                &symbols_from_values(vec![Value::UInt(*MIN_THRESHOLD)])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 27)".to_string()
+        );
+
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-stx",
+                &symbols_from_values(vec![
+                    Value::UInt(*MIN_THRESHOLD),
+                    POX_ADDRS[0].clone(),
+                    burn_height.clone(),
+                    Value::UInt(2),
+                ])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, lock-amount: {}, unlock-burn-height: {} }})",
+                Value::from(&USER_KEYS[0]),
+                Value::UInt(*MIN_THRESHOLD),
+                Value::UInt(REWARD_CYCLE_LENGTH * 3)
+            ))
+        );
+
+        // can't increase by more than the unlocked balance
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-increase",
+                &symbols_from_values(vec![Value::UInt(USTX_PER_HOLDER)])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 1)".to_string()
+        );
+
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-increase",
+                &symbols_from_values(vec![Value::UInt(*MIN_THRESHOLD)])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, total-locked: {} }})",
+                Value::from(&USER_KEYS[0]),
+                Value::UInt(2 * *MIN_THRESHOLD)
+            ))
+        );
+
+        assert_eq!(
+            env.eval_read_only(
+                &POX_2_CONTRACT_TESTNET,
+                &format!("(stx-get-balance '{})", &Value::from(&USER_KEYS[0]))
+            )
+            .unwrap()
+            .0,
+            Value::UInt(USTX_PER_HOLDER - 2 * *MIN_THRESHOLD)
+        );
+
+        // can't extend past the maximum lock period
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-extend",
+                &symbols_from_values(vec![Value::UInt(11), POX_ADDRS[0].clone()])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 2)".to_string()
+        );
+
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[0]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-extend",
+                &symbols_from_values(vec![Value::UInt(3), POX_ADDRS[0].clone()])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, unlock-burn-height: {} }})",
+                Value::from(&USER_KEYS[0]),
+                Value::UInt(REWARD_CYCLE_LENGTH * 6)
+            ))
+        );
+
+        // the increase covers the rest of the original lock, and the extension
+        //  carries the increased amount
+        for reward_cycle in 1..6 {
+            assert_eq!(
+                env.eval_read_only(
+                    &POX_2_CONTRACT_TESTNET,
+                    &format!("(get-total-ustx-stacked u{})", reward_cycle)
+                )
+                .unwrap()
+                .0,
+                Value::UInt(2 * *MIN_THRESHOLD)
+            );
+        }
+        assert_eq!(
+            env.eval_read_only(&POX_2_CONTRACT_TESTNET, "(get-total-ustx-stacked u6)")
+                .unwrap()
+                .0,
+            Value::UInt(0)
+        );
+
+        // stacking in the old contract prevents stacking in the new one
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[1]).into(),
+                POX_CONTRACT_TESTNET.clone(),
+                "stack-stx",
+                &symbols_from_values(vec![
+                    Value::UInt(*MIN_THRESHOLD),
+                    POX_ADDRS[1].clone(),
+                    burn_height.clone(),
+                    Value::UInt(2),
+                ])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, lock-amount: {}, unlock-burn-height: {} }})",
+                Value::from(&USER_KEYS[1]),
+                Value::UInt(*MIN_THRESHOLD),
+                Value::UInt(REWARD_CYCLE_LENGTH * 3)
+            ))
+        );
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[1]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-stx",
+                &symbols_from_values(vec![
+                    Value::UInt(*MIN_THRESHOLD),
+                    POX_ADDRS[1].clone(),
+                    burn_height.clone(),
+                    Value::UInt(2),
+                ])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 3)".to_string()
+        );
+
+        // delegated stacking can be extended and increased by the delegate
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[2]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "delegate-stx",
+                &symbols_from_values(vec![
+                    Value::UInt(3 * *MIN_THRESHOLD),
+                    (&delegator).into(),
+                    Value::none(),
+                    Value::none()
+                ])
+            )
+            .unwrap()
+            .0,
+            Value::okay_true()
+        );
+        assert_eq!(
+            env.execute_transaction(
+                (&delegator).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "delegate-stack-stx",
+                &symbols_from_values(vec![
+                    (&USER_KEYS[2]).into(),
+                    Value::UInt(*MIN_THRESHOLD),
+                    POX_ADDRS[2].clone(),
+                    burn_height.clone(),
+                    Value::UInt(2)
+                ])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, lock-amount: {}, unlock-burn-height: {} }})",
+                Value::from(&USER_KEYS[2]),
+                Value::UInt(*MIN_THRESHOLD),
+                Value::UInt(REWARD_CYCLE_LENGTH * 3)
+            ))
+        );
+
+        // the stacker can't manage a delegated lock themselves
+        assert_eq!(
+            env.execute_transaction(
+                (&USER_KEYS[2]).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "stack-extend",
+                &symbols_from_values(vec![Value::UInt(1), POX_ADDRS[2].clone()])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 30)".to_string()
+        );
+
+        assert_eq!(
+            env.execute_transaction(
+                (&delegator).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "delegate-stack-increase",
+                &symbols_from_values(vec![
+                    (&USER_KEYS[2]).into(),
+                    POX_ADDRS[2].clone(),
+                    Value::UInt(*MIN_THRESHOLD)
+                ])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, total-locked: {} }})",
+                Value::from(&USER_KEYS[2]),
+                Value::UInt(2 * *MIN_THRESHOLD)
+            ))
+        );
+
+        // can't lock more than was delegated
+        assert_eq!(
+            env.execute_transaction(
+                (&delegator).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "delegate-stack-increase",
+                &symbols_from_values(vec![
+                    (&USER_KEYS[2]).into(),
+                    POX_ADDRS[2].clone(),
+                    Value::UInt(2 * *MIN_THRESHOLD)
+                ])
+            )
+            .unwrap()
+            .0
+            .to_string(),
+            "(err 22)".to_string()
+        );
+
+        assert_eq!(
+            env.execute_transaction(
+                (&delegator).into(),
+                POX_2_CONTRACT_TESTNET.clone(),
+                "delegate-stack-extend",
+                &symbols_from_values(vec![
+                    (&USER_KEYS[2]).into(),
+                    POX_ADDRS[2].clone(),
+                    Value::UInt(1)
+                ])
+            )
+            .unwrap()
+            .0,
+            execute(&format!(
+                "(ok {{ stacker: '{}, unlock-burn-height: {} }})",
+                Value::from(&USER_KEYS[2]),
+                Value::UInt(REWARD_CYCLE_LENGTH * 4)
+            ))
+        );
+
+        assert_eq!(
+            env.eval_read_only(
+                &POX_2_CONTRACT_TESTNET,
+                &format!("(stx-get-balance '{})", &Value::from(&USER_KEYS[2]))
+            )
+            .unwrap()
+            .0,
+            Value::UInt(USTX_PER_HOLDER - 2 * *MIN_THRESHOLD)
+        );
+    });
+}
+
 #[test]
 fn test_vote_withdrawal() {
     let mut sim = ClarityTestSim::new();
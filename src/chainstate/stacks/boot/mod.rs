@@ -27,7 +27,7 @@ use crate::chainstate::stacks::db::StacksChainState;
 use crate::chainstate::stacks::index::marf::MarfConnection;
 use crate::chainstate::stacks::Error;
 use crate::clarity_vm::clarity::ClarityConnection;
-use crate::core::{POX_MAXIMAL_SCALING, POX_THRESHOLD_STEPS_USTX};
+use crate::core::{StacksEpochId, POX_MAXIMAL_SCALING, POX_THRESHOLD_STEPS_USTX};
 use clarity::vm::contexts::ContractContext;
 use clarity::vm::costs::{
     cost_functions::ClarityCostFunction, ClarityCostFunctionReference, CostStateSummary,
@@ -52,17 +52,22 @@ use crate::util_lib::boot;
 use crate::vm::{costs::LimitedCostTracker, SymbolicExpression};
 
 const BOOT_CODE_POX_BODY: &'static str = std::include_str!("pox.clar");
+const BOOT_CODE_POX_2_BODY: &'static str = std::include_str!("pox-2.clar");
 const BOOT_CODE_POX_TESTNET_CONSTS: &'static str = std::include_str!("pox-testnet.clar");
 const BOOT_CODE_POX_MAINNET_CONSTS: &'static str = std::include_str!("pox-mainnet.clar");
 const BOOT_CODE_LOCKUP: &'static str = std::include_str!("lockup.clar");
 pub const BOOT_CODE_COSTS: &'static str = std::include_str!("costs.clar");
 pub const BOOT_CODE_COSTS_2: &'static str = std::include_str!("costs-2.clar");
 pub const BOOT_CODE_COSTS_2_TESTNET: &'static str = std::include_str!("costs-2-testnet.clar");
+pub const BOOT_CODE_COSTS_3: &'static str = std::include_str!("costs-3.clar");
 const BOOT_CODE_COST_VOTING_MAINNET: &'static str = std::include_str!("cost-voting.clar");
 const BOOT_CODE_BNS: &'static str = std::include_str!("bns.clar");
 const BOOT_CODE_GENESIS: &'static str = std::include_str!("genesis.clar");
 pub const COSTS_1_NAME: &'static str = "costs";
 pub const COSTS_2_NAME: &'static str = "costs-2";
+pub const COSTS_3_NAME: &'static str = "costs-3";
+pub const POX_1_NAME: &'static str = "pox";
+pub const POX_2_NAME: &'static str = "pox-2";
 
 pub mod docs;
 
@@ -71,6 +76,10 @@ lazy_static! {
         format!("{}\n{}", BOOT_CODE_POX_MAINNET_CONSTS, BOOT_CODE_POX_BODY);
     pub static ref BOOT_CODE_POX_TESTNET: String =
         format!("{}\n{}", BOOT_CODE_POX_TESTNET_CONSTS, BOOT_CODE_POX_BODY);
+    pub static ref BOOT_CODE_POX_2_MAINNET: String =
+        format!("{}\n{}", BOOT_CODE_POX_MAINNET_CONSTS, BOOT_CODE_POX_2_BODY);
+    pub static ref BOOT_CODE_POX_2_TESTNET: String =
+        format!("{}\n{}", BOOT_CODE_POX_TESTNET_CONSTS, BOOT_CODE_POX_2_BODY);
     pub static ref BOOT_CODE_COST_VOTING_TESTNET: String = make_testnet_cost_voting();
     pub static ref STACKS_BOOT_CODE_MAINNET: [(&'static str, &'static str); 6] = [
         ("pox", &BOOT_CODE_POX_MAINNET),
@@ -185,6 +194,32 @@ impl StacksChainState {
         .map(|value| value.expect_u128())
     }
 
+    /// Which PoX contracts hold stacking state in the given epoch?  Once the chain enters
+    /// Stacks 2.1, stackers locked in the original `pox` contract coexist with stackers in
+    /// `pox-2` until their locks expire, so both must be consulted.
+    pub fn pox_contracts_in_epoch(epoch: StacksEpochId) -> Vec<&'static str> {
+        match epoch {
+            StacksEpochId::Epoch10 | StacksEpochId::Epoch20 | StacksEpochId::Epoch2_05 => {
+                vec![POX_1_NAME]
+            }
+            StacksEpochId::Epoch21 => vec![POX_1_NAME, POX_2_NAME],
+        }
+    }
+
+    /// Which PoX contracts hold stacking state as of the given block?
+    pub fn get_active_pox_contracts(
+        &mut self,
+        stacks_block_id: &StacksBlockId,
+    ) -> Result<Vec<&'static str>, Error> {
+        let connection = self
+            .clarity_state
+            .read_only_connection_checked(stacks_block_id, &NULL_HEADER_DB, &NULL_BURN_STATE_DB)
+            .map_err(Error::ClarityError)?;
+        Ok(StacksChainState::pox_contracts_in_epoch(
+            connection.get_epoch(),
+        ))
+    }
+
     pub fn get_total_ustx_stacked(
         &mut self,
         sortdb: &SortitionDB,
@@ -193,22 +228,31 @@ impl StacksChainState {
     ) -> Result<u128, Error> {
         let function = "get-total-ustx-stacked";
         let mainnet = self.mainnet;
-        let contract_identifier = boot::boot_code_id("pox", mainnet);
-        let cost_track = LimitedCostTracker::new_free();
-        let sender = PrincipalData::Standard(StandardPrincipalData::transient());
         let result = self
             .maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
-                clarity_tx.with_readonly_clarity_env(mainnet, sender, cost_track, |env| {
-                    env.execute_contract(
-                        &contract_identifier,
-                        function,
-                        &vec![SymbolicExpression::atom_value(Value::UInt(reward_cycle))],
-                        true,
-                    )
-                })
+                let mut total = 0u128;
+                for pox_contract in StacksChainState::pox_contracts_in_epoch(clarity_tx.get_epoch())
+                {
+                    let contract_identifier = boot::boot_code_id(pox_contract, mainnet);
+                    let cost_track = LimitedCostTracker::new_free();
+                    let sender = PrincipalData::Standard(StandardPrincipalData::transient());
+                    let stacked = clarity_tx
+                        .with_readonly_clarity_env(mainnet, sender, cost_track, |env| {
+                            env.execute_contract(
+                                &contract_identifier,
+                                function,
+                                &vec![SymbolicExpression::atom_value(Value::UInt(reward_cycle))],
+                                true,
+                            )
+                        })?
+                        .expect_u128();
+                    total = total
+                        .checked_add(stacked)
+                        .expect("CORRUPTION: total stacked uSTX overflowed u128");
+                }
+                Ok::<_, Error>(total)
             })?
-            .ok_or_else(|| Error::NoSuchBlockError)??
-            .expect_u128();
+            .ok_or_else(|| Error::NoSuchBlockError)??;
         Ok(result)
     }
 
@@ -236,13 +280,21 @@ impl StacksChainState {
         stacks_block_id: &StacksBlockId,
         reward_cycle: u128,
     ) -> Result<bool, Error> {
-        self.eval_boot_code_read_only(
-            sortdb,
-            stacks_block_id,
-            "pox",
-            &format!("(is-pox-active u{})", reward_cycle),
-        )
-        .map(|value| value.expect_bool())
+        // PoX is active only if it has not been rejected in any of the active PoX contracts
+        for pox_contract in self.get_active_pox_contracts(stacks_block_id)? {
+            let active = self
+                .eval_boot_code_read_only(
+                    sortdb,
+                    stacks_block_id,
+                    pox_contract,
+                    &format!("(is-pox-active u{})", reward_cycle),
+                )?
+                .expect_bool();
+            if !active {
+                return Ok(false);
+            }
+        }
+        Ok(true)
     }
 
     /// Given a threshold and set of registered addresses, return a reward set where
@@ -356,64 +408,66 @@ impl StacksChainState {
             return Ok(vec![]);
         }
 
-        // how many in this cycle?
-        let num_addrs = self
-            .eval_boot_code_read_only(
-                sortdb,
-                block_id,
-                "pox",
-                &format!("(get-reward-set-size u{})", reward_cycle),
-            )?
-            .expect_u128();
-
-        debug!(
-            "At block {:?} (reward cycle {}): {} PoX reward addresses",
-            block_id, reward_cycle, num_addrs
-        );
-
         let mut ret = vec![];
-        for i in 0..num_addrs {
-            // value should be (optional (tuple (pox-addr (tuple (...))) (total-ustx uint))).
-            // Get the tuple.
-            let tuple_data = self
+        for pox_contract in self.get_active_pox_contracts(block_id)? {
+            // how many in this cycle?
+            let num_addrs = self
                 .eval_boot_code_read_only(
                     sortdb,
                     block_id,
-                    "pox",
-                    &format!("(get-reward-set-pox-address u{} u{})", reward_cycle, i),
+                    pox_contract,
+                    &format!("(get-reward-set-size u{})", reward_cycle),
                 )?
-                .expect_optional()
-                .expect(&format!(
-                    "FATAL: missing PoX address in slot {} out of {} in reward cycle {}",
-                    i, num_addrs, reward_cycle
-                ))
-                .expect_tuple();
-
-            let pox_addr_tuple = tuple_data
-                .get("pox-addr")
-                .expect(&format!("FATAL: no 'pox-addr' in return value from (get-reward-set-pox-address u{} u{})", reward_cycle, i))
-                .to_owned()
-                .expect_tuple();
-
-            let (hash_mode, hash) = tuple_to_pox_addr(pox_addr_tuple);
-
-            let total_ustx = tuple_data
-                .get("total-ustx")
-                .expect(&format!("FATAL: no 'total-ustx' in return value from (get-reward-set-pox-address u{} u{})", reward_cycle, i))
-                .to_owned()
                 .expect_u128();
 
-            let version = match self.mainnet {
-                true => hash_mode.to_version_mainnet(),
-                false => hash_mode.to_version_testnet(),
-            };
-
-            test_debug!(
-                "PoX reward address (for {} ustx): {:?}",
-                total_ustx,
-                &StacksAddress::new(version, hash)
+            debug!(
+                "At block {:?} (reward cycle {}): {} PoX reward addresses in {}",
+                block_id, reward_cycle, num_addrs, pox_contract
             );
-            ret.push((StacksAddress::new(version, hash), total_ustx));
+
+            for i in 0..num_addrs {
+                // value should be (optional (tuple (pox-addr (tuple (...))) (total-ustx uint))).
+                // Get the tuple.
+                let tuple_data = self
+                    .eval_boot_code_read_only(
+                        sortdb,
+                        block_id,
+                        pox_contract,
+                        &format!("(get-reward-set-pox-address u{} u{})", reward_cycle, i),
+                    )?
+                    .expect_optional()
+                    .expect(&format!(
+                        "FATAL: missing PoX address in slot {} out of {} in reward cycle {}",
+                        i, num_addrs, reward_cycle
+                    ))
+                    .expect_tuple();
+
+                let pox_addr_tuple = tuple_data
+                    .get("pox-addr")
+                    .expect(&format!("FATAL: no 'pox-addr' in return value from (get-reward-set-pox-address u{} u{})", reward_cycle, i))
+                    .to_owned()
+                    .expect_tuple();
+
+                let (hash_mode, hash) = tuple_to_pox_addr(pox_addr_tuple);
+
+                let total_ustx = tuple_data
+                    .get("total-ustx")
+                    .expect(&format!("FATAL: no 'total-ustx' in return value from (get-reward-set-pox-address u{} u{})", reward_cycle, i))
+                    .to_owned()
+                    .expect_u128();
+
+                let version = match self.mainnet {
+                    true => hash_mode.to_version_mainnet(),
+                    false => hash_mode.to_version_testnet(),
+                };
+
+                test_debug!(
+                    "PoX reward address (for {} ustx): {:?}",
+                    total_ustx,
+                    &StacksAddress::new(version, hash)
+                );
+                ret.push((StacksAddress::new(version, hash), total_ustx));
+            }
         }
 
         Ok(ret)
@@ -307,6 +307,67 @@ impl StacksChainState {
         Ok(())
     }
 
+    /// Extend an existing PoX lock so that it expires at `unlock_burn_height` instead.  Does
+    /// NOT touch the account nonce.
+    pub fn pox_lock_extend(
+        db: &mut ClarityDatabase,
+        principal: &PrincipalData,
+        unlock_burn_height: u64,
+    ) -> Result<(), Error> {
+        assert!(unlock_burn_height > 0);
+
+        let mut snapshot = db.get_stx_balance_snapshot(principal);
+        if !snapshot.has_locked_tokens() {
+            return Err(Error::PoxNotLocked);
+        }
+        if unlock_burn_height <= snapshot.balance().unlock_height {
+            return Err(Error::PoxInvalidLockUpdate);
+        }
+        snapshot.extend_lock(unlock_burn_height);
+
+        debug!(
+            "PoX lock of {} uSTX extended until burnchain block height {} for {:?}",
+            snapshot.balance().amount_locked,
+            unlock_burn_height,
+            principal
+        );
+
+        snapshot.save();
+        Ok(())
+    }
+
+    /// Increase an existing PoX lock so that `new_total_locked` uSTX are locked.  Does NOT
+    /// touch the account nonce.
+    pub fn pox_lock_increase(
+        db: &mut ClarityDatabase,
+        principal: &PrincipalData,
+        new_total_locked: u128,
+    ) -> Result<(), Error> {
+        let mut snapshot = db.get_stx_balance_snapshot(principal);
+        if !snapshot.has_locked_tokens() {
+            return Err(Error::PoxNotLocked);
+        }
+        let amount_locked = snapshot.balance().amount_locked;
+        if new_total_locked <= amount_locked {
+            return Err(Error::PoxInvalidLockUpdate);
+        }
+        if !snapshot.can_transfer(new_total_locked - amount_locked) {
+            return Err(Error::PoxInsufficientBalance);
+        }
+        snapshot.increase_lock(new_total_locked);
+
+        debug!(
+            "PoX lock increased to {} uSTX (new balance {}) until burnchain block height {} for {:?}",
+            snapshot.balance().amount_locked,
+            snapshot.balance().amount_unlocked,
+            snapshot.balance().unlock_height,
+            principal
+        );
+
+        snapshot.save();
+        Ok(())
+    }
+
     /// Schedule a miner payment in the future.
     /// Schedules payments out to both miners and users that support them.
     pub fn insert_miner_payment_schedule<'a>(
@@ -523,7 +584,7 @@ impl StacksChainState {
     ///
     /// If poison_reporter_opt is not None, then the returned MinerReward will reward the _poison reporter_,
     /// not the miner, for reporting the microblock stream fork.
-    fn calculate_miner_reward(
+    pub(crate) fn calculate_miner_reward(
         mainnet: bool,
         participant: &MinerPaymentSchedule,
         miner: &MinerPaymentSchedule,
@@ -0,0 +1,684 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional address activity index.  When it is turned on, every processed block records which
+//! principals its transactions touched (and how), and which asset events named them.  Rows are
+//! kept for every fork; reads only return the rows whose blocks are ancestors of a given tip.
+
+use std::fmt;
+use std::str::FromStr;
+
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::Error;
+use crate::chainstate::stacks::*;
+use crate::util_lib::db::Error as db_error;
+use crate::util_lib::db::{query_rows, u64_to_sql, DBTx, FromColumn, FromRow};
+use clarity::vm::types::PrincipalData;
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+/// Number of rows to load at a time when scanning an index for rows in a given fork
+const INDEX_SCAN_BATCH: u32 = 256;
+
+/// Most rows to scan in one call when looking for a page of rows in a given fork.  Rows from
+/// other forks count against it, so a caller can't make one call walk an unbounded history.
+const INDEX_SCAN_LIMIT: usize = 4096;
+
+/// Where a page of index rows ends, newest first.  The next page starts with the first row
+/// that sorts strictly before it.  Index tables without an event index treat it as 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct IndexCursor {
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+}
+
+impl fmt::Display for IndexCursor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}-{}-{}",
+            self.block_height, self.tx_index, self.event_index
+        )
+    }
+}
+
+impl FromStr for IndexCursor {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<IndexCursor, String> {
+        let parts: Vec<&str> = s.split('-').collect();
+        if parts.len() != 3 {
+            return Err(format!("Invalid index cursor: {}", s));
+        }
+        let bad_cursor = |_e| format!("Invalid index cursor: {}", s);
+        Ok(IndexCursor {
+            block_height: parts[0].parse().map_err(bad_cursor)?,
+            tx_index: parts[1].parse().map_err(bad_cursor)?,
+            event_index: parts[2].parse().map_err(bad_cursor)?,
+        })
+    }
+}
+
+/// How a principal took part in a transaction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PrincipalRole {
+    /// the transaction's origin
+    Sender,
+    /// the transaction's sponsor
+    Sponsor,
+    /// the recipient of a token transfer
+    Recipient,
+    /// the contract called or deployed
+    Contract,
+    /// a sender, recipient or locked address in one of the transaction's asset events
+    AssetEvent,
+}
+
+/// A transaction that touched a principal, loaded from the address index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrincipalTransaction {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub roles: Vec<PrincipalRole>,
+}
+
+/// An asset event that named a principal, loaded from the address index
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrincipalAssetEvent {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+    /// the event, in the JSON form sent to event observers
+    pub event: serde_json::Value,
+}
+
+impl PrincipalTransaction {
+    pub fn cursor(&self) -> IndexCursor {
+        IndexCursor {
+            block_height: self.block_height,
+            tx_index: self.tx_index,
+            event_index: 0,
+        }
+    }
+}
+
+impl PrincipalAssetEvent {
+    pub fn cursor(&self) -> IndexCursor {
+        IndexCursor {
+            block_height: self.block_height,
+            tx_index: self.tx_index,
+            event_index: self.event_index,
+        }
+    }
+}
+
+impl FromRow<PrincipalTransaction> for PrincipalTransaction {
+    fn from_row<'a>(row: &'a Row) -> Result<PrincipalTransaction, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let roles_json: String = row.get_unwrap("roles");
+        let roles = serde_json::from_str(&roles_json).map_err(|_e| db_error::ParseError)?;
+
+        Ok(PrincipalTransaction {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            roles,
+        })
+    }
+}
+
+impl FromRow<PrincipalAssetEvent> for PrincipalAssetEvent {
+    fn from_row<'a>(row: &'a Row) -> Result<PrincipalAssetEvent, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let event_index: u32 = row.get_unwrap("event_index");
+        let event_json: String = row.get_unwrap("event");
+        let event = serde_json::from_str(&event_json).map_err(|_e| db_error::ParseError)?;
+
+        Ok(PrincipalAssetEvent {
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            event_index,
+            event,
+        })
+    }
+}
+
+impl StacksChainState {
+    /// Record which principals the transactions processed in the given block touched, and which
+    /// asset events named them.
+    pub fn index_principal_activity(
+        tx: &DBTx,
+        tip: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), db_error> {
+        let index_block_hash = tip.index_block_hash();
+        let block_height = u64_to_sql(tip.stacks_block_height)?;
+
+        let tx_sql = "INSERT OR REPLACE INTO principal_txs
+            (principal, index_block_hash, block_height, txid, tx_index, roles)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6)";
+        let event_sql = "INSERT OR REPLACE INTO principal_asset_events
+            (principal, index_block_hash, block_height, txid, tx_index, event_index, event)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)";
+
+        for receipt in receipts.iter() {
+            let txid = receipt.transaction.txid();
+
+            for (principal, roles) in receipt.principal_roles().into_iter() {
+                let roles_json =
+                    serde_json::to_string(&roles).expect("FATAL: failed to serialize roles");
+                let args: &[&dyn ToSql] = &[
+                    &principal.to_string(),
+                    &index_block_hash,
+                    &block_height,
+                    &txid,
+                    &receipt.tx_index,
+                    &roles_json,
+                ];
+                tx.execute(tx_sql, args)?;
+            }
+
+            let committed = !receipt.post_condition_aborted;
+            for (event_index, event) in receipt.events.iter().enumerate() {
+                let mut parties = StacksTransactionReceipt::asset_event_parties(event);
+                if parties.is_empty() {
+                    continue;
+                }
+                parties.dedup();
+
+                let event_json = event
+                    .json_serialize(event_index, &txid, committed)
+                    .to_string();
+                let event_index = event_index as u32;
+                for principal in parties.iter() {
+                    let args: &[&dyn ToSql] = &[
+                        &principal.to_string(),
+                        &index_block_hash,
+                        &block_height,
+                        &txid,
+                        &receipt.tx_index,
+                        &event_index,
+                        &event_json,
+                    ];
+                    tx.execute(event_sql, args)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Load one page of the rows of `table` that match `clauses`, newest first by
+    /// `key_columns`, skipping rows from blocks that are not ancestors of `tip`.  The page
+    /// starts after the row at `after`, if given.  `args` are the arguments to `clauses`.
+    ///
+    /// Rows are read a batch at a time, and at most `INDEX_SCAN_LIMIT` rows are read per call.
+    /// Returns the rows found, and the cursor to continue from if there may be more rows.  The
+    /// page may be short (even empty) if the scan limit was reached first.
+    pub(crate) fn load_fork_rows_after<T: FromRow<T>>(
+        &self,
+        tip: &StacksBlockId,
+        table: &str,
+        key_columns: &[&str],
+        clauses: &[&str],
+        args: &[&dyn ToSql],
+        after: Option<&IndexCursor>,
+        page_size: usize,
+        block_of: fn(&T) -> &StacksBlockId,
+        cursor_of: fn(&T) -> IndexCursor,
+    ) -> Result<(Vec<T>, Option<IndexCursor>), Error> {
+        let index_conn = self.index_conn()?;
+        let key_list = key_columns.join(",");
+        let order_by = key_columns
+            .iter()
+            .map(|column| format!("{} DESC", column))
+            .collect::<Vec<_>>()
+            .join(",");
+
+        let mut found = vec![];
+        let mut scanned = 0;
+        let mut cursor = after.copied();
+        loop {
+            let mut batch_clauses: Vec<String> = clauses.iter().map(|c| c.to_string()).collect();
+            let mut key_args = vec![];
+            if let Some(ref cursor) = cursor {
+                let key = [
+                    u64_to_sql(cursor.block_height)?,
+                    i64::from(cursor.tx_index),
+                    i64::from(cursor.event_index),
+                ];
+                key_args.extend_from_slice(&key[0..key_columns.len()]);
+                batch_clauses.push(format!(
+                    "({}) < ({})",
+                    key_list,
+                    vec!["?"; key_columns.len()].join(",")
+                ));
+            }
+            let sql = format!(
+                "SELECT * FROM {} {} ORDER BY {} LIMIT ?",
+                table,
+                if batch_clauses.is_empty() {
+                    "".to_string()
+                } else {
+                    format!("WHERE {}", batch_clauses.join(" AND "))
+                },
+                order_by
+            );
+            let mut batch_args = args.to_vec();
+            for key_arg in key_args.iter() {
+                batch_args.push(key_arg);
+            }
+            batch_args.push(&INDEX_SCAN_BATCH);
+
+            let mut rows: Vec<T> = query_rows(self.db(), &sql, batch_args.as_slice())?;
+            let exhausted = rows.len() < INDEX_SCAN_BATCH as usize;
+            if !exhausted {
+                // rows from sibling forks can share the last row's key, and some of them may
+                // not have fit in this batch.  Leave them all to the next batch, unless the
+                // whole batch shares one key.
+                let last_key = cursor_of(rows.last().expect("FATAL: non-empty batch"));
+                let first_with_last_key = rows
+                    .iter()
+                    .position(|row| cursor_of(row) == last_key)
+                    .expect("FATAL: last row not found in its batch");
+                if first_with_last_key > 0 {
+                    rows.truncate(first_with_last_key);
+                }
+            }
+            scanned += rows.len();
+
+            for row in rows.into_iter() {
+                let row_cursor = cursor_of(&row);
+                let in_fork = index_conn
+                    .get_ancestor_block_height(block_of(&row), tip)?
+                    .is_some();
+                cursor = Some(row_cursor);
+                if !in_fork {
+                    continue;
+                }
+                found.push(row);
+                if found.len() >= page_size {
+                    // a block numbers its transactions across its whole microblock stream
+                    // and then its anchored transactions, so a fork has at most one row per
+                    // key and no row at this key is left
+                    return Ok((found, cursor));
+                }
+            }
+
+            if exhausted {
+                return Ok((found, None));
+            }
+            if scanned >= INDEX_SCAN_LIMIT {
+                return Ok((found, cursor));
+            }
+        }
+    }
+
+    /// Load a page of the transactions that touched a principal in the fork ending at `tip`,
+    /// newest first, starting after `after`.  Also returns the cursor for the next page, if
+    /// there may be one.
+    pub fn get_principal_transactions(
+        &self,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        after: Option<&IndexCursor>,
+        page_size: usize,
+    ) -> Result<(Vec<PrincipalTransaction>, Option<IndexCursor>), Error> {
+        let principal_str = principal.to_string();
+        let args: &[&dyn ToSql] = &[&principal_str];
+        self.load_fork_rows_after(
+            tip,
+            "principal_txs",
+            &["block_height", "tx_index"],
+            &["principal = ?"],
+            args,
+            after,
+            page_size,
+            |row| &row.index_block_hash,
+            PrincipalTransaction::cursor,
+        )
+    }
+
+    /// Load a page of the asset events that named a principal in the fork ending at `tip`,
+    /// newest first, starting after `after`.  Also returns the cursor for the next page, if
+    /// there may be one.
+    pub fn get_principal_asset_events(
+        &self,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        after: Option<&IndexCursor>,
+        page_size: usize,
+    ) -> Result<(Vec<PrincipalAssetEvent>, Option<IndexCursor>), Error> {
+        let principal_str = principal.to_string();
+        let args: &[&dyn ToSql] = &[&principal_str];
+        self.load_fork_rows_after(
+            tip,
+            "principal_asset_events",
+            &["block_height", "tx_index", "event_index"],
+            &["principal = ?"],
+            args,
+            after,
+            page_size,
+            |row| &row.index_block_hash,
+            PrincipalAssetEvent::cursor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chainstate::burn::ConsensusHash;
+    use crate::chainstate::stacks::db::blocks::test::process_sample_microblock_stream;
+    use crate::chainstate::stacks::db::test::*;
+    use clarity::vm::costs::ExecutionCost;
+    use clarity::vm::events::*;
+    use clarity::vm::types::*;
+    use stacks_common::types::chainstate::BurnchainHeaderHash;
+    use stacks_common::util::hash::Hash160;
+
+    use super::*;
+
+    /// Append a block to `parent`.  Children of the same parent made with different `fork`
+    /// bytes are siblings.
+    fn advance_tip(
+        chainstate: &mut StacksChainState,
+        parent: &StacksHeaderInfo,
+        fork: u8,
+    ) -> StacksHeaderInfo {
+        let mut new_tip = parent.clone();
+        new_tip.anchored_header.parent_block = parent.anchored_header.block_hash();
+        new_tip.anchored_header.microblock_pubkey_hash = Hash160([fork; 20]);
+        new_tip.anchored_header.total_work.work = parent.anchored_header.total_work.work + 1;
+        new_tip.consensus_hash = ConsensusHash([fork; 20]);
+        new_tip.burn_header_hash = BurnchainHeaderHash([fork; 32]);
+        new_tip.burn_header_height = parent.burn_header_height + 1;
+
+        let mut block_reward = MinerPaymentSchedule::genesis(false);
+        block_reward.parent_consensus_hash = parent.consensus_hash.clone();
+        block_reward.parent_block_hash = parent.anchored_header.block_hash();
+        block_reward.consensus_hash = new_tip.consensus_hash.clone();
+        block_reward.block_hash = new_tip.anchored_header.block_hash();
+        block_reward.stacks_block_height = new_tip.anchored_header.total_work.work;
+
+        let mut tx = chainstate.index_tx_begin().unwrap();
+        let tip = StacksChainState::advance_tip(
+            &mut tx,
+            &parent.anchored_header,
+            &parent.consensus_hash,
+            &new_tip.anchored_header,
+            &new_tip.consensus_hash,
+            &new_tip.burn_header_hash,
+            new_tip.burn_header_height,
+            new_tip.burn_header_timestamp,
+            None,
+            &block_reward,
+            &vec![],
+            &ExecutionCost::zero(),
+            0,
+            false,
+        )
+        .unwrap();
+        tx.commit().unwrap();
+        tip
+    }
+
+    fn make_receipt(
+        txid_byte: u8,
+        tx_index: u32,
+        events: Vec<StacksTransactionEvent>,
+    ) -> StacksTransactionReceipt {
+        StacksTransactionReceipt {
+            transaction: TransactionOrigin::Burn(Txid([txid_byte; 32])),
+            events,
+            post_condition_aborted: false,
+            result: Value::okay_true(),
+            stx_burned: 0,
+            contract_analysis: None,
+            execution_cost: ExecutionCost::zero(),
+            microblock_header: None,
+            tx_index,
+        }
+    }
+
+    fn make_stx_transfer_event(
+        sender: &PrincipalData,
+        recipient: &PrincipalData,
+    ) -> StacksTransactionEvent {
+        StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(STXTransferEventData {
+            sender: sender.clone(),
+            recipient: recipient.clone(),
+            amount: 1,
+        }))
+    }
+
+    #[test]
+    fn test_index_cursor_string() {
+        let cursor = IndexCursor {
+            block_height: 12,
+            tx_index: 3,
+            event_index: 0,
+        };
+        assert_eq!(cursor.to_string(), "12-3-0");
+        assert_eq!("12-3-0".parse::<IndexCursor>().unwrap(), cursor);
+
+        assert!("12-3".parse::<IndexCursor>().is_err());
+        assert!("12-3-0-1".parse::<IndexCursor>().is_err());
+        assert!("12--3-0".parse::<IndexCursor>().is_err());
+        assert!("a-3-0".parse::<IndexCursor>().is_err());
+    }
+
+    #[test]
+    fn test_principal_activity_in_sibling_forks() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "addrindex-sibling-forks");
+        let alice = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let bob = PrincipalData::parse("ST1RFD5Q2QPK3E0F08HG9XDX7SSC7CNRS0QR0SGEV").unwrap();
+
+        // block_a and block_b are siblings; block_a_2 builds on block_a
+        let genesis = StacksHeaderInfo::regtest_genesis();
+        let block_a = advance_tip(&mut chainstate, &genesis, 1);
+        let block_b = advance_tip(&mut chainstate, &genesis, 2);
+        let block_a_2 = advance_tip(&mut chainstate, &block_a, 3);
+
+        // the sibling blocks each have a transaction at the same height and tx index
+        let tx = chainstate.db_tx_begin().unwrap();
+        StacksChainState::index_principal_activity(
+            &tx,
+            &block_a,
+            &[make_receipt(
+                1,
+                0,
+                vec![
+                    make_stx_transfer_event(&alice, &bob),
+                    make_stx_transfer_event(&bob, &alice),
+                ],
+            )],
+        )
+        .unwrap();
+        StacksChainState::index_principal_activity(
+            &tx,
+            &block_b,
+            &[make_receipt(
+                2,
+                0,
+                vec![make_stx_transfer_event(&alice, &bob)],
+            )],
+        )
+        .unwrap();
+        StacksChainState::index_principal_activity(
+            &tx,
+            &block_a_2,
+            &[make_receipt(
+                3,
+                1,
+                vec![make_stx_transfer_event(&bob, &alice)],
+            )],
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let txids = |txs: &[PrincipalTransaction]| -> Vec<Txid> {
+            txs.iter().map(|tx| tx.txid.clone()).collect()
+        };
+
+        // each tip only sees its own fork, newest first
+        let tip_a = block_a_2.index_block_hash();
+        let tip_b = block_b.index_block_hash();
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_a, &alice, None, 50)
+            .unwrap();
+        assert_eq!(txids(&txs), vec![Txid([3; 32]), Txid([1; 32])]);
+        assert_eq!(txs[0].index_block_hash, tip_a);
+        assert_eq!(txs[0].block_height, 2);
+        assert_eq!(txs[0].tx_index, 1);
+        assert_eq!(txs[0].roles, vec![PrincipalRole::AssetEvent]);
+        assert_eq!(next, None);
+
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_b, &alice, None, 50)
+            .unwrap();
+        assert_eq!(txids(&txs), vec![Txid([2; 32])]);
+        assert_eq!(txs[0].index_block_hash, tip_b);
+        assert_eq!(next, None);
+
+        // the sibling's tip is not an ancestor of either
+        let (txs, _) = chainstate
+            .get_principal_transactions(&block_a.index_block_hash(), &alice, None, 50)
+            .unwrap();
+        assert_eq!(txids(&txs), vec![Txid([1; 32])]);
+
+        // one row at a time, following the cursor
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_a, &alice, None, 1)
+            .unwrap();
+        assert_eq!(txids(&txs), vec![Txid([3; 32])]);
+        assert_eq!(next, Some(txs[0].cursor()));
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_a, &alice, next.as_ref(), 1)
+            .unwrap();
+        assert_eq!(txids(&txs), vec![Txid([1; 32])]);
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_a, &alice, next.as_ref(), 1)
+            .unwrap();
+        assert!(txs.is_empty());
+        assert_eq!(next, None);
+
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_b, &alice, None, 1)
+            .unwrap();
+        assert_eq!(txids(&txs), vec![Txid([2; 32])]);
+        let (txs, next) = chainstate
+            .get_principal_transactions(&tip_b, &alice, next.as_ref(), 1)
+            .unwrap();
+        assert!(txs.is_empty());
+        assert_eq!(next, None);
+
+        // asset events, by event index too
+        let events = |events: &[PrincipalAssetEvent]| -> Vec<(Txid, u32)> {
+            events
+                .iter()
+                .map(|event| (event.txid.clone(), event.event_index))
+                .collect()
+        };
+        let (found, next) = chainstate
+            .get_principal_asset_events(&tip_a, &bob, None, 50)
+            .unwrap();
+        assert_eq!(
+            events(&found),
+            vec![(Txid([3; 32]), 0), (Txid([1; 32]), 1), (Txid([1; 32]), 0)]
+        );
+        assert_eq!(found[1].event["type"], "stx_transfer_event");
+        assert_eq!(next, None);
+
+        let (found, next) = chainstate
+            .get_principal_asset_events(&tip_a, &bob, None, 2)
+            .unwrap();
+        assert_eq!(events(&found), vec![(Txid([3; 32]), 0), (Txid([1; 32]), 1)]);
+        let (found, _) = chainstate
+            .get_principal_asset_events(&tip_a, &bob, next.as_ref(), 2)
+            .unwrap();
+        assert_eq!(events(&found), vec![(Txid([1; 32]), 0)]);
+
+        let (found, _) = chainstate
+            .get_principal_asset_events(&tip_b, &bob, None, 50)
+            .unwrap();
+        assert_eq!(events(&found), vec![(Txid([2; 32]), 0)]);
+    }
+
+    #[test]
+    fn test_principal_activity_across_microblocks() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "addrindex-across-microblocks");
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+        let sender = TransactionAuth::from_p2pkh(&privk)
+            .unwrap()
+            .origin()
+            .address_testnet()
+            .to_account_principal();
+
+        // three microblocks of two transactions each, numbered across the whole stream
+        let receipts = process_sample_microblock_stream(&mut chainstate, &privk, 3, 2);
+        let tx_indexes: Vec<u32> = receipts.iter().map(|receipt| receipt.tx_index).collect();
+        assert_eq!(tx_indexes, vec![0, 1, 2, 3, 4, 5]);
+        assert_eq!(receipts[2].microblock_header.as_ref().unwrap().sequence, 1);
+
+        let genesis = StacksHeaderInfo::regtest_genesis();
+        let block = advance_tip(&mut chainstate, &genesis, 1);
+        let tx = chainstate.db_tx_begin().unwrap();
+        StacksChainState::index_principal_activity(&tx, &block, &receipts).unwrap();
+        tx.commit().unwrap();
+
+        // pages smaller than the stream's microblock count still reach every transaction
+        let tip = block.index_block_hash();
+        let mut txids = vec![];
+        let mut next = None;
+        loop {
+            let (txs, cursor) = chainstate
+                .get_principal_transactions(&tip, &sender, next.as_ref(), 2)
+                .unwrap();
+            assert!(txs.len() <= 2);
+            txids.extend(txs.into_iter().map(|tx| tx.txid));
+            next = cursor;
+            if next.is_none() {
+                break;
+            }
+        }
+        let expected: Vec<Txid> = receipts
+            .iter()
+            .rev()
+            .map(|receipt| receipt.transaction.txid())
+            .collect();
+        assert_eq!(txids, expected);
+    }
+}
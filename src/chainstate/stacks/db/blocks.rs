@@ -73,12 +73,13 @@ use clarity::vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, SequenceData,
     StandardPrincipalData, TupleData, TypeSignature, Value,
 };
+use clarity::vm::ClarityVersion;
 use stacks_common::util::get_epoch_time_ms;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::retry::BoundReader;
 
-use crate::chainstate::coordinator::BlockEventDispatcher;
+use crate::chainstate::coordinator::{BlockEventDispatcher, ChainReorg};
 use crate::chainstate::stacks::address::StacksAddressExtensions;
 use crate::chainstate::stacks::StacksBlockHeader;
 use crate::chainstate::stacks::StacksMicroblockHeader;
@@ -155,6 +156,7 @@ pub enum MemPoolRejection {
     BadTransactionVersion,
     TransferRecipientIsSender(PrincipalData),
     TransferAmountMustBePositive,
+    ClarityVersionNotSupported(Option<ClarityVersion>, StacksEpochId),
     DBError(db_error),
     EstimatorError(EstimatorError),
     Other(String),
@@ -217,6 +219,13 @@ impl BlockEventDispatcher for DummyEventDispatcher {
             "We should never try to dispatch boot receipts to the dummy dispatcher"
         );
     }
+
+    fn announce_reorg(&self, _reorg: &ChainReorg) {
+        assert!(
+            false,
+            "We should never try to announce a reorg to the dummy dispatcher"
+        );
+    }
 }
 
 impl MemPoolRejection {
@@ -260,6 +269,13 @@ impl MemPoolRejection {
                 Some(json!({"recipient": recipient.to_string()})),
             ),
             TransferAmountMustBePositive => ("TransferAmountMustBePositive", None),
+            ClarityVersionNotSupported(clarity_version, epoch_id) => (
+                "ClarityVersionNotSupported",
+                Some(json!({
+                    "clarity_version": clarity_version.map(|v| v.to_string()),
+                    "epoch": epoch_id.to_string(),
+                })),
+            ),
             BadNonces(TransactionNonceMismatch {
                 expected,
                 actual,
@@ -4468,6 +4484,7 @@ impl StacksChainState {
 
     /// Process a stream of microblocks
     /// Return the fees and burns.
+    /// Transactions are numbered in stream order, so no two receipts share a `tx_index`.
     pub fn process_microblocks_transactions(
         clarity_tx: &mut ClarityTx,
         microblocks: &Vec<StacksMicroblock>,
@@ -4475,15 +4492,17 @@ impl StacksChainState {
         let mut fees = 0u128;
         let mut burns = 0u128;
         let mut receipts = vec![];
+        let mut tx_index = 0u32;
         for microblock in microblocks.iter() {
             debug!("Process microblock {}", &microblock.block_hash());
-            for (tx_index, tx) in microblock.txs.iter().enumerate() {
+            for tx in microblock.txs.iter() {
                 let (tx_fee, mut tx_receipt) =
                     StacksChainState::process_transaction(clarity_tx, tx, false)
                         .map_err(|e| (e, microblock.block_hash()))?;
 
                 tx_receipt.microblock_header = Some(microblock.header.clone());
-                tx_receipt.tx_index = tx_index as u32;
+                tx_receipt.tx_index = tx_index;
+                tx_index += 1;
                 fees = fees.checked_add(tx_fee as u128).expect("Fee overflow");
                 burns = burns
                     .checked_add(tx_receipt.stx_burned as u128)
@@ -4525,16 +4544,25 @@ impl StacksChainState {
                         panic!("Clarity VM believes it was running in 1.0: pre-Clarity.")
                     }
                     StacksEpochId::Epoch20 => {
+                        receipts.push(clarity_tx.block.initialize_epoch_2_05()?);
+                        // no Stacks blocks may have been mined during 2.05, in which case
+                        //  the 2.1 transition must be applied right after it.
+                        if sortition_epoch.epoch_id == StacksEpochId::Epoch21 {
+                            receipts.push(clarity_tx.block.initialize_epoch_2_1()?);
+                        }
+                        applied = true;
+                    }
+                    StacksEpochId::Epoch2_05 => {
                         assert_eq!(
                             sortition_epoch.epoch_id,
-                            StacksEpochId::Epoch2_05,
-                            "Should only transition from Epoch20 to Epoch2_05"
+                            StacksEpochId::Epoch21,
+                            "Should only transition from Epoch2_05 to Epoch21"
                         );
-                        receipts.push(clarity_tx.block.initialize_epoch_2_05()?);
+                        receipts.push(clarity_tx.block.initialize_epoch_2_1()?);
                         applied = true;
                     }
-                    StacksEpochId::Epoch2_05 => {
-                        panic!("No defined transition from Epoch2_05 forward")
+                    StacksEpochId::Epoch21 => {
+                        panic!("No defined transition from Epoch21 forward")
                     }
                 }
             }
@@ -4551,6 +4579,11 @@ impl StacksChainState {
         let mut all_receipts = vec![];
         let mainnet = clarity_tx.config.mainnet;
         let mut cost_so_far = clarity_tx.cost_so_far();
+        // burnchain stacking operations always go to the newest PoX contract
+        let epoch = clarity_tx.with_clarity_db_readonly(|db| db.get_clarity_epoch_version());
+        let pox_contract = *StacksChainState::pox_contracts_in_epoch(epoch)
+            .last()
+            .expect("BUG: no PoX contract in epoch");
         for stack_stx_op in operations.into_iter() {
             let StackStxOp {
                 sender,
@@ -4565,7 +4598,7 @@ impl StacksChainState {
             let result = clarity_tx.connection().as_transaction(|tx| {
                 tx.run_contract_call(
                     &sender.into(),
-                    &boot_code_id("pox", mainnet),
+                    &boot_code_id(pox_contract, mainnet),
                     "stack-stx",
                     &[
                         Value::UInt(stacked_ustx),
@@ -4838,6 +4871,176 @@ impl StacksChainState {
         Ok(parent_miner)
     }
 
+    /// Re-execute an already-processed block against its parent's state the way block processing
+    /// does -- epoch transition, burnchain STX operations, parent microblocks, transactions,
+    /// matured miner rewards and genesis STX unlocks -- and return the receipt it would have
+    /// produced.  Nothing is written: the Clarity block and the chainstate transaction are
+    /// rolled back once the block has run.
+    ///
+    /// The receipts for the epoch transition and the burnchain operations come first, then the
+    /// microblock transactions, then the anchored transactions.  As in block processing, the
+    /// genesis STX unlocks are reported as mint events on the coinbase's receipt.  A transaction
+    /// that fails to replay is left out of the receipt.  Returns Ok(None) if the block or its
+    /// header is not stored.
+    pub fn replay_block_receipt(
+        &mut self,
+        sortdb: &SortitionDB,
+        index_block_hash: &StacksBlockId,
+    ) -> Result<Option<StacksEpochReceipt>, Error> {
+        let header_info = match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+            self.db(),
+            index_block_hash,
+        )? {
+            Some(header_info) => header_info,
+            None => {
+                return Ok(None);
+            }
+        };
+        let staging_block =
+            match StacksChainState::load_staging_block_info(self.db(), index_block_hash)? {
+                Some(staging_block) => staging_block,
+                None => {
+                    return Ok(None);
+                }
+            };
+        let block = match StacksChainState::load_block(
+            &self.blocks_path,
+            &header_info.consensus_hash,
+            &header_info.anchored_header.block_hash(),
+        )? {
+            Some(block) => block,
+            None => {
+                return Ok(None);
+            }
+        };
+        let parent_microblocks =
+            StacksChainState::find_parent_microblock_stream(self.db(), &staging_block)?
+                .unwrap_or(vec![]);
+
+        // the first-ever mined block's parent is the boot block
+        let (parent_consensus_hash, parent_block_hash) = if staging_block.is_first_mined() {
+            (
+                FIRST_BURNCHAIN_CONSENSUS_HASH.clone(),
+                FIRST_STACKS_BLOCK_HASH.clone(),
+            )
+        } else {
+            (
+                staging_block.parent_consensus_hash.clone(),
+                staging_block.parent_anchored_block_hash.clone(),
+            )
+        };
+        let parent_header_info = StacksChainState::get_anchored_block_header_info(
+            self.db(),
+            &parent_consensus_hash,
+            &parent_block_hash,
+        )?
+        .ok_or(Error::NoSuchBlockError)?;
+
+        // the burnchain block that precedes this block's sortition
+        let parent_burn_hash =
+            SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &header_info.consensus_hash)?
+                .ok_or(Error::NoSuchBlockError)?
+                .parent_burn_header_hash;
+
+        let mainnet = self.mainnet;
+        let burn_dbconn = sortdb.index_conn();
+        let (mut chainstate_tx, clarity_instance) = self.chainstate_tx_begin()?;
+        let SetupBlockResult {
+            mut clarity_tx,
+            mut tx_receipts,
+            microblock_execution_cost,
+            microblock_txs_receipts,
+            matured_miner_rewards_opt,
+            evaluated_epoch,
+            ..
+        } = StacksChainState::setup_block(
+            &mut chainstate_tx,
+            clarity_instance,
+            &burn_dbconn,
+            sortdb.conn(),
+            &parent_header_info,
+            parent_burn_hash,
+            header_info.burn_header_height,
+            parent_consensus_hash,
+            parent_block_hash,
+            &parent_microblocks,
+            mainnet,
+            None,
+        )?;
+
+        let mut tx_index = microblock_txs_receipts.len() as u32;
+        tx_receipts.extend(microblock_txs_receipts.into_iter());
+
+        let mut block_receipts = vec![];
+        for tx in block.txs.iter() {
+            match StacksChainState::process_transaction(&mut clarity_tx, tx, true) {
+                Ok((_, mut tx_receipt)) => {
+                    tx_receipt.tx_index = tx_index;
+                    block_receipts.push(tx_receipt);
+                }
+                Err(e) => {
+                    warn!("Failed to replay block transaction";
+                          "txid" => %tx.txid(),
+                          "block" => %index_block_hash,
+                          "error" => ?e);
+                }
+            }
+            tx_index += 1;
+        }
+        let anchored_block_cost = clarity_tx.cost_so_far();
+
+        let (matured_rewards, matured_rewards_info, miner_payouts_opt) =
+            if let Some((miner_reward, mut user_rewards, parent_reward, reward_ptr)) =
+                matured_miner_rewards_opt
+            {
+                let mut ret = vec![];
+                ret.push(miner_reward.clone());
+                ret.append(&mut user_rewards);
+                ret.push(parent_reward.clone());
+                (
+                    ret,
+                    Some(reward_ptr),
+                    Some((miner_reward, user_rewards, parent_reward)),
+                )
+            } else {
+                (vec![], None, None)
+            };
+
+        let finish_result = StacksChainState::finish_block(
+            &mut clarity_tx,
+            miner_payouts_opt,
+            block.header.total_work.work as u32,
+            block.header.microblock_pubkey_hash,
+        );
+        clarity_tx.rollback_block();
+        let mut lockup_events = finish_result?;
+
+        if lockup_events.len() > 0 {
+            match block_receipts
+                .iter_mut()
+                .find(|receipt| receipt.is_coinbase_tx())
+            {
+                Some(receipt) => receipt.events.append(&mut lockup_events),
+                None => warn!("Unable to attach lockup events, block has no coinbase transaction";
+                              "block" => %index_block_hash),
+            }
+        }
+        tx_receipts.append(&mut block_receipts);
+
+        Ok(Some(StacksEpochReceipt {
+            header: header_info,
+            tx_receipts,
+            matured_rewards,
+            matured_rewards_info,
+            parent_microblocks_cost: microblock_execution_cost,
+            anchored_block_cost,
+            parent_burn_block_hash: parent_header_info.burn_header_hash,
+            parent_burn_block_height: parent_header_info.burn_header_height,
+            parent_burn_block_timestamp: parent_header_info.burn_header_timestamp,
+            evaluated_epoch,
+        }))
+    }
+
     /// Called in both follower and miner block assembly paths.
     /// Returns clarity_tx, list of receipts, microblock execution cost,
     /// microblock fees, microblock burns, list of microblock tx receipts,
@@ -5462,6 +5665,8 @@ impl StacksChainState {
         .expect("FATAL: failed to advance chain tip");
 
         chainstate_tx.log_transactions_processed(&new_tip.index_block_hash(), &tx_receipts);
+        chainstate_tx.log_principal_activity(&new_tip, &tx_receipts);
+        chainstate_tx.log_contract_events(&new_tip, &tx_receipts);
 
         set_last_execution_cost_observed(&block_execution_cost, &block_limit);
 
@@ -6336,9 +6541,19 @@ impl StacksChainState {
                         .map_err(|e| MemPoolRejection::BadFunctionArgument(e))
                 })?;
             }
-            TransactionPayload::SmartContract(TransactionSmartContract { name, code_body: _ }) => {
-                let contract_identifier =
-                    QualifiedContractIdentifier::new(tx.origin_address().into(), name.clone());
+            TransactionPayload::SmartContract(smart_contract) => {
+                let epoch_id = clarity_connection.get_epoch();
+                if !smart_contract.is_supported_in_epoch(epoch_id) {
+                    return Err(MemPoolRejection::ClarityVersionNotSupported(
+                        smart_contract.clarity_version,
+                        epoch_id,
+                    ));
+                }
+
+                let contract_identifier = QualifiedContractIdentifier::new(
+                    tx.origin_address().into(),
+                    smart_contract.name.clone(),
+                );
 
                 let exists = clarity_connection
                     .with_analysis_db_readonly(|db| db.has_contract(&contract_identifier));
@@ -6410,6 +6625,7 @@ pub mod test {
 
     use super::*;
 
+    use clarity::vm::test_util::TEST_BURN_STATE_DB;
     use clarity::vm::types::StacksAddressExtensions;
 
     use serde_json;
@@ -6662,6 +6878,73 @@ pub mod test {
         make_sample_microblock_stream_fork(privk, anchored_block_hash, 0)
     }
 
+    /// Process a stream of `num_microblocks` microblocks, each with `txs_per_microblock`
+    /// contract deployments that print a `{ topic: "hello" }` tuple, on top of the boot block.
+    /// The chain state is left unchanged; only the receipts are returned.
+    pub fn process_sample_microblock_stream(
+        chainstate: &mut StacksChainState,
+        privk: &StacksPrivateKey,
+        num_microblocks: usize,
+        txs_per_microblock: usize,
+    ) -> Vec<StacksTransactionReceipt> {
+        let auth = TransactionAuth::from_p2pkh(privk).unwrap();
+        let mut microblocks: Vec<StacksMicroblock> = vec![];
+        for i in 0..num_microblocks {
+            let mut txs = vec![];
+            for j in 0..txs_per_microblock {
+                let nonce = (i * txs_per_microblock + j) as u64;
+                let mut tx_contract = StacksTransaction::new(
+                    TransactionVersion::Testnet,
+                    auth.clone(),
+                    TransactionPayload::new_smart_contract(
+                        &format!("hello-{}", nonce),
+                        "(print { topic: \"hello\" })",
+                    )
+                    .unwrap(),
+                );
+                tx_contract.chain_id = 0x80000000;
+                tx_contract.anchor_mode = TransactionAnchorMode::OffChainOnly;
+                tx_contract.set_tx_fee(0);
+                tx_contract.set_origin_nonce(nonce);
+
+                let mut tx_signer = StacksTransactionSigner::new(&tx_contract);
+                tx_signer.sign_origin(privk).unwrap();
+                txs.push(tx_signer.get_tx().unwrap());
+            }
+
+            let txid_vecs = txs.iter().map(|tx| tx.txid().as_bytes().to_vec()).collect();
+            let merkle_tree = MerkleTree::<Sha512Trunc256Sum>::new(&txid_vecs);
+            let prev_block = match microblocks.last() {
+                Some(mblock) => mblock.block_hash(),
+                None => FIRST_STACKS_BLOCK_HASH.clone(),
+            };
+            let header = StacksMicroblockHeader {
+                version: 0x12,
+                sequence: i as u16,
+                prev_block,
+                tx_merkle_root: merkle_tree.root(),
+                signature: MessageSignature([0u8; 65]),
+            };
+
+            let mut mblock = StacksMicroblock { header, txs };
+            mblock.sign(privk).unwrap();
+            microblocks.push(mblock);
+        }
+
+        let mut clarity_tx = chainstate.block_begin(
+            &TEST_BURN_STATE_DB,
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &FIRST_STACKS_BLOCK_HASH,
+            &ConsensusHash([1u8; 20]),
+            &BlockHeaderHash([1u8; 32]),
+        );
+        let (_, _, receipts) =
+            StacksChainState::process_microblocks_transactions(&mut clarity_tx, &microblocks)
+                .unwrap();
+        clarity_tx.rollback_block();
+        receipts
+    }
+
     fn resign_microblocks(
         microblocks: &mut Vec<StacksMicroblock>,
         privk: &StacksPrivateKey,
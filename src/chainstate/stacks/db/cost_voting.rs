@@ -0,0 +1,389 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Read-only view of the cost-voting boot contract: the proposals submitted to it, the overrides
+//! miners have confirmed, and the cost functions that Clarity code actually runs under as a
+//! result.
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::Error;
+use crate::clarity_vm::clarity::ClarityConnection;
+use crate::util_lib::boot::boot_code_id;
+use clarity::vm::costs::load_effective_cost_functions;
+use clarity::vm::database::ClarityDatabase;
+use clarity::vm::errors::Error as ClarityRuntimeError;
+use clarity::vm::types::{QualifiedContractIdentifier, TupleData, Value};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+/// Where a cost-function proposal is in the cost-voting process
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CostProposalStatus {
+    /// STX holders can still vote for the proposal
+    Voting,
+    /// The proposal did not get enough votes before its vote period ended
+    Expired,
+    /// The proposal got enough votes, and miners can still veto it
+    VetoPeriod,
+    /// The veto period is over, and a miner can now confirm the proposal
+    AwaitingConfirmation,
+    /// Miners vetoed the proposal
+    Vetoed,
+    /// Miners confirmed the proposal
+    Confirmed,
+}
+
+/// A proposal submitted to the cost-voting contract
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostProposal {
+    pub proposal_id: u64,
+    pub function_contract: String,
+    pub function_name: String,
+    pub cost_function_contract: String,
+    pub cost_function_name: String,
+    /// block height at which voting ends
+    pub expiration_block_height: u64,
+    pub votes: u64,
+    pub vetos: u64,
+    /// block height at which the veto period ends, once the votes have been confirmed
+    pub veto_expiration_block_height: Option<u64>,
+    /// index into the contract's `confirmed-proposals` map, once miners have confirmed it
+    pub confirmed_id: Option<u64>,
+    pub status: CostProposalStatus,
+}
+
+/// A proposal that miners have confirmed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ConfirmedCostOverride {
+    pub confirmed_id: u64,
+    pub function_contract: String,
+    pub function_name: String,
+    pub cost_function_contract: String,
+    pub cost_function_name: String,
+    pub confirmed_height: u64,
+    /// whether this override is in effect.  Overrides that fail validation are ignored, and an
+    /// override is superseded by any later override of the same function.
+    pub in_effect: bool,
+}
+
+/// The cost function that Clarity runs for a `ClarityCostFunction`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveCostFunction {
+    pub cost_function: String,
+    pub contract_id: String,
+    pub function_name: String,
+    /// whether this comes from a confirmed override rather than the boot cost contract
+    pub overridden: bool,
+}
+
+/// A contract function whose runtime cost is computed by a cost function instead of by
+/// evaluating it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveContractCallCost {
+    pub contract_id: String,
+    pub function_name: String,
+    pub cost_contract_id: String,
+    pub cost_function_name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CostVotingReport {
+    pub block_id: StacksBlockId,
+    pub block_height: u64,
+    pub proposals: Vec<CostProposal>,
+    pub confirmed_overrides: Vec<ConfirmedCostOverride>,
+    /// one entry per `ClarityCostFunction`, sorted by name
+    pub cost_functions: Vec<EffectiveCostFunction>,
+    /// sorted by contract and function
+    pub contract_call_costs: Vec<EffectiveContractCallCost>,
+}
+
+fn cost_voting_tuple_key(name: &str, id: u128) -> Value {
+    Value::Tuple(
+        TupleData::from_data(vec![(name.into(), Value::UInt(id))])
+            .expect("BUG: failed to construct cost-voting key"),
+    )
+}
+
+fn fetch_cost_voting_entry(
+    clarity_db: &mut ClarityDatabase,
+    contract_id: &QualifiedContractIdentifier,
+    map_name: &str,
+    key: &Value,
+) -> Result<Option<TupleData>, ClarityRuntimeError> {
+    Ok(clarity_db
+        .fetch_entry_unknown_descriptor(contract_id, map_name, key)?
+        .expect_optional()
+        .map(|value| value.expect_tuple()))
+}
+
+fn get_u64(tuple: &TupleData, name: &str) -> u64 {
+    tuple
+        .get(name)
+        .expect("FATAL: malformed cost-voting tuple")
+        .to_owned()
+        .expect_u128() as u64
+}
+
+fn get_principal_string(tuple: &TupleData, name: &str) -> String {
+    tuple
+        .get(name)
+        .expect("FATAL: malformed cost-voting tuple")
+        .to_owned()
+        .expect_principal()
+        .to_string()
+}
+
+fn get_ascii_string(tuple: &TupleData, name: &str) -> String {
+    tuple
+        .get(name)
+        .expect("FATAL: malformed cost-voting tuple")
+        .to_owned()
+        .expect_ascii()
+}
+
+impl StacksChainState {
+    /// Report the state of the cost-voting contract as of the given block: every proposal and
+    /// where it is in the voting process, every override confirmed by miners, and the cost
+    /// function that a block built on this one would use for every Clarity cost function and
+    /// overridden contract function.
+    pub fn get_cost_voting_report(
+        &mut self,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+    ) -> Result<CostVotingReport, Error> {
+        let mainnet = self.mainnet;
+        let cost_voting_contract = boot_code_id("cost-voting", mainnet);
+
+        let report = self
+            .maybe_read_only_clarity_tx(&sortdb.index_conn(), block_id, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let block_height = clarity_db.get_current_block_height() as u64;
+                    let required_vetos = clarity_db
+                        .get_contract(&cost_voting_contract)?
+                        .contract_context
+                        .lookup_variable("REQUIRED_VETOES")
+                        .expect("FATAL: no REQUIRED_VETOES in cost-voting contract")
+                        .clone()
+                        .expect_u128() as u64;
+
+                    let proposal_count = clarity_db
+                        .lookup_variable_unknown_descriptor(
+                            &cost_voting_contract,
+                            "proposal-count",
+                        )?
+                        .expect_u128();
+                    let mut proposals = vec![];
+                    for proposal_id in 0..proposal_count {
+                        let key = cost_voting_tuple_key("proposal-id", proposal_id);
+                        let proposal = match fetch_cost_voting_entry(
+                            clarity_db,
+                            &cost_voting_contract,
+                            "proposals",
+                            &key,
+                        )? {
+                            Some(proposal) => proposal,
+                            None => continue,
+                        };
+                        let votes = fetch_cost_voting_entry(
+                            clarity_db,
+                            &cost_voting_contract,
+                            "proposal-votes",
+                            &key,
+                        )?
+                        .map(|entry| get_u64(&entry, "votes"))
+                        .unwrap_or(0);
+                        let vetos = fetch_cost_voting_entry(
+                            clarity_db,
+                            &cost_voting_contract,
+                            "proposal-vetos",
+                            &key,
+                        )?
+                        .map(|entry| get_u64(&entry, "vetos"))
+                        .unwrap_or(0);
+                        let veto_expiration_block_height = fetch_cost_voting_entry(
+                            clarity_db,
+                            &cost_voting_contract,
+                            "vote-confirmed-proposals",
+                            &key,
+                        )?
+                        .map(|entry| get_u64(&entry, "expiration-block-height"));
+                        let confirmed_id = fetch_cost_voting_entry(
+                            clarity_db,
+                            &cost_voting_contract,
+                            "proposal-confirmed-id",
+                            &key,
+                        )?
+                        .map(|entry| get_u64(&entry, "confirmed-id"));
+                        let expiration_block_height = get_u64(&proposal, "expiration-block-height");
+
+                        let status = match (confirmed_id, veto_expiration_block_height) {
+                            (Some(_), _) => CostProposalStatus::Confirmed,
+                            (None, Some(_)) if vetos >= required_vetos => {
+                                CostProposalStatus::Vetoed
+                            }
+                            (None, Some(veto_expiration)) if block_height < veto_expiration => {
+                                CostProposalStatus::VetoPeriod
+                            }
+                            (None, Some(_)) => CostProposalStatus::AwaitingConfirmation,
+                            (None, None) if block_height < expiration_block_height => {
+                                CostProposalStatus::Voting
+                            }
+                            (None, None) => CostProposalStatus::Expired,
+                        };
+
+                        proposals.push(CostProposal {
+                            proposal_id: proposal_id as u64,
+                            function_contract: get_principal_string(&proposal, "function-contract"),
+                            function_name: get_ascii_string(&proposal, "function-name"),
+                            cost_function_contract: get_principal_string(
+                                &proposal,
+                                "cost-function-contract",
+                            ),
+                            cost_function_name: get_ascii_string(&proposal, "cost-function-name"),
+                            expiration_block_height,
+                            votes,
+                            vetos,
+                            veto_expiration_block_height,
+                            confirmed_id,
+                            status,
+                        });
+                    }
+
+                    let confirmed_count = clarity_db
+                        .lookup_variable_unknown_descriptor(
+                            &cost_voting_contract,
+                            "confirmed-proposal-count",
+                        )?
+                        .expect_u128();
+                    let mut confirmed_overrides = vec![];
+                    for confirmed_id in 0..confirmed_count {
+                        let key = cost_voting_tuple_key("confirmed-id", confirmed_id);
+                        if let Some(confirmed) = fetch_cost_voting_entry(
+                            clarity_db,
+                            &cost_voting_contract,
+                            "confirmed-proposals",
+                            &key,
+                        )? {
+                            confirmed_overrides.push(ConfirmedCostOverride {
+                                confirmed_id: confirmed_id as u64,
+                                function_contract: get_principal_string(
+                                    &confirmed,
+                                    "function-contract",
+                                ),
+                                function_name: get_ascii_string(&confirmed, "function-name"),
+                                cost_function_contract: get_principal_string(
+                                    &confirmed,
+                                    "cost-function-contract",
+                                ),
+                                cost_function_name: get_ascii_string(
+                                    &confirmed,
+                                    "cost-function-name",
+                                ),
+                                confirmed_height: get_u64(&confirmed, "confirmed-height"),
+                                in_effect: false,
+                            });
+                        }
+                    }
+
+                    let effective = load_effective_cost_functions(mainnet, clarity_db)?;
+                    Ok::<_, ClarityRuntimeError>((
+                        block_height,
+                        proposals,
+                        confirmed_overrides,
+                        effective,
+                    ))
+                })
+            })?
+            .ok_or(Error::NoSuchBlockError)?;
+
+        let (block_height, proposals, mut confirmed_overrides, effective) = report?;
+
+        let boot_costs_contract = boot_code_id("costs", mainnet);
+        let mut cost_functions: Vec<_> = effective
+            .cost_function_references
+            .iter()
+            .map(|(cost_function, reference)| EffectiveCostFunction {
+                cost_function: cost_function.get_name(),
+                contract_id: reference.contract_id.to_string(),
+                function_name: reference.function_name.clone(),
+                overridden: confirmed_overrides.iter().any(|confirmed| {
+                    confirmed.function_contract == boot_costs_contract.to_string()
+                        && confirmed.function_name == cost_function.get_name_str()
+                        && confirmed.cost_function_contract == reference.contract_id.to_string()
+                        && confirmed.cost_function_name == reference.function_name
+                }),
+            })
+            .collect();
+        cost_functions.sort_by(|a, b| a.cost_function.cmp(&b.cost_function));
+
+        let mut contract_call_costs: Vec<_> = effective
+            .contract_call_circuits
+            .iter()
+            .map(
+                |((contract_id, function_name), reference)| EffectiveContractCallCost {
+                    contract_id: contract_id.to_string(),
+                    function_name: function_name.to_string(),
+                    cost_contract_id: reference.contract_id.to_string(),
+                    cost_function_name: reference.function_name.clone(),
+                },
+            )
+            .collect();
+        contract_call_costs.sort_by(|a, b| {
+            (&a.contract_id, &a.function_name).cmp(&(&b.contract_id, &b.function_name))
+        });
+
+        // an override is in effect if it is the last confirmed override of its function, and
+        // the cost function it installs is the one in use
+        for i in 0..confirmed_overrides.len() {
+            let superseded = confirmed_overrides[i + 1..].iter().any(|later| {
+                later.function_contract == confirmed_overrides[i].function_contract
+                    && later.function_name == confirmed_overrides[i].function_name
+            });
+            if superseded {
+                continue;
+            }
+            let confirmed = &confirmed_overrides[i];
+            let installed = if confirmed.function_contract == boot_costs_contract.to_string() {
+                cost_functions.iter().any(|f| {
+                    f.overridden
+                        && f.cost_function == confirmed.function_name
+                        && f.contract_id == confirmed.cost_function_contract
+                        && f.function_name == confirmed.cost_function_name
+                })
+            } else {
+                contract_call_costs.iter().any(|c| {
+                    c.contract_id == confirmed.function_contract
+                        && c.function_name == confirmed.function_name
+                        && c.cost_contract_id == confirmed.cost_function_contract
+                        && c.cost_function_name == confirmed.cost_function_name
+                })
+            };
+            confirmed_overrides[i].in_effect = installed;
+        }
+
+        Ok(CostVotingReport {
+            block_id: block_id.clone(),
+            block_height,
+            proposals,
+            confirmed_overrides,
+            cost_functions,
+            contract_call_costs,
+        })
+    }
+}
@@ -0,0 +1,636 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional contract event log.  When it is turned on, every processed block records the `print`
+//! events and fungible and non-fungible token events its transactions emitted, keyed by the
+//! contract that emitted them.  STX events are not emitted by a contract, so they are not logged.
+//! Rows are kept for every fork; reads only return the rows whose blocks are ancestors of a given
+//! tip.
+
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
+use crate::chainstate::stacks::db::addrindex::IndexCursor;
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::Error;
+use crate::chainstate::stacks::*;
+use crate::util_lib::db::Error as db_error;
+use crate::util_lib::db::{u64_to_sql, DBTx, FromColumn, FromRow};
+use clarity::vm::events::{FTEventType, NFTEventType};
+use clarity::vm::types::{CharType, QualifiedContractIdentifier, SequenceData, Value};
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+/// Tuple fields of a `print` event's value that name its topic, in order of preference
+pub const CONTRACT_EVENT_TOPIC_FIELDS: &[&str] = &["topic", "event"];
+
+/// Which logged contract events to load.  Unset fields match every event.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ContractEventFilter {
+    pub contract_id: Option<QualifiedContractIdentifier>,
+    pub topic: Option<String>,
+    /// lowest block height to include
+    pub from_height: Option<u64>,
+    /// highest block height to include
+    pub to_height: Option<u64>,
+}
+
+/// A contract event loaded from the event log
+#[derive(Debug, Clone, PartialEq)]
+pub struct ContractEvent {
+    pub contract_id: QualifiedContractIdentifier,
+    pub topic: Option<String>,
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+    /// the event, in the JSON form sent to event observers
+    pub event: serde_json::Value,
+}
+
+impl ContractEvent {
+    pub fn cursor(&self) -> IndexCursor {
+        IndexCursor {
+            block_height: self.block_height,
+            tx_index: self.tx_index,
+            event_index: self.event_index,
+        }
+    }
+}
+
+impl FromRow<ContractEvent> for ContractEvent {
+    fn from_row<'a>(row: &'a Row) -> Result<ContractEvent, db_error> {
+        let contract_id = QualifiedContractIdentifier::from_column(row, "contract_id")?;
+        let topic: Option<String> = row.get_unwrap("topic");
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_index: u32 = row.get_unwrap("tx_index");
+        let event_index: u32 = row.get_unwrap("event_index");
+        let event_json: String = row.get_unwrap("event");
+        let event = serde_json::from_str(&event_json).map_err(|_e| db_error::ParseError)?;
+
+        Ok(ContractEvent {
+            contract_id,
+            topic,
+            txid,
+            index_block_hash,
+            block_height,
+            tx_index,
+            event_index,
+            event,
+        })
+    }
+}
+
+impl StacksChainState {
+    /// The contract that emitted an event, and the event's topic.  `print` events have a topic if
+    /// they print a tuple with one of the `CONTRACT_EVENT_TOPIC_FIELDS`; token events use their
+    /// type (e.g. `ft_transfer_event`) as their topic.  None if the event was not emitted by a
+    /// contract.
+    pub fn contract_event_key(
+        event: &StacksTransactionEvent,
+    ) -> Option<(QualifiedContractIdentifier, Option<String>)> {
+        let (contract_id, topic) = match event {
+            StacksTransactionEvent::SmartContractEvent(data) => {
+                let topic = match data.value {
+                    Value::Tuple(ref tuple) => CONTRACT_EVENT_TOPIC_FIELDS
+                        .iter()
+                        .find_map(|field| tuple.data_map.get(*field))
+                        .map(StacksChainState::contract_event_topic_string),
+                    _ => None,
+                };
+                (&data.key.0, topic)
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => (
+                &data.asset_identifier.contract_identifier,
+                Some("nft_transfer_event".to_string()),
+            ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(data)) => (
+                &data.asset_identifier.contract_identifier,
+                Some("nft_mint_event".to_string()),
+            ),
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => (
+                &data.asset_identifier.contract_identifier,
+                Some("nft_burn_event".to_string()),
+            ),
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => (
+                &data.asset_identifier.contract_identifier,
+                Some("ft_transfer_event".to_string()),
+            ),
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(data)) => (
+                &data.asset_identifier.contract_identifier,
+                Some("ft_mint_event".to_string()),
+            ),
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => (
+                &data.asset_identifier.contract_identifier,
+                Some("ft_burn_event".to_string()),
+            ),
+            StacksTransactionEvent::STXEvent(_) => {
+                return None;
+            }
+        };
+        Some((contract_id.clone(), topic))
+    }
+
+    /// Render a topic value.  Strings are stored without their Clarity quoting, so a printed
+    /// `{ topic: "transfer" }` has the topic `transfer`.
+    fn contract_event_topic_string(value: &Value) -> String {
+        match value {
+            Value::Sequence(SequenceData::String(CharType::ASCII(data))) => {
+                String::from_utf8_lossy(&data.data).to_string()
+            }
+            Value::Sequence(SequenceData::String(CharType::UTF8(data))) => {
+                String::from_utf8_lossy(&data.data.concat()).to_string()
+            }
+            _ => value.to_string(),
+        }
+    }
+
+    /// Record the contract events emitted by the transactions processed in the given block
+    pub fn log_contract_events(
+        tx: &DBTx,
+        tip: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), db_error> {
+        let index_block_hash = tip.index_block_hash();
+        let block_height = u64_to_sql(tip.stacks_block_height)?;
+
+        let sql = "INSERT OR REPLACE INTO contract_events
+            (contract_id, topic, index_block_hash, block_height, txid, tx_index, event_index, event)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)";
+
+        for receipt in receipts.iter() {
+            let txid = receipt.transaction.txid();
+            let committed = !receipt.post_condition_aborted;
+            for (event_index, event) in receipt.events.iter().enumerate() {
+                let (contract_id, topic) = match StacksChainState::contract_event_key(event) {
+                    Some(key) => key,
+                    None => {
+                        continue;
+                    }
+                };
+
+                let event_json = event
+                    .json_serialize(event_index, &txid, committed)
+                    .to_string();
+                let event_index = event_index as u32;
+                let args: &[&dyn ToSql] = &[
+                    &contract_id.to_string(),
+                    &topic,
+                    &index_block_hash,
+                    &block_height,
+                    &txid,
+                    &receipt.tx_index,
+                    &event_index,
+                    &event_json,
+                ];
+                tx.execute(sql, args)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Load a page of the logged contract events that match `filter` in the fork ending at
+    /// `tip`, newest first, starting after `after`.  Also returns the cursor for the next page,
+    /// if there may be one.
+    pub fn get_contract_events(
+        &self,
+        tip: &StacksBlockId,
+        filter: &ContractEventFilter,
+        after: Option<&IndexCursor>,
+        page_size: usize,
+    ) -> Result<(Vec<ContractEvent>, Option<IndexCursor>), Error> {
+        let contract_id = filter.contract_id.as_ref().map(|id| id.to_string());
+        let from_height = filter.from_height.map(u64_to_sql).transpose()?;
+        let to_height = filter.to_height.map(u64_to_sql).transpose()?;
+
+        let mut clauses = vec![];
+        let mut args: Vec<&dyn ToSql> = vec![];
+        if let Some(ref contract_id) = contract_id {
+            clauses.push("contract_id = ?");
+            args.push(contract_id);
+        }
+        if let Some(ref topic) = filter.topic {
+            clauses.push("topic = ?");
+            args.push(topic);
+        }
+        if let Some(ref from_height) = from_height {
+            clauses.push("block_height >= ?");
+            args.push(from_height);
+        }
+        if let Some(ref to_height) = to_height {
+            clauses.push("block_height <= ?");
+            args.push(to_height);
+        }
+
+        self.load_fork_rows_after(
+            tip,
+            "contract_events",
+            &["block_height", "tx_index", "event_index"],
+            &clauses,
+            &args,
+            after,
+            page_size,
+            |row| &row.index_block_hash,
+            ContractEvent::cursor,
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::chainstate::burn::ConsensusHash;
+    use crate::chainstate::stacks::db::blocks::test::process_sample_microblock_stream;
+    use crate::chainstate::stacks::db::test::*;
+    use clarity::vm::costs::ExecutionCost;
+    use clarity::vm::events::*;
+    use clarity::vm::types::*;
+
+    use super::*;
+
+    fn make_receipt(
+        txid_byte: u8,
+        tx_index: u32,
+        events: Vec<StacksTransactionEvent>,
+    ) -> StacksTransactionReceipt {
+        StacksTransactionReceipt {
+            transaction: TransactionOrigin::Burn(Txid([txid_byte; 32])),
+            events,
+            post_condition_aborted: false,
+            result: Value::okay_true(),
+            stx_burned: 0,
+            contract_analysis: None,
+            execution_cost: ExecutionCost::zero(),
+            microblock_header: None,
+            tx_index,
+        }
+    }
+
+    fn make_print_event(
+        contract_id: &QualifiedContractIdentifier,
+        value: Value,
+    ) -> StacksTransactionEvent {
+        StacksTransactionEvent::SmartContractEvent(SmartContractEventData {
+            key: (contract_id.clone(), "print".to_string()),
+            value,
+        })
+    }
+
+    fn make_topic_tuple(field: &str, topic: &str) -> Value {
+        Value::Tuple(
+            TupleData::from_data(vec![
+                (
+                    field.into(),
+                    Value::string_ascii_from_bytes(topic.as_bytes().to_vec()).unwrap(),
+                ),
+                ("amount".into(), Value::UInt(1)),
+            ])
+            .unwrap(),
+        )
+    }
+
+    fn make_ft_mint_event(contract_id: &QualifiedContractIdentifier) -> StacksTransactionEvent {
+        StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(FTMintEventData {
+            asset_identifier: AssetIdentifier {
+                contract_identifier: contract_id.clone(),
+                asset_name: "token".into(),
+            },
+            recipient: PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+            amount: 100,
+        }))
+    }
+
+    fn make_stx_transfer_event() -> StacksTransactionEvent {
+        StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(STXTransferEventData {
+            sender: PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap(),
+            recipient: PrincipalData::parse("ST1RFD5Q2QPK3E0F08HG9XDX7SSC7CNRS0QR0SGEV").unwrap(),
+            amount: 1,
+        }))
+    }
+
+    #[test]
+    fn test_contract_event_key() {
+        let contract_id = QualifiedContractIdentifier::parse(
+            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.exchange",
+        )
+        .unwrap();
+
+        // topic is taken from the tuple's topic field, then its event field
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_print_event(
+                &contract_id,
+                make_topic_tuple("topic", "transfer")
+            )),
+            Some((contract_id.clone(), Some("transfer".to_string())))
+        );
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_print_event(
+                &contract_id,
+                make_topic_tuple("event", "new-order")
+            )),
+            Some((contract_id.clone(), Some("new-order".to_string())))
+        );
+        let both = Value::Tuple(
+            TupleData::from_data(vec![
+                ("event".into(), Value::UInt(2)),
+                ("topic".into(), Value::UInt(1)),
+            ])
+            .unwrap(),
+        );
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_print_event(&contract_id, both)),
+            Some((contract_id.clone(), Some("u1".to_string())))
+        );
+        let utf8 = Value::Tuple(
+            TupleData::from_data(vec![(
+                "topic".into(),
+                Value::string_utf8_from_bytes("caf\u{e9}".as_bytes().to_vec()).unwrap(),
+            )])
+            .unwrap(),
+        );
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_print_event(&contract_id, utf8)),
+            Some((contract_id.clone(), Some("caf\u{e9}".to_string())))
+        );
+
+        // no topic
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_print_event(
+                &contract_id,
+                make_topic_tuple("kind", "transfer")
+            )),
+            Some((contract_id.clone(), None))
+        );
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_print_event(&contract_id, Value::UInt(1))),
+            Some((contract_id.clone(), None))
+        );
+
+        // token events are keyed by their asset's contract and type
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_ft_mint_event(&contract_id)),
+            Some((contract_id.clone(), Some("ft_mint_event".to_string())))
+        );
+
+        // STX events are not logged
+        assert_eq!(
+            StacksChainState::contract_event_key(&make_stx_transfer_event()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_log_and_filter_contract_events() {
+        let mut chainstate = instantiate_chainstate(false, 0x80000000, "contract-event-log");
+        let exchange = QualifiedContractIdentifier::parse(
+            "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.exchange",
+        )
+        .unwrap();
+        let token =
+            QualifiedContractIdentifier::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.token")
+                .unwrap();
+
+        // log everything under the boot block, which is in the MARF, but at different heights
+        // so the height filters have something to select.
+        let genesis = StacksChainState::get_genesis_header_info(chainstate.db()).unwrap();
+        let tip = genesis.index_block_hash();
+        let mut later = genesis.clone();
+        later.stacks_block_height = 5;
+
+        // a block that isn't in the MARF, as if it had been orphaned
+        let mut orphan = genesis.clone();
+        orphan.consensus_hash = ConsensusHash([0x09; 20]);
+
+        let tx = chainstate.db_tx_begin().unwrap();
+        StacksChainState::log_contract_events(
+            &tx,
+            &genesis,
+            &[make_receipt(
+                1,
+                0,
+                vec![
+                    make_print_event(&exchange, make_topic_tuple("topic", "transfer")),
+                    make_stx_transfer_event(),
+                    make_ft_mint_event(&token),
+                ],
+            )],
+        )
+        .unwrap();
+        StacksChainState::log_contract_events(
+            &tx,
+            &later,
+            &[make_receipt(
+                2,
+                3,
+                vec![
+                    make_print_event(&exchange, make_topic_tuple("event", "new-order")),
+                    make_print_event(&exchange, Value::UInt(1)),
+                ],
+            )],
+        )
+        .unwrap();
+        StacksChainState::log_contract_events(
+            &tx,
+            &orphan,
+            &[make_receipt(
+                3,
+                0,
+                vec![make_print_event(
+                    &exchange,
+                    make_topic_tuple("topic", "transfer"),
+                )],
+            )],
+        )
+        .unwrap();
+        tx.commit().unwrap();
+
+        let keys = |events: Vec<ContractEvent>| -> Vec<(Txid, u32)> {
+            events
+                .into_iter()
+                .map(|event| (event.txid, event.event_index))
+                .collect()
+        };
+
+        // everything in the fork, newest first, without the STX event or the orphan
+        let (all, next) = chainstate
+            .get_contract_events(&tip, &ContractEventFilter::default(), None, 50)
+            .unwrap();
+        assert_eq!(
+            keys(all.clone()),
+            vec![
+                (Txid([2; 32]), 1),
+                (Txid([2; 32]), 0),
+                (Txid([1; 32]), 2),
+                (Txid([1; 32]), 0),
+            ]
+        );
+        assert_eq!(next, None);
+        assert_eq!(all[3].contract_id, exchange);
+        assert_eq!(all[3].topic, Some("transfer".to_string()));
+        assert_eq!(all[3].index_block_hash, tip);
+        assert_eq!(all[3].block_height, 0);
+        assert_eq!(all[3].tx_index, 0);
+        assert_eq!(all[3].event["type"], "contract_event");
+        assert_eq!(all[2].contract_id, token);
+        assert_eq!(all[2].topic, Some("ft_mint_event".to_string()));
+        assert_eq!(all[1].block_height, 5);
+        assert_eq!(all[1].tx_index, 3);
+        assert_eq!(all[0].topic, None);
+
+        // by contract
+        let filter = ContractEventFilter {
+            contract_id: Some(exchange.clone()),
+            ..ContractEventFilter::default()
+        };
+        assert_eq!(
+            keys(
+                chainstate
+                    .get_contract_events(&tip, &filter, None, 50)
+                    .unwrap()
+                    .0
+            ),
+            vec![(Txid([2; 32]), 1), (Txid([2; 32]), 0), (Txid([1; 32]), 0)]
+        );
+
+        // by topic
+        let filter = ContractEventFilter {
+            topic: Some("transfer".to_string()),
+            ..ContractEventFilter::default()
+        };
+        assert_eq!(
+            keys(
+                chainstate
+                    .get_contract_events(&tip, &filter, None, 50)
+                    .unwrap()
+                    .0
+            ),
+            vec![(Txid([1; 32]), 0)]
+        );
+
+        // by contract and topic
+        let filter = ContractEventFilter {
+            contract_id: Some(token.clone()),
+            topic: Some("transfer".to_string()),
+            ..ContractEventFilter::default()
+        };
+        assert!(chainstate
+            .get_contract_events(&tip, &filter, None, 50)
+            .unwrap()
+            .0
+            .is_empty());
+
+        // by height
+        let filter = ContractEventFilter {
+            from_height: Some(1),
+            ..ContractEventFilter::default()
+        };
+        assert_eq!(
+            keys(
+                chainstate
+                    .get_contract_events(&tip, &filter, None, 50)
+                    .unwrap()
+                    .0
+            ),
+            vec![(Txid([2; 32]), 1), (Txid([2; 32]), 0)]
+        );
+        let filter = ContractEventFilter {
+            to_height: Some(4),
+            ..ContractEventFilter::default()
+        };
+        assert_eq!(
+            keys(
+                chainstate
+                    .get_contract_events(&tip, &filter, None, 50)
+                    .unwrap()
+                    .0
+            ),
+            vec![(Txid([1; 32]), 2), (Txid([1; 32]), 0)]
+        );
+
+        // paged, following the cursor
+        let filter = ContractEventFilter::default();
+        let (page, next) = chainstate
+            .get_contract_events(&tip, &filter, None, 3)
+            .unwrap();
+        assert_eq!(keys(page.clone()), keys(all[0..3].to_vec()));
+        assert_eq!(next, Some(page[2].cursor()));
+        let (page, next) = chainstate
+            .get_contract_events(&tip, &filter, next.as_ref(), 3)
+            .unwrap();
+        assert_eq!(keys(page), keys(all[3..].to_vec()));
+        assert_eq!(next, None);
+
+        // a cursor past the height filter's range finds nothing
+        let filter = ContractEventFilter {
+            from_height: Some(1),
+            ..ContractEventFilter::default()
+        };
+        let (page, next) = chainstate
+            .get_contract_events(&tip, &filter, Some(&all[1].cursor()), 50)
+            .unwrap();
+        assert!(page.is_empty());
+        assert_eq!(next, None);
+    }
+
+    #[test]
+    fn test_contract_events_across_microblocks() {
+        let mut chainstate =
+            instantiate_chainstate(false, 0x80000000, "contract-event-log-microblocks");
+        let privk = StacksPrivateKey::from_hex(
+            "6d430bb91222408e7706c9001cfaeb91b08c2be6d5ac95779ab52c6b431950e001",
+        )
+        .unwrap();
+
+        // three microblocks of two transactions each, and one print event per transaction
+        let receipts = process_sample_microblock_stream(&mut chainstate, &privk, 3, 2);
+        let genesis = StacksChainState::get_genesis_header_info(chainstate.db()).unwrap();
+        let tip = genesis.index_block_hash();
+        let tx = chainstate.db_tx_begin().unwrap();
+        StacksChainState::log_contract_events(&tx, &genesis, &receipts).unwrap();
+        tx.commit().unwrap();
+
+        let txids = |events: Vec<ContractEvent>| -> Vec<Txid> {
+            events.into_iter().map(|event| event.txid).collect()
+        };
+        let expected: Vec<Txid> = receipts
+            .iter()
+            .rev()
+            .map(|receipt| receipt.transaction.txid())
+            .collect();
+
+        // the first page ends partway through the second microblock, and the next page
+        // picks up with the rest of it
+        let filter = ContractEventFilter {
+            topic: Some("hello".to_string()),
+            ..ContractEventFilter::default()
+        };
+        let (page, next) = chainstate
+            .get_contract_events(&tip, &filter, None, 3)
+            .unwrap();
+        assert_eq!(txids(page), expected[0..3].to_vec());
+        let (page, next) = chainstate
+            .get_contract_events(&tip, &filter, next.as_ref(), 3)
+            .unwrap();
+        assert_eq!(txids(page), expected[3..].to_vec());
+        let (page, next) = chainstate
+            .get_contract_events(&tip, &filter, next.as_ref(), 3)
+            .unwrap();
+        assert!(page.is_empty());
+        assert_eq!(next, None);
+    }
+}
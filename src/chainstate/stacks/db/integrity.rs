@@ -0,0 +1,1062 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Offline consistency checks over an existing node's chainstate.  These never change the
+//! databases; they walk the canonical Stacks fork and confirm that the headers, the staging
+//! tables, the chunk store, both MARFs and the sortition DB all agree with one another.
+//! STX are checked both in aggregate, via the liquid supply, and per account: each block is
+//! replayed, and every account it touched must have changed by exactly the STX its events,
+//! fees and matured rewards moved.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use rusqlite::types::ToSql;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::events::TransactionOrigin;
+use crate::chainstate::stacks::index::marf::{MarfConnection, MARF};
+use crate::chainstate::stacks::Error;
+use crate::chainstate::stacks::*;
+use crate::clarity_vm::clarity::ClarityConnection;
+use crate::util_lib::boot::boot_code_id;
+use crate::util_lib::db::Error as db_error;
+use crate::util_lib::db::{query_row_columns, query_rows};
+use clarity::vm::events::{STXEventType, StacksTransactionEvent};
+use clarity::vm::types::{OptionalData, PrincipalData, SequenceData, StandardPrincipalData, Value};
+
+use stacks_common::types::chainstate::{BlockHeaderHash, SortitionId, StacksBlockId, TrieHash};
+
+/// Something found wrong with a block on the canonical fork
+#[derive(Debug, Clone, PartialEq)]
+pub enum IntegrityProblem {
+    /// The block's header row is missing
+    MissingHeader,
+    /// The header row does not agree with itself or with its parent's header
+    BadHeader(String),
+    /// The Clarity MARF's root hash for this block (if it has one) is not the header's
+    /// `state_index_root`
+    StateRootMismatch {
+        expected: TrieHash,
+        actual: Option<TrieHash>,
+    },
+    /// The headers MARF's root hash for this block (if it has one) is not the header's
+    /// `index_root`
+    IndexRootMismatch {
+        expected: TrieHash,
+        actual: Option<TrieHash>,
+    },
+    /// The block is missing from, or disagrees with, the staging tables or the chunk store
+    BadStagingBlock(String),
+    /// The microblock stream this block confirms is missing or does not match the header
+    BadMicroblocks(String),
+    /// The block does not match the sortition that selected it
+    BadSortition(String),
+    /// No miner payment is recorded for the block whose coinbase matures in this block
+    MissingMinerPayment(StacksBlockId),
+    /// The Clarity state of this block (or its parent) is missing
+    MissingClarityState(StacksBlockId),
+    /// The Clarity state holds a value that does not have the type block processing gives it
+    BadClarityState(String),
+    /// The liquid STX supply did not change by the recorded rewards, unlocks and burns
+    LiquidSupplyMismatch { expected: u128, actual: u128 },
+    /// The block does not replay on top of its parent's state
+    ReplayFailed(String),
+    /// An account's STX balance did not change by the STX that the replayed block moved
+    AccountBalanceMismatch {
+        principal: PrincipalData,
+        expected: u128,
+        actual: u128,
+    },
+}
+
+impl fmt::Display for IntegrityProblem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            IntegrityProblem::MissingHeader => write!(f, "block header is missing"),
+            IntegrityProblem::BadHeader(ref s) => write!(f, "bad block header: {}", s),
+            IntegrityProblem::StateRootMismatch {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "Clarity MARF root is {:?}, but state_index_root is {}",
+                actual, expected
+            ),
+            IntegrityProblem::IndexRootMismatch {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "headers MARF root is {:?}, but index_root is {}",
+                actual, expected
+            ),
+            IntegrityProblem::BadStagingBlock(ref s) => write!(f, "bad staging block: {}", s),
+            IntegrityProblem::BadMicroblocks(ref s) => {
+                write!(f, "bad confirmed microblocks: {}", s)
+            }
+            IntegrityProblem::BadSortition(ref s) => write!(f, "bad sortition: {}", s),
+            IntegrityProblem::MissingMinerPayment(ref block_id) => {
+                write!(
+                    f,
+                    "no miner payment recorded for matured block {}",
+                    block_id
+                )
+            }
+            IntegrityProblem::MissingClarityState(ref block_id) => {
+                write!(f, "no Clarity state for block {}", block_id)
+            }
+            IntegrityProblem::BadClarityState(ref s) => write!(f, "bad Clarity state: {}", s),
+            IntegrityProblem::LiquidSupplyMismatch {
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "liquid STX supply is {}, but recorded rewards, unlocks and burns give {}",
+                actual, expected
+            ),
+            IntegrityProblem::ReplayFailed(ref s) => write!(f, "block does not replay: {}", s),
+            IntegrityProblem::AccountBalanceMismatch {
+                ref principal,
+                ref expected,
+                ref actual,
+            } => write!(
+                f,
+                "STX balance of {} is {}, but the replayed block gives {}",
+                principal, actual, expected
+            ),
+        }
+    }
+}
+
+/// The first block on the canonical fork that failed a check
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainstateInconsistency {
+    pub index_block_hash: StacksBlockId,
+    pub stacks_block_height: u64,
+    pub problem: IntegrityProblem,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainstateIntegrityReport {
+    /// The canonical Stacks chain tip the walk started from
+    pub tip: StacksBlockId,
+    /// How many blocks passed every check
+    pub blocks_checked: u64,
+    /// The lowest block on the canonical fork that failed a check, if any
+    pub first_inconsistency: Option<ChainstateInconsistency>,
+}
+
+impl StacksChainState {
+    /// Walk the canonical Stacks fork (as chosen by `sortdb`) from the genesis block up to its
+    /// tip, and check at every block at or above `start_height` that:
+    /// * the Clarity MARF's root matches the header's `state_index_root`, and the headers MARF's
+    /// root matches its `index_root`;
+    /// * the block and the microblocks it confirms are in the staging tables and chunk store, and
+    /// hash to what the header says;
+    /// * the header links to its parent, to the sortition that selected it, and to that
+    /// sortition's burnchain block;
+    /// * the liquid STX supply changed by exactly the matured coinbase, the unlocked genesis STX
+    /// and the burned STX recorded for the block;
+    /// * every account the block touched holds the balance it had in the parent block, plus or
+    /// minus the STX moved by the block's replayed transfer, mint and burn events, transaction
+    /// fees and matured miner rewards.  Only touched accounts are checked at each block, so a
+    /// balance that was changed out of band is reported at the last block that moved it.
+    /// Stops at the first block that fails a check.
+    pub fn check_integrity(
+        &mut self,
+        sortdb: &SortitionDB,
+        start_height: u64,
+    ) -> Result<ChainstateIntegrityReport, Error> {
+        let canonical_sn = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        let tip = StacksBlockHeader::make_index_block_hash(
+            &canonical_sn.canonical_stacks_tip_consensus_hash,
+            &canonical_sn.canonical_stacks_tip_hash,
+        );
+
+        // load the fork from the tip down, so we can check it from the bottom up
+        let mut chain: Vec<StacksHeaderInfo> = vec![];
+        let mut cursor = tip.clone();
+        let mut cursor_height = canonical_sn.canonical_stacks_tip_height;
+        let mut first_inconsistency = None;
+        loop {
+            let header_info =
+                match StacksChainState::get_stacks_block_header_info_by_index_block_hash(
+                    self.db(),
+                    &cursor,
+                ) {
+                    Ok(Some(header_info)) => header_info,
+                    Ok(None) => {
+                        first_inconsistency = Some(ChainstateInconsistency {
+                            index_block_hash: cursor,
+                            stacks_block_height: cursor_height,
+                            problem: IntegrityProblem::MissingHeader,
+                        });
+                        break;
+                    }
+                    Err(Error::DBError(db_error::ParseError)) => {
+                        first_inconsistency = Some(ChainstateInconsistency {
+                            index_block_hash: cursor,
+                            stacks_block_height: cursor_height,
+                            problem: IntegrityProblem::BadHeader(
+                                "header row does not hash to its block hash".into(),
+                            ),
+                        });
+                        break;
+                    }
+                    Err(e) => {
+                        return Err(e);
+                    }
+                };
+
+            if header_info.stacks_block_height == 0 {
+                chain.push(header_info);
+                break;
+            }
+
+            let parent_id = match StacksChainState::get_parent_block_id(self.db(), &cursor)? {
+                Some(parent_id) => parent_id,
+                None => {
+                    first_inconsistency = Some(ChainstateInconsistency {
+                        index_block_hash: cursor,
+                        stacks_block_height: cursor_height,
+                        problem: IntegrityProblem::BadHeader(
+                            "header row has no parent block".into(),
+                        ),
+                    });
+                    break;
+                }
+            };
+            cursor_height = header_info.stacks_block_height - 1;
+            cursor = parent_id;
+            chain.push(header_info);
+        }
+        chain.reverse();
+
+        if first_inconsistency.is_some() {
+            // nothing below a missing or unreadable header can be linked to the tip, so that is
+            // the lowest inconsistency we can report.
+            return Ok(ChainstateIntegrityReport {
+                tip,
+                blocks_checked: 0,
+                first_inconsistency,
+            });
+        }
+
+        let heights: HashMap<u64, StacksBlockId> = chain
+            .iter()
+            .map(|header_info| {
+                (
+                    header_info.stacks_block_height,
+                    header_info.index_block_hash(),
+                )
+            })
+            .collect();
+
+        let mut clarity_marf = StacksChainState::open_index(&self.clarity_state_index_path)?;
+
+        let mut blocks_checked = 0;
+        for (i, header_info) in chain.iter().enumerate() {
+            if header_info.stacks_block_height < start_height {
+                continue;
+            }
+
+            let parent_opt = if i > 0 { Some(&chain[i - 1]) } else { None };
+            let problem_opt = self.check_block_integrity(
+                sortdb,
+                &canonical_sn.sortition_id,
+                &mut clarity_marf,
+                &heights,
+                parent_opt,
+                header_info,
+            )?;
+
+            if let Some(problem) = problem_opt {
+                first_inconsistency = Some(ChainstateInconsistency {
+                    index_block_hash: header_info.index_block_hash(),
+                    stacks_block_height: header_info.stacks_block_height,
+                    problem,
+                });
+                break;
+            }
+
+            blocks_checked += 1;
+            if blocks_checked % 1000 == 0 {
+                info!(
+                    "Checked {} blocks (up to height {})",
+                    blocks_checked, header_info.stacks_block_height
+                );
+            }
+        }
+
+        Ok(ChainstateIntegrityReport {
+            tip,
+            blocks_checked,
+            first_inconsistency,
+        })
+    }
+
+    /// Run every check on a single block.  `parent_opt` is None only for the genesis block, for
+    /// which only the headers MARF root can be checked.
+    fn check_block_integrity(
+        &mut self,
+        sortdb: &SortitionDB,
+        canonical_sortition_id: &SortitionId,
+        clarity_marf: &mut MARF<StacksBlockId>,
+        heights: &HashMap<u64, StacksBlockId>,
+        parent_opt: Option<&StacksHeaderInfo>,
+        header_info: &StacksHeaderInfo,
+    ) -> Result<Option<IntegrityProblem>, Error> {
+        let block_id = header_info.index_block_hash();
+        let header = &header_info.anchored_header;
+
+        // the headers MARF must commit to this block
+        let index_root = self.state_index.get_root_hash_at(&block_id).ok();
+        if index_root.as_ref() != Some(&header_info.index_root) {
+            return Ok(Some(IntegrityProblem::IndexRootMismatch {
+                expected: header_info.index_root.clone(),
+                actual: index_root,
+            }));
+        }
+
+        let parent = match parent_opt {
+            Some(parent) => parent,
+            None => {
+                return Ok(None);
+            }
+        };
+
+        // the Clarity MARF must commit to this block's state.  The genesis header carries no
+        // state root, so this only applies to its descendants.
+        let state_root = clarity_marf.get_root_hash_at(&block_id).ok();
+        if state_root.as_ref() != Some(&header.state_index_root) {
+            return Ok(Some(IntegrityProblem::StateRootMismatch {
+                expected: header.state_index_root.clone(),
+                actual: state_root,
+            }));
+        }
+
+        if let Some(problem) = StacksChainState::check_header_linkage(parent, header_info) {
+            return Ok(Some(problem));
+        }
+        if let Some(problem) = self.check_staging_block(parent, header_info)? {
+            return Ok(Some(problem));
+        }
+        if let Some(problem) = self.check_confirmed_microblocks(parent, header_info)? {
+            return Ok(Some(problem));
+        }
+        if let Some(problem) =
+            StacksChainState::check_block_sortition(sortdb, canonical_sortition_id, header_info)?
+        {
+            return Ok(Some(problem));
+        }
+        if let Some(problem) = self.check_liquid_supply(sortdb, heights, parent, header_info)? {
+            return Ok(Some(problem));
+        }
+        self.check_account_balances(sortdb, parent, header_info)
+    }
+
+    fn check_header_linkage(
+        parent: &StacksHeaderInfo,
+        header_info: &StacksHeaderInfo,
+    ) -> Option<IntegrityProblem> {
+        let header = &header_info.anchored_header;
+        let parent_hash = parent.anchored_header.block_hash();
+        if header.parent_block != parent_hash {
+            return Some(IntegrityProblem::BadHeader(format!(
+                "parent block is {}, but parent header hashes to {}",
+                &header.parent_block, &parent_hash
+            )));
+        }
+        if header_info.stacks_block_height != parent.stacks_block_height + 1 {
+            return Some(IntegrityProblem::BadHeader(format!(
+                "height is {}, but parent height is {}",
+                header_info.stacks_block_height, parent.stacks_block_height
+            )));
+        }
+        None
+    }
+
+    /// The block must be processed in the staging tables, and the chunk store must hold exactly
+    /// the block this header describes.
+    fn check_staging_block(
+        &self,
+        parent: &StacksHeaderInfo,
+        header_info: &StacksHeaderInfo,
+    ) -> Result<Option<IntegrityProblem>, Error> {
+        let block_id = header_info.index_block_hash();
+        let header = &header_info.anchored_header;
+        let block_hash = header.block_hash();
+
+        let stored_block_hashes = query_row_columns::<BlockHeaderHash, _>(
+            self.db(),
+            "SELECT block_hash FROM block_headers WHERE index_block_hash = ?1",
+            &[&block_id as &dyn ToSql],
+            "block_hash",
+        )?;
+        if stored_block_hashes != vec![block_hash.clone()] {
+            return Ok(Some(IntegrityProblem::BadHeader(format!(
+                "block_hash column is {:?}, but header hashes to {}",
+                &stored_block_hashes, &block_hash
+            ))));
+        }
+
+        let staging_block = match StacksChainState::load_staging_block_info(self.db(), &block_id)? {
+            Some(staging_block) => staging_block,
+            None => {
+                return Ok(Some(IntegrityProblem::BadStagingBlock(
+                    "not in staging_blocks".into(),
+                )));
+            }
+        };
+
+        if !staging_block.processed {
+            return Ok(Some(IntegrityProblem::BadStagingBlock(
+                "not marked as processed".into(),
+            )));
+        }
+        if staging_block.anchored_block_hash != block_hash
+            || staging_block.consensus_hash != header_info.consensus_hash
+        {
+            return Ok(Some(IntegrityProblem::BadStagingBlock(format!(
+                "staging row is for {}/{}",
+                &staging_block.consensus_hash, &staging_block.anchored_block_hash
+            ))));
+        }
+        // children of the genesis block record the first sortition's consensus hash as their
+        // parent's, not the genesis header's.
+        if staging_block.parent_anchored_block_hash != header.parent_block
+            || (parent.stacks_block_height > 0
+                && staging_block.parent_consensus_hash != parent.consensus_hash)
+        {
+            return Ok(Some(IntegrityProblem::BadStagingBlock(format!(
+                "staging row's parent is {}/{}",
+                &staging_block.parent_consensus_hash, &staging_block.parent_anchored_block_hash
+            ))));
+        }
+        if staging_block.parent_microblock_hash != header.parent_microblock
+            || staging_block.parent_microblock_seq != header.parent_microblock_sequence
+        {
+            return Ok(Some(IntegrityProblem::BadStagingBlock(format!(
+                "staging row confirms microblock {} (seq {})",
+                &staging_block.parent_microblock_hash, staging_block.parent_microblock_seq
+            ))));
+        }
+
+        match StacksChainState::load_block(
+            &self.blocks_path,
+            &header_info.consensus_hash,
+            &block_hash,
+        ) {
+            Ok(Some(block)) => {
+                if block.header != *header {
+                    return Ok(Some(IntegrityProblem::BadStagingBlock(format!(
+                        "stored block hashes to {}",
+                        &block.block_hash()
+                    ))));
+                }
+            }
+            Ok(None) => {
+                return Ok(Some(IntegrityProblem::BadStagingBlock(
+                    "stored block was invalidated".into(),
+                )));
+            }
+            Err(e) => {
+                return Ok(Some(IntegrityProblem::BadStagingBlock(format!(
+                    "failed to load stored block: {:?}",
+                    &e
+                ))));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// The microblocks the block confirms must all be processed, and must form a chain from the
+    /// parent block to the header's `parent_microblock`.
+    fn check_confirmed_microblocks(
+        &self,
+        parent: &StacksHeaderInfo,
+        header_info: &StacksHeaderInfo,
+    ) -> Result<Option<IntegrityProblem>, Error> {
+        let header = &header_info.anchored_header;
+        if header.parent_microblock == EMPTY_MICROBLOCK_PARENT_HASH
+            && header.parent_microblock_sequence == 0
+        {
+            return Ok(None);
+        }
+
+        let parent_hash = parent.anchored_header.block_hash();
+        let microblocks = match StacksChainState::load_processed_microblock_stream_fork(
+            self.db(),
+            &parent.consensus_hash,
+            &parent_hash,
+            &header.parent_microblock,
+        )? {
+            Some(microblocks) if microblocks.len() > 0 => microblocks,
+            _ => {
+                return Ok(Some(IntegrityProblem::BadMicroblocks(format!(
+                    "no processed stream ending in {}",
+                    &header.parent_microblock
+                ))));
+            }
+        };
+
+        let mut prev_hash = parent_hash;
+        for (i, microblock) in microblocks.iter().enumerate() {
+            if microblock.header.prev_block != prev_hash || microblock.header.sequence as usize != i
+            {
+                return Ok(Some(IntegrityProblem::BadMicroblocks(format!(
+                    "microblock {} (seq {}) does not follow {}",
+                    &microblock.block_hash(),
+                    microblock.header.sequence,
+                    &prev_hash
+                ))));
+            }
+            prev_hash = microblock.block_hash();
+        }
+
+        if prev_hash != header.parent_microblock
+            || microblocks.len() != header.parent_microblock_sequence as usize + 1
+        {
+            return Ok(Some(IntegrityProblem::BadMicroblocks(format!(
+                "stream ends in {} (seq {})",
+                &prev_hash,
+                microblocks.len() - 1
+            ))));
+        }
+
+        Ok(None)
+    }
+
+    /// The block must have won a sortition on the canonical burnchain fork, and the header must
+    /// record that sortition's burnchain block.
+    fn check_block_sortition(
+        sortdb: &SortitionDB,
+        canonical_sortition_id: &SortitionId,
+        header_info: &StacksHeaderInfo,
+    ) -> Result<Option<IntegrityProblem>, Error> {
+        let block_hash = header_info.anchored_header.block_hash();
+        let sn = match SortitionDB::get_block_snapshot_consensus(
+            sortdb.conn(),
+            &header_info.consensus_hash,
+        )? {
+            Some(sn) => sn,
+            None => {
+                return Ok(Some(IntegrityProblem::BadSortition(format!(
+                    "no sortition with consensus hash {}",
+                    &header_info.consensus_hash
+                ))));
+            }
+        };
+
+        if !sn.sortition || sn.winning_stacks_block_hash != block_hash {
+            return Ok(Some(IntegrityProblem::BadSortition(format!(
+                "sortition {} picked {}",
+                &sn.consensus_hash, &sn.winning_stacks_block_hash
+            ))));
+        }
+        if sn.burn_header_hash != header_info.burn_header_hash
+            || sn.block_height != header_info.burn_header_height as u64
+        {
+            return Ok(Some(IntegrityProblem::BadSortition(format!(
+                "header records burnchain block {} at {}, but sortition is in {} at {}",
+                &header_info.burn_header_hash,
+                header_info.burn_header_height,
+                &sn.burn_header_hash,
+                sn.block_height
+            ))));
+        }
+
+        let ic = sortdb.index_handle(canonical_sortition_id);
+        let canonical_ancestor =
+            SortitionDB::get_ancestor_snapshot(&ic, sn.block_height, canonical_sortition_id)?;
+        if canonical_ancestor.map(|ancestor| ancestor.consensus_hash)
+            != Some(header_info.consensus_hash.clone())
+        {
+            return Ok(Some(IntegrityProblem::BadSortition(format!(
+                "sortition {} is not on the canonical burnchain fork",
+                &sn.consensus_hash
+            ))));
+        }
+
+        Ok(None)
+    }
+
+    /// The liquid STX supply only changes when a coinbase matures, when genesis STX unlock, and
+    /// when STX are burned, and each of these is recorded.  Recompute the change the same way
+    /// block processing does and compare it to what the Clarity DB holds.  Transfers between
+    /// accounts leave the supply unchanged; `check_account_balances` covers those.
+    fn check_liquid_supply(
+        &mut self,
+        sortdb: &SortitionDB,
+        heights: &HashMap<u64, StacksBlockId>,
+        parent: &StacksHeaderInfo,
+        header_info: &StacksHeaderInfo,
+    ) -> Result<Option<IntegrityProblem>, Error> {
+        let block_id = header_info.index_block_hash();
+        let parent_id = parent.index_block_hash();
+        let burn_conn = sortdb.index_conn();
+
+        // coinbases that matured in this block
+        let mut minted: u128 = 0;
+        if parent.stacks_block_height > MINER_REWARD_MATURITY {
+            let reward_height = parent.stacks_block_height - MINER_REWARD_MATURITY;
+            let reward_block_id = match heights.get(&reward_height) {
+                Some(reward_block_id) => reward_block_id.clone(),
+                None => {
+                    return Ok(Some(IntegrityProblem::BadHeader(format!(
+                        "no block at height {} below this one",
+                        reward_height
+                    ))));
+                }
+            };
+            let mut miners = query_rows::<MinerPaymentSchedule, _>(
+                self.db(),
+                "SELECT * FROM payments WHERE index_block_hash = ?1 ORDER BY vtxindex ASC",
+                &[&reward_block_id as &dyn ToSql],
+            )?;
+            let users = if miners.len() > 1 {
+                miners.split_off(1)
+            } else {
+                vec![]
+            };
+            let miner = match miners.pop() {
+                Some(miner) if miner.miner => miner,
+                _ => {
+                    return Ok(Some(IntegrityProblem::MissingMinerPayment(reward_block_id)));
+                }
+            };
+
+            let poison_reporter =
+                match self.with_read_only_clarity_tx(&burn_conn, &parent_id, |conn| {
+                    StacksChainState::get_poison_microblock_report(conn, reward_height)
+                }) {
+                    Some(report) => report?.map(|(reporter, _)| reporter),
+                    None => {
+                        return Ok(Some(IntegrityProblem::MissingClarityState(parent_id)));
+                    }
+                };
+
+            // the parent's schedule only decides its share of the streamed fees, which were
+            // already liquid, so the genesis schedule stands in for it here.
+            let parent_miner = MinerPaymentSchedule::genesis(self.mainnet);
+            let (_, miner_reward) = StacksChainState::calculate_miner_reward(
+                self.mainnet,
+                &miner,
+                &miner,
+                &users,
+                &parent_miner,
+                poison_reporter.as_ref(),
+            );
+            minted += miner_reward.coinbase;
+            for user in users.iter() {
+                let (_, user_reward) = StacksChainState::calculate_miner_reward(
+                    self.mainnet,
+                    user,
+                    &miner,
+                    &users,
+                    &parent_miner,
+                    poison_reporter.as_ref(),
+                );
+                minted += user_reward.coinbase;
+            }
+        }
+
+        // genesis STX that unlocked in this block
+        let lockup_contract_id = boot_code_id("lockup", self.mainnet);
+        let lockups = match self.with_read_only_clarity_tx(&burn_conn, &parent_id, |conn| {
+            conn.with_clarity_db_readonly(|db| {
+                db.fetch_entry_unknown_descriptor(
+                    &lockup_contract_id,
+                    "lockups",
+                    &Value::UInt(header_info.stacks_block_height as u128),
+                )
+            })
+        }) {
+            Some(lockups) => lockups?,
+            None => {
+                return Ok(Some(IntegrityProblem::MissingClarityState(parent_id)));
+            }
+        };
+        let entries = match lockups {
+            Value::Optional(OptionalData { data: None }) => vec![],
+            Value::Optional(OptionalData { data: Some(list) }) => match *list {
+                Value::Sequence(SequenceData::List(entries)) => entries.data,
+                _ => {
+                    return Ok(Some(IntegrityProblem::BadClarityState(format!(
+                        "lockups at height {} are not a list",
+                        header_info.stacks_block_height
+                    ))));
+                }
+            },
+            _ => {
+                return Ok(Some(IntegrityProblem::BadClarityState(format!(
+                    "lockups entry at height {} is not optional",
+                    header_info.stacks_block_height
+                ))));
+            }
+        };
+        for entry in entries.into_iter() {
+            let amount = match entry {
+                Value::Tuple(ref tuple) => match tuple.get("amount") {
+                    Ok(Value::UInt(amount)) => Some(*amount),
+                    _ => None,
+                },
+                _ => None,
+            };
+            match amount {
+                Some(amount) => {
+                    minted += amount;
+                }
+                None => {
+                    return Ok(Some(IntegrityProblem::BadClarityState(format!(
+                        "lockup at height {} has no amount",
+                        header_info.stacks_block_height
+                    ))));
+                }
+            }
+        }
+
+        // STX burned in this block and the microblocks it confirmed
+        let burned: u128 = query_rows::<MinerPaymentSchedule, _>(
+            self.db(),
+            "SELECT * FROM payments WHERE index_block_hash = ?1",
+            &[&block_id as &dyn ToSql],
+        )?
+        .iter()
+        .map(|payment| payment.stx_burns)
+        .sum();
+
+        let supply_before = match self.with_read_only_clarity_tx(&burn_conn, &parent_id, |conn| {
+            conn.with_clarity_db_readonly(|db| db.get_total_liquid_ustx())
+        }) {
+            Some(supply) => supply,
+            None => {
+                return Ok(Some(IntegrityProblem::MissingClarityState(parent_id)));
+            }
+        };
+        let supply_after = match self.with_read_only_clarity_tx(&burn_conn, &block_id, |conn| {
+            conn.with_clarity_db_readonly(|db| db.get_total_liquid_ustx())
+        }) {
+            Some(supply) => supply,
+            None => {
+                return Ok(Some(IntegrityProblem::MissingClarityState(block_id)));
+            }
+        };
+
+        let expected = (supply_before + minted).checked_sub(burned).unwrap_or(0);
+        if expected != supply_after {
+            return Ok(Some(IntegrityProblem::LiquidSupplyMismatch {
+                expected,
+                actual: supply_after,
+            }));
+        }
+
+        Ok(None)
+    }
+
+    /// Replay the block and add up, per account, the STX moved by its committed transfer, mint
+    /// and burn events, the fees its transactions paid and the miner rewards that matured in it.
+    /// Each account's total balance (unlocked and locked) must have changed by exactly that much
+    /// between the parent block and this one.  STX locked for stacking stay in the account, so
+    /// lock events and unlocks are not counted.
+    fn check_account_balances(
+        &mut self,
+        sortdb: &SortitionDB,
+        parent: &StacksHeaderInfo,
+        header_info: &StacksHeaderInfo,
+    ) -> Result<Option<IntegrityProblem>, Error> {
+        let block_id = header_info.index_block_hash();
+        let parent_id = parent.index_block_hash();
+
+        let receipt = match self.replay_block_receipt(sortdb, &block_id) {
+            Ok(Some(receipt)) => receipt,
+            Ok(None) => {
+                return Ok(Some(IntegrityProblem::ReplayFailed(
+                    "block is not stored".into(),
+                )));
+            }
+            Err(Error::InvalidStacksBlock(msg)) | Err(Error::InvalidStacksMicroblock(msg, _)) => {
+                return Ok(Some(IntegrityProblem::ReplayFailed(msg)));
+            }
+            Err(e) => {
+                return Err(e);
+            }
+        };
+
+        // accounts in the order the block first touched them, so the report is deterministic
+        let mut principals: Vec<PrincipalData> = vec![];
+        let mut deltas: HashMap<PrincipalData, i128> = HashMap::new();
+        let mut add = |principal: &PrincipalData, amount: i128| {
+            if !deltas.contains_key(principal) {
+                principals.push(principal.clone());
+            }
+            *deltas.entry(principal.clone()).or_insert(0) += amount;
+        };
+
+        for tx_receipt in receipt.tx_receipts.iter() {
+            if let TransactionOrigin::Stacks(ref tx) = tx_receipt.transaction {
+                let payer = tx.sponsor_address().unwrap_or(tx.origin_address());
+                add(&payer.into(), -(tx.get_tx_fee() as i128));
+            }
+
+            // events of an aborted transaction were rolled back with it
+            let committed = match tx_receipt.result {
+                Value::Response(ref response) => {
+                    response.committed && !tx_receipt.post_condition_aborted
+                }
+                _ => false,
+            };
+            if !committed {
+                continue;
+            }
+            for event in tx_receipt.events.iter() {
+                match event {
+                    StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                        add(&data.sender, -(data.amount as i128));
+                        add(&data.recipient, data.amount as i128);
+                    }
+                    StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(data)) => {
+                        add(&data.recipient, data.amount as i128);
+                    }
+                    StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => {
+                        add(&data.sender, -(data.amount as i128));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        for reward in receipt.matured_rewards.iter() {
+            let principal =
+                PrincipalData::Standard(StandardPrincipalData::from(reward.address.clone()));
+            add(&principal, reward.total() as i128);
+        }
+
+        let burn_conn = sortdb.index_conn();
+        let balances_before = match self.with_read_only_clarity_tx(&burn_conn, &parent_id, |conn| {
+            conn.with_clarity_db_readonly(|db| {
+                principals
+                    .iter()
+                    .map(|principal| db.get_account_stx_balance(principal).get_total_balance())
+                    .collect::<Vec<_>>()
+            })
+        }) {
+            Some(balances) => balances,
+            None => {
+                return Ok(Some(IntegrityProblem::MissingClarityState(parent_id)));
+            }
+        };
+        let balances_after = match self.with_read_only_clarity_tx(&burn_conn, &block_id, |conn| {
+            conn.with_clarity_db_readonly(|db| {
+                principals
+                    .iter()
+                    .map(|principal| db.get_account_stx_balance(principal).get_total_balance())
+                    .collect::<Vec<_>>()
+            })
+        }) {
+            Some(balances) => balances,
+            None => {
+                return Ok(Some(IntegrityProblem::MissingClarityState(block_id)));
+            }
+        };
+
+        for (i, principal) in principals.into_iter().enumerate() {
+            let expected = balances_before[i] as i128 + deltas[&principal];
+            let actual = balances_after[i];
+            if expected != actual as i128 {
+                return Ok(Some(IntegrityProblem::AccountBalanceMismatch {
+                    principal,
+                    expected: if expected < 0 { 0 } else { expected as u128 },
+                    actual,
+                }));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use crate::chainstate::burn::db::sortdb::SortitionDB;
+    use crate::chainstate::stacks::index::MARFValue;
+    use crate::net::test::*;
+    use clarity::vm::database::ClaritySerializable;
+
+    fn make_chain(peer: &mut TestPeer, num_blocks: usize) -> Vec<StacksBlockId> {
+        let mut block_ids = vec![];
+        for _ in 0..num_blocks {
+            let (burn_ops, stacks_block, microblocks) = peer.make_default_tenure();
+            let (_, _, consensus_hash) = peer.next_burnchain_block(burn_ops);
+            peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+            block_ids.push(StacksBlockHeader::make_index_block_hash(
+                &consensus_hash,
+                &stacks_block.block_hash(),
+            ));
+        }
+        block_ids
+    }
+
+    fn check(peer: &mut TestPeer, start_height: u64) -> ChainstateIntegrityReport {
+        let sortdb = peer.sortdb.take().unwrap();
+        let report = peer
+            .chainstate()
+            .check_integrity(&sortdb, start_height)
+            .unwrap();
+        peer.sortdb = Some(sortdb);
+        report
+    }
+
+    #[test]
+    fn test_check_integrity_clean_chain() {
+        let peer_config = TestPeerConfig::new("test_check_integrity_clean_chain", 21400, 21401);
+        let mut peer = TestPeer::new(peer_config);
+
+        let num_blocks = 10;
+        let block_ids = make_chain(&mut peer, num_blocks);
+
+        let report = check(&mut peer, 0);
+        assert_eq!(report.tip, *block_ids.last().unwrap());
+        assert_eq!(report.first_inconsistency, None);
+
+        // every block, plus the genesis block
+        assert_eq!(report.blocks_checked, num_blocks as u64 + 1);
+
+        let report = check(&mut peer, 5);
+        assert_eq!(report.first_inconsistency, None);
+        assert_eq!(report.blocks_checked, num_blocks as u64 - 4);
+    }
+
+    #[test]
+    fn test_check_integrity_finds_first_inconsistency() {
+        let peer_config = TestPeerConfig::new(
+            "test_check_integrity_finds_first_inconsistency",
+            21402,
+            21403,
+        );
+        let mut peer = TestPeer::new(peer_config);
+
+        let block_ids = make_chain(&mut peer, 10);
+
+        // forget that the block at height 6 was processed
+        peer.chainstate()
+            .db()
+            .execute(
+                "UPDATE staging_blocks SET processed = 0 WHERE index_block_hash = ?1",
+                &[&block_ids[5]],
+            )
+            .unwrap();
+
+        let report = check(&mut peer, 0);
+        let inconsistency = report.first_inconsistency.unwrap();
+        assert_eq!(inconsistency.index_block_hash, block_ids[5]);
+        assert_eq!(inconsistency.stacks_block_height, 6);
+        match inconsistency.problem {
+            IntegrityProblem::BadStagingBlock(_) => {}
+            problem => panic!("Unexpected problem {:?}", &problem),
+        }
+        assert_eq!(report.blocks_checked, 6);
+
+        // blocks below the start height are not checked
+        let report = check(&mut peer, 7);
+        assert_eq!(report.first_inconsistency, None);
+
+        // tamper with the header at height 3, which is then reported in its stead
+        peer.chainstate()
+            .db()
+            .execute(
+                "UPDATE block_headers SET state_index_root = ?1 WHERE index_block_hash = ?2",
+                rusqlite::params![TrieHash([0x11; 32]), &block_ids[2]],
+            )
+            .unwrap();
+
+        let report = check(&mut peer, 0);
+        let inconsistency = report.first_inconsistency.unwrap();
+        assert_eq!(inconsistency.index_block_hash, block_ids[2]);
+        assert_eq!(inconsistency.stacks_block_height, 3);
+        match inconsistency.problem {
+            IntegrityProblem::BadHeader(_) => {}
+            problem => panic!("Unexpected problem {:?}", &problem),
+        }
+    }
+
+    #[test]
+    fn test_check_integrity_finds_corrupt_balance() {
+        let peer_config =
+            TestPeerConfig::new("test_check_integrity_finds_corrupt_balance", 21406, 21407);
+        let mut peer = TestPeer::new(peer_config);
+
+        let block_ids = make_chain(&mut peer, 10);
+        let tip = block_ids.last().unwrap().clone();
+
+        // the miner's matured coinbase changes its balance in every block
+        let miner_addr = query_row_columns::<StacksAddress, _>(
+            peer.chainstate().db(),
+            "SELECT address FROM payments WHERE index_block_hash = ?1 AND miner = 1",
+            &[&tip as &dyn ToSql],
+            "address",
+        )
+        .unwrap()
+        .pop()
+        .unwrap();
+        let miner = PrincipalData::Standard(StandardPrincipalData::from(miner_addr));
+
+        let sortdb = peer.sortdb.take().unwrap();
+        let balance = peer
+            .chainstate()
+            .with_read_only_clarity_tx(&sortdb.index_conn(), &tip, |conn| {
+                conn.with_clarity_db_readonly(|db| db.get_account_stx_balance(&miner))
+            })
+            .unwrap();
+        peer.sortdb = Some(sortdb);
+        assert!(balance.get_total_balance() > 0);
+
+        // credit the miner one uSTX out of band.  The MARF only commits to the value's hash, so
+        // the state root still matches, and the liquid supply is untouched.
+        let mut corrupt_balance = balance.clone();
+        corrupt_balance.amount_unlocked += 1;
+        let conn = rusqlite::Connection::open(&peer.chainstate().clarity_state_index_path).unwrap();
+        let updated = conn
+            .execute(
+                "UPDATE data_table SET value = ?1 WHERE key = ?2",
+                rusqlite::params![
+                    corrupt_balance.serialize(),
+                    MARFValue::from_value(&balance.serialize()).to_hex()
+                ],
+            )
+            .unwrap();
+        assert_eq!(updated, 1);
+
+        let report = check(&mut peer, 0);
+        let inconsistency = report.first_inconsistency.unwrap();
+        assert_eq!(inconsistency.index_block_hash, tip);
+        assert_eq!(inconsistency.stacks_block_height, 10);
+        assert_eq!(
+            inconsistency.problem,
+            IntegrityProblem::AccountBalanceMismatch {
+                principal: miner,
+                expected: balance.get_total_balance(),
+                actual: balance.get_total_balance() + 1,
+            }
+        );
+        assert_eq!(report.blocks_checked, 10);
+    }
+}
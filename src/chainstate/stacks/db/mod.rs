@@ -82,7 +82,6 @@ use clarity::vm::representations::ClarityName;
 use clarity::vm::representations::ContractName;
 use clarity::vm::types::TupleData;
 use stacks_common::util;
-use stacks_common::util::hash::to_hex;
 
 use crate::chainstate::burn::ConsensusHashExtensions;
 use crate::chainstate::stacks::address::StacksAddressExtensions;
@@ -95,10 +94,15 @@ use crate::util_lib::boot::{boot_code_acc, boot_code_addr, boot_code_id, boot_co
 use clarity::vm::Value;
 use stacks_common::types::chainstate::{StacksAddress, StacksBlockId, TrieHash};
 pub mod accounts;
+pub mod addrindex;
 pub mod blocks;
 pub mod contracts;
+pub mod cost_voting;
+pub mod eventlog;
 pub mod headers;
+pub mod integrity;
 pub mod transactions;
+pub mod txindex;
 pub mod unconfirmed;
 
 lazy_static! {
@@ -116,6 +120,12 @@ pub struct StacksChainState {
     pub clarity_state_index_root: String, // path to dir containing clarity MARF and side-store
     pub root_path: String,
     pub unconfirmed_state: Option<UnconfirmedState>,
+    /// Store the receipt of every processed transaction in the txid index
+    pub txindex: bool,
+    /// Record the principals touched by every processed transaction in the address index
+    pub address_index: bool,
+    /// Record the contract events emitted by every processed transaction in the event log
+    pub event_log: bool,
     marf_opts: Option<MARFOpenOpts>,
 }
 
@@ -192,8 +202,25 @@ impl DBConfig {
     pub fn supports_epoch(&self, epoch_id: StacksEpochId) -> bool {
         match epoch_id {
             StacksEpochId::Epoch10 => false,
-            StacksEpochId::Epoch20 => (self.version == "1" || self.version == "2"),
-            StacksEpochId::Epoch2_05 => self.version == "2",
+            StacksEpochId::Epoch20 => {
+                (self.version == "1"
+                    || self.version == "2"
+                    || self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5")
+            }
+            StacksEpochId::Epoch2_05 => {
+                (self.version == "2"
+                    || self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5")
+            }
+            StacksEpochId::Epoch21 => {
+                (self.version == "2"
+                    || self.version == "3"
+                    || self.version == "4"
+                    || self.version == "5")
+            }
         }
     }
 }
@@ -423,6 +450,9 @@ pub struct ChainstateTx<'a> {
     pub blocks_path: String,
     pub tx: StacksDBTx<'a>,
     pub root_path: String,
+    pub txindex: bool,
+    pub address_index: bool,
+    pub event_log: bool,
 }
 
 impl<'a> ChainstateTx<'a> {
@@ -431,12 +461,18 @@ impl<'a> ChainstateTx<'a> {
         blocks_path: String,
         root_path: String,
         config: DBConfig,
+        txindex: bool,
+        address_index: bool,
+        event_log: bool,
     ) -> ChainstateTx<'a> {
         ChainstateTx {
             config,
             blocks_path,
             tx,
             root_path,
+            txindex,
+            address_index,
+            event_log,
         }
     }
 
@@ -457,17 +493,11 @@ impl<'a> ChainstateTx<'a> {
         block_id: &StacksBlockId,
         events: &[StacksTransactionReceipt],
     ) {
-        if *TRANSACTION_LOG {
-            let insert =
-                "INSERT INTO transactions (txid, index_block_hash, tx_hex, result) VALUES (?, ?, ?, ?)";
-            for tx_event in events.iter() {
-                let txid = tx_event.transaction.txid();
-                let tx_hex = to_hex(&tx_event.transaction.serialize_to_vec());
-                let result = tx_event.result.to_string();
-                let params: &[&dyn ToSql] = &[&txid, block_id, &tx_hex, &result];
-                if let Err(e) = self.tx.tx().execute(insert, params) {
-                    warn!("Failed to log TX: {}", e);
-                }
+        if self.txindex || *TRANSACTION_LOG {
+            if let Err(e) =
+                StacksChainState::index_transaction_receipts(self.tx.tx(), block_id, events)
+            {
+                warn!("Failed to log TX: {}", e);
             }
         }
         for tx_event in events.iter() {
@@ -477,6 +507,31 @@ impl<'a> ChainstateTx<'a> {
             }
         }
     }
+
+    pub fn log_principal_activity(
+        &self,
+        tip: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) {
+        if self.address_index {
+            if let Err(e) = StacksChainState::index_principal_activity(self.tx.tx(), tip, receipts)
+            {
+                warn!("Failed to index principal activity: {}", e);
+            }
+        }
+    }
+
+    pub fn log_contract_events(
+        &self,
+        tip: &StacksHeaderInfo,
+        receipts: &[StacksTransactionReceipt],
+    ) {
+        if self.event_log {
+            if let Err(e) = StacksChainState::log_contract_events(self.tx.tx(), tip, receipts) {
+                warn!("Failed to log contract events: {}", e);
+            }
+        }
+    }
 }
 
 impl<'a> Deref for ChainstateTx<'a> {
@@ -578,7 +633,7 @@ pub struct TxStreamData {
     pub corked: bool,
 }
 
-pub const CHAINSTATE_VERSION: &'static str = "2";
+pub const CHAINSTATE_VERSION: &'static str = "5";
 
 const CHAINSTATE_INITIAL_SCHEMA: &'static [&'static str] = &[
     "PRAGMA foreign_keys = ON;",
@@ -731,6 +786,107 @@ const CHAINSTATE_SCHEMA_2: &'static [&'static str] = &[
     "#,
 ];
 
+const CHAINSTATE_SCHEMA_3: &'static [&'static str] = &[
+    // new in schema version 3
+    // receipt data for the optional txid index.  These are NULL for rows logged beforehand.
+    r#"
+    ALTER TABLE transactions ADD COLUMN tx_index INTEGER;
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN microblock_hash TEXT;
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN microblock_sequence INTEGER;
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN result_hex TEXT;        -- consensus-serialized result
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN events TEXT;            -- JSON array of events
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN execution_cost TEXT;    -- JSON-encoded ExecutionCost
+    "#,
+    r#"
+    ALTER TABLE transactions ADD COLUMN post_condition_aborted INTEGER;
+    "#,
+    r#"
+    UPDATE db_config SET version = "3";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_4: &'static [&'static str] = &[
+    // new in schema version 4
+    // optional address index: the transactions that touched each principal, and the asset
+    // events that named it, in every fork.
+    r#"
+    CREATE TABLE principal_txs(
+        principal TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        txid TEXT NOT NULL,
+        tx_index INTEGER NOT NULL,
+        roles TEXT NOT NULL,        -- JSON array of PrincipalRoles
+
+        PRIMARY KEY(principal,index_block_hash,txid)
+    );
+    "#,
+    r#"
+    CREATE TABLE principal_asset_events(
+        principal TEXT NOT NULL,
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        txid TEXT NOT NULL,
+        tx_index INTEGER NOT NULL,
+        event_index INTEGER NOT NULL,
+        event TEXT NOT NULL,        -- the event as sent to event observers
+
+        PRIMARY KEY(principal,index_block_hash,txid,event_index)
+    );
+    "#,
+    r#"
+    CREATE INDEX principal_txs_by_height ON principal_txs(principal,block_height,tx_index);
+    "#,
+    r#"
+    CREATE INDEX principal_asset_events_by_height ON principal_asset_events(principal,block_height,tx_index,event_index);
+    "#,
+    r#"
+    UPDATE db_config SET version = "4";
+    "#,
+];
+
+const CHAINSTATE_SCHEMA_5: &'static [&'static str] = &[
+    // new in schema version 5
+    // optional contract event log: the print and token events emitted by each contract, in
+    // every fork.
+    r#"
+    CREATE TABLE contract_events(
+        contract_id TEXT NOT NULL,
+        topic TEXT,                 -- NULL if a print event has no topic field
+        index_block_hash TEXT NOT NULL,
+        block_height INTEGER NOT NULL,
+        txid TEXT NOT NULL,
+        tx_index INTEGER NOT NULL,
+        event_index INTEGER NOT NULL,
+        event TEXT NOT NULL,        -- the event as sent to event observers
+
+        PRIMARY KEY(index_block_hash,txid,event_index)
+    );
+    "#,
+    r#"
+    CREATE INDEX contract_events_by_contract ON contract_events(contract_id,topic,block_height,tx_index,event_index);
+    "#,
+    r#"
+    CREATE INDEX contract_events_by_topic ON contract_events(topic,block_height,tx_index,event_index);
+    "#,
+    r#"
+    CREATE INDEX contract_events_by_height ON contract_events(block_height,tx_index,event_index);
+    "#,
+    r#"
+    UPDATE db_config SET version = "5";
+    "#,
+];
+
 const CHAINSTATE_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS index_block_hash_to_primary_key ON block_headers(index_block_hash,consensus_hash,block_hash);",
     "CREATE INDEX IF NOT EXISTS block_headers_hash_index ON block_headers(block_hash,block_height);",
@@ -941,6 +1097,27 @@ impl StacksChainState {
                             tx.execute_batch(cmd)?;
                         }
                     }
+                    "2" => {
+                        // migrate to 3
+                        info!("Migrating chainstate schema from version 2 to 3");
+                        for cmd in CHAINSTATE_SCHEMA_3.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "3" => {
+                        // migrate to 4
+                        info!("Migrating chainstate schema from version 3 to 4");
+                        for cmd in CHAINSTATE_SCHEMA_4.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
+                    "4" => {
+                        // migrate to 5
+                        info!("Migrating chainstate schema from version 4 to 5");
+                        for cmd in CHAINSTATE_SCHEMA_5.iter() {
+                            tx.execute_batch(cmd)?;
+                        }
+                    }
                     _ => {
                         error!(
                             "Invalid chain state database: expected version = {}, got {}",
@@ -1107,6 +1284,7 @@ impl StacksChainState {
                         .expect("FATAL: invalid boot-code contract name"),
                     code_body: StacksString::from_str(boot_code_contract)
                         .expect("FATAL: invalid boot code body"),
+                    clarity_version: None,
                 });
 
                 let boot_code_smart_contract = StacksTransaction::new(
@@ -1639,6 +1817,9 @@ impl StacksChainState {
             clarity_state_index_root: clarity_state_index_root,
             root_path: path_str.to_string(),
             unconfirmed_state: None,
+            txindex: false,
+            address_index: false,
+            event_log: false,
             marf_opts: marf_opts,
         };
 
@@ -1694,8 +1875,15 @@ impl StacksChainState {
         let clarity_instance = &mut self.clarity_state;
         let inner_tx = StacksDBTx::new(&mut self.state_index, ());
 
-        let chainstate_tx =
-            ChainstateTx::new(inner_tx, blocks_path, self.root_path.clone(), config);
+        let chainstate_tx = ChainstateTx::new(
+            inner_tx,
+            blocks_path,
+            self.root_path.clone(),
+            config,
+            self.txindex,
+            self.address_index,
+            self.event_log,
+        );
 
         Ok((chainstate_tx, clarity_instance))
     }
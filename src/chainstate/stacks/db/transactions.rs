@@ -23,6 +23,7 @@ use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
 
 use crate::chainstate::burn::db::sortdb::*;
+use crate::chainstate::stacks::db::addrindex::PrincipalRole;
 use crate::chainstate::stacks::db::*;
 use crate::chainstate::stacks::Error;
 use crate::chainstate::stacks::*;
@@ -50,12 +51,14 @@ use clarity::vm::costs::CostTracker;
 use clarity::vm::costs::ExecutionCost;
 use clarity::vm::database::ClarityDatabase;
 use clarity::vm::errors::Error as InterpreterError;
+use clarity::vm::events::{FTEventType, NFTEventType, STXEventType};
 use clarity::vm::representations::ClarityName;
 use clarity::vm::representations::ContractName;
 use clarity::vm::types::{
     AssetIdentifier, BuffData, PrincipalData, QualifiedContractIdentifier, SequenceData,
     StandardPrincipalData, TupleData, TypeSignature, Value,
 };
+use clarity::vm::ClarityVersion;
 
 use crate::chainstate::stacks::StacksMicroblockHeader;
 use clarity::vm::types::StacksAddressExtensions as ClarityStacksAddressExt;
@@ -217,6 +220,106 @@ impl StacksTransactionReceipt {
         }
         false
     }
+
+    /// Principals this transaction touched: its origin and sponsor, the recipient of a token
+    /// transfer, the contract it called or deployed, and every sender, recipient or locked
+    /// address named in its asset events.  Each principal is listed once, in the order first seen.
+    pub fn affected_principals(&self) -> Vec<PrincipalData> {
+        self.principal_roles()
+            .into_iter()
+            .map(|(principal, _)| principal)
+            .collect()
+    }
+
+    /// The principals this transaction touched (see `affected_principals()`), each with the
+    /// roles it played.
+    pub fn principal_roles(&self) -> Vec<(PrincipalData, Vec<PrincipalRole>)> {
+        let mut principals: Vec<(PrincipalData, Vec<PrincipalRole>)> = vec![];
+        let mut add = |principal: &PrincipalData, role: PrincipalRole| match principals
+            .iter_mut()
+            .find(|(p, _)| p == principal)
+        {
+            Some((_, roles)) => {
+                if !roles.contains(&role) {
+                    roles.push(role);
+                }
+            }
+            None => principals.push((principal.clone(), vec![role])),
+        };
+
+        if let TransactionOrigin::Stacks(ref tx) = self.transaction {
+            add(
+                &tx.origin_address().to_account_principal(),
+                PrincipalRole::Sender,
+            );
+            if let Some(sponsor) = tx.sponsor_address() {
+                add(&sponsor.to_account_principal(), PrincipalRole::Sponsor);
+            }
+            match tx.payload {
+                TransactionPayload::TokenTransfer(ref recipient, ..) => {
+                    add(recipient, PrincipalRole::Recipient)
+                }
+                TransactionPayload::ContractCall(ref cc) => add(
+                    &PrincipalData::Contract(cc.to_clarity_contract_id()),
+                    PrincipalRole::Contract,
+                ),
+                TransactionPayload::SmartContract(ref sc) => add(
+                    &PrincipalData::Contract(QualifiedContractIdentifier::new(
+                        StandardPrincipalData::from(tx.origin_address()),
+                        sc.name.clone(),
+                    )),
+                    PrincipalRole::Contract,
+                ),
+                _ => {}
+            }
+        }
+
+        for event in self.events.iter() {
+            for party in StacksTransactionReceipt::asset_event_parties(event).iter() {
+                add(party, PrincipalRole::AssetEvent);
+            }
+        }
+
+        principals
+    }
+
+    /// The senders, recipients and locked addresses named in an asset event.  Empty if this is
+    /// not an asset event.
+    pub fn asset_event_parties(event: &StacksTransactionEvent) -> Vec<PrincipalData> {
+        match event {
+            StacksTransactionEvent::STXEvent(STXEventType::STXTransferEvent(data)) => {
+                vec![data.sender.clone(), data.recipient.clone()]
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXMintEvent(data)) => {
+                vec![data.recipient.clone()]
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXBurnEvent(data)) => {
+                vec![data.sender.clone()]
+            }
+            StacksTransactionEvent::STXEvent(STXEventType::STXLockEvent(data)) => {
+                vec![data.locked_address.clone()]
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTTransferEvent(data)) => {
+                vec![data.sender.clone(), data.recipient.clone()]
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTMintEvent(data)) => {
+                vec![data.recipient.clone()]
+            }
+            StacksTransactionEvent::FTEvent(FTEventType::FTBurnEvent(data)) => {
+                vec![data.sender.clone()]
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTTransferEvent(data)) => {
+                vec![data.sender.clone(), data.recipient.clone()]
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTMintEvent(data)) => {
+                vec![data.recipient.clone()]
+            }
+            StacksTransactionEvent::NFTEvent(NFTEventType::NFTBurnEvent(data)) => {
+                vec![data.sender.clone()]
+            }
+            StacksTransactionEvent::SmartContractEvent(_) => vec![],
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -967,13 +1070,32 @@ impl StacksChainState {
                     return Err(Error::InvalidStacksTransaction(msg, false));
                 }
 
+                // the Clarity version must exist in this epoch -- this is also checked when the
+                // transaction is admitted to the mempool.
+                let epoch = clarity_tx.get_epoch();
+                if !smart_contract.is_supported_in_epoch(epoch) {
+                    let msg = format!(
+                        "Invalid contract '{}': {:?} is not supported in {}",
+                        &contract_id, &smart_contract.clarity_version, epoch
+                    );
+                    warn!("{}", &msg);
+
+                    return Err(Error::InvalidStacksTransaction(msg, false));
+                }
+                let clarity_version = smart_contract
+                    .clarity_version
+                    .unwrap_or(ClarityVersion::default_for_epoch(epoch));
+
                 let cost_before = clarity_tx.cost_so_far();
 
                 // analysis pass -- if this fails, then the transaction is still accepted, but nothing is stored or processed.
                 // The reason for this is that analyzing the transaction is itself an expensive
                 // operation, and the paying account will need to be debited the fee regardless.
-                let analysis_resp =
-                    clarity_tx.analyze_smart_contract(&contract_id, &contract_code_str);
+                let analysis_resp = clarity_tx.analyze_versioned_smart_contract(
+                    &contract_id,
+                    clarity_version,
+                    &contract_code_str,
+                );
                 let (contract_ast, contract_analysis) = match analysis_resp {
                     Ok(x) => x,
                     Err(e) => {
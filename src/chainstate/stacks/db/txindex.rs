@@ -0,0 +1,189 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Optional txid index over the `transactions` table.  When it is turned on, the receipt of every
+//! transaction in a processed anchored block (and in the microblock stream it confirms) is stored
+//! keyed by txid, so the node can report where a transaction was mined and what it did.  A node
+//! only has receipts for the blocks it processed while the index was on.
+
+use rusqlite::types::ToSql;
+use rusqlite::Row;
+
+use crate::chainstate::stacks::db::*;
+use crate::chainstate::stacks::Error;
+use crate::chainstate::stacks::*;
+use crate::codec::StacksMessageCodec;
+use crate::util_lib::db::Error as db_error;
+use crate::util_lib::db::{query_rows, DBConn, DBTx, FromColumn, FromRow};
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::database::ClaritySerializable;
+use stacks_common::util::hash::to_hex;
+
+use stacks_common::types::chainstate::StacksBlockId;
+
+/// A transaction receipt loaded from the txid index
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedTransaction {
+    pub txid: Txid,
+    pub index_block_hash: StacksBlockId,
+    pub block_hash: BlockHeaderHash,
+    pub block_height: u64,
+    /// hex-encoded transaction
+    pub tx_hex: String,
+    /// The fields below are empty for rows logged before chainstate schema version 3, whose
+    /// `tx_hex` only holds the txid.
+    pub tx_index: Option<u32>,
+    pub microblock_hash: Option<BlockHeaderHash>,
+    pub microblock_sequence: Option<u16>,
+    /// hex-encoded consensus serialization of the transaction's result
+    pub result_hex: Option<String>,
+    /// the transaction's events, in the JSON form sent to event observers
+    pub events: Option<Vec<serde_json::Value>>,
+    pub execution_cost: Option<ExecutionCost>,
+    pub post_condition_aborted: Option<bool>,
+}
+
+impl FromRow<IndexedTransaction> for IndexedTransaction {
+    fn from_row<'a>(row: &'a Row) -> Result<IndexedTransaction, db_error> {
+        let txid = Txid::from_column(row, "txid")?;
+        let index_block_hash = StacksBlockId::from_column(row, "index_block_hash")?;
+        let block_hash = BlockHeaderHash::from_column(row, "block_hash")?;
+        let block_height = u64::from_column(row, "block_height")?;
+        let tx_hex: String = row.get_unwrap("tx_hex");
+        let tx_index: Option<u32> = row.get_unwrap("tx_index");
+        let microblock_hash = match row.get_unwrap::<_, Option<String>>("microblock_hash") {
+            Some(hex) => Some(BlockHeaderHash::from_hex(&hex).map_err(|_e| db_error::ParseError)?),
+            None => None,
+        };
+        let microblock_sequence: Option<u16> = row.get_unwrap("microblock_sequence");
+        let result_hex: Option<String> = row.get_unwrap("result_hex");
+        let events = match row.get_unwrap::<_, Option<String>>("events") {
+            Some(json) => Some(serde_json::from_str(&json).map_err(|_e| db_error::ParseError)?),
+            None => None,
+        };
+        let execution_cost = match row.get_unwrap::<_, Option<String>>("execution_cost") {
+            Some(json) => Some(serde_json::from_str(&json).map_err(|_e| db_error::ParseError)?),
+            None => None,
+        };
+        let post_condition_aborted: Option<bool> = row.get_unwrap("post_condition_aborted");
+
+        Ok(IndexedTransaction {
+            txid,
+            index_block_hash,
+            block_hash,
+            block_height,
+            tx_hex,
+            tx_index,
+            microblock_hash,
+            microblock_sequence,
+            result_hex,
+            events,
+            execution_cost,
+            post_condition_aborted,
+        })
+    }
+}
+
+impl StacksChainState {
+    /// Store the receipts of the transactions processed in the given block in the txid index
+    pub fn index_transaction_receipts(
+        tx: &DBTx,
+        block_id: &StacksBlockId,
+        receipts: &[StacksTransactionReceipt],
+    ) -> Result<(), db_error> {
+        let sql = "INSERT OR REPLACE INTO transactions
+            (txid, index_block_hash, tx_hex, result, tx_index, microblock_hash, microblock_sequence,
+             result_hex, events, execution_cost, post_condition_aborted)
+            VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)";
+
+        for receipt in receipts.iter() {
+            let txid = receipt.transaction.txid();
+            let tx_hex = match receipt.transaction {
+                TransactionOrigin::Stacks(ref stacks_tx) => to_hex(&stacks_tx.serialize_to_vec()),
+                TransactionOrigin::Burn(ref burn_txid) => to_hex(burn_txid.as_bytes()),
+            };
+            let microblock_hash = receipt
+                .microblock_header
+                .as_ref()
+                .map(|header| header.block_hash());
+            let microblock_sequence = receipt
+                .microblock_header
+                .as_ref()
+                .map(|header| header.sequence);
+
+            let committed = !receipt.post_condition_aborted;
+            let events: Vec<serde_json::Value> = receipt
+                .events
+                .iter()
+                .enumerate()
+                .map(|(event_index, event)| event.json_serialize(event_index, &txid, committed))
+                .collect();
+            let events_json =
+                serde_json::to_string(&events).expect("FATAL: failed to serialize events");
+            let cost_json = serde_json::to_string(&receipt.execution_cost)
+                .expect("FATAL: failed to serialize execution cost");
+
+            let args: &[&dyn ToSql] = &[
+                &txid,
+                block_id,
+                &tx_hex,
+                &receipt.result.to_string(),
+                &receipt.tx_index,
+                &microblock_hash,
+                &microblock_sequence,
+                &receipt.result.serialize(),
+                &events_json,
+                &cost_json,
+                &receipt.post_condition_aborted,
+            ];
+            tx.execute(sql, args)?;
+        }
+        Ok(())
+    }
+
+    /// Load every indexed receipt for the given txid -- one for each block, in any fork, that
+    /// mined it.
+    pub fn get_indexed_transactions(
+        conn: &DBConn,
+        txid: &Txid,
+    ) -> Result<Vec<IndexedTransaction>, Error> {
+        let sql = "SELECT transactions.*, block_headers.block_hash, block_headers.block_height
+            FROM transactions JOIN block_headers
+            ON transactions.index_block_hash = block_headers.index_block_hash
+            WHERE transactions.txid = ?1";
+        let args: &[&dyn ToSql] = &[txid];
+        query_rows(conn, sql, args).map_err(Error::DBError)
+    }
+
+    /// Load the indexed receipt for the given txid from the block that mined it in the fork
+    /// ending at `tip`, if there is one.
+    pub fn get_indexed_transaction_at_tip(
+        &self,
+        tip: &StacksBlockId,
+        txid: &Txid,
+    ) -> Result<Option<IndexedTransaction>, Error> {
+        let index_conn = self.index_conn()?;
+        for entry in StacksChainState::get_indexed_transactions(self.db(), txid)?.into_iter() {
+            if index_conn
+                .get_ancestor_block_height(&entry.index_block_hash, tip)?
+                .is_some()
+            {
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+}
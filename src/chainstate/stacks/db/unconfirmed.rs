@@ -35,6 +35,7 @@ use clarity::vm::database::BurnStateDB;
 use clarity::vm::database::HeadersDB;
 use clarity::vm::database::NULL_BURN_STATE_DB;
 use clarity::vm::database::NULL_HEADER_DB;
+use clarity::vm::types::PrincipalData;
 
 use crate::chainstate::burn::db::sortdb::SortitionDB;
 use crate::clarity_vm::database::marf::MarfedKV;
@@ -42,6 +43,8 @@ use crate::types::chainstate::StacksBlockId;
 use stacks_common::types::chainstate::BurnchainHeaderHash;
 
 pub type UnconfirmedTxMap = HashMap<Txid, (StacksTransaction, BlockHeaderHash, u16)>;
+/// Txids of the mined microblock transactions that touched each principal, in stream order
+pub type UnconfirmedPrincipalTxMap = HashMap<PrincipalData, Vec<Txid>>;
 
 pub struct ProcessedUnconfirmedState {
     pub total_burns: u128,
@@ -73,6 +76,7 @@ pub struct UnconfirmedState {
     pub unconfirmed_chain_tip: StacksBlockId,
     pub clarity_inst: ClarityInstance,
     pub mined_txs: UnconfirmedTxMap,
+    pub mined_txs_by_principal: UnconfirmedPrincipalTxMap,
     pub cost_so_far: ExecutionCost,
     pub bytes_so_far: u64,
 
@@ -109,6 +113,7 @@ impl UnconfirmedState {
             unconfirmed_chain_tip: unconfirmed_tip,
             clarity_inst: clarity_instance,
             mined_txs: UnconfirmedTxMap::new(),
+            mined_txs_by_principal: UnconfirmedPrincipalTxMap::new(),
             cost_so_far: cost_so_far.clone(),
             bytes_so_far: 0,
 
@@ -146,6 +151,7 @@ impl UnconfirmedState {
             unconfirmed_chain_tip: unconfirmed_tip,
             clarity_inst: clarity_instance,
             mined_txs: UnconfirmedTxMap::new(),
+            mined_txs_by_principal: UnconfirmedPrincipalTxMap::new(),
             cost_so_far: cost_so_far,
             bytes_so_far: 0,
 
@@ -203,6 +209,7 @@ impl UnconfirmedState {
         let mut total_burns = 0;
         let mut all_receipts = vec![];
         let mut mined_txs = UnconfirmedTxMap::new();
+        let mut mined_txs_by_principal = UnconfirmedPrincipalTxMap::new();
         let mut new_cost = ExecutionCost::zero();
         let mut new_bytes = 0;
         let mut num_new_mblocks = 0;
@@ -265,6 +272,16 @@ impl UnconfirmedState {
                 total_fees += stx_fees;
                 total_burns += stx_burns;
                 num_new_mblocks += 1;
+
+                for receipt in receipts.iter() {
+                    let txid = receipt.transaction.txid();
+                    for principal in receipt.affected_principals().into_iter() {
+                        mined_txs_by_principal
+                            .entry(principal)
+                            .or_insert_with(Vec::new)
+                            .push(txid.clone());
+                    }
+                }
                 all_receipts.push((seq, mblock.header, receipts));
 
                 last_mblock = Some(mblock_header);
@@ -292,6 +309,12 @@ impl UnconfirmedState {
         self.last_mblock = last_mblock;
         self.last_mblock_seq = last_mblock_seq;
         self.mined_txs.extend(mined_txs);
+        for (principal, mut txids) in mined_txs_by_principal.into_iter() {
+            self.mined_txs_by_principal
+                .entry(principal)
+                .or_insert_with(Vec::new)
+                .append(&mut txids);
+        }
         self.cost_so_far = new_cost;
         self.bytes_so_far += new_bytes;
         self.num_mblocks_added += num_new_mblocks;
@@ -395,6 +418,15 @@ impl UnconfirmedState {
         self.mined_txs.get(txid).map(|x| x.clone())
     }
 
+    /// Get the txids of the mined microblock transactions that touched the given principal,
+    /// in the order they were mined
+    pub fn get_unconfirmed_txids_for_principal(&self, principal: &PrincipalData) -> Vec<Txid> {
+        self.mined_txs_by_principal
+            .get(principal)
+            .cloned()
+            .unwrap_or_default()
+    }
+
     pub fn num_microblocks(&self) -> u64 {
         if self.last_mblock.is_some() {
             (self.last_mblock_seq as u64) + 1
@@ -819,6 +851,21 @@ mod test {
 
             // move 1 stx per round
             assert_eq!(recv_balance.amount_unlocked, (tenure_id + 1) as u128);
+
+            // the transfer is indexed under both the sender and the recipient
+            {
+                let unconfirmed = peer.chainstate().unconfirmed_state.as_ref().unwrap();
+                let sender_txids =
+                    unconfirmed.get_unconfirmed_txids_for_principal(&addr.to_account_principal());
+                let recv_txids = unconfirmed
+                    .get_unconfirmed_txids_for_principal(&recv_addr.to_account_principal());
+                assert_eq!(sender_txids.len(), 1);
+                assert_eq!(sender_txids, recv_txids);
+                assert!(unconfirmed
+                    .get_unconfirmed_transaction(&sender_txids[0])
+                    .is_some());
+            }
+
             let (canonical_burn, canonical_block) =
                 SortitionDB::get_canonical_stacks_chain_tip_hash(peer.sortdb().conn()).unwrap();
 
@@ -1010,7 +1010,7 @@ impl<'a> StacksMicroblockBuilder<'a> {
         self.runtime.considered.replace(considered);
         self.runtime.num_mined = num_txs;
 
-        mem_pool.drop_txs(&invalidated_txs)?;
+        mem_pool.drop_txs(&invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE)?;
         event_dispatcher.mempool_txs_dropped(invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE);
 
         match result {
@@ -2112,7 +2112,7 @@ impl StacksBlockBuilder {
             intermediate_result
         };
 
-        mempool.drop_txs(&invalidated_txs)?;
+        mempool.drop_txs(&invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE)?;
 
         if let Some(observer) = event_observer {
             observer.mempool_txs_dropped(invalidated_txs, MemPoolDropReason::TOO_EXPENSIVE);
@@ -2190,6 +2190,7 @@ pub mod test {
     };
     use crate::chainstate::burn::*;
     use crate::chainstate::coordinator::Error as CoordinatorError;
+    use crate::chainstate::stacks::db::addrindex::PrincipalRole;
     use crate::chainstate::stacks::db::blocks::test::store_staging_block;
     use crate::chainstate::stacks::db::test::*;
     use crate::chainstate::stacks::db::*;
@@ -2200,6 +2201,7 @@ pub mod test {
     use crate::util_lib::db::Error as db_error;
     use clarity::vm::types::*;
     use stacks_common::address::*;
+    use stacks_common::util::hash::to_hex;
     use stacks_common::util::sleep_ms;
     use stacks_common::util::vrf::VRFProof;
 
@@ -3191,6 +3193,8 @@ pub mod test {
             &full_test_name,
             vec![miner.origin_address().unwrap()],
         );
+        node.chainstate.txindex = true;
+        node.chainstate.address_index = true;
 
         let first_snapshot =
             SortitionDB::get_first_block_snapshot(burn_node.sortdb.conn()).unwrap();
@@ -3322,6 +3326,42 @@ pub mod test {
                     &fork_snapshot.consensus_hash,
                     &chain_tip.anchored_header
                 ));
+
+                // every transaction in the block is in the txid index.  Anchored transactions
+                // are numbered after those of the microblocks the block confirmed.
+                let index_block_hash = chain_tip.index_block_hash();
+                let mut first_tx_index = None;
+                for (i, tx) in stacks_block.txs.iter().enumerate() {
+                    let indexed = node
+                        .chainstate
+                        .get_indexed_transaction_at_tip(&index_block_hash, &tx.txid())
+                        .unwrap()
+                        .unwrap();
+                    assert_eq!(indexed.index_block_hash, index_block_hash);
+                    assert_eq!(indexed.block_hash, stacks_block.block_hash());
+                    let tx_index = indexed.tx_index.unwrap();
+                    let first_tx_index = *first_tx_index.get_or_insert(tx_index);
+                    assert_eq!(tx_index, first_tx_index + i as u32);
+                    assert_eq!(indexed.microblock_hash, None);
+                    assert_eq!(indexed.tx_hex, to_hex(&tx.serialize_to_vec()));
+                    assert!(indexed.result_hex.is_some());
+                    assert!(indexed.events.is_some());
+
+                    // ...and in the address index, under its origin
+                    let origin = tx.origin_address().to_account_principal();
+                    let (origin_txs, _) = node
+                        .chainstate
+                        .get_principal_transactions(&index_block_hash, &origin, None, 50)
+                        .unwrap();
+                    let entry = origin_txs
+                        .iter()
+                        .find(|entry| entry.txid == tx.txid())
+                        .unwrap();
+                    assert_eq!(entry.index_block_hash, index_block_hash);
+                    assert_eq!(entry.block_height, chain_tip.stacks_block_height);
+                    assert_eq!(entry.tx_index, tx_index);
+                    assert!(entry.roles.contains(&PrincipalRole::Sender));
+                }
             }
 
             let mut next_miner_trace = TestMinerTracePoint::new();
@@ -6898,7 +6938,11 @@ pub mod test {
         let name = ContractName::from(contract_name);
         let code_body = StacksString::from_string(&contract_content.to_string()).unwrap();
 
-        let payload = TransactionSmartContract { name, code_body };
+        let payload = TransactionSmartContract {
+            name,
+            code_body,
+            clarity_version: None,
+        };
 
         sign_standard_singlesig_tx(payload.into(), sender, nonce, tx_fee)
     }
@@ -48,6 +48,7 @@ use clarity::vm::representations::{ClarityName, ContractName};
 use clarity::vm::types::{
     PrincipalData, QualifiedContractIdentifier, StandardPrincipalData, Value,
 };
+use clarity::vm::ClarityVersion;
 use stacks_common::address::AddressHashMode;
 use stacks_common::util::hash::Hash160;
 use stacks_common::util::hash::Sha512Trunc256Sum;
@@ -117,6 +118,8 @@ pub enum Error {
     MemPoolError(String),
     PoxAlreadyLocked,
     PoxInsufficientBalance,
+    PoxNotLocked,
+    PoxInvalidLockUpdate,
     PoxNoRewardCycle,
 }
 
@@ -180,6 +183,8 @@ impl fmt::Display for Error {
             Error::NoTransactionsToMine => write!(f, "No transactions to mine"),
             Error::PoxAlreadyLocked => write!(f, "Account has already locked STX for PoX"),
             Error::PoxInsufficientBalance => write!(f, "Not enough STX to lock"),
+            Error::PoxNotLocked => write!(f, "Account has not locked STX for PoX"),
+            Error::PoxInvalidLockUpdate => write!(f, "Invalid update to an existing PoX lock"),
             Error::PoxNoRewardCycle => write!(f, "No such reward cycle"),
             Error::StacksTransactionSkipped(ref r) => {
                 write!(
@@ -219,6 +224,8 @@ impl error::Error for Error {
             Error::NoTransactionsToMine => None,
             Error::PoxAlreadyLocked => None,
             Error::PoxInsufficientBalance => None,
+            Error::PoxNotLocked => None,
+            Error::PoxInvalidLockUpdate => None,
             Error::PoxNoRewardCycle => None,
             Error::StacksTransactionSkipped(ref _r) => None,
         }
@@ -252,6 +259,8 @@ impl Error {
             Error::NoTransactionsToMine => "NoTransactionsToMine",
             Error::PoxAlreadyLocked => "PoxAlreadyLocked",
             Error::PoxInsufficientBalance => "PoxInsufficientBalance",
+            Error::PoxNotLocked => "PoxNotLocked",
+            Error::PoxInvalidLockUpdate => "PoxInvalidLockUpdate",
             Error::PoxNoRewardCycle => "PoxNoRewardCycle",
             Error::StacksTransactionSkipped(ref _r) => "StacksTransactionSkipped",
         }
@@ -548,6 +557,10 @@ pub struct TransactionContractCall {
 pub struct TransactionSmartContract {
     pub name: ContractName,
     pub code_body: StacksString,
+    /// The Clarity version the contract is written in. If `None`, the contract is deployed
+    /// with the default version of the epoch it is mined in.
+    #[serde(default)]
+    pub clarity_version: Option<ClarityVersion>,
 }
 
 /// A coinbase commits to 32 bytes of control-plane information
@@ -596,6 +609,7 @@ pub enum TransactionPayloadID {
     ContractCall = 2,
     PoisonMicroblock = 3,
     Coinbase = 4,
+    VersionedSmartContract = 5,
 }
 
 /// Encoding of an asset type identifier
@@ -1143,6 +1157,7 @@ pub mod test {
             TransactionPayload::SmartContract(TransactionSmartContract {
                 name: ContractName::try_from(hello_contract_name).unwrap(),
                 code_body: StacksString::from_str(hello_contract_body).unwrap(),
+                clarity_version: None,
             }),
             TransactionPayload::Coinbase(CoinbasePayload([0x12; 32])),
             TransactionPayload::PoisonMicroblock(mblock_header_1, mblock_header_2),
@@ -28,7 +28,7 @@ use clarity::vm::ast::build_ast;
 use clarity::vm::representations::{ClarityName, ContractName};
 use clarity::vm::types::serialization::SerializationError as clarity_serialization_error;
 use clarity::vm::types::{QualifiedContractIdentifier, StandardPrincipalData};
-use clarity::vm::{SymbolicExpression, SymbolicExpressionType, Value};
+use clarity::vm::{ClarityVersion, SymbolicExpression, SymbolicExpressionType, Value};
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::hash::Sha512Trunc256Sum;
 use stacks_common::util::retry::BoundReader;
@@ -109,7 +109,26 @@ impl StacksMessageCodec for TransactionSmartContract {
     fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<TransactionSmartContract, codec_error> {
         let name: ContractName = read_next(fd)?;
         let code_body: StacksString = read_next(fd)?;
-        Ok(TransactionSmartContract { name, code_body })
+        Ok(TransactionSmartContract {
+            name,
+            code_body,
+            clarity_version: None,
+        })
+    }
+}
+
+impl TransactionSmartContract {
+    /// Can this contract be deployed in `epoch_id`? Smart contracts that select a Clarity
+    /// version use a payload type that did not exist before epoch 2.1, so they are only valid
+    /// from 2.1 onwards, and only if the selected version is.
+    pub fn is_supported_in_epoch(&self, epoch_id: StacksEpochId) -> bool {
+        match self.clarity_version {
+            None => true,
+            Some(ref clarity_version) => {
+                epoch_id >= StacksEpochId::Epoch21
+                    && clarity_version.is_supported_in_epoch(epoch_id)
+            }
+        }
     }
 }
 
@@ -126,10 +145,17 @@ impl StacksMessageCodec for TransactionPayload {
                 write_next(fd, &(TransactionPayloadID::ContractCall as u8))?;
                 cc.consensus_serialize(fd)?;
             }
-            TransactionPayload::SmartContract(ref sc) => {
-                write_next(fd, &(TransactionPayloadID::SmartContract as u8))?;
-                sc.consensus_serialize(fd)?;
-            }
+            TransactionPayload::SmartContract(ref sc) => match sc.clarity_version {
+                Some(clarity_version) => {
+                    write_next(fd, &(TransactionPayloadID::VersionedSmartContract as u8))?;
+                    write_next(fd, &(clarity_version as u8))?;
+                    sc.consensus_serialize(fd)?;
+                }
+                None => {
+                    write_next(fd, &(TransactionPayloadID::SmartContract as u8))?;
+                    sc.consensus_serialize(fd)?;
+                }
+            },
             TransactionPayload::PoisonMicroblock(ref h1, ref h2) => {
                 write_next(fd, &(TransactionPayloadID::PoisonMicroblock as u8))?;
                 h1.consensus_serialize(fd)?;
@@ -160,6 +186,18 @@ impl StacksMessageCodec for TransactionPayload {
                 let payload: TransactionSmartContract = read_next(fd)?;
                 TransactionPayload::SmartContract(payload)
             }
+            x if x == TransactionPayloadID::VersionedSmartContract as u8 => {
+                let version_byte: u8 = read_next(fd)?;
+                let clarity_version = ClarityVersion::from_u8(version_byte).ok_or_else(|| {
+                    codec_error::DeserializeError(format!(
+                        "Failed to parse transaction -- unknown Clarity version {}",
+                        version_byte
+                    ))
+                })?;
+                let mut payload: TransactionSmartContract = read_next(fd)?;
+                payload.clarity_version = Some(clarity_version);
+                TransactionPayload::SmartContract(payload)
+            }
             x if x == TransactionPayloadID::PoisonMicroblock as u8 => {
                 let h1: StacksMicroblockHeader = read_next(fd)?;
                 let h2: StacksMicroblockHeader = read_next(fd)?;
@@ -237,6 +275,27 @@ impl TransactionPayload {
                 TransactionSmartContract {
                     name: s_name,
                     code_body: s_body,
+                    clarity_version: None,
+                },
+            )),
+            (_, _) => None,
+        }
+    }
+
+    pub fn new_versioned_smart_contract(
+        name: &str,
+        contract: &str,
+        clarity_version: ClarityVersion,
+    ) -> Option<TransactionPayload> {
+        match (
+            ContractName::try_from(name.to_string()),
+            StacksString::from_str(contract),
+        ) {
+            (Ok(s_name), Some(s_body)) => Some(TransactionPayload::SmartContract(
+                TransactionSmartContract {
+                    name: s_name,
+                    code_body: s_body,
+                    clarity_version: Some(clarity_version),
                 },
             )),
             (_, _) => None,
@@ -1445,6 +1504,7 @@ mod test {
                 TransactionPayload::SmartContract(TransactionSmartContract {
                     name: ContractName::try_from("corrupt-name").unwrap(),
                     code_body: StacksString::from_str("corrupt body").unwrap(),
+                    clarity_version: None,
                 })
             }
             TransactionPayload::SmartContract(_) => {
@@ -1605,6 +1665,7 @@ mod test {
         let smart_contract = TransactionSmartContract {
             name: ContractName::try_from(hello_contract_name).unwrap(),
             code_body: StacksString::from_str(hello_contract_body).unwrap(),
+            clarity_version: None,
         };
 
         let mut contract_call_bytes = vec![];
@@ -1656,6 +1717,70 @@ mod test {
         );
     }
 
+    #[test]
+    fn tx_stacks_transaction_payload_versioned_smart_contract() {
+        let smart_contract = TransactionSmartContract {
+            name: ContractName::try_from("hello-world").unwrap(),
+            code_body: StacksString::from_str("(begin (print \"hello world\"))").unwrap(),
+            clarity_version: Some(ClarityVersion::Clarity2),
+        };
+
+        let mut smart_contract_bytes = vec![];
+        smart_contract
+            .name
+            .consensus_serialize(&mut smart_contract_bytes)
+            .unwrap();
+        smart_contract
+            .code_body
+            .consensus_serialize(&mut smart_contract_bytes)
+            .unwrap();
+
+        let mut transaction_smart_contract = vec![
+            TransactionPayloadID::VersionedSmartContract as u8,
+            ClarityVersion::Clarity2 as u8,
+        ];
+        transaction_smart_contract.append(&mut smart_contract_bytes.clone());
+
+        check_codec_and_corruption::<TransactionPayload>(
+            &TransactionPayload::SmartContract(smart_contract.clone()),
+            &transaction_smart_contract,
+        );
+        assert_eq!(
+            TransactionPayload::new_versioned_smart_contract(
+                "hello-world",
+                "(begin (print \"hello world\"))",
+                ClarityVersion::Clarity2
+            )
+            .unwrap(),
+            TransactionPayload::SmartContract(smart_contract)
+        );
+
+        // unknown Clarity versions do not decode
+        let mut bad_version = transaction_smart_contract.clone();
+        bad_version[1] = 0xff;
+        assert!(TransactionPayload::consensus_deserialize(&mut &bad_version[..]).is_err());
+    }
+
+    #[test]
+    fn tx_stacks_transaction_payload_versioned_smart_contract_epoch() {
+        let mut smart_contract = TransactionSmartContract {
+            name: ContractName::try_from("hello-world").unwrap(),
+            code_body: StacksString::from_str("(begin (print \"hello world\"))").unwrap(),
+            clarity_version: None,
+        };
+        assert!(smart_contract.is_supported_in_epoch(StacksEpochId::Epoch20));
+        assert!(smart_contract.is_supported_in_epoch(StacksEpochId::Epoch21));
+
+        // versioned payloads do not exist before 2.1, even for Clarity 1
+        smart_contract.clarity_version = Some(ClarityVersion::Clarity1);
+        assert!(!smart_contract.is_supported_in_epoch(StacksEpochId::Epoch2_05));
+        assert!(smart_contract.is_supported_in_epoch(StacksEpochId::Epoch21));
+
+        smart_contract.clarity_version = Some(ClarityVersion::Clarity2);
+        assert!(!smart_contract.is_supported_in_epoch(StacksEpochId::Epoch2_05));
+        assert!(smart_contract.is_supported_in_epoch(StacksEpochId::Epoch21));
+    }
+
     #[test]
     fn tx_stacks_transaction_payload_coinbase() {
         let coinbase_payload = TransactionPayload::Coinbase(CoinbasePayload([0x12; 32]));
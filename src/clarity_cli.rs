@@ -33,7 +33,7 @@ use crate::chainstate::stacks::index::{storage::TrieFileStorage, MarfTrieId};
 use crate::util_lib::db::sqlite_open;
 use crate::util_lib::db::FromColumn;
 use stacks_common::address::c32::c32_address;
-use stacks_common::util::hash::{bytes_to_hex, Sha512Trunc256Sum};
+use stacks_common::util::hash::{bytes_to_hex, Hash160, Sha512Trunc256Sum};
 
 use crate::clarity::{
     vm::analysis,
@@ -53,7 +53,7 @@ use crate::clarity::{
     vm::types::{OptionalData, PrincipalData, QualifiedContractIdentifier},
     vm::ContractContext,
     vm::ContractName,
-    vm::{SymbolicExpression, SymbolicExpressionType, Value},
+    vm::{ClarityVersion, SymbolicExpression, SymbolicExpressionType, Value},
 };
 use stacks_common::util::log;
 
@@ -151,8 +151,9 @@ struct EvalInput {
 fn parse(
     contract_identifier: &QualifiedContractIdentifier,
     source_code: &str,
+    clarity_version: ClarityVersion,
 ) -> Result<Vec<SymbolicExpression>, Error> {
-    let ast = build_ast(contract_identifier, source_code, &mut ())
+    let ast = build_ast(contract_identifier, source_code, &mut (), clarity_version)
         .map_err(|e| RuntimeErrorType::ASTError(e))?;
     Ok(ast.expressions)
 }
@@ -199,6 +200,7 @@ fn run_analysis_free<C: ClarityStorage>(
     expressions: &mut [SymbolicExpression],
     marf_kv: &mut C,
     save_contract: bool,
+    clarity_version: ClarityVersion,
 ) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
     analysis::run_analysis(
         contract_identifier,
@@ -206,6 +208,7 @@ fn run_analysis_free<C: ClarityStorage>(
         &mut marf_kv.get_analysis_db(),
         save_contract,
         LimitedCostTracker::new_free(),
+        clarity_version,
     )
 }
 
@@ -215,6 +218,7 @@ fn run_analysis<C: ClarityStorage>(
     header_db: &CLIHeadersDB,
     marf_kv: &mut C,
     save_contract: bool,
+    clarity_version: ClarityVersion,
 ) -> Result<ContractAnalysis, (CheckError, LimitedCostTracker)> {
     let mainnet = header_db.is_mainnet();
     let cost_track = LimitedCostTracker::new(
@@ -234,6 +238,7 @@ fn run_analysis<C: ClarityStorage>(
         &mut marf_kv.get_analysis_db(),
         save_contract,
         cost_track,
+        clarity_version,
     )
 }
 
@@ -412,7 +417,8 @@ where
 ///  for program evaluation, not by consensus critical code.
 pub fn vm_execute(program: &str) -> Result<Option<Value>, Error> {
     let contract_id = QualifiedContractIdentifier::transient();
-    let mut contract_context = ContractContext::new(contract_id.clone());
+    let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
+    let mut contract_context = ContractContext::new(contract_id.clone(), clarity_version);
     let mut marf = MemoryBackingStore::new();
     let conn = marf.as_clarity_db();
     let mut global_context = GlobalContext::new(
@@ -422,7 +428,7 @@ pub fn vm_execute(program: &str) -> Result<Option<Value>, Error> {
         DEFAULT_CLI_EPOCH,
     );
     global_context.execute(|g| {
-        let parsed = ast::build_ast(&contract_id, program, &mut ())?.expressions;
+        let parsed = ast::build_ast(&contract_id, program, &mut (), clarity_version)?.expressions;
         eval_all(&parsed, &mut contract_context, g)
     })
 }
@@ -629,6 +635,16 @@ impl HeadersDB for CLIHeadersDB {
     fn get_miner_address(&self, _id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         None
     }
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        let conn = self.conn();
+        if let Some(_) = get_cli_block_height(&conn, id_bhh) {
+            // mock it, but make it unique
+            let hash_bytes = Hash160::from_data(&id_bhh.0);
+            Some(ConsensusHash(hash_bytes.0))
+        } else {
+            None
+        }
+    }
 }
 
 fn get_eval_input(invoked_by: &str, args: &[String]) -> EvalInput {
@@ -741,12 +757,14 @@ fn install_boot_code<C: ClarityStorage>(header_db: &CLIHeadersDB, marf: &mut C)
             boot_code_contract.len()
         );
 
+        let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
         let mut ast = friendly_expect(
-            parse(&contract_identifier, &contract_content),
+            parse(&contract_identifier, &contract_content, clarity_version),
             "Failed to parse program.",
         );
 
-        let analysis_result = run_analysis_free(&contract_identifier, &mut ast, marf, true);
+        let analysis_result =
+            run_analysis_free(&contract_identifier, &mut ast, marf, true, clarity_version);
         match analysis_result {
             Ok(_) => {
                 let db = marf.get_clarity_db(header_db, &NULL_BURN_STATE_DB);
@@ -937,7 +955,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
         "check" => {
             if args.len() < 2 {
                 eprintln!(
-                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--output_analysis] [--costs] [--testnet] (vm-state.db)",
+                    "Usage: {} {} [program-file.clar] [--contract_id CONTRACT_ID] [--clarity_version CLARITY_VERSION] [--output_analysis] [--costs] [--testnet] (vm-state.db)",
                     invoked_by, args[0]
                 );
                 panic_test!();
@@ -958,6 +976,21 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 panic_test!();
             };
 
+            let clarity_version =
+                if let Ok(optarg) = consume_arg(&mut argv, &["--clarity_version"], true) {
+                    optarg
+                        .map(|optarg_str| {
+                            friendly_expect(
+                                ClarityVersion::from_str(&optarg_str),
+                                &format!("Error parsing Clarity version '{}'", &optarg_str),
+                            )
+                        })
+                        .unwrap_or(ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH))
+                } else {
+                    eprintln!("Expected argument for --clarity_version");
+                    panic_test!();
+                };
+
             let output_analysis =
                 if let Ok(optarg) = consume_arg(&mut argv, &["--output_analysis"], false) {
                     optarg.is_some()
@@ -995,8 +1028,10 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 )
             };
 
-            // TODO: Add --clarity_version as command line argument
-            let mut ast = friendly_expect(parse(&contract_id, &content), "Failed to parse program");
+            let mut ast = friendly_expect(
+                parse(&contract_id, &content, clarity_version),
+                "Failed to parse program",
+            );
 
             let contract_analysis_res = {
                 if argv.len() >= 3 {
@@ -1014,8 +1049,14 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     );
 
                     let result = at_chaintip(&argv[2], marf_kv, |mut marf| {
-                        let result =
-                            run_analysis(&contract_id, &mut ast, &header_db, &mut marf, false);
+                        let result = run_analysis(
+                            &contract_id,
+                            &mut ast,
+                            &header_db,
+                            &mut marf,
+                            false,
+                            clarity_version,
+                        );
                         (marf, result)
                     });
                     result
@@ -1030,6 +1071,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                         &header_db,
                         &mut analysis_marf,
                         false,
+                        clarity_version,
                     )
                 }
             };
@@ -1078,6 +1120,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
             let mut analysis_marf = MemoryBackingStore::new();
 
             let contract_id = QualifiedContractIdentifier::transient();
+            let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
 
             let mut stdout = io::stdout();
 
@@ -1099,7 +1142,7 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 };
 
-                let mut ast = match parse(&contract_id, &content) {
+                let mut ast = match parse(&contract_id, &content, clarity_version) {
                     Ok(val) => val,
                     Err(error) => {
                         println!("Parse error:\n{}", error);
@@ -1107,7 +1150,13 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                     }
                 };
 
-                match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, true) {
+                match run_analysis_free(
+                    &contract_id,
+                    &mut ast,
+                    &mut analysis_marf,
+                    true,
+                    clarity_version,
+                ) {
                     Ok(_) => (),
                     Err((error, _)) => {
                         println!("Type check error:\n{}", error);
@@ -1143,9 +1192,19 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let contract_id = QualifiedContractIdentifier::transient();
 
-            let mut ast =
-                friendly_expect(parse(&contract_id, &content), "Failed to parse program.");
-            match run_analysis_free(&contract_id, &mut ast, &mut analysis_marf, true) {
+            let clarity_version = ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH);
+
+            let mut ast = friendly_expect(
+                parse(&contract_id, &content, clarity_version),
+                "Failed to parse program.",
+            );
+            match run_analysis_free(
+                &contract_id,
+                &mut ast,
+                &mut analysis_marf,
+                true,
+                clarity_version,
+            ) {
                 Ok(_) => {
                     let result = vm_env.get_exec_environment(None).eval_raw(&content);
                     match result {
@@ -1373,9 +1432,23 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 } else {
                     false
                 };
+            let clarity_version =
+                if let Ok(optarg) = consume_arg(&mut argv, &["--clarity_version"], true) {
+                    optarg
+                        .map(|optarg_str| {
+                            friendly_expect(
+                                ClarityVersion::from_str(&optarg_str),
+                                &format!("Error parsing Clarity version '{}'", &optarg_str),
+                            )
+                        })
+                        .unwrap_or(ClarityVersion::default_for_epoch(DEFAULT_CLI_EPOCH))
+                } else {
+                    eprintln!("Expected argument for --clarity_version");
+                    panic_test!();
+                };
             if argv.len() < 4 {
                 eprintln!(
-                    "Usage: {} {} [--costs] [--assets] [--output_analysis] [contract-identifier] [contract-definition.clar] [vm-state.db]",
+                    "Usage: {} {} [--costs] [--assets] [--output_analysis] [--clarity_version CLARITY_VERSION] [contract-identifier] [contract-definition.clar] [vm-state.db]",
                     invoked_by, argv[0]
                 );
                 panic_test!();
@@ -1392,9 +1465,8 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
                 &format!("Error reading file: {}", argv[2]),
             );
 
-            // TODO: Add --clarity_version as command line argument
             let mut ast = friendly_expect(
-                parse(&contract_identifier, &contract_content),
+                parse(&contract_identifier, &contract_content, clarity_version),
                 "Failed to parse program.",
             );
             let header_db =
@@ -1407,15 +1479,24 @@ pub fn invoke_command(invoked_by: &str, args: &[String]) -> (i32, Option<serde_j
 
             let (_, _, analysis_result_and_cost) =
                 in_block(header_db, marf_kv, |header_db, mut marf| {
-                    let analysis_result =
-                        run_analysis(&contract_identifier, &mut ast, &header_db, &mut marf, true);
+                    let analysis_result = run_analysis(
+                        &contract_identifier,
+                        &mut ast,
+                        &header_db,
+                        &mut marf,
+                        true,
+                        clarity_version,
+                    );
                     match analysis_result {
                         Err(e) => (header_db, marf, Err(e)),
                         Ok(analysis) => {
                             let result_and_cost =
                                 with_env_costs(mainnet, &header_db, &mut marf, |vm_env| {
-                                    vm_env
-                                        .initialize_contract(contract_identifier, &contract_content)
+                                    vm_env.initialize_versioned_contract(
+                                        contract_identifier,
+                                        clarity_version,
+                                        &contract_content,
+                                    )
                                 });
                             (header_db, marf, Ok((analysis, result_and_cost)))
                         }
@@ -21,8 +21,10 @@ use std::thread;
 
 use crate::chainstate::stacks::boot::BOOT_CODE_COSTS_2_TESTNET;
 use crate::chainstate::stacks::boot::{
-    BOOT_CODE_COSTS, BOOT_CODE_COSTS_2, BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING,
-    BOOT_CODE_POX_TESTNET, COSTS_2_NAME,
+    BOOT_CODE_COSTS, BOOT_CODE_COSTS_2, BOOT_CODE_COSTS_3,
+    BOOT_CODE_COST_VOTING_TESTNET as BOOT_CODE_COST_VOTING, BOOT_CODE_POX_2_MAINNET,
+    BOOT_CODE_POX_2_TESTNET, BOOT_CODE_POX_TESTNET, COSTS_2_NAME, COSTS_3_NAME, POX_1_NAME,
+    POX_2_NAME,
 };
 use crate::chainstate::stacks::db::StacksAccount;
 use crate::chainstate::stacks::db::StacksChainState;
@@ -688,6 +690,7 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
                     .expect("FATAL: invalid boot-code contract name"),
                 code_body: StacksString::from_str(cost_2_code)
                     .expect("FATAL: invalid boot code body"),
+                clarity_version: None,
             });
 
             let costs_2_contract_tx =
@@ -726,6 +729,179 @@ impl<'a, 'b> ClarityBlockConnection<'a, 'b> {
         })
     }
 
+    pub fn initialize_epoch_2_1(&mut self) -> Result<StacksTransactionReceipt, Error> {
+        // use the `using!` statement to ensure that the old cost_tracker is placed
+        //  back in all branches after initialization
+        using!(self.cost_track, "cost tracker", |old_cost_tracker| {
+            // epoch initialization is *free*
+            self.cost_track.replace(LimitedCostTracker::new_free());
+
+            let mainnet = self.mainnet;
+
+            // get the boot code account information
+            //  for processing the pox contract initialization
+            let tx_version = if mainnet {
+                TransactionVersion::Mainnet
+            } else {
+                TransactionVersion::Testnet
+            };
+
+            let boot_code_address = boot_code_addr(mainnet);
+
+            let boot_code_auth = boot_code_tx_auth(boot_code_address);
+
+            let boot_code_nonce = self.with_clarity_db_readonly(|db| {
+                db.get_account_nonce(&boot_code_address.clone().into())
+            });
+
+            let boot_code_account = boot_code_acc(boot_code_address, boot_code_nonce);
+
+            // instantiate costs 3 contract...
+            let payload = TransactionPayload::SmartContract(TransactionSmartContract {
+                name: ContractName::try_from(COSTS_3_NAME)
+                    .expect("FATAL: invalid boot-code contract name"),
+                code_body: StacksString::from_str(BOOT_CODE_COSTS_3)
+                    .expect("FATAL: invalid boot code body"),
+                clarity_version: None,
+            });
+
+            let costs_3_contract_tx =
+                StacksTransaction::new(tx_version.clone(), boot_code_auth.clone(), payload);
+
+            let costs_3_initialization_receipt = self.as_transaction(|tx_conn| {
+                // bump the epoch in the Clarity DB
+                tx_conn
+                    .with_clarity_db(|db| {
+                        db.set_clarity_epoch_version(StacksEpochId::Epoch21);
+                        Ok(())
+                    })
+                    .unwrap();
+
+                // initialize with a synthetic transaction
+                let receipt = StacksChainState::process_transaction_payload(
+                    tx_conn,
+                    &costs_3_contract_tx,
+                    &boot_code_account,
+                )
+                .expect("FATAL: Failed to process Costs 3 contract initialization");
+
+                receipt
+            });
+
+            if costs_3_initialization_receipt.result != Value::okay_true()
+                || costs_3_initialization_receipt.post_condition_aborted
+            {
+                panic!(
+                    "FATAL: Failure processing Costs 3 contract initialization: {:#?}",
+                    &costs_3_initialization_receipt
+                );
+            }
+
+            // the pox-2 contract inherits the burnchain parameters of the pox contract
+            let pox_1_contract = boot_code_id(POX_1_NAME, mainnet);
+            let pox_1_info = self
+                .with_readonly_clarity_env(
+                    mainnet,
+                    PrincipalData::from(pox_1_contract.clone()),
+                    LimitedCostTracker::new_free(),
+                    |env| env.execute_contract(&pox_1_contract, "get-pox-info", &[], true),
+                )
+                .expect("FATAL: failed to read PoX parameters from the pox contract")
+                .expect_result_ok()
+                .expect_tuple();
+            let pox_2_params: Vec<Value> = [
+                "first-burnchain-block-height",
+                "prepare-cycle-length",
+                "reward-cycle-length",
+                "rejection-fraction",
+            ]
+            .iter()
+            .map(|name| {
+                pox_1_info
+                    .get(name)
+                    .expect("FATAL: missing PoX parameter in (get-pox-info)")
+                    .clone()
+            })
+            .collect();
+
+            // instantiate PoX 2 contract...
+            let pox_2_code = if mainnet {
+                &*BOOT_CODE_POX_2_MAINNET
+            } else {
+                &*BOOT_CODE_POX_2_TESTNET
+            };
+
+            let payload = TransactionPayload::SmartContract(TransactionSmartContract {
+                name: ContractName::try_from(POX_2_NAME)
+                    .expect("FATAL: invalid boot-code contract name"),
+                code_body: StacksString::from_str(pox_2_code)
+                    .expect("FATAL: invalid boot code body"),
+                clarity_version: None,
+            });
+
+            let pox_2_contract_tx =
+                StacksTransaction::new(tx_version.clone(), boot_code_auth.clone(), payload);
+
+            let initialization_receipt = self.as_transaction(|tx_conn| {
+                // initialize with a synthetic transaction
+                let receipt = StacksChainState::process_transaction_payload(
+                    tx_conn,
+                    &pox_2_contract_tx,
+                    &boot_code_account,
+                )
+                .expect("FATAL: Failed to process PoX 2 contract initialization");
+
+                receipt
+            });
+
+            if initialization_receipt.result != Value::okay_true()
+                || initialization_receipt.post_condition_aborted
+            {
+                panic!(
+                    "FATAL: Failure processing PoX 2 contract initialization: {:#?}",
+                    &initialization_receipt
+                );
+            }
+
+            // set up the burnchain parameters for the pox-2 contract
+            let pox_2_contract = boot_code_id(POX_2_NAME, mainnet);
+            let sender = PrincipalData::from(pox_2_contract.clone());
+            self.as_transaction(|tx_conn| {
+                tx_conn
+                    .run_contract_call(
+                        &sender,
+                        &pox_2_contract,
+                        "set-burnchain-parameters",
+                        &pox_2_params,
+                        |_, _| false,
+                    )
+                    .expect("FATAL: Failed to set burnchain parameters in PoX 2 contract");
+            });
+
+            // the block's cost tracker was loaded from the 2.05 default cost contract, which
+            //  does not define the costs of natives added in 2.1. Reload it against costs-3.
+            let cost_tracker = match old_cost_tracker {
+                LimitedCostTracker::Free => LimitedCostTracker::Free,
+                old_cost_tracker => {
+                    let mut clarity_db = self
+                        .datastore
+                        .as_clarity_db(&NULL_HEADER_DB, &NULL_BURN_STATE_DB);
+                    let mut cost_tracker = LimitedCostTracker::new_mid_block(
+                        mainnet,
+                        old_cost_tracker.get_limit(),
+                        &mut clarity_db,
+                        StacksEpochId::Epoch21,
+                    )
+                    .expect("FATAL: failed to reload cost tracking for Epoch 2.1");
+                    cost_tracker.set_total(old_cost_tracker.get_total());
+                    cost_tracker
+                }
+            };
+
+            (cost_tracker, Ok(initialization_receipt))
+        })
+    }
+
     pub fn start_transaction_processing<'c>(&'c mut self) -> ClarityTransactionConnection<'c, 'a> {
         let store = &mut self.datastore;
         let cost_track = &mut self.cost_track;
@@ -1002,13 +1178,14 @@ mod tests {
     use crate::chainstate::stacks::index::storage::TrieFileStorage;
     use clarity::vm::analysis::errors::CheckErrors;
     use clarity::vm::database::{ClarityBackingStore, STXBalance};
-    use clarity::vm::types::{StandardPrincipalData, Value};
+    use clarity::vm::types::{StandardPrincipalData, TupleData, Value};
 
     use crate::core::{PEER_VERSION_EPOCH_1_0, PEER_VERSION_EPOCH_2_0, PEER_VERSION_EPOCH_2_05};
     use clarity::vm::test_util::{TEST_BURN_STATE_DB, TEST_HEADER_DB};
 
     use crate::chainstate::stacks::index::ClarityMarfTrieId;
     use crate::clarity_vm::database::marf::MarfedKV;
+    use crate::types::chainstate::ConsensusHash;
 
     use super::*;
 
@@ -1595,6 +1772,7 @@ mod tests {
             TransactionPayload::SmartContract(TransactionSmartContract {
                 name: "hello-world".into(),
                 code_body: StacksString::from_str(contract).unwrap(),
+                clarity_version: None,
             })
             .into(),
         );
@@ -1605,6 +1783,7 @@ mod tests {
             TransactionPayload::SmartContract(TransactionSmartContract {
                 name: "hello-world".into(),
                 code_body: StacksString::from_str(contract).unwrap(),
+                clarity_version: None,
             })
             .into(),
         );
@@ -1722,6 +1901,21 @@ mod tests {
             ) -> Option<StacksEpoch> {
                 self.get_stacks_epoch(0)
             }
+
+            fn get_sortition_id_from_consensus_hash(
+                &self,
+                _consensus_hash: &ConsensusHash,
+            ) -> Option<SortitionId> {
+                None
+            }
+
+            fn get_pox_payout_addrs(
+                &self,
+                _height: u32,
+                _sortition_id: &SortitionId,
+            ) -> Option<(Vec<TupleData>, u128)> {
+                None
+            }
         }
 
         let burn_state_db = BlockLimitBurnStateDB {};
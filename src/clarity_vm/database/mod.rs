@@ -3,6 +3,7 @@ use rusqlite::{Connection, OptionalExtension};
 use crate::chainstate::burn::db::sortdb::{
     SortitionDB, SortitionDBConn, SortitionHandleConn, SortitionHandleTx,
 };
+use crate::chainstate::stacks::address::StacksAddressExtensions;
 use crate::chainstate::stacks::db::{MinerPaymentSchedule, StacksHeaderInfo};
 use crate::chainstate::stacks::index::MarfTrieId;
 use crate::util_lib::db::{DBConn, FromRow};
@@ -12,12 +13,13 @@ use clarity::vm::database::{
     NULL_BURN_STATE_DB, NULL_HEADER_DB,
 };
 use clarity::vm::errors::{InterpreterResult, RuntimeErrorType};
+use clarity::vm::types::TupleData;
 
 use crate::chainstate::stacks::db::ChainstateTx;
 use crate::chainstate::stacks::index::marf::MarfConnection;
 use crate::chainstate::stacks::index::{ClarityMarfTrieId, TrieMerkleProof};
 use crate::types::chainstate::StacksBlockId;
-use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, SortitionId};
+use crate::types::chainstate::{BlockHeaderHash, BurnchainHeaderHash, ConsensusHash, SortitionId};
 use crate::types::chainstate::{StacksAddress, VRFSeed};
 
 use crate::core::StacksEpoch;
@@ -62,6 +64,10 @@ impl<'a> HeadersDB for HeadersDBConn<'a> {
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         get_miner_info(self.0, id_bhh).map(|x| x.address)
     }
+
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        get_stacks_header_info(self.0, id_bhh).map(|x| x.consensus_hash)
+    }
 }
 
 impl<'a> HeadersDB for ChainstateTx<'a> {
@@ -95,6 +101,10 @@ impl<'a> HeadersDB for ChainstateTx<'a> {
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         get_miner_info(self.deref().deref(), id_bhh).map(|x| x.address)
     }
+
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        get_stacks_header_info(self.deref().deref(), id_bhh).map(|x| x.consensus_hash)
+    }
 }
 
 impl HeadersDB for crate::chainstate::stacks::index::marf::MARF<StacksBlockId> {
@@ -128,6 +138,10 @@ impl HeadersDB for crate::chainstate::stacks::index::marf::MARF<StacksBlockId> {
     fn get_miner_address(&self, id_bhh: &StacksBlockId) -> Option<StacksAddress> {
         get_miner_info(self.sqlite_conn(), id_bhh).map(|x| x.address)
     }
+
+    fn get_consensus_hash_for_block(&self, id_bhh: &StacksBlockId) -> Option<ConsensusHash> {
+        get_stacks_header_info(self.sqlite_conn(), id_bhh).map(|x| x.consensus_hash)
+    }
 }
 
 fn get_stacks_header_info(conn: &DBConn, id_bhh: &StacksBlockId) -> Option<StacksHeaderInfo> {
@@ -150,6 +164,24 @@ fn get_miner_info(conn: &DBConn, id_bhh: &StacksBlockId) -> Option<MinerPaymentS
     .expect("Unexpected SQL failure querying payment table")
 }
 
+/// Get the PoX payouts of the burnchain block at `height` in the fork of `db_handle`'s chain tip.
+fn get_pox_payout_addrs_at_height(
+    db_handle: &SortitionHandleConn,
+    height: u32,
+) -> Option<(Vec<TupleData>, u128)> {
+    let snapshot = db_handle
+        .get_block_snapshot_by_height(height as u64)
+        .ok()??;
+    let (pox_addrs, payout) = db_handle
+        .get_reward_set_payouts_at(&snapshot.sortition_id)
+        .ok()?;
+    let pox_addrs = pox_addrs
+        .iter()
+        .map(|addr| addr.as_clarity_tuple())
+        .collect();
+    Some((pox_addrs, payout))
+}
+
 impl BurnStateDB for SortitionHandleTx<'_> {
     fn get_burn_block_height(&self, sortition_id: &SortitionId) -> Option<u32> {
         match SortitionDB::get_block_snapshot(self.tx(), sortition_id) {
@@ -185,6 +217,29 @@ impl BurnStateDB for SortitionHandleTx<'_> {
         SortitionDB::get_stacks_epoch_by_epoch_id(self.tx(), epoch_id)
             .expect("BUG: failed to get epoch for epoch id")
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        SortitionDB::get_sortition_id_by_consensus(self.tx(), consensus_hash)
+            .expect("BUG: failed to query sortition id by consensus hash")
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let readonly_marf = self
+            .index()
+            .reopen_readonly()
+            .expect("BUG: failure trying to get a read-only interface into the sortition db.");
+        let mut context = self.context.clone();
+        context.chain_tip = sortition_id.clone();
+        let db_handle = SortitionHandleConn::new(&readonly_marf, context);
+        get_pox_payout_addrs_at_height(&db_handle, height)
+    }
 }
 
 impl BurnStateDB for SortitionDBConn<'_> {
@@ -216,6 +271,75 @@ impl BurnStateDB for SortitionDBConn<'_> {
         SortitionDB::get_stacks_epoch_by_epoch_id(self.conn(), epoch_id)
             .expect("BUG: failed to get epoch for epoch id")
     }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        SortitionDB::get_sortition_id_by_consensus(self.conn(), consensus_hash)
+            .expect("BUG: failed to query sortition id by consensus hash")
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let db_handle = SortitionHandleConn::open_reader(self, &sortition_id).ok()?;
+        get_pox_payout_addrs_at_height(&db_handle, height)
+    }
+}
+
+impl BurnStateDB for SortitionHandleConn<'_> {
+    fn get_burn_block_height(&self, sortition_id: &SortitionId) -> Option<u32> {
+        match SortitionDB::get_block_snapshot(self.conn(), sortition_id) {
+            Ok(Some(x)) => Some(x.block_height as u32),
+            _ => return None,
+        }
+    }
+
+    fn get_burn_header_hash(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<BurnchainHeaderHash> {
+        let mut context = self.context.clone();
+        context.chain_tip = sortition_id.clone();
+        let db_handle = SortitionHandleConn::new(self.index, context);
+        match db_handle.get_block_snapshot_by_height(height as u64) {
+            Ok(Some(x)) => Some(x.burn_header_hash),
+            _ => return None,
+        }
+    }
+
+    fn get_stacks_epoch(&self, height: u32) -> Option<StacksEpoch> {
+        SortitionDB::get_stacks_epoch(self.conn(), height as u64)
+            .expect("BUG: failed to get epoch for burn block height")
+    }
+
+    fn get_stacks_epoch_by_epoch_id(&self, epoch_id: &StacksEpochId) -> Option<StacksEpoch> {
+        SortitionDB::get_stacks_epoch_by_epoch_id(self.conn(), epoch_id)
+            .expect("BUG: failed to get epoch for epoch id")
+    }
+
+    fn get_sortition_id_from_consensus_hash(
+        &self,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<SortitionId> {
+        SortitionDB::get_sortition_id_by_consensus(self.conn(), consensus_hash)
+            .expect("BUG: failed to query sortition id by consensus hash")
+    }
+
+    fn get_pox_payout_addrs(
+        &self,
+        height: u32,
+        sortition_id: &SortitionId,
+    ) -> Option<(Vec<TupleData>, u128)> {
+        let mut context = self.context.clone();
+        context.chain_tip = sortition_id.clone();
+        let db_handle = SortitionHandleConn::new(self.index, context);
+        get_pox_payout_addrs_at_height(&db_handle, height)
+    }
 }
 
 pub struct MemoryBackingStore {
@@ -30,8 +30,11 @@ use clarity::vm::types::{
     Value,
 };
 
+use crate::chainstate::stacks::boot::{POX_1_NAME, POX_2_NAME};
 use crate::chainstate::stacks::db::StacksChainState;
+use crate::chainstate::stacks::Error as ChainstateError;
 use crate::chainstate::stacks::StacksMicroblockHeader;
+use crate::core::StacksEpochId;
 use crate::util_lib::boot::boot_code_id;
 
 use clarity::vm::events::{STXEventType, STXLockEventData, StacksTransactionEvent};
@@ -73,19 +76,110 @@ fn parse_pox_stacking_result(
     }
 }
 
+fn parse_pox_extend_result(result: &Value) -> std::result::Result<(PrincipalData, u64), i128> {
+    match result.clone().expect_result() {
+        Ok(res) => {
+            // should have gotten back (ok { stacker: principal, unlock-burn-height: uint })
+            let tuple_data = res.expect_tuple();
+            let stacker = tuple_data
+                .get("stacker")
+                .expect(&format!("FATAL: no 'stacker'"))
+                .to_owned()
+                .expect_principal();
+
+            let unlock_burn_height = tuple_data
+                .get("unlock-burn-height")
+                .expect(&format!("FATAL: no 'unlock-burn-height'"))
+                .to_owned()
+                .expect_u128()
+                .try_into()
+                .expect("FATAL: 'unlock-burn-height' overflow");
+
+            Ok((stacker, unlock_burn_height))
+        }
+        Err(e) => Err(e.expect_i128()),
+    }
+}
+
+fn parse_pox_increase_result(result: &Value) -> std::result::Result<(PrincipalData, u128), i128> {
+    match result.clone().expect_result() {
+        Ok(res) => {
+            // should have gotten back (ok { stacker: principal, total-locked: uint })
+            let tuple_data = res.expect_tuple();
+            let stacker = tuple_data
+                .get("stacker")
+                .expect(&format!("FATAL: no 'stacker'"))
+                .to_owned()
+                .expect_principal();
+
+            let total_locked = tuple_data
+                .get("total-locked")
+                .expect(&format!("FATAL: no 'total-locked'"))
+                .to_owned()
+                .expect_u128();
+
+            Ok((stacker, total_locked))
+        }
+        Err(e) => Err(e.expect_i128()),
+    }
+}
+
+/// Starting in Stacks 2.1, a lock can fail because the account is already locked through the
+/// other PoX contract.  That is the sender's fault rather than a contract bug, so the
+/// transaction is aborted instead of crashing the node.
+fn handle_pox_lock_error(
+    global_context: &mut GlobalContext,
+    e: ChainstateError,
+    description: String,
+) -> Result<()> {
+    if global_context.database.get_clarity_epoch_version() >= StacksEpochId::Epoch21 {
+        match e {
+            ChainstateError::PoxAlreadyLocked
+            | ChainstateError::PoxNotLocked
+            | ChainstateError::PoxInsufficientBalance
+            | ChainstateError::PoxInvalidLockUpdate => {
+                warn!(
+                    "Aborting PoX contract-call: failed to {}: '{:?}'",
+                    description, &e
+                );
+                return Err(Error::Runtime(RuntimeErrorType::PoxAlreadyLocked, None));
+            }
+            _ => {}
+        }
+    }
+    panic!("FATAL: failed to {}: '{:?}'", description, &e);
+}
+
+fn push_pox_lock_event(
+    global_context: &mut GlobalContext,
+    locked_address: PrincipalData,
+    locked_amount: u128,
+    unlock_height: u64,
+) {
+    if let Some(batch) = global_context.event_batches.last_mut() {
+        batch.events.push(StacksTransactionEvent::STXEvent(
+            STXEventType::STXLockEvent(STXLockEventData {
+                locked_amount,
+                unlock_height,
+                locked_address,
+            }),
+        ));
+    }
+}
+
 /// Handle special cases when calling into the PoX API contract
 fn handle_pox_api_contract_call(
     global_context: &mut GlobalContext,
     _sender_opt: Option<&PrincipalData>,
+    contract_id: &QualifiedContractIdentifier,
     function_name: &str,
     value: &Value,
 ) -> Result<()> {
+    let is_pox_2 = *contract_id == boot_code_id(POX_2_NAME, global_context.mainnet);
     if function_name == "stack-stx" || function_name == "delegate-stack-stx" {
         debug!(
             "Handle special-case contract-call to {:?} {} (which returned {:?})",
-            boot_code_id("pox", global_context.mainnet),
-            function_name,
-            value
+            contract_id, function_name, value
         );
 
         // applying a pox lock at this point is equivalent to evaluating a transfer
@@ -98,7 +192,7 @@ fn handle_pox_api_contract_call(
         match parse_pox_stacking_result(value) {
             Ok((stacker, locked_amount, unlock_height)) => {
                 // if this fails, then there's a bug in the contract (since it already does
-                // the necessary checks)
+                // the necessary checks), or the account is locked in the other PoX contract
                 match StacksChainState::pox_lock(
                     &mut global_context.database,
                     &stacker,
@@ -106,21 +200,100 @@ fn handle_pox_api_contract_call(
                     unlock_height as u64,
                 ) {
                     Ok(_) => {
-                        if let Some(batch) = global_context.event_batches.last_mut() {
-                            batch.events.push(StacksTransactionEvent::STXEvent(
-                                STXEventType::STXLockEvent(STXLockEventData {
-                                    locked_amount,
-                                    unlock_height,
-                                    locked_address: stacker,
-                                }),
-                            ));
-                        }
+                        push_pox_lock_event(global_context, stacker, locked_amount, unlock_height);
                     }
                     Err(e) => {
-                        panic!(
-                            "FATAL: failed to lock {} from {} until {}: '{:?}'",
-                            locked_amount, stacker, unlock_height, &e
+                        let description = format!(
+                            "lock {} from {} until {}",
+                            locked_amount, stacker, unlock_height
                         );
+                        return handle_pox_lock_error(global_context, e, description);
+                    }
+                }
+
+                return Ok(());
+            }
+            Err(_) => {
+                // nothing to do -- the function failed
+                return Ok(());
+            }
+        }
+    } else if is_pox_2
+        && (function_name == "stack-extend" || function_name == "delegate-stack-extend")
+    {
+        debug!(
+            "Handle special-case contract-call to {:?} {} (which returned {:?})",
+            contract_id, function_name, value
+        );
+
+        // extending a pox lock at this point is equivalent to evaluating a transfer
+        runtime_cost(
+            ClarityCostFunction::StxTransfer,
+            &mut global_context.cost_track,
+            1,
+        )?;
+
+        match parse_pox_extend_result(value) {
+            Ok((stacker, unlock_height)) => {
+                match StacksChainState::pox_lock_extend(
+                    &mut global_context.database,
+                    &stacker,
+                    unlock_height,
+                ) {
+                    Ok(_) => {
+                        let locked_amount = global_context
+                            .database
+                            .get_account_stx_balance(&stacker)
+                            .amount_locked;
+                        push_pox_lock_event(global_context, stacker, locked_amount, unlock_height);
+                    }
+                    Err(e) => {
+                        let description =
+                            format!("extend lock of {} until {}", stacker, unlock_height);
+                        return handle_pox_lock_error(global_context, e, description);
+                    }
+                }
+
+                return Ok(());
+            }
+            Err(_) => {
+                // nothing to do -- the function failed
+                return Ok(());
+            }
+        }
+    } else if is_pox_2
+        && (function_name == "stack-increase" || function_name == "delegate-stack-increase")
+    {
+        debug!(
+            "Handle special-case contract-call to {:?} {} (which returned {:?})",
+            contract_id, function_name, value
+        );
+
+        // increasing a pox lock at this point is equivalent to evaluating a transfer
+        runtime_cost(
+            ClarityCostFunction::StxTransfer,
+            &mut global_context.cost_track,
+            1,
+        )?;
+
+        match parse_pox_increase_result(value) {
+            Ok((stacker, total_locked)) => {
+                match StacksChainState::pox_lock_increase(
+                    &mut global_context.database,
+                    &stacker,
+                    total_locked,
+                ) {
+                    Ok(_) => {
+                        let unlock_height = global_context
+                            .database
+                            .get_account_stx_balance(&stacker)
+                            .unlock_height;
+                        push_pox_lock_event(global_context, stacker, total_locked, unlock_height);
+                    }
+                    Err(e) => {
+                        let description =
+                            format!("increase lock of {} to {}", stacker, total_locked);
+                        return handle_pox_lock_error(global_context, e, description);
                     }
                 }
 
@@ -144,8 +317,16 @@ pub fn handle_contract_call_special_cases(
     function_name: &str,
     result: &Value,
 ) -> Result<()> {
-    if *contract_id == boot_code_id("pox", global_context.mainnet) {
-        return handle_pox_api_contract_call(global_context, sender, function_name, result);
+    if *contract_id == boot_code_id(POX_1_NAME, global_context.mainnet)
+        || *contract_id == boot_code_id(POX_2_NAME, global_context.mainnet)
+    {
+        return handle_pox_api_contract_call(
+            global_context,
+            sender,
+            contract_id,
+            function_name,
+            result,
+        );
     }
     // TODO: insert more special cases here, as needed
     Ok(())
@@ -30,6 +30,7 @@ use clarity::vm::test_util::{TEST_BURN_STATE_DB, TEST_HEADER_DB};
 use clarity::vm::types::{
     AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, Value,
 };
+use clarity::vm::ClarityVersion;
 use stacks_common::util::hash::hex_bytes;
 
 use crate::chainstate::stacks::index::ClarityMarfTrieId;
@@ -178,7 +179,10 @@ pub fn test_tracked_costs(prog: &str, use_mainnet: bool, epoch: StacksEpochId) -
 fn test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch20);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.get_version() <= ClarityVersion::Clarity1)
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch20);
         assert!(cost.exceeds(&baseline));
@@ -198,7 +202,10 @@ fn test_all_testnet() {
 fn epoch_205_test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch2_05);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.get_version() <= ClarityVersion::Clarity1)
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch2_05);
         assert!(cost.exceeds(&baseline));
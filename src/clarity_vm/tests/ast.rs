@@ -2,6 +2,7 @@ use crate::clarity_vm::{clarity::ClarityInstance, database::marf::MarfedKV};
 use clarity::vm::ast::build_ast;
 use clarity::vm::test_util::{TEST_BURN_STATE_DB, TEST_HEADER_DB};
 use clarity::vm::types::QualifiedContractIdentifier;
+use clarity::vm::ClarityVersion;
 use stacks_common::types::chainstate::StacksBlockId;
 
 use crate::chainstate::stacks::index::ClarityMarfTrieId;
@@ -41,6 +42,7 @@ fn dependency_edge_counting_runtime(iters: usize) -> u64 {
         &QualifiedContractIdentifier::transient(),
         &progn,
         &mut cost_track,
+        ClarityVersion::Clarity1,
     )
     .unwrap();
 
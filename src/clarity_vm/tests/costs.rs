@@ -28,7 +28,9 @@ use clarity::vm::contexts::Environment;
 use clarity::vm::contexts::{AssetMap, AssetMapEntry, GlobalContext, OwnedEnvironment};
 use clarity::vm::contracts::Contract;
 use clarity::vm::costs::cost_functions::ClarityCostFunction;
-use clarity::vm::costs::{ClarityCostFunctionReference, ExecutionCost, LimitedCostTracker};
+use clarity::vm::costs::{
+    load_effective_cost_functions, ClarityCostFunctionReference, ExecutionCost, LimitedCostTracker,
+};
 use clarity::vm::database::ClarityDatabase;
 use clarity::vm::errors::{CheckErrors, Error, RuntimeErrorType};
 use clarity::vm::events::StacksTransactionEvent;
@@ -38,8 +40,9 @@ use clarity::vm::test_util::{
     execute, execute_on_network, symbols_from_values, TEST_BURN_STATE_DB, TEST_HEADER_DB,
 };
 use clarity::vm::types::{
-    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, Value,
+    AssetIdentifier, PrincipalData, QualifiedContractIdentifier, ResponseData, TypeSignature, Value,
 };
+use clarity::vm::ClarityVersion;
 use stacks_common::util::hash::hex_bytes;
 use std::collections::HashMap;
 
@@ -138,6 +141,26 @@ pub fn get_simple_test(function: &NativeFunctions) -> &'static str {
         GetStxBalance => "(stx-get-balance 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxTransfer => "(stx-transfer? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
         StxBurn => "(stx-burn? u1 'SZ2J6ZY48GV1EZ5V2V5RB9MP66SW86PYKKQ9H6DPR)",
+        ToConsensusBuff => "(to-consensus-buff u1)",
+        FromConsensusBuff => "(from-consensus-buff bool 0x03)",
+        Slice => "(slice list-bar u1 u2)",
+        ReplaceAt => "(replace-at list-bar u0 5)",
+        BuffToIntLe => "(buff-to-int-le 0x00000000000000000000000000000001)",
+        BuffToUIntLe => "(buff-to-uint-le 0x00000000000000000000000000000001)",
+        BuffToIntBe => "(buff-to-int-be 0x00000000000000000000000000000001)",
+        BuffToUIntBe => "(buff-to-uint-be 0x00000000000000000000000000000001)",
+        IntToAscii => "(int-to-ascii 1)",
+        StringToInt => "(string-to-int \"1\")",
+        StringToUInt => "(string-to-uint \"1\")",
+        PrincipalDestruct => "(principal-destruct 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)",
+        PrincipalConstruct => "(principal-construct 0x1a 0x164247d6f2b425ac5771423ae6c80c754f7172b0)",
+        IsStandard => "(is-standard 'STB44HYPYAT2BB2QE513NSP81HTMYWBJP02HPGK6)",
+        GetBurnBlockInfo => "(get-burn-block-info? header-hash u1)",
+        BitwiseAnd => "(bit-and 2 3)",
+        BitwiseOr => "(bit-or 5 2)",
+        BitwiseNot => "(bit-not 26)",
+        BitwiseLShift => "(bit-shift-left 2 u1)",
+        BitwiseRShift => "(bit-shift-right 2 u1)",
     }
 }
 
@@ -861,7 +884,10 @@ fn test_tracked_costs(prog: &str, use_mainnet: bool, epoch: StacksEpochId) -> Ex
 fn test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch20);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.get_version() <= ClarityVersion::Clarity1)
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch20);
         assert!(cost.exceeds(&baseline));
@@ -883,7 +909,10 @@ fn test_all_testnet() {
 fn epoch_205_test_all(use_mainnet: bool) {
     let baseline = test_tracked_costs("1", use_mainnet, StacksEpochId::Epoch2_05);
 
-    for f in NativeFunctions::ALL.iter() {
+    for f in NativeFunctions::ALL
+        .iter()
+        .filter(|f| f.get_version() <= ClarityVersion::Clarity1)
+    {
         let test = get_simple_test(f);
         let cost = test_tracked_costs(test, use_mainnet, StacksEpochId::Epoch2_05);
         assert!(cost.exceeds(&baseline));
@@ -1455,6 +1484,44 @@ fn test_cost_voting_integration(use_mainnet: bool) {
         store.test_commit();
     }
 
+    {
+        // the newly-confirmed proposals are reported as effective without being processed into
+        // the stored cost state
+        let mut store = marf_kv.begin(&StacksBlockId([4 as u8; 32]), &StacksBlockId([6 as u8; 32]));
+        let mut db = store.as_clarity_db(&TEST_HEADER_DB, &TEST_BURN_STATE_DB);
+        db.begin();
+
+        let processed_count_before = db
+            .get_value("vm-costs::last_processed_count", &TypeSignature::UIntType)
+            .map(|result| result.value);
+        let effective = load_effective_cost_functions(use_mainnet, &mut db).unwrap();
+        assert_eq!(effective.contract_call_circuits.len(), 2);
+        assert_eq!(
+            effective.cost_function_references.len(),
+            ClarityCostFunction::ALL.len()
+        );
+        for (target, referenced_function) in effective.cost_function_references.iter() {
+            if target == &ClarityCostFunction::Le {
+                assert_eq!(&referenced_function.contract_id, &cost_definer);
+                assert_eq!(&referenced_function.function_name, "cost-definition-le");
+            } else {
+                assert_eq!(
+                    &referenced_function.contract_id,
+                    &boot_code_id("costs", false)
+                );
+                assert_eq!(&referenced_function.function_name, target.get_name_str());
+            }
+        }
+        assert_eq!(
+            db.get_value("vm-costs::last_processed_count", &TypeSignature::UIntType)
+                .map(|result| result.value),
+            processed_count_before
+        );
+
+        db.roll_back();
+        store.rollback_block();
+    }
+
     {
         let mut store = marf_kv.begin(&StacksBlockId([4 as u8; 32]), &StacksBlockId([5 as u8; 32]));
         let mut owned_env = OwnedEnvironment::new_max_limit(
@@ -65,6 +65,33 @@ fn test_burnstatedb_epoch(
     }
 }
 
+fn test_burnstatedb_burn_block_info(
+    burnstatedb: &dyn BurnStateDB,
+    tip_sortition_id: &SortitionId,
+    height_start: u32,
+    height_end: u32,
+) {
+    for height in height_start..=height_end {
+        assert!(burnstatedb
+            .get_burn_header_hash(height, tip_sortition_id)
+            .is_some());
+
+        // no block-commits were mined, so no PoX outputs were paid
+        assert_eq!(
+            burnstatedb.get_pox_payout_addrs(height, tip_sortition_id),
+            Some((vec![], 0))
+        );
+    }
+
+    // no such burn block yet
+    assert!(burnstatedb
+        .get_burn_header_hash(height_end + 1, tip_sortition_id)
+        .is_none());
+    assert!(burnstatedb
+        .get_pox_payout_addrs(height_end + 1, tip_sortition_id)
+        .is_none());
+}
+
 #[test]
 fn test_vm_epoch_switch() {
     use crate::chainstate::burn::db::sortdb::tests::test_append_snapshot;
@@ -118,10 +145,28 @@ fn test_vm_epoch_switch() {
         end_height = cur_snapshot.block_height as u32;
     }
 
-    // impl BurnStateDB for SortitionHandleConn
+    // impl BurnStateDB for SortitionDBConn
     {
         let burndb = db.index_conn();
         test_burnstatedb_epoch(&burndb, start_height, end_height, 8, 12);
+        test_burnstatedb_burn_block_info(
+            &burndb,
+            &cur_snapshot.sortition_id,
+            start_height,
+            end_height,
+        );
+    }
+
+    // impl BurnStateDB for SortitionHandleConn
+    {
+        let burndb = db.index_handle_at_tip();
+        test_burnstatedb_epoch(&burndb, start_height, end_height, 8, 12);
+        test_burnstatedb_burn_block_info(
+            &burndb,
+            &cur_snapshot.sortition_id,
+            start_height,
+            end_height,
+        );
     }
 
     // impl BurnStateDB for SortitionHandleTx
@@ -129,5 +174,6 @@ fn test_vm_epoch_switch() {
         let tip = SortitionDB::get_canonical_burn_chain_tip(db.conn()).unwrap();
         let burntx = db.tx_handle_begin(&tip.sortition_id).unwrap();
         test_burnstatedb_epoch(&burntx, start_height, end_height, 8, 12);
+        test_burnstatedb_burn_block_info(&burntx, &tip.sortition_id, start_height, end_height);
     }
 }
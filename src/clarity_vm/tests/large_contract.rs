@@ -34,6 +34,7 @@ use stacks_common::util::hash::hex_bytes;
 
 use crate::clarity_vm::database::marf::MarfedKV;
 use clarity::vm::clarity::TransactionConnection;
+use clarity::vm::ClarityVersion;
 
 fn test_block_headers(n: u8) -> StacksBlockId {
     StacksBlockId([n as u8; 32])
@@ -94,7 +95,13 @@ fn test_simple_token_system() {
 
         let tokens_contract = SIMPLE_TOKENS;
 
-        let contract_ast = ast::build_ast(&contract_identifier, tokens_contract, &mut ()).unwrap();
+        let contract_ast = ast::build_ast(
+            &contract_identifier,
+            tokens_contract,
+            &mut (),
+            ClarityVersion::Clarity1,
+        )
+        .unwrap();
 
         block.as_transaction(|tx| {
             tx.initialize_smart_contract(
@@ -15,6 +15,7 @@
 // along with this program.  If not, see <http://www.gnu.org/licenses/>.
 
 use std::cmp;
+use std::collections::HashMap;
 use std::collections::HashSet;
 use std::fs;
 use std::hash::Hasher;
@@ -78,6 +79,7 @@ use crate::chainstate::stacks::StacksBlock;
 use crate::codec::Error as codec_error;
 use crate::codec::StacksMessageCodec;
 use crate::cost_estimates;
+use crate::cost_estimates::fee_medians::FeeRateAndWeight;
 use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::metrics::UnitMetric;
 use crate::cost_estimates::CostEstimator;
@@ -91,6 +93,9 @@ use crate::util_lib::db::table_exists;
 pub const MEMPOOL_MAX_TRANSACTION_AGE: u64 = 256;
 pub const MAXIMUM_MEMPOOL_TX_CHAINING: u64 = 25;
 
+// number of seconds for which we remember why a transaction was dropped from the mempool
+pub const MEMPOOL_DROPPED_TX_RETENTION: u64 = 7 * 24 * 3600;
+
 // name of table for storing the counting bloom filter
 pub const BLOOM_COUNTER_TABLE: &'static str = "txid_bloom_counter";
 
@@ -414,6 +419,20 @@ const MEMPOOL_SCHEMA_3_BLOOM_STATE: &'static [&'static str] = &[
     "#,
 ];
 
+const MEMPOOL_SCHEMA_4_DROPPED_TXS: &'static [&'static str] = &[
+    r#"
+    -- why (and when) a transaction left the mempool without being mined
+    CREATE TABLE IF NOT EXISTS dropped_txs(
+        txid TEXT PRIMARY KEY NOT NULL,
+        reason TEXT NOT NULL,
+        drop_time INTEGER NOT NULL
+    );
+    "#,
+    r#"
+    INSERT INTO schema_version (version) VALUES (4)
+    "#,
+];
+
 const MEMPOOL_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS by_txid ON mempool(txid);",
     "CREATE INDEX IF NOT EXISTS by_height ON mempool(height);",
@@ -425,6 +444,7 @@ const MEMPOOL_INDEXES: &'static [&'static str] = &[
     "CREATE INDEX IF NOT EXISTS fee_by_txid ON fee_estimates(txid);",
     "CREATE INDEX IF NOT EXISTS by_ordered_hashed_txid ON randomized_txids(hashed_txid ASC);",
     "CREATE INDEX IF NOT EXISTS by_hashed_txid ON randomized_txids(txid,hashed_txid);",
+    "CREATE INDEX IF NOT EXISTS by_drop_time ON dropped_txs(drop_time);",
 ];
 
 pub struct MemPoolDB {
@@ -692,6 +712,9 @@ impl MemPoolDB {
                     MemPoolDB::instantiate_bloom_state(tx)?;
                 }
                 3 => {
+                    MemPoolDB::instantiate_dropped_txs(tx)?;
+                }
+                4 => {
                     break;
                 }
                 _ => {
@@ -727,6 +750,15 @@ impl MemPoolDB {
         Ok(())
     }
 
+    /// Instantiate the table of dropped transactions
+    fn instantiate_dropped_txs(tx: &DBTx) -> Result<(), db_error> {
+        for sql_exec in MEMPOOL_SCHEMA_4_DROPPED_TXS {
+            tx.execute_batch(sql_exec)?;
+        }
+
+        Ok(())
+    }
+
     /// Instantiate the cost estimator schema
     fn instantiate_cost_estimator(tx: &DBTx) -> Result<(), db_error> {
         for sql_exec in MEMPOOL_SCHEMA_2_COST_ESTIMATOR {
@@ -1001,6 +1033,43 @@ impl MemPoolDB {
         Ok(updated)
     }
 
+    /// Sample the fee rates of the transactions that are still waiting to be mined, in
+    /// descending fee rate order.  Each transaction is weighted by the scalar cost it will
+    /// occupy in a block, which is recovered from its fee and its estimated fee rate.
+    /// Transactions whose nonces have already been consumed, and transactions without a fee
+    /// rate estimate, are skipped.  Sampling stops once the cumulative weight reaches
+    /// `max_weight`, so the returned list covers at most the first `max_weight` units of the
+    /// backlog.
+    pub fn get_pending_fee_rates(
+        conn: &DBConn,
+        max_weight: u64,
+    ) -> Result<Vec<FeeRateAndWeight>, db_error> {
+        let sql = "SELECT m.tx_fee AS tx_fee, f.fee_rate AS fee_rate FROM mempool AS m JOIN fee_estimates AS f ON m.txid = f.txid WHERE
+                   (m.last_known_origin_nonce IS NULL OR m.origin_nonce >= m.last_known_origin_nonce) AND
+                   (m.last_known_sponsor_nonce IS NULL OR m.sponsor_nonce >= m.last_known_sponsor_nonce) AND
+                   f.fee_rate IS NOT NULL AND f.fee_rate > 0 ORDER BY f.fee_rate DESC";
+        let mut stmt = conn.prepare(sql)?;
+        let mut rows = stmt.query(NO_PARAMS)?;
+
+        let mut pending = vec![];
+        let mut total_weight = 0u64;
+        while let Some(row) = rows.next()? {
+            if total_weight >= max_weight {
+                break;
+            }
+            let tx_fee: u64 = u64::from_column(row, "tx_fee")?;
+            let fee_rate: f64 = row.get_unwrap("fee_rate");
+            if !fee_rate.is_finite() {
+                continue;
+            }
+            let weight = cmp::max((tx_fee as f64 / fee_rate) as u64, 1);
+            total_weight = total_weight.saturating_add(weight);
+            pending.push(FeeRateAndWeight { fee_rate, weight });
+        }
+
+        Ok(pending)
+    }
+
     ///
     /// Iterate over candidates in the mempool
     ///  `todo` will be called once for each transaction whose origin nonce is equal
@@ -1373,6 +1442,13 @@ impl MemPoolDB {
 
         tx.update_mempool_pager(&txid)?;
 
+        // this tx is live again, even if it was dropped before
+        MemPoolDB::forget_dropped_tx(tx, &txid)?;
+
+        if let Some(ref prior_tx) = prior_tx {
+            MemPoolDB::record_dropped_txs(tx, &[prior_tx.txid.clone()], &replace_reason)?;
+        }
+
         // broadcast drop event if a tx is being replaced
         if let (Some(prior_tx), Some(event_observer)) = (prior_tx, event_observer) {
             event_observer.mempool_txs_dropped(vec![prior_tx.txid], replace_reason);
@@ -1382,31 +1458,99 @@ impl MemPoolDB {
     }
 
     /// Garbage-collect the mempool.  Remove transactions that have a given number of
-    /// confirmations.
-    pub fn garbage_collect(
+    /// confirmations.  Of these, the ones that were never mined are remembered as dropped.
+    /// `account_nonce` gives an account's nonce at the chain tip, if it can be found; a
+    /// transaction whose origin or sponsor nonce is below it (or whose accounts' nonces are
+    /// unknown) may have been mined, so it is not remembered.
+    pub fn garbage_collect<F>(
         tx: &mut MemPoolTx,
         min_height: u64,
+        mut account_nonce: F,
         event_observer: Option<&dyn MemPoolEventDispatcher>,
-    ) -> Result<(), db_error> {
+    ) -> Result<(), db_error>
+    where
+        F: FnMut(&StacksAddress) -> Option<u64>,
+    {
         let args: &[&dyn ToSql] = &[&u64_to_sql(min_height)?];
 
+        let sql = "SELECT * FROM mempool WHERE height < ?1";
+        let collected: Vec<MemPoolTxMetadata> = query_rows(tx, sql, args)?;
+
+        let mut unmined_txids = vec![];
+        for metadata in collected.iter() {
+            let origin_unmined = account_nonce(&metadata.origin_address)
+                .map(|nonce| nonce <= metadata.origin_nonce)
+                .unwrap_or(false);
+            let sponsor_unmined = account_nonce(&metadata.sponsor_address)
+                .map(|nonce| nonce <= metadata.sponsor_nonce)
+                .unwrap_or(false);
+            if origin_unmined && sponsor_unmined {
+                unmined_txids.push(metadata.txid.clone());
+            }
+        }
+        MemPoolDB::record_dropped_txs(tx, &unmined_txids, &MemPoolDropReason::STALE_COLLECT)?;
+
+        let txids: Vec<Txid> = collected
+            .into_iter()
+            .map(|metadata| metadata.txid)
+            .collect();
+
         if let Some(event_observer) = event_observer {
-            let sql = "SELECT txid FROM mempool WHERE height < ?1";
-            let txids = query_rows(tx, sql, args)?;
             event_observer.mempool_txs_dropped(txids, MemPoolDropReason::STALE_COLLECT);
         }
 
         let sql = "DELETE FROM mempool WHERE height < ?1";
 
         tx.execute(sql, args)?;
+
+        // forget about old drops
+        let sql = "DELETE FROM dropped_txs WHERE drop_time < ?1";
+        let min_drop_time = get_epoch_time_secs().saturating_sub(MEMPOOL_DROPPED_TX_RETENTION);
+        let args: &[&dyn ToSql] = &[&u64_to_sql(min_drop_time)?];
+        tx.execute(sql, args)?;
+
         increment_stx_mempool_gc();
         Ok(())
     }
 
+    /// Remember why the given transactions were dropped from the mempool
+    fn record_dropped_txs(
+        tx: &DBTx,
+        txids: &[Txid],
+        reason: &MemPoolDropReason,
+    ) -> Result<(), db_error> {
+        let sql =
+            "INSERT OR REPLACE INTO dropped_txs (txid, reason, drop_time) VALUES (?1, ?2, ?3)";
+        let drop_time = u64_to_sql(get_epoch_time_secs())?;
+        let reason = reason.to_string();
+        for txid in txids.iter() {
+            let args: &[&dyn ToSql] = &[txid, &reason, &drop_time];
+            tx.execute(sql, args)?;
+        }
+        Ok(())
+    }
+
+    /// Forget that the given transaction was dropped
+    fn forget_dropped_tx(tx: &DBTx, txid: &Txid) -> Result<(), db_error> {
+        let sql = "DELETE FROM dropped_txs WHERE txid = ?1";
+        let args: &[&dyn ToSql] = &[txid];
+        tx.execute(sql, args)?;
+        Ok(())
+    }
+
+    /// Why was this transaction dropped from the mempool, if it was?  Returns the
+    /// `MemPoolDropReason` name.
+    pub fn get_dropped_tx_reason(conn: &DBConn, txid: &Txid) -> Result<Option<String>, db_error> {
+        let sql = "SELECT reason FROM dropped_txs WHERE txid = ?1";
+        let args: &[&dyn ToSql] = &[txid];
+        let reason = conn.query_row(sql, args, |row| row.get(0)).optional()?;
+        Ok(reason)
+    }
+
     #[cfg(test)]
     pub fn clear_before_height(&mut self, min_height: u64) -> Result<(), db_error> {
         let mut tx = self.tx_begin()?;
-        MemPoolDB::garbage_collect(&mut tx, min_height, None)?;
+        MemPoolDB::garbage_collect(&mut tx, min_height, |_| None, None)?;
         tx.commit()?;
         Ok(())
     }
@@ -1629,12 +1773,13 @@ impl MemPoolDB {
     }
 
     /// Drop transactions from the mempool
-    pub fn drop_txs(&mut self, txids: &[Txid]) -> Result<(), db_error> {
+    pub fn drop_txs(&mut self, txids: &[Txid], reason: MemPoolDropReason) -> Result<(), db_error> {
         let mempool_tx = self.tx_begin()?;
         let sql = "DELETE FROM mempool WHERE txid = ?";
         for txid in txids.iter() {
             mempool_tx.execute(sql, &[txid])?;
         }
+        MemPoolDB::record_dropped_txs(&mempool_tx, txids, &reason)?;
         mempool_tx.commit()?;
         Ok(())
     }
@@ -1708,6 +1853,42 @@ impl MemPoolDB {
         })
     }
 
+    /// Find the transactions in the mempool whose tags under the given seed are in `tags`.
+    /// A tag that matches more than one transaction is left out, since we can't tell which of
+    /// them the tag's creator meant.
+    pub fn find_txs_by_tags(
+        &self,
+        seed: &[u8],
+        tags: &HashSet<TxTag>,
+    ) -> Result<HashMap<TxTag, StacksTransaction>, db_error> {
+        let sql = "SELECT txid FROM mempool";
+        let txids: Vec<Txid> = query_rows(&self.conn(), sql, NO_PARAMS)?;
+
+        let mut matches: HashMap<TxTag, Option<Txid>> = HashMap::new();
+        for txid in txids.into_iter() {
+            let tag = TxTag::from(seed, &txid);
+            if !tags.contains(&tag) {
+                continue;
+            }
+            if matches.contains_key(&tag) {
+                // ambiguous
+                matches.insert(tag, None);
+            } else {
+                matches.insert(tag, Some(txid));
+            }
+        }
+
+        let mut found = HashMap::new();
+        for (tag, txid_opt) in matches.into_iter() {
+            if let Some(txid) = txid_opt {
+                if let Some(txinfo) = MemPoolDB::get_tx(&self.conn(), &txid)? {
+                    found.insert(tag, txinfo.tx);
+                }
+            }
+        }
+        Ok(found)
+    }
+
     /// How many recent transactions are there -- i.e. within BLOOM_COUNTER_DEPTH block heights of
     /// the chain tip?
     pub fn get_num_recent_txs(conn: &DBConn) -> Result<u64, db_error> {
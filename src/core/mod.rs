@@ -55,6 +55,7 @@ pub const PEER_VERSION_TESTNET: u32 = 0xfacade05;
 pub const PEER_VERSION_EPOCH_1_0: u8 = 0x00;
 pub const PEER_VERSION_EPOCH_2_0: u8 = 0x00;
 pub const PEER_VERSION_EPOCH_2_05: u8 = 0x05;
+pub const PEER_VERSION_EPOCH_2_1: u8 = 0x06;
 
 // network identifiers
 pub const NETWORK_ID_MAINNET: u32 = 0x17000000;
@@ -274,6 +275,10 @@ lazy_static! {
 /// *or greater*.
 pub static STACKS_EPOCH_2_05_MARKER: u8 = 0x05;
 
+/// Stacks 2.1 epoch marker.  All block-commits in 2.1 must have a memo bitfield with this value
+/// *or greater*.
+pub static STACKS_EPOCH_2_1_MARKER: u8 = 0x06;
+
 #[test]
 fn test_ord_for_stacks_epoch() {
     let epochs = STACKS_EPOCHS_MAINNET.clone();
@@ -326,6 +331,14 @@ fn test_ord_for_stacks_epoch_id() {
         StacksEpochId::Epoch20.cmp(&StacksEpochId::Epoch10),
         Ordering::Greater
     );
+    assert_eq!(
+        StacksEpochId::Epoch2_05.cmp(&StacksEpochId::Epoch21),
+        Ordering::Less
+    );
+    assert_eq!(
+        StacksEpochId::Epoch21.cmp(&StacksEpochId::Epoch20),
+        Ordering::Greater
+    );
 }
 pub trait StacksEpochExtension {
     #[cfg(test)]
@@ -334,6 +347,8 @@ pub trait StacksEpochExtension {
     fn unit_test_2_05(epoch_2_0_block_height: u64) -> Vec<StacksEpoch>;
     #[cfg(test)]
     fn unit_test_pre_2_05(epoch_2_0_block_height: u64) -> Vec<StacksEpoch>;
+    #[cfg(test)]
+    fn unit_test_2_1(epoch_2_0_block_height: u64) -> Vec<StacksEpoch>;
 }
 
 impl StacksEpochExtension for StacksEpoch {
@@ -400,6 +415,57 @@ impl StacksEpochExtension for StacksEpoch {
         ]
     }
 
+    #[cfg(test)]
+    fn unit_test_2_1(first_burnchain_height: u64) -> Vec<StacksEpoch> {
+        info!(
+            "StacksEpoch unit_test first_burn_height = {}",
+            first_burnchain_height
+        );
+
+        vec![
+            StacksEpoch {
+                epoch_id: StacksEpochId::Epoch10,
+                start_height: 0,
+                end_height: first_burnchain_height,
+                block_limit: ExecutionCost::max_value(),
+                network_epoch: PEER_VERSION_EPOCH_1_0,
+            },
+            StacksEpoch {
+                epoch_id: StacksEpochId::Epoch20,
+                start_height: first_burnchain_height,
+                end_height: first_burnchain_height + 4,
+                block_limit: ExecutionCost::max_value(),
+                network_epoch: PEER_VERSION_EPOCH_2_0,
+            },
+            StacksEpoch {
+                epoch_id: StacksEpochId::Epoch2_05,
+                start_height: first_burnchain_height + 4,
+                end_height: first_burnchain_height + 8,
+                block_limit: ExecutionCost {
+                    write_length: 205205,
+                    write_count: 205205,
+                    read_length: 205205,
+                    read_count: 205205,
+                    runtime: 205205,
+                },
+                network_epoch: PEER_VERSION_EPOCH_2_05,
+            },
+            StacksEpoch {
+                epoch_id: StacksEpochId::Epoch21,
+                start_height: first_burnchain_height + 8,
+                end_height: STACKS_EPOCH_MAX,
+                block_limit: ExecutionCost {
+                    write_length: 210210,
+                    write_count: 210210,
+                    read_length: 210210,
+                    read_count: 210210,
+                    runtime: 210210,
+                },
+                network_epoch: PEER_VERSION_EPOCH_2_1,
+            },
+        ]
+    }
+
     #[cfg(test)]
     fn unit_test(stacks_epoch_id: StacksEpochId, first_burnchain_height: u64) -> Vec<StacksEpoch> {
         match stacks_epoch_id {
@@ -407,6 +473,7 @@ impl StacksEpochExtension for StacksEpoch {
                 StacksEpoch::unit_test_pre_2_05(first_burnchain_height)
             }
             StacksEpochId::Epoch2_05 => StacksEpoch::unit_test_2_05(first_burnchain_height),
+            StacksEpochId::Epoch21 => StacksEpoch::unit_test_2_1(first_burnchain_height),
         }
     }
 }
@@ -651,6 +651,7 @@ fn mempool_db_load_store_replace_tx() {
     let num_txs = txs.len() as u64;
 
     let mut mempool_tx = mempool.tx_begin().unwrap();
+    let mut live_txids = vec![];
 
     eprintln!("add all txs");
     for (i, mut tx) in txs.drain(..).enumerate() {
@@ -761,6 +762,14 @@ fn mempool_db_load_store_replace_tx() {
         // was replaced
         assert!(!MemPoolDB::db_has_tx(&mempool_tx, &old_txid).unwrap());
         assert!(MemPoolDB::db_has_tx(&mempool_tx, &txid).unwrap());
+        assert_eq!(
+            MemPoolDB::get_dropped_tx_reason(&mempool_tx, &old_txid).unwrap(),
+            Some("ReplaceByFee".to_string())
+        );
+        assert_eq!(
+            MemPoolDB::get_dropped_tx_reason(&mempool_tx, &txid).unwrap(),
+            None
+        );
 
         let tx_info_after =
             MemPoolDB::get_tx_metadata_by_address(&mempool_tx, true, &origin_address, origin_nonce)
@@ -825,6 +834,11 @@ fn mempool_db_load_store_replace_tx() {
         // was NOT replaced
         assert!(MemPoolDB::db_has_tx(&mempool_tx, &old_txid).unwrap());
         assert!(!MemPoolDB::db_has_tx(&mempool_tx, &txid).unwrap());
+        assert_eq!(
+            MemPoolDB::get_dropped_tx_reason(&mempool_tx, &old_txid).unwrap(),
+            None
+        );
+        live_txids.push(old_txid);
     }
     mempool_tx.commit().unwrap();
 
@@ -862,8 +876,25 @@ fn mempool_db_load_store_replace_tx() {
     assert_eq!(txs.len(), 0);
 
     eprintln!("garbage-collect");
+    // the first transaction's origin account has moved past its nonce, so it may have been mined
+    let mined_address = StacksAddress {
+        version: 22,
+        bytes: Hash160::from_data(&0usize.to_be_bytes()),
+    };
     let mut mempool_tx = mempool.tx_begin().unwrap();
-    MemPoolDB::garbage_collect(&mut mempool_tx, 101, None).unwrap();
+    MemPoolDB::garbage_collect(
+        &mut mempool_tx,
+        101,
+        |address| {
+            if *address == mined_address {
+                Some(u64::MAX)
+            } else {
+                Some(0)
+            }
+        },
+        None,
+    )
+    .unwrap();
     mempool_tx.commit().unwrap();
 
     let txs = MemPoolDB::get_txs_after(
@@ -875,6 +906,17 @@ fn mempool_db_load_store_replace_tx() {
     )
     .unwrap();
     assert_eq!(txs.len(), 0);
+
+    assert_eq!(
+        MemPoolDB::get_dropped_tx_reason(&mempool.db, &live_txids[0]).unwrap(),
+        None
+    );
+    for txid in live_txids[1..].iter() {
+        assert_eq!(
+            MemPoolDB::get_dropped_tx_reason(&mempool.db, txid).unwrap(),
+            Some("StaleGarbageCollect".to_string())
+        );
+    }
 }
 
 #[test]
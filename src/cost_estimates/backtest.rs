@@ -0,0 +1,339 @@
+use std::io;
+use std::io::Write;
+
+use clarity::vm::costs::ExecutionCost;
+
+use crate::burnchains::Txid;
+use crate::chainstate::stacks::db::StacksEpochReceipt;
+use crate::chainstate::stacks::events::TransactionOrigin;
+use crate::chainstate::stacks::TransactionPayload;
+use crate::types::chainstate::StacksBlockId;
+
+use super::metrics::CostMetric;
+use super::{CostEstimator, EstimatorError, FeeEstimator};
+
+/// What a backtested estimator predicts for each transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BacktestKind {
+    /// A `CostEstimator`'s estimate of the transaction's scalar cost
+    Cost,
+    /// A `FeeEstimator`'s estimate of the fee the transaction needed to pay
+    Fee,
+}
+
+impl BacktestKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BacktestKind::Cost => "cost",
+            BacktestKind::Fee => "fee",
+        }
+    }
+}
+
+/// A low, middle and high prediction. Cost estimators make a single prediction, so all three
+/// are the same.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestPrediction {
+    pub low: f64,
+    pub middle: f64,
+    pub high: f64,
+}
+
+/// One estimator's prediction for one mined transaction, made before the estimator was
+/// notified of the block that mined it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BacktestObservation {
+    pub estimator: String,
+    pub kind: BacktestKind,
+    pub block_height: u64,
+    pub index_block_hash: StacksBlockId,
+    pub txid: Txid,
+    pub tx_type: String,
+    /// For cost estimators, the scalar cost of the estimated `ExecutionCost`. For fee
+    /// estimators, the estimated fee rates times the transaction's actual scalar cost.
+    /// `None` if the estimator had no estimate.
+    pub prediction: Option<BacktestPrediction>,
+    /// The scalar cost the transaction actually consumed, or the fee it actually paid.
+    pub actual: f64,
+}
+
+/// Error metrics for one estimator over a backtest. The error metrics compare the middle
+/// prediction to the actual value, and only count transactions the estimator had an estimate
+/// for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BacktestSummary {
+    pub estimator: String,
+    pub kind: BacktestKind,
+    pub transactions: u64,
+    pub no_estimate: u64,
+    pub mean_absolute_error: f64,
+    /// Mean of `|predicted - actual| / actual`, over the transactions whose actual value is
+    /// non-zero.
+    pub mean_absolute_percentage_error: f64,
+    pub root_mean_squared_error: f64,
+    /// Fraction of transactions whose actual value was at least the middle prediction. For
+    /// cost estimators, this is how often the estimator under-estimated; for fee estimators,
+    /// how often the transaction paid at least the estimated fee.
+    pub actual_at_least_predicted: f64,
+    /// Fraction of transactions whose actual value was within the low and high predictions.
+    pub actual_within_range: f64,
+}
+
+/// Replays mined blocks through a set of cost and fee estimators, in order. Before each block
+/// is passed to the estimators' `notify_block`, every estimator is asked what it would have
+/// predicted for each of the block's transactions, so that the predictions can be compared to
+/// what the transactions actually cost and paid.
+pub struct EstimatorBacktest {
+    cost_estimators: Vec<(String, Box<dyn CostEstimator>)>,
+    fee_estimators: Vec<(String, Box<dyn FeeEstimator>)>,
+    /// Converts execution costs into the scalar units fee rates are measured in.
+    metric: Box<dyn CostMetric>,
+    observations: Vec<BacktestObservation>,
+}
+
+impl EstimatorBacktest {
+    pub fn new(metric: Box<dyn CostMetric>) -> EstimatorBacktest {
+        EstimatorBacktest {
+            cost_estimators: vec![],
+            fee_estimators: vec![],
+            metric,
+            observations: vec![],
+        }
+    }
+
+    pub fn add_cost_estimator(&mut self, name: &str, estimator: Box<dyn CostEstimator>) {
+        self.cost_estimators.push((name.to_string(), estimator));
+    }
+
+    pub fn add_fee_estimator(&mut self, name: &str, estimator: Box<dyn FeeEstimator>) {
+        self.fee_estimators.push((name.to_string(), estimator));
+    }
+
+    pub fn observations(&self) -> &[BacktestObservation] {
+        &self.observations
+    }
+
+    /// Record every estimator's predictions for the transactions in `receipt`, and then notify
+    /// the estimators of the block. Blocks must be replayed in order.
+    pub fn replay_block(&mut self, receipt: &StacksEpochReceipt, block_limit: &ExecutionCost) {
+        let block_height = receipt.header.stacks_block_height;
+        let index_block_hash = receipt.header.index_block_hash();
+
+        // fee estimates do not depend on the transaction, so only ask once per block
+        let fee_rates: Vec<_> = self
+            .fee_estimators
+            .iter()
+            .map(|(name, estimator)| match estimator.get_rate_estimates() {
+                Ok(fee_rates) => Some(fee_rates),
+                Err(EstimatorError::NoEstimateAvailable) => None,
+                Err(e) => {
+                    warn!("Fee estimator failed to estimate";
+                          "estimator" => %name,
+                          "error" => %e);
+                    None
+                }
+            })
+            .collect();
+
+        for tx_receipt in receipt.tx_receipts.iter() {
+            let tx = match tx_receipt.transaction {
+                TransactionOrigin::Stacks(ref tx) => tx,
+                TransactionOrigin::Burn(_) => continue,
+            };
+            if let TransactionPayload::Coinbase(_) = tx.payload {
+                // Coinbase txs are "free", so they don't factor into the fee market.
+                continue;
+            }
+
+            let actual_cost =
+                self.metric
+                    .from_cost_and_len(&tx_receipt.execution_cost, block_limit, tx.tx_len());
+
+            for (name, estimator) in self.cost_estimators.iter() {
+                let prediction =
+                    match estimator.estimate_cost(&tx.payload, &receipt.evaluated_epoch) {
+                        Ok(cost) => {
+                            let scalar =
+                                self.metric
+                                    .from_cost_and_len(&cost, block_limit, tx.tx_len())
+                                    as f64;
+                            Some(BacktestPrediction {
+                                low: scalar,
+                                middle: scalar,
+                                high: scalar,
+                            })
+                        }
+                        Err(EstimatorError::NoEstimateAvailable) => None,
+                        Err(e) => {
+                            warn!("Cost estimator failed to estimate";
+                                  "estimator" => %name,
+                                  "txid" => %tx.txid(),
+                                  "error" => %e);
+                            None
+                        }
+                    };
+                self.observations.push(BacktestObservation {
+                    estimator: name.clone(),
+                    kind: BacktestKind::Cost,
+                    block_height,
+                    index_block_hash: index_block_hash.clone(),
+                    txid: tx.txid(),
+                    tx_type: tx.payload.name().to_string(),
+                    prediction,
+                    actual: actual_cost as f64,
+                });
+            }
+
+            for ((name, _), fee_rates) in self.fee_estimators.iter().zip(fee_rates.iter()) {
+                let prediction = fee_rates.as_ref().map(|fee_rates| {
+                    let fees = fee_rates.clone() * (actual_cost as f64);
+                    BacktestPrediction {
+                        low: fees.low,
+                        middle: fees.middle,
+                        high: fees.high,
+                    }
+                });
+                self.observations.push(BacktestObservation {
+                    estimator: name.clone(),
+                    kind: BacktestKind::Fee,
+                    block_height,
+                    index_block_hash: index_block_hash.clone(),
+                    txid: tx.txid(),
+                    tx_type: tx.payload.name().to_string(),
+                    prediction,
+                    actual: tx.get_tx_fee() as f64,
+                });
+            }
+        }
+
+        for (_, estimator) in self.cost_estimators.iter_mut() {
+            estimator.notify_block(&receipt.tx_receipts, block_limit, &receipt.evaluated_epoch);
+        }
+        for (name, estimator) in self.fee_estimators.iter_mut() {
+            if let Err(e) = estimator.notify_block(receipt, block_limit) {
+                warn!("Fee estimator failed to process block";
+                      "estimator" => %name,
+                      "block" => %index_block_hash,
+                      "error" => %e);
+            }
+        }
+    }
+
+    /// Summarize each estimator's predictions, in the order the estimators were added.
+    pub fn summarize(&self) -> Vec<BacktestSummary> {
+        let cost_names = self
+            .cost_estimators
+            .iter()
+            .map(|(name, _)| (name, BacktestKind::Cost));
+        let fee_names = self
+            .fee_estimators
+            .iter()
+            .map(|(name, _)| (name, BacktestKind::Fee));
+
+        cost_names
+            .chain(fee_names)
+            .map(|(name, kind)| {
+                let observations: Vec<_> = self
+                    .observations
+                    .iter()
+                    .filter(|obs| obs.kind == kind && &obs.estimator == name)
+                    .collect();
+                summarize_observations(name, kind, &observations)
+            })
+            .collect()
+    }
+
+    /// Write every observation as CSV, with a header row.
+    pub fn write_csv<W: Write>(&self, fd: &mut W) -> Result<(), io::Error> {
+        writeln!(
+            fd,
+            "estimator,kind,block_height,index_block_hash,txid,tx_type,predicted_low,predicted,predicted_high,actual"
+        )?;
+        for obs in self.observations.iter() {
+            let (low, middle, high) = match obs.prediction {
+                Some(ref prediction) => (
+                    prediction.low.to_string(),
+                    prediction.middle.to_string(),
+                    prediction.high.to_string(),
+                ),
+                None => ("".to_string(), "".to_string(), "".to_string()),
+            };
+            writeln!(
+                fd,
+                "{},{},{},{},{},{},{},{},{},{}",
+                &obs.estimator,
+                obs.kind.as_str(),
+                obs.block_height,
+                &obs.index_block_hash,
+                &obs.txid,
+                &obs.tx_type,
+                low,
+                middle,
+                high,
+                obs.actual
+            )?;
+        }
+        Ok(())
+    }
+}
+
+fn summarize_observations(
+    name: &str,
+    kind: BacktestKind,
+    observations: &[&BacktestObservation],
+) -> BacktestSummary {
+    let mut no_estimate = 0;
+    let mut estimated = 0;
+    let mut total_absolute_error = 0f64;
+    let mut total_squared_error = 0f64;
+    let mut total_percentage_error = 0f64;
+    let mut percentage_count = 0;
+    let mut at_least_predicted = 0;
+    let mut within_range = 0;
+
+    for obs in observations.iter() {
+        let prediction = match obs.prediction {
+            Some(ref prediction) => prediction,
+            None => {
+                no_estimate += 1;
+                continue;
+            }
+        };
+        estimated += 1;
+
+        let error = prediction.middle - obs.actual;
+        total_absolute_error += error.abs();
+        total_squared_error += error * error;
+        if obs.actual != 0f64 {
+            total_percentage_error += error.abs() / obs.actual;
+            percentage_count += 1;
+        }
+        if obs.actual >= prediction.middle {
+            at_least_predicted += 1;
+        }
+        if prediction.low <= obs.actual && obs.actual <= prediction.high {
+            within_range += 1;
+        }
+    }
+
+    let mean = |total: f64, count: u64| {
+        if count > 0 {
+            total / (count as f64)
+        } else {
+            0f64
+        }
+    };
+
+    BacktestSummary {
+        estimator: name.to_string(),
+        kind,
+        transactions: observations.len() as u64,
+        no_estimate,
+        mean_absolute_error: mean(total_absolute_error, estimated),
+        mean_absolute_percentage_error: mean(total_percentage_error, percentage_count),
+        root_mean_squared_error: mean(total_squared_error, estimated).sqrt(),
+        actual_at_least_predicted: mean(at_least_predicted as f64, estimated),
+        actual_within_range: mean(within_range as f64, estimated),
+    }
+}
@@ -0,0 +1,154 @@
+use std::path::{Path, PathBuf};
+
+use clarity::vm::costs::ExecutionCost;
+use rusqlite::OpenFlags;
+
+use crate::chainstate::stacks::db::StacksEpochReceipt;
+use crate::core::mempool::MemPoolDB;
+use crate::util_lib::db::sqlite_open;
+use crate::util_lib::db::Error as db_error;
+
+use super::fee_medians::{fee_rate_estimate_from_sorted_weighted_fees, FeeRateAndWeight};
+use super::FeeRateEstimate;
+use super::{EstimatorError, FeeEstimator, FEE_CONFIRMATION_TARGETS};
+
+/// The CongestionAwareFeeRateEstimator wraps an underlying FeeEstimator that tracks the fee rates
+/// paid in mined blocks. It passes `notify_block` calls to the underlying estimator. On
+/// `get_rate_estimates_for_target` calls, it also samples the transactions still waiting in the
+/// mempool:
+///
+/// 1) Pending transactions are ranked by fee rate, highest first, and weighted by the scalar cost
+///    they will occupy in a block.
+/// 2) If the backlog fills at least `target_blocks` full blocks, a transaction must outbid the
+///    pending transactions that fill those blocks to be included in time. The weighted
+///    percentiles of the fee rates in that window are the congestion estimate.
+/// 3) The result is the larger of the congestion estimate and the underlying estimate, in each
+///    of `low`, `middle` and `high`. If the backlog does not fill the target, the mempool is not
+///    congested and the underlying estimate is returned as-is.
+///
+/// The mempool database is opened read-only for each estimate, so that the estimator can be
+/// created before the node has instantiated its mempool.
+pub struct CongestionAwareFeeRateEstimator<UnderlyingEstimator: FeeEstimator> {
+    /// Estimates the fee rates paid in recently mined blocks.
+    underlying: UnderlyingEstimator,
+    /// Path to the node's mempool database.
+    mempool_path: PathBuf,
+    /// The weight of a "full block" in abstract scalar cost units. This must be in the same
+    /// units as the mempool's fee rate estimates.
+    full_block_weight: u64,
+}
+
+impl<UnderlyingEstimator: FeeEstimator> CongestionAwareFeeRateEstimator<UnderlyingEstimator> {
+    pub fn new(
+        underlying: UnderlyingEstimator,
+        mempool_path: &Path,
+        full_block_weight: u64,
+    ) -> CongestionAwareFeeRateEstimator<UnderlyingEstimator> {
+        assert!(full_block_weight > 0);
+        Self {
+            underlying,
+            mempool_path: mempool_path.to_path_buf(),
+            full_block_weight,
+        }
+    }
+
+    /// Sample up to `max_weight` units of the pending transactions in the mempool, highest fee
+    /// rate first. A mempool that has not been created yet is empty.
+    fn sample_mempool(&self, max_weight: u64) -> Result<Vec<FeeRateAndWeight>, EstimatorError> {
+        if !self.mempool_path.exists() {
+            return Ok(vec![]);
+        }
+        let conn = sqlite_open(&self.mempool_path, OpenFlags::SQLITE_OPEN_READ_ONLY, false)
+            .map_err(EstimatorError::SqliteError)?;
+        MemPoolDB::get_pending_fee_rates(&conn, max_weight).map_err(|e| match e {
+            db_error::SqliteError(e) => EstimatorError::SqliteError(e),
+            e => {
+                warn!("Failed to sample mempool fee rates"; "error" => ?e);
+                EstimatorError::NoEstimateAvailable
+            }
+        })
+    }
+}
+
+impl<T: FeeEstimator> FeeEstimator for CongestionAwareFeeRateEstimator<T> {
+    /// Just passes the information straight to `underlying`.
+    fn notify_block(
+        &mut self,
+        receipt: &StacksEpochReceipt,
+        block_limit: &ExecutionCost,
+    ) -> Result<(), EstimatorError> {
+        self.underlying.notify_block(receipt, block_limit)
+    }
+
+    /// Estimate for inclusion in the next block.
+    fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError> {
+        self.get_rate_estimates_for_target(FEE_CONFIRMATION_TARGETS[0])
+    }
+
+    fn get_rate_estimates_for_target(
+        &self,
+        target_blocks: u64,
+    ) -> Result<FeeRateEstimate, EstimatorError> {
+        let target_blocks = target_blocks.max(1);
+        let pending = self.sample_mempool(target_blocks.saturating_mul(self.full_block_weight))?;
+        let congestion_estimate =
+            fee_rate_estimate_from_backlog(&pending, target_blocks, self.full_block_weight);
+
+        match (
+            self.underlying.get_rate_estimates_for_target(target_blocks),
+            congestion_estimate,
+        ) {
+            (Ok(history), Some(congestion)) => Ok(max_fee_rate_estimate(history, congestion)),
+            (Ok(history), None) => Ok(history),
+            (Err(EstimatorError::NoEstimateAvailable), Some(congestion)) => Ok(congestion),
+            (Err(e), _) => Err(e),
+        }
+    }
+}
+
+/// Computes a `FeeRateEstimate` from the pending transactions that would fill the next
+/// `target_blocks` blocks, using the same weighted percentiles as the
+/// `WeightedMedianFeeRateEstimator`.
+///
+/// `pending` must be sorted by fee rate in descending order. Returns `None` if the pending
+/// transactions do not fill `target_blocks` full blocks.
+pub fn fee_rate_estimate_from_backlog(
+    pending: &[FeeRateAndWeight],
+    target_blocks: u64,
+    full_block_weight: u64,
+) -> Option<FeeRateEstimate> {
+    let capacity = target_blocks.saturating_mul(full_block_weight);
+    if capacity == 0 {
+        return None;
+    }
+
+    let mut window = vec![];
+    let mut total_weight = 0u64;
+    for rate_and_weight in pending {
+        if total_weight >= capacity {
+            break;
+        }
+        // only the part of the transaction that fits in the target blocks counts
+        let weight = rate_and_weight.weight.min(capacity - total_weight);
+        total_weight += weight;
+        window.push(FeeRateAndWeight {
+            fee_rate: rate_and_weight.fee_rate,
+            weight,
+        });
+    }
+
+    if total_weight < capacity {
+        return None;
+    }
+
+    window.reverse();
+    Some(fee_rate_estimate_from_sorted_weighted_fees(&window))
+}
+
+fn max_fee_rate_estimate(a: FeeRateEstimate, b: FeeRateEstimate) -> FeeRateEstimate {
+    FeeRateEstimate {
+        high: a.high.max(b.high),
+        middle: a.middle.max(b.middle),
+        low: a.low.max(b.low),
+    }
+}
@@ -89,4 +89,15 @@ impl<T: FeeEstimator> FeeEstimator for FeeRateFuzzer<T> {
         let underlying_estimate = self.underlying.get_rate_estimates()?;
         Ok(self.fuzz_estimate(underlying_estimate))
     }
+
+    /// Call underlying estimator for the target and add some fuzz.
+    fn get_rate_estimates_for_target(
+        &self,
+        target_blocks: u64,
+    ) -> Result<FeeRateEstimate, EstimatorError> {
+        let underlying_estimate = self
+            .underlying
+            .get_rate_estimates_for_target(target_blocks)?;
+        Ok(self.fuzz_estimate(underlying_estimate))
+    }
 }
@@ -13,6 +13,8 @@ use rusqlite::Error as SqliteError;
 use crate::burnchains::Txid;
 use crate::chainstate::stacks::db::StacksEpochReceipt;
 
+pub mod backtest;
+pub mod fee_congestion;
 pub mod fee_medians;
 pub mod fee_rate_fuzzer;
 pub mod fee_scalar;
@@ -49,8 +51,21 @@ pub trait FeeEstimator {
     ) -> Result<(), EstimatorError>;
     /// Get the current estimates for fee rate
     fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError>;
+    /// Get the current estimates for the fee rate needed to be included within
+    ///  `target_blocks` blocks. Estimators that only track mined blocks cannot
+    ///  distinguish between confirmation targets, so by default this is the same
+    ///  as `get_rate_estimates`.
+    fn get_rate_estimates_for_target(
+        &self,
+        _target_blocks: u64,
+    ) -> Result<FeeRateEstimate, EstimatorError> {
+        self.get_rate_estimates()
+    }
 }
 
+/// The confirmation targets, in blocks, for which fee rate estimates are reported
+pub const FEE_CONFIRMATION_TARGETS: [u64; 3] = [1, 3, 10];
+
 #[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
 /// This struct is returned from fee rate estimators as the current best estimate for
 /// fee rates to include a transaction in a block.
@@ -0,0 +1,273 @@
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::Value;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::stacks::db::StacksEpochReceipt;
+use crate::chainstate::stacks::events::{StacksTransactionReceipt, TransactionOrigin};
+use crate::chainstate::stacks::{
+    CoinbasePayload, StacksBlockHeader, StacksTransaction, TransactionAuth,
+    TransactionContractCall, TransactionPayload, TransactionSpendingCondition, TransactionVersion,
+};
+use crate::core::StacksEpochId;
+use crate::cost_estimates::backtest::{BacktestKind, BacktestSummary, EstimatorBacktest};
+use crate::cost_estimates::metrics::UnitMetric;
+use crate::cost_estimates::tests::common::make_block_receipt;
+use crate::cost_estimates::{CostEstimator, EstimatorError, FeeEstimator, FeeRateEstimate};
+use crate::net::test::{TestPeer, TestPeerConfig};
+use crate::types::chainstate::{StacksAddress, StacksBlockId};
+use stacks_common::util::hash::Hash160;
+
+/// Has no estimate until it is notified of a transaction, and then estimates the last cost
+/// it was notified of.
+struct LastCostEstimator {
+    last_cost: Option<ExecutionCost>,
+}
+
+impl CostEstimator for LastCostEstimator {
+    fn notify_event(
+        &mut self,
+        _tx: &TransactionPayload,
+        actual_cost: &ExecutionCost,
+        _block_limit: &ExecutionCost,
+        _evaluated_epoch: &StacksEpochId,
+    ) -> Result<(), EstimatorError> {
+        self.last_cost = Some(actual_cost.clone());
+        Ok(())
+    }
+
+    fn estimate_cost(
+        &self,
+        _tx: &TransactionPayload,
+        _evaluated_epoch: &StacksEpochId,
+    ) -> Result<ExecutionCost, EstimatorError> {
+        self.last_cost
+            .clone()
+            .ok_or(EstimatorError::NoEstimateAvailable)
+    }
+}
+
+/// Has no estimate until it is notified of a block, and then estimates fee rates that grow
+/// with the number of blocks it was notified of.
+struct CountingFeeEstimator {
+    blocks: u64,
+}
+
+impl FeeEstimator for CountingFeeEstimator {
+    fn notify_block(
+        &mut self,
+        _receipt: &StacksEpochReceipt,
+        _block_limit: &ExecutionCost,
+    ) -> Result<(), EstimatorError> {
+        self.blocks += 1;
+        Ok(())
+    }
+
+    fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError> {
+        if self.blocks == 0 {
+            return Err(EstimatorError::NoEstimateAvailable);
+        }
+        Ok(FeeRateEstimate {
+            high: 4f64 * self.blocks as f64,
+            middle: 2f64 * self.blocks as f64,
+            low: self.blocks as f64,
+        })
+    }
+}
+
+fn make_dummy_coinbase_receipt() -> StacksTransactionReceipt {
+    let tx = StacksTransaction::new(
+        TransactionVersion::Mainnet,
+        TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+        TransactionPayload::Coinbase(CoinbasePayload([0; 32])),
+    );
+    StacksTransactionReceipt::from_coinbase(tx)
+}
+
+fn make_dummy_cc_receipt(fee: u64) -> StacksTransactionReceipt {
+    let mut tx = StacksTransaction::new(
+        TransactionVersion::Mainnet,
+        TransactionAuth::Standard(TransactionSpendingCondition::new_initial_sighash()),
+        TransactionPayload::ContractCall(TransactionContractCall {
+            address: StacksAddress::new(0, Hash160([0; 20])),
+            contract_name: "cc-dummy".into(),
+            function_name: "func-name".into(),
+            function_args: vec![],
+        }),
+    );
+    tx.set_tx_fee(fee);
+    StacksTransactionReceipt::from_contract_call(
+        tx,
+        vec![],
+        Value::okay(Value::Bool(true)).unwrap(),
+        0,
+        ExecutionCost {
+            write_length: 0,
+            write_count: 0,
+            read_length: 0,
+            read_count: 0,
+            runtime: 10,
+        },
+    )
+}
+
+fn make_test_backtest() -> EstimatorBacktest {
+    let mut backtest = EstimatorBacktest::new(Box::new(UnitMetric));
+    backtest.add_cost_estimator("last_cost", Box::new(LastCostEstimator { last_cost: None }));
+    backtest.add_fee_estimator("counting", Box::new(CountingFeeEstimator { blocks: 0 }));
+
+    let block_limit = ExecutionCost::max_value();
+    backtest.replay_block(
+        &make_block_receipt(vec![
+            make_dummy_coinbase_receipt(),
+            make_dummy_cc_receipt(10),
+            make_dummy_cc_receipt(20),
+        ]),
+        &block_limit,
+    );
+    backtest.replay_block(
+        &make_block_receipt(vec![
+            make_dummy_coinbase_receipt(),
+            make_dummy_cc_receipt(3),
+        ]),
+        &block_limit,
+    );
+    backtest
+}
+
+/// Estimators are asked for their predictions before they are notified of each block.
+#[test]
+fn test_backtest_predicts_before_notify() {
+    let backtest = make_test_backtest();
+
+    // coinbases are not observed
+    let observations = backtest.observations();
+    assert_eq!(observations.len(), 6);
+
+    let fee_observations: Vec<_> = observations
+        .iter()
+        .filter(|obs| obs.kind == BacktestKind::Fee)
+        .collect();
+    assert_eq!(fee_observations.len(), 3);
+    assert!(fee_observations[0].prediction.is_none());
+    assert!(fee_observations[1].prediction.is_none());
+    assert_eq!(fee_observations[0].actual, 10f64);
+
+    // after one block, the fee rates are (1, 2, 4), and every transaction has a scalar cost of 1
+    let prediction = fee_observations[2].prediction.as_ref().unwrap();
+    assert_eq!(prediction.low, 1f64);
+    assert_eq!(prediction.middle, 2f64);
+    assert_eq!(prediction.high, 4f64);
+    assert_eq!(fee_observations[2].actual, 3f64);
+}
+
+#[test]
+fn test_backtest_summary() {
+    let backtest = make_test_backtest();
+
+    assert_eq!(
+        backtest.summarize(),
+        vec![
+            BacktestSummary {
+                estimator: "last_cost".into(),
+                kind: BacktestKind::Cost,
+                transactions: 3,
+                no_estimate: 2,
+                mean_absolute_error: 0f64,
+                mean_absolute_percentage_error: 0f64,
+                root_mean_squared_error: 0f64,
+                actual_at_least_predicted: 1f64,
+                actual_within_range: 1f64,
+            },
+            BacktestSummary {
+                estimator: "counting".into(),
+                kind: BacktestKind::Fee,
+                transactions: 3,
+                no_estimate: 2,
+                mean_absolute_error: 1f64,
+                mean_absolute_percentage_error: 1f64 / 3f64,
+                root_mean_squared_error: 1f64,
+                actual_at_least_predicted: 1f64,
+                actual_within_range: 1f64,
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_backtest_csv() {
+    let backtest = make_test_backtest();
+
+    let mut csv = vec![];
+    backtest.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+
+    assert_eq!(lines.len(), 7);
+    assert_eq!(
+        lines[0],
+        "estimator,kind,block_height,index_block_hash,txid,tx_type,predicted_low,predicted,predicted_high,actual"
+    );
+
+    // the first block's transactions had no estimates
+    let first: Vec<_> = lines[1].split(',').collect();
+    assert_eq!(first[0], "last_cost");
+    assert_eq!(first[1], "cost");
+    assert_eq!(first[5], "ContractCall");
+    assert_eq!(&first[6..], &["", "", "", "1"]);
+
+    let last: Vec<_> = lines[6].split(',').collect();
+    assert_eq!(last[0], "counting");
+    assert_eq!(last[1], "fee");
+    assert_eq!(&last[6..], &["1", "2", "4", "3"]);
+}
+
+/// Replaying a stored block reproduces its transactions, and does not change the chainstate.
+#[test]
+fn test_replay_block_receipt() {
+    let peer_config = TestPeerConfig::new("test_replay_block_receipt", 21404, 21405);
+    let mut peer = TestPeer::new(peer_config);
+
+    let mut blocks = vec![];
+    for _ in 0..3 {
+        let (burn_ops, stacks_block, microblocks) = peer.make_default_tenure();
+        let (_, _, consensus_hash) = peer.next_burnchain_block(burn_ops);
+        peer.process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+        blocks.push((consensus_hash, stacks_block));
+    }
+
+    let sortdb = peer.sortdb.take().unwrap();
+    for (consensus_hash, block) in blocks.iter() {
+        let block_id =
+            StacksBlockHeader::make_index_block_hash(consensus_hash, &block.block_hash());
+
+        for _ in 0..2 {
+            let receipt = peer
+                .chainstate()
+                .replay_block_receipt(&sortdb, &block_id)
+                .unwrap()
+                .unwrap();
+
+            assert_eq!(receipt.header.index_block_hash(), block_id);
+
+            // the anchored transactions come last, after any epoch transition
+            assert!(receipt.tx_receipts.len() >= block.txs.len());
+            let anchored_receipts =
+                &receipt.tx_receipts[receipt.tx_receipts.len() - block.txs.len()..];
+            for (tx_receipt, tx) in anchored_receipts.iter().zip(block.txs.iter()) {
+                match tx_receipt.transaction {
+                    TransactionOrigin::Stacks(ref replayed_tx) => {
+                        assert_eq!(replayed_tx.txid(), tx.txid())
+                    }
+                    TransactionOrigin::Burn(_) => panic!("Replayed a burnchain operation"),
+                }
+            }
+        }
+    }
+
+    assert!(peer
+        .chainstate()
+        .replay_block_receipt(&sortdb, &StacksBlockId([0x11; 32]))
+        .unwrap()
+        .is_none());
+    peer.sortdb = Some(sortdb);
+}
@@ -0,0 +1,256 @@
+use std::path::{Path, PathBuf};
+
+use clarity::vm::costs::ExecutionCost;
+use clarity::vm::types::StacksAddressExtensions;
+
+use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::stacks::db::test::{chainstate_path, instantiate_chainstate};
+use crate::chainstate::stacks::db::StacksEpochReceipt;
+use crate::chainstate::stacks::{
+    StacksTransaction, TokenTransferMemo, TransactionAnchorMode, TransactionAuth,
+    TransactionPayload, TransactionPostConditionMode, TransactionVersion,
+};
+use crate::codec::StacksMessageCodec;
+use crate::core::mempool::MemPoolDB;
+use crate::core::StacksEpochId;
+use crate::cost_estimates::fee_congestion::{
+    fee_rate_estimate_from_backlog, CongestionAwareFeeRateEstimator,
+};
+use crate::cost_estimates::fee_medians::{
+    fee_rate_estimate_from_sorted_weighted_fees, FeeRateAndWeight,
+};
+use crate::cost_estimates::{EstimatorError, FeeEstimator, FeeRateEstimate};
+use crate::types::chainstate::{BlockHeaderHash, StacksAddress, StacksPrivateKey};
+use stacks_common::util::hash::Hash160;
+
+/// Returns a fixed estimate, or no estimate at all.
+struct ConstantFeeEstimator {
+    estimate: Option<FeeRateEstimate>,
+}
+
+impl FeeEstimator for ConstantFeeEstimator {
+    fn notify_block(
+        &mut self,
+        _receipt: &StacksEpochReceipt,
+        _block_limit: &ExecutionCost,
+    ) -> Result<(), EstimatorError> {
+        Ok(())
+    }
+
+    fn get_rate_estimates(&self) -> Result<FeeRateEstimate, EstimatorError> {
+        self.estimate
+            .clone()
+            .ok_or(EstimatorError::NoEstimateAvailable)
+    }
+}
+
+fn history_estimate() -> FeeRateEstimate {
+    FeeRateEstimate {
+        high: 5f64,
+        middle: 3f64,
+        low: 2f64,
+    }
+}
+
+/// Fill a test mempool with one token transfer per fee in `fees`. The mempool uses the unit
+/// cost estimator and metric, so each transaction's fee rate is its fee and its weight is 1.
+fn make_test_mempool(test_name: &str, fees: &[u64]) -> (MemPoolDB, PathBuf) {
+    let mut chainstate = instantiate_chainstate(false, 0x80000000, test_name);
+    let chainstate_path = chainstate_path(test_name);
+    let mut mempool = MemPoolDB::open_test(false, 0x80000000, &chainstate_path).unwrap();
+
+    let addr = StacksAddress {
+        version: 1,
+        bytes: Hash160([0xff; 20]),
+    };
+
+    let mut mempool_tx = mempool.tx_begin().unwrap();
+    for (i, fee) in fees.iter().enumerate() {
+        let pk = StacksPrivateKey::new();
+        let mut tx = StacksTransaction {
+            version: TransactionVersion::Testnet,
+            chain_id: 0x80000000,
+            auth: TransactionAuth::from_p2pkh(&pk).unwrap(),
+            anchor_mode: TransactionAnchorMode::Any,
+            post_condition_mode: TransactionPostConditionMode::Allow,
+            post_conditions: vec![],
+            payload: TransactionPayload::TokenTransfer(
+                addr.to_account_principal(),
+                (i + 1) as u64,
+                TokenTransferMemo([0u8; 34]),
+            ),
+        };
+        tx.set_tx_fee(*fee);
+        tx.set_origin_nonce(0);
+
+        let txid = tx.txid();
+        let tx_bytes = tx.serialize_to_vec();
+        let origin_addr = tx.origin_address();
+
+        MemPoolDB::try_add_tx(
+            &mut mempool_tx,
+            &mut chainstate,
+            &ConsensusHash([0x1; 20]),
+            &BlockHeaderHash([0x2; 32]),
+            txid,
+            tx_bytes,
+            *fee,
+            1,
+            &origin_addr,
+            0,
+            &origin_addr,
+            0,
+            None,
+        )
+        .unwrap();
+    }
+    mempool_tx.commit().unwrap();
+
+    mempool
+        .estimate_tx_rates(
+            fees.len() as u32,
+            &ExecutionCost::max_value(),
+            &StacksEpochId::Epoch20,
+        )
+        .unwrap();
+
+    let mempool_path = PathBuf::from(MemPoolDB::db_path(&chainstate_path).unwrap());
+    (mempool, mempool_path)
+}
+
+fn unit_weights(fee_rates: &[u64]) -> Vec<FeeRateAndWeight> {
+    fee_rates
+        .iter()
+        .map(|fee_rate| FeeRateAndWeight {
+            fee_rate: *fee_rate as f64,
+            weight: 1,
+        })
+        .collect()
+}
+
+#[test]
+fn test_backlog_estimate_requires_full_blocks() {
+    let pending = vec![
+        FeeRateAndWeight {
+            fee_rate: 10f64,
+            weight: 6,
+        },
+        FeeRateAndWeight {
+            fee_rate: 5f64,
+            weight: 6,
+        },
+    ];
+
+    // only the first 4 units of the second transaction fit in the block
+    let expected_window = vec![
+        FeeRateAndWeight {
+            fee_rate: 5f64,
+            weight: 4,
+        },
+        FeeRateAndWeight {
+            fee_rate: 10f64,
+            weight: 6,
+        },
+    ];
+    assert_eq!(
+        fee_rate_estimate_from_backlog(&pending, 1, 10),
+        Some(fee_rate_estimate_from_sorted_weighted_fees(
+            &expected_window
+        ))
+    );
+
+    // 12 units of backlog do not fill two blocks
+    assert_eq!(fee_rate_estimate_from_backlog(&pending, 2, 10), None);
+    assert_eq!(fee_rate_estimate_from_backlog(&[], 1, 10), None);
+}
+
+#[test]
+fn test_congestion_estimates_by_target() {
+    let fees: Vec<u64> = (1..=25).collect();
+    let (mempool, mempool_path) = make_test_mempool("fee_congestion_estimates_by_target", &fees);
+
+    let estimator = CongestionAwareFeeRateEstimator::new(
+        ConstantFeeEstimator {
+            estimate: Some(history_estimate()),
+        },
+        &mempool_path,
+        10,
+    );
+
+    // the 10 highest-paying transactions fill the next block
+    let expected_next_block =
+        fee_rate_estimate_from_sorted_weighted_fees(&unit_weights(&(16..=25).collect::<Vec<_>>()));
+    assert_eq!(
+        estimator.get_rate_estimates_for_target(1).unwrap(),
+        expected_next_block
+    );
+    assert_eq!(estimator.get_rate_estimates().unwrap(), expected_next_block);
+
+    // 25 pending transactions will all be mined within 3 blocks
+    assert_eq!(
+        estimator.get_rate_estimates_for_target(3).unwrap(),
+        history_estimate()
+    );
+    assert_eq!(
+        estimator.get_rate_estimates_for_target(10).unwrap(),
+        history_estimate()
+    );
+
+    // the highest-paying transaction has been mined, so it no longer counts
+    mempool
+        .conn()
+        .execute(
+            "UPDATE mempool SET last_known_origin_nonce = 1 WHERE tx_fee = 25",
+            rusqlite::NO_PARAMS,
+        )
+        .unwrap();
+    let expected_next_block =
+        fee_rate_estimate_from_sorted_weighted_fees(&unit_weights(&(15..=24).collect::<Vec<_>>()));
+    assert_eq!(
+        estimator.get_rate_estimates_for_target(1).unwrap(),
+        expected_next_block
+    );
+}
+
+#[test]
+fn test_congestion_estimates_without_history() {
+    let fees: Vec<u64> = (1..=25).collect();
+    let (_mempool, mempool_path) =
+        make_test_mempool("fee_congestion_estimates_without_history", &fees);
+
+    let estimator = CongestionAwareFeeRateEstimator::new(
+        ConstantFeeEstimator { estimate: None },
+        &mempool_path,
+        10,
+    );
+
+    // a congested mempool gives an estimate on its own...
+    assert_eq!(
+        estimator.get_rate_estimates_for_target(2).unwrap(),
+        fee_rate_estimate_from_sorted_weighted_fees(&unit_weights(&(6..=25).collect::<Vec<_>>()))
+    );
+
+    // ...but an uncongested one does not
+    assert_eq!(
+        estimator.get_rate_estimates_for_target(3).unwrap_err(),
+        EstimatorError::NoEstimateAvailable
+    );
+}
+
+#[test]
+fn test_congestion_estimates_without_mempool() {
+    let estimator = CongestionAwareFeeRateEstimator::new(
+        ConstantFeeEstimator {
+            estimate: Some(history_estimate()),
+        },
+        Path::new("/tmp/fee_congestion_estimates_without_mempool/mempool.sqlite"),
+        10,
+    );
+
+    for target in [1, 3, 10].iter() {
+        assert_eq!(
+            estimator.get_rate_estimates_for_target(*target).unwrap(),
+            history_estimate()
+        );
+    }
+}
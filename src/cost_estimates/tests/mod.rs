@@ -1,7 +1,9 @@
 use crate::cost_estimates::FeeRateEstimate;
 
+pub mod backtest;
 pub mod common;
 pub mod cost_estimators;
+pub mod fee_congestion;
 pub mod fee_medians;
 pub mod fee_rate_fuzzer;
 pub mod fee_scalar;
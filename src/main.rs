@@ -33,6 +33,7 @@ extern crate slog;
 
 use std::io;
 use std::io::prelude::*;
+use std::path::PathBuf;
 use std::process;
 use std::thread;
 use std::{collections::HashMap, env};
@@ -40,6 +41,12 @@ use std::{convert::TryFrom, fs};
 
 use blockstack_lib::burnchains::BLOCKSTACK_MAGIC_MAINNET;
 use blockstack_lib::clarity_cli;
+use blockstack_lib::cost_estimates::backtest::EstimatorBacktest;
+use blockstack_lib::cost_estimates::fee_medians::WeightedMedianFeeRateEstimator;
+use blockstack_lib::cost_estimates::fee_rate_fuzzer::FeeRateFuzzer;
+use blockstack_lib::cost_estimates::fee_scalar::ScalarFeeRateEstimator;
+use blockstack_lib::cost_estimates::metrics::ProportionalDotProduct;
+use blockstack_lib::cost_estimates::PessimisticEstimator;
 use blockstack_lib::cost_estimates::UnitEstimator;
 use rusqlite::types::ToSql;
 use rusqlite::Connection;
@@ -481,6 +488,172 @@ fn main() {
         process::exit(0);
     }
 
+    if argv[1] == "backtest-estimators" {
+        if argv.len() < 7 {
+            eprintln!(
+                "Usage: {} backtest-estimators <working-dir> <scratch-dir> <start-height> <end-height> <csv-path> [window-size [fuzz-fraction]]
+
+Given a <working-dir>, replay the canonical Stacks blocks from <start-height> to <end-height> through
+fresh instances of each cost and fee estimator, whose databases are created in <scratch-dir>.
+Before each block is passed to the estimators, record what each of them would have predicted for
+the block's transactions. Every prediction is written to <csv-path>, and a summary of each
+estimator's errors is printed as JSON. [window-size] and [fuzz-fraction] configure the weighted
+median and fuzzed fee estimators (defaults: 5 and 0.1).
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+        let scratch_dir = PathBuf::from(&argv[3]);
+        let start_height: u64 = argv[4].parse().expect("Failed to parse <start-height>");
+        let end_height: u64 = argv[5].parse().expect("Failed to parse <end-height>");
+        let csv_path = &argv[6];
+        let window_size: u32 = if argv.len() > 7 {
+            argv[7].parse().expect("Failed to parse [window-size]")
+        } else {
+            5
+        };
+        let fuzz_fraction: f64 = if argv.len() > 8 {
+            argv[8].parse().expect("Failed to parse [fuzz-fraction]")
+        } else {
+            0.1
+        };
+
+        // the estimators must start out knowing nothing
+        if let Ok(mut entries) = fs::read_dir(&scratch_dir) {
+            if entries.next().is_some() {
+                eprintln!("Scratch directory {} is not empty", scratch_dir.display());
+                process::exit(1);
+            }
+        }
+        fs::create_dir_all(&scratch_dir).expect("Failed to create scratch directory");
+        let scratch_path = |name: &str| {
+            let mut path = scratch_dir.clone();
+            path.push(name);
+            path
+        };
+        let make_metric = || ProportionalDotProduct::new(MAX_BLOCK_LEN as u64);
+
+        let mut backtest = EstimatorBacktest::new(Box::new(make_metric()));
+        backtest.add_cost_estimator(
+            "naive_pessimistic",
+            Box::new(
+                PessimisticEstimator::open(
+                    &scratch_path("cost_estimator_pessimistic.sqlite"),
+                    false,
+                )
+                .expect("Failed to open cost estimator"),
+            ),
+        );
+        backtest.add_fee_estimator(
+            "scalar_fee_rate",
+            Box::new(
+                ScalarFeeRateEstimator::open(
+                    &scratch_path("fee_estimator_scalar_rate.sqlite"),
+                    make_metric(),
+                )
+                .expect("Failed to open fee estimator"),
+            ),
+        );
+        backtest.add_fee_estimator(
+            "weighted_median_fee_rate",
+            Box::new(
+                WeightedMedianFeeRateEstimator::open(
+                    &scratch_path("fee_weighted_median.sqlite"),
+                    make_metric(),
+                    window_size,
+                )
+                .expect("Failed to open fee estimator"),
+            ),
+        );
+        backtest.add_fee_estimator(
+            "fuzzed_weighted_median_fee_rate",
+            Box::new(FeeRateFuzzer::new(
+                WeightedMedianFeeRateEstimator::open(
+                    &scratch_path("fee_fuzzed_weighted_median.sqlite"),
+                    make_metric(),
+                    window_size,
+                )
+                .expect("Failed to open fee estimator"),
+                fuzz_fraction,
+            )),
+        );
+
+        let sort_db = SortitionDB::open(&sort_db_path, false)
+            .expect(&format!("Failed to open {}", &sort_db_path));
+        let (mut chain_state, _) =
+            StacksChainState::open(true, CHAIN_ID_MAINNET, &chain_state_path, None)
+                .expect("Failed to open stacks chain state");
+
+        // find the canonical blocks in the height range, from the tip down
+        let canonical_sn = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn())
+            .expect("Failed to get sortition chain tip");
+        let mut block_ids = vec![];
+        let mut cursor = StacksBlockHeader::make_index_block_hash(
+            &canonical_sn.canonical_stacks_tip_consensus_hash,
+            &canonical_sn.canonical_stacks_tip_hash,
+        );
+        let mut cursor_height = canonical_sn.canonical_stacks_tip_height;
+        while cursor_height >= start_height && cursor_height > 0 {
+            if cursor_height <= end_height {
+                block_ids.push(cursor.clone());
+            }
+            cursor = StacksChainState::get_parent_block_id(chain_state.db(), &cursor)
+                .expect("Failed to load parent block ID")
+                .expect("Block has no parent");
+            cursor_height -= 1;
+        }
+        block_ids.reverse();
+
+        let start = time::Instant::now();
+        for (i, block_id) in block_ids.iter().enumerate() {
+            let receipt = match chain_state
+                .replay_block_receipt(&sort_db, block_id)
+                .expect("Failed to replay block")
+            {
+                Some(receipt) => receipt,
+                None => {
+                    eprintln!("Block {} is not stored; skipping", block_id);
+                    continue;
+                }
+            };
+            let block_limit =
+                SortitionDB::get_stacks_epoch_by_epoch_id(sort_db.conn(), &receipt.evaluated_epoch)
+                    .expect("Failed to load Stacks epoch")
+                    .expect("No such Stacks epoch")
+                    .block_limit;
+            backtest.replay_block(&receipt, &block_limit);
+
+            if (i + 1) % 1000 == 0 {
+                info!(
+                    "Replayed {} of {} blocks (up to height {})",
+                    i + 1,
+                    block_ids.len(),
+                    receipt.header.stacks_block_height
+                );
+            }
+        }
+
+        let mut csv_file = fs::File::create(csv_path).expect("Failed to create CSV file");
+        backtest
+            .write_csv(&mut csv_file)
+            .expect("Failed to write CSV file");
+
+        eprintln!(
+            "Replayed {} blocks in {}",
+            block_ids.len(),
+            start.elapsed().as_seconds_f32()
+        );
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&backtest.summarize()).unwrap()
+        );
+        process::exit(0);
+    }
+
     if argv[1] == "get-block-inventory" {
         if argv.len() < 3 {
             eprintln!(
@@ -527,6 +700,108 @@ Given a <working-dir>, obtain a 2100 header hash block inventory (with an empty
         process::exit(0);
     }
 
+    if argv[1] == "check-chainstate" {
+        if argv.len() < 3 {
+            eprintln!(
+                "Usage: {} check-chainstate <working-dir> [start-height]
+
+Given a <working-dir>, walk the canonical Stacks fork and check each block's MARF roots, staging
+data, sortition and liquid STX supply against one another.  Each block is also replayed, and the
+balances of the accounts it touched are checked against the STX it moved.  Blocks below
+[start-height] are skipped.  Reports the first inconsistent block.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let start_height: u64 = if argv.len() > 3 {
+            argv[3].parse().expect("Failed to parse <start-height>")
+        } else {
+            0
+        };
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let sort_db = SortitionDB::open(&sort_db_path, false)
+            .expect(&format!("Failed to open {}", &sort_db_path));
+        let (mut chain_state, _) =
+            StacksChainState::open(true, CHAIN_ID_MAINNET, &chain_state_path, None)
+                .expect("Failed to open stacks chain state");
+
+        let start = time::Instant::now();
+        let report = chain_state
+            .check_integrity(&sort_db, start_height)
+            .expect("Failed to check chainstate");
+
+        println!(
+            "Checked {} blocks up to tip {} in {}",
+            report.blocks_checked,
+            &report.tip,
+            start.elapsed().as_seconds_f32()
+        );
+        match report.first_inconsistency {
+            Some(inconsistency) => {
+                println!(
+                    "First inconsistent block is {} at height {}: {}",
+                    &inconsistency.index_block_hash,
+                    inconsistency.stacks_block_height,
+                    &inconsistency.problem
+                );
+                process::exit(1);
+            }
+            None => {
+                println!("OK");
+                process::exit(0);
+            }
+        }
+    }
+
+    if argv[1] == "get-cost-voting" {
+        if argv.len() < 3 {
+            eprintln!(
+                "Usage: {} get-cost-voting <working-dir> [index-block-hash]
+
+Given a <working-dir>, print the cost-voting contract's proposals and confirmed overrides, and the
+cost function used for every Clarity cost function, as of the block [index-block-hash] (defaults
+to the canonical Stacks chain tip).  Prints JSON.
+",
+                argv[0]
+            );
+            process::exit(1);
+        }
+
+        let sort_db_path = format!("{}/mainnet/burnchain/sortition", &argv[2]);
+        let chain_state_path = format!("{}/mainnet/chainstate/", &argv[2]);
+
+        let sort_db = SortitionDB::open(&sort_db_path, false)
+            .expect(&format!("Failed to open {}", &sort_db_path));
+        let (mut chain_state, _) =
+            StacksChainState::open(true, CHAIN_ID_MAINNET, &chain_state_path, None)
+                .expect("Failed to open stacks chain state");
+
+        let block_id = if argv.len() > 3 {
+            StacksBlockId::from_hex(&argv[3]).expect("Failed to parse <index-block-hash>")
+        } else {
+            let canonical_sn = SortitionDB::get_canonical_burn_chain_tip(sort_db.conn())
+                .expect("Failed to get sortition chain tip");
+            StacksBlockHeader::make_index_block_hash(
+                &canonical_sn.canonical_stacks_tip_consensus_hash,
+                &canonical_sn.canonical_stacks_tip_hash,
+            )
+        };
+
+        let report = chain_state
+            .get_cost_voting_report(&sort_db, &block_id)
+            .expect("Failed to load cost-voting state");
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&report).expect("Failed to serialize report")
+        );
+        process::exit(0);
+    }
+
     if argv[1] == "can-download-microblock" {
         if argv.len() < 3 {
             eprintln!(
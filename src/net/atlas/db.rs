@@ -450,6 +450,16 @@ impl AtlasDB {
         Ok(rows)
     }
 
+    pub fn find_attachment_instances_for_contract(
+        &self,
+        contract_id: &QualifiedContractIdentifier,
+    ) -> Result<Vec<AttachmentInstance>, db_error> {
+        let qry = "SELECT * FROM attachment_instances WHERE contract_id = ?1 ORDER BY block_height, attachment_index".to_string();
+        let args = [&contract_id.to_string() as &dyn ToSql];
+        let rows = query_rows::<AttachmentInstance, _>(&self.conn, &qry, &args)?;
+        Ok(rows)
+    }
+
     pub fn find_attachment(
         &mut self,
         content_hash: &Hash160,
@@ -1202,3 +1202,51 @@ fn test_attachments_inventory_requests_hashing() {
 
     println!("{:?}", requests);
 }
+
+#[test]
+fn test_find_attachment_instances_for_contract() {
+    let atlas_config = AtlasConfig {
+        contracts: HashSet::new(),
+        attachments_max_size: 1024,
+        max_uninstantiated_attachments: 100,
+        uninstantiated_attachments_expire_after: 10,
+        unresolved_attachment_instances_expire_after: 10,
+        genesis_attachments: None,
+    };
+    let mut atlas_db = AtlasDB::connect_memory(atlas_config).unwrap();
+
+    let attachment_instances = [
+        new_attachment_instance_from(&new_attachment_from("facade13"), 1, 2),
+        new_attachment_instance_from(&new_attachment_from("facade11"), 0, 1),
+        new_attachment_instance_from(&new_attachment_from("facade12"), 0, 2),
+    ];
+    for attachment_instance in attachment_instances.iter() {
+        atlas_db
+            .insert_uninstantiated_attachment_instance(attachment_instance, true)
+            .unwrap();
+    }
+
+    // instances from other contracts are not reported
+    let mut other_instance = new_attachment_instance_from(&new_attachment_from("facade21"), 0, 1);
+    other_instance.contract_id =
+        QualifiedContractIdentifier::parse("ST000000000000000000002AMW42H.bns").unwrap();
+    atlas_db
+        .insert_uninstantiated_attachment_instance(&other_instance, true)
+        .unwrap();
+
+    let found = atlas_db
+        .find_attachment_instances_for_contract(&QualifiedContractIdentifier::transient())
+        .unwrap();
+    let expected = [
+        attachment_instances[1].content_hash.clone(),
+        attachment_instances[2].content_hash.clone(),
+        attachment_instances[0].content_hash.clone(),
+    ];
+    assert_eq!(
+        found
+            .iter()
+            .map(|instance| instance.content_hash.clone())
+            .collect::<Vec<_>>(),
+        expected.to_vec()
+    );
+}
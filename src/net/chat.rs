@@ -635,6 +635,64 @@ impl ConversationP2P {
         (peer_services & expected_bits) == expected_bits
     }
 
+    /// Can we talk to this remote neighbor over the encrypted transport?  We can if we both have
+    /// the ENCRYPTION bit set.
+    pub fn supports_encryption(local_services: u16, peer_services: u16) -> bool {
+        let expected_bit = ServiceFlags::ENCRYPTION as u16;
+        (local_services & expected_bit) != 0 && (peer_services & expected_bit) != 0
+    }
+
+    /// Can we push compact blocks to this remote neighbor?  We can if we both have the
+    /// COMPACT_BLOCKS bit set.
+    pub fn supports_compact_blocks(local_services: u16, peer_services: u16) -> bool {
+        let expected_bit = ServiceFlags::COMPACT_BLOCKS as u16;
+        (local_services & expected_bit) != 0 && (peer_services & expected_bit) != 0
+    }
+
+    /// Switch our side of the conversation to the encrypted transport, if we and the remote peer
+    /// both support it and we haven't done so already.  This queues a SessionKey message, after
+    /// which everything we send to this peer is encrypted to its public key.
+    fn start_encryption(
+        &mut self,
+        local_peer: &LocalPeer,
+        burnchain_view: &BurnchainView,
+    ) -> Result<(), net_error> {
+        if !Self::supports_encryption(local_peer.services, self.peer_services)
+            || self.connection.is_encrypting()
+        {
+            return Ok(());
+        }
+
+        let (mut handle, ephemeral_public_key) =
+            self.connection.make_session_key_handle(self.conn_id)?;
+        let session_key = self.sign_message(
+            burnchain_view,
+            &local_peer.private_key,
+            StacksMessageType::SessionKey(SessionKeyData {
+                ephemeral_public_key: StacksPublicKeyBuffer::from_public_key(&ephemeral_public_key),
+            }),
+        )?;
+        session_key.consensus_serialize(&mut handle)?;
+
+        self.stats.msgs_tx += 1;
+        debug!("{:?}: Switching to the encrypted transport", &self);
+
+        self.reply_handles.push_back(handle);
+        Ok(())
+    }
+
+    /// Remember the private key that goes with the public key we are handshaking with, since
+    /// that is the key the remote peer encrypts its side of the conversation to.  Once the
+    /// remote peer has switched to the encrypted transport, it keeps encrypting to that key even
+    /// if ours is rotated, so the key stays pinned from then on.
+    fn pin_session_private_key(&mut self, local_peer: &LocalPeer) -> Result<(), net_error> {
+        if self.connection.is_decrypting() {
+            return Ok(());
+        }
+        self.connection
+            .set_session_private_key(local_peer.private_key.clone())
+    }
+
     /// Determine whether or not a given (height, burn_header_hash) pair _disagrees_ with our
     /// burnchain view.  If it does, return true.  If it doesn't (including if the given pair is
     /// simply absent from the chain_view), then return False.
@@ -1180,6 +1238,7 @@ impl ConversationP2P {
             );
         }
 
+        self.pin_session_private_key(local_peer)?;
         let accept_data = HandshakeAcceptData::new(local_peer, self.heartbeat);
         let accept = StacksMessage::from_chain_view(
             self.version,
@@ -1624,6 +1683,71 @@ impl ConversationP2P {
         self.sign_and_reply(local_peer, burnchain_view, preamble, response)
     }
 
+    /// Create a response to an inbound GetCompactBlockTxs request, but unsigned.
+    /// Returns a Nack if we don't have the block, or if the request asks for transactions the
+    /// block doesn't have.
+    pub fn make_getcompactblocktxs_response(
+        chainstate: &StacksChainState,
+        get_txs: &GetCompactBlockTxsData,
+    ) -> Result<StacksMessageType, net_error> {
+        let block = match StacksChainState::load_block(
+            &chainstate.blocks_path,
+            &get_txs.consensus_hash,
+            &get_txs.block_hash,
+        ) {
+            Ok(Some(block)) => block,
+            Ok(None) | Err(chainstate_error::DBError(db_error::NotFoundError)) => {
+                return Ok(StacksMessageType::Nack(NackData::new(
+                    NackErrorCodes::NoSuchBlock,
+                )));
+            }
+            Err(e) => {
+                return Err(net_error::from(e));
+            }
+        };
+
+        let mut txs = Vec::with_capacity(get_txs.indexes.len());
+        for index in get_txs.indexes.iter() {
+            match block.txs.get(*index as usize) {
+                Some(tx) => txs.push(tx.clone()),
+                None => {
+                    return Ok(StacksMessageType::Nack(NackData::new(
+                        NackErrorCodes::InvalidMessage,
+                    )));
+                }
+            }
+        }
+
+        Ok(StacksMessageType::CompactBlockTxs(CompactBlockTxsData {
+            consensus_hash: get_txs.consensus_hash.clone(),
+            block_hash: get_txs.block_hash.clone(),
+            txs,
+        }))
+    }
+
+    /// Handle an inbound GetCompactBlockTxs request -- the peer we pushed a compact block to is
+    /// missing some of its transactions.
+    /// Returns a reply handle to the generated message (possibly a nack)
+    fn handle_getcompactblocktxs(
+        &mut self,
+        local_peer: &LocalPeer,
+        chainstate: &StacksChainState,
+        burnchain_view: &BurnchainView,
+        preamble: &Preamble,
+        get_txs: &GetCompactBlockTxsData,
+    ) -> Result<ReplyHandleP2P, net_error> {
+        let response = ConversationP2P::make_getcompactblocktxs_response(chainstate, get_txs)?;
+        debug!(
+            "{:?}: Handled GetCompactBlockTxs for {} transaction(s) in {}/{}. Reply {}",
+            &local_peer,
+            get_txs.indexes.len(),
+            &get_txs.consensus_hash,
+            &get_txs.block_hash,
+            response.get_message_name()
+        );
+        self.sign_and_reply(local_peer, burnchain_view, preamble, response)
+    }
+
     /// Verify that there are no cycles in our relayers list.
     /// Identify relayers by public key hash
     fn check_relayer_cycles(relayers: &Vec<RelayData>) -> bool {
@@ -1841,6 +1965,31 @@ impl ConversationP2P {
                     }
                 }
             }
+            StacksMessageType::CompactBlock(_) | StacksMessageType::CompactBlockTxs(_) => {
+                monitoring::increment_stx_blocks_received_counter();
+
+                // not handled here (the peer network reconstructs the block), but count it
+                // towards the block-push bandwidth
+                match self.validate_blocks_push(
+                    local_peer,
+                    chain_view,
+                    &msg.preamble,
+                    msg.relayers.clone(),
+                )? {
+                    Some(handle) => Ok(handle),
+                    None => {
+                        // will forward upstream
+                        return Ok(Some(msg));
+                    }
+                }
+            }
+            StacksMessageType::GetCompactBlockTxs(ref get_txs) => self.handle_getcompactblocktxs(
+                local_peer,
+                chainstate,
+                chain_view,
+                &msg.preamble,
+                get_txs,
+            ),
             StacksMessageType::Microblocks(_) => {
                 monitoring::increment_stx_micro_blocks_received_counter();
 
@@ -2049,8 +2198,12 @@ impl ConversationP2P {
             }
             StacksMessageType::HandshakeAccept(ref data) => {
                 test_debug!("{:?}: Got HandshakeAccept", &self);
-                self.handle_handshake_accept(&msg.preamble, data)
-                    .and_then(|_| Ok(None))
+                self.handle_handshake_accept(&msg.preamble, data)?;
+                self.pin_session_private_key(local_peer)?;
+
+                // the peer that sent the handshake is the one that begins encrypting
+                self.start_encryption(local_peer, burnchain_view)?;
+                Ok(None)
             }
             StacksMessageType::Ping(_) => {
                 test_debug!("{:?}: Got Ping", &self);
@@ -2080,6 +2233,21 @@ impl ConversationP2P {
                 test_debug!("{:?}: Got NatPunchReply({})", &self, _m.nonce);
                 Ok(None)
             }
+            StacksMessageType::SessionKey(_) => {
+                test_debug!("{:?}: Got SessionKey", &self);
+                if !Self::supports_encryption(local_peer.services, self.peer_services) {
+                    debug!(
+                        "{:?}: Got SessionKey, but the encrypted transport was not negotiated",
+                        &self
+                    );
+                    return Err(net_error::InvalidMessage);
+                }
+
+                // the remote peer is now encrypting, so follow suit
+                consume = true;
+                self.start_encryption(local_peer, burnchain_view)?;
+                Ok(None)
+            }
             _ => {
                 test_debug!(
                     "{:?}: Got a data-plane message (type {})",
@@ -2108,7 +2276,7 @@ impl ConversationP2P {
         // Anything else will be nack'ed -- the peer will first need to handshake.
         let mut consume = false;
         let solicited = self.connection.is_solicited(&msg);
-        let reply_opt = match msg.payload {
+        let reply_opt: Result<Option<StacksMessage>, net_error> = match msg.payload {
             StacksMessageType::Handshake(_) => {
                 // monitoring::increment_p2p_msg_unauthenticated_handshake_received_counter();
                 monitoring::increment_msg_counter("p2p_unauthenticated_handshake".to_string());
@@ -2121,8 +2289,12 @@ impl ConversationP2P {
             StacksMessageType::HandshakeAccept(ref data) => {
                 if solicited {
                     test_debug!("{:?}: Got unauthenticated HandshakeAccept", &self);
-                    self.handle_handshake_accept(&msg.preamble, data)
-                        .and_then(|_| Ok(None))
+                    self.handle_handshake_accept(&msg.preamble, data)?;
+                    self.pin_session_private_key(local_peer)?;
+
+                    // the peer that sent the handshake is the one that begins encrypting
+                    self.start_encryption(local_peer, burnchain_view)?;
+                    Ok(None)
                 } else {
                     test_debug!("{:?}: Unsolicited unauthenticated HandshakeAccept", &self);
 
@@ -2171,6 +2343,11 @@ impl ConversationP2P {
                 // it's okay to forward this back (i.e. don't consume)
                 Ok(None)
             }
+            StacksMessageType::SessionKey(_) => {
+                // can't switch to the encrypted transport before the handshake
+                debug!("{:?}: Got unauthenticated SessionKey", &self);
+                return Err(net_error::InvalidMessage);
+            }
             _ => {
                 test_debug!(
                     "{:?}: Got unauthenticated message (type {}), will NACK",
@@ -2193,8 +2370,8 @@ impl ConversationP2P {
                 consume = true;
                 Ok(Some(nack))
             }
-        }?;
-        Ok((reply_opt, consume))
+        };
+        Ok((reply_opt?, consume))
     }
 
     /// Carry on a conversation with the remote peer.
@@ -3372,6 +3549,237 @@ mod test {
         }
     }
 
+    /// Handshake two conversations whose peers may or may not advertise the encrypted transport,
+    /// and then have them exchange a ping and a pong.
+    fn convo_handshake_encryption_test(
+        test_name: &str,
+        encrypt_1: bool,
+        encrypt_2: bool,
+        rotate_key_2: bool,
+    ) {
+        let conn_opts = ConnectionOptions::default();
+        let socketaddr_1 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)), 8080);
+        let socketaddr_2 = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(1, 2, 3, 4)), 8081);
+
+        let burnchain = testing_burnchain_config();
+
+        let mut chain_view = BurnchainView {
+            burn_block_height: 12348,
+            burn_block_hash: BurnchainHeaderHash([0x11; 32]),
+            burn_stable_block_height: 12341,
+            burn_stable_block_hash: BurnchainHeaderHash([0x22; 32]),
+            last_burn_block_hashes: HashMap::new(),
+        };
+        chain_view.make_test_data();
+
+        let (mut peerdb_1, mut sortdb_1, pox_id_1, mut chainstate_1) = make_test_chain_dbs(
+            &format!("{}_1", test_name),
+            &burnchain,
+            0x9abcdef0,
+            12350,
+            "http://peer1.com".into(),
+            &vec![],
+            &vec![],
+        );
+        let (mut peerdb_2, mut sortdb_2, pox_id_2, mut chainstate_2) = make_test_chain_dbs(
+            &format!("{}_2", test_name),
+            &burnchain,
+            0x9abcdef0,
+            12351,
+            "http://peer2.com".into(),
+            &vec![],
+            &vec![],
+        );
+
+        db_setup(&mut peerdb_1, &mut sortdb_1, &socketaddr_1, &chain_view);
+        db_setup(&mut peerdb_2, &mut sortdb_2, &socketaddr_2, &chain_view);
+
+        for (peerdb, encrypt) in [(&mut peerdb_1, encrypt_1), (&mut peerdb_2, encrypt_2)] {
+            let mut services = (ServiceFlags::RELAY as u16) | (ServiceFlags::RPC as u16);
+            if encrypt {
+                services |= ServiceFlags::ENCRYPTION as u16;
+            }
+            let mut tx = peerdb.tx_begin().unwrap();
+            PeerDB::set_local_services(&mut tx, services).unwrap();
+            tx.commit().unwrap();
+        }
+
+        let local_peer_1 = PeerDB::get_local_peer(&peerdb_1.conn()).unwrap();
+        let local_peer_2 = PeerDB::get_local_peer(&peerdb_2.conn()).unwrap();
+
+        let mut convo_1 = ConversationP2P::new(
+            123,
+            456,
+            &burnchain,
+            &socketaddr_2,
+            &conn_opts,
+            true,
+            0,
+            StacksEpoch::unit_test_pre_2_05(0),
+        );
+        let mut convo_2 = ConversationP2P::new(
+            123,
+            456,
+            &burnchain,
+            &socketaddr_1,
+            &conn_opts,
+            true,
+            0,
+            StacksEpoch::unit_test_pre_2_05(0),
+        );
+
+        let encrypted = encrypt_1 && encrypt_2;
+
+        // convo_1 sends a handshake to convo_2
+        let handshake_1 = convo_1
+            .sign_message(
+                &chain_view,
+                &local_peer_1.private_key,
+                StacksMessageType::Handshake(HandshakeData::from_local_peer(&local_peer_1)),
+            )
+            .unwrap();
+        let mut rh_handshake_1 = convo_1.send_signed_request(handshake_1, 1000000).unwrap();
+
+        convo_send_recv(&mut convo_1, vec![&mut rh_handshake_1], &mut convo_2);
+        convo_2
+            .chat(
+                &local_peer_2,
+                &mut peerdb_2,
+                &sortdb_2,
+                &pox_id_2,
+                &mut chainstate_2,
+                &mut BlockHeaderCache::new(),
+                &chain_view,
+            )
+            .unwrap();
+
+        // convo_2 only accepts the handshake; it's up to convo_1 to begin encrypting
+        assert!(!convo_2.connection.is_encrypting());
+
+        // convo_1 gets the HandshakeAccept, and switches to the encrypted transport
+        convo_send_recv(&mut convo_2, vec![&mut rh_handshake_1], &mut convo_1);
+        convo_1
+            .chat(
+                &local_peer_1,
+                &mut peerdb_1,
+                &sortdb_1,
+                &pox_id_1,
+                &mut chainstate_1,
+                &mut BlockHeaderCache::new(),
+                &chain_view,
+            )
+            .unwrap();
+
+        match rh_handshake_1.recv(0).unwrap().payload {
+            StacksMessageType::HandshakeAccept(_) => {}
+            _ => {
+                assert!(false);
+            }
+        }
+        assert_eq!(convo_1.connection.is_encrypting(), encrypted);
+
+        // convo_1 sends a ping (encrypted, if both sides support it)
+        let ping_data_1 = PingData::new();
+        let ping_1 = convo_1
+            .sign_message(
+                &chain_view,
+                &local_peer_1.private_key,
+                StacksMessageType::Ping(ping_data_1.clone()),
+            )
+            .unwrap();
+        let mut rh_ping_1 = convo_1.send_signed_request(ping_1, 1000000).unwrap();
+
+        // convo_2's key may be rotated after the handshake, but convo_1 still encrypts to the key
+        // it handshook with
+        let mut local_peer_2 = local_peer_2;
+        if rotate_key_2 {
+            local_peer_2.private_key = Secp256k1PrivateKey::new();
+        }
+
+        // convo_2 gets the session key (if any) and ping, and follows suit
+        convo_send_recv(&mut convo_1, vec![&mut rh_ping_1], &mut convo_2);
+        let unhandled_2 = convo_2
+            .chat(
+                &local_peer_2,
+                &mut peerdb_2,
+                &sortdb_2,
+                &pox_id_2,
+                &mut chainstate_2,
+                &mut BlockHeaderCache::new(),
+                &chain_view,
+            )
+            .unwrap();
+
+        // session key and ping are both consumed
+        assert_eq!(unhandled_2.len(), 0);
+        assert_eq!(convo_2.connection.is_decrypting(), encrypted);
+        assert_eq!(convo_2.connection.is_encrypting(), encrypted);
+
+        if rotate_key_2 {
+            // convo_2 signed its pong with its new key, which convo_1 won't accept until they
+            // handshake again
+            return;
+        }
+
+        // convo_1 gets the session key (if any) and pong
+        convo_send_recv(&mut convo_2, vec![&mut rh_ping_1], &mut convo_1);
+        let unhandled_1 = convo_1
+            .chat(
+                &local_peer_1,
+                &mut peerdb_1,
+                &sortdb_1,
+                &pox_id_1,
+                &mut chainstate_1,
+                &mut BlockHeaderCache::new(),
+                &chain_view,
+            )
+            .unwrap();
+
+        assert_eq!(unhandled_1.len(), 0);
+        assert_eq!(convo_1.connection.is_decrypting(), encrypted);
+
+        match rh_ping_1.recv(0).unwrap().payload {
+            StacksMessageType::Pong(ref data) => {
+                assert_eq!(data.nonce, ping_data_1.nonce);
+            }
+            _ => {
+                assert!(false);
+            }
+        }
+    }
+
+    #[test]
+    fn convo_handshake_encryption() {
+        convo_handshake_encryption_test("convo_handshake_encryption", true, true, false);
+    }
+
+    #[test]
+    fn convo_handshake_encryption_key_rotation() {
+        convo_handshake_encryption_test(
+            "convo_handshake_encryption_key_rotation",
+            true,
+            true,
+            true,
+        );
+    }
+
+    #[test]
+    fn convo_handshake_encryption_unsupported() {
+        // neither peer switches if only one of them supports the encrypted transport
+        convo_handshake_encryption_test(
+            "convo_handshake_encryption_unsupported_1",
+            true,
+            false,
+            false,
+        );
+        convo_handshake_encryption_test(
+            "convo_handshake_encryption_unsupported_2",
+            false,
+            true,
+            false,
+        );
+    }
+
     #[test]
     fn convo_handshake_ping_loop() {
         let conn_opts = ConnectionOptions::default();
@@ -0,0 +1,392 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Encrypted transport for the p2p network.
+//!
+//! Once two peers have both advertised `ServiceFlags::ENCRYPTION` in their handshakes, each
+//! side generates an ephemeral secp256k1 key and sends its public half to the other side in a
+//! signed `SessionKey` message.  Everything the sender writes after that message is sealed
+//! into frames keyed by the ECDH shared secret between the ephemeral key and the recipient's
+//! node key, so each direction of a conversation has its own keys.
+//!
+//! A frame is laid out as follows:
+//!
+//! ```text
+//! [ciphertext length: u32 BE][ChaCha20-Poly1305 ciphertext][Poly1305 tag: 16 bytes]
+//! ```
+//!
+//! The i-th frame is sealed with ChaCha20-Poly1305 (RFC 8439), using i as the nonce and the
+//! length header as associated data.  Since the sequence number is implicit, frames that are
+//! dropped, reordered or replayed fail to authenticate.
+
+use std::fmt;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Key, Nonce};
+use secp256k1::ecdh::SharedSecret as LibSecp256k1SharedSecret;
+use secp256k1::PublicKey as LibSecp256k1PublicKey;
+use secp256k1::SecretKey as LibSecp256k1PrivateKey;
+use sha2::{Digest, Sha256};
+
+use crate::burnchains::PrivateKey;
+use crate::net::Error as net_error;
+
+use stacks_common::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
+
+/// Largest ciphertext a single frame may carry
+pub const SESSION_FRAME_MAX_LEN: u32 = 65536;
+
+/// Length of the frame header (the ciphertext length)
+pub const SESSION_FRAME_HEADER_LEN: usize = 4;
+
+/// Length of the frame authentication tag
+pub const SESSION_FRAME_TAG_LEN: usize = 16;
+
+/// Domain separator for the key derived from the ECDH shared secret
+const SESSION_KEY_LABEL: &[u8] = b"stacks-p2p-session-key";
+
+/// One direction of an encrypted conversation.
+pub struct SessionCipher {
+    aead: ChaCha20Poly1305,
+    frame_seq: u64,
+}
+
+impl fmt::Debug for SessionCipher {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // never log key material
+        write!(f, "SessionCipher(frame_seq={})", self.frame_seq)
+    }
+}
+
+/// Compute ECDH(privk, pubk) -- the SHA256 of the shared point.
+fn ecdh(
+    privk: &Secp256k1PrivateKey,
+    pubk: &Secp256k1PublicKey,
+) -> Result<LibSecp256k1SharedSecret, net_error> {
+    let lib_privk = LibSecp256k1PrivateKey::from_slice(&privk.to_bytes()[0..32])
+        .map_err(|e| net_error::SigningError(format!("Invalid session private key: {:?}", &e)))?;
+    let lib_pubk = LibSecp256k1PublicKey::from_slice(&pubk.to_bytes_compressed())
+        .map_err(|e| net_error::VerifyingError(format!("Invalid session public key: {:?}", &e)))?;
+    Ok(LibSecp256k1SharedSecret::new(&lib_pubk, &lib_privk))
+}
+
+/// Derive a 32-byte key from the shared secret, bound to both public keys involved.
+fn derive_key(
+    label: &[u8],
+    shared_secret: &[u8],
+    ephemeral_public_key: &Secp256k1PublicKey,
+    recipient_public_key: &Secp256k1PublicKey,
+) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(label);
+    hasher.update(shared_secret);
+    hasher.update(&ephemeral_public_key.to_bytes_compressed());
+    hasher.update(&recipient_public_key.to_bytes_compressed());
+
+    let mut ret = [0u8; 32];
+    ret.copy_from_slice(&hasher.finalize()[..]);
+    ret
+}
+
+impl SessionCipher {
+    fn from_shared_secret(
+        shared_secret: &[u8],
+        ephemeral_public_key: &Secp256k1PublicKey,
+        recipient_public_key: &Secp256k1PublicKey,
+    ) -> SessionCipher {
+        let key = derive_key(
+            SESSION_KEY_LABEL,
+            shared_secret,
+            ephemeral_public_key,
+            recipient_public_key,
+        );
+        SessionCipher {
+            aead: ChaCha20Poly1305::new(Key::from_slice(&key)),
+            frame_seq: 0,
+        }
+    }
+
+    /// Set up the sending side of a session to the peer with the given node public key.
+    /// Returns the cipher, and the ephemeral public key the peer needs in order to set up the
+    /// receiving side.
+    pub fn new_sender(
+        recipient_public_key: &Secp256k1PublicKey,
+    ) -> Result<(SessionCipher, Secp256k1PublicKey), net_error> {
+        let ephemeral_private_key = Secp256k1PrivateKey::new();
+        let mut ephemeral_public_key = Secp256k1PublicKey::from_private(&ephemeral_private_key);
+        ephemeral_public_key.set_compressed(true);
+
+        let shared_secret = ecdh(&ephemeral_private_key, recipient_public_key)?;
+        let cipher = SessionCipher::from_shared_secret(
+            shared_secret.as_ref(),
+            &ephemeral_public_key,
+            recipient_public_key,
+        );
+        Ok((cipher, ephemeral_public_key))
+    }
+
+    /// Set up the receiving side of a session, given our node private key and the ephemeral
+    /// public key the peer sent us.
+    pub fn new_recipient(
+        local_private_key: &Secp256k1PrivateKey,
+        ephemeral_public_key: &Secp256k1PublicKey,
+    ) -> Result<SessionCipher, net_error> {
+        let recipient_public_key = Secp256k1PublicKey::from_private(local_private_key);
+        let shared_secret = ecdh(local_private_key, ephemeral_public_key)?;
+        Ok(SessionCipher::from_shared_secret(
+            shared_secret.as_ref(),
+            ephemeral_public_key,
+            &recipient_public_key,
+        ))
+    }
+
+    /// The nonce for the current frame: its sequence number, zero-padded to 96 bits.
+    fn frame_nonce(&self) -> Nonce {
+        let mut nonce = [0u8; 12];
+        nonce[4..12].copy_from_slice(&self.frame_seq.to_be_bytes());
+        Nonce::clone_from_slice(&nonce)
+    }
+
+    /// Seal plaintext into the next frame.
+    pub fn seal_frame(&mut self, plaintext: &[u8]) -> Result<Vec<u8>, net_error> {
+        if plaintext.len() > SESSION_FRAME_MAX_LEN as usize {
+            return Err(net_error::SerializeError(format!(
+                "Session frame of {} bytes is too big",
+                plaintext.len()
+            )));
+        }
+
+        let len_bytes = (plaintext.len() as u32).to_be_bytes();
+        let sealed = self
+            .aead
+            .encrypt(
+                &self.frame_nonce(),
+                Payload {
+                    msg: plaintext,
+                    aad: &len_bytes,
+                },
+            )
+            .map_err(|_| {
+                net_error::SerializeError(format!(
+                    "Failed to seal session frame {}",
+                    self.frame_seq
+                ))
+            })?;
+
+        let mut frame = Vec::with_capacity(SESSION_FRAME_HEADER_LEN + sealed.len());
+        frame.extend_from_slice(&len_bytes);
+        frame.extend_from_slice(&sealed);
+
+        self.frame_seq = self
+            .frame_seq
+            .checked_add(1)
+            .ok_or(net_error::OverflowError(
+                "Session frame sequence overflow".to_string(),
+            ))?;
+        Ok(frame)
+    }
+
+    /// Authenticate and decrypt the next frame, given its length header and its ciphertext
+    /// followed by its tag.
+    fn open_frame(&mut self, len_bytes: &[u8], sealed: &[u8]) -> Result<Vec<u8>, net_error> {
+        let plaintext = self
+            .aead
+            .decrypt(
+                &self.frame_nonce(),
+                Payload {
+                    msg: sealed,
+                    aad: len_bytes,
+                },
+            )
+            .map_err(|_| {
+                net_error::VerifyingError(format!(
+                    "Session frame {} failed to authenticate",
+                    self.frame_seq
+                ))
+            })?;
+
+        self.frame_seq = self
+            .frame_seq
+            .checked_add(1)
+            .ok_or(net_error::OverflowError(
+                "Session frame sequence overflow".to_string(),
+            ))?;
+        Ok(plaintext)
+    }
+}
+
+/// Reassembles frames out of a stream of ciphertext bytes.
+#[derive(Debug)]
+pub struct SessionFrameReader {
+    cipher: SessionCipher,
+    buf: Vec<u8>,
+}
+
+impl SessionFrameReader {
+    pub fn new(cipher: SessionCipher) -> SessionFrameReader {
+        SessionFrameReader {
+            cipher: cipher,
+            buf: vec![],
+        }
+    }
+
+    /// Buffer up ciphertext bytes, and return the plaintext of every complete frame they
+    /// finish.  Partial frames stay buffered until the rest of their bytes arrive.
+    pub fn open(&mut self, bytes: &[u8]) -> Result<Vec<u8>, net_error> {
+        self.buf.extend_from_slice(bytes);
+
+        let mut plaintext = vec![];
+        let mut ptr = 0;
+        loop {
+            let avail = &self.buf[ptr..];
+            if avail.len() < SESSION_FRAME_HEADER_LEN {
+                break;
+            }
+
+            let mut len_bytes = [0u8; SESSION_FRAME_HEADER_LEN];
+            len_bytes.copy_from_slice(&avail[0..SESSION_FRAME_HEADER_LEN]);
+            let frame_len = u32::from_be_bytes(len_bytes);
+            if frame_len > SESSION_FRAME_MAX_LEN {
+                return Err(net_error::DeserializeError(format!(
+                    "Session frame of {} bytes is too big",
+                    frame_len
+                )));
+            }
+
+            let frame_end = SESSION_FRAME_HEADER_LEN + (frame_len as usize) + SESSION_FRAME_TAG_LEN;
+            if avail.len() < frame_end {
+                break;
+            }
+
+            let frame_plaintext = self
+                .cipher
+                .open_frame(&len_bytes, &avail[SESSION_FRAME_HEADER_LEN..frame_end])?;
+            plaintext.extend_from_slice(&frame_plaintext);
+            ptr += frame_end;
+        }
+
+        self.buf.drain(0..ptr);
+        Ok(plaintext)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn make_session() -> (SessionCipher, SessionFrameReader) {
+        let recipient_private_key = Secp256k1PrivateKey::new();
+        let recipient_public_key = Secp256k1PublicKey::from_private(&recipient_private_key);
+
+        let (sender, ephemeral_public_key) =
+            SessionCipher::new_sender(&recipient_public_key).unwrap();
+        let recipient =
+            SessionCipher::new_recipient(&recipient_private_key, &ephemeral_public_key).unwrap();
+        (sender, SessionFrameReader::new(recipient))
+    }
+
+    #[test]
+    fn test_session_frames_roundtrip() {
+        let (mut sender, mut reader) = make_session();
+
+        let messages: Vec<Vec<u8>> = vec![vec![], vec![0x01], (0..255).collect(), vec![0xab; 8192]];
+
+        let mut stream = vec![];
+        for msg in messages.iter() {
+            let frame = sender.seal_frame(msg).unwrap();
+            assert_eq!(
+                frame.len(),
+                SESSION_FRAME_HEADER_LEN + msg.len() + SESSION_FRAME_TAG_LEN
+            );
+            if msg.len() > 16 {
+                assert!(
+                    &frame[SESSION_FRAME_HEADER_LEN..SESSION_FRAME_HEADER_LEN + msg.len()]
+                        != &msg[..]
+                );
+            }
+            stream.extend_from_slice(&frame);
+        }
+
+        let expected: Vec<u8> = messages.iter().flatten().cloned().collect();
+
+        // feed the stream in awkwardly-sized chunks
+        let mut plaintext = vec![];
+        for chunk in stream.chunks(7) {
+            plaintext.extend_from_slice(&reader.open(chunk).unwrap());
+        }
+        assert_eq!(plaintext, expected);
+        assert_eq!(reader.buf.len(), 0);
+    }
+
+    #[test]
+    fn test_session_frames_tampered() {
+        let (mut sender, mut reader) = make_session();
+        let mut frame = sender.seal_frame(b"hello world").unwrap();
+        frame[SESSION_FRAME_HEADER_LEN] ^= 0x01;
+        match reader.open(&frame) {
+            Err(net_error::VerifyingError(_)) => {}
+            x => panic!("Expected VerifyingError, got {:?}", &x),
+        }
+    }
+
+    #[test]
+    fn test_session_frames_replayed() {
+        let (mut sender, mut reader) = make_session();
+        let _frame_1 = sender.seal_frame(b"hello").unwrap();
+        let frame_2 = sender.seal_frame(b"world").unwrap();
+
+        // out of order
+        assert!(reader.open(&frame_2).is_err());
+
+        let (mut sender, mut reader) = make_session();
+        let frame_1 = sender.seal_frame(b"hello").unwrap();
+        assert_eq!(reader.open(&frame_1).unwrap(), b"hello".to_vec());
+
+        // replayed
+        assert!(reader.open(&frame_1).is_err());
+    }
+
+    #[test]
+    fn test_session_wrong_recipient() {
+        let recipient_private_key = Secp256k1PrivateKey::new();
+        let recipient_public_key = Secp256k1PublicKey::from_private(&recipient_private_key);
+        let (mut sender, ephemeral_public_key) =
+            SessionCipher::new_sender(&recipient_public_key).unwrap();
+
+        let other_private_key = Secp256k1PrivateKey::new();
+        let other =
+            SessionCipher::new_recipient(&other_private_key, &ephemeral_public_key).unwrap();
+        let mut reader = SessionFrameReader::new(other);
+
+        let frame = sender.seal_frame(b"hello world").unwrap();
+        assert!(reader.open(&frame).is_err());
+    }
+
+    #[test]
+    fn test_session_frame_too_big() {
+        let (mut sender, mut reader) = make_session();
+        assert!(sender
+            .seal_frame(&vec![0u8; SESSION_FRAME_MAX_LEN as usize + 1])
+            .is_err());
+
+        let mut frame = vec![];
+        frame.extend_from_slice(&(SESSION_FRAME_MAX_LEN + 1).to_be_bytes());
+        match reader.open(&frame) {
+            Err(net_error::DeserializeError(_)) => {}
+            x => panic!("Expected DeserializeError, got {:?}", &x),
+        }
+    }
+}
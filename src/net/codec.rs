@@ -714,6 +714,150 @@ impl StacksMessageCodec for NatPunchData {
     }
 }
 
+impl StacksMessageCodec for SessionKeyData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.ephemeral_public_key)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<SessionKeyData, codec_error> {
+        let ephemeral_public_key: StacksPublicKeyBuffer = read_next(fd)?;
+        Ok(SessionKeyData {
+            ephemeral_public_key,
+        })
+    }
+}
+
+impl StacksMessageCodec for PrefilledTransaction {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.index)?;
+        write_next(fd, &self.tx)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<PrefilledTransaction, codec_error> {
+        let index: u16 = read_next(fd)?;
+        let tx: StacksTransaction = read_next(fd)?;
+        Ok(PrefilledTransaction { index, tx })
+    }
+}
+
+/// Transaction positions in compact block messages must be strictly increasing, and must refer
+/// to transactions in a block with `num_txs` transactions.
+fn check_compact_block_indexes<I: Iterator<Item = u16>>(
+    indexes: I,
+    num_txs: usize,
+) -> Result<(), codec_error> {
+    let mut last_index: Option<u16> = None;
+    for index in indexes {
+        if (index as usize) >= num_txs {
+            return Err(codec_error::DeserializeError(format!(
+                "Invalid compact block transaction index {} (out of {})",
+                index, num_txs
+            )));
+        }
+        if let Some(last_index) = last_index {
+            if index <= last_index {
+                return Err(codec_error::DeserializeError(
+                    "Invalid compact block transaction indexes: not sorted".to_string(),
+                ));
+            }
+        }
+        last_index = Some(index);
+    }
+    Ok(())
+}
+
+impl StacksMessageCodec for CompactBlockData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.header)?;
+        write_next(fd, &self.seed)?;
+        write_next(fd, &self.tx_tags)?;
+        write_next(fd, &self.prefilled_txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<CompactBlockData, codec_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let header: StacksBlockHeader = read_next(fd)?;
+        let seed: [u8; 32] = read_next(fd)?;
+        let tx_tags: Vec<TxTag> = read_next_at_most(fd, COMPACT_BLOCK_MAX_TXS)?;
+        let prefilled_txs: Vec<PrefilledTransaction> = {
+            let mut bound_read = BoundReader::from_reader(fd, MAX_BLOCK_LEN as u64);
+            read_next_at_most(&mut bound_read, COMPACT_BLOCK_MAX_TXS)
+        }?;
+
+        // there's always at least the coinbase
+        if tx_tags.len() == 0 {
+            return Err(codec_error::DeserializeError(
+                "Invalid CompactBlockData: no transactions".to_string(),
+            ));
+        }
+        check_compact_block_indexes(prefilled_txs.iter().map(|ptx| ptx.index), tx_tags.len())?;
+
+        Ok(CompactBlockData {
+            consensus_hash,
+            header,
+            seed,
+            tx_tags,
+            prefilled_txs,
+        })
+    }
+}
+
+impl StacksMessageCodec for GetCompactBlockTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.block_hash)?;
+        write_next(fd, &self.indexes)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<GetCompactBlockTxsData, codec_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let block_hash: BlockHeaderHash = read_next(fd)?;
+        let indexes: Vec<u16> = read_next_at_most(fd, COMPACT_BLOCK_MAX_TXS)?;
+
+        if indexes.len() == 0 {
+            return Err(codec_error::DeserializeError(
+                "Invalid GetCompactBlockTxsData: no indexes".to_string(),
+            ));
+        }
+        check_compact_block_indexes(indexes.iter().map(|i| *i), COMPACT_BLOCK_MAX_TXS as usize)?;
+
+        Ok(GetCompactBlockTxsData {
+            consensus_hash,
+            block_hash,
+            indexes,
+        })
+    }
+}
+
+impl StacksMessageCodec for CompactBlockTxsData {
+    fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
+        write_next(fd, &self.consensus_hash)?;
+        write_next(fd, &self.block_hash)?;
+        write_next(fd, &self.txs)?;
+        Ok(())
+    }
+
+    fn consensus_deserialize<R: Read>(fd: &mut R) -> Result<CompactBlockTxsData, codec_error> {
+        let consensus_hash: ConsensusHash = read_next(fd)?;
+        let block_hash: BlockHeaderHash = read_next(fd)?;
+        let txs: Vec<StacksTransaction> = {
+            let mut bound_read = BoundReader::from_reader(fd, MAX_BLOCK_LEN as u64);
+            read_next_at_most(&mut bound_read, COMPACT_BLOCK_MAX_TXS)
+        }?;
+
+        Ok(CompactBlockTxsData {
+            consensus_hash,
+            block_hash,
+            txs,
+        })
+    }
+}
+
 impl StacksMessageCodec for MemPoolSyncData {
     fn consensus_serialize<W: Write>(&self, fd: &mut W) -> Result<(), codec_error> {
         match *self {
@@ -787,6 +931,10 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => StacksMessageID::Pong,
             StacksMessageType::NatPunchRequest(ref _m) => StacksMessageID::NatPunchRequest,
             StacksMessageType::NatPunchReply(ref _m) => StacksMessageID::NatPunchReply,
+            StacksMessageType::SessionKey(ref _m) => StacksMessageID::SessionKey,
+            StacksMessageType::CompactBlock(ref _m) => StacksMessageID::CompactBlock,
+            StacksMessageType::GetCompactBlockTxs(ref _m) => StacksMessageID::GetCompactBlockTxs,
+            StacksMessageType::CompactBlockTxs(ref _m) => StacksMessageID::CompactBlockTxs,
         }
     }
 
@@ -811,6 +959,10 @@ impl StacksMessageType {
             StacksMessageType::Pong(ref _m) => "Pong",
             StacksMessageType::NatPunchRequest(ref _m) => "NatPunchRequest",
             StacksMessageType::NatPunchReply(ref _m) => "NatPunchReply",
+            StacksMessageType::SessionKey(ref _m) => "SessionKey",
+            StacksMessageType::CompactBlock(ref _m) => "CompactBlock",
+            StacksMessageType::GetCompactBlockTxs(ref _m) => "GetCompactBlockTxs",
+            StacksMessageType::CompactBlockTxs(ref _m) => "CompactBlockTxs",
         }
     }
 
@@ -869,6 +1021,27 @@ impl StacksMessageType {
             StacksMessageType::NatPunchReply(ref m) => {
                 format!("NatPunchReply({},{}:{})", m.nonce, &m.addrbytes, m.port)
             }
+            StacksMessageType::SessionKey(ref m) => format!(
+                "SessionKey({})",
+                &to_hex(&m.ephemeral_public_key.to_bytes())
+            ),
+            StacksMessageType::CompactBlock(ref m) => format!(
+                "CompactBlock({}/{},{},{})",
+                &m.consensus_hash,
+                &m.header.block_hash(),
+                m.tx_tags.len(),
+                m.prefilled_txs.len()
+            ),
+            StacksMessageType::GetCompactBlockTxs(ref m) => format!(
+                "GetCompactBlockTxs({}/{},{:?})",
+                &m.consensus_hash, &m.block_hash, &m.indexes
+            ),
+            StacksMessageType::CompactBlockTxs(ref m) => format!(
+                "CompactBlockTxs({}/{},{:?})",
+                &m.consensus_hash,
+                &m.block_hash,
+                m.txs.iter().map(|tx| tx.txid()).collect::<Vec<Txid>>()
+            ),
         }
     }
 }
@@ -902,6 +1075,12 @@ impl StacksMessageCodec for StacksMessageID {
             x if x == StacksMessageID::Pong as u8 => StacksMessageID::Pong,
             x if x == StacksMessageID::NatPunchRequest as u8 => StacksMessageID::NatPunchRequest,
             x if x == StacksMessageID::NatPunchReply as u8 => StacksMessageID::NatPunchReply,
+            x if x == StacksMessageID::SessionKey as u8 => StacksMessageID::SessionKey,
+            x if x == StacksMessageID::CompactBlock as u8 => StacksMessageID::CompactBlock,
+            x if x == StacksMessageID::GetCompactBlockTxs as u8 => {
+                StacksMessageID::GetCompactBlockTxs
+            }
+            x if x == StacksMessageID::CompactBlockTxs as u8 => StacksMessageID::CompactBlockTxs,
             _ => {
                 return Err(codec_error::DeserializeError(
                     "Unknown message ID".to_string(),
@@ -935,6 +1114,10 @@ impl StacksMessageCodec for StacksMessageType {
             StacksMessageType::Pong(ref m) => write_next(fd, m)?,
             StacksMessageType::NatPunchRequest(ref nonce) => write_next(fd, nonce)?,
             StacksMessageType::NatPunchReply(ref m) => write_next(fd, m)?,
+            StacksMessageType::SessionKey(ref m) => write_next(fd, m)?,
+            StacksMessageType::CompactBlock(ref m) => write_next(fd, m)?,
+            StacksMessageType::GetCompactBlockTxs(ref m) => write_next(fd, m)?,
+            StacksMessageType::CompactBlockTxs(ref m) => write_next(fd, m)?,
         }
         Ok(())
     }
@@ -1012,6 +1195,22 @@ impl StacksMessageCodec for StacksMessageType {
                 let m: NatPunchData = read_next(fd)?;
                 StacksMessageType::NatPunchReply(m)
             }
+            StacksMessageID::SessionKey => {
+                let m: SessionKeyData = read_next(fd)?;
+                StacksMessageType::SessionKey(m)
+            }
+            StacksMessageID::CompactBlock => {
+                let m: CompactBlockData = read_next(fd)?;
+                StacksMessageType::CompactBlock(m)
+            }
+            StacksMessageID::GetCompactBlockTxs => {
+                let m: GetCompactBlockTxsData = read_next(fd)?;
+                StacksMessageType::GetCompactBlockTxs(m)
+            }
+            StacksMessageID::CompactBlockTxs => {
+                let m: CompactBlockTxsData = read_next(fd)?;
+                StacksMessageType::CompactBlockTxs(m)
+            }
             StacksMessageID::Reserved => {
                 return Err(codec_error::DeserializeError(
                     "Unsupported message ID 'reserved'".to_string(),
@@ -1285,10 +1484,21 @@ impl ProtocolFamily for StacksP2P {
     ) -> Result<(), net_error> {
         message.consensus_serialize(fd).map_err(|e| e.into())
     }
+
+    /// A SessionKey message switches its sender over to the encrypted transport
+    fn session_key(&self, message: &StacksMessage) -> Option<StacksPublicKey> {
+        match message.payload {
+            StacksMessageType::SessionKey(ref data) => {
+                data.ephemeral_public_key.to_public_key().ok()
+            }
+            _ => None,
+        }
+    }
 }
 
 #[cfg(test)]
 pub mod test {
+    use crate::chainstate::stacks::test::make_codec_test_block;
     use stacks_common::codec::NEIGHBOR_ADDRESS_ENCODED_SIZE;
     use stacks_common::util::hash::hex_bytes;
     use stacks_common::util::secp256k1::*;
@@ -1933,6 +2143,145 @@ pub mod test {
         check_codec_and_corruption::<NatPunchData>(&data, &bytes);
     }
 
+    #[test]
+    fn codec_SessionKeyData() {
+        let data = SessionKeyData {
+            ephemeral_public_key: StacksPublicKeyBuffer::from_bytes(
+                &hex_bytes("034e316be04870cef1795fba64d581cf64bad0c894b01a068fb9edf85321dcd9bb")
+                    .unwrap(),
+            )
+            .unwrap(),
+        };
+        let bytes = vec![
+            // ephemeral public key
+            0x03, 0x4e, 0x31, 0x6b, 0xe0, 0x48, 0x70, 0xce, 0xf1, 0x79, 0x5f, 0xba, 0x64, 0xd5,
+            0x81, 0xcf, 0x64, 0xba, 0xd0, 0xc8, 0x94, 0xb0, 0x1a, 0x06, 0x8f, 0xb9, 0xed, 0xf8,
+            0x53, 0x21, 0xdc, 0xd9, 0xbb,
+        ];
+
+        check_codec_and_corruption::<SessionKeyData>(&data, &bytes);
+    }
+
+    #[test]
+    fn codec_GetCompactBlockTxsData() {
+        let data = GetCompactBlockTxsData {
+            consensus_hash: ConsensusHash([0x11; 20]),
+            block_hash: BlockHeaderHash([0x22; 32]),
+            indexes: vec![1, 2, 0x0304],
+        };
+        let mut bytes = vec![];
+        // consensus hash
+        bytes.extend_from_slice(&[0x11; 20]);
+        // block hash
+        bytes.extend_from_slice(&[0x22; 32]);
+        // indexes
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x03, 0x00, 0x01, 0x00, 0x02, 0x03, 0x04]);
+
+        check_codec_and_corruption::<GetCompactBlockTxsData>(&data, &bytes);
+
+        // indexes must be sorted and unique
+        let mut unsorted = data.clone();
+        unsorted.indexes = vec![2, 1];
+        assert!(check_deserialize_failure::<GetCompactBlockTxsData>(
+            &unsorted
+        ));
+
+        let mut dup = data.clone();
+        dup.indexes = vec![1, 1];
+        assert!(check_deserialize_failure::<GetCompactBlockTxsData>(&dup));
+
+        // must ask for something
+        let mut empty = data.clone();
+        empty.indexes = vec![];
+        assert!(check_deserialize_failure::<GetCompactBlockTxsData>(&empty));
+    }
+
+    #[test]
+    fn codec_CompactBlockData() {
+        let block = make_codec_test_block(5);
+        let txids: Vec<Txid> = block.txs.iter().map(|tx| tx.txid()).collect();
+        let tx_tags: Vec<TxTag> = txids
+            .iter()
+            .map(|txid| TxTag::from(&[0x33; 32], txid))
+            .collect();
+        let prefilled_txs = vec![PrefilledTransaction {
+            index: 0,
+            tx: block.txs[0].clone(),
+        }];
+        let data = CompactBlockData {
+            consensus_hash: ConsensusHash([0x11; 20]),
+            header: block.header.clone(),
+            seed: [0x33; 32],
+            tx_tags: tx_tags.clone(),
+            prefilled_txs: prefilled_txs.clone(),
+        };
+
+        let mut bytes = vec![];
+        // consensus hash
+        bytes.extend_from_slice(&[0x11; 20]);
+        // header
+        bytes.append(&mut block.header.serialize_to_vec());
+        // seed
+        bytes.extend_from_slice(&[0x33; 32]);
+        // tags
+        bytes.extend_from_slice(&(tx_tags.len() as u32).to_be_bytes());
+        for tag in tx_tags.iter() {
+            bytes.extend_from_slice(&tag.0);
+        }
+        // prefilled transactions
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x01, 0x00, 0x00]);
+        bytes.append(&mut block.txs[0].serialize_to_vec());
+
+        check_codec_and_corruption::<CompactBlockData>(&data, &bytes);
+
+        // prefilled transactions have to be in the block
+        let mut out_of_range = data.clone();
+        out_of_range.prefilled_txs[0].index = tx_tags.len() as u16;
+        assert!(check_deserialize_failure::<CompactBlockData>(&out_of_range));
+
+        // ...in order
+        let mut unsorted = data.clone();
+        unsorted.prefilled_txs = vec![
+            PrefilledTransaction {
+                index: 1,
+                tx: block.txs[1].clone(),
+            },
+            PrefilledTransaction {
+                index: 0,
+                tx: block.txs[0].clone(),
+            },
+        ];
+        assert!(check_deserialize_failure::<CompactBlockData>(&unsorted));
+
+        // there has to be at least one transaction
+        let mut empty = data.clone();
+        empty.tx_tags = vec![];
+        empty.prefilled_txs = vec![];
+        assert!(check_deserialize_failure::<CompactBlockData>(&empty));
+    }
+
+    #[test]
+    fn codec_CompactBlockTxsData() {
+        let block = make_codec_test_block(5);
+        let data = CompactBlockTxsData {
+            consensus_hash: ConsensusHash([0x11; 20]),
+            block_hash: block.block_hash(),
+            txs: block.txs[1..3].to_vec(),
+        };
+
+        let mut bytes = vec![];
+        // consensus hash
+        bytes.extend_from_slice(&[0x11; 20]);
+        // block hash
+        bytes.extend_from_slice(&block.block_hash().0);
+        // transactions
+        bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x02]);
+        bytes.append(&mut block.txs[1].serialize_to_vec());
+        bytes.append(&mut block.txs[2].serialize_to_vec());
+
+        check_codec_and_corruption::<CompactBlockTxsData>(&data, &bytes);
+    }
+
     #[test]
     fn codec_StacksMessage() {
         let payloads: Vec<StacksMessageType> = vec![
@@ -2049,6 +2398,25 @@ pub mod test {
                 port: 12345,
                 nonce: 0x12345678,
             }),
+            StacksMessageType::SessionKey(SessionKeyData {
+                ephemeral_public_key: StacksPublicKeyBuffer::from_bytes(
+                    &hex_bytes(
+                        "034e316be04870cef1795fba64d581cf64bad0c894b01a068fb9edf85321dcd9bb",
+                    )
+                    .unwrap(),
+                )
+                .unwrap(),
+            }),
+            StacksMessageType::GetCompactBlockTxs(GetCompactBlockTxsData {
+                consensus_hash: ConsensusHash([0x11; 20]),
+                block_hash: BlockHeaderHash([0x22; 32]),
+                indexes: vec![1, 2, 3],
+            }),
+            StacksMessageType::CompactBlockTxs(CompactBlockTxsData {
+                consensus_hash: ConsensusHash([0x11; 20]),
+                block_hash: BlockHeaderHash([0x22; 32]),
+                txs: vec![],
+            }),
         ];
 
         let mut maximal_relayers: Vec<RelayData> = vec![];
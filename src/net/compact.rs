@@ -0,0 +1,660 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Compact block relay.
+//!
+//! Peers that both advertise `ServiceFlags::COMPACT_BLOCKS` push new anchored blocks to each
+//! other as `CompactBlock` messages instead of `Blocks` messages.  A compact block carries the
+//! block header and an 8-byte `TxTag` for each transaction, computed with a seed the sender
+//! picks for the block.  The sender also sends inline the transactions it expects the
+//! recipient won't have -- the coinbase, and anything that never made it into the sender's own
+//! mempool.
+//!
+//! The recipient fills in the remaining transactions from its mempool.  If any are missing, it
+//! asks the sender for them with a `GetCompactBlockTxs` message, and the sender replies with a
+//! `CompactBlockTxs` message.  Once the block is complete, it is handed to the relayer as if it
+//! had been pushed in full.  If the block can't be reconstructed (e.g. a tag matched the wrong
+//! transaction, or the sender never replied), the recipient treats the compact block as a
+//! `BlocksAvailable` announcement and downloads the block the usual way.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+
+use rand::thread_rng;
+use rand::Rng;
+
+use crate::burnchains::Txid;
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::stacks::{
+    StacksBlock, StacksBlockHeader, StacksTransaction, TransactionPayload,
+};
+use crate::codec::StacksMessageCodec;
+use crate::core::mempool::{MemPoolDB, TxTag};
+use crate::net::chat::ConversationP2P;
+use crate::net::p2p::PeerNetwork;
+use crate::net::Error as net_error;
+use crate::net::{
+    BlocksAvailableData, BlocksData, BlocksDatum, CompactBlockData, CompactBlockTxsData,
+    GetCompactBlockTxsData, NeighborKey, PrefilledTransaction, StacksMessage, StacksMessageType,
+    COMPACT_BLOCK_MAX_TXS,
+};
+use crate::types::chainstate::{BlockHeaderHash, StacksBlockId};
+use stacks_common::util::get_epoch_time_secs;
+
+impl CompactBlockData {
+    /// Make a compact block for a block we have.  The coinbase is always sent inline, as is
+    /// every transaction for which `is_known` returns false -- if a transaction never showed up
+    /// in our mempool, odds are it didn't show up in the recipient's either.
+    /// Returns None if the block has too many transactions to be sent this way.
+    pub fn from_block<F>(
+        consensus_hash: &ConsensusHash,
+        block: &StacksBlock,
+        mut is_known: F,
+    ) -> Option<CompactBlockData>
+    where
+        F: FnMut(&Txid) -> bool,
+    {
+        if block.txs.len() > COMPACT_BLOCK_MAX_TXS as usize {
+            return None;
+        }
+
+        let mut seed = [0u8; 32];
+        thread_rng().fill(&mut seed);
+
+        let mut tx_tags = Vec::with_capacity(block.txs.len());
+        let mut prefilled_txs = vec![];
+        for (i, tx) in block.txs.iter().enumerate() {
+            let txid = tx.txid();
+            tx_tags.push(TxTag::from(&seed, &txid));
+
+            let is_coinbase = match tx.payload {
+                TransactionPayload::Coinbase(..) => true,
+                _ => false,
+            };
+            if is_coinbase || !is_known(&txid) {
+                prefilled_txs.push(PrefilledTransaction {
+                    index: i as u16,
+                    tx: tx.clone(),
+                });
+            }
+        }
+
+        Some(CompactBlockData {
+            consensus_hash: consensus_hash.clone(),
+            header: block.header.clone(),
+            seed,
+            tx_tags,
+            prefilled_txs,
+        })
+    }
+
+    pub fn block_hash(&self) -> BlockHeaderHash {
+        self.header.block_hash()
+    }
+
+    pub fn index_block_hash(&self) -> StacksBlockId {
+        StacksBlockHeader::make_index_block_hash(&self.consensus_hash, &self.block_hash())
+    }
+}
+
+/// A compact block that we're reconstructing
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartialCompactBlock {
+    pub compact_block: CompactBlockData,
+    txs: Vec<Option<StacksTransaction>>,
+}
+
+impl PartialCompactBlock {
+    /// Fill in what we can of a compact block, using its prefilled transactions and `known_txs`
+    /// -- the transactions we have, keyed by their tags under the compact block's seed.
+    pub fn new(
+        compact_block: CompactBlockData,
+        known_txs: &HashMap<TxTag, StacksTransaction>,
+    ) -> PartialCompactBlock {
+        let mut txs: Vec<Option<StacksTransaction>> = compact_block
+            .tx_tags
+            .iter()
+            .map(|tag| known_txs.get(tag).cloned())
+            .collect();
+
+        for prefilled_tx in compact_block.prefilled_txs.iter() {
+            if let Some(slot) = txs.get_mut(prefilled_tx.index as usize) {
+                *slot = Some(prefilled_tx.tx.clone());
+            }
+        }
+
+        PartialCompactBlock { compact_block, txs }
+    }
+
+    /// Fill in what we can of a compact block from our mempool.
+    pub fn from_mempool(
+        compact_block: CompactBlockData,
+        mempool: &MemPoolDB,
+    ) -> Result<PartialCompactBlock, net_error> {
+        let tags: HashSet<TxTag> = compact_block.tx_tags.iter().cloned().collect();
+        let known_txs = mempool.find_txs_by_tags(&compact_block.seed, &tags)?;
+        Ok(PartialCompactBlock::new(compact_block, &known_txs))
+    }
+
+    /// Positions of the transactions we still need
+    pub fn missing_indexes(&self) -> Vec<u16> {
+        self.txs
+            .iter()
+            .enumerate()
+            .filter_map(|(i, tx_opt)| match tx_opt {
+                Some(_) => None,
+                None => Some(i as u16),
+            })
+            .collect()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.txs.iter().all(|tx_opt| tx_opt.is_some())
+    }
+
+    /// Fill in the transactions a peer sent us in reply to a GetCompactBlockTxs for `indexes`.
+    /// Fails if the peer sent the wrong number of transactions, or transactions whose tags
+    /// don't match the ones in the compact block.
+    pub fn fill(&mut self, indexes: &[u16], txs: Vec<StacksTransaction>) -> Result<(), net_error> {
+        if indexes.len() != txs.len() {
+            debug!(
+                "Asked for {} compact block transactions, but got {}",
+                indexes.len(),
+                txs.len()
+            );
+            return Err(net_error::InvalidMessage);
+        }
+
+        for (index, tx) in indexes.iter().zip(txs.into_iter()) {
+            let index = *index as usize;
+            let expected_tag = self
+                .compact_block
+                .tx_tags
+                .get(index)
+                .ok_or(net_error::InvalidMessage)?;
+
+            if TxTag::from(&self.compact_block.seed, &tx.txid()) != *expected_tag {
+                debug!(
+                    "Compact block transaction {} at index {} does not match tag {}",
+                    &tx.txid(),
+                    index,
+                    expected_tag
+                );
+                return Err(net_error::InvalidMessage);
+            }
+            self.txs[index] = Some(tx);
+        }
+        Ok(())
+    }
+
+    /// Assemble the block, and check it the same way we'd check a block that was pushed to us
+    /// in full.  Fails if any transaction is still missing, or if the block is invalid -- e.g.
+    /// because a tag matched the wrong transaction in our mempool.
+    pub fn into_block(self) -> Result<StacksBlock, net_error> {
+        let mut txs = Vec::with_capacity(self.txs.len());
+        for tx_opt in self.txs.into_iter() {
+            txs.push(tx_opt.ok_or(net_error::InvalidMessage)?);
+        }
+
+        let block = StacksBlock {
+            header: self.compact_block.header,
+            txs,
+        };
+
+        // re-runs all the well-formedness checks, including the tx Merkle root check
+        let block_bytes = block.serialize_to_vec();
+        StacksBlock::consensus_deserialize(&mut &block_bytes[..]).map_err(|e| {
+            net_error::DeserializeError(format!("Invalid reconstructed block: {:?}", &e))
+        })
+    }
+}
+
+/// A compact block whose sender we've asked for the transactions we're missing
+#[derive(Debug, Clone, PartialEq)]
+pub struct PendingCompactBlock {
+    pub partial_block: PartialCompactBlock,
+    /// which transactions we asked for
+    pub requested: Vec<u16>,
+    /// the CompactBlock message this block arrived in
+    pub message: StacksMessage,
+    /// when we asked
+    pub request_time: u64,
+}
+
+impl PeerNetwork {
+    /// Can we push compact blocks to this peer?  We can if we both have the COMPACT_BLOCKS bit
+    /// set.
+    pub fn peer_supports_compact_blocks(&self, neighbor_key: &NeighborKey) -> bool {
+        match self.get_convo(neighbor_key) {
+            Some(convo) => ConversationP2P::supports_compact_blocks(
+                self.local_peer.services,
+                convo.peer_services,
+            ),
+            None => false,
+        }
+    }
+
+    /// Make a message that looks like it came in the same way as `message`, but carries
+    /// `payload` instead.  The relayer only looks at the payload and who sent it.
+    fn rewrite_compact_block_message(
+        mut message: StacksMessage,
+        payload: StacksMessageType,
+    ) -> StacksMessage {
+        message.payload = payload;
+        message
+    }
+
+    /// We couldn't reconstruct a compact block.  Treat it as a BlocksAvailable from the peer
+    /// that sent it, so we'll go and download the whole block instead.
+    fn compact_block_fallback(
+        &self,
+        sortdb: &SortitionDB,
+        message: StacksMessage,
+        consensus_hash: &ConsensusHash,
+    ) -> Option<StacksMessage> {
+        match SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash) {
+            Ok(Some(sn)) => {
+                let available = BlocksAvailableData {
+                    available: vec![(consensus_hash.clone(), sn.burn_header_hash)],
+                };
+                Some(PeerNetwork::rewrite_compact_block_message(
+                    message,
+                    StacksMessageType::BlocksAvailable(available),
+                ))
+            }
+            Ok(None) => {
+                debug!(
+                    "{:?}: Drop compact block from {} -- consensus hash not recognized",
+                    &self.local_peer, consensus_hash
+                );
+                None
+            }
+            Err(e) => {
+                warn!(
+                    "{:?}: Failed to query block snapshot for {}: {:?}",
+                    &self.local_peer, consensus_hash, &e
+                );
+                None
+            }
+        }
+    }
+
+    /// Turn a complete compact block into a Blocks message, or fall back to a download if it
+    /// turns out to be invalid.
+    fn finish_compact_block(
+        &self,
+        sortdb: &SortitionDB,
+        message: StacksMessage,
+        partial_block: PartialCompactBlock,
+    ) -> Option<StacksMessage> {
+        let consensus_hash = partial_block.compact_block.consensus_hash.clone();
+        let block_hash = partial_block.compact_block.block_hash();
+        match partial_block.into_block() {
+            Ok(block) => {
+                debug!(
+                    "{:?}: Reconstructed compact block {}/{}",
+                    &self.local_peer, &consensus_hash, &block_hash
+                );
+                let blocks_data = BlocksData {
+                    blocks: vec![BlocksDatum(consensus_hash, block)],
+                };
+                Some(PeerNetwork::rewrite_compact_block_message(
+                    message,
+                    StacksMessageType::Blocks(blocks_data),
+                ))
+            }
+            Err(e) => {
+                info!(
+                    "{:?}: Failed to reconstruct compact block {}/{}: {:?}",
+                    &self.local_peer, &consensus_hash, &block_hash, &e
+                );
+                self.compact_block_fallback(sortdb, message, &consensus_hash)
+            }
+        }
+    }
+
+    /// Handle a CompactBlock pushed to us.  Returns the message to pass along in its place, if
+    /// any -- a Blocks message if we could reconstruct the block right away from our mempool.
+    /// Otherwise, ask the sender for the missing transactions.
+    fn handle_compact_block(
+        &mut self,
+        sortdb: &SortitionDB,
+        mempool: &MemPoolDB,
+        neighbor_key: &NeighborKey,
+        message: StacksMessage,
+        compact_block: CompactBlockData,
+    ) -> Option<StacksMessage> {
+        let consensus_hash = compact_block.consensus_hash.clone();
+        let block_hash = compact_block.block_hash();
+        let index_block_hash = compact_block.index_block_hash();
+
+        let partial_block = match PartialCompactBlock::from_mempool(compact_block, mempool) {
+            Ok(partial_block) => partial_block,
+            Err(e) => {
+                warn!(
+                    "{:?}: Failed to look up transactions for compact block {}/{}: {:?}",
+                    &self.local_peer, &consensus_hash, &block_hash, &e
+                );
+                return self.compact_block_fallback(sortdb, message, &consensus_hash);
+            }
+        };
+
+        if partial_block.is_complete() {
+            return self.finish_compact_block(sortdb, message, partial_block);
+        }
+
+        let missing = partial_block.missing_indexes();
+        debug!(
+            "{:?}: Ask {:?} for {} missing transaction(s) in compact block {}/{}",
+            &self.local_peer,
+            neighbor_key,
+            missing.len(),
+            &consensus_hash,
+            &block_hash
+        );
+
+        let request = GetCompactBlockTxsData {
+            consensus_hash: consensus_hash.clone(),
+            block_hash: block_hash.clone(),
+            indexes: missing.clone(),
+        };
+        let send_res = self
+            .sign_for_peer(neighbor_key, StacksMessageType::GetCompactBlockTxs(request))
+            .and_then(|request_msg| self.relay_signed_message(neighbor_key, request_msg));
+
+        match send_res {
+            Ok(_) => {
+                self.pending_compact_blocks.insert(
+                    (neighbor_key.clone(), index_block_hash),
+                    PendingCompactBlock {
+                        partial_block,
+                        requested: missing,
+                        message,
+                        request_time: get_epoch_time_secs(),
+                    },
+                );
+                None
+            }
+            Err(e) => {
+                debug!(
+                    "{:?}: Failed to ask {:?} for compact block transactions: {:?}",
+                    &self.local_peer, neighbor_key, &e
+                );
+                self.compact_block_fallback(sortdb, message, &consensus_hash)
+            }
+        }
+    }
+
+    /// Handle the reply to a GetCompactBlockTxs we sent.  Returns the message to pass along in
+    /// its place, if any.
+    fn handle_compact_block_txs(
+        &mut self,
+        sortdb: &SortitionDB,
+        neighbor_key: &NeighborKey,
+        block_txs: CompactBlockTxsData,
+    ) -> Option<StacksMessage> {
+        let index_block_hash = StacksBlockHeader::make_index_block_hash(
+            &block_txs.consensus_hash,
+            &block_txs.block_hash,
+        );
+
+        let mut pending = match self
+            .pending_compact_blocks
+            .remove(&(neighbor_key.clone(), index_block_hash))
+        {
+            Some(pending) => pending,
+            None => {
+                debug!(
+                    "{:?}: Drop unexpected compact block transactions for {}/{} from {:?}",
+                    &self.local_peer,
+                    &block_txs.consensus_hash,
+                    &block_txs.block_hash,
+                    neighbor_key
+                );
+                return None;
+            }
+        };
+
+        if let Err(e) = pending
+            .partial_block
+            .fill(&pending.requested, block_txs.txs)
+        {
+            info!(
+                "{:?}: Invalid compact block transactions for {}/{} from {:?}: {:?}",
+                &self.local_peer,
+                &block_txs.consensus_hash,
+                &block_txs.block_hash,
+                neighbor_key,
+                &e
+            );
+            return self.compact_block_fallback(sortdb, pending.message, &block_txs.consensus_hash);
+        }
+
+        self.finish_compact_block(sortdb, pending.message, pending.partial_block)
+    }
+
+    /// Give up on compact blocks whose senders haven't sent us the missing transactions in
+    /// time, and fall back to downloading them.  Returns the fallback messages, keyed by event.
+    fn expire_pending_compact_blocks(
+        &mut self,
+        sortdb: &SortitionDB,
+    ) -> Vec<(usize, StacksMessage)> {
+        let now = get_epoch_time_secs();
+        let expired: Vec<(NeighborKey, StacksBlockId)> = self
+            .pending_compact_blocks
+            .iter()
+            .filter_map(|(key, pending)| {
+                if pending.request_time + self.connection_opts.timeout < now {
+                    Some(key.clone())
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        let mut ret = vec![];
+        for key in expired.into_iter() {
+            let pending = match self.pending_compact_blocks.remove(&key) {
+                Some(pending) => pending,
+                None => {
+                    continue;
+                }
+            };
+            let (neighbor_key, index_block_hash) = key;
+            debug!(
+                "{:?}: Timed out waiting for {:?} to send transactions for compact block {}",
+                &self.local_peer, &neighbor_key, &index_block_hash
+            );
+
+            let event_id = match self.get_event_id(&neighbor_key) {
+                Some(event_id) => event_id,
+                None => {
+                    continue;
+                }
+            };
+            let consensus_hash = pending.partial_block.compact_block.consensus_hash.clone();
+            if let Some(message) =
+                self.compact_block_fallback(sortdb, pending.message, &consensus_hash)
+            {
+                ret.push((event_id, message));
+            }
+        }
+        ret
+    }
+
+    /// Reconstruct the compact blocks pushed to us.  CompactBlock and CompactBlockTxs messages
+    /// are replaced by Blocks messages for the blocks we could reconstruct, and by
+    /// BlocksAvailable messages for the ones we couldn't, so the rest of the unsolicited
+    /// message handling can treat them like any other block push or announcement.
+    /// Messages for blocks that are still missing transactions are held back until the
+    /// sender replies.
+    pub fn handle_unsolicited_compact_blocks(
+        &mut self,
+        sortdb: &SortitionDB,
+        mempool: &MemPoolDB,
+        unsolicited: HashMap<usize, Vec<StacksMessage>>,
+    ) -> HashMap<usize, Vec<StacksMessage>> {
+        let mut ret: HashMap<usize, Vec<StacksMessage>> = HashMap::new();
+        for (event_id, message) in self.expire_pending_compact_blocks(sortdb).into_iter() {
+            ret.entry(event_id).or_insert_with(Vec::new).push(message);
+        }
+
+        for (event_id, messages) in unsolicited.into_iter() {
+            let neighbor_key = match self.peers.get(&event_id) {
+                Some(convo) => convo.to_neighbor_key(),
+                None => {
+                    // will be dropped later
+                    ret.entry(event_id)
+                        .or_insert_with(Vec::new)
+                        .extend(messages);
+                    continue;
+                }
+            };
+
+            for message in messages.into_iter() {
+                let message_opt = match message.payload {
+                    StacksMessageType::CompactBlock(ref compact_block) => {
+                        let compact_block = compact_block.clone();
+                        self.handle_compact_block(
+                            sortdb,
+                            mempool,
+                            &neighbor_key,
+                            message,
+                            compact_block,
+                        )
+                    }
+                    StacksMessageType::CompactBlockTxs(block_txs) => {
+                        self.handle_compact_block_txs(sortdb, &neighbor_key, block_txs)
+                    }
+                    _ => Some(message),
+                };
+
+                if let Some(message) = message_opt {
+                    ret.entry(event_id).or_insert_with(Vec::new).push(message);
+                }
+            }
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chainstate::stacks::test::make_codec_test_block;
+
+    fn make_compact_block(block: &StacksBlock, known: &[usize]) -> CompactBlockData {
+        let known_txids: HashSet<Txid> = known.iter().map(|i| block.txs[*i].txid()).collect();
+        CompactBlockData::from_block(&ConsensusHash([0x11; 20]), block, |txid| {
+            known_txids.contains(txid)
+        })
+        .unwrap()
+    }
+
+    fn tx_table(
+        compact_block: &CompactBlockData,
+        txs: &[StacksTransaction],
+    ) -> HashMap<TxTag, StacksTransaction> {
+        txs.iter()
+            .map(|tx| (TxTag::from(&compact_block.seed, &tx.txid()), tx.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn test_compact_block_prefills_coinbase_and_unknown_txs() {
+        let block = make_codec_test_block(10);
+        assert!(block.txs.len() > 3);
+
+        let known: Vec<usize> = (1..block.txs.len() - 1).collect();
+        let compact_block = make_compact_block(&block, &known);
+
+        assert_eq!(compact_block.tx_tags.len(), block.txs.len());
+        assert_eq!(compact_block.block_hash(), block.block_hash());
+
+        let prefilled: Vec<u16> = compact_block
+            .prefilled_txs
+            .iter()
+            .map(|ptx| ptx.index)
+            .collect();
+        assert_eq!(prefilled, vec![0, (block.txs.len() - 1) as u16]);
+    }
+
+    #[test]
+    fn test_compact_block_reconstruct_from_known_txs() {
+        let block = make_codec_test_block(10);
+        let known: Vec<usize> = (1..block.txs.len()).collect();
+        let compact_block = make_compact_block(&block, &known);
+
+        let known_txs = tx_table(&compact_block, &block.txs[1..]);
+        let partial_block = PartialCompactBlock::new(compact_block, &known_txs);
+        assert!(partial_block.is_complete());
+        assert_eq!(partial_block.into_block().unwrap(), block);
+    }
+
+    #[test]
+    fn test_compact_block_reconstruct_with_missing_txs() {
+        let block = make_codec_test_block(10);
+        let known: Vec<usize> = (1..block.txs.len()).collect();
+        let compact_block = make_compact_block(&block, &known);
+
+        // we only have the first half of what the sender expected us to have
+        let half = block.txs.len() / 2;
+        let known_txs = tx_table(&compact_block, &block.txs[1..half]);
+        let mut partial_block = PartialCompactBlock::new(compact_block, &known_txs);
+        assert!(!partial_block.is_complete());
+
+        let missing = partial_block.missing_indexes();
+        let expected_missing: Vec<u16> = (half..block.txs.len()).map(|i| i as u16).collect();
+        assert_eq!(missing, expected_missing);
+        assert!(partial_block.clone().into_block().is_err());
+
+        // wrong number of transactions
+        assert!(partial_block
+            .clone()
+            .fill(&missing, block.txs[half..block.txs.len() - 1].to_vec())
+            .is_err());
+
+        // wrong transactions
+        let mut wrong_txs = block.txs[half..].to_vec();
+        wrong_txs.reverse();
+        assert!(partial_block.clone().fill(&missing, wrong_txs).is_err());
+
+        partial_block
+            .fill(&missing, block.txs[half..].to_vec())
+            .unwrap();
+        assert!(partial_block.is_complete());
+        assert_eq!(partial_block.into_block().unwrap(), block);
+    }
+
+    #[test]
+    fn test_compact_block_reconstruct_wrong_tx() {
+        let block = make_codec_test_block(10);
+        let known: Vec<usize> = (1..block.txs.len()).collect();
+        let compact_block = make_compact_block(&block, &known);
+
+        // a tag that collides with the wrong transaction yields an invalid block
+        let mut known_txs = tx_table(&compact_block, &block.txs[1..]);
+        let tag_1 = compact_block.tx_tags[1].clone();
+        let tag_2 = compact_block.tx_tags[2].clone();
+        let tx_2 = known_txs.get(&tag_2).unwrap().clone();
+        known_txs.insert(tag_1, tx_2);
+
+        let partial_block = PartialCompactBlock::new(compact_block, &known_txs);
+        assert!(partial_block.is_complete());
+        assert!(partial_block.into_block().is_err());
+    }
+}
@@ -18,6 +18,7 @@ use std::collections::VecDeque;
 use std::convert::TryFrom;
 use std::io;
 use std::io::{Read, Write};
+use std::mem;
 use std::net;
 use std::ops::Deref;
 use std::ops::DerefMut;
@@ -37,6 +38,7 @@ use mio::net as mio_net;
 use crate::codec::StacksMessageCodec;
 use crate::codec::MAX_MESSAGE_LEN;
 use crate::core::mempool::MAX_BLOOM_COUNTER_TXS;
+use crate::net::cipher::{SessionCipher, SessionFrameReader};
 use crate::net::codec::*;
 use crate::net::Error as net_error;
 use crate::net::HttpRequestPreamble;
@@ -64,7 +66,7 @@ use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::to_hex;
 use stacks_common::util::log;
 use stacks_common::util::pipe::*;
-use stacks_common::util::secp256k1::Secp256k1PublicKey;
+use stacks_common::util::secp256k1::{Secp256k1PrivateKey, Secp256k1PublicKey};
 use stacks_common::util::sleep_ms;
 
 use crate::monitoring::{update_inbound_bandwidth, update_outbound_bandwidth};
@@ -286,6 +288,8 @@ impl<P: ProtocolFamily> Write for NetworkReplyHandle<P> {
 struct InflightMessage<P: ProtocolFamily> {
     pipe_read: Option<PipeRead>,
     notify: Option<ReceiverNotify<P>>,
+    // if set, then everything sent after this message gets encrypted with this cipher
+    session_cipher: Option<SessionCipher>,
 }
 
 #[derive(Debug)]
@@ -301,6 +305,16 @@ struct ConnectionInbox<P: ProtocolFamily> {
     buf: Vec<u8>,
     message_ptr: usize, // index into buf where the message begins
     payload_ptr: usize, // for payloads of unknown length, this points to where to read next
+
+    // encrypted transport (see net::cipher).
+    // our node private key, which the remote peer encrypts its side of the conversation to
+    local_private_key: Option<Secp256k1PrivateKey>,
+    // the remote peer's ephemeral key, once it has switched to the encrypted transport
+    session_key: Option<Secp256k1PublicKey>,
+    // decrypts the remote peer's frames, once we know both keys
+    frame_reader: Option<SessionFrameReader>,
+    // ciphertext received before we knew our private key
+    pending_ciphertext: Vec<u8>,
 }
 
 #[derive(Debug)]
@@ -315,6 +329,9 @@ struct ConnectionOutbox<P: ProtocolFamily> {
 
     // in-flight messages
     inflight: VecDeque<ReceiverNotify<P>>,
+
+    // encrypts everything we send once our session key message has been sent
+    session_cipher: Option<SessionCipher>,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -380,6 +397,12 @@ pub struct ConnectionOptions {
     pub mempool_max_tx_query: u64,
     /// how long a mempool sync is allowed to take, in total, before timing out
     pub mempool_sync_timeout: u64,
+    /// whether or not to advertise (and use) the encrypted p2p transport
+    pub p2p_encryption: bool,
+    /// whether or not to advertise (and use) compact block relay
+    pub compact_block_relay: bool,
+    /// whether or not to fetch and validate peers' header chains before downloading blocks
+    pub headers_first_sync: bool,
 
     // fault injection
     pub disable_neighbor_walk: bool,
@@ -466,6 +489,9 @@ impl std::default::Default for ConnectionOptions {
             mempool_sync_interval: 30, // number of seconds in-between mempool sync
             mempool_max_tx_query: 128, // maximum number of transactions to visit per mempool query
             mempool_sync_timeout: 180, // how long a mempool sync can go for (3 minutes)
+            p2p_encryption: false,     // only talk plaintext by default
+            compact_block_relay: false, // only push whole blocks by default
+            headers_first_sync: false, // download blocks straight from block inventories by default
 
             // no faults on by default
             disable_neighbor_walk: false,
@@ -508,6 +534,10 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
             buf: vec![],
             message_ptr: 0,
             payload_ptr: 0,
+            local_private_key: None,
+            session_key: None,
+            frame_reader: None,
+            pending_ciphertext: vec![],
         }
     }
 
@@ -765,16 +795,54 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
         }
     }
 
-    /// Consume messages while we have space in our inbox.
+    /// Determine whether or not a message we just received switches the remote peer over to the
+    /// encrypted transport.  This can only happen once, and only once we know the peer's public
+    /// key (so the message that carries the session key was authenticated).
+    fn check_session_key(&mut self, protocol: &P, message: &P::Message) -> Result<bool, net_error> {
+        let session_key = match protocol.session_key(message) {
+            Some(session_key) => session_key,
+            None => {
+                return Ok(false);
+            }
+        };
+
+        if self.session_key.is_some() {
+            debug!("Remote peer sent a session key after it had already sent one");
+            return Err(net_error::InvalidMessage);
+        }
+        if self.public_key.is_none() {
+            debug!("Remote peer sent a session key before it was authenticated");
+            return Err(net_error::InvalidMessage);
+        }
+
+        test_debug!("Remote peer switched to session key {:?}", &session_key);
+        self.session_key = Some(session_key);
+        Ok(true)
+    }
+
+    /// Once the remote peer switches to the encrypted transport, everything it sent after the
+    /// switching message is ciphertext -- including anything we had already buffered.
+    fn take_ciphertext(&mut self, rest: &[u8]) -> Vec<u8> {
+        let mut ciphertext = mem::replace(&mut self.buf, vec![]);
+        ciphertext.extend_from_slice(rest);
+
+        self.preamble = None;
+        self.message_ptr = 0;
+        self.payload_ptr = 0;
+        ciphertext
+    }
+
+    /// Consume plaintext messages while we have space in our inbox.
     /// It is possible for this method to append more messages to the inbox than inbox_maxsize.
     /// However, since only so many messages can fit into buf, the number of messages that can be
     /// inserted into the inbox beyond inbox_maxsize is still limited.  Subsequent calls to
     /// recv_bytes() will prevent more data from being read from the socket until the messages are
     /// dequeued.
     ///
-    /// Returns nothing on success, and enqueues zero or more messages into our inbox.
+    /// Returns the bytes that follow a message that switched the remote peer to the encrypted
+    /// transport (if any), and enqueues zero or more messages into our inbox.
     /// Returns net_error::InvalidMessage if a message could not be parsed or authenticated.
-    fn consume_messages(&mut self, protocol: &mut P, buf: &[u8]) -> Result<(), net_error> {
+    fn consume_plaintext(&mut self, protocol: &mut P, buf: &[u8]) -> Result<Vec<u8>, net_error> {
         let mut offset = 0;
         loop {
             if self.inbox.len() > self.inbox_maxlen {
@@ -803,6 +871,7 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
             }
 
             let mut consumed_message = false;
+            let mut switched_session = false;
             let bytes_consumed_message = {
                 let mut preamble_opt = self.preamble.take();
                 let bytes_consumed = if let Some(ref mut preamble) = preamble_opt {
@@ -817,6 +886,7 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
                                 message.request_id(),
                                 bytes_consumed
                             );
+                            switched_session = self.check_session_key(protocol, &message)?;
                             self.inbox.push_back(message);
                             consumed_message = true;
                         }
@@ -837,6 +907,9 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
             }
 
             offset += bytes_consumed_message;
+            if switched_session {
+                return Ok(self.take_ciphertext(&buf[offset..]));
+            }
             if offset == buf.len() {
                 break;
             }
@@ -847,6 +920,7 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
         if self.buf.len() > 0 {
             loop {
                 let mut consumed_message = false;
+                let mut switched_session = false;
 
                 if self.preamble.is_none() {
                     let (preamble_opt, _bytes_consumed) = self.consume_preamble(protocol, &[])?;
@@ -868,6 +942,7 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
                             Some(message) => {
                                 // queue up
                                 test_debug!("Consumed buffered message '{}' (request {}) from {} input buffer bytes", message.get_message_name(), message.request_id(), _bytes_consumed);
+                                switched_session = self.check_session_key(protocol, &message)?;
                                 self.inbox.push_back(message);
                                 consumed_message = true;
                             }
@@ -882,6 +957,10 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
                     }
                 }
 
+                if switched_session {
+                    return Ok(self.take_ciphertext(&[]));
+                }
+
                 if !consumed_message {
                     // nothing more to do
                     break;
@@ -889,6 +968,63 @@ impl<P: ProtocolFamily> ConnectionInbox<P> {
             }
         }
 
+        Ok(vec![])
+    }
+
+    /// Consume ciphertext from a remote peer that has switched to the encrypted transport.
+    /// Ciphertext is buffered until we know the private key to decrypt it with.
+    fn consume_ciphertext(&mut self, protocol: &mut P, buf: &[u8]) -> Result<(), net_error> {
+        if self.frame_reader.is_none() {
+            let session_key = self
+                .session_key
+                .as_ref()
+                .expect("BUG: consuming ciphertext without a session key");
+
+            let cipher = match self.local_private_key {
+                Some(ref privk) => SessionCipher::new_recipient(privk, session_key)?,
+                None => {
+                    // can't decrypt yet
+                    if self.pending_ciphertext.len() + buf.len() > MAX_MESSAGE_LEN as usize {
+                        return Err(net_error::InboxOverflow);
+                    }
+                    self.pending_ciphertext.extend_from_slice(buf);
+                    return Ok(());
+                }
+            };
+            self.frame_reader = Some(SessionFrameReader::new(cipher));
+        }
+
+        let mut ciphertext = mem::replace(&mut self.pending_ciphertext, vec![]);
+        ciphertext.extend_from_slice(buf);
+
+        let plaintext = self
+            .frame_reader
+            .as_mut()
+            .expect("BUG: no session frame reader")
+            .open(&ciphertext)?;
+
+        if plaintext.len() > 0 {
+            // the remote peer can't switch keys again (check_session_key() rejects this), so
+            // there will never be any leftover ciphertext.
+            self.consume_plaintext(protocol, &plaintext)?;
+        }
+        Ok(())
+    }
+
+    /// Consume messages from the remote peer, decrypting them first if it has switched to the
+    /// encrypted transport.
+    ///
+    /// Returns nothing on success, and enqueues zero or more messages into our inbox.
+    /// Returns net_error::InvalidMessage if a message could not be parsed or authenticated.
+    fn consume_messages(&mut self, protocol: &mut P, buf: &[u8]) -> Result<(), net_error> {
+        if self.session_key.is_some() {
+            return self.consume_ciphertext(protocol, buf);
+        }
+
+        let ciphertext = self.consume_plaintext(protocol, buf)?;
+        if self.session_key.is_some() {
+            self.consume_ciphertext(protocol, &ciphertext)?;
+        }
         Ok(())
     }
 
@@ -977,6 +1113,7 @@ impl<P: ProtocolFamily> ConnectionOutbox<P> {
             socket_out_buf: vec![],
             socket_out_ptr: 0,
             inflight: VecDeque::new(),
+            session_cipher: None,
         }
     }
 
@@ -1010,14 +1147,29 @@ impl<P: ProtocolFamily> ConnectionOutbox<P> {
                 if receiver_notify.notify.is_some() {
                     self.inflight.push_back(receiver_notify.notify.unwrap());
                 }
+                if receiver_notify.session_cipher.is_some() {
+                    // our session key is out, so encrypt everything from here on
+                    test_debug!("Connection switched to the encrypted transport");
+                    self.session_cipher = receiver_notify.session_cipher;
+                }
             }
         }
     }
 
+    /// Have we switched to the encrypted transport, or queued the message that does so?
+    fn has_session(&self) -> bool {
+        self.session_cipher.is_some()
+            || self
+                .outbox
+                .iter()
+                .any(|inflight| inflight.session_cipher.is_some())
+    }
+
     fn queue_message(
         &mut self,
         pipe_read: PipeRead,
         recv_notify: Option<ReceiverNotify<P>>,
+        session_cipher: Option<SessionCipher>,
     ) -> Result<(), net_error> {
         if self.outbox.len() > self.outbox_maxlen {
             test_debug!(
@@ -1031,6 +1183,7 @@ impl<P: ProtocolFamily> ConnectionOutbox<P> {
         let inflight = InflightMessage {
             pipe_read: Some(pipe_read),
             notify: recv_notify,
+            session_cipher: session_cipher,
         };
         self.outbox.push_back(inflight);
         Ok(())
@@ -1091,7 +1244,17 @@ impl<P: ProtocolFamily> ConnectionOutbox<P> {
                         },
                     };
 
-                    self.socket_out_buf.extend_from_slice(&buf[0..nr_input]);
+                    if nr_input > 0 {
+                        match self.session_cipher {
+                            Some(ref mut cipher) => {
+                                let frame = cipher.seal_frame(&buf[0..nr_input])?;
+                                self.socket_out_buf.extend_from_slice(&frame);
+                            }
+                            None => {
+                                self.socket_out_buf.extend_from_slice(&buf[0..nr_input]);
+                            }
+                        }
+                    }
 
                     test_debug!(
                         "Connection buffered {} bytes from pipe ({} total, ptr = {}, blocked = {})",
@@ -1312,7 +1475,8 @@ impl<P: ProtocolFamily + Clone> NetworkConnection<P> {
         let mut recv_handle = NetworkReplyHandle::new(recv_ch, pipe_write, socket_event_id);
         recv_handle.set_deadline(timeout + get_epoch_time_secs());
 
-        self.outbox.queue_message(pipe_read, Some(recv_notify))?;
+        self.outbox
+            .queue_message(pipe_read, Some(recv_notify), None)?;
         Ok(recv_handle)
     }
 
@@ -1323,12 +1487,61 @@ impl<P: ProtocolFamily + Clone> NetworkConnection<P> {
         socket_event_id: usize,
     ) -> Result<NetworkReplyHandle<P>, net_error> {
         let (pipe_read, pipe_write) = Pipe::new();
-        self.outbox.queue_message(pipe_read, None)?;
+        self.outbox.queue_message(pipe_read, None, None)?;
 
         let send_handle = NetworkReplyHandle::new_relay(pipe_write, socket_event_id);
         Ok(send_handle)
     }
 
+    /// Forward a message that switches our side of the conversation to the encrypted transport,
+    /// and expect no reply.  Everything sent after it gets encrypted to the remote peer's public
+    /// key, which must be known.
+    /// Returns a Write-able handle into which the message should be written, and flushed, as well
+    /// as the ephemeral public key that the message must carry.
+    pub fn make_session_key_handle(
+        &mut self,
+        socket_event_id: usize,
+    ) -> Result<(NetworkReplyHandle<P>, Secp256k1PublicKey), net_error> {
+        if self.outbox.has_session() {
+            return Err(net_error::InProgress);
+        }
+        let recipient_public_key = self
+            .inbox
+            .public_key
+            .as_ref()
+            .ok_or(net_error::InvalidHandshake)?;
+
+        let (cipher, ephemeral_public_key) = SessionCipher::new_sender(recipient_public_key)?;
+
+        let (pipe_read, pipe_write) = Pipe::new();
+        self.outbox.queue_message(pipe_read, None, Some(cipher))?;
+
+        let send_handle = NetworkReplyHandle::new_relay(pipe_write, socket_event_id);
+        Ok((send_handle, ephemeral_public_key))
+    }
+
+    /// Set our private key, which the remote peer encrypts its side of the conversation to if
+    /// it switches to the encrypted transport.  Any ciphertext that arrived before we knew the
+    /// key gets decrypted now.
+    pub fn set_session_private_key(&mut self, privk: Secp256k1PrivateKey) -> Result<(), net_error> {
+        self.inbox.local_private_key = Some(privk);
+        if self.inbox.session_key.is_some() && self.inbox.frame_reader.is_none() {
+            self.inbox.consume_ciphertext(&mut self.protocol, &[])?;
+        }
+        Ok(())
+    }
+
+    /// Have we switched (or begun to switch) our side of the conversation to the encrypted
+    /// transport?
+    pub fn is_encrypting(&self) -> bool {
+        self.outbox.has_session()
+    }
+
+    /// Has the remote peer switched its side of the conversation to the encrypted transport?
+    pub fn is_decrypting(&self) -> bool {
+        self.inbox.session_key.is_some()
+    }
+
     /// Send data
     pub fn send_data<W: Write>(&mut self, fd: &mut W) -> Result<usize, net_error> {
         self.outbox.send_bytes(fd)
@@ -1413,6 +1626,7 @@ mod test {
 
     use crate::chainstate::stacks::test::make_codec_test_block;
     use crate::net::http::*;
+    use crate::types::StacksPublicKeyBuffer;
 
     use crate::util_lib::test::*;
 
@@ -2019,6 +2233,199 @@ mod test {
         pinger.join().unwrap();
     }
 
+    /// Have `sender` send a ping, switch to the encrypted transport, and send three more pings.
+    /// Returns the messages sent (in order) and the bytes that went out on the wire.
+    fn send_session_pings(
+        sender: &mut ConnectionP2P,
+        sender_privkey: &Secp256k1PrivateKey,
+    ) -> (Vec<StacksMessage>, Vec<u8>) {
+        let make_message = |seq: u32, payload: StacksMessageType| {
+            let mut msg = StacksMessage::new(
+                0x12345678,
+                0x9abcdef0,
+                12345,
+                &BurnchainHeaderHash([0x11; 32]),
+                12339,
+                &BurnchainHeaderHash([0x22; 32]),
+                payload,
+            );
+            msg.sign(seq, sender_privkey).unwrap();
+            msg
+        };
+
+        let mut msgs = vec![];
+        let mut pipes = vec![];
+
+        let ping = make_message(0, StacksMessageType::Ping(PingData { nonce: 0 }));
+        let mut pipe = sender.make_relay_handle(0).unwrap();
+        ping.consensus_serialize(&mut pipe).unwrap();
+        pipes.push(pipe);
+        msgs.push(ping);
+
+        assert!(!sender.is_encrypting());
+        let (mut pipe, ephemeral_public_key) = sender.make_session_key_handle(0).unwrap();
+        let session_key = make_message(
+            1,
+            StacksMessageType::SessionKey(SessionKeyData {
+                ephemeral_public_key: StacksPublicKeyBuffer::from_public_key(&ephemeral_public_key),
+            }),
+        );
+        session_key.consensus_serialize(&mut pipe).unwrap();
+        pipes.push(pipe);
+        msgs.push(session_key);
+
+        // can only switch once
+        assert!(sender.is_encrypting());
+        match sender.make_session_key_handle(0) {
+            Err(net_error::InProgress) => {}
+            x => panic!("Expected InProgress, got {:?}", &x),
+        }
+
+        for i in 2..5 {
+            let ping = make_message(i, StacksMessageType::Ping(PingData { nonce: i }));
+            let mut pipe = sender.make_relay_handle(0).unwrap();
+            ping.consensus_serialize(&mut pipe).unwrap();
+            pipes.push(pipe);
+            msgs.push(ping);
+        }
+
+        let mut wire = vec![];
+        let mut rounds = 0;
+        while sender.outbox_len() > 0 {
+            for p in pipes.iter_mut() {
+                let _ = p.try_flush();
+            }
+            sender.send_data(&mut wire).unwrap();
+
+            rounds += 1;
+            assert!(rounds < 1000, "Failed to send all messages");
+        }
+
+        (msgs, wire)
+    }
+
+    #[test]
+    fn connection_session_send_recv() {
+        let sender_privkey = Secp256k1PrivateKey::new();
+        let sender_pubkey = Secp256k1PublicKey::from_private(&sender_privkey);
+        let recipient_privkey = Secp256k1PrivateKey::new();
+        let recipient_pubkey = Secp256k1PublicKey::from_private(&recipient_privkey);
+
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.inbox_maxlen = 10;
+        conn_opts.outbox_maxlen = 10;
+
+        let mut sender = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(recipient_pubkey));
+        let mut recipient = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(sender_pubkey));
+
+        let (msgs, wire) = send_session_pings(&mut sender, &sender_privkey);
+
+        // the messages sent after the session key are not in the clear
+        for msg in msgs[0..2].iter() {
+            let mut bytes = vec![];
+            msg.consensus_serialize(&mut bytes).unwrap();
+            assert!(wire.windows(bytes.len()).any(|w| w == &bytes[..]));
+        }
+        for msg in msgs[2..].iter() {
+            let mut bytes = vec![];
+            msg.consensus_serialize(&mut bytes).unwrap();
+            assert!(!wire.windows(bytes.len()).any(|w| w == &bytes[..]));
+        }
+
+        // recipient doesn't know its key yet, so it only gets the plaintext messages
+        let num_read = recipient.recv_data(&mut io::Cursor::new(&wire)).unwrap();
+        assert_eq!(num_read, wire.len());
+        assert!(recipient.is_decrypting());
+        assert_eq!(recipient.inbox_len(), 2);
+
+        // recipient learns its key, and decrypts the rest
+        recipient
+            .set_session_private_key(recipient_privkey)
+            .unwrap();
+        assert_eq!(recipient.inbox_len(), 5);
+        assert_eq!(recipient.drain_inbox(), msgs);
+
+        // recipient never switched its side of the conversation
+        assert!(!recipient.is_encrypting());
+    }
+
+    #[test]
+    fn connection_session_recv_byte_at_a_time() {
+        let sender_privkey = Secp256k1PrivateKey::new();
+        let sender_pubkey = Secp256k1PublicKey::from_private(&sender_privkey);
+        let recipient_privkey = Secp256k1PrivateKey::new();
+        let recipient_pubkey = Secp256k1PublicKey::from_private(&recipient_privkey);
+
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.inbox_maxlen = 10;
+        conn_opts.outbox_maxlen = 10;
+
+        let mut sender = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(recipient_pubkey));
+        let mut recipient = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(sender_pubkey));
+        recipient
+            .set_session_private_key(recipient_privkey)
+            .unwrap();
+
+        let (msgs, wire) = send_session_pings(&mut sender, &sender_privkey);
+        for i in 0..wire.len() {
+            recipient
+                .recv_data(&mut io::Cursor::new(&wire[i..(i + 1)]))
+                .unwrap();
+        }
+        assert_eq!(recipient.drain_inbox(), msgs);
+    }
+
+    #[test]
+    fn connection_session_recv_tampered() {
+        let sender_privkey = Secp256k1PrivateKey::new();
+        let sender_pubkey = Secp256k1PublicKey::from_private(&sender_privkey);
+        let recipient_privkey = Secp256k1PrivateKey::new();
+        let recipient_pubkey = Secp256k1PublicKey::from_private(&recipient_privkey);
+
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.inbox_maxlen = 10;
+        conn_opts.outbox_maxlen = 10;
+
+        let mut sender = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(recipient_pubkey));
+        let mut recipient = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(sender_pubkey));
+        recipient
+            .set_session_private_key(recipient_privkey)
+            .unwrap();
+
+        let (_msgs, mut wire) = send_session_pings(&mut sender, &sender_privkey);
+        let last = wire.len() - 1;
+        wire[last] ^= 0x01;
+
+        match recipient.recv_data(&mut io::Cursor::new(&wire)) {
+            Err(net_error::VerifyingError(_)) => {}
+            x => panic!("Expected VerifyingError, got {:?}", &x),
+        }
+    }
+
+    #[test]
+    fn connection_session_key_requires_public_key() {
+        let sender_privkey = Secp256k1PrivateKey::new();
+        let recipient_privkey = Secp256k1PrivateKey::new();
+        let recipient_pubkey = Secp256k1PublicKey::from_private(&recipient_privkey);
+
+        let mut conn_opts = ConnectionOptions::default();
+        conn_opts.inbox_maxlen = 10;
+        conn_opts.outbox_maxlen = 10;
+
+        // recipient doesn't know who the sender is, so it can't trust its session key
+        let mut sender = ConnectionP2P::new(StacksP2P::new(), &conn_opts, Some(recipient_pubkey));
+        let mut recipient = ConnectionP2P::new(StacksP2P::new(), &conn_opts, None);
+        recipient
+            .set_session_private_key(recipient_privkey)
+            .unwrap();
+
+        let (_msgs, wire) = send_session_pings(&mut sender, &sender_privkey);
+        match recipient.recv_data(&mut io::Cursor::new(&wire)) {
+            Err(net_error::InvalidMessage) => {}
+            x => panic!("Expected InvalidMessage, got {:?}", &x),
+        }
+    }
+
     #[ignore] // fails intermittently when run via `cargo test`
     #[test]
     fn connection_send_recv() {
@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::cmp::Reverse;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -186,10 +187,87 @@ impl std::fmt::Display for BlockRequestKey {
     }
 }
 
+/// In headers-first mode, how many times we re-queue a block download that failed to connect
+/// before we give up on the peer's URL for a while.
+pub const HEADERS_FIRST_MAX_BLOCK_RETRIES: u64 = 3;
+
+/// Request for a chain of block headers, ending at `tip`, from a peer's data-plane endpoint
+#[derive(Debug, PartialEq, Clone, Hash, Eq)]
+pub struct HeadersRequestKey {
+    pub neighbor: NeighborKey,
+    pub data_url: UrlString,
+    pub tip: StacksBlockId,
+    pub num_headers: u64,
+    pub canonical_stacks_tip_height: u64,
+}
+
+impl Requestable for HeadersRequestKey {
+    fn get_url(&self) -> &UrlString {
+        &self.data_url
+    }
+
+    fn make_request_type(&self, peer_host: PeerHost) -> HttpRequestType {
+        HttpRequestType::GetHeaders(
+            HttpRequestMetadata::from_host(peer_host, Some(self.canonical_stacks_tip_height)),
+            self.num_headers,
+            TipRequest::SpecificTip(self.tip.clone()),
+        )
+    }
+}
+
+impl std::fmt::Display for HeadersRequestKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "<Request<Headers>: {} ({}) {} {:?}>",
+            self.tip, self.num_headers, self.neighbor, self.data_url
+        )
+    }
+}
+
+/// Block download statistics for a peer's data-plane endpoint.  Used in headers-first mode to
+/// decide which peers to ask for block bodies first.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct PeerThroughput {
+    /// total bytes of blocks downloaded
+    pub bytes: u64,
+    /// total time spent downloading them
+    pub elapsed_ms: u64,
+    /// number of requests that succeeded
+    pub successes: u64,
+    /// number of requests that failed
+    pub failures: u64,
+}
+
+impl PeerThroughput {
+    pub fn record_success(&mut self, bytes: u64, elapsed_ms: u64) -> () {
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.elapsed_ms = self.elapsed_ms.saturating_add(elapsed_ms);
+        self.successes = self.successes.saturating_add(1);
+    }
+
+    pub fn record_failure(&mut self) -> () {
+        self.failures = self.failures.saturating_add(1);
+    }
+
+    /// Observed bytes per second, scaled by the fraction of requests that succeeded.  A peer we
+    /// have never asked gets the highest possible score, so it gets tried.
+    pub fn score(&self) -> u64 {
+        let attempts = self.successes.saturating_add(self.failures);
+        if attempts == 0 {
+            return u64::MAX;
+        }
+        let bytes_per_sec = self.bytes.saturating_mul(1000) / self.elapsed_ms.max(1);
+        bytes_per_sec.saturating_mul(self.successes) / attempts
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Copy)]
 pub enum BlockDownloaderState {
     DNSLookupBegin,
     DNSLookupFinish,
+    GetHeadersBegin,
+    GetHeadersFinish,
     GetBlocksBegin,
     GetBlocksFinish,
     GetMicroblocksBegin,
@@ -255,6 +333,28 @@ pub struct BlockDownloader {
     /// when did we last request a given block hash
     requested_blocks: HashMap<StacksBlockId, u64>,
     requested_microblocks: HashMap<StacksBlockId, u64>,
+
+    /// Fetch and validate each peer's chain of block headers before asking it for blocks
+    headers_first: bool,
+
+    /// In-flight requests for header chains
+    getheaders_requests: HashMap<HeadersRequestKey, usize>,
+
+    /// Headers of sortition-winning blocks, checked in this pass, keyed by index block hash
+    valid_headers: HashMap<StacksBlockId, StacksBlockHeader>,
+
+    /// Peers that served us a valid header chain in this pass, and peers that did not
+    valid_header_neighbors: HashSet<NeighborKey>,
+    invalid_header_neighbors: HashSet<NeighborKey>,
+
+    /// When each in-flight block request was sent, in milliseconds (headers-first only)
+    getblock_request_start: HashMap<BlockRequestKey, u128>,
+
+    /// How many times we re-queued each block download (headers-first only)
+    block_retries: HashMap<StacksBlockId, u64>,
+
+    /// Block download statistics for each peer (headers-first only)
+    peer_throughput: HashMap<NeighborKey, PeerThroughput>,
 }
 
 impl BlockDownloader {
@@ -262,6 +362,7 @@ impl BlockDownloader {
         dns_timeout: u128,
         download_interval: u64,
         max_inflight_requests: u64,
+        headers_first: bool,
     ) -> BlockDownloader {
         BlockDownloader {
             state: BlockDownloaderState::DNSLookupBegin,
@@ -300,6 +401,15 @@ impl BlockDownloader {
             download_interval: download_interval,
             requested_blocks: HashMap::new(),
             requested_microblocks: HashMap::new(),
+
+            headers_first,
+            getheaders_requests: HashMap::new(),
+            valid_headers: HashMap::new(),
+            valid_header_neighbors: HashSet::new(),
+            invalid_header_neighbors: HashSet::new(),
+            getblock_request_start: HashMap::new(),
+            block_retries: HashMap::new(),
+            peer_throughput: HashMap::new(),
         }
     }
 
@@ -321,8 +431,15 @@ impl BlockDownloader {
         self.broken_peers.clear();
         self.broken_neighbors.clear();
 
+        self.getheaders_requests.clear();
+        self.valid_headers.clear();
+        self.valid_header_neighbors.clear();
+        self.invalid_header_neighbors.clear();
+        self.getblock_request_start.clear();
+        self.block_retries.clear();
+
         // perserve sortition height
-        // preserve download accounting
+        // preserve download accounting (including peer throughput)
     }
 
     pub fn restart_scan(&mut self, sortition_start: u64) -> () {
@@ -424,12 +541,266 @@ impl BlockDownloader {
         if inflight == 0 {
             // done with DNS
             dns_client.clear_all_requests();
-            self.state = BlockDownloaderState::GetBlocksBegin;
+            self.state = if self.headers_first {
+                BlockDownloaderState::GetHeadersBegin
+            } else {
+                BlockDownloaderState::GetBlocksBegin
+            };
         }
 
         Ok(inflight == 0)
     }
 
+    /// Start fetching header chains
+    pub fn getheaders_begin(&mut self, requests: HashMap<HeadersRequestKey, usize>) -> () {
+        assert_eq!(self.state, BlockDownloaderState::GetHeadersBegin);
+
+        self.getheaders_requests = requests;
+        self.state = BlockDownloaderState::GetHeadersFinish;
+    }
+
+    /// Finish fetching header chains.  Return true once all reply handles have been fulfilled
+    /// (either with data, or with an error).
+    /// Remember each valid header chain, and which peers served one.  Once we're done, prune the
+    /// block requests accordingly.
+    pub fn getheaders_try_finish(
+        &mut self,
+        network: &mut PeerNetwork,
+        sortdb: &SortitionDB,
+    ) -> Result<bool, net_error> {
+        assert_eq!(self.state, BlockDownloaderState::GetHeadersFinish);
+
+        // requests that are still pending
+        let mut pending_headers_requests = HashMap::new();
+
+        PeerNetwork::with_http(
+            network,
+            |ref mut _network, ref mut http| -> Result<(), net_error> {
+                for (headers_key, event_id) in self.getheaders_requests.drain() {
+                    match http.get_conversation(event_id) {
+                        None => {
+                            if http.is_connecting(event_id) {
+                                debug!("Event {} ({:?}, {:?} for headers ending at {}) is not connected yet", event_id, &headers_key.neighbor, &headers_key.data_url, &headers_key.tip);
+                                pending_headers_requests.insert(headers_key, event_id);
+                            } else {
+                                // we'll find out whether or not this URL works when we ask it for
+                                // blocks; until then, this peer's chain is unverified.
+                                debug!("Event {} ({:?}, {:?}) for headers ending at {} failed to connect", event_id, &headers_key.neighbor, &headers_key.data_url, &headers_key.tip);
+                                self.dead_peers.push(event_id);
+                                self.peer_throughput
+                                    .entry(headers_key.neighbor)
+                                    .or_insert_with(PeerThroughput::default)
+                                    .record_failure();
+                            }
+                        }
+                        Some(ref mut convo) => match convo.try_get_response() {
+                            None => {
+                                // still waiting
+                                debug!("Event {} ({:?}, {:?} for headers ending at {}) is still waiting for a response", event_id, &headers_key.neighbor, &headers_key.data_url, &headers_key.tip);
+                                pending_headers_requests.insert(headers_key, event_id);
+                            }
+                            Some(http_response) => match http_response {
+                                HttpResponseType::Headers(_md, headers) => {
+                                    if BlockDownloader::validate_header_chain(
+                                        sortdb,
+                                        &headers_key.tip,
+                                        headers_key.num_headers,
+                                        &headers,
+                                    )? {
+                                        debug!(
+                                            "Got {} valid headers ending at {} from {:?}",
+                                            headers.len(),
+                                            &headers_key.tip,
+                                            &headers_key.neighbor
+                                        );
+                                        for ext_header in headers.into_iter() {
+                                            let index_block_hash =
+                                                StacksBlockHeader::make_index_block_hash(
+                                                    &ext_header.consensus_hash,
+                                                    &ext_header.header.block_hash(),
+                                                );
+                                            self.valid_headers
+                                                .insert(index_block_hash, ext_header.header);
+                                        }
+                                        self.valid_header_neighbors.insert(headers_key.neighbor);
+                                    } else {
+                                        info!(
+                                            "Invalid header chain from {:?} ({:?}) ending at {}",
+                                            &headers_key.neighbor,
+                                            &headers_key.data_url,
+                                            &headers_key.tip
+                                        );
+                                        self.broken_peers.push(event_id);
+                                        self.broken_neighbors.push(headers_key.neighbor.clone());
+                                        self.invalid_header_neighbors.insert(headers_key.neighbor);
+                                    }
+                                }
+                                HttpResponseType::NotFound(_, _) => {
+                                    // remote peer didn't have the block its inventory claims it has
+                                    info!("Remote neighbor {:?} ({:?}) does not actually have block {}", &headers_key.neighbor, &headers_key.data_url, &headers_key.tip);
+                                    self.broken_peers.push(event_id);
+                                    self.broken_neighbors.push(headers_key.neighbor.clone());
+                                    self.invalid_header_neighbors.insert(headers_key.neighbor);
+                                }
+                                _ => {
+                                    // wrong message response
+                                    info!(
+                                        "Got bad HTTP response from {:?}: {:?}",
+                                        &headers_key.data_url, &http_response
+                                    );
+                                    self.broken_peers.push(event_id);
+                                    self.broken_neighbors.push(headers_key.neighbor.clone());
+                                    self.invalid_header_neighbors.insert(headers_key.neighbor);
+                                }
+                            },
+                        },
+                    }
+                }
+                Ok(())
+            },
+        )?;
+
+        // are we done?
+        if pending_headers_requests.len() == 0 {
+            self.filter_block_requests();
+            self.state = BlockDownloaderState::GetBlocksBegin;
+            return Ok(true);
+        }
+
+        // still have more to go
+        for (headers_key, event_id) in pending_headers_requests.drain() {
+            self.getheaders_requests.insert(headers_key, event_id);
+        }
+        return Ok(false);
+    }
+
+    /// Check that a header chain served for `tip` is well-formed: it starts at `tip`, has no
+    /// more than `num_headers` headers, and each header's parent is the header that follows it.
+    pub fn check_header_chain_linkage(
+        tip: &StacksBlockId,
+        num_headers: u64,
+        headers: &[ExtendedStacksHeader],
+    ) -> bool {
+        if headers.len() == 0 || (headers.len() as u64) > num_headers {
+            return false;
+        }
+
+        let mut expected_index_block_hash = tip.clone();
+        let mut expected_block_hash = None;
+        for ext_header in headers.iter() {
+            let block_hash = ext_header.header.block_hash();
+            if let Some(expected_block_hash) = expected_block_hash {
+                if block_hash != expected_block_hash {
+                    return false;
+                }
+            }
+            let index_block_hash =
+                StacksBlockHeader::make_index_block_hash(&ext_header.consensus_hash, &block_hash);
+            if index_block_hash != expected_index_block_hash {
+                return false;
+            }
+
+            expected_index_block_hash = ext_header.parent_block_id.clone();
+            expected_block_hash = Some(ext_header.header.parent_block.clone());
+        }
+        true
+    }
+
+    /// Check that a header chain served for `tip` is well-formed, and that each of its headers
+    /// belongs to the block that won its sortition on a PoX-valid burnchain fork.
+    pub fn validate_header_chain(
+        sortdb: &SortitionDB,
+        tip: &StacksBlockId,
+        num_headers: u64,
+        headers: &[ExtendedStacksHeader],
+    ) -> Result<bool, net_error> {
+        if !BlockDownloader::check_header_chain_linkage(tip, num_headers, headers) {
+            debug!("Header chain ending at {} is not well-formed", tip);
+            return Ok(false);
+        }
+
+        for ext_header in headers.iter() {
+            let sn = match SortitionDB::get_block_snapshot_consensus(
+                sortdb.conn(),
+                &ext_header.consensus_hash,
+            )? {
+                Some(sn) => sn,
+                None => {
+                    debug!("No such sortition {}", &ext_header.consensus_hash);
+                    return Ok(false);
+                }
+            };
+
+            if !sn.pox_valid
+                || !sn.sortition
+                || sn.winning_stacks_block_hash != ext_header.header.block_hash()
+            {
+                debug!(
+                    "Block {}/{} did not win its sortition",
+                    &ext_header.consensus_hash,
+                    &ext_header.header.block_hash()
+                );
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+
+    /// Apply the header chains we fetched to the block requests we're about to make.  Peers that
+    /// served an invalid header chain are not asked for anything.  Blocks whose headers we
+    /// validated are only requested from peers whose header chains checked out.  Blocks we did
+    /// not get headers for (e.g. off of any peer's chain) can still be fetched from anyone else.
+    fn filter_block_requests(&mut self) -> () {
+        let valid_headers = &self.valid_headers;
+        let valid_header_neighbors = &self.valid_header_neighbors;
+        let invalid_header_neighbors = &self.invalid_header_neighbors;
+
+        for (_, requests) in self.blocks_to_try.iter_mut() {
+            let have_header = requests
+                .front()
+                .map(|request| valid_headers.contains_key(&request.index_block_hash))
+                .unwrap_or(false);
+
+            requests.retain(|request| {
+                !invalid_header_neighbors.contains(&request.neighbor)
+                    && (!have_header || valid_header_neighbors.contains(&request.neighbor))
+            });
+        }
+        for (_, requests) in self.microblocks_to_try.iter_mut() {
+            requests.retain(|request| !invalid_header_neighbors.contains(&request.neighbor));
+        }
+
+        self.blocks_to_try.retain(|_, requests| requests.len() > 0);
+        self.microblocks_to_try
+            .retain(|_, requests| requests.len() > 0);
+
+        debug!(
+            "Validated {} headers from {} peers ({} peers served invalid headers); will try {} blocks",
+            self.valid_headers.len(),
+            self.valid_header_neighbors.len(),
+            self.invalid_header_neighbors.len(),
+            self.blocks_to_try.len()
+        );
+    }
+
+    /// Order the candidate requests for a block so that we first ask the peer with the fewest
+    /// requests already assigned to it in this round, breaking ties by measured throughput.
+    /// This spreads block downloads across peers, while favoring the fast ones.
+    pub fn rank_block_requests(
+        requests: &mut VecDeque<BlockRequestKey>,
+        peer_throughput: &HashMap<NeighborKey, PeerThroughput>,
+        assigned: &HashMap<NeighborKey, u64>,
+    ) -> () {
+        requests.make_contiguous().sort_by_key(|request| {
+            let load = assigned.get(&request.neighbor).cloned().unwrap_or(0);
+            let score = peer_throughput
+                .get(&request.neighbor)
+                .map(|throughput| throughput.score())
+                .unwrap_or(u64::MAX);
+            (load, Reverse(score))
+        });
+    }
+
     pub fn getblocks_begin(&mut self, requests: HashMap<BlockRequestKey, usize>) -> () {
         assert_eq!(self.state, BlockDownloaderState::GetBlocksBegin);
 
@@ -465,6 +836,30 @@ impl BlockDownloader {
 
                             // try again
                             self.requested_blocks.remove(&block_key.index_block_hash);
+                            self.getblock_request_start.remove(&block_key);
+
+                            if self.headers_first {
+                                self.peer_throughput
+                                    .entry(block_key.neighbor.clone())
+                                    .or_insert_with(PeerThroughput::default)
+                                    .record_failure();
+
+                                // re-queue behind the other peers that can serve it, unless we
+                                // have already done so too many times
+                                let retries = self
+                                    .block_retries
+                                    .entry(block_key.index_block_hash.clone())
+                                    .or_insert(0);
+                                if *retries < HEADERS_FIRST_MAX_BLOCK_RETRIES {
+                                    *retries += 1;
+                                    debug!("Event {} ({:?}, {:?}) for block {} failed to connect. Will retry ({} of {})", event_id, &block_key.neighbor, &block_key.data_url, &block_key.index_block_hash, *retries, HEADERS_FIRST_MAX_BLOCK_RETRIES);
+                                    self.blocks_to_try
+                                        .entry(block_key.sortition_height)
+                                        .or_insert_with(VecDeque::new)
+                                        .push_back(block_key);
+                                    continue;
+                                }
+                            }
 
                             let is_always_allowed = match PeerDB::get_peer(
                                 &network.peerdb.conn(),
@@ -520,6 +915,20 @@ impl BlockDownloader {
                                             &block_key.consensus_hash,
                                             block.block_hash()
                                         );
+                                        if self.headers_first {
+                                            let now_ms = get_epoch_time_ms();
+                                            let start_ms = self
+                                                .getblock_request_start
+                                                .remove(&block_key)
+                                                .unwrap_or(now_ms);
+                                            self.peer_throughput
+                                                .entry(block_key.neighbor.clone())
+                                                .or_insert_with(PeerThroughput::default)
+                                                .record_success(
+                                                    block.serialize_to_vec().len() as u64,
+                                                    now_ms.saturating_sub(start_ms) as u64,
+                                                );
+                                        }
                                         self.blocks.insert(block_key, block);
                                     }
                                 }
@@ -2023,6 +2432,93 @@ impl PeerNetwork {
         None
     }
 
+    /// Make requests for the header chains of the peers we're about to ask for blocks.  Each
+    /// peer is asked for the headers leading up to the highest block we'd request from it,
+    /// going back far enough to cover the lowest one.
+    fn make_header_requests(&self, downloader: &BlockDownloader) -> Vec<HeadersRequestKey> {
+        // neighbor --> (data URL, lowest sortition height, highest sortition height, tip)
+        let mut ranges: HashMap<NeighborKey, (UrlString, u64, u64, StacksBlockId)> = HashMap::new();
+        for (_, requests) in downloader.blocks_to_try.iter() {
+            for request in requests.iter() {
+                if let Some((_, lowest, highest, tip)) = ranges.get_mut(&request.neighbor) {
+                    if request.sortition_height < *lowest {
+                        *lowest = request.sortition_height;
+                    }
+                    if request.sortition_height > *highest {
+                        *highest = request.sortition_height;
+                        *tip = request.index_block_hash.clone();
+                    }
+                    continue;
+                }
+                ranges.insert(
+                    request.neighbor.clone(),
+                    (
+                        request.data_url.clone(),
+                        request.sortition_height,
+                        request.sortition_height,
+                        request.index_block_hash.clone(),
+                    ),
+                );
+            }
+        }
+
+        let mut requests = vec![];
+        for (neighbor, (data_url, lowest, highest, tip)) in ranges.into_iter() {
+            // there's at most one block per sortition
+            let num_headers = (highest - lowest + 1).min(MAX_HEADERS as u64);
+            debug!(
+                "{:?}: Make request for {} headers ending at {} (sortition height {}) to {:?}",
+                &self.local_peer, num_headers, &tip, highest, &neighbor
+            );
+            requests.push(HeadersRequestKey {
+                neighbor,
+                data_url,
+                tip,
+                num_headers,
+                canonical_stacks_tip_height: self.burnchain_tip.canonical_stacks_tip_height,
+            });
+        }
+        requests
+    }
+
+    /// Start fetching header chains
+    pub fn block_getheaders_begin(
+        &mut self,
+        mempool: &MemPoolDB,
+        chainstate: &mut StacksChainState,
+    ) -> Result<(), net_error> {
+        test_debug!("{:?}: block_getheaders_begin", &self.local_peer);
+        PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
+            let mut requests = HashMap::new();
+            for key in network.make_header_requests(downloader).into_iter() {
+                let mut keys = VecDeque::from(vec![key]);
+                match PeerNetwork::begin_request(
+                    network,
+                    &downloader.dns_lookups,
+                    &mut keys,
+                    mempool,
+                    chainstate,
+                ) {
+                    Some((key, handle)) => {
+                        requests.insert(key, handle);
+                    }
+                    None => {}
+                }
+            }
+
+            downloader.getheaders_begin(requests);
+            Ok(())
+        })
+    }
+
+    /// Try to see if all header chains are finished downloading
+    pub fn block_getheaders_try_finish(&mut self, sortdb: &SortitionDB) -> Result<bool, net_error> {
+        test_debug!("{:?}: block_getheaders_try_finish", &self.local_peer);
+        PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
+            downloader.getheaders_try_finish(network, sortdb)
+        })
+    }
+
     /// Start fetching blocks
     pub fn block_getblocks_begin(
         &mut self,
@@ -2033,9 +2529,20 @@ impl PeerNetwork {
         PeerNetwork::with_downloader_state(self, |ref mut network, ref mut downloader| {
             let mut priority = PeerNetwork::prioritize_requests(&downloader.blocks_to_try);
             let mut requests = HashMap::new();
+
+            // in headers-first mode, how many requests we've sent to each peer in this round
+            let mut assigned = HashMap::new();
+
             for sortition_height in priority.drain(..) {
                 match downloader.blocks_to_try.get_mut(&sortition_height) {
                     Some(ref mut keys) => {
+                        if downloader.headers_first {
+                            BlockDownloader::rank_block_requests(
+                                keys,
+                                &downloader.peer_throughput,
+                                &assigned,
+                            );
+                        }
                         match PeerNetwork::begin_request(
                             network,
                             &downloader.dns_lookups,
@@ -2044,6 +2551,12 @@ impl PeerNetwork {
                             chainstate,
                         ) {
                             Some((key, handle)) => {
+                                if downloader.headers_first {
+                                    *assigned.entry(key.neighbor.clone()).or_insert(0) += 1;
+                                    downloader
+                                        .getblock_request_start
+                                        .insert(key.clone(), get_epoch_time_ms());
+                                }
                                 requests.insert(key.clone(), handle);
                             }
                             None => {}
@@ -2378,6 +2891,7 @@ impl PeerNetwork {
             self.connection_opts.dns_timeout,
             self.connection_opts.download_interval,
             self.connection_opts.max_inflight_blocks,
+            self.connection_opts.headers_first_sync,
         ));
     }
 
@@ -2500,6 +3014,12 @@ impl PeerNetwork {
                 BlockDownloaderState::DNSLookupFinish => {
                     self.block_dns_lookups_try_finish(dns_client)?;
                 }
+                BlockDownloaderState::GetHeadersBegin => {
+                    self.block_getheaders_begin(mempool, chainstate)?;
+                }
+                BlockDownloaderState::GetHeadersFinish => {
+                    self.block_getheaders_try_finish(sortdb)?;
+                }
                 BlockDownloaderState::GetBlocksBegin => {
                     self.block_getblocks_begin(mempool, chainstate)?;
                 }
@@ -3613,6 +4133,7 @@ pub mod test {
                                             "(begin (print \"hello world\"))",
                                         )
                                         .expect("FATAL: valid code"),
+                                        clarity_version: None,
                                     });
                                 let mut mblock = microblocks.last().unwrap().clone();
                                 let last_nonce = mblock
@@ -3789,4 +4310,235 @@ pub mod test {
             );
         })
     }
+
+    #[test]
+    #[ignore]
+    pub fn test_get_blocks_and_microblocks_2_peers_download_headers_first() {
+        with_timeout(600, || {
+            run_get_blocks_and_microblocks(
+                "test_get_blocks_and_microblocks_2_peers_download_headers_first",
+                3270,
+                2,
+                |ref mut peer_configs| {
+                    // build initial network topology
+                    assert_eq!(peer_configs.len(), 2);
+
+                    peer_configs[0].connection_opts.disable_block_advertisement = true;
+                    peer_configs[1].connection_opts.disable_block_advertisement = true;
+
+                    peer_configs[0].connection_opts.headers_first_sync = true;
+                    peer_configs[1].connection_opts.headers_first_sync = true;
+
+                    let peer_0 = peer_configs[0].to_neighbor();
+                    let peer_1 = peer_configs[1].to_neighbor();
+                    peer_configs[0].add_neighbor(&peer_1);
+                    peer_configs[1].add_neighbor(&peer_0);
+                },
+                |num_blocks, ref mut peers| {
+                    // build up block data to replicate
+                    let mut block_data = vec![];
+                    for _ in 0..num_blocks {
+                        let (mut burn_ops, stacks_block, microblocks) =
+                            peers[1].make_default_tenure();
+
+                        let (_, burn_header_hash, consensus_hash) =
+                            peers[1].next_burnchain_block(burn_ops.clone());
+                        peers[1].process_stacks_epoch_at_tip(&stacks_block, &microblocks);
+
+                        TestPeer::set_ops_burn_header_hash(&mut burn_ops, &burn_header_hash);
+
+                        peers[0].next_burnchain_block_raw(burn_ops);
+
+                        let sn = SortitionDB::get_canonical_burn_chain_tip(
+                            &peers[1].sortdb.as_ref().unwrap().conn(),
+                        )
+                        .unwrap();
+                        block_data.push((
+                            sn.consensus_hash.clone(),
+                            Some(stacks_block),
+                            Some(microblocks),
+                        ));
+                    }
+                    block_data
+                },
+                |_| {},
+                |peer| {
+                    // check peer health
+                    // nothing should break, and nobody should serve us a bad header chain
+                    match peer.network.block_downloader {
+                        Some(ref dl) => {
+                            assert_eq!(dl.broken_peers.len(), 0);
+                            assert_eq!(dl.dead_peers.len(), 0);
+                            assert_eq!(dl.invalid_header_neighbors.len(), 0);
+                            for (_, throughput) in dl.peer_throughput.iter() {
+                                assert_eq!(throughput.failures, 0);
+                            }
+                        }
+                        None => {}
+                    }
+                    true
+                },
+                |_| true,
+            );
+        })
+    }
+
+    /// Make a chain of `len` headers, and return the index block hash of its tip along with the
+    /// headers as /v2/headers would serve them (tip first).
+    fn make_header_chain(len: usize) -> (StacksBlockId, Vec<ExtendedStacksHeader>) {
+        let mut headers = vec![];
+        let mut parent_header = StacksBlockHeader::genesis_block_header();
+        let mut parent_block_id = StacksBlockHeader::make_index_block_hash(
+            &FIRST_BURNCHAIN_CONSENSUS_HASH,
+            &parent_header.block_hash(),
+        );
+        for i in 0..len {
+            let mut header = StacksBlockHeader::genesis_block_header();
+            header.parent_block = parent_header.block_hash();
+            header.tx_merkle_root = Sha512Trunc256Sum([(i + 1) as u8; 32]);
+
+            let consensus_hash = ConsensusHash([(i + 1) as u8; 20]);
+            headers.push(ExtendedStacksHeader {
+                consensus_hash: consensus_hash.clone(),
+                header: header.clone(),
+                parent_block_id: parent_block_id.clone(),
+            });
+
+            parent_block_id =
+                StacksBlockHeader::make_index_block_hash(&consensus_hash, &header.block_hash());
+            parent_header = header;
+        }
+        headers.reverse();
+        (parent_block_id, headers)
+    }
+
+    #[test]
+    fn test_check_header_chain_linkage() {
+        let (tip, headers) = make_header_chain(5);
+        assert!(BlockDownloader::check_header_chain_linkage(
+            &tip, 5, &headers
+        ));
+        assert!(BlockDownloader::check_header_chain_linkage(
+            &tip,
+            MAX_HEADERS as u64,
+            &headers
+        ));
+
+        // a suffix of the chain is fine too
+        assert!(BlockDownloader::check_header_chain_linkage(
+            &tip,
+            5,
+            &headers[0..2]
+        ));
+
+        // no headers, or too many headers
+        assert!(!BlockDownloader::check_header_chain_linkage(&tip, 5, &[]));
+        assert!(!BlockDownloader::check_header_chain_linkage(
+            &tip, 4, &headers
+        ));
+
+        // not the tip we asked for
+        assert!(!BlockDownloader::check_header_chain_linkage(
+            &StacksBlockId([0xff; 32]),
+            5,
+            &headers
+        ));
+
+        // parent block ID doesn't match the next header
+        let mut bad_headers = headers.clone();
+        bad_headers[1].parent_block_id = StacksBlockId([0xff; 32]);
+        assert!(!BlockDownloader::check_header_chain_linkage(
+            &tip,
+            5,
+            &bad_headers
+        ));
+
+        // signed parent block hash doesn't match the next header
+        let mut bad_headers = headers.clone();
+        bad_headers[0].header.parent_block = BlockHeaderHash([0xff; 32]);
+        let bad_tip = StacksBlockHeader::make_index_block_hash(
+            &bad_headers[0].consensus_hash,
+            &bad_headers[0].header.block_hash(),
+        );
+        assert!(!BlockDownloader::check_header_chain_linkage(
+            &bad_tip,
+            5,
+            &bad_headers
+        ));
+    }
+
+    #[test]
+    fn test_rank_block_requests() {
+        let make_neighbor = |port: u16| NeighborKey {
+            peer_version: 0x18000000,
+            network_id: 0x80000000,
+            addrbytes: PeerAddress([0u8; 16]),
+            port: port,
+        };
+        let make_request = |nk: &NeighborKey| {
+            BlockRequestKey::new(
+                nk.clone(),
+                UrlString::try_from(format!("http://127.0.0.1:{}", nk.port)).unwrap(),
+                ConsensusHash([0x01; 20]),
+                BlockHeaderHash([0x02; 32]),
+                StacksBlockId([0x03; 32]),
+                None,
+                None,
+                1,
+                BlockRequestKeyKind::Block,
+                0,
+            )
+        };
+
+        let fast = make_neighbor(1);
+        let slow = make_neighbor(2);
+        let flaky = make_neighbor(3);
+        let fresh = make_neighbor(4);
+
+        let mut peer_throughput = HashMap::new();
+        let mut fast_throughput = PeerThroughput::default();
+        fast_throughput.record_success(1_000_000, 1000);
+        peer_throughput.insert(fast.clone(), fast_throughput);
+
+        let mut slow_throughput = PeerThroughput::default();
+        slow_throughput.record_success(1_000, 1000);
+        peer_throughput.insert(slow.clone(), slow_throughput);
+
+        let mut flaky_throughput = PeerThroughput::default();
+        flaky_throughput.record_success(1_000_000, 1000);
+        flaky_throughput.record_failure();
+        flaky_throughput.record_failure();
+        flaky_throughput.record_failure();
+        peer_throughput.insert(flaky.clone(), flaky_throughput);
+
+        assert_eq!(peer_throughput.get(&fast).unwrap().score(), 1_000_000);
+        assert_eq!(peer_throughput.get(&slow).unwrap().score(), 1_000);
+        assert_eq!(peer_throughput.get(&flaky).unwrap().score(), 250_000);
+        assert_eq!(PeerThroughput::default().score(), u64::MAX);
+
+        let mut only_failures = PeerThroughput::default();
+        only_failures.record_failure();
+        assert_eq!(only_failures.score(), 0);
+
+        let mut requests: VecDeque<BlockRequestKey> = vec![&slow, &flaky, &fast, &fresh]
+            .into_iter()
+            .map(|nk| make_request(nk))
+            .collect();
+
+        // untested peers first, then fastest first
+        BlockDownloader::rank_block_requests(&mut requests, &peer_throughput, &HashMap::new());
+        let order: Vec<_> = requests.iter().map(|r| r.neighbor.clone()).collect();
+        assert_eq!(
+            order,
+            vec![fresh.clone(), fast.clone(), flaky.clone(), slow.clone()]
+        );
+
+        // peers that are already busy in this round go last
+        let mut assigned = HashMap::new();
+        assigned.insert(fresh.clone(), 1);
+        assigned.insert(fast.clone(), 1);
+        BlockDownloader::rank_block_requests(&mut requests, &peer_throughput, &assigned);
+        let order: Vec<_> = requests.iter().map(|r| r.neighbor.clone()).collect();
+        assert_eq!(order, vec![flaky, slow, fresh, fast]);
+    }
 }
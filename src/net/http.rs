@@ -38,6 +38,8 @@ use url::{form_urlencoded, Url};
 
 use crate::burnchains::{Address, Txid};
 use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::stacks::db::addrindex::IndexCursor;
+use crate::chainstate::stacks::db::eventlog::ContractEventFilter;
 use crate::chainstate::stacks::{
     StacksBlock, StacksMicroblock, StacksPublicKey, StacksTransaction,
 };
@@ -63,6 +65,7 @@ use crate::net::PeerHost;
 use crate::net::ProtocolFamily;
 use crate::net::StacksHttpMessage;
 use crate::net::StacksHttpPreamble;
+use crate::net::TransactionReceiptResponse;
 use crate::net::UnconfirmedTransactionResponse;
 use crate::net::UnconfirmedTransactionStatus;
 use crate::net::HTTP_PREAMBLE_MAX_ENCODED_SIZE;
@@ -72,7 +75,7 @@ use crate::net::MAX_HEADERS;
 use crate::net::MAX_MICROBLOCKS_UNCONFIRMED;
 use crate::net::{CallReadOnlyRequestBody, TipRequest};
 use crate::net::{GetAttachmentResponse, GetAttachmentsInvResponse, PostTransactionRequestBody};
-use clarity::vm::types::{StandardPrincipalData, TraitIdentifier};
+use clarity::vm::types::{QualifiedContractIdentifier, StandardPrincipalData, TraitIdentifier};
 use clarity::vm::{
     ast::parser::{
         CLARITY_NAME_REGEX, CONTRACT_NAME_REGEX, PRINCIPAL_DATA_REGEX, STANDARD_PRINCIPAL_REGEX,
@@ -100,6 +103,25 @@ use super::FeeRateEstimateRequestBody;
 lazy_static! {
     static ref PATH_GETINFO: Regex = Regex::new(r#"^/v2/info$"#).unwrap();
     static ref PATH_GETPOXINFO: Regex = Regex::new(r#"^/v2/pox$"#).unwrap();
+    static ref PATH_GET_STACKER_INFO: Regex = Regex::new(&format!(
+        "^/v2/pox/stackers/(?P<principal>{})$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_REWARD_SET: Regex =
+        Regex::new(r#"^/v2/pox/reward_set/([0-9]{1,10})$"#).unwrap();
+    static ref PATH_GET_NAME_RESOLUTION: Regex = Regex::new(
+        r#"^/v2/names/(?P<name>[a-z0-9\-_]{1,48})\.(?P<namespace>[a-z0-9\-_]{1,20})$"#
+    )
+    .unwrap();
+    static ref PATH_GET_NAMES_BY_OWNER: Regex = Regex::new(&format!(
+        "^/v2/names/owners/(?P<principal>{})$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_NAMESPACE_NAMES: Regex =
+        Regex::new(r#"^/v2/namespaces/(?P<namespace>[a-z0-9\-_]{1,20})/names$"#).unwrap();
+    static ref PATH_GET_COST_VOTING: Regex = Regex::new(r#"^/v2/cost_voting$"#).unwrap();
     static ref PATH_GETNEIGHBORS: Regex = Regex::new(r#"^/v2/neighbors$"#).unwrap();
     static ref PATH_GETHEADERS: Regex = Regex::new(r#"^/v2/headers/([0-9]+)$"#).unwrap();
     static ref PATH_GETBLOCK: Regex = Regex::new(r#"^/v2/blocks/([0-9a-f]{64})$"#).unwrap();
@@ -111,6 +133,8 @@ lazy_static! {
         Regex::new(r#"^/v2/microblocks/unconfirmed/([0-9a-f]{64})/([0-9]{1,5})$"#).unwrap();
     static ref PATH_GETTRANSACTION_UNCONFIRMED: Regex =
         Regex::new(r#"^/v2/transactions/unconfirmed/([0-9a-f]{64})$"#).unwrap();
+    static ref PATH_GETTRANSACTION: Regex =
+        Regex::new(r#"^/v2/transactions/([0-9a-f]{64})$"#).unwrap();
     static ref PATH_POSTTRANSACTION: Regex = Regex::new(r#"^/v2/transactions$"#).unwrap();
     static ref PATH_POST_FEE_RATE_ESIMATE: Regex = Regex::new(r#"^/v2/fees/transaction$"#).unwrap();
     static ref PATH_POSTBLOCK: Regex = Regex::new(r#"^/v2/blocks/upload/([0-9a-f]{40})$"#).unwrap();
@@ -120,6 +144,22 @@ lazy_static! {
         *PRINCIPAL_DATA_REGEX
     ))
     .unwrap();
+    static ref PATH_GET_UNCONFIRMED_ACCOUNT: Regex = Regex::new(&format!(
+        "^/v2/accounts/(?P<principal>{})/unconfirmed$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_PRINCIPAL_TRANSACTIONS: Regex = Regex::new(&format!(
+        "^/v2/accounts/(?P<principal>{})/transactions$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_PRINCIPAL_ASSET_EVENTS: Regex = Regex::new(&format!(
+        "^/v2/accounts/(?P<principal>{})/events$",
+        *PRINCIPAL_DATA_REGEX
+    ))
+    .unwrap();
+    static ref PATH_GET_CONTRACT_EVENTS: Regex = Regex::new("^/v2/events$").unwrap();
     static ref PATH_GET_DATA_VAR: Regex = Regex::new(&format!(
         "^/v2/data_var/(?P<address>{})/(?P<contract>{})/(?P<varname>{})$",
         *STANDARD_PRINCIPAL_REGEX, *CONTRACT_NAME_REGEX, *CLARITY_NAME_REGEX
@@ -1497,6 +1537,36 @@ impl HttpRequestType {
         )] = &[
             ("GET", &PATH_GETINFO, &HttpRequestType::parse_getinfo),
             ("GET", &PATH_GETPOXINFO, &HttpRequestType::parse_getpoxinfo),
+            (
+                "GET",
+                &PATH_GET_STACKER_INFO,
+                &HttpRequestType::parse_get_stacker_info,
+            ),
+            (
+                "GET",
+                &PATH_GET_REWARD_SET,
+                &HttpRequestType::parse_get_reward_set,
+            ),
+            (
+                "GET",
+                &PATH_GET_NAME_RESOLUTION,
+                &HttpRequestType::parse_get_name_resolution,
+            ),
+            (
+                "GET",
+                &PATH_GET_NAMES_BY_OWNER,
+                &HttpRequestType::parse_get_names_by_owner,
+            ),
+            (
+                "GET",
+                &PATH_GET_NAMESPACE_NAMES,
+                &HttpRequestType::parse_get_namespace_names,
+            ),
+            (
+                "GET",
+                &PATH_GET_COST_VOTING,
+                &HttpRequestType::parse_get_cost_voting,
+            ),
             (
                 "GET",
                 &PATH_GETNEIGHBORS,
@@ -1524,6 +1594,11 @@ impl HttpRequestType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpRequestType::parse_gettransaction_unconfirmed,
             ),
+            (
+                "GET",
+                &PATH_GETTRANSACTION,
+                &HttpRequestType::parse_gettransaction,
+            ),
             (
                 "POST",
                 &PATH_POST_FEE_RATE_ESIMATE,
@@ -1545,6 +1620,26 @@ impl HttpRequestType {
                 &PATH_GET_ACCOUNT,
                 &HttpRequestType::parse_get_account,
             ),
+            (
+                "GET",
+                &PATH_GET_UNCONFIRMED_ACCOUNT,
+                &HttpRequestType::parse_get_unconfirmed_account,
+            ),
+            (
+                "GET",
+                &PATH_GET_PRINCIPAL_TRANSACTIONS,
+                &HttpRequestType::parse_get_principal_transactions,
+            ),
+            (
+                "GET",
+                &PATH_GET_PRINCIPAL_ASSET_EVENTS,
+                &HttpRequestType::parse_get_principal_asset_events,
+            ),
+            (
+                "GET",
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpRequestType::parse_get_contract_events,
+            ),
             (
                 "GET",
                 &PATH_GET_DATA_VAR,
@@ -1686,6 +1781,161 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_stacker_info<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetStackerInfo".to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse stacker principal".into())
+        })?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetStackerInfo(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            tip,
+        ))
+    }
+
+    fn parse_get_reward_set<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetRewardSet".to_string(),
+            ));
+        }
+
+        let reward_cycle: u64 = captures
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to reward cycle".to_string(),
+            ))?
+            .as_str()
+            .parse()
+            .map_err(|_| net_error::DeserializeError("Failed to parse reward cycle".to_string()))?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+        let with_proof = HttpRequestType::get_proof_query(query);
+
+        Ok(HttpRequestType::GetRewardSet(
+            HttpRequestMetadata::from_preamble(preamble),
+            reward_cycle,
+            tip,
+            with_proof,
+        ))
+    }
+
+    fn parse_get_name_resolution<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetNameResolution".to_string(),
+            ));
+        }
+
+        let name = captures["name"].to_string();
+        let namespace = captures["namespace"].to_string();
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetNameResolution(
+            HttpRequestMetadata::from_preamble(preamble),
+            name,
+            namespace,
+            tip,
+        ))
+    }
+
+    fn parse_get_names_by_owner<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetNamesByOwner".to_string(),
+            ));
+        }
+
+        let owner = PrincipalData::parse(&captures["principal"])
+            .map_err(|_e| net_error::DeserializeError("Failed to parse owner principal".into()))?;
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetNamesByOwner(
+            HttpRequestMetadata::from_preamble(preamble),
+            owner,
+            tip,
+        ))
+    }
+
+    fn parse_get_namespace_names<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetNamespaceNames".to_string(),
+            ));
+        }
+
+        let namespace = captures["namespace"].to_string();
+        let page = HttpRequestType::get_names_page_query(query);
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetNamespaceNames(
+            HttpRequestMetadata::from_preamble(preamble),
+            namespace,
+            page,
+            tip,
+        ))
+    }
+
+    fn parse_get_cost_voting<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetCostVoting".to_string(),
+            ));
+        }
+
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetCostVoting(
+            HttpRequestMetadata::from_preamble(preamble),
+            tip,
+        ))
+    }
+
     fn parse_getneighbors<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -1762,6 +2012,90 @@ impl HttpRequestType {
         }
     }
 
+    /// get the optional page query argument (`page`) of BNS name, principal activity
+    /// and contract event listings.
+    /// Take the first value we can parse, and default to the first page.
+    fn get_names_page_query(query: Option<&str>) -> u32 {
+        match query {
+            Some(query_string) => {
+                for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+                    if key != "page" {
+                        continue;
+                    }
+                    if let Ok(page) = value.parse::<u32>() {
+                        return page;
+                    }
+                }
+                0
+            }
+            None => 0,
+        }
+    }
+
+    /// get the optional index cursor query argument (`cursor`), which says where the previous
+    /// page of address index or event log rows ended.  Rejected if it cannot be parsed, since
+    /// starting over from the newest row would return the wrong page.
+    fn get_index_cursor_query(query: Option<&str>) -> Result<Option<IndexCursor>, net_error> {
+        let query_string = match query {
+            Some(query_string) => query_string,
+            None => {
+                return Ok(None);
+            }
+        };
+        for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+            if key != "cursor" {
+                continue;
+            }
+            let cursor = value
+                .parse::<IndexCursor>()
+                .map_err(|_e| net_error::DeserializeError("Failed to parse cursor".into()))?;
+            return Ok(Some(cursor));
+        }
+        Ok(None)
+    }
+
+    /// get the optional contract event log filter query arguments (`contract_id`, `topic`,
+    /// `from_height` and `to_height`).  Unlike the other query arguments, these are rejected if
+    /// they cannot be parsed, since ignoring a filter would return the wrong events.
+    fn get_contract_event_filter_query(
+        query: Option<&str>,
+    ) -> Result<ContractEventFilter, net_error> {
+        let mut filter = ContractEventFilter::default();
+        let query_string = match query {
+            Some(query_string) => query_string,
+            None => {
+                return Ok(filter);
+            }
+        };
+        for (key, value) in form_urlencoded::parse(query_string.as_bytes()) {
+            match key.as_ref() {
+                "contract_id" => {
+                    let contract_id = QualifiedContractIdentifier::parse(&value).map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse contract_id".into())
+                    })?;
+                    filter.contract_id = Some(contract_id);
+                }
+                "topic" => {
+                    filter.topic = Some(value.to_string());
+                }
+                "from_height" => {
+                    let height = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse from_height".into())
+                    })?;
+                    filter.from_height = Some(height);
+                }
+                "to_height" => {
+                    let height = value.parse::<u64>().map_err(|_e| {
+                        net_error::DeserializeError("Failed to parse to_height".into())
+                    })?;
+                    filter.to_height = Some(height);
+                }
+                _ => {}
+            }
+        }
+        Ok(filter)
+    }
+
     /// get the mempool page ID optional query argument (`page_id`)
     /// Take the first value we can parse.
     fn get_mempool_page_id_query(query: Option<&str>) -> Option<Txid> {
@@ -1811,6 +2145,111 @@ impl HttpRequestType {
         ))
     }
 
+    fn parse_get_unconfirmed_account<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetUnconfirmedAccount"
+                    .to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse account principal".into())
+        })?;
+
+        Ok(HttpRequestType::GetUnconfirmedAccount(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+        ))
+    }
+
+    fn parse_get_principal_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetPrincipalTransactions"
+                    .to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse account principal".into())
+        })?;
+        let cursor = HttpRequestType::get_index_cursor_query(query)?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetPrincipalTransactions(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            cursor,
+            tip,
+        ))
+    }
+
+    fn parse_get_principal_asset_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetPrincipalAssetEvents"
+                    .to_string(),
+            ));
+        }
+
+        let principal = PrincipalData::parse(&captures["principal"]).map_err(|_e| {
+            net_error::DeserializeError("Failed to parse account principal".into())
+        })?;
+        let cursor = HttpRequestType::get_index_cursor_query(query)?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetPrincipalAssetEvents(
+            HttpRequestMetadata::from_preamble(preamble),
+            principal,
+            cursor,
+            tip,
+        ))
+    }
+
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        _captures: &Captures,
+        query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetContractEvents".to_string(),
+            ));
+        }
+
+        let filter = HttpRequestType::get_contract_event_filter_query(query)?;
+        let cursor = HttpRequestType::get_index_cursor_query(query)?;
+        let tip = HttpRequestType::get_chain_tip_query(query);
+
+        Ok(HttpRequestType::GetContractEvents(
+            HttpRequestMetadata::from_preamble(preamble),
+            filter,
+            cursor,
+            tip,
+        ))
+    }
+
     fn parse_get_data_var<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
@@ -2210,7 +2649,43 @@ impl HttpRequestType {
         ))
     }
 
-    fn parse_gettransaction_unconfirmed<R: Read>(
+    fn parse_gettransaction_unconfirmed<R: Read>(
+        _protocol: &mut StacksHttp,
+        preamble: &HttpRequestPreamble,
+        regex: &Captures,
+        _query: Option<&str>,
+        _fd: &mut R,
+    ) -> Result<HttpRequestType, net_error> {
+        if preamble.get_content_length() != 0 {
+            return Err(net_error::DeserializeError(
+                "Invalid Http request: expected 0-length body for GetMicrolocksUnconfirmed"
+                    .to_string(),
+            ));
+        }
+
+        let txid_hex = regex
+            .get(1)
+            .ok_or(net_error::DeserializeError(
+                "Failed to match path to txid group".to_string(),
+            ))?
+            .as_str();
+
+        if txid_hex.len() != 64 {
+            return Err(net_error::DeserializeError(
+                "Invalid txid: expected 64 bytes".to_string(),
+            ));
+        }
+
+        let txid = Txid::from_hex(&txid_hex)
+            .map_err(|_e| net_error::DeserializeError("Failed to decode txid hex".to_string()))?;
+
+        Ok(HttpRequestType::GetTransactionUnconfirmed(
+            HttpRequestMetadata::from_preamble(preamble),
+            txid,
+        ))
+    }
+
+    fn parse_gettransaction<R: Read>(
         _protocol: &mut StacksHttp,
         preamble: &HttpRequestPreamble,
         regex: &Captures,
@@ -2219,8 +2694,7 @@ impl HttpRequestType {
     ) -> Result<HttpRequestType, net_error> {
         if preamble.get_content_length() != 0 {
             return Err(net_error::DeserializeError(
-                "Invalid Http request: expected 0-length body for GetMicrolocksUnconfirmed"
-                    .to_string(),
+                "Invalid Http request: expected 0-length body for GetTransaction".to_string(),
             ));
         }
 
@@ -2231,16 +2705,10 @@ impl HttpRequestType {
             ))?
             .as_str();
 
-        if txid_hex.len() != 64 {
-            return Err(net_error::DeserializeError(
-                "Invalid txid: expected 64 bytes".to_string(),
-            ));
-        }
-
         let txid = Txid::from_hex(&txid_hex)
             .map_err(|_e| net_error::DeserializeError("Failed to decode txid hex".to_string()))?;
 
-        Ok(HttpRequestType::GetTransactionUnconfirmed(
+        Ok(HttpRequestType::GetTransaction(
             HttpRequestMetadata::from_preamble(preamble),
             txid,
         ))
@@ -2670,6 +3138,12 @@ impl HttpRequestType {
         match *self {
             HttpRequestType::GetInfo(ref md) => md,
             HttpRequestType::GetPoxInfo(ref md, ..) => md,
+            HttpRequestType::GetStackerInfo(ref md, ..) => md,
+            HttpRequestType::GetRewardSet(ref md, ..) => md,
+            HttpRequestType::GetNameResolution(ref md, ..) => md,
+            HttpRequestType::GetNamesByOwner(ref md, ..) => md,
+            HttpRequestType::GetNamespaceNames(ref md, ..) => md,
+            HttpRequestType::GetCostVoting(ref md, ..) => md,
             HttpRequestType::GetNeighbors(ref md) => md,
             HttpRequestType::GetHeaders(ref md, ..) => md,
             HttpRequestType::GetBlock(ref md, _) => md,
@@ -2677,10 +3151,15 @@ impl HttpRequestType {
             HttpRequestType::GetMicroblocksConfirmed(ref md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref md, _) => md,
+            HttpRequestType::GetTransaction(ref md, _) => md,
             HttpRequestType::PostTransaction(ref md, _, _) => md,
             HttpRequestType::PostBlock(ref md, ..) => md,
             HttpRequestType::PostMicroblock(ref md, ..) => md,
             HttpRequestType::GetAccount(ref md, ..) => md,
+            HttpRequestType::GetUnconfirmedAccount(ref md, ..) => md,
+            HttpRequestType::GetPrincipalTransactions(ref md, ..) => md,
+            HttpRequestType::GetPrincipalAssetEvents(ref md, ..) => md,
+            HttpRequestType::GetContractEvents(ref md, ..) => md,
             HttpRequestType::GetDataVar(ref md, ..) => md,
             HttpRequestType::GetMapEntry(ref md, ..) => md,
             HttpRequestType::GetTransferCost(ref md) => md,
@@ -2701,6 +3180,12 @@ impl HttpRequestType {
         match *self {
             HttpRequestType::GetInfo(ref mut md) => md,
             HttpRequestType::GetPoxInfo(ref mut md, ..) => md,
+            HttpRequestType::GetStackerInfo(ref mut md, ..) => md,
+            HttpRequestType::GetRewardSet(ref mut md, ..) => md,
+            HttpRequestType::GetNameResolution(ref mut md, ..) => md,
+            HttpRequestType::GetNamesByOwner(ref mut md, ..) => md,
+            HttpRequestType::GetNamespaceNames(ref mut md, ..) => md,
+            HttpRequestType::GetCostVoting(ref mut md, ..) => md,
             HttpRequestType::GetNeighbors(ref mut md) => md,
             HttpRequestType::GetHeaders(ref mut md, ..) => md,
             HttpRequestType::GetBlock(ref mut md, _) => md,
@@ -2708,10 +3193,15 @@ impl HttpRequestType {
             HttpRequestType::GetMicroblocksConfirmed(ref mut md, _) => md,
             HttpRequestType::GetMicroblocksUnconfirmed(ref mut md, _, _) => md,
             HttpRequestType::GetTransactionUnconfirmed(ref mut md, _) => md,
+            HttpRequestType::GetTransaction(ref mut md, _) => md,
             HttpRequestType::PostTransaction(ref mut md, _, _) => md,
             HttpRequestType::PostBlock(ref mut md, ..) => md,
             HttpRequestType::PostMicroblock(ref mut md, ..) => md,
             HttpRequestType::GetAccount(ref mut md, ..) => md,
+            HttpRequestType::GetUnconfirmedAccount(ref mut md, ..) => md,
+            HttpRequestType::GetPrincipalTransactions(ref mut md, ..) => md,
+            HttpRequestType::GetPrincipalAssetEvents(ref mut md, ..) => md,
+            HttpRequestType::GetContractEvents(ref mut md, ..) => md,
             HttpRequestType::GetDataVar(ref mut md, ..) => md,
             HttpRequestType::GetMapEntry(ref mut md, ..) => md,
             HttpRequestType::GetTransferCost(ref mut md) => md,
@@ -2746,6 +3236,18 @@ impl HttpRequestType {
         }
     }
 
+    /// Make the `cursor` query argument to follow `tip_query`, if there is a cursor
+    fn make_index_cursor_query_string(tip_query: &str, cursor: &Option<IndexCursor>) -> String {
+        match cursor {
+            Some(cursor) => format!(
+                "{}cursor={}",
+                if tip_query.is_empty() { "?" } else { "&" },
+                cursor
+            ),
+            None => "".to_string(),
+        }
+    }
+
     pub fn request_path(&self) -> String {
         match self {
             HttpRequestType::GetInfo(_md) => "/v2/info".to_string(),
@@ -2753,6 +3255,41 @@ impl HttpRequestType {
                 "/v2/pox{}",
                 HttpRequestType::make_tip_query_string(tip_req, true)
             ),
+            HttpRequestType::GetStackerInfo(_md, principal, tip_req) => format!(
+                "/v2/pox/stackers/{}{}",
+                &principal.to_string(),
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::GetRewardSet(_md, reward_cycle, tip_req, with_proof) => format!(
+                "/v2/pox/reward_set/{}{}",
+                reward_cycle,
+                HttpRequestType::make_tip_query_string(tip_req, *with_proof)
+            ),
+            HttpRequestType::GetNameResolution(_md, name, namespace, tip_req) => format!(
+                "/v2/names/{}.{}{}",
+                name,
+                namespace,
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::GetNamesByOwner(_md, owner, tip_req) => format!(
+                "/v2/names/owners/{}{}",
+                &owner.to_string(),
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
+            HttpRequestType::GetNamespaceNames(_md, namespace, page, tip_req) => {
+                let tip_query = HttpRequestType::make_tip_query_string(tip_req, true);
+                format!(
+                    "/v2/namespaces/{}/names{}{}page={}",
+                    namespace,
+                    tip_query,
+                    if tip_query.is_empty() { "?" } else { "&" },
+                    page
+                )
+            }
+            HttpRequestType::GetCostVoting(_md, tip_req) => format!(
+                "/v2/cost_voting{}",
+                HttpRequestType::make_tip_query_string(tip_req, true)
+            ),
             HttpRequestType::GetNeighbors(_md) => "/v2/neighbors".to_string(),
             HttpRequestType::GetHeaders(_md, quantity, tip_req) => format!(
                 "/v2/headers/{}{}",
@@ -2776,6 +3313,7 @@ impl HttpRequestType {
             HttpRequestType::GetTransactionUnconfirmed(_md, txid) => {
                 format!("/v2/transactions/unconfirmed/{}", txid)
             }
+            HttpRequestType::GetTransaction(_md, txid) => format!("/v2/transactions/{}", txid),
             HttpRequestType::PostTransaction(_md, ..) => "/v2/transactions".to_string(),
             HttpRequestType::PostBlock(_md, ch, ..) => format!("/v2/blocks/upload/{}", &ch),
             HttpRequestType::PostMicroblock(_md, _, tip_req) => format!(
@@ -2789,6 +3327,59 @@ impl HttpRequestType {
                     HttpRequestType::make_tip_query_string(tip_req, *with_proof,)
                 )
             }
+            HttpRequestType::GetUnconfirmedAccount(_md, principal) => {
+                format!("/v2/accounts/{}/unconfirmed", &principal.to_string())
+            }
+            HttpRequestType::GetPrincipalTransactions(_md, principal, cursor, tip_req) => {
+                let tip_query = HttpRequestType::make_tip_query_string(tip_req, true);
+                format!(
+                    "/v2/accounts/{}/transactions{}{}",
+                    &principal.to_string(),
+                    tip_query,
+                    HttpRequestType::make_index_cursor_query_string(&tip_query, cursor)
+                )
+            }
+            HttpRequestType::GetPrincipalAssetEvents(_md, principal, cursor, tip_req) => {
+                let tip_query = HttpRequestType::make_tip_query_string(tip_req, true);
+                format!(
+                    "/v2/accounts/{}/events{}{}",
+                    &principal.to_string(),
+                    tip_query,
+                    HttpRequestType::make_index_cursor_query_string(&tip_query, cursor)
+                )
+            }
+            HttpRequestType::GetContractEvents(_md, filter, cursor, tip_req) => {
+                let tip_query = HttpRequestType::make_tip_query_string(tip_req, true);
+                let mut filter_query = form_urlencoded::Serializer::new(String::new());
+                if let Some(ref contract_id) = filter.contract_id {
+                    filter_query.append_pair("contract_id", &contract_id.to_string());
+                }
+                if let Some(ref topic) = filter.topic {
+                    filter_query.append_pair("topic", topic);
+                }
+                if let Some(from_height) = filter.from_height {
+                    filter_query.append_pair("from_height", &from_height.to_string());
+                }
+                if let Some(to_height) = filter.to_height {
+                    filter_query.append_pair("to_height", &to_height.to_string());
+                }
+                if let Some(cursor) = cursor {
+                    filter_query.append_pair("cursor", &cursor.to_string());
+                }
+                let filter_query = filter_query.finish();
+                format!(
+                    "/v2/events{}{}{}",
+                    tip_query,
+                    if filter_query.is_empty() {
+                        ""
+                    } else if tip_query.is_empty() {
+                        "?"
+                    } else {
+                        "&"
+                    },
+                    filter_query
+                )
+            }
             HttpRequestType::GetDataVar(
                 _md,
                 contract_addr,
@@ -2904,6 +3495,12 @@ impl HttpRequestType {
         match self {
             HttpRequestType::GetInfo(..) => "/v2/info",
             HttpRequestType::GetPoxInfo(..) => "/v2/pox",
+            HttpRequestType::GetStackerInfo(..) => "/v2/pox/stackers/:principal",
+            HttpRequestType::GetRewardSet(..) => "/v2/pox/reward_set/:cycle",
+            HttpRequestType::GetNameResolution(..) => "/v2/names/:name",
+            HttpRequestType::GetNamesByOwner(..) => "/v2/names/owners/:principal",
+            HttpRequestType::GetNamespaceNames(..) => "/v2/namespaces/:namespace/names",
+            HttpRequestType::GetCostVoting(..) => "/v2/cost_voting",
             HttpRequestType::GetNeighbors(..) => "/v2/neighbors",
             HttpRequestType::GetHeaders(..) => "/v2/headers/:height",
             HttpRequestType::GetBlock(..) => "/v2/blocks/:hash",
@@ -2913,10 +3510,15 @@ impl HttpRequestType {
                 "/v2/microblocks/unconfirmed/:hash/:seq"
             }
             HttpRequestType::GetTransactionUnconfirmed(..) => "/v2/transactions/unconfirmed/:txid",
+            HttpRequestType::GetTransaction(..) => "/v2/transactions/:txid",
             HttpRequestType::PostTransaction(..) => "/v2/transactions",
             HttpRequestType::PostBlock(..) => "/v2/blocks/upload/:block",
             HttpRequestType::PostMicroblock(..) => "/v2/microblocks",
             HttpRequestType::GetAccount(..) => "/v2/accounts/:principal",
+            HttpRequestType::GetUnconfirmedAccount(..) => "/v2/accounts/:principal/unconfirmed",
+            HttpRequestType::GetPrincipalTransactions(..) => "/v2/accounts/:principal/transactions",
+            HttpRequestType::GetPrincipalAssetEvents(..) => "/v2/accounts/:principal/events",
+            HttpRequestType::GetContractEvents(..) => "/v2/events",
             HttpRequestType::GetDataVar(..) => "/v2/data_var/:principal/:contract_name/:var_name",
             HttpRequestType::GetMapEntry(..) => "/v2/map_entry/:principal/:contract_name/:map_name",
             HttpRequestType::GetTransferCost(..) => "/v2/fees/transfer",
@@ -3350,6 +3952,24 @@ impl HttpResponseType {
         )] = &[
             (&PATH_GETINFO, &HttpResponseType::parse_peerinfo),
             (&PATH_GETPOXINFO, &HttpResponseType::parse_poxinfo),
+            (
+                &PATH_GET_STACKER_INFO,
+                &HttpResponseType::parse_stacker_info,
+            ),
+            (&PATH_GET_REWARD_SET, &HttpResponseType::parse_reward_set),
+            (
+                &PATH_GET_NAME_RESOLUTION,
+                &HttpResponseType::parse_name_resolution,
+            ),
+            (
+                &PATH_GET_NAMES_BY_OWNER,
+                &HttpResponseType::parse_names_by_owner,
+            ),
+            (
+                &PATH_GET_NAMESPACE_NAMES,
+                &HttpResponseType::parse_namespace_names,
+            ),
+            (&PATH_GET_COST_VOTING, &HttpResponseType::parse_cost_voting),
             (&PATH_GETNEIGHBORS, &HttpResponseType::parse_neighbors),
             (&PATH_GETHEADERS, &HttpResponseType::parse_headers),
             (&PATH_GETBLOCK, &HttpResponseType::parse_block),
@@ -3371,6 +3991,10 @@ impl HttpResponseType {
                 &PATH_GETTRANSACTION_UNCONFIRMED,
                 &HttpResponseType::parse_transaction_unconfirmed,
             ),
+            (
+                &PATH_GETTRANSACTION,
+                &HttpResponseType::parse_transaction_receipt,
+            ),
             (&PATH_POSTTRANSACTION, &HttpResponseType::parse_txid),
             (
                 &PATH_POSTBLOCK,
@@ -3381,6 +4005,22 @@ impl HttpResponseType {
                 &HttpResponseType::parse_microblock_hash,
             ),
             (&PATH_GET_ACCOUNT, &HttpResponseType::parse_get_account),
+            (
+                &PATH_GET_UNCONFIRMED_ACCOUNT,
+                &HttpResponseType::parse_get_unconfirmed_account,
+            ),
+            (
+                &PATH_GET_PRINCIPAL_TRANSACTIONS,
+                &HttpResponseType::parse_get_principal_transactions,
+            ),
+            (
+                &PATH_GET_PRINCIPAL_ASSET_EVENTS,
+                &HttpResponseType::parse_get_principal_asset_events,
+            ),
+            (
+                &PATH_GET_CONTRACT_EVENTS,
+                &HttpResponseType::parse_get_contract_events,
+            ),
             (
                 &PATH_GET_CONTRACT_SRC,
                 &HttpResponseType::parse_get_contract_src,
@@ -3487,6 +4127,93 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_stacker_info<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let stacker_info =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::StackerInfo(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            stacker_info,
+        ))
+    }
+
+    fn parse_reward_set<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let reward_set =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::RewardSet(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            reward_set,
+        ))
+    }
+
+    fn parse_name_resolution<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let name_resolution =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::NameResolution(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            name_resolution,
+        ))
+    }
+
+    fn parse_names_by_owner<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let names = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::NamesByOwner(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            names,
+        ))
+    }
+
+    fn parse_namespace_names<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let names = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::NamespaceNames(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            names,
+        ))
+    }
+
+    fn parse_cost_voting<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let report = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::CostVoting(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            report,
+        ))
+    }
+
     fn parse_neighbors<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3562,6 +4289,64 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_get_unconfirmed_account<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let account_data =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::GetUnconfirmedAccount(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            account_data,
+        ))
+    }
+
+    fn parse_get_principal_transactions<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let transactions =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::PrincipalTransactions(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            transactions,
+        ))
+    }
+
+    fn parse_get_principal_asset_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let events = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::PrincipalAssetEvents(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            events,
+        ))
+    }
+
+    fn parse_get_contract_events<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let events = HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+        Ok(HttpResponseType::ContractEvents(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            events,
+        ))
+    }
+
     fn parse_get_data_var<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3715,6 +4500,34 @@ impl HttpResponseType {
         ))
     }
 
+    fn parse_transaction_receipt<R: Read>(
+        _protocol: &mut StacksHttp,
+        request_version: HttpVersion,
+        preamble: &HttpResponsePreamble,
+        fd: &mut R,
+        len_hint: Option<usize>,
+    ) -> Result<HttpResponseType, net_error> {
+        let receipt: TransactionReceiptResponse =
+            HttpResponseType::parse_json(preamble, fd, len_hint, MAX_MESSAGE_LEN as u64)?;
+
+        // tx payload, if given, must decode to a transaction
+        if let Some(ref tx_hex) = receipt.tx {
+            let tx_bytes = hex_bytes(tx_hex).map_err(|_| {
+                net_error::DeserializeError("Transaction is not hex-encoded".to_string())
+            })?;
+            let _ = StacksTransaction::consensus_deserialize(&mut &tx_bytes[..]).map_err(|_| {
+                net_error::DeserializeError(
+                    "Transaction is not a well-formed Stacks transaction".to_string(),
+                )
+            })?;
+        }
+
+        Ok(HttpResponseType::TransactionReceipt(
+            HttpResponseMetadata::from_preamble(request_version, preamble),
+            receipt,
+        ))
+    }
+
     fn parse_txid<R: Read>(
         _protocol: &mut StacksHttp,
         request_version: HttpVersion,
@@ -3982,6 +4795,12 @@ impl HttpResponseType {
         match *self {
             HttpResponseType::PeerInfo(ref md, _) => md,
             HttpResponseType::PoxInfo(ref md, _) => md,
+            HttpResponseType::StackerInfo(ref md, _) => md,
+            HttpResponseType::RewardSet(ref md, _) => md,
+            HttpResponseType::NameResolution(ref md, _) => md,
+            HttpResponseType::NamesByOwner(ref md, _) => md,
+            HttpResponseType::NamespaceNames(ref md, _) => md,
+            HttpResponseType::CostVoting(ref md, _) => md,
             HttpResponseType::Neighbors(ref md, _) => md,
             HttpResponseType::HeaderStream(ref md) => md,
             HttpResponseType::Headers(ref md, _) => md,
@@ -3996,11 +4815,16 @@ impl HttpResponseType {
             HttpResponseType::GetDataVar(ref md, _) => md,
             HttpResponseType::GetMapEntry(ref md, _) => md,
             HttpResponseType::GetAccount(ref md, _) => md,
+            HttpResponseType::GetUnconfirmedAccount(ref md, _) => md,
+            HttpResponseType::PrincipalTransactions(ref md, _) => md,
+            HttpResponseType::PrincipalAssetEvents(ref md, _) => md,
+            HttpResponseType::ContractEvents(ref md, _) => md,
             HttpResponseType::GetContractABI(ref md, _) => md,
             HttpResponseType::GetContractSrc(ref md, _) => md,
             HttpResponseType::GetIsTraitImplemented(ref md, _) => md,
             HttpResponseType::CallReadOnlyFunction(ref md, _) => md,
             HttpResponseType::UnconfirmedTransaction(ref md, _) => md,
+            HttpResponseType::TransactionReceipt(ref md, _) => md,
             HttpResponseType::GetAttachment(ref md, _) => md,
             HttpResponseType::GetAttachmentsInv(ref md, _) => md,
             HttpResponseType::MemPoolTxStream(ref md) => md,
@@ -4086,6 +4910,22 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, account_data)?;
             }
+            HttpResponseType::GetUnconfirmedAccount(ref md, ref account_data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, account_data)?;
+            }
+            HttpResponseType::PrincipalTransactions(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::PrincipalAssetEvents(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
+            HttpResponseType::ContractEvents(ref md, ref data) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, data)?;
+            }
             HttpResponseType::TransactionFeeEstimation(ref md, ref data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, data)?;
@@ -4126,6 +4966,30 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, pox_info)?;
             }
+            HttpResponseType::StackerInfo(ref md, ref stacker_info) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, stacker_info)?;
+            }
+            HttpResponseType::RewardSet(ref md, ref reward_set) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, reward_set)?;
+            }
+            HttpResponseType::NameResolution(ref md, ref name_resolution) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, name_resolution)?;
+            }
+            HttpResponseType::NamesByOwner(ref md, ref names) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, names)?;
+            }
+            HttpResponseType::NamespaceNames(ref md, ref names) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, names)?;
+            }
+            HttpResponseType::CostVoting(ref md, ref report) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, report)?;
+            }
             HttpResponseType::Neighbors(ref md, ref neighbor_data) => {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, neighbor_data)?;
@@ -4259,6 +5123,10 @@ impl HttpResponseType {
                 HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
                 HttpResponseType::send_json(protocol, md, fd, unconfirmed_status)?;
             }
+            HttpResponseType::TransactionReceipt(ref md, ref receipt) => {
+                HttpResponsePreamble::ok_JSON_from_md(fd, md)?;
+                HttpResponseType::send_json(protocol, md, fd, receipt)?;
+            }
             HttpResponseType::MemPoolTxStream(ref md) => {
                 // only send the preamble.  The caller will need to figure out how to send along
                 // the tx data itself.
@@ -4396,6 +5264,12 @@ impl MessageSequence for StacksHttpMessage {
             StacksHttpMessage::Request(ref req) => match req {
                 HttpRequestType::GetInfo(_) => "HTTP(GetInfo)",
                 HttpRequestType::GetPoxInfo(_, _) => "HTTP(GetPoxInfo)",
+                HttpRequestType::GetStackerInfo(..) => "HTTP(GetStackerInfo)",
+                HttpRequestType::GetRewardSet(..) => "HTTP(GetRewardSet)",
+                HttpRequestType::GetNameResolution(..) => "HTTP(GetNameResolution)",
+                HttpRequestType::GetNamesByOwner(..) => "HTTP(GetNamesByOwner)",
+                HttpRequestType::GetNamespaceNames(..) => "HTTP(GetNamespaceNames)",
+                HttpRequestType::GetCostVoting(..) => "HTTP(GetCostVoting)",
                 HttpRequestType::GetNeighbors(_) => "HTTP(GetNeighbors)",
                 HttpRequestType::GetHeaders(..) => "HTTP(GetHeaders)",
                 HttpRequestType::GetBlock(_, _) => "HTTP(GetBlock)",
@@ -4407,10 +5281,15 @@ impl MessageSequence for StacksHttpMessage {
                 HttpRequestType::GetTransactionUnconfirmed(_, _) => {
                     "HTTP(GetTransactionUnconfirmed)"
                 }
+                HttpRequestType::GetTransaction(_, _) => "HTTP(GetTransaction)",
                 HttpRequestType::PostTransaction(_, _, _) => "HTTP(PostTransaction)",
                 HttpRequestType::PostBlock(..) => "HTTP(PostBlock)",
                 HttpRequestType::PostMicroblock(..) => "HTTP(PostMicroblock)",
                 HttpRequestType::GetAccount(..) => "HTTP(GetAccount)",
+                HttpRequestType::GetUnconfirmedAccount(..) => "HTTP(GetUnconfirmedAccount)",
+                HttpRequestType::GetPrincipalTransactions(..) => "HTTP(GetPrincipalTransactions)",
+                HttpRequestType::GetPrincipalAssetEvents(..) => "HTTP(GetPrincipalAssetEvents)",
+                HttpRequestType::GetContractEvents(..) => "HTTP(GetContractEvents)",
                 HttpRequestType::GetDataVar(..) => "HTTP(GetDataVar)",
                 HttpRequestType::GetMapEntry(..) => "HTTP(GetMapEntry)",
                 HttpRequestType::GetTransferCost(_) => "HTTP(GetTransferCost)",
@@ -4430,6 +5309,10 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::GetDataVar(_, _) => "HTTP(GetDataVar)",
                 HttpResponseType::GetMapEntry(_, _) => "HTTP(GetMapEntry)",
                 HttpResponseType::GetAccount(_, _) => "HTTP(GetAccount)",
+                HttpResponseType::GetUnconfirmedAccount(_, _) => "HTTP(GetUnconfirmedAccount)",
+                HttpResponseType::PrincipalTransactions(_, _) => "HTTP(PrincipalTransactions)",
+                HttpResponseType::PrincipalAssetEvents(_, _) => "HTTP(PrincipalAssetEvents)",
+                HttpResponseType::ContractEvents(_, _) => "HTTP(ContractEvents)",
                 HttpResponseType::GetContractABI(..) => "HTTP(GetContractABI)",
                 HttpResponseType::GetContractSrc(..) => "HTTP(GetContractSrc)",
                 HttpResponseType::GetIsTraitImplemented(..) => "HTTP(GetIsTraitImplemented)",
@@ -4438,6 +5321,12 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::GetAttachmentsInv(_, _) => "HTTP(GetAttachmentsInv)",
                 HttpResponseType::PeerInfo(_, _) => "HTTP(PeerInfo)",
                 HttpResponseType::PoxInfo(_, _) => "HTTP(PeerInfo)",
+                HttpResponseType::StackerInfo(_, _) => "HTTP(StackerInfo)",
+                HttpResponseType::RewardSet(_, _) => "HTTP(RewardSet)",
+                HttpResponseType::NameResolution(_, _) => "HTTP(NameResolution)",
+                HttpResponseType::NamesByOwner(_, _) => "HTTP(NamesByOwner)",
+                HttpResponseType::NamespaceNames(_, _) => "HTTP(NamespaceNames)",
+                HttpResponseType::CostVoting(_, _) => "HTTP(CostVoting)",
                 HttpResponseType::Neighbors(_, _) => "HTTP(Neighbors)",
                 HttpResponseType::Headers(..) => "HTTP(Headers)",
                 HttpResponseType::HeaderStream(..) => "HTTP(HeaderStream)",
@@ -4449,6 +5338,7 @@ impl MessageSequence for StacksHttpMessage {
                 HttpResponseType::StacksBlockAccepted(..) => "HTTP(StacksBlockAccepted)",
                 HttpResponseType::MicroblockHash(_, _) => "HTTP(MicroblockHash)",
                 HttpResponseType::UnconfirmedTransaction(_, _) => "HTTP(UnconfirmedTransaction)",
+                HttpResponseType::TransactionReceipt(_, _) => "HTTP(TransactionReceipt)",
                 HttpResponseType::MemPoolTxStream(..) => "HTTP(MemPoolTxStream)",
                 HttpResponseType::MemPoolTxs(..) => "HTTP(MemPoolTxs)",
                 HttpResponseType::OptionsPreflight(_) => "HTTP(OptionsPreflight)",
@@ -6963,6 +7853,124 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_http_parse_index_cursor_query() {
+        let cursor = IndexCursor {
+            block_height: 10,
+            tx_index: 2,
+            event_index: 0,
+        };
+        assert_eq!(
+            HttpRequestType::get_index_cursor_query(Some("tip=latest&cursor=10-2-0")).unwrap(),
+            Some(cursor)
+        );
+        assert_eq!(HttpRequestType::get_index_cursor_query(None).unwrap(), None);
+        assert_eq!(
+            HttpRequestType::get_index_cursor_query(Some("tip=latest")).unwrap(),
+            None
+        );
+
+        // cursors that can't be parsed are rejected, not ignored
+        assert!(HttpRequestType::get_index_cursor_query(Some("cursor=10-2")).is_err());
+        assert!(HttpRequestType::get_index_cursor_query(Some("cursor=page")).is_err());
+
+        // the request path carries the cursor, if there is one
+        let principal = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let md = HttpRequestMetadata::from_host(PeerHost::DNS("www.foo.com".to_string(), 80), None);
+        let request = HttpRequestType::GetPrincipalTransactions(
+            md.clone(),
+            principal.clone(),
+            Some(cursor),
+            TipRequest::UseLatestAnchoredTip,
+        );
+        assert_eq!(
+            request.request_path(),
+            "/v2/accounts/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/transactions?cursor=10-2-0"
+        );
+        let request = HttpRequestType::GetPrincipalAssetEvents(
+            md,
+            principal,
+            None,
+            TipRequest::UseLatestUnconfirmedTip,
+        );
+        assert_eq!(
+            request.request_path(),
+            "/v2/accounts/ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R/events?tip=latest"
+        );
+    }
+
+    #[test]
+    fn test_http_parse_contract_event_filter_query() {
+        let query_txt = "contract_id=ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world&topic=new%20order&from_height=10&to_height=20&cursor=20-1-3";
+        let filter = HttpRequestType::get_contract_event_filter_query(Some(query_txt)).unwrap();
+        assert_eq!(
+            filter,
+            ContractEventFilter {
+                contract_id: Some(
+                    QualifiedContractIdentifier::parse(
+                        "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world"
+                    )
+                    .unwrap()
+                ),
+                topic: Some("new order".to_string()),
+                from_height: Some(10),
+                to_height: Some(20),
+            }
+        );
+        let cursor = IndexCursor {
+            block_height: 20,
+            tx_index: 1,
+            event_index: 3,
+        };
+        assert_eq!(
+            HttpRequestType::get_index_cursor_query(Some(query_txt)).unwrap(),
+            Some(cursor)
+        );
+
+        // no filter
+        assert_eq!(
+            HttpRequestType::get_contract_event_filter_query(None).unwrap(),
+            ContractEventFilter::default()
+        );
+        assert_eq!(
+            HttpRequestType::get_contract_event_filter_query(Some("cursor=1-0-0")).unwrap(),
+            ContractEventFilter::default()
+        );
+
+        // filters that can't be parsed are rejected, not ignored
+        assert!(HttpRequestType::get_contract_event_filter_query(Some("contract_id=bad")).is_err());
+        assert!(HttpRequestType::get_contract_event_filter_query(Some("from_height=-1")).is_err());
+        assert!(HttpRequestType::get_contract_event_filter_query(Some("to_height=tip")).is_err());
+
+        // the request path carries the filter and the cursor
+        let md = HttpRequestMetadata::from_host(PeerHost::DNS("www.foo.com".to_string(), 80), None);
+        let request = HttpRequestType::GetContractEvents(
+            md.clone(),
+            filter.clone(),
+            Some(cursor),
+            TipRequest::UseLatestAnchoredTip,
+        );
+        let path = request.request_path();
+        let query = path.split_once('?').unwrap().1;
+        assert_eq!(
+            HttpRequestType::get_contract_event_filter_query(Some(query)).unwrap(),
+            filter
+        );
+        assert_eq!(
+            HttpRequestType::get_index_cursor_query(Some(query)).unwrap(),
+            Some(cursor)
+        );
+
+        // ...or neither
+        let request = HttpRequestType::GetContractEvents(
+            md,
+            ContractEventFilter::default(),
+            None,
+            TipRequest::UseLatestAnchoredTip,
+        );
+        assert_eq!(request.request_path(), "/v2/events");
+    }
+
     #[test]
     fn test_http_live_headers() {
         // headers pulled from prod
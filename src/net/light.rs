@@ -0,0 +1,1175 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Light client state.
+//!
+//! A light client does not process Stacks blocks.  Instead, it keeps the burnchain and sortition
+//! DB in sync like any other node, and follows the Stacks chain by fetching block headers from
+//! full peers (via `/v2/headers`).  A header is only accepted if it won its sortition on a
+//! PoX-valid burnchain fork, and if it connects to a header we already accepted (or to the boot
+//! block).
+//!
+//! Chain state reads are answered by asking a full peer for the value along with a MARF proof
+//! (i.e. `?proof=1`), and checking the proof against the `state_index_root` of the header of the
+//! block the value was read at.  MARF proofs of older values refer back to the state roots of
+//! ancestor blocks, so the light client keeps every header it accepts, indexed by state root.
+//!
+//! The MARF has no proofs of absence, so a value that a peer claims does not exist cannot be
+//! verified.
+
+use std::collections::{BTreeSet, HashMap};
+use std::convert::{TryFrom, TryInto};
+use std::io::Cursor;
+
+use crate::chainstate::burn::db::sortdb::SortitionDB;
+use crate::chainstate::burn::{BlockSnapshot, ConsensusHash};
+use crate::chainstate::stacks::index::node::TriePath;
+use crate::chainstate::stacks::index::{MARFValue, TrieMerkleProof};
+use crate::chainstate::stacks::StacksBlockHeader;
+use crate::codec::StacksMessageCodec;
+use crate::core::FIRST_STACKS_BLOCK_ID;
+use crate::net::download::BlockDownloader;
+use crate::net::Error as net_error;
+use crate::net::{AccountEntryResponse, DataVarResponse, ExtendedStacksHeader, MapEntryResponse};
+use crate::net::{RPCMarfValue, RPCRewardSetProofs};
+use crate::types::chainstate::{StacksAddress, StacksBlockId, TrieHash};
+use crate::util_lib::boot::boot_code_id;
+use clarity::vm::database::{ClarityDatabase, ClaritySerializable, STXBalance, StoreType};
+use clarity::vm::types::{
+    BuffData, OptionalData, PrincipalData, QualifiedContractIdentifier, SequenceData, TupleData,
+    Value,
+};
+use clarity::vm::ClarityName;
+use stacks_common::address::AddressHashMode;
+use stacks_common::util::hash::{hex_bytes, Hash160};
+
+/// Size of a serialized `STXBalance`: two u128s and a u64
+const STX_BALANCE_SIZE: usize = 16 + 16 + 8;
+
+/// A reward cycle's reward set, as read from PoX state proven against its anchor block
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProvenRewardSet {
+    /// the registered PoX addresses and the uSTX stacked towards each (empty if PoX is not
+    /// active in the reward cycle)
+    pub registered_addrs: Vec<(StacksAddress, u128)>,
+    pub liquid_ustx: u128,
+    pub is_pox_active: bool,
+    /// keys whose values were proven
+    pub proven_keys: Vec<String>,
+    /// keys the peer claimed have no value.  These cannot be proven, so they are only as good
+    /// as the peer's word.
+    pub absent_keys: Vec<String>,
+}
+
+/// Get a field of a data map entry (i.e. `(some (tuple ...))`, as stored).  Returns Ok(None) if
+/// there is no entry.
+pub fn get_map_entry_field(entry: Value, field: &str) -> Result<Option<Value>, net_error> {
+    match entry {
+        Value::Optional(OptionalData { data: None }) => Ok(None),
+        Value::Optional(OptionalData { data: Some(inner) }) => match *inner {
+            Value::Tuple(tuple) => tuple
+                .get_owned(field)
+                .map(Some)
+                .map_err(|_| net_error::DeserializeError(format!("No '{}' in map entry", field))),
+            _ => Err(net_error::DeserializeError(
+                "Map entry is not a tuple".into(),
+            )),
+        },
+        _ => Err(net_error::DeserializeError(
+            "Map entry is not an optional".into(),
+        )),
+    }
+}
+
+pub struct LightClient {
+    /// every header we have accepted, by index block hash
+    headers: HashMap<StacksBlockId, ExtendedStacksHeader>,
+    /// maps each accepted header's state root back to its index block hash, for checking MARF
+    /// proofs.  Includes the boot block's state root.
+    root_to_block: HashMap<TrieHash, StacksBlockId>,
+    /// accepted headers that no accepted header builds on
+    leaves: BTreeSet<StacksBlockId>,
+    /// the accepted header with the most work on the canonical burnchain fork
+    tip: Option<StacksBlockId>,
+}
+
+impl LightClient {
+    /// Instantiate a light client that knows only the boot block, whose MARF root hash is given.
+    pub fn new(boot_state_root: TrieHash) -> LightClient {
+        let mut root_to_block = HashMap::new();
+        root_to_block.insert(boot_state_root, FIRST_STACKS_BLOCK_ID.clone());
+        LightClient {
+            headers: HashMap::new(),
+            root_to_block,
+            leaves: BTreeSet::new(),
+            tip: None,
+        }
+    }
+
+    /// Get the index block hash of the accepted header with the most work on the canonical
+    /// burnchain fork, if we have any.
+    pub fn get_tip(&self) -> Option<&StacksBlockId> {
+        self.tip.as_ref()
+    }
+
+    /// Get the Stacks block height of our tip (0 if we only know the boot block)
+    pub fn get_tip_height(&self) -> u64 {
+        self.tip
+            .as_ref()
+            .and_then(|tip| self.headers.get(tip))
+            .map(|ext_header| ext_header.header.total_work.work)
+            .unwrap_or(0)
+    }
+
+    pub fn get_header(&self, index_block_hash: &StacksBlockId) -> Option<&ExtendedStacksHeader> {
+        self.headers.get(index_block_hash)
+    }
+
+    /// Do we know this block?  The boot block is always known.
+    pub fn has_header(&self, index_block_hash: &StacksBlockId) -> bool {
+        *index_block_hash == *FIRST_STACKS_BLOCK_ID || self.headers.contains_key(index_block_hash)
+    }
+
+    pub fn num_headers(&self) -> usize {
+        self.headers.len()
+    }
+
+    fn add_header(&mut self, index_block_hash: StacksBlockId, ext_header: ExtendedStacksHeader) {
+        self.leaves.remove(&ext_header.parent_block_id);
+        self.leaves.insert(index_block_hash.clone());
+        self.root_to_block.insert(
+            ext_header.header.state_index_root.clone(),
+            index_block_hash.clone(),
+        );
+        self.headers.insert(index_block_hash, ext_header);
+    }
+
+    /// Accept a header chain served for `tip`, as returned by `/v2/headers` (i.e. tip first).
+    /// The chain must be valid in our sortition DB, and its oldest header's parent must already
+    /// be known to us.  Returns the number of new headers accepted.
+    pub fn accept_headers(
+        &mut self,
+        sortdb: &SortitionDB,
+        tip: &StacksBlockId,
+        num_headers: u64,
+        headers: Vec<ExtendedStacksHeader>,
+    ) -> Result<usize, net_error> {
+        if !BlockDownloader::validate_header_chain(sortdb, tip, num_headers, &headers)? {
+            debug!("Light client: invalid header chain for {}", tip);
+            return Err(net_error::InvalidMessage);
+        }
+
+        // validate_header_chain() guarantees that there is at least one header
+        let oldest_parent = &headers[headers.len() - 1].parent_block_id;
+        if !self.has_header(oldest_parent) {
+            debug!(
+                "Light client: header chain for {} does not connect to {}",
+                tip, oldest_parent
+            );
+            return Err(net_error::InvalidMessage);
+        }
+
+        let mut num_new = 0;
+        for ext_header in headers.into_iter().rev() {
+            let index_block_hash = StacksBlockHeader::make_index_block_hash(
+                &ext_header.consensus_hash,
+                &ext_header.header.block_hash(),
+            );
+            if self.headers.contains_key(&index_block_hash) {
+                continue;
+            }
+            self.add_header(index_block_hash, ext_header);
+            num_new += 1;
+        }
+        self.update_tip(sortdb)?;
+        Ok(num_new)
+    }
+
+    /// Re-choose our tip against the sortition DB's canonical burnchain fork.  This must be
+    /// called whenever the burnchain tip changes, since a burnchain reorg can orphan the
+    /// sortitions our tip's ancestors won.
+    pub fn update_tip(&mut self, sortdb: &SortitionDB) -> Result<(), net_error> {
+        let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn())?;
+        self.choose_tip(|consensus_hash| {
+            LightClient::is_on_canonical_fork(sortdb, &burn_tip, consensus_hash)
+        })
+    }
+
+    /// Is the sortition with this consensus hash an ancestor of the canonical burnchain tip?
+    fn is_on_canonical_fork(
+        sortdb: &SortitionDB,
+        burn_tip: &BlockSnapshot,
+        consensus_hash: &ConsensusHash,
+    ) -> Result<bool, net_error> {
+        let sn = match SortitionDB::get_block_snapshot_consensus(sortdb.conn(), consensus_hash)? {
+            Some(sn) => sn,
+            None => return Ok(false),
+        };
+        let ancestor = SortitionDB::get_ancestor_snapshot(
+            &sortdb.index_conn(),
+            sn.block_height,
+            &burn_tip.sortition_id,
+        )?;
+        Ok(ancestor
+            .map(|ancestor| ancestor.sortition_id == sn.sortition_id)
+            .unwrap_or(false))
+    }
+
+    /// Choose the accepted header with the most work whose sortition is canonical, according to
+    /// `is_canonical`.  Since a header's ancestors won earlier sortitions on the same burnchain
+    /// fork, we only need to walk back from each leaf until we find one that is canonical.
+    fn choose_tip<F>(&mut self, mut is_canonical: F) -> Result<(), net_error>
+    where
+        F: FnMut(&ConsensusHash) -> Result<bool, net_error>,
+    {
+        let mut best: Option<(u64, StacksBlockId)> = None;
+        for leaf in self.leaves.iter() {
+            let mut cursor = leaf;
+            while let Some(ext_header) = self.headers.get(cursor) {
+                let work = ext_header.header.total_work.work;
+                if let Some((best_work, _)) = best.as_ref() {
+                    // ancestors have less work, so none of them can do better
+                    if work <= *best_work {
+                        break;
+                    }
+                }
+                if is_canonical(&ext_header.consensus_hash)? {
+                    best = Some((work, cursor.clone()));
+                    break;
+                }
+                cursor = &ext_header.parent_block_id;
+            }
+        }
+
+        let tip = best.map(|(_, tip)| tip);
+        if tip != self.tip {
+            debug!("Light client: tip is now {:?}", &tip);
+        }
+        self.tip = tip;
+        Ok(())
+    }
+
+    /// Check that `value` is stored under `key` as of the block `tip`, given the hex-encoded
+    /// MARF proof (with or without a `0x` prefix) a peer served for it.  Returns NotFoundError if
+    /// we don't have the header for `tip`, or if there is no proof (i.e. the peer says there is no
+    /// such value).
+    pub fn verify_marf_value(
+        &self,
+        tip: &StacksBlockId,
+        key: &str,
+        value: &str,
+        proof_hex: &str,
+    ) -> Result<(), net_error> {
+        let header = self.headers.get(tip).ok_or(net_error::NotFoundError)?;
+
+        let proof_hex = proof_hex.strip_prefix("0x").unwrap_or(proof_hex);
+        if proof_hex.len() == 0 {
+            return Err(net_error::NotFoundError);
+        }
+
+        let proof_bytes = hex_bytes(proof_hex)
+            .map_err(|_| net_error::DeserializeError("Invalid MARF proof hex".into()))?;
+        let proof = TrieMerkleProof::<StacksBlockId>::consensus_deserialize(&mut Cursor::new(
+            &proof_bytes,
+        ))?;
+
+        if !proof.verify(
+            &TriePath::from_key(key),
+            &MARFValue::from_value(value),
+            &header.header.state_index_root,
+            &self.root_to_block,
+        ) {
+            return Err(net_error::VerifyingError(format!(
+                "Invalid proof for '{}' at {}",
+                key, tip
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check an account's balance and nonce, as served by a full peer with proofs.  Accounts that
+    /// have received STX but never sent a transaction have no stored nonce, so a nonce of 0 with
+    /// no proof is taken as-is.
+    pub fn verify_account(
+        &self,
+        tip: &StacksBlockId,
+        account: &PrincipalData,
+        entry: &AccountEntryResponse,
+    ) -> Result<(), net_error> {
+        let balance_proof = entry.balance_proof.as_ref().ok_or_else(|| {
+            net_error::VerifyingError(format!("No balance proof for {}", account))
+        })?;
+        let nonce_proof = entry
+            .nonce_proof
+            .as_ref()
+            .ok_or_else(|| net_error::VerifyingError(format!("No nonce proof for {}", account)))?;
+        let balance_data = entry.balance_data.as_deref().unwrap_or("");
+
+        self.verify_marf_value(
+            tip,
+            &ClarityDatabase::make_key_for_account_balance(account),
+            balance_data,
+            balance_proof,
+        )?;
+
+        let no_nonce = nonce_proof.strip_prefix("0x").unwrap_or(nonce_proof).len() == 0;
+        if !(no_nonce && entry.nonce == 0) {
+            self.verify_marf_value(
+                tip,
+                &ClarityDatabase::make_key_for_account_nonce(account),
+                &entry.nonce.serialize(),
+                nonce_proof,
+            )?;
+        }
+
+        let balance = LightClient::decode_stx_balance(balance_data)?;
+        if !LightClient::is_balance_consistent(&balance, entry) {
+            return Err(net_error::VerifyingError(format!(
+                "Balance for {} does not match its proof",
+                account
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check a data var's value, as served by a full peer with a proof.
+    pub fn verify_data_var(
+        &self,
+        tip: &StacksBlockId,
+        contract_identifier: &QualifiedContractIdentifier,
+        var_name: &ClarityName,
+        entry: &DataVarResponse,
+    ) -> Result<(), net_error> {
+        let key =
+            ClarityDatabase::make_key_for_trip(contract_identifier, StoreType::Variable, var_name);
+        self.verify_clarity_value(tip, &key, &entry.data, &entry.marf_proof)
+    }
+
+    /// Check a data map entry, as served by a full peer with a proof.
+    pub fn verify_map_entry(
+        &self,
+        tip: &StacksBlockId,
+        contract_identifier: &QualifiedContractIdentifier,
+        map_name: &ClarityName,
+        map_key: &Value,
+        entry: &MapEntryResponse,
+    ) -> Result<(), net_error> {
+        let key =
+            ClarityDatabase::make_key_for_data_map_entry(contract_identifier, map_name, map_key);
+        self.verify_clarity_value(tip, &key, &entry.data, &entry.marf_proof)
+    }
+
+    /// Clarity values are served as `0x` followed by the hex string they are stored as.
+    fn verify_clarity_value(
+        &self,
+        tip: &StacksBlockId,
+        key: &str,
+        data: &str,
+        marf_proof: &Option<String>,
+    ) -> Result<(), net_error> {
+        let proof = marf_proof
+            .as_ref()
+            .ok_or_else(|| net_error::VerifyingError(format!("No proof for '{}'", key)))?;
+        let value = data.strip_prefix("0x").unwrap_or(data);
+        self.verify_marf_value(tip, key, value, proof)
+    }
+
+    /// Check a reward set's PoX state, as served by a full peer with proofs, against the state
+    /// root of the anchor block it was read at, and compute the reward set from it the way the
+    /// PoX contracts do.  `pox_contracts` are the PoX contracts active at the anchor block.
+    pub fn verify_reward_set(
+        &self,
+        anchor_block_id: &StacksBlockId,
+        mainnet: bool,
+        pox_contracts: &[&str],
+        reward_cycle: u64,
+        proofs: &RPCRewardSetProofs,
+    ) -> Result<ProvenRewardSet, net_error> {
+        if !self.headers.contains_key(anchor_block_id) {
+            return Err(net_error::NotFoundError);
+        }
+        if proofs.pox_contracts.len() != pox_contracts.len() {
+            return Err(net_error::VerifyingError(format!(
+                "Expected state for {} PoX contract(s), got {}",
+                pox_contracts.len(),
+                proofs.pox_contracts.len()
+            )));
+        }
+
+        let mut proven_keys = vec![];
+        let mut absent_keys = vec![];
+        let mut verify = |key: String, proven: &RPCMarfValue| -> Result<Option<Value>, net_error> {
+            let value = self.verify_proven_value(anchor_block_id, &key, proven)?;
+            if value.is_some() {
+                proven_keys.push(key);
+            } else {
+                absent_keys.push(key);
+            }
+            Ok(value)
+        };
+
+        let liquid_ustx = match verify(
+            ClarityDatabase::ustx_liquid_supply_key().to_string(),
+            &proofs.liquid_ustx,
+        )? {
+            Some(Value::UInt(liquid_ustx)) => liquid_ustx,
+            None => 0,
+            Some(_) => return Err(net_error::VerifyingError("Invalid liquid supply".into())),
+        };
+
+        let cycle_key = Value::Tuple(
+            TupleData::from_data(vec![(
+                "reward-cycle".into(),
+                Value::UInt(reward_cycle as u128),
+            )])
+            .expect("FATAL: failed to construct reward cycle tuple"),
+        );
+        let mut is_pox_active = true;
+        let mut registered_addrs = vec![];
+        for (pox_contract, contract_proofs) in pox_contracts.iter().zip(proofs.pox_contracts.iter())
+        {
+            if contract_proofs.contract != *pox_contract {
+                return Err(net_error::VerifyingError(format!(
+                    "Expected state for {}, got {}",
+                    pox_contract, &contract_proofs.contract
+                )));
+            }
+            let contract_id = boot_code_id(pox_contract, mainnet);
+
+            // (is-pox-active reward-cycle)
+            let rejection_fraction = match verify(
+                ClarityDatabase::make_key_for_trip(
+                    &contract_id,
+                    StoreType::Variable,
+                    "pox-rejection-fraction",
+                ),
+                &contract_proofs.rejection_fraction,
+            )? {
+                Some(Value::UInt(fraction)) => fraction,
+                _ => {
+                    return Err(net_error::VerifyingError(format!(
+                        "Invalid rejection fraction for {}",
+                        &contract_id
+                    )))
+                }
+            };
+            let rejection_votes = verify(
+                ClarityDatabase::make_key_for_data_map_entry(
+                    &contract_id,
+                    "stacking-rejection",
+                    &cycle_key,
+                ),
+                &contract_proofs.rejection_votes,
+            )?
+            .map(|entry| get_map_entry_field(entry, "amount"))
+            .transpose()?
+            .flatten();
+            let rejection_votes = match rejection_votes {
+                Some(Value::UInt(votes)) => votes,
+                None => 0,
+                Some(_) => {
+                    return Err(net_error::VerifyingError(format!(
+                        "Invalid rejection votes for {}",
+                        &contract_id
+                    )))
+                }
+            };
+            if rejection_votes.saturating_mul(100) >= rejection_fraction.saturating_mul(liquid_ustx)
+            {
+                is_pox_active = false;
+            }
+
+            // (get-reward-set-size reward-cycle)
+            let num_entries = verify(
+                ClarityDatabase::make_key_for_data_map_entry(
+                    &contract_id,
+                    "reward-cycle-pox-address-list-len",
+                    &cycle_key,
+                ),
+                &contract_proofs.num_entries,
+            )?
+            .map(|entry| get_map_entry_field(entry, "len"))
+            .transpose()?
+            .flatten();
+            let num_entries = match num_entries {
+                Some(Value::UInt(len)) => len,
+                None => 0,
+                Some(_) => {
+                    return Err(net_error::VerifyingError(format!(
+                        "Invalid reward set size for {}",
+                        &contract_id
+                    )))
+                }
+            };
+            if num_entries != contract_proofs.entries.len() as u128 {
+                return Err(net_error::VerifyingError(format!(
+                    "Expected {} reward set entries for {}, got {}",
+                    num_entries,
+                    &contract_id,
+                    contract_proofs.entries.len()
+                )));
+            }
+
+            // (get-reward-set-pox-address reward-cycle index)
+            for (index, proven_entry) in contract_proofs.entries.iter().enumerate() {
+                let entry_key = Value::Tuple(
+                    TupleData::from_data(vec![
+                        ("reward-cycle".into(), Value::UInt(reward_cycle as u128)),
+                        ("index".into(), Value::UInt(index as u128)),
+                    ])
+                    .expect("FATAL: failed to construct reward set entry tuple"),
+                );
+                let entry = verify(
+                    ClarityDatabase::make_key_for_data_map_entry(
+                        &contract_id,
+                        "reward-cycle-pox-address-list",
+                        &entry_key,
+                    ),
+                    proven_entry,
+                )?
+                .ok_or_else(|| {
+                    net_error::VerifyingError(format!(
+                        "No proof for reward set entry {} in {}",
+                        index, &contract_id
+                    ))
+                })?;
+                registered_addrs.push(LightClient::decode_reward_set_entry(entry, mainnet)?);
+            }
+        }
+
+        if !is_pox_active {
+            registered_addrs.clear();
+        }
+        Ok(ProvenRewardSet {
+            registered_addrs,
+            liquid_ustx,
+            is_pox_active,
+            proven_keys,
+            absent_keys,
+        })
+    }
+
+    /// Check a served MARF value, and decode it.  Returns Ok(None) if the peer says there is no
+    /// such value.
+    fn verify_proven_value(
+        &self,
+        tip: &StacksBlockId,
+        key: &str,
+        proven: &RPCMarfValue,
+    ) -> Result<Option<Value>, net_error> {
+        let data = proven.data.strip_prefix("0x").unwrap_or(&proven.data);
+        match self.verify_marf_value(tip, key, data, &proven.marf_proof) {
+            Ok(()) => Value::try_deserialize_hex_untyped(data)
+                .map(Some)
+                .map_err(|_| net_error::DeserializeError(format!("Invalid value for '{}'", key))),
+            Err(net_error::NotFoundError) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Decode a `reward-cycle-pox-address-list` entry into its PoX address and stacked uSTX
+    fn decode_reward_set_entry(
+        entry: Value,
+        mainnet: bool,
+    ) -> Result<(StacksAddress, u128), net_error> {
+        let invalid = || net_error::DeserializeError("Invalid reward set entry".into());
+        let pox_addr = match get_map_entry_field(entry.clone(), "pox-addr")? {
+            Some(Value::Tuple(pox_addr)) => pox_addr,
+            _ => return Err(invalid()),
+        };
+        let total_ustx = match get_map_entry_field(entry, "total-ustx")? {
+            Some(Value::UInt(total_ustx)) => total_ustx,
+            _ => return Err(invalid()),
+        };
+
+        // buffers shorter than their declared size are zero-padded, as in `tuple_to_pox_addr()`
+        let buff = |name: &str, len: usize| match pox_addr.get(name) {
+            Ok(Value::Sequence(SequenceData::Buffer(BuffData { data }))) if data.len() <= len => {
+                let mut data = data.clone();
+                data.resize(len, 0);
+                Ok(data)
+            }
+            _ => Err(invalid()),
+        };
+        let version = AddressHashMode::try_from(buff("version", 1)?[0]).map_err(|_| invalid())?;
+        let hashbytes = Hash160::from_bytes(&buff("hashbytes", 20)?).ok_or_else(invalid)?;
+        let version = if mainnet {
+            version.to_version_mainnet()
+        } else {
+            version.to_version_testnet()
+        };
+        Ok((StacksAddress::new(version, hashbytes), total_ustx))
+    }
+
+    fn decode_stx_balance(balance_data: &str) -> Result<STXBalance, net_error> {
+        let bytes = hex_bytes(balance_data)
+            .map_err(|_| net_error::DeserializeError("Invalid balance hex".into()))?;
+        if bytes.len() != STX_BALANCE_SIZE {
+            return Err(net_error::DeserializeError(format!(
+                "Invalid balance length {}",
+                bytes.len()
+            )));
+        }
+        Ok(STXBalance {
+            amount_unlocked: u128::from_be_bytes(bytes[0..16].try_into().unwrap()),
+            amount_locked: u128::from_be_bytes(bytes[16..32].try_into().unwrap()),
+            unlock_height: u64::from_be_bytes(bytes[32..40].try_into().unwrap()),
+        })
+    }
+
+    /// The served balance depends on the burnchain height the peer read it at: if the lock has
+    /// expired, the locked amount is reported as unlocked.  Either way, it must agree with the
+    /// stored balance record.
+    fn is_balance_consistent(balance: &STXBalance, entry: &AccountEntryResponse) -> bool {
+        let parse_u128 = |s: &str| {
+            hex_bytes(s.strip_prefix("0x").unwrap_or(s))
+                .ok()
+                .and_then(|bytes| bytes.as_slice().try_into().ok())
+                .map(u128::from_be_bytes)
+        };
+        let (unlocked, locked) = match (parse_u128(&entry.balance), parse_u128(&entry.locked)) {
+            (Some(unlocked), Some(locked)) => (unlocked, locked),
+            _ => return false,
+        };
+
+        if locked == 0 && entry.unlock_height == 0 {
+            Some(unlocked) == balance.amount_unlocked.checked_add(balance.amount_locked)
+        } else {
+            unlocked == balance.amount_unlocked
+                && locked == balance.amount_locked
+                && entry.unlock_height == balance.unlock_height
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::chainstate::stacks::index::marf::{MARFOpenOpts, MARF};
+    use crate::chainstate::stacks::index::ClarityMarfTrieId;
+    use crate::net::RPCPoxContractProofs;
+    use clarity::vm::types::StandardPrincipalData;
+    use stacks_common::util::hash::to_hex;
+
+    /// Make a MARF with the boot block and two blocks on top of it, and a light client that
+    /// knows headers for them (keyed by the MARF's block IDs).
+    fn make_marf_and_light_client(
+        kvs: &[Vec<(String, String)>],
+    ) -> (MARF<StacksBlockId>, LightClient, Vec<StacksBlockId>) {
+        let mut marf: MARF<StacksBlockId> =
+            MARF::from_path(":memory:", MARFOpenOpts::default()).unwrap();
+
+        marf.begin(&StacksBlockId::sentinel(), &FIRST_STACKS_BLOCK_ID)
+            .unwrap();
+        marf.insert("boot", MARFValue::from_value("boot-value"))
+            .unwrap();
+        marf.commit().unwrap();
+
+        let boot_root = marf.get_root_hash_at(&FIRST_STACKS_BLOCK_ID).unwrap();
+        let mut light_client = LightClient::new(boot_root);
+
+        let mut parent = FIRST_STACKS_BLOCK_ID.clone();
+        let mut block_ids = vec![];
+        for (i, block_kvs) in kvs.iter().enumerate() {
+            let block_id = StacksBlockId([(i + 1) as u8; 32]);
+            marf.begin(&parent, &block_id).unwrap();
+            for (k, v) in block_kvs.iter() {
+                marf.insert(k, MARFValue::from_value(v)).unwrap();
+            }
+            marf.commit().unwrap();
+
+            let mut header = StacksBlockHeader::genesis_block_header();
+            header.total_work.work = (i + 1) as u64;
+            header.state_index_root = marf.get_root_hash_at(&block_id).unwrap();
+            light_client.add_header(
+                block_id.clone(),
+                ExtendedStacksHeader {
+                    consensus_hash: ConsensusHash([(i + 1) as u8; 20]),
+                    header,
+                    parent_block_id: parent.clone(),
+                },
+            );
+
+            parent = block_id.clone();
+            block_ids.push(block_id);
+        }
+        light_client.choose_tip(|_| Ok(true)).unwrap();
+        (marf, light_client, block_ids)
+    }
+
+    fn get_proof_hex(marf: &mut MARF<StacksBlockId>, tip: &StacksBlockId, key: &str) -> String {
+        let (_, proof) = marf.get_with_proof(tip, key).unwrap().unwrap();
+        format!("0x{}", to_hex(&proof.serialize_to_vec()))
+    }
+
+    #[test]
+    fn test_light_client_tip() {
+        let (_, light_client, block_ids) =
+            make_marf_and_light_client(&[vec![("a".into(), "1".into())], vec![]]);
+        assert_eq!(light_client.get_tip(), Some(&block_ids[1]));
+        assert_eq!(light_client.get_tip_height(), 2);
+        assert_eq!(light_client.num_headers(), 2);
+        assert!(light_client.has_header(&FIRST_STACKS_BLOCK_ID));
+        assert!(light_client.has_header(&block_ids[0]));
+        assert!(!light_client.has_header(&StacksBlockId([0xfe; 32])));
+    }
+
+    #[test]
+    fn test_light_client_tip_on_canonical_fork() {
+        let mut light_client = LightClient::new(TrieHash([0x00; 32]));
+        let mut add = |id: u8, parent: &StacksBlockId, work: u64| {
+            let mut header = StacksBlockHeader::genesis_block_header();
+            header.total_work.work = work;
+            header.state_index_root = TrieHash([id; 32]);
+            let block_id = StacksBlockId([id; 32]);
+            light_client.add_header(
+                block_id.clone(),
+                ExtendedStacksHeader {
+                    consensus_hash: ConsensusHash([id; 20]),
+                    header,
+                    parent_block_id: parent.clone(),
+                },
+            );
+            block_id
+        };
+
+        // a <- b <- e
+        //  \
+        //   c <- d <- f
+        let a = add(1, &FIRST_STACKS_BLOCK_ID, 1);
+        let b = add(2, &a, 2);
+        let e = add(5, &b, 3);
+        let c = add(3, &a, 2);
+        let d = add(4, &c, 3);
+        let f = add(6, &d, 4);
+
+        // the longest fork wins if all of its sortitions are canonical
+        light_client.choose_tip(|_| Ok(true)).unwrap();
+        assert_eq!(light_client.get_tip(), Some(&f));
+
+        // if the burnchain reorgs away the sortitions of d and f, the other fork wins
+        let orphaned = vec![ConsensusHash([4; 20]), ConsensusHash([6; 20])];
+        light_client
+            .choose_tip(|ch| Ok(!orphaned.contains(ch)))
+            .unwrap();
+        assert_eq!(light_client.get_tip(), Some(&e));
+
+        // if e's sortition is orphaned too, we fall back to the best canonical ancestor
+        let orphaned = vec![
+            ConsensusHash([4; 20]),
+            ConsensusHash([5; 20]),
+            ConsensusHash([6; 20]),
+        ];
+        light_client
+            .choose_tip(|ch| Ok(!orphaned.contains(ch)))
+            .unwrap();
+        assert!(light_client.get_tip() == Some(&b) || light_client.get_tip() == Some(&c));
+        assert_eq!(light_client.get_tip_height(), 2);
+
+        // nothing is canonical
+        light_client.choose_tip(|_| Ok(false)).unwrap();
+        assert_eq!(light_client.get_tip(), None);
+        assert_eq!(light_client.get_tip_height(), 0);
+    }
+
+    #[test]
+    fn test_light_client_verify_marf_value() {
+        let (mut marf, light_client, block_ids) = make_marf_and_light_client(&[
+            vec![("a".into(), "1".into()), ("b".into(), "2".into())],
+            vec![("a".into(), "3".into())],
+        ]);
+
+        // a value written in the tip block
+        let proof = get_proof_hex(&mut marf, &block_ids[1], "a");
+        light_client
+            .verify_marf_value(&block_ids[1], "a", "3", &proof)
+            .unwrap();
+        assert!(light_client
+            .verify_marf_value(&block_ids[1], "a", "1", &proof)
+            .is_err());
+
+        // a value written in an ancestor block is proven through its state root
+        let proof = get_proof_hex(&mut marf, &block_ids[1], "b");
+        light_client
+            .verify_marf_value(&block_ids[1], "b", "2", &proof)
+            .unwrap();
+
+        // a value written in the boot block
+        let proof = get_proof_hex(&mut marf, &block_ids[1], "boot");
+        light_client
+            .verify_marf_value(&block_ids[1], "boot", "boot-value", &proof)
+            .unwrap();
+
+        // a proof for the ancestor's value doesn't check out against the tip
+        let proof = get_proof_hex(&mut marf, &block_ids[0], "a");
+        light_client
+            .verify_marf_value(&block_ids[0], "a", "1", &proof)
+            .unwrap();
+        assert!(light_client
+            .verify_marf_value(&block_ids[1], "a", "1", &proof)
+            .is_err());
+
+        // no proof, bad proof, or unknown block
+        assert!(light_client
+            .verify_marf_value(&block_ids[1], "a", "3", "")
+            .is_err());
+        assert!(light_client
+            .verify_marf_value(&block_ids[1], "a", "3", "0xzz")
+            .is_err());
+        let proof = get_proof_hex(&mut marf, &block_ids[1], "a");
+        match light_client.verify_marf_value(&StacksBlockId([0xfe; 32]), "a", "3", &proof) {
+            Err(net_error::NotFoundError) => {}
+            x => panic!("Expected NotFoundError, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_light_client_verify_account() {
+        let principal: PrincipalData = StandardPrincipalData(1, [0x01; 20]).into();
+        let balance = STXBalance {
+            amount_unlocked: 100,
+            amount_locked: 50,
+            unlock_height: 200,
+        };
+        let nonce: u64 = 7;
+
+        let balance_key = ClarityDatabase::make_key_for_account_balance(&principal);
+        let nonce_key = ClarityDatabase::make_key_for_account_nonce(&principal);
+        // this account has received STX, but never sent a transaction
+        let recipient: PrincipalData = StandardPrincipalData(1, [0x02; 20]).into();
+        let recipient_balance_key = ClarityDatabase::make_key_for_account_balance(&recipient);
+        let recipient_balance = STXBalance {
+            amount_unlocked: 10,
+            amount_locked: 0,
+            unlock_height: 0,
+        };
+
+        let (mut marf, light_client, block_ids) = make_marf_and_light_client(&[vec![
+            (balance_key.clone(), balance.serialize()),
+            (nonce_key.clone(), nonce.serialize()),
+            (recipient_balance_key.clone(), recipient_balance.serialize()),
+        ]]);
+        let tip = &block_ids[0];
+
+        let entry = AccountEntryResponse {
+            balance: format!("0x{}", to_hex(&100u128.to_be_bytes())),
+            locked: format!("0x{}", to_hex(&50u128.to_be_bytes())),
+            unlock_height: 200,
+            nonce,
+            balance_proof: Some(get_proof_hex(&mut marf, tip, &balance_key)),
+            nonce_proof: Some(get_proof_hex(&mut marf, tip, &nonce_key)),
+            balance_data: Some(balance.serialize()),
+        };
+        light_client
+            .verify_account(tip, &principal, &entry)
+            .unwrap();
+
+        // once the lock expires, the locked amount is reported as unlocked
+        let mut unlocked_entry = entry.clone();
+        unlocked_entry.balance = format!("0x{}", to_hex(&150u128.to_be_bytes()));
+        unlocked_entry.locked = format!("0x{}", to_hex(&0u128.to_be_bytes()));
+        unlocked_entry.unlock_height = 0;
+        light_client
+            .verify_account(tip, &principal, &unlocked_entry)
+            .unwrap();
+
+        // lying about the balance, the nonce, or the balance record is caught
+        let mut bad_entry = entry.clone();
+        bad_entry.balance = format!("0x{}", to_hex(&1000u128.to_be_bytes()));
+        assert!(light_client
+            .verify_account(tip, &principal, &bad_entry)
+            .is_err());
+
+        let mut bad_entry = entry.clone();
+        bad_entry.nonce = 8;
+        assert!(light_client
+            .verify_account(tip, &principal, &bad_entry)
+            .is_err());
+
+        let mut bad_entry = entry.clone();
+        bad_entry.balance_data = Some(
+            STXBalance {
+                amount_unlocked: 1000,
+                amount_locked: 50,
+                unlock_height: 200,
+            }
+            .serialize(),
+        );
+        assert!(light_client
+            .verify_account(tip, &principal, &bad_entry)
+            .is_err());
+
+        let mut bad_entry = entry.clone();
+        bad_entry.balance_data = None;
+        assert!(light_client
+            .verify_account(tip, &principal, &bad_entry)
+            .is_err());
+
+        // the recipient has no nonce record
+        let recipient_entry = AccountEntryResponse {
+            balance: format!("0x{}", to_hex(&10u128.to_be_bytes())),
+            locked: format!("0x{}", to_hex(&0u128.to_be_bytes())),
+            unlock_height: 0,
+            nonce: 0,
+            balance_proof: Some(get_proof_hex(&mut marf, tip, &recipient_balance_key)),
+            nonce_proof: Some("".into()),
+            balance_data: Some(recipient_balance.serialize()),
+        };
+        light_client
+            .verify_account(tip, &recipient, &recipient_entry)
+            .unwrap();
+
+        let mut bad_entry = recipient_entry.clone();
+        bad_entry.nonce = 1;
+        assert!(light_client
+            .verify_account(tip, &recipient, &bad_entry)
+            .is_err());
+
+        // an account with no balance record can't be verified
+        let mut absent_entry = entry.clone();
+        absent_entry.balance_proof = Some("".into());
+        absent_entry.balance_data = None;
+        match light_client.verify_account(tip, &principal, &absent_entry) {
+            Err(net_error::NotFoundError) => {}
+            x => panic!("Expected NotFoundError, got {:?}", x),
+        }
+    }
+
+    #[test]
+    fn test_light_client_verify_data_var_and_map_entry() {
+        let contract_id = QualifiedContractIdentifier::local("test-contract").unwrap();
+        let var_name = ClarityName::try_from("counter".to_string()).unwrap();
+        let map_name = ClarityName::try_from("balances".to_string()).unwrap();
+        let map_key = Value::UInt(1);
+
+        let var_key =
+            ClarityDatabase::make_key_for_trip(&contract_id, StoreType::Variable, &var_name);
+        let entry_key =
+            ClarityDatabase::make_key_for_data_map_entry(&contract_id, &map_name, &map_key);
+        let var_value = Value::Int(42);
+        let entry_value = Value::some(Value::UInt(100)).unwrap();
+
+        let (mut marf, light_client, block_ids) = make_marf_and_light_client(&[vec![
+            (var_key.clone(), var_value.serialize()),
+            (entry_key.clone(), entry_value.serialize()),
+        ]]);
+        let tip = &block_ids[0];
+
+        let var_entry = DataVarResponse {
+            data: format!("0x{}", var_value.serialize()),
+            marf_proof: Some(get_proof_hex(&mut marf, tip, &var_key)),
+        };
+        light_client
+            .verify_data_var(tip, &contract_id, &var_name, &var_entry)
+            .unwrap();
+
+        let mut bad_var_entry = var_entry.clone();
+        bad_var_entry.data = format!("0x{}", Value::Int(43).serialize());
+        assert!(light_client
+            .verify_data_var(tip, &contract_id, &var_name, &bad_var_entry)
+            .is_err());
+
+        let map_entry = MapEntryResponse {
+            data: format!("0x{}", entry_value.serialize()),
+            marf_proof: Some(get_proof_hex(&mut marf, tip, &entry_key)),
+        };
+        light_client
+            .verify_map_entry(tip, &contract_id, &map_name, &map_key, &map_entry)
+            .unwrap();
+
+        // the proof is for a different key
+        assert!(light_client
+            .verify_map_entry(tip, &contract_id, &map_name, &Value::UInt(2), &map_entry)
+            .is_err());
+
+        // absent entries come back without a proof, and can't be verified
+        let absent_entry = MapEntryResponse {
+            data: format!("0x{}", Value::none().serialize()),
+            marf_proof: Some("".into()),
+        };
+        assert!(light_client
+            .verify_map_entry(tip, &contract_id, &map_name, &Value::UInt(2), &absent_entry)
+            .is_err());
+    }
+
+    #[test]
+    fn test_light_client_verify_reward_set() {
+        let mainnet = false;
+        let pox_id = boot_code_id("pox", mainnet);
+        let reward_cycle = 5;
+        let tuple = |fields: Vec<(&str, Value)>| {
+            Value::Tuple(
+                TupleData::from_data(
+                    fields
+                        .into_iter()
+                        .map(|(name, value)| (name.into(), value))
+                        .collect(),
+                )
+                .unwrap(),
+            )
+        };
+        let cycle_key = tuple(vec![("reward-cycle", Value::UInt(reward_cycle as u128))]);
+
+        let liquid_key = ClarityDatabase::ustx_liquid_supply_key().to_string();
+        let fraction_key = ClarityDatabase::make_key_for_trip(
+            &pox_id,
+            StoreType::Variable,
+            "pox-rejection-fraction",
+        );
+        let votes_key =
+            ClarityDatabase::make_key_for_data_map_entry(&pox_id, "stacking-rejection", &cycle_key);
+        let len_key = ClarityDatabase::make_key_for_data_map_entry(
+            &pox_id,
+            "reward-cycle-pox-address-list-len",
+            &cycle_key,
+        );
+        let entry_key = ClarityDatabase::make_key_for_data_map_entry(
+            &pox_id,
+            "reward-cycle-pox-address-list",
+            &tuple(vec![
+                ("reward-cycle", Value::UInt(reward_cycle as u128)),
+                ("index", Value::UInt(0)),
+            ]),
+        );
+        let entry_value = Value::some(tuple(vec![
+            (
+                "pox-addr",
+                tuple(vec![
+                    ("version", Value::buff_from(vec![0x00]).unwrap()),
+                    ("hashbytes", Value::buff_from(vec![0x01; 20]).unwrap()),
+                ]),
+            ),
+            ("total-ustx", Value::UInt(100)),
+        ]))
+        .unwrap();
+
+        // no rejection votes as of the first block; enough to disable PoX as of the second
+        let kvs = vec![
+            (liquid_key.clone(), Value::UInt(1000).serialize()),
+            (fraction_key.clone(), Value::UInt(25).serialize()),
+            (
+                len_key.clone(),
+                Value::some(tuple(vec![("len", Value::UInt(1))]))
+                    .unwrap()
+                    .serialize(),
+            ),
+            (entry_key.clone(), entry_value.serialize()),
+            (
+                votes_key.clone(),
+                Value::some(tuple(vec![("amount", Value::UInt(300))]))
+                    .unwrap()
+                    .serialize(),
+            ),
+        ];
+        let (mut marf, light_client, block_ids) =
+            make_marf_and_light_client(&[kvs[0..4].to_vec(), kvs[4..].to_vec()]);
+
+        let proven = |marf: &mut MARF<StacksBlockId>, tip: &StacksBlockId, key: &str| {
+            let (_, data) = kvs.iter().find(|(k, _)| k == key).unwrap();
+            RPCMarfValue {
+                data: format!("0x{}", data),
+                marf_proof: get_proof_hex(marf, tip, key),
+            }
+        };
+        let absent = || RPCMarfValue {
+            data: format!("0x{}", Value::none().serialize()),
+            marf_proof: "".into(),
+        };
+        let make_proofs =
+            |marf: &mut MARF<StacksBlockId>, tip: &StacksBlockId, votes: RPCMarfValue| {
+                RPCRewardSetProofs {
+                    liquid_ustx: proven(marf, tip, &liquid_key),
+                    pox_contracts: vec![RPCPoxContractProofs {
+                        contract: "pox".into(),
+                        rejection_fraction: proven(marf, tip, &fraction_key),
+                        rejection_votes: votes,
+                        num_entries: proven(marf, tip, &len_key),
+                        entries: vec![proven(marf, tip, &entry_key)],
+                    }],
+                }
+            };
+
+        let tip = &block_ids[0];
+        let proofs = make_proofs(&mut marf, tip, absent());
+        let reward_set = light_client
+            .verify_reward_set(tip, mainnet, &["pox"], reward_cycle, &proofs)
+            .unwrap();
+        assert!(reward_set.is_pox_active);
+        assert_eq!(reward_set.liquid_ustx, 1000);
+        assert_eq!(
+            reward_set.registered_addrs,
+            vec![(
+                StacksAddress::new(
+                    AddressHashMode::SerializeP2PKH.to_version_testnet(),
+                    Hash160([0x01; 20])
+                ),
+                100
+            )]
+        );
+        assert_eq!(reward_set.absent_keys, vec![votes_key.clone()]);
+        assert_eq!(reward_set.proven_keys.len(), 4);
+
+        // the wrong set of PoX contracts
+        assert!(light_client
+            .verify_reward_set(tip, mainnet, &["pox", "pox-2"], reward_cycle, &proofs)
+            .is_err());
+
+        // lying about an entry, or leaving one out
+        let mut bad_proofs = proofs.clone();
+        bad_proofs.pox_contracts[0].entries[0].data = format!(
+            "0x{}",
+            Value::some(tuple(vec![("total-ustx", Value::UInt(1000))]))
+                .unwrap()
+                .serialize()
+        );
+        assert!(light_client
+            .verify_reward_set(tip, mainnet, &["pox"], reward_cycle, &bad_proofs)
+            .is_err());
+
+        let mut bad_proofs = proofs.clone();
+        bad_proofs.pox_contracts[0].entries.clear();
+        assert!(light_client
+            .verify_reward_set(tip, mainnet, &["pox"], reward_cycle, &bad_proofs)
+            .is_err());
+
+        // a proof from a different reward cycle's key doesn't check out
+        assert!(light_client
+            .verify_reward_set(tip, mainnet, &["pox"], reward_cycle + 1, &proofs)
+            .is_err());
+
+        // hiding the reward set is only caught by comparing against a peer that proves it
+        let mut hiding_proofs = proofs.clone();
+        hiding_proofs.pox_contracts[0].num_entries = absent();
+        hiding_proofs.pox_contracts[0].entries.clear();
+        let hidden = light_client
+            .verify_reward_set(tip, mainnet, &["pox"], reward_cycle, &hiding_proofs)
+            .unwrap();
+        assert_eq!(hidden.registered_addrs, vec![]);
+        assert!(hidden.absent_keys.contains(&len_key));
+        assert!(reward_set.proven_keys.contains(&len_key));
+
+        // enough rejection votes disable PoX
+        let tip = &block_ids[1];
+        let votes = proven(&mut marf, tip, &votes_key);
+        let proofs = make_proofs(&mut marf, tip, votes);
+        let reward_set = light_client
+            .verify_reward_set(tip, mainnet, &["pox"], reward_cycle, &proofs)
+            .unwrap();
+        assert!(!reward_set.is_pox_active);
+        assert_eq!(reward_set.registered_addrs, vec![]);
+        assert_eq!(reward_set.absent_keys.len(), 0);
+    }
+}
@@ -46,7 +46,10 @@ use url;
 use crate::burnchains::Txid;
 use crate::chainstate::burn::ConsensusHash;
 use crate::chainstate::coordinator::Error as coordinator_error;
+use crate::chainstate::stacks::db::addrindex::{IndexCursor, PrincipalRole};
 use crate::chainstate::stacks::db::blocks::MemPoolRejection;
+use crate::chainstate::stacks::db::cost_voting::CostVotingReport;
+use crate::chainstate::stacks::db::eventlog::ContractEventFilter;
 use crate::chainstate::stacks::index::Error as marf_error;
 use crate::chainstate::stacks::Error as chainstate_error;
 use crate::chainstate::stacks::{
@@ -110,9 +113,16 @@ pub mod atlas;
 ///     * dealing with and responding to invalid messages
 ///     * rate limiting messages  
 pub mod chat;
+/// Implements `SessionCipher`, which encrypts and authenticates the byte stream of a p2p
+/// conversation once both peers have negotiated `ServiceFlags::ENCRYPTION`.
+pub mod cipher;
 /// Implements serialization and deserialization for `StacksMessage` types.
 /// Also has functionality to sign, verify, and ensure well-formedness of messages.
 pub mod codec;
+/// Implements compact block relay: building `CompactBlockData` from a block we have, and
+/// reconstructing pushed compact blocks from our mempool, asking the sender for whatever
+/// transactions we're missing.
+pub mod compact;
 pub mod connection;
 pub mod db;
 /// Implements `DNSResolver`, a simple DNS resolver state machine. Also implements `DNSClient`,
@@ -121,6 +131,9 @@ pub mod dns;
 pub mod download;
 pub mod http;
 pub mod inv;
+/// Implements `LightClient`, which follows the Stacks chain by headers alone and checks the MARF
+/// proofs full peers serve for chain state reads.
+pub mod light;
 pub mod neighbors;
 pub mod p2p;
 /// Implements wrapper around `mio` crate, which itself is a wrapper around Linux's `epoll(2)` syscall.
@@ -841,6 +854,8 @@ pub struct HandshakeData {
 pub enum ServiceFlags {
     RELAY = 0x01,
     RPC = 0x02,
+    ENCRYPTION = 0x04,
+    COMPACT_BLOCKS = 0x08,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -859,6 +874,7 @@ pub mod NackErrorCodes {
     pub const Throttled: u32 = 3;
     pub const InvalidPoxFork: u32 = 4;
     pub const InvalidMessage: u32 = 5;
+    pub const NoSuchBlock: u32 = 6;
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -878,6 +894,49 @@ pub struct NatPunchData {
     pub nonce: u32,
 }
 
+/// Sent by a peer to switch its side of the conversation to the encrypted transport.
+/// Everything the sender writes after this message is encrypted to the recipient's node key
+/// with this ephemeral public key (see `net::cipher`).
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionKeyData {
+    pub ephemeral_public_key: StacksPublicKeyBuffer,
+}
+
+/// A transaction sent inline in a compact block, along with its position in the block
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrefilledTransaction {
+    pub index: u16,
+    pub tx: StacksTransaction,
+}
+
+/// An anchored block, pushed as its header and a short tag for each of its transactions.
+/// The sender includes the transactions it expects the recipient won't have in its mempool
+/// (at least the coinbase); the recipient fills in the rest from its own mempool.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlockData {
+    pub consensus_hash: ConsensusHash,
+    pub header: StacksBlockHeader,
+    pub seed: [u8; 32],      // seed for the tx tags, chosen by the sender
+    pub tx_tags: Vec<TxTag>, // one tag per transaction, in block order
+    pub prefilled_txs: Vec<PrefilledTransaction>, // sorted by index
+}
+
+/// Ask the sender of a compact block for the transactions at the given (sorted) positions
+#[derive(Debug, Clone, PartialEq)]
+pub struct GetCompactBlockTxsData {
+    pub consensus_hash: ConsensusHash,
+    pub block_hash: BlockHeaderHash,
+    pub indexes: Vec<u16>,
+}
+
+/// Reply to a GetCompactBlockTxs, with the transactions in the order they were asked for
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlockTxsData {
+    pub consensus_hash: ConsensusHash,
+    pub block_hash: BlockHeaderHash,
+    pub txs: Vec<StacksTransaction>,
+}
+
 define_u8_enum!(MemPoolSyncDataID {
     BloomFilter = 0x01,
     TxTags = 0x02
@@ -917,6 +976,10 @@ pub enum StacksMessageType {
     Pong(PongData),
     NatPunchRequest(u32),
     NatPunchReply(NatPunchData),
+    SessionKey(SessionKeyData),
+    CompactBlock(CompactBlockData),
+    GetCompactBlockTxs(GetCompactBlockTxsData),
+    CompactBlockTxs(CompactBlockTxsData),
 }
 
 /// Peer address variants
@@ -1095,6 +1158,135 @@ pub struct RPCPoxInfoData {
     pub next_reward_cycle_in: u64,
 }
 
+/// A PoX reward address, as stored in the PoX contract and as rendered on the burnchain
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPoxAddress {
+    /// address hash mode byte
+    pub version: u8,
+    /// hex-encoded 20-byte hash
+    pub hashbytes: String,
+    /// base58check-encoded burnchain address, if the version is a supported hash mode
+    pub address: Option<String>,
+}
+
+/// A stacker's active lock-up, decoded from the PoX contract's `stacking-state` map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCStackingState {
+    pub amount_ustx: u64,
+    pub pox_address: RPCPoxAddress,
+    pub first_reward_cycle: u64,
+    pub lock_period: u64,
+}
+
+/// A stacker's active delegation, decoded from the PoX contract's `delegation-state` map
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCDelegationState {
+    pub amount_ustx: u64,
+    pub delegated_to: String,
+    pub until_burn_height: Option<u64>,
+    pub pox_address: Option<RPCPoxAddress>,
+}
+
+/// The data we return on GET /v2/pox/stackers/:principal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCStackerInfoData {
+    pub stacker: String,
+    pub burn_block_height: u64,
+    pub locked_ustx: u64,
+    pub unlock_height: u64,
+    pub stacking: Option<RPCStackingState>,
+    pub delegation: Option<RPCDelegationState>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetEntry {
+    pub pox_address: RPCPoxAddress,
+    pub total_ustx: u64,
+}
+
+/// The data we return on GET /v2/pox/reward_set/:cycle
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetData {
+    pub reward_cycle: u64,
+    pub reward_cycle_start_burn_height: u64,
+    pub is_pox_active: bool,
+    pub total_stacked_ustx: u64,
+    /// liquid uSTX as of the queried chain tip, from which the reward threshold is computed
+    #[serde(default)]
+    pub liquid_ustx: u64,
+    pub entries: Vec<RPCRewardSetEntry>,
+    /// the PoX state the reward set is computed from, with MARF proofs (unless `proof=0`)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proofs: Option<RPCRewardSetProofs>,
+}
+
+/// A value stored in the MARF, and its proof.  A value that does not exist is served as `none`
+/// with an empty proof.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCMarfValue {
+    pub data: String,
+    #[serde(rename = "proof")]
+    pub marf_proof: String,
+}
+
+/// A PoX contract's state for one reward cycle, as read by the reward set computation
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPoxContractProofs {
+    /// boot contract name, e.g. `pox`
+    pub contract: String,
+    /// the `pox-rejection-fraction` data var
+    pub rejection_fraction: RPCMarfValue,
+    /// the reward cycle's `stacking-rejection` entry
+    pub rejection_votes: RPCMarfValue,
+    /// the reward cycle's `reward-cycle-pox-address-list-len` entry
+    pub num_entries: RPCMarfValue,
+    /// the reward cycle's `reward-cycle-pox-address-list` entries, in index order
+    pub entries: Vec<RPCMarfValue>,
+}
+
+/// The proven state a reward set is computed from, so light clients can check it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCRewardSetProofs {
+    pub liquid_ustx: RPCMarfValue,
+    /// one per PoX contract active at the queried chain tip, in the order they are read
+    pub pox_contracts: Vec<RPCPoxContractProofs>,
+}
+
+/// The data we return on GET /v2/names/:name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCNameResolutionData {
+    /// fully-qualified name, i.e. `name.namespace`
+    pub name: String,
+    /// one of `active`, `grace_period`, `expired` or `revoked`
+    pub status: String,
+    pub owner: String,
+    pub lease_started_at: Option<u64>,
+    pub lease_ending_at: Option<u64>,
+    /// hex-encoded hash of the name's zonefile
+    pub zonefile_hash: String,
+    /// hex-encoded zonefile, if this node has obtained it through Atlas
+    pub zonefile: Option<String>,
+}
+
+/// The data we return on GET /v2/names/owners/:principal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCNamesByOwnerData {
+    pub owner: String,
+    pub names: Vec<String>,
+}
+
+/// The data we return on GET /v2/namespaces/:namespace/names
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCNamespaceNamesData {
+    pub namespace: String,
+    pub page: u32,
+    /// whether there are later pages.  A page can hold fewer names than the page size even
+    /// when there are.
+    pub has_more: bool,
+    pub names: Vec<String>,
+}
+
 /// Headers response payload
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ExtendedStacksHeader {
@@ -1172,12 +1364,21 @@ impl RPCFeeEstimate {
     }
 }
 
+/// Fee estimates for inclusion within `target_blocks` blocks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCFeeConfirmationTarget {
+    pub target_blocks: u64,
+    pub estimations: Vec<RPCFeeEstimate>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RPCFeeEstimateResponse {
     pub estimated_cost: ExecutionCost,
     pub estimated_cost_scalar: u64,
     pub estimations: Vec<RPCFeeEstimate>,
     pub cost_scalar_change_by_byte: f64,
+    #[serde(default)]
+    pub confirmation_targets: Vec<RPCFeeConfirmationTarget>,
 }
 
 #[derive(Debug, Clone, PartialEq, Copy, Hash)]
@@ -1251,6 +1452,35 @@ pub struct AccountEntryResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub nonce_proof: Option<String>,
+    /// the stored balance record that `balance_proof` commits to, so the locked and unlocked
+    /// amounts can be checked against the proof
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub balance_data: Option<String>,
+}
+
+/// An account's balance and nonce as of a single chain tip
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCAccountStateData {
+    pub balance: String,
+    pub locked: String,
+    pub unlock_height: u64,
+    pub nonce: u64,
+}
+
+/// The data we return on GET /v2/accounts/:principal/unconfirmed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCUnconfirmedAccountData {
+    pub anchored_tip: StacksBlockId,
+    pub anchored: RPCAccountStateData,
+    /// the tip of the unconfirmed microblock stream, or the anchored tip if there is none
+    pub unconfirmed_tip: StacksBlockId,
+    /// sequence number of the last microblock in the unconfirmed stream, if there is one
+    pub unconfirmed_seq: Option<u16>,
+    pub unconfirmed: RPCAccountStateData,
+    /// txids of the transactions in the unconfirmed stream that touched this account, in the
+    /// order they were mined
+    pub unconfirmed_txids: Vec<String>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -1268,6 +1498,113 @@ pub struct UnconfirmedTransactionResponse {
     pub status: UnconfirmedTransactionStatus,
 }
 
+/// A transaction that touched a principal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPrincipalTransaction {
+    pub txid: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub roles: Vec<PrincipalRole>,
+}
+
+/// The data we return on GET /v2/accounts/:principal/transactions
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPrincipalTransactionsData {
+    pub principal: String,
+    /// pass as `cursor` to get the next page; `None` once there are no more rows
+    pub next_cursor: Option<String>,
+    pub transactions: Vec<RPCPrincipalTransaction>,
+}
+
+/// An asset event that named a principal
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPrincipalAssetEvent {
+    pub txid: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+    pub event: serde_json::Value,
+}
+
+/// The data we return on GET /v2/accounts/:principal/events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCPrincipalAssetEventsData {
+    pub principal: String,
+    /// pass as `cursor` to get the next page; `None` once there are no more rows
+    pub next_cursor: Option<String>,
+    pub events: Vec<RPCPrincipalAssetEvent>,
+}
+
+/// A contract event loaded from the event log
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCContractEvent {
+    pub contract_id: String,
+    pub topic: Option<String>,
+    pub txid: String,
+    pub index_block_hash: StacksBlockId,
+    pub block_height: u64,
+    pub tx_index: u32,
+    pub event_index: u32,
+    pub event: serde_json::Value,
+}
+
+/// The data we return on GET /v2/events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RPCContractEventsData {
+    /// pass as `cursor` to get the next page; `None` once there are no more rows
+    pub next_cursor: Option<String>,
+    pub events: Vec<RPCContractEvent>,
+}
+
+/// Where a transaction is, as far as this node knows
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TransactionStatus {
+    /// waiting in the mempool
+    Pending,
+    /// mined in the unconfirmed microblock stream
+    Microblock {
+        block_hash: BlockHeaderHash,
+        seq: u16,
+    },
+    /// mined in an anchored block (or a microblock it confirmed) on the canonical fork
+    Anchored {
+        index_block_hash: StacksBlockId,
+        block_hash: BlockHeaderHash,
+        block_height: u64,
+        tx_index: Option<u32>,
+        microblock_hash: Option<BlockHeaderHash>,
+        microblock_seq: Option<u16>,
+    },
+    /// dropped from the mempool without being mined, with the `MemPoolDropReason`
+    Dropped { reason: String },
+}
+
+/// The data we return on GET /v2/transactions/:txid
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TransactionReceiptResponse {
+    pub txid: String,
+    pub status: TransactionStatus,
+    /// Whether the node's txid index is on.  If it is off, a mined transaction is only found
+    /// while it is still in the mempool, where it shows up as `Pending`.
+    #[serde(default)]
+    pub txindex: bool,
+    /// hex-encoded transaction, if the node still has it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tx: Option<String>,
+    /// The fields below are only known for anchored transactions found in the txid index.
+    /// hex-encoded consensus serialization of the result value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub events: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub execution_cost: Option<ExecutionCost>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub post_condition_aborted: Option<bool>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PostTransactionRequestBody {
     pub tx: String,
@@ -1417,6 +1754,12 @@ pub enum TipRequest {
 pub enum HttpRequestType {
     GetInfo(HttpRequestMetadata),
     GetPoxInfo(HttpRequestMetadata, TipRequest),
+    GetStackerInfo(HttpRequestMetadata, PrincipalData, TipRequest),
+    GetRewardSet(HttpRequestMetadata, u64, TipRequest, bool),
+    GetNameResolution(HttpRequestMetadata, String, String, TipRequest),
+    GetNamesByOwner(HttpRequestMetadata, PrincipalData, TipRequest),
+    GetNamespaceNames(HttpRequestMetadata, String, u32, TipRequest),
+    GetCostVoting(HttpRequestMetadata, TipRequest),
     GetNeighbors(HttpRequestMetadata),
     GetHeaders(HttpRequestMetadata, u64, TipRequest),
     GetBlock(HttpRequestMetadata, StacksBlockId),
@@ -1424,10 +1767,30 @@ pub enum HttpRequestType {
     GetMicroblocksConfirmed(HttpRequestMetadata, StacksBlockId),
     GetMicroblocksUnconfirmed(HttpRequestMetadata, StacksBlockId, u16),
     GetTransactionUnconfirmed(HttpRequestMetadata, Txid),
+    GetTransaction(HttpRequestMetadata, Txid),
     PostTransaction(HttpRequestMetadata, StacksTransaction, Option<Attachment>),
     PostBlock(HttpRequestMetadata, ConsensusHash, StacksBlock),
     PostMicroblock(HttpRequestMetadata, StacksMicroblock, TipRequest),
     GetAccount(HttpRequestMetadata, PrincipalData, TipRequest, bool),
+    GetUnconfirmedAccount(HttpRequestMetadata, PrincipalData),
+    GetPrincipalTransactions(
+        HttpRequestMetadata,
+        PrincipalData,
+        Option<IndexCursor>,
+        TipRequest,
+    ),
+    GetPrincipalAssetEvents(
+        HttpRequestMetadata,
+        PrincipalData,
+        Option<IndexCursor>,
+        TipRequest,
+    ),
+    GetContractEvents(
+        HttpRequestMetadata,
+        ContractEventFilter,
+        Option<IndexCursor>,
+        TipRequest,
+    ),
     GetDataVar(
         HttpRequestMetadata,
         StacksAddress,
@@ -1567,6 +1930,12 @@ impl HttpResponseMetadata {
 pub enum HttpResponseType {
     PeerInfo(HttpResponseMetadata, RPCPeerInfoData),
     PoxInfo(HttpResponseMetadata, RPCPoxInfoData),
+    StackerInfo(HttpResponseMetadata, RPCStackerInfoData),
+    RewardSet(HttpResponseMetadata, RPCRewardSetData),
+    NameResolution(HttpResponseMetadata, RPCNameResolutionData),
+    NamesByOwner(HttpResponseMetadata, RPCNamesByOwnerData),
+    NamespaceNames(HttpResponseMetadata, RPCNamespaceNamesData),
+    CostVoting(HttpResponseMetadata, CostVotingReport),
     Neighbors(HttpResponseMetadata, RPCNeighborsInfo),
     Headers(HttpResponseMetadata, Vec<ExtendedStacksHeader>),
     HeaderStream(HttpResponseMetadata),
@@ -1582,10 +1951,15 @@ pub enum HttpResponseType {
     GetMapEntry(HttpResponseMetadata, MapEntryResponse),
     CallReadOnlyFunction(HttpResponseMetadata, CallReadOnlyResponse),
     GetAccount(HttpResponseMetadata, AccountEntryResponse),
+    GetUnconfirmedAccount(HttpResponseMetadata, RPCUnconfirmedAccountData),
+    PrincipalTransactions(HttpResponseMetadata, RPCPrincipalTransactionsData),
+    PrincipalAssetEvents(HttpResponseMetadata, RPCPrincipalAssetEventsData),
+    ContractEvents(HttpResponseMetadata, RPCContractEventsData),
     GetContractABI(HttpResponseMetadata, ContractInterface),
     GetContractSrc(HttpResponseMetadata, ContractSrcResponse),
     GetIsTraitImplemented(HttpResponseMetadata, GetIsTraitImplementedResponse),
     UnconfirmedTransaction(HttpResponseMetadata, UnconfirmedTransactionResponse),
+    TransactionReceipt(HttpResponseMetadata, TransactionReceiptResponse),
     GetAttachment(HttpResponseMetadata, GetAttachmentResponse),
     GetAttachmentsInv(HttpResponseMetadata, GetAttachmentsInvResponse),
     MemPoolTxStream(HttpResponseMetadata),
@@ -1632,6 +2006,10 @@ pub enum StacksMessageID {
     Pong = 16,
     NatPunchRequest = 17,
     NatPunchReply = 18,
+    SessionKey = 19,
+    CompactBlock = 20,
+    GetCompactBlockTxs = 21,
+    CompactBlockTxs = 22,
     // reserved
     Reserved = 255,
 }
@@ -1712,6 +2090,13 @@ pub trait ProtocolFamily {
     /// and writing out a Preamble for its Message.
     fn write_message<W: Write>(&mut self, fd: &mut W, message: &Self::Message)
         -> Result<(), Error>;
+
+    /// If this message switches the sender's side of the conversation to an encrypted
+    /// transport, then return the ephemeral public key the rest of the stream is encrypted with.
+    /// Protocols without an encrypted transport never do this.
+    fn session_key(&self, _message: &Self::Message) -> Option<StacksPublicKey> {
+        None
+    }
 }
 
 // these implement the ProtocolFamily trait
@@ -1742,6 +2127,10 @@ pub const GETPOXINV_MAX_BITLEN: u64 = 8;
 // message.
 pub const BLOCKS_PUSHED_MAX: u32 = 32;
 
+// maximum number of transactions a compact block can describe.  Transaction positions are encoded
+// as u16s.
+pub const COMPACT_BLOCK_MAX_TXS: u32 = 65535;
+
 impl_byte_array_message_codec!(PeerAddress, 16);
 impl_byte_array_message_codec!(Txid, 32);
 
@@ -1875,6 +2264,16 @@ pub const MAX_MICROBLOCKS_UNCONFIRMED: usize = 1024;
 // maximum number of block headers we'll get streamed to us
 pub const MAX_HEADERS: usize = 2100;
 
+/// maximum number of BNS names to return in a single page of a namespace listing
+pub const MAX_BNS_NAMES_PAGE_SIZE: usize = 100;
+
+/// maximum number of transactions or asset events to return in a single page of a principal's
+/// history
+pub const MAX_PRINCIPAL_ACTIVITY_PAGE_SIZE: usize = 50;
+
+/// maximum number of contract events to return in a single page of the event log
+pub const MAX_CONTRACT_EVENTS_PAGE_SIZE: usize = 50;
+
 // how long a peer will be denied for if it misbehaves
 #[cfg(test)]
 pub const DENY_BAN_DURATION: u64 = 30; // seconds
@@ -2315,6 +2714,7 @@ pub mod test {
 
     pub struct TestEventObserver {
         blocks: Mutex<Vec<TestEventObserverBlock>>,
+        reorgs: Mutex<Vec<ChainReorg>>,
     }
 
     impl TestEventObserver {
@@ -2322,9 +2722,14 @@ pub mod test {
             self.blocks.lock().unwrap().deref().to_vec()
         }
 
+        pub fn get_reorgs(&self) -> Vec<ChainReorg> {
+            self.reorgs.lock().unwrap().deref().to_vec()
+        }
+
         pub fn new() -> TestEventObserver {
             TestEventObserver {
                 blocks: Mutex::new(vec![]),
+                reorgs: Mutex::new(vec![]),
             }
         }
     }
@@ -2370,6 +2775,10 @@ pub mod test {
         fn dispatch_boot_receipts(&mut self, _receipts: Vec<events::StacksTransactionReceipt>) {
             // pass
         }
+
+        fn announce_reorg(&self, reorg: &ChainReorg) {
+            self.reorgs.lock().unwrap().push(reorg.clone())
+        }
     }
 
     // describes a peer's initial configuration
@@ -2657,6 +3066,7 @@ pub mod test {
                                     .expect("FATAL: invalid boot-code contract name"),
                                 code_body: StacksString::from_str(&conf.setup_code)
                                     .expect("FATAL: invalid boot code body"),
+                                clarity_version: None,
                             });
 
                         let boot_code_smart_contract = StacksTransaction::new(
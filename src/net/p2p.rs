@@ -53,6 +53,7 @@ use crate::net::atlas::AtlasDB;
 use crate::net::atlas::{AttachmentInstance, AttachmentsDownloader};
 use crate::net::chat::ConversationP2P;
 use crate::net::chat::NeighborStats;
+use crate::net::compact::PendingCompactBlock;
 use crate::net::connection::ConnectionOptions;
 use crate::net::connection::NetworkReplyHandle;
 use crate::net::connection::ReplyHandleP2P;
@@ -90,7 +91,11 @@ use crate::types::chainstate::{PoxId, SortitionId};
 #[derive(Debug)]
 pub enum NetworkRequest {
     Ban(Vec<NeighborKey>),
-    AdvertizeBlocks(BlocksAvailableMap, HashMap<ConsensusHash, StacksBlock>), // announce to all wanting neighbors that we have these blocks
+    AdvertizeBlocks(
+        BlocksAvailableMap,
+        HashMap<ConsensusHash, StacksBlock>,
+        HashMap<ConsensusHash, CompactBlockData>,
+    ), // announce to all wanting neighbors that we have these blocks (and push compact forms of them to peers that support it)
     AdvertizeMicroblocks(
         BlocksAvailableMap,
         HashMap<ConsensusHash, (StacksBlockId, Vec<StacksMicroblock>)>,
@@ -146,8 +151,9 @@ impl NetworkHandle {
         &mut self,
         blocks: BlocksAvailableMap,
         block_data: HashMap<ConsensusHash, StacksBlock>,
+        compact_block_data: HashMap<ConsensusHash, CompactBlockData>,
     ) -> Result<(), net_error> {
-        let req = NetworkRequest::AdvertizeBlocks(blocks, block_data);
+        let req = NetworkRequest::AdvertizeBlocks(blocks, block_data, compact_block_data);
         self.send_request(req)
     }
 
@@ -347,6 +353,9 @@ pub struct PeerNetwork {
     // can't process yet, but might be able to process on the next chain view update
     pub pending_messages: HashMap<usize, Vec<StacksMessage>>,
 
+    // compact blocks pushed to us that we've asked the sender to fill in, keyed by that sender
+    pub pending_compact_blocks: HashMap<(NeighborKey, StacksBlockId), PendingCompactBlock>,
+
     // fault injection -- force disconnects
     fault_last_disconnect: u64,
 }
@@ -464,6 +473,7 @@ impl PeerNetwork {
             antientropy_start_reward_cycle: 0,
 
             pending_messages: HashMap::new(),
+            pending_compact_blocks: HashMap::new(),
 
             fault_last_disconnect: 0,
         };
@@ -1032,9 +1042,9 @@ impl PeerNetwork {
                 }
                 Ok(())
             }
-            NetworkRequest::AdvertizeBlocks(blocks, block_data) => {
+            NetworkRequest::AdvertizeBlocks(blocks, block_data, compact_block_data) => {
                 if !(cfg!(test) && self.connection_opts.disable_block_advertisement) {
-                    self.advertize_blocks(blocks, block_data)?;
+                    self.advertize_blocks(blocks, block_data, compact_block_data)?;
                 }
                 Ok(())
             }
@@ -5005,6 +5015,10 @@ impl PeerNetwork {
             );
             self.deregister_peer(error_event);
         }
+
+        // reconstruct pushed compact blocks, so they get handled like any other pushed block
+        let unsolicited_messages =
+            self.handle_unsolicited_compact_blocks(sortdb, mempool, unsolicited_messages);
         let unhandled_messages =
             self.handle_unsolicited_messages(sortdb, chainstate, unsolicited_messages, ibd, true)?;
         network_result.consume_unsolicited(unhandled_messages);
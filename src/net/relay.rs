@@ -1036,8 +1036,14 @@ impl Relayer {
         mempool: &mut MemPoolDB,
         event_observer: Option<&dyn MemPoolEventDispatcher>,
     ) -> Result<Vec<(Vec<RelayData>, StacksTransaction)>, net_error> {
-        let chain_height = match chainstate.get_stacks_chain_tip(sortdb)? {
-            Some(tip) => tip.height,
+        let (chain_height, chain_tip) = match chainstate.get_stacks_chain_tip(sortdb)? {
+            Some(tip) => (
+                tip.height,
+                StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                ),
+            ),
             None => {
                 debug!(
                     "No Stacks chain tip; dropping {} transaction(s)",
@@ -1081,7 +1087,17 @@ impl Relayer {
                 "Remove all transactions beneath block height {}",
                 min_height
             );
-            MemPoolDB::garbage_collect(&mut mempool_tx, min_height, event_observer)?;
+            let burn_conn = sortdb.index_conn();
+            MemPoolDB::garbage_collect(
+                &mut mempool_tx,
+                min_height,
+                |address| {
+                    chainstate.with_read_only_clarity_tx(&burn_conn, &chain_tip, |conn| {
+                        StacksChainState::get_account(conn, &address.clone().into()).nonce
+                    })
+                },
+                event_observer,
+            )?;
             mempool_tx.commit()?;
         }
         update_stacks_tip_height(chain_height as i64);
@@ -1089,12 +1105,31 @@ impl Relayer {
         Ok(ret)
     }
 
+    /// Make compact forms of blocks we're about to push, for peers that support compact block
+    /// relay.  Transactions that aren't in our mempool get sent inline.
+    pub fn make_compact_blocks(
+        blocks: &HashMap<ConsensusHash, StacksBlock>,
+        mempool: &MemPoolDB,
+    ) -> HashMap<ConsensusHash, CompactBlockData> {
+        let mut compact_blocks = HashMap::new();
+        for (consensus_hash, block) in blocks.iter() {
+            if let Some(compact_block) =
+                CompactBlockData::from_block(consensus_hash, block, |txid| mempool.has_tx(txid))
+            {
+                compact_blocks.insert(consensus_hash.clone(), compact_block);
+            }
+        }
+        compact_blocks
+    }
+
     pub fn advertize_blocks(
         &mut self,
         available: BlocksAvailableMap,
         blocks: HashMap<ConsensusHash, StacksBlock>,
+        mempool: &MemPoolDB,
     ) -> Result<(), net_error> {
-        self.p2p.advertize_blocks(available, blocks)
+        let compact_blocks = Relayer::make_compact_blocks(&blocks, mempool);
+        self.p2p.advertize_blocks(available, blocks, compact_blocks)
     }
 
     pub fn broadcast_block(
@@ -1234,7 +1269,11 @@ impl Relayer {
                     let available = Relayer::load_blocks_available_data(sortdb, new_block_chs)?;
                     if available.len() > 0 {
                         debug!("{:?}: Blocks available: {}", &_local_peer, available.len());
-                        if let Err(e) = self.p2p.advertize_blocks(available, new_blocks) {
+                        let compact_blocks = Relayer::make_compact_blocks(&new_blocks, mempool);
+                        if let Err(e) =
+                            self.p2p
+                                .advertize_blocks(available, new_blocks, compact_blocks)
+                        {
                             warn!("Failed to advertize new blocks: {:?}", &e);
                         }
                     }
@@ -1483,6 +1522,42 @@ impl PeerNetwork {
         });
     }
 
+    /// Try to push a compact block to a peer.
+    /// Absorb and log errors.
+    fn push_compact_block_to_peer(
+        &mut self,
+        recipient: &NeighborKey,
+        compact_block: CompactBlockData,
+    ) -> () {
+        let blk_hash = compact_block.block_hash();
+        let ch = compact_block.consensus_hash.clone();
+        let message =
+            match self.sign_for_peer(recipient, StacksMessageType::CompactBlock(compact_block)) {
+                Ok(m) => m,
+                Err(e) => {
+                    warn!(
+                        "{:?}: Failed to sign for {:?}: {:?}",
+                        &self.local_peer, recipient, &e
+                    );
+                    return;
+                }
+            };
+
+        debug!(
+            "{:?}: Push compact block {}/{} to {:?}",
+            &self.local_peer, &ch, &blk_hash, recipient
+        );
+
+        // absorb errors
+        let _ = self.relay_signed_message(recipient, message).map_err(|e| {
+            warn!(
+                "{:?}: Failed to push compact block {}/{} to {:?}: {:?}",
+                &self.local_peer, &ch, &blk_hash, recipient, &e
+            );
+            e
+        });
+    }
+
     /// Try to push a confirmed microblock stream to a peer.
     /// Absorb and log errors.
     fn push_microblocks_to_peer(
@@ -1523,13 +1598,14 @@ impl PeerNetwork {
     }
 
     /// Announce blocks that we have to an outbound peer that doesn't have them.
-    /// If we were given the block, send the block itself.
-    /// Otherwise, send a BlocksAvailable.
+    /// If we were given the block, send the block itself (in compact form, if the peer supports
+    /// it).  Otherwise, send a BlocksAvailable.
     fn advertize_or_push_blocks_to_outbound_peer(
         &mut self,
         recipient: &NeighborKey,
         available: &BlocksAvailableMap,
         blocks: &HashMap<ConsensusHash, StacksBlock>,
+        compact_blocks: &HashMap<ConsensusHash, CompactBlockData>,
     ) -> Result<(), net_error> {
         PeerNetwork::with_inv_state(self, |network, inv_state| {
             if let Some(stats) = inv_state.block_stats.get(recipient) {
@@ -1542,15 +1618,23 @@ impl PeerNetwork {
                             bhh
                         );
 
-                        match blocks.get(ch) {
-                            Some(block) => {
+                        match (blocks.get(ch), compact_blocks.get(ch)) {
+                            (Some(_), Some(compact_block))
+                                if network.peer_supports_compact_blocks(recipient) =>
+                            {
+                                network.push_compact_block_to_peer(
+                                    recipient,
+                                    (*compact_block).clone(),
+                                );
+                            }
+                            (Some(block), _) => {
                                 network.push_block_to_peer(
                                     recipient,
                                     (*ch).clone(),
                                     (*block).clone(),
                                 );
                             }
-                            None => {
+                            (None, _) => {
                                 network.advertize_to_peer(
                                     recipient,
                                     &[((*ch).clone(), (*bhh).clone())],
@@ -1640,6 +1724,7 @@ impl PeerNetwork {
         &mut self,
         availability_data: BlocksAvailableMap,
         blocks: HashMap<ConsensusHash, StacksBlock>,
+        compact_blocks: HashMap<ConsensusHash, CompactBlockData>,
     ) -> Result<(usize, usize), net_error> {
         let (mut outbound_recipients, mut inbound_recipients) =
             self.find_block_recipients(&availability_data)?;
@@ -1665,6 +1750,7 @@ impl PeerNetwork {
                 &recipient,
                 &availability_data,
                 &blocks,
+                &compact_blocks,
             )?;
         }
         for recipient in inbound_recipients.drain(..) {
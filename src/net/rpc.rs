@@ -17,6 +17,7 @@
  along with Blockstack. If not, see <http://www.gnu.org/licenses/>.
 */
 
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::collections::VecDeque;
@@ -36,7 +37,9 @@ use crate::burnchains::BurnchainView;
 use crate::burnchains::*;
 use crate::chainstate::burn::db::sortdb::SortitionDB;
 use crate::chainstate::burn::ConsensusHash;
+use crate::chainstate::stacks::db::addrindex::IndexCursor;
 use crate::chainstate::stacks::db::blocks::CheckError;
+use crate::chainstate::stacks::db::eventlog::ContractEventFilter;
 use crate::chainstate::stacks::db::{
     blocks::MINIMUM_TX_FEE_RATE_PER_BYTE, StacksChainState, StreamCursor,
 };
@@ -48,6 +51,7 @@ use crate::core::mempool::*;
 use crate::cost_estimates::metrics::CostMetric;
 use crate::cost_estimates::CostEstimator;
 use crate::cost_estimates::FeeEstimator;
+use crate::cost_estimates::FEE_CONFIRMATION_TARGETS;
 use crate::monitoring;
 use crate::net::atlas::{AtlasDB, Attachment, MAX_ATTACHMENT_INV_PAGES_PER_REQUEST};
 use crate::net::connection::ConnectionHttp;
@@ -55,6 +59,7 @@ use crate::net::connection::ConnectionOptions;
 use crate::net::connection::ReplyHandleHttp;
 use crate::net::db::PeerDB;
 use crate::net::http::*;
+use crate::net::light::get_map_entry_field;
 use crate::net::p2p::PeerMap;
 use crate::net::p2p::PeerNetwork;
 use crate::net::relay::Relayer;
@@ -71,15 +76,19 @@ use crate::net::NeighborsData;
 use crate::net::PeerAddress;
 use crate::net::PeerHost;
 use crate::net::ProtocolFamily;
+use crate::net::RPCFeeConfirmationTarget;
 use crate::net::RPCFeeEstimate;
 use crate::net::RPCFeeEstimateResponse;
 use crate::net::StacksHttp;
 use crate::net::StacksHttpMessage;
 use crate::net::StacksMessageType;
+use crate::net::TransactionReceiptResponse;
+use crate::net::TransactionStatus;
 use crate::net::UnconfirmedTransactionResponse;
 use crate::net::UnconfirmedTransactionStatus;
 use crate::net::UrlString;
 use crate::net::HTTP_REQUEST_ID_RESERVED;
+use crate::net::MAX_BNS_NAMES_PAGE_SIZE;
 use crate::net::MAX_HEADERS;
 use crate::net::MAX_NEIGHBORS_DATA_LEN;
 use crate::net::{
@@ -88,8 +97,20 @@ use crate::net::{
 };
 use crate::net::{BlocksData, GetIsTraitImplementedResponse};
 use crate::net::{ClientError, TipRequest};
+use crate::net::{RPCAccountStateData, RPCUnconfirmedAccountData};
+use crate::net::{RPCContractEvent, RPCContractEventsData, MAX_CONTRACT_EVENTS_PAGE_SIZE};
+use crate::net::{
+    RPCDelegationState, RPCPoxAddress, RPCRewardSetData, RPCRewardSetEntry, RPCStackerInfoData,
+    RPCStackingState,
+};
+use crate::net::{RPCMarfValue, RPCPoxContractProofs, RPCRewardSetProofs};
+use crate::net::{RPCNameResolutionData, RPCNamesByOwnerData, RPCNamespaceNamesData};
 use crate::net::{RPCNeighbor, RPCNeighborsInfo};
 use crate::net::{RPCPeerInfoData, RPCPoxInfoData};
+use crate::net::{
+    RPCPrincipalAssetEvent, RPCPrincipalAssetEventsData, RPCPrincipalTransaction,
+    RPCPrincipalTransactionsData, MAX_PRINCIPAL_ACTIVITY_PAGE_SIZE,
+};
 use crate::util_lib::db::DBConn;
 use crate::util_lib::db::Error as db_error;
 use clarity::vm::database::clarity_store::make_contract_hash_key;
@@ -104,13 +125,18 @@ use clarity::vm::{
     errors::Error as ClarityRuntimeError,
     errors::Error::Unchecked,
     errors::InterpreterError,
-    types::{PrincipalData, QualifiedContractIdentifier, StandardPrincipalData},
+    types::{
+        OptionalData, PrincipalData, QualifiedContractIdentifier, SequenceData,
+        StandardPrincipalData, TupleData,
+    },
     ClarityName, ContractName, SymbolicExpression, Value,
 };
+use stacks_common::address::AddressHashMode;
 use stacks_common::util::get_epoch_time_secs;
 use stacks_common::util::hash::Hash160;
 use stacks_common::util::hash::{hex_bytes, to_hex};
 
+use crate::chainstate::stacks::address::StacksAddressExtensions;
 use crate::chainstate::stacks::StacksBlockHeader;
 use crate::clarity_vm::database::marf::MarfedKV;
 use stacks_common::types::chainstate::BlockHeaderHash;
@@ -410,14 +436,14 @@ impl RPCPoxInfoData {
             current_cycle: RPCPoxCurrentCycleInfo {
                 id: reward_cycle_id,
                 min_threshold_ustx: cur_cycle_threshold,
-                stacked_ustx: cur_cycle_stacked_ustx as u64,
+                stacked_ustx: ustx_to_u64(cur_cycle_stacked_ustx, "stacked uSTX")?,
                 is_pox_active: cur_cycle_pox_active,
             },
             next_cycle: RPCPoxNextCycleInfo {
                 id: reward_cycle_id + 1,
                 min_threshold_ustx: next_threshold,
                 min_increment_ustx: min_stacking_increment_ustx,
-                stacked_ustx: next_cycle_stacked_ustx as u64,
+                stacked_ustx: ustx_to_u64(next_cycle_stacked_ustx, "stacked uSTX")?,
                 prepare_phase_start_block_height: next_prepare_phase_start,
                 blocks_until_prepare_phase: next_prepare_phase_in,
                 reward_phase_start_block_height: next_rewards_start,
@@ -434,236 +460,1410 @@ impl RPCPoxInfoData {
     }
 }
 
-impl RPCNeighborsInfo {
-    /// Load neighbor address information from the peer network
-    pub fn from_p2p(
-        network_id: u32,
-        network_epoch: u8,
-        peers: &PeerMap,
-        chain_view: &BurnchainView,
-        peerdb: &PeerDB,
-    ) -> Result<RPCNeighborsInfo, net_error> {
-        let neighbor_sample = PeerDB::get_random_neighbors(
-            peerdb.conn(),
-            network_id,
-            network_epoch,
-            MAX_NEIGHBORS_DATA_LEN,
-            chain_view.burn_block_height,
-            false,
-        )
-        .map_err(net_error::DBError)?;
+/// Convert a uSTX amount from the chainstate to the u64 we report it as, or fail if it doesn't fit
+fn ustx_to_u64(amount: u128, what: &str) -> Result<u64, net_error> {
+    u64::try_from(amount).map_err(|_e| {
+        net_error::ChainstateError(format!("{} does not fit in a u64: {}", what, amount))
+    })
+}
 
-        let sample: Vec<RPCNeighbor> = neighbor_sample
-            .into_iter()
-            .map(|n| {
-                RPCNeighbor::from_neighbor_key_and_pubkh(
-                    n.addr.clone(),
-                    Hash160::from_node_public_key(&n.public_key),
-                    true,
-                )
-            })
-            .collect();
+/// Error for PoX contract data that is not in the form we expect
+fn pox_data_error(what: &str) -> net_error {
+    net_error::ChainstateError(format!("Unexpected PoX contract data: {}", what))
+}
 
-        let mut inbound = vec![];
-        let mut outbound = vec![];
-        for (_, convo) in peers.iter() {
-            let nk = convo.to_neighbor_key();
-            let naddr = convo.to_neighbor_address();
-            if convo.is_outbound() {
-                outbound.push(RPCNeighbor::from_neighbor_key_and_pubkh(
-                    nk,
-                    naddr.public_key_hash,
-                    convo.is_authenticated(),
-                ));
-            } else {
-                inbound.push(RPCNeighbor::from_neighbor_key_and_pubkh(
-                    nk,
-                    naddr.public_key_hash,
-                    convo.is_authenticated(),
-                ));
-            }
-        }
+/// Get a field out of a tuple of PoX contract data
+fn pox_tuple_field<'a>(tuple: &'a TupleData, field: &str) -> Result<&'a Value, net_error> {
+    tuple
+        .get(field)
+        .map_err(|_e| pox_data_error(&format!("no '{}'", field)))
+}
 
-        Ok(RPCNeighborsInfo {
-            sample: sample,
-            inbound: inbound,
-            outbound: outbound,
-        })
+/// Get a tuple out of PoX contract data
+fn pox_expect_tuple(value: &Value, what: &str) -> Result<TupleData, net_error> {
+    match value {
+        Value::Tuple(tuple) => Ok(tuple.clone()),
+        _ => Err(pox_data_error(&format!("{} is not a tuple", what))),
     }
 }
 
-impl ConversationHttp {
-    pub fn new(
-        peer_addr: SocketAddr,
-        outbound_url: Option<UrlString>,
-        peer_host: PeerHost,
-        conn_opts: &ConnectionOptions,
-        conn_id: usize,
-    ) -> ConversationHttp {
-        let mut stacks_http = StacksHttp::new(peer_addr.clone());
-        stacks_http.maximum_call_argument_size = conn_opts.maximum_call_argument_size;
-        ConversationHttp {
-            connection: ConnectionHttp::new(stacks_http, conn_opts, None),
-            conn_id: conn_id,
-            timeout: conn_opts.timeout,
-            reply_streams: VecDeque::new(),
-            peer_addr: peer_addr,
-            outbound_url: outbound_url,
-            peer_host: peer_host,
-            canonical_stacks_tip_height: None,
-            pending_request: None,
-            pending_response: None,
-            pending_error_response: None,
-            keep_alive: true,
-            total_request_count: 0,
-            total_reply_count: 0,
-            last_request_timestamp: 0,
-            last_response_timestamp: 0,
-            connection_time: get_epoch_time_secs(),
-        }
+/// Get an optional value out of PoX contract data
+fn pox_expect_optional<'a>(value: &'a Value, what: &str) -> Result<Option<&'a Value>, net_error> {
+    match value {
+        Value::Optional(OptionalData { data }) => Ok(data.as_deref()),
+        _ => Err(pox_data_error(&format!("{} is not optional", what))),
     }
+}
 
-    /// How many ongoing requests do we have on this conversation?
-    pub fn num_pending_outbound(&self) -> usize {
-        self.reply_streams.len()
+/// Get a uint that we report as a u64 (an amount, height or cycle) out of PoX contract data
+fn pox_expect_u64(value: &Value, what: &str) -> Result<u64, net_error> {
+    match value {
+        Value::UInt(n) => u64::try_from(*n)
+            .map_err(|_e| pox_data_error(&format!("{} does not fit in a u64", what))),
+        _ => Err(pox_data_error(&format!("{} is not a uint", what))),
     }
+}
 
-    /// What's our outbound URL?
-    pub fn get_url(&self) -> Option<&UrlString> {
-        self.outbound_url.as_ref()
+/// Get a buffer of at most `max_len` bytes out of PoX contract data, zero-padded to `max_len`
+fn pox_expect_buff_padded(value: &Value, max_len: usize, what: &str) -> Result<Vec<u8>, net_error> {
+    match value {
+        Value::Sequence(SequenceData::Buffer(buff)) if buff.data.len() <= max_len => {
+            let mut bytes = buff.data.clone();
+            bytes.resize(max_len, 0);
+            Ok(bytes)
+        }
+        _ => Err(pox_data_error(&format!(
+            "{} is not a buffer of at most {} bytes",
+            what, max_len
+        ))),
     }
+}
 
-    /// What's our peer IP address?
-    pub fn get_peer_addr(&self) -> &SocketAddr {
-        &self.peer_addr
-    }
+impl RPCPoxAddress {
+    /// Decode a PoX address from its `{ version: (buff 1), hashbytes: (buff 20) }` tuple.
+    /// Delegations do not validate the version byte, so an unsupported hash mode is tolerated
+    /// here and simply has no burnchain address.
+    fn from_clarity_tuple(tuple: &TupleData, mainnet: bool) -> Result<RPCPoxAddress, net_error> {
+        let version =
+            pox_expect_buff_padded(pox_tuple_field(tuple, "version")?, 1, "pox-addr version")?[0];
+
+        let hashbytes_vec = pox_expect_buff_padded(
+            pox_tuple_field(tuple, "hashbytes")?,
+            20,
+            "pox-addr hashbytes",
+        )?;
 
-    /// Is a request in-progress?
-    pub fn is_request_inflight(&self) -> bool {
-        self.pending_request.is_some()
-    }
+        let mut hashbytes = [0u8; 20];
+        hashbytes.copy_from_slice(&hashbytes_vec[0..20]);
 
-    /// Start a HTTP request from this peer, and expect a response.
-    /// Returns the request handle; does not set the handle into this connection.
-    fn start_request(&mut self, req: HttpRequestType) -> Result<ReplyHandleHttp, net_error> {
-        test_debug!(
-            "{:?},id={}: Start HTTP request {:?}",
-            &self.peer_host,
-            self.conn_id,
-            &req
-        );
-        let mut handle = self.connection.make_request_handle(
-            HTTP_REQUEST_ID_RESERVED,
-            get_epoch_time_secs() + self.timeout,
-            self.conn_id,
-        )?;
-        let stacks_msg = StacksHttpMessage::Request(req);
-        self.connection.send_message(&mut handle, &stacks_msg)?;
-        Ok(handle)
+        let address = AddressHashMode::try_from(version).ok().map(|hash_mode| {
+            let addr_version = if mainnet {
+                hash_mode.to_version_mainnet()
+            } else {
+                hash_mode.to_version_testnet()
+            };
+            StacksAddress::new(addr_version, Hash160(hashbytes)).to_b58()
+        });
+
+        Ok(RPCPoxAddress {
+            version,
+            hashbytes: to_hex(&hashbytes),
+            address,
+        })
     }
 
-    /// Start a HTTP request from this peer, and expect a response.
-    /// Non-blocking.
-    /// Only one request in-flight is allowed.
-    pub fn send_request(&mut self, req: HttpRequestType) -> Result<(), net_error> {
-        if self.is_request_inflight() {
-            test_debug!(
-                "{:?},id={}: Request in progress still",
-                &self.peer_host,
-                self.conn_id
-            );
-            return Err(net_error::InProgress);
-        }
-        if self.pending_error_response.is_some() {
-            test_debug!(
-                "{:?},id={}: Error response is inflight",
-                &self.peer_host,
-                self.conn_id
-            );
-            return Err(net_error::InProgress);
+    fn from_stacks_address(addr: StacksAddress) -> RPCPoxAddress {
+        RPCPoxAddress {
+            version: AddressHashMode::from_version(addr.version) as u8,
+            hashbytes: to_hex(&addr.bytes.0),
+            address: Some(addr.to_b58()),
         }
+    }
+}
 
-        let handle = self.start_request(req)?;
+impl RPCStackingState {
+    /// Decode a PoX contract's `stacking-state` entry
+    fn from_clarity_tuple(tuple: &TupleData, mainnet: bool) -> Result<RPCStackingState, net_error> {
+        let pox_addr = pox_expect_tuple(pox_tuple_field(tuple, "pox-addr")?, "pox-addr")?;
+        Ok(RPCStackingState {
+            amount_ustx: pox_expect_u64(pox_tuple_field(tuple, "amount-ustx")?, "amount-ustx")?,
+            pox_address: RPCPoxAddress::from_clarity_tuple(&pox_addr, mainnet)?,
+            first_reward_cycle: pox_expect_u64(
+                pox_tuple_field(tuple, "first-reward-cycle")?,
+                "first-reward-cycle",
+            )?,
+            lock_period: pox_expect_u64(pox_tuple_field(tuple, "lock-period")?, "lock-period")?,
+        })
+    }
+}
 
-        self.pending_request = Some(handle);
-        self.pending_response = None;
-        Ok(())
+impl RPCDelegationState {
+    /// Decode a PoX contract's `delegation-state` entry
+    fn from_clarity_tuple(
+        tuple: &TupleData,
+        mainnet: bool,
+    ) -> Result<RPCDelegationState, net_error> {
+        let delegated_to = match pox_tuple_field(tuple, "delegated-to")? {
+            Value::Principal(principal) => principal.to_string(),
+            _ => return Err(pox_data_error("delegated-to is not a principal")),
+        };
+        let until_burn_height =
+            match pox_expect_optional(pox_tuple_field(tuple, "until-burn-ht")?, "until-burn-ht")? {
+                Some(height) => Some(pox_expect_u64(height, "until-burn-ht")?),
+                None => None,
+            };
+        let pox_address =
+            match pox_expect_optional(pox_tuple_field(tuple, "pox-addr")?, "pox-addr")? {
+                Some(addr) => Some(RPCPoxAddress::from_clarity_tuple(
+                    &pox_expect_tuple(addr, "pox-addr")?,
+                    mainnet,
+                )?),
+                None => None,
+            };
+        Ok(RPCDelegationState {
+            amount_ustx: pox_expect_u64(pox_tuple_field(tuple, "amount-ustx")?, "amount-ustx")?,
+            delegated_to,
+            until_burn_height,
+            pox_address,
+        })
     }
+}
 
-    /// Send a HTTP error response.
-    /// Discontinues and disables sending a non-error response
-    pub fn reply_error<W: Write>(
-        &mut self,
-        fd: &mut W,
-        res: HttpResponseType,
-    ) -> Result<(), net_error> {
-        if self.is_request_inflight() || self.pending_response.is_some() {
-            test_debug!(
-                "{:?},id={}: Request or response is already in progress",
-                &self.peer_host,
-                self.conn_id
-            );
-            return Err(net_error::InProgress);
-        }
-        if self.pending_error_response.is_some() {
-            // error already in-flight
-            return Ok(());
-        }
+impl RPCStackerInfoData {
+    /// Load a principal's PoX lock-up and delegation state as of the given chain tip.
+    /// Expired stacking and delegation records are not reported, just as the PoX contract's
+    /// `get-stacker-info` and `get-check-delegation` would not report them.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        stacker: &PrincipalData,
+    ) -> Result<RPCStackerInfoData, net_error> {
+        let mainnet = chainstate.mainnet;
+        let key = Value::Tuple(
+            TupleData::from_data(vec![("stacker".into(), Value::Principal(stacker.clone()))])
+                .expect("BUG: failed to construct PoX stacker key"),
+        );
 
-        res.send(&mut self.connection.protocol, fd)?;
+        let (account, burn_block_height, stacking_state, delegation_state) = chainstate
+            .maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                let account = StacksChainState::get_account(clarity_tx, stacker);
+                let pox_contracts =
+                    StacksChainState::pox_contracts_in_epoch(clarity_tx.get_epoch());
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
+                    // report the state held by the newest PoX contract that has any
+                    let mut stacking_state = Value::none();
+                    let mut delegation_state = Value::none();
+                    for pox_contract in pox_contracts.into_iter() {
+                        let contract_identifier = boot_code_id(pox_contract, mainnet);
+                        let contract_stacking_state = clarity_db.fetch_entry_unknown_descriptor(
+                            &contract_identifier,
+                            "stacking-state",
+                            &key,
+                        )?;
+                        if contract_stacking_state != Value::none() {
+                            stacking_state = contract_stacking_state;
+                        }
+                        let contract_delegation_state = clarity_db.fetch_entry_unknown_descriptor(
+                            &contract_identifier,
+                            "delegation-state",
+                            &key,
+                        )?;
+                        if contract_delegation_state != Value::none() {
+                            delegation_state = contract_delegation_state;
+                        }
+                    }
+                    Ok::<_, ClarityRuntimeError>((
+                        account,
+                        burn_block_height,
+                        stacking_state,
+                        delegation_state,
+                    ))
+                })
+            })
+            .map_err(|_| net_error::NotFoundError)?
+            .ok_or(net_error::NotFoundError)?
+            .map_err(|e| {
+                net_error::ChainstateError(format!("Failed to load PoX state: {:?}", &e))
+            })?;
 
-        let reply = self.connection.make_relay_handle(self.conn_id)?;
+        let (locked_ustx, unlock_height) = account
+            .stx_balance
+            .get_locked_balance_at_burn_block(burn_block_height);
 
-        self.pending_error_response = Some(res);
-        self.reply_streams.push_back((reply, None, false));
-        Ok(())
+        let current_reward_cycle = burnchain
+            .block_height_to_reward_cycle(burn_block_height)
+            .unwrap_or(0);
+
+        let stacking = match pox_expect_optional(&stacking_state, "stacking-state entry")? {
+            Some(entry) => {
+                let tuple = pox_expect_tuple(entry, "stacking-state entry")?;
+                Some(RPCStackingState::from_clarity_tuple(&tuple, mainnet)?)
+            }
+            None => None,
+        }
+        .filter(|state| {
+            state.first_reward_cycle.saturating_add(state.lock_period) > current_reward_cycle
+        });
+
+        let delegation = match pox_expect_optional(&delegation_state, "delegation-state entry")? {
+            Some(entry) => {
+                let tuple = pox_expect_tuple(entry, "delegation-state entry")?;
+                Some(RPCDelegationState::from_clarity_tuple(&tuple, mainnet)?)
+            }
+            None => None,
+        }
+        .filter(|state| {
+            state
+                .until_burn_height
+                .map(|height| burn_block_height <= height)
+                .unwrap_or(true)
+        });
+
+        Ok(RPCStackerInfoData {
+            stacker: stacker.to_string(),
+            burn_block_height,
+            locked_ustx: ustx_to_u64(locked_ustx, "locked uSTX")?,
+            unlock_height,
+            stacking,
+            delegation,
+        })
     }
+}
 
-    /// Handle a GET peer info.
-    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
-    fn handle_getinfo<W: Write>(
-        http: &mut StacksHttp,
-        fd: &mut W,
-        req: &HttpRequestType,
-        network: &PeerNetwork,
-        chainstate: &StacksChainState,
-        handler_args: &RPCHandlerArgs,
-        canonical_stacks_tip_height: u64,
-    ) -> Result<(), net_error> {
-        let response_metadata =
-            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
-        let pi = RPCPeerInfoData::from_network(
-            network,
-            chainstate,
-            &handler_args.exit_at_block_height,
-            &handler_args.genesis_chainstate_hash,
-        );
-        let response = HttpResponseType::PeerInfo(response_metadata, pi);
-        response.send(http, fd)
+impl RPCMarfValue {
+    /// Read the value stored under `key`, and its proof.
+    fn from_db(clarity_db: &mut ClarityDatabase, key: &str) -> (RPCMarfValue, Value) {
+        let (value, marf_proof) = clarity_db
+            .get_with_proof::<Value>(key)
+            .map(|(value, proof)| (value, format!("0x{}", to_hex(&proof))))
+            .unwrap_or_else(|| (Value::none(), "".into()));
+        let data = format!("0x{}", value.serialize());
+        (RPCMarfValue { data, marf_proof }, value)
     }
+}
 
-    /// Handle a GET pox info.
-    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
-    fn handle_getpoxinfo<W: Write>(
-        http: &mut StacksHttp,
-        fd: &mut W,
-        req: &HttpRequestType,
+impl RPCRewardSetProofs {
+    /// Load the PoX state that the reward set for the given reward cycle is computed from, as of
+    /// the given chain tip, with MARF proofs.
+    pub fn from_db(
         sortdb: &SortitionDB,
         chainstate: &mut StacksChainState,
         tip: &StacksBlockId,
-        burnchain: &Burnchain,
-        canonical_stacks_tip_height: u64,
-    ) -> Result<(), net_error> {
-        let response_metadata =
-            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        reward_cycle: u64,
+    ) -> Result<RPCRewardSetProofs, net_error> {
+        let mainnet = chainstate.mainnet;
+        let pox_contract_names = chainstate.get_active_pox_contracts(tip)?;
+        let cycle_key = Value::Tuple(
+            TupleData::from_data(vec![(
+                "reward-cycle".into(),
+                Value::UInt(reward_cycle as u128),
+            )])
+            .expect("FATAL: failed to construct reward cycle tuple"),
+        );
 
-        match RPCPoxInfoData::from_db(sortdb, chainstate, tip, burnchain) {
-            Ok(pi) => {
-                let response = HttpResponseType::PoxInfo(response_metadata, pi);
-                response.send(http, fd)
-            }
-            Err(net_error::NotFoundError) => {
-                debug!("Chain tip not found during get PoX info: {:?}", req);
+        chainstate
+            .maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let (liquid_ustx, _) = RPCMarfValue::from_db(
+                        clarity_db,
+                        ClarityDatabase::ustx_liquid_supply_key(),
+                    );
+
+                    let mut pox_contracts = vec![];
+                    for pox_contract in pox_contract_names.into_iter() {
+                        let contract_id = boot_code_id(pox_contract, mainnet);
+                        let (rejection_fraction, _) = RPCMarfValue::from_db(
+                            clarity_db,
+                            &ClarityDatabase::make_key_for_trip(
+                                &contract_id,
+                                StoreType::Variable,
+                                "pox-rejection-fraction",
+                            ),
+                        );
+                        let (rejection_votes, _) = RPCMarfValue::from_db(
+                            clarity_db,
+                            &ClarityDatabase::make_key_for_data_map_entry(
+                                &contract_id,
+                                "stacking-rejection",
+                                &cycle_key,
+                            ),
+                        );
+                        let (num_entries, num_entries_value) = RPCMarfValue::from_db(
+                            clarity_db,
+                            &ClarityDatabase::make_key_for_data_map_entry(
+                                &contract_id,
+                                "reward-cycle-pox-address-list-len",
+                                &cycle_key,
+                            ),
+                        );
+                        let len = match get_map_entry_field(num_entries_value, "len")? {
+                            Some(Value::UInt(len)) => len,
+                            Some(_) => {
+                                return Err(net_error::DeserializeError(format!(
+                                    "Invalid reward set length in {}",
+                                    &contract_id
+                                )))
+                            }
+                            None => 0,
+                        };
+
+                        let mut entries = vec![];
+                        for index in 0..len {
+                            let entry_key = Value::Tuple(
+                                TupleData::from_data(vec![
+                                    ("reward-cycle".into(), Value::UInt(reward_cycle as u128)),
+                                    ("index".into(), Value::UInt(index)),
+                                ])
+                                .expect("FATAL: failed to construct reward set entry tuple"),
+                            );
+                            let (entry, _) = RPCMarfValue::from_db(
+                                clarity_db,
+                                &ClarityDatabase::make_key_for_data_map_entry(
+                                    &contract_id,
+                                    "reward-cycle-pox-address-list",
+                                    &entry_key,
+                                ),
+                            );
+                            entries.push(entry);
+                        }
+
+                        pox_contracts.push(RPCPoxContractProofs {
+                            contract: pox_contract.to_string(),
+                            rejection_fraction,
+                            rejection_votes,
+                            num_entries,
+                            entries,
+                        });
+                    }
+                    Ok(RPCRewardSetProofs {
+                        liquid_ustx,
+                        pox_contracts,
+                    })
+                })
+            })?
+            .ok_or(net_error::NotFoundError)?
+    }
+}
+
+impl RPCRewardSetData {
+    /// Load the PoX addresses registered for the given reward cycle as of the given chain tip,
+    /// along with how many uSTX are stacked towards each of them.  If `with_proof` is set, also
+    /// load the PoX state they are read from with MARF proofs, so light clients can check them.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+        with_proof: bool,
+    ) -> Result<RPCRewardSetData, net_error> {
+        let reward_cycle_start_burn_height = burnchain.reward_cycle_to_block_height(reward_cycle);
+
+        let is_pox_active = chainstate.is_pox_active(sortdb, tip, reward_cycle as u128)?;
+        let addresses = chainstate.get_reward_addresses(
+            burnchain,
+            sortdb,
+            reward_cycle_start_burn_height,
+            tip,
+        )?;
+        let total_stacked_ustx = ustx_to_u64(
+            chainstate.get_total_ustx_stacked(sortdb, tip, reward_cycle as u128)?,
+            "total stacked uSTX",
+        )?;
+        let liquid_ustx = ustx_to_u64(chainstate.get_liquid_ustx(tip), "liquid uSTX")?;
+
+        let entries = addresses
+            .into_iter()
+            .map(|(addr, total_ustx)| {
+                Ok(RPCRewardSetEntry {
+                    pox_address: RPCPoxAddress::from_stacks_address(addr),
+                    total_ustx: ustx_to_u64(total_ustx, "reward address total uSTX")?,
+                })
+            })
+            .collect::<Result<_, net_error>>()?;
+
+        let proofs = if with_proof {
+            Some(RPCRewardSetProofs::from_db(
+                sortdb,
+                chainstate,
+                tip,
+                reward_cycle,
+            )?)
+        } else {
+            None
+        };
+
+        Ok(RPCRewardSetData {
+            reward_cycle,
+            reward_cycle_start_burn_height,
+            is_pox_active,
+            total_stacked_ustx,
+            liquid_ustx,
+            entries,
+            proofs,
+        })
+    }
+}
+
+impl RPCAccountStateData {
+    /// Load an account's spendable and locked balance and its nonce as of the given chain tip,
+    /// which may be the unconfirmed microblock tip.  Returns None if the tip is unknown.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        account: &PrincipalData,
+    ) -> Result<Option<RPCAccountStateData>, net_error> {
+        let account_state =
+            chainstate.maybe_read_only_clarity_tx(&sortdb.index_conn(), tip, |clarity_tx| {
+                clarity_tx.with_clarity_db_readonly(|clarity_db| {
+                    let burn_block_height = clarity_db.get_current_burnchain_block_height() as u64;
+                    let balance = clarity_db.get_account_stx_balance(account);
+                    let nonce = clarity_db.get_account_nonce(account);
+
+                    let unlocked = balance.get_available_balance_at_burn_block(burn_block_height);
+                    let (locked, unlock_height) =
+                        balance.get_locked_balance_at_burn_block(burn_block_height);
+
+                    RPCAccountStateData {
+                        balance: format!("0x{}", to_hex(&unlocked.to_be_bytes())),
+                        locked: format!("0x{}", to_hex(&locked.to_be_bytes())),
+                        unlock_height,
+                        nonce,
+                    }
+                })
+            })?;
+        Ok(account_state)
+    }
+}
+
+impl RPCUnconfirmedAccountData {
+    /// Load an account's state at the anchored chain tip and at the tip of the unconfirmed
+    /// microblock stream built on it, along with the stream's transactions that touched the
+    /// account.  If there is no readable unconfirmed state, both sides report the canonical
+    /// anchored tip.  Returns None if there is no chain tip yet.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        account: &PrincipalData,
+    ) -> Result<Option<RPCUnconfirmedAccountData>, net_error> {
+        let mut unconfirmed_view = None;
+        if let Some(unconfirmed_state) = chainstate.unconfirmed_state.as_mut() {
+            let unconfirmed_tip_opt = unconfirmed_state
+                .get_unconfirmed_state_if_exists()
+                .map_err(net_error::ChainstateError)?;
+            if let Some(unconfirmed_tip) = unconfirmed_tip_opt {
+                let unconfirmed_seq = if unconfirmed_state.num_microblocks() > 0 {
+                    Some(unconfirmed_state.last_mblock_seq)
+                } else {
+                    None
+                };
+                unconfirmed_view = Some((
+                    unconfirmed_state.confirmed_chain_tip.clone(),
+                    unconfirmed_tip,
+                    unconfirmed_seq,
+                    unconfirmed_state.get_unconfirmed_txids_for_principal(account),
+                ));
+            }
+        }
+
+        let (anchored_tip, unconfirmed_tip, unconfirmed_seq, unconfirmed_txids) =
+            match unconfirmed_view {
+                Some(view) => view,
+                None => match chainstate.get_stacks_chain_tip(sortdb)? {
+                    Some(tip) => {
+                        let anchored_tip = StacksBlockHeader::make_index_block_hash(
+                            &tip.consensus_hash,
+                            &tip.anchored_block_hash,
+                        );
+                        (anchored_tip.clone(), anchored_tip, None, vec![])
+                    }
+                    None => {
+                        return Ok(None);
+                    }
+                },
+            };
+
+        let anchored =
+            match RPCAccountStateData::from_db(sortdb, chainstate, &anchored_tip, account)? {
+                Some(state) => state,
+                None => {
+                    return Ok(None);
+                }
+            };
+        let unconfirmed =
+            match RPCAccountStateData::from_db(sortdb, chainstate, &unconfirmed_tip, account)? {
+                Some(state) => state,
+                None => {
+                    return Ok(None);
+                }
+            };
+
+        Ok(Some(RPCUnconfirmedAccountData {
+            anchored_tip,
+            anchored,
+            unconfirmed_tip,
+            unconfirmed_seq,
+            unconfirmed,
+            unconfirmed_txids: unconfirmed_txids
+                .into_iter()
+                .map(|txid| txid.to_hex())
+                .collect(),
+        }))
+    }
+}
+
+impl RPCPrincipalTransactionsData {
+    /// Load a page of the transactions that touched a principal in the fork ending at `tip`,
+    /// newest first, from the address index
+    pub fn from_db(
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        cursor: Option<&IndexCursor>,
+    ) -> Result<RPCPrincipalTransactionsData, net_error> {
+        let (transactions, next_cursor) = chainstate.get_principal_transactions(
+            tip,
+            principal,
+            cursor,
+            MAX_PRINCIPAL_ACTIVITY_PAGE_SIZE,
+        )?;
+        let transactions = transactions
+            .into_iter()
+            .map(|entry| RPCPrincipalTransaction {
+                txid: entry.txid.to_hex(),
+                index_block_hash: entry.index_block_hash,
+                block_height: entry.block_height,
+                tx_index: entry.tx_index,
+                roles: entry.roles,
+            })
+            .collect();
+
+        Ok(RPCPrincipalTransactionsData {
+            principal: principal.to_string(),
+            next_cursor: next_cursor.map(|cursor| cursor.to_string()),
+            transactions,
+        })
+    }
+}
+
+impl RPCPrincipalAssetEventsData {
+    /// Load a page of the asset events that named a principal in the fork ending at `tip`,
+    /// newest first, from the address index
+    pub fn from_db(
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        cursor: Option<&IndexCursor>,
+    ) -> Result<RPCPrincipalAssetEventsData, net_error> {
+        let (events, next_cursor) = chainstate.get_principal_asset_events(
+            tip,
+            principal,
+            cursor,
+            MAX_PRINCIPAL_ACTIVITY_PAGE_SIZE,
+        )?;
+        let events = events
+            .into_iter()
+            .map(|entry| RPCPrincipalAssetEvent {
+                txid: entry.txid.to_hex(),
+                index_block_hash: entry.index_block_hash,
+                block_height: entry.block_height,
+                tx_index: entry.tx_index,
+                event_index: entry.event_index,
+                event: entry.event,
+            })
+            .collect();
+
+        Ok(RPCPrincipalAssetEventsData {
+            principal: principal.to_string(),
+            next_cursor: next_cursor.map(|cursor| cursor.to_string()),
+            events,
+        })
+    }
+}
+
+impl RPCContractEventsData {
+    /// Load a page of the contract events that match `filter` in the fork ending at `tip`,
+    /// newest first, from the event log
+    pub fn from_db(
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        filter: &ContractEventFilter,
+        cursor: Option<&IndexCursor>,
+    ) -> Result<RPCContractEventsData, net_error> {
+        let (events, next_cursor) =
+            chainstate.get_contract_events(tip, filter, cursor, MAX_CONTRACT_EVENTS_PAGE_SIZE)?;
+        let events = events
+            .into_iter()
+            .map(|entry| RPCContractEvent {
+                contract_id: entry.contract_id.to_string(),
+                topic: entry.topic,
+                txid: entry.txid.to_hex(),
+                index_block_hash: entry.index_block_hash,
+                block_height: entry.block_height,
+                tx_index: entry.tx_index,
+                event_index: entry.event_index,
+                event: entry.event,
+            })
+            .collect();
+
+        Ok(RPCContractEventsData {
+            next_cursor: next_cursor.map(|cursor| cursor.to_string()),
+            events,
+        })
+    }
+}
+
+/// Render a BNS name and namespace, as stored in the BNS contract, as a fully-qualified name
+fn bns_fqn(name: &[u8], namespace: &[u8]) -> String {
+    format!(
+        "{}.{}",
+        String::from_utf8_lossy(name),
+        String::from_utf8_lossy(namespace)
+    )
+}
+
+/// Clarity literal for the BNS contract's `{ name, namespace }` key
+fn bns_name_key_literal(name: &[u8], namespace: &[u8]) -> String {
+    format!(
+        "{{ name: 0x{}, namespace: 0x{} }}",
+        to_hex(name),
+        to_hex(namespace)
+    )
+}
+
+/// Error for BNS contract data that is not in the form we expect
+fn bns_data_error(what: &str) -> net_error {
+    net_error::ChainstateError(format!("Unexpected BNS contract data: {}", what))
+}
+
+/// Get a tuple out of BNS contract data
+fn bns_expect_tuple(value: Value, what: &str) -> Result<TupleData, net_error> {
+    match value {
+        Value::Tuple(tuple) => Ok(tuple),
+        _ => Err(bns_data_error(what)),
+    }
+}
+
+/// Get a field out of a tuple of BNS contract data
+fn bns_tuple_field<'a>(tuple: &'a TupleData, field: &str) -> Result<&'a Value, net_error> {
+    tuple
+        .get(field)
+        .map_err(|_e| bns_data_error(&format!("no '{}'", field)))
+}
+
+/// Get a buffer field out of a tuple of BNS contract data
+fn bns_tuple_buff(tuple: &TupleData, field: &str) -> Result<Vec<u8>, net_error> {
+    match bns_tuple_field(tuple, field)? {
+        Value::Sequence(SequenceData::Buffer(buff)) => Ok(buff.data.clone()),
+        _ => Err(bns_data_error(&format!("'{}' is not a buffer", field))),
+    }
+}
+
+/// Get a block height out of BNS contract data
+fn bns_expect_height(value: &Value, field: &str) -> Result<u64, net_error> {
+    match value {
+        Value::UInt(height) => u64::try_from(*height)
+            .map_err(|_e| bns_data_error(&format!("'{}' is out of range", field))),
+        _ => Err(bns_data_error(&format!("'{}' is not a uint", field))),
+    }
+}
+
+impl RPCNameResolutionData {
+    /// Resolve `name.namespace` through the BNS contract as of the given chain tip, and load its
+    /// zonefile from the Atlas DB if we have it.  Names that are in their grace period, expired or
+    /// revoked are still reported, but without lease information.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        atlasdb: &mut AtlasDB,
+        tip: &StacksBlockId,
+        name: &str,
+        namespace: &str,
+    ) -> Result<RPCNameResolutionData, net_error> {
+        let bns_contract = boot_code_id("bns", chainstate.mainnet);
+        let resolved = chainstate
+            .clarity_eval_read_only_checked(
+                &sortdb.index_conn(),
+                tip,
+                &bns_contract,
+                &format!(
+                    "(name-resolve 0x{} 0x{})",
+                    to_hex(namespace.as_bytes()),
+                    to_hex(name.as_bytes())
+                ),
+            )
+            .map_err(|e| {
+                net_error::ChainstateError(format!("Failed to resolve BNS name: {:?}", &e))
+            })?;
+
+        let (status, resolution) = match resolved {
+            Value::Response(response) => {
+                if response.committed {
+                    (
+                        "active",
+                        bns_expect_tuple(*response.data, "name-resolve result")?,
+                    )
+                } else {
+                    let status = match *response.data {
+                        Value::Int(2009) => "grace_period",
+                        Value::Int(2008) => "expired",
+                        Value::Int(2014) => "revoked",
+                        _ => return Err(net_error::NotFoundError),
+                    };
+                    // name-resolve does not hand back the name's state in these cases, so go
+                    // read it directly.
+                    let state = chainstate
+                        .clarity_eval_read_only_checked(
+                            &sortdb.index_conn(),
+                            tip,
+                            &bns_contract,
+                            &format!(
+                                "{{ owner: (unwrap-panic (nft-get-owner? names {key})), zonefile-hash: (get zonefile-hash (unwrap-panic (map-get? name-properties {key}))) }}",
+                                key = bns_name_key_literal(name.as_bytes(), namespace.as_bytes())
+                            ),
+                        )
+                        .map_err(|e| {
+                            net_error::ChainstateError(format!(
+                                "Failed to load BNS name state: {:?}",
+                                &e
+                            ))
+                        })?;
+                    (status, bns_expect_tuple(state, "name state")?)
+                }
+            }
+            _ => {
+                return Err(net_error::ChainstateError(
+                    "BUG: name-resolve did not return a response".to_string(),
+                ));
+            }
+        };
+
+        let mut data =
+            RPCNameResolutionData::from_bns_resolution(name, namespace, status, &resolution)?;
+        data.zonefile = match Hash160::from_hex(&data.zonefile_hash) {
+            Ok(content_hash) => atlasdb
+                .find_attachment(&content_hash)?
+                .map(|attachment| to_hex(&attachment.content)),
+            Err(_) => None,
+        };
+        Ok(data)
+    }
+
+    /// Decode a name's state, as `name-resolve` returns it (or, for names that are not active,
+    /// just its owner and zonefile hash).  Does not load the zonefile.
+    pub fn from_bns_resolution(
+        name: &str,
+        namespace: &str,
+        status: &str,
+        resolution: &TupleData,
+    ) -> Result<RPCNameResolutionData, net_error> {
+        let owner = match bns_tuple_field(resolution, "owner")? {
+            Value::Principal(owner) => owner.to_string(),
+            _ => return Err(bns_data_error("'owner' is not a principal")),
+        };
+        let zonefile_hash = bns_tuple_buff(resolution, "zonefile-hash")?;
+        let (lease_started_at, lease_ending_at) = if status == "active" {
+            let lease_started_at = bns_expect_height(
+                bns_tuple_field(resolution, "lease-started-at")?,
+                "lease-started-at",
+            )?;
+            let lease_ending_at = match bns_tuple_field(resolution, "lease-ending-at")? {
+                Value::Optional(OptionalData { data: Some(height) }) => {
+                    Some(bns_expect_height(height, "lease-ending-at")?)
+                }
+                Value::Optional(OptionalData { data: None }) => None,
+                _ => return Err(bns_data_error("'lease-ending-at' is not optional")),
+            };
+            (Some(lease_started_at), lease_ending_at)
+        } else {
+            (None, None)
+        };
+
+        Ok(RPCNameResolutionData {
+            name: format!("{}.{}", name, namespace),
+            status: status.to_string(),
+            owner,
+            lease_started_at,
+            lease_ending_at,
+            zonefile_hash: to_hex(&zonefile_hash),
+            zonefile: None,
+        })
+    }
+}
+
+impl RPCNamesByOwnerData {
+    /// Load the names owned by a principal as of the given chain tip.  The BNS contract only lets
+    /// a principal own one name at a time, so this is either empty or a single name.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        owner: &PrincipalData,
+    ) -> Result<RPCNamesByOwnerData, net_error> {
+        let bns_contract = boot_code_id("bns", chainstate.mainnet);
+        // owner-name is not cleared when a name is transferred away, so double-check it against
+        // the names NFT.
+        let owned = chainstate
+            .clarity_eval_read_only_checked(
+                &sortdb.index_conn(),
+                tip,
+                &bns_contract,
+                &format!(
+                    "(let ((entry (map-get? owner-name '{owner}))) (match entry name-key (if (is-eq (nft-get-owner? names name-key) (some '{owner})) entry none) none))",
+                    owner = owner
+                ),
+            )
+            .map_err(|e| {
+                net_error::ChainstateError(format!("Failed to load BNS names by owner: {:?}", &e))
+            })?;
+
+        let names = match owned {
+            Value::Optional(OptionalData { data: Some(entry) }) => {
+                let tuple = bns_expect_tuple(*entry, "owner-name entry")?;
+                let name = bns_tuple_buff(&tuple, "name")?;
+                let namespace = bns_tuple_buff(&tuple, "namespace")?;
+                vec![bns_fqn(&name, &namespace)]
+            }
+            Value::Optional(OptionalData { data: None }) => vec![],
+            _ => return Err(bns_data_error("owner-name entry is not optional")),
+        };
+
+        Ok(RPCNamesByOwnerData {
+            owner: owner.to_string(),
+            names,
+        })
+    }
+}
+
+impl RPCNamespaceNamesData {
+    /// Load one page of the names registered in a namespace.  The BNS contract has no index of
+    /// the names in a namespace, so we rebuild it from the BNS attachment instances in the Atlas
+    /// DB.  The names are paged before they are checked against the chain, so that a request
+    /// only checks one page of names; names that don't exist as of the given chain tip are left
+    /// out of their page.
+    pub fn from_db(
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        atlasdb: &AtlasDB,
+        tip: &StacksBlockId,
+        namespace: &str,
+        page: u32,
+    ) -> Result<RPCNamespaceNamesData, net_error> {
+        let bns_contract = boot_code_id("bns", chainstate.mainnet);
+        let mut candidates = BTreeSet::new();
+        for instance in atlasdb
+            .find_attachment_instances_for_contract(&bns_contract)?
+            .into_iter()
+        {
+            let metadata = match Value::try_deserialize_hex_untyped(&instance.metadata) {
+                Ok(Value::Tuple(metadata)) => metadata,
+                _ => continue,
+            };
+            match (metadata.get("name"), metadata.get("namespace")) {
+                (
+                    Ok(Value::Sequence(SequenceData::Buffer(name))),
+                    Ok(Value::Sequence(SequenceData::Buffer(name_namespace))),
+                ) if name_namespace.data == namespace.as_bytes() => {
+                    candidates.insert(name.data.clone());
+                }
+                _ => {}
+            }
+        }
+
+        let start = (page as usize).saturating_mul(MAX_BNS_NAMES_PAGE_SIZE);
+        let has_more = candidates.len() > start.saturating_add(MAX_BNS_NAMES_PAGE_SIZE);
+        let candidates: Vec<_> = candidates
+            .into_iter()
+            .skip(start)
+            .take(MAX_BNS_NAMES_PAGE_SIZE)
+            .collect();
+
+        let mut names = vec![];
+        if !candidates.is_empty() {
+            // check the whole page in one evaluation
+            let exists = chainstate
+                .clarity_eval_read_only_checked(
+                    &sortdb.index_conn(),
+                    tip,
+                    &bns_contract,
+                    &format!(
+                        "(list {})",
+                        candidates
+                            .iter()
+                            .map(|name| format!(
+                                "(is-some (nft-get-owner? names {}))",
+                                bns_name_key_literal(name, namespace.as_bytes())
+                            ))
+                            .collect::<Vec<_>>()
+                            .join(" ")
+                    ),
+                )
+                .map_err(|e| {
+                    net_error::ChainstateError(format!("Failed to load BNS names: {:?}", &e))
+                })?;
+            let exists = match exists {
+                Value::Sequence(SequenceData::List(list))
+                    if list.data.len() == candidates.len() =>
+                {
+                    list.data
+                }
+                _ => {
+                    return Err(bns_data_error(
+                        "name checks are not a list of the page's size",
+                    ))
+                }
+            };
+            for (name, exists) in candidates.iter().zip(exists.into_iter()) {
+                if exists == Value::Bool(true) {
+                    names.push(bns_fqn(name, namespace.as_bytes()));
+                }
+            }
+        }
+
+        Ok(RPCNamespaceNamesData {
+            namespace: namespace.to_string(),
+            page,
+            has_more,
+            names,
+        })
+    }
+}
+
+impl RPCNeighborsInfo {
+    /// Load neighbor address information from the peer network
+    pub fn from_p2p(
+        network_id: u32,
+        network_epoch: u8,
+        peers: &PeerMap,
+        chain_view: &BurnchainView,
+        peerdb: &PeerDB,
+    ) -> Result<RPCNeighborsInfo, net_error> {
+        let neighbor_sample = PeerDB::get_random_neighbors(
+            peerdb.conn(),
+            network_id,
+            network_epoch,
+            MAX_NEIGHBORS_DATA_LEN,
+            chain_view.burn_block_height,
+            false,
+        )
+        .map_err(net_error::DBError)?;
+
+        let sample: Vec<RPCNeighbor> = neighbor_sample
+            .into_iter()
+            .map(|n| {
+                RPCNeighbor::from_neighbor_key_and_pubkh(
+                    n.addr.clone(),
+                    Hash160::from_node_public_key(&n.public_key),
+                    true,
+                )
+            })
+            .collect();
+
+        let mut inbound = vec![];
+        let mut outbound = vec![];
+        for (_, convo) in peers.iter() {
+            let nk = convo.to_neighbor_key();
+            let naddr = convo.to_neighbor_address();
+            if convo.is_outbound() {
+                outbound.push(RPCNeighbor::from_neighbor_key_and_pubkh(
+                    nk,
+                    naddr.public_key_hash,
+                    convo.is_authenticated(),
+                ));
+            } else {
+                inbound.push(RPCNeighbor::from_neighbor_key_and_pubkh(
+                    nk,
+                    naddr.public_key_hash,
+                    convo.is_authenticated(),
+                ));
+            }
+        }
+
+        Ok(RPCNeighborsInfo {
+            sample: sample,
+            inbound: inbound,
+            outbound: outbound,
+        })
+    }
+}
+
+impl ConversationHttp {
+    pub fn new(
+        peer_addr: SocketAddr,
+        outbound_url: Option<UrlString>,
+        peer_host: PeerHost,
+        conn_opts: &ConnectionOptions,
+        conn_id: usize,
+    ) -> ConversationHttp {
+        let mut stacks_http = StacksHttp::new(peer_addr.clone());
+        stacks_http.maximum_call_argument_size = conn_opts.maximum_call_argument_size;
+        ConversationHttp {
+            connection: ConnectionHttp::new(stacks_http, conn_opts, None),
+            conn_id: conn_id,
+            timeout: conn_opts.timeout,
+            reply_streams: VecDeque::new(),
+            peer_addr: peer_addr,
+            outbound_url: outbound_url,
+            peer_host: peer_host,
+            canonical_stacks_tip_height: None,
+            pending_request: None,
+            pending_response: None,
+            pending_error_response: None,
+            keep_alive: true,
+            total_request_count: 0,
+            total_reply_count: 0,
+            last_request_timestamp: 0,
+            last_response_timestamp: 0,
+            connection_time: get_epoch_time_secs(),
+        }
+    }
+
+    /// How many ongoing requests do we have on this conversation?
+    pub fn num_pending_outbound(&self) -> usize {
+        self.reply_streams.len()
+    }
+
+    /// What's our outbound URL?
+    pub fn get_url(&self) -> Option<&UrlString> {
+        self.outbound_url.as_ref()
+    }
+
+    /// What's our peer IP address?
+    pub fn get_peer_addr(&self) -> &SocketAddr {
+        &self.peer_addr
+    }
+
+    /// Is a request in-progress?
+    pub fn is_request_inflight(&self) -> bool {
+        self.pending_request.is_some()
+    }
+
+    /// Start a HTTP request from this peer, and expect a response.
+    /// Returns the request handle; does not set the handle into this connection.
+    fn start_request(&mut self, req: HttpRequestType) -> Result<ReplyHandleHttp, net_error> {
+        test_debug!(
+            "{:?},id={}: Start HTTP request {:?}",
+            &self.peer_host,
+            self.conn_id,
+            &req
+        );
+        let mut handle = self.connection.make_request_handle(
+            HTTP_REQUEST_ID_RESERVED,
+            get_epoch_time_secs() + self.timeout,
+            self.conn_id,
+        )?;
+        let stacks_msg = StacksHttpMessage::Request(req);
+        self.connection.send_message(&mut handle, &stacks_msg)?;
+        Ok(handle)
+    }
+
+    /// Start a HTTP request from this peer, and expect a response.
+    /// Non-blocking.
+    /// Only one request in-flight is allowed.
+    pub fn send_request(&mut self, req: HttpRequestType) -> Result<(), net_error> {
+        if self.is_request_inflight() {
+            test_debug!(
+                "{:?},id={}: Request in progress still",
+                &self.peer_host,
+                self.conn_id
+            );
+            return Err(net_error::InProgress);
+        }
+        if self.pending_error_response.is_some() {
+            test_debug!(
+                "{:?},id={}: Error response is inflight",
+                &self.peer_host,
+                self.conn_id
+            );
+            return Err(net_error::InProgress);
+        }
+
+        let handle = self.start_request(req)?;
+
+        self.pending_request = Some(handle);
+        self.pending_response = None;
+        Ok(())
+    }
+
+    /// Send a HTTP error response.
+    /// Discontinues and disables sending a non-error response
+    pub fn reply_error<W: Write>(
+        &mut self,
+        fd: &mut W,
+        res: HttpResponseType,
+    ) -> Result<(), net_error> {
+        if self.is_request_inflight() || self.pending_response.is_some() {
+            test_debug!(
+                "{:?},id={}: Request or response is already in progress",
+                &self.peer_host,
+                self.conn_id
+            );
+            return Err(net_error::InProgress);
+        }
+        if self.pending_error_response.is_some() {
+            // error already in-flight
+            return Ok(());
+        }
+
+        res.send(&mut self.connection.protocol, fd)?;
+
+        let reply = self.connection.make_relay_handle(self.conn_id)?;
+
+        self.pending_error_response = Some(res);
+        self.reply_streams.push_back((reply, None, false));
+        Ok(())
+    }
+
+    /// Handle a GET peer info.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getinfo<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        network: &PeerNetwork,
+        chainstate: &StacksChainState,
+        handler_args: &RPCHandlerArgs,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let pi = RPCPeerInfoData::from_network(
+            network,
+            chainstate,
+            &handler_args.exit_at_block_height,
+            &handler_args.genesis_chainstate_hash,
+        );
+        let response = HttpResponseType::PeerInfo(response_metadata, pi);
+        response.send(http, fd)
+    }
+
+    /// Handle a GET pox info.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_getpoxinfo<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCPoxInfoData::from_db(sortdb, chainstate, tip, burnchain) {
+            Ok(pi) => {
+                let response = HttpResponseType::PoxInfo(response_metadata, pi);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Chain tip not found during get PoX info: {:?}", req);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Failed to find chain tip".to_string(),
+                );
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to get PoX info {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query peer info".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET stacker info.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_stacker_info<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        stacker: &PrincipalData,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCStackerInfoData::from_db(sortdb, chainstate, tip, burnchain, stacker) {
+            Ok(stacker_info) => {
+                let response = HttpResponseType::StackerInfo(response_metadata, stacker_info);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Chain tip not found during get stacker info: {:?}", req);
+                let response = HttpResponseType::NotFound(
+                    response_metadata,
+                    "Failed to find chain tip".to_string(),
+                );
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to get stacker info {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query stacker info".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET reward set.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_reward_set<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        burnchain: &Burnchain,
+        reward_cycle: u64,
+        with_proof: bool,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCRewardSetData::from_db(
+            sortdb,
+            chainstate,
+            tip,
+            burnchain,
+            reward_cycle,
+            with_proof,
+        ) {
+            Ok(reward_set) => {
+                let response = HttpResponseType::RewardSet(response_metadata, reward_set);
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to get reward set {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query reward set".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET BNS name resolution.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_name_resolution<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        atlasdb: &mut AtlasDB,
+        tip: &StacksBlockId,
+        name: &str,
+        namespace: &str,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCNameResolutionData::from_db(sortdb, chainstate, atlasdb, tip, name, namespace) {
+            Ok(resolution) => {
+                let response = HttpResponseType::NameResolution(response_metadata, resolution);
+                response.send(http, fd)
+            }
+            Err(net_error::NotFoundError) => {
+                debug!("Name not found during get name resolution: {:?}", req);
+                let response =
+                    HttpResponseType::NotFound(response_metadata, "Name not found".to_string());
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to resolve name {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to resolve name".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET BNS names by owner.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_names_by_owner<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        owner: &PrincipalData,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCNamesByOwnerData::from_db(sortdb, chainstate, tip, owner) {
+            Ok(names) => {
+                let response = HttpResponseType::NamesByOwner(response_metadata, names);
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to get names by owner {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query names by owner".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET BNS namespace names.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_namespace_names<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        atlasdb: &AtlasDB,
+        tip: &StacksBlockId,
+        namespace: &str,
+        page: u32,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match RPCNamespaceNamesData::from_db(sortdb, chainstate, atlasdb, tip, namespace, page) {
+            Ok(names) => {
+                let response = HttpResponseType::NamespaceNames(response_metadata, names);
+                response.send(http, fd)
+            }
+            Err(e) => {
+                warn!("Failed to get namespace names {:?}: {:?}", req, &e);
+                let response = HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query namespace names".to_string(),
+                );
+                response.send(http, fd)
+            }
+        }
+    }
+
+    /// Handle a GET cost voting report.
+    /// The response will be synchronously written to the given fd (so use a fd that can buffer!)
+    fn handle_get_cost_voting<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        tip: &StacksBlockId,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+
+        match chainstate.get_cost_voting_report(sortdb, tip) {
+            Ok(report) => {
+                let response = HttpResponseType::CostVoting(response_metadata, report);
+                response.send(http, fd)
+            }
+            Err(chain_error::NoSuchBlockError) => {
+                debug!("Chain tip not found during get cost voting: {:?}", req);
                 let response = HttpResponseType::NotFound(
                     response_metadata,
                     "Failed to find chain tip".to_string(),
@@ -671,10 +1871,10 @@ impl ConversationHttp {
                 response.send(http, fd)
             }
             Err(e) => {
-                warn!("Failed to get PoX info {:?}: {:?}", req, &e);
+                warn!("Failed to get cost voting report {:?}: {:?}", req, &e);
                 let response = HttpResponseType::ServerError(
                     response_metadata,
-                    "Failed to query peer info".to_string(),
+                    "Failed to query cost voting state".to_string(),
                 );
                 response.send(http, fd)
             }
@@ -1179,6 +2379,11 @@ impl ConversationHttp {
                             .unwrap_or_else(|| (0, None))
                     };
 
+                    let balance_data = match balance_proof {
+                        Some(ref proof) if proof.len() > 0 => Some(balance.serialize()),
+                        _ => None,
+                    };
+
                     let unlocked = balance.get_available_balance_at_burn_block(burn_block_height);
                     let (locked, unlock_height) =
                         balance.get_locked_balance_at_burn_block(burn_block_height);
@@ -1193,6 +2398,7 @@ impl ConversationHttp {
                         nonce,
                         balance_proof,
                         nonce_proof,
+                        balance_data,
                     }
                 })
             }) {
@@ -1205,6 +2411,117 @@ impl ConversationHttp {
         response.send(http, fd).map(|_| ())
     }
 
+    /// Handle a GET on an account's anchored and unconfirmed state, side by side, along with
+    /// the txids of the unconfirmed microblock transactions that touched it.
+    fn handle_get_unconfirmed_account<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &mut StacksChainState,
+        account: &PrincipalData,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = match RPCUnconfirmedAccountData::from_db(sortdb, chainstate, account) {
+            Ok(Some(data)) => HttpResponseType::GetUnconfirmedAccount(response_metadata, data),
+            Ok(None) => HttpResponseType::NotFound(response_metadata, "Chain tip not found".into()),
+            Err(e) => {
+                warn!("Failed to load unconfirmed account {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query unconfirmed account state".to_string(),
+                )
+            }
+        };
+
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET on a page of the transactions that touched a principal, given the chain tip.
+    /// The response will be synchronously written to the fd.
+    fn handle_get_principal_transactions<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        cursor: Option<&IndexCursor>,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response =
+            match RPCPrincipalTransactionsData::from_db(chainstate, tip, principal, cursor) {
+                Ok(data) => HttpResponseType::PrincipalTransactions(response_metadata, data),
+                Err(e) => {
+                    warn!("Failed to load principal transactions {:?}: {:?}", req, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        "Failed to query principal transactions".to_string(),
+                    )
+                }
+            };
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET on a page of the asset events that named a principal, given the chain tip.
+    /// The response will be synchronously written to the fd.
+    fn handle_get_principal_asset_events<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        principal: &PrincipalData,
+        cursor: Option<&IndexCursor>,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response =
+            match RPCPrincipalAssetEventsData::from_db(chainstate, tip, principal, cursor) {
+                Ok(data) => HttpResponseType::PrincipalAssetEvents(response_metadata, data),
+                Err(e) => {
+                    warn!("Failed to load principal asset events {:?}: {:?}", req, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        "Failed to query principal asset events".to_string(),
+                    )
+                }
+            };
+        response.send(http, fd).map(|_| ())
+    }
+
+    /// Handle a GET on a page of the contract event log, given the chain tip.
+    /// The response will be synchronously written to the fd.
+    fn handle_get_contract_events<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        chainstate: &StacksChainState,
+        tip: &StacksBlockId,
+        filter: &ContractEventFilter,
+        cursor: Option<&IndexCursor>,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response = match RPCContractEventsData::from_db(chainstate, tip, filter, cursor) {
+            Ok(data) => HttpResponseType::ContractEvents(response_metadata, data),
+            Err(e) => {
+                warn!("Failed to load contract events {:?}: {:?}", req, &e);
+                HttpResponseType::ServerError(
+                    response_metadata,
+                    "Failed to query contract events".to_string(),
+                )
+            }
+        };
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Handle a GET on a smart contract's data var, given the current chain tip.  Optionally
     /// supplies a MARF proof for the value.
     fn handle_get_data_var<W: Write>(
@@ -1701,6 +3018,129 @@ impl ConversationHttp {
         return response.send(http, fd).map(|_| ());
     }
 
+    /// Find out where a transaction is: mined on the canonical fork (if the txid index has it),
+    /// mined in the unconfirmed microblock stream, pending in the mempool, or dropped from it.
+    fn load_transaction_receipt(
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        mempool: &MemPoolDB,
+        txid: &Txid,
+    ) -> Result<Option<TransactionReceiptResponse>, net_error> {
+        let mut receipt = TransactionReceiptResponse {
+            txid: txid.to_hex(),
+            status: TransactionStatus::Pending,
+            txindex: chainstate.txindex,
+            tx: None,
+            result: None,
+            events: None,
+            execution_cost: None,
+            post_condition_aborted: None,
+        };
+
+        // mined on the canonical fork?  Check against the tip the unconfirmed state builds on,
+        // if there is one.
+        let anchored_tip = match chainstate.unconfirmed_state.as_ref() {
+            Some(unconfirmed) => Some(unconfirmed.confirmed_chain_tip.clone()),
+            None => chainstate.get_stacks_chain_tip(sortdb)?.map(|tip| {
+                StacksBlockHeader::make_index_block_hash(
+                    &tip.consensus_hash,
+                    &tip.anchored_block_hash,
+                )
+            }),
+        };
+        if let Some(anchored_tip) = anchored_tip {
+            if let Some(indexed) = chainstate.get_indexed_transaction_at_tip(&anchored_tip, txid)? {
+                receipt.status = TransactionStatus::Anchored {
+                    index_block_hash: indexed.index_block_hash,
+                    block_hash: indexed.block_hash,
+                    block_height: indexed.block_height,
+                    tx_index: indexed.tx_index,
+                    microblock_hash: indexed.microblock_hash,
+                    microblock_seq: indexed.microblock_sequence,
+                };
+                // rows logged before the txid index existed only hold the txid
+                if indexed.tx_index.is_some() {
+                    receipt.tx = Some(indexed.tx_hex);
+                }
+                receipt.result = indexed.result_hex;
+                receipt.events = indexed.events;
+                receipt.execution_cost = indexed.execution_cost;
+                receipt.post_condition_aborted = indexed.post_condition_aborted;
+                return Ok(Some(receipt));
+            }
+        }
+
+        // mined in the unconfirmed state?
+        if let Some(unconfirmed) = chainstate.unconfirmed_state.as_ref() {
+            if let Some((transaction, mblock_hash, seq)) =
+                unconfirmed.get_unconfirmed_transaction(txid)
+            {
+                receipt.status = TransactionStatus::Microblock {
+                    block_hash: mblock_hash,
+                    seq,
+                };
+                receipt.tx = Some(to_hex(&transaction.serialize_to_vec()));
+                return Ok(Some(receipt));
+            }
+        }
+
+        // pending in the mempool?
+        if let Some(txinfo) = MemPoolDB::get_tx(mempool.conn(), txid)? {
+            receipt.tx = Some(to_hex(&txinfo.tx.serialize_to_vec()));
+            return Ok(Some(receipt));
+        }
+
+        // dropped from the mempool?
+        if let Some(reason) = MemPoolDB::get_dropped_tx_reason(mempool.conn(), txid)? {
+            receipt.status = TransactionStatus::Dropped { reason };
+            return Ok(Some(receipt));
+        }
+
+        Ok(None)
+    }
+
+    /// Handle a GET on a transaction's status and, if it was mined on the canonical fork and the
+    /// txid index is on, its receipt.
+    /// The response will be synchronously written to the fd.
+    fn handle_gettransaction<W: Write>(
+        http: &mut StacksHttp,
+        fd: &mut W,
+        req: &HttpRequestType,
+        sortdb: &SortitionDB,
+        chainstate: &StacksChainState,
+        mempool: &MemPoolDB,
+        txid: &Txid,
+        canonical_stacks_tip_height: u64,
+    ) -> Result<(), net_error> {
+        let response_metadata =
+            HttpResponseMetadata::from_http_request_type(req, Some(canonical_stacks_tip_height));
+        let response =
+            match ConversationHttp::load_transaction_receipt(sortdb, chainstate, mempool, txid) {
+                Ok(Some(receipt)) => {
+                    HttpResponseType::TransactionReceipt(response_metadata, receipt)
+                }
+                Ok(None) if !chainstate.txindex => HttpResponseType::NotFound(
+                    response_metadata,
+                    format!(
+                        "No such transaction {} in the mempool; the txid index is disabled, so mined transactions cannot be found",
+                        txid
+                    ),
+                ),
+                Ok(None) => HttpResponseType::NotFound(
+                    response_metadata,
+                    format!("No such transaction {}", txid),
+                ),
+                Err(e) => {
+                    warn!("Failed to load transaction {}: {:?}", txid, &e);
+                    HttpResponseType::ServerError(
+                        response_metadata,
+                        format!("Failed to query transaction {}", txid),
+                    )
+                }
+            };
+        response.send(http, fd).map(|_| ())
+    }
+
     /// Load up the canonical Stacks chain tip.  Note that this is subject to both burn chain block
     /// Stacks block availability -- different nodes with different partial replicas of the Stacks chain state
     /// will return different values here.
@@ -1872,6 +3312,37 @@ impl ConversationHttp {
                 }
             }
 
+            let mut confirmation_targets = vec![];
+            for target_blocks in FEE_CONFIRMATION_TARGETS.iter() {
+                let target_fee_rates = match fee_estimator
+                    .get_rate_estimates_for_target(*target_blocks)
+                {
+                    Ok(x) => x,
+                    Err(e) => {
+                        debug!(
+                                "Estimator RPC endpoint failed to estimate fees for tx: {} within {} blocks: {}",
+                                tx.name(),
+                                target_blocks,
+                                e
+                            );
+                        continue;
+                    }
+                };
+
+                let mut target_estimations =
+                    RPCFeeEstimate::estimate_fees(scalar_cost, target_fee_rates);
+                for estimate in target_estimations.iter_mut() {
+                    if estimate.fee < minimum_fee {
+                        estimate.fee = minimum_fee;
+                    }
+                }
+
+                confirmation_targets.push(RPCFeeConfirmationTarget {
+                    target_blocks: *target_blocks,
+                    estimations: target_estimations,
+                });
+            }
+
             let response = HttpResponseType::TransactionFeeEstimation(
                 response_metadata,
                 RPCFeeEstimateResponse {
@@ -1879,6 +3350,7 @@ impl ConversationHttp {
                     estimations,
                     estimated_cost_scalar: scalar_cost,
                     cost_scalar_change_by_byte: metric.change_per_byte(),
+                    confirmation_targets,
                 },
             );
             response.send(http, fd)
@@ -2204,14 +3676,66 @@ impl ConversationHttp {
                     &mut self.connection.protocol,
                     &mut reply,
                     &req,
-                    network,
+                    network,
+                    chainstate,
+                    handler_opts,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
+            HttpRequestType::GetPoxInfo(ref _md, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_getpoxinfo(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &network.burnchain,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetStackerInfo(ref _md, ref stacker, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
                     chainstate,
-                    handler_opts,
                     network.burnchain_tip.canonical_stacks_tip_height,
-                )?;
+                )? {
+                    ConversationHttp::handle_get_stacker_info(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        &network.burnchain,
+                        stacker,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
                 None
             }
-            HttpRequestType::GetPoxInfo(ref _md, ref tip_req) => {
+            HttpRequestType::GetRewardSet(
+                ref _md,
+                ref reward_cycle,
+                ref tip_req,
+                ref with_proof,
+            ) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
                     &mut reply,
@@ -2221,7 +3745,7 @@ impl ConversationHttp {
                     chainstate,
                     network.burnchain_tip.canonical_stacks_tip_height,
                 )? {
-                    ConversationHttp::handle_getpoxinfo(
+                    ConversationHttp::handle_get_reward_set(
                         &mut self.connection.protocol,
                         &mut reply,
                         &req,
@@ -2229,6 +3753,103 @@ impl ConversationHttp {
                         chainstate,
                         &tip,
                         &network.burnchain,
+                        *reward_cycle,
+                        *with_proof,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetNameResolution(ref _md, ref name, ref namespace, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_name_resolution(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &mut network.atlasdb,
+                        &tip,
+                        name,
+                        namespace,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetNamesByOwner(ref _md, ref owner, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_names_by_owner(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
+                        owner,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetNamespaceNames(ref _md, ref namespace, ref page, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_namespace_names(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &network.atlasdb,
+                        &tip,
+                        namespace,
+                        *page,
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetCostVoting(ref _md, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_cost_voting(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        sortdb,
+                        chainstate,
+                        &tip,
                         network.burnchain_tip.canonical_stacks_tip_height,
                     )?;
                 }
@@ -2322,6 +3943,19 @@ impl ConversationHttp {
                 )?;
                 None
             }
+            HttpRequestType::GetTransaction(ref _md, ref txid) => {
+                ConversationHttp::handle_gettransaction(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    mempool,
+                    txid,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
             HttpRequestType::GetAccount(ref _md, ref principal, ref tip_req, ref with_proof) => {
                 if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
                     &mut self.connection.protocol,
@@ -2346,6 +3980,97 @@ impl ConversationHttp {
                 }
                 None
             }
+            HttpRequestType::GetUnconfirmedAccount(ref _md, ref principal) => {
+                ConversationHttp::handle_get_unconfirmed_account(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    sortdb,
+                    chainstate,
+                    principal,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )?;
+                None
+            }
+            HttpRequestType::GetPrincipalTransactions(
+                ref _md,
+                ref principal,
+                ref cursor,
+                ref tip_req,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_principal_transactions(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        principal,
+                        cursor.as_ref(),
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetPrincipalAssetEvents(
+                ref _md,
+                ref principal,
+                ref cursor,
+                ref tip_req,
+            ) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_principal_asset_events(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        principal,
+                        cursor.as_ref(),
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
+            HttpRequestType::GetContractEvents(ref _md, ref filter, ref cursor, ref tip_req) => {
+                if let Some(tip) = ConversationHttp::handle_load_stacks_chain_tip(
+                    &mut self.connection.protocol,
+                    &mut reply,
+                    &req,
+                    tip_req,
+                    sortdb,
+                    chainstate,
+                    network.burnchain_tip.canonical_stacks_tip_height,
+                )? {
+                    ConversationHttp::handle_get_contract_events(
+                        &mut self.connection.protocol,
+                        &mut reply,
+                        &req,
+                        chainstate,
+                        &tip,
+                        filter,
+                        cursor.as_ref(),
+                        network.burnchain_tip.canonical_stacks_tip_height,
+                    )?;
+                }
+                None
+            }
             HttpRequestType::GetDataVar(
                 ref _md,
                 ref contract_addr,
@@ -3125,28 +4850,107 @@ impl ConversationHttp {
                 }
             };
 
-            total_sz += sz;
-            if sz > 0 {
-                self.last_response_timestamp = get_epoch_time_secs();
-            } else {
-                break;
-            }
-        }
-        monitoring::update_inbound_rpc_bandwidth(total_sz as i64);
-        Ok(total_sz)
+            total_sz += sz;
+            if sz > 0 {
+                self.last_response_timestamp = get_epoch_time_secs();
+            } else {
+                break;
+            }
+        }
+        monitoring::update_inbound_rpc_bandwidth(total_sz as i64);
+        Ok(total_sz)
+    }
+
+    /// Make a new getinfo request to this endpoint
+    pub fn new_getinfo(&self, stacks_height: Option<u64>) -> HttpRequestType {
+        HttpRequestType::GetInfo(HttpRequestMetadata::from_host(
+            self.peer_host.clone(),
+            stacks_height,
+        ))
+    }
+
+    /// Make a new getinfo request to this endpoint
+    pub fn new_getpoxinfo(&self, tip_req: TipRequest) -> HttpRequestType {
+        HttpRequestType::GetPoxInfo(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            tip_req,
+        )
+    }
+
+    /// Make a new request for a stacker's PoX state
+    pub fn new_get_stacker_info(
+        &self,
+        stacker: PrincipalData,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetStackerInfo(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            stacker,
+            tip_req,
+        )
+    }
+
+    /// Make a new request for a reward cycle's reward set
+    pub fn new_get_reward_set(
+        &self,
+        reward_cycle: u64,
+        tip_req: TipRequest,
+        with_proof: bool,
+    ) -> HttpRequestType {
+        HttpRequestType::GetRewardSet(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            reward_cycle,
+            tip_req,
+            with_proof,
+        )
+    }
+
+    /// Make a new request to resolve a BNS name
+    pub fn new_get_name_resolution(
+        &self,
+        name: String,
+        namespace: String,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetNameResolution(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            name,
+            namespace,
+            tip_req,
+        )
+    }
+
+    /// Make a new request for the BNS names owned by a principal
+    pub fn new_get_names_by_owner(
+        &self,
+        owner: PrincipalData,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetNamesByOwner(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            owner,
+            tip_req,
+        )
     }
 
-    /// Make a new getinfo request to this endpoint
-    pub fn new_getinfo(&self, stacks_height: Option<u64>) -> HttpRequestType {
-        HttpRequestType::GetInfo(HttpRequestMetadata::from_host(
-            self.peer_host.clone(),
-            stacks_height,
-        ))
+    /// Make a new request for a page of the BNS names in a namespace
+    pub fn new_get_namespace_names(
+        &self,
+        namespace: String,
+        page: u32,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetNamespaceNames(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            namespace,
+            page,
+            tip_req,
+        )
     }
 
-    /// Make a new getinfo request to this endpoint
-    pub fn new_getpoxinfo(&self, tip_req: TipRequest) -> HttpRequestType {
-        HttpRequestType::GetPoxInfo(
+    /// Make a new request for the cost-voting contract's state and the effective cost functions
+    pub fn new_get_cost_voting(&self, tip_req: TipRequest) -> HttpRequestType {
+        HttpRequestType::GetCostVoting(
             HttpRequestMetadata::from_host(self.peer_host.clone(), None),
             tip_req,
         )
@@ -3217,6 +5021,14 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for a transaction's status and receipt
+    pub fn new_gettransaction(&self, txid: Txid) -> HttpRequestType {
+        HttpRequestType::GetTransaction(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            txid,
+        )
+    }
+
     /// Make a new post-transaction request
     pub fn new_post_transaction(&self, tx: StacksTransaction) -> HttpRequestType {
         HttpRequestType::PostTransaction(
@@ -3263,6 +5075,59 @@ impl ConversationHttp {
         )
     }
 
+    /// Make a new request for an account's anchored and unconfirmed state
+    pub fn new_get_unconfirmed_account(&self, principal: PrincipalData) -> HttpRequestType {
+        HttpRequestType::GetUnconfirmedAccount(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            principal,
+        )
+    }
+
+    /// Make a new request for a page of the transactions that touched a principal
+    pub fn new_get_principal_transactions(
+        &self,
+        principal: PrincipalData,
+        cursor: Option<IndexCursor>,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetPrincipalTransactions(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            principal,
+            cursor,
+            tip_req,
+        )
+    }
+
+    /// Make a new request for a page of the asset events that named a principal
+    pub fn new_get_principal_asset_events(
+        &self,
+        principal: PrincipalData,
+        cursor: Option<IndexCursor>,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetPrincipalAssetEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            principal,
+            cursor,
+            tip_req,
+        )
+    }
+
+    /// Make a new request for a page of the contract event log
+    pub fn new_get_contract_events(
+        &self,
+        filter: ContractEventFilter,
+        cursor: Option<IndexCursor>,
+        tip_req: TipRequest,
+    ) -> HttpRequestType {
+        HttpRequestType::GetContractEvents(
+            HttpRequestMetadata::from_host(self.peer_host.clone(), None),
+            filter,
+            cursor,
+            tip_req,
+        )
+    }
+
     /// Make a new request for a data var
     pub fn new_getdatavar(
         &self,
@@ -3404,6 +5269,7 @@ mod test {
     use crate::net::http::*;
     use crate::net::test::*;
     use crate::net::*;
+    use clarity::vm::costs::cost_functions::ClarityCostFunction;
     use clarity::vm::types::*;
     use stacks_common::address::*;
     use stacks_common::util::get_epoch_time_secs;
@@ -3415,6 +5281,7 @@ mod test {
     use crate::types::chainstate::BurnchainHeaderHash;
 
     use crate::core::mempool::{BLOOM_COUNTER_ERROR_RATE, MAX_BLOOM_COUNTER_TXS};
+    use crate::util_lib::boot::boot_code_addr;
 
     use super::*;
 
@@ -3593,6 +5460,32 @@ mod test {
         tx_signer.sign_origin(&privk1).unwrap();
         let tx_contract_signed = tx_signer.get_tx().unwrap();
 
+        // have addr2 delegate to addr1, so there is PoX state to report
+        let mut tx_delegate = StacksTransaction::new(
+            TransactionVersion::Testnet,
+            TransactionAuth::from_p2pkh(&privk2).unwrap(),
+            TransactionPayload::new_contract_call(
+                boot_code_addr(false),
+                "pox",
+                "delegate-stx",
+                vec![
+                    Value::UInt(123456),
+                    Value::Principal(addr1.to_account_principal()),
+                    Value::some(Value::UInt(10000)).unwrap(),
+                    Value::none(),
+                ],
+            )
+            .unwrap(),
+        );
+
+        tx_delegate.chain_id = 0x80000000;
+        tx_delegate.auth.set_origin_nonce(0);
+        tx_delegate.set_tx_fee(0);
+
+        let mut tx_signer = StacksTransactionSigner::new(&tx_delegate);
+        tx_signer.sign_origin(&privk2).unwrap();
+        let tx_delegate_signed = tx_signer.get_tx().unwrap();
+
         // update account and state in a microblock that will be unconfirmed
         let mut tx_cc = StacksTransaction::new(
             TransactionVersion::Testnet,
@@ -3685,7 +5578,11 @@ mod test {
                         block_builder,
                         chainstate,
                         &sortdb.index_conn(),
-                        vec![tx_coinbase_signed.clone(), tx_contract_signed.clone()],
+                        vec![
+                            tx_coinbase_signed.clone(),
+                            tx_contract_signed.clone(),
+                            tx_delegate_signed.clone(),
+                        ],
                     )
                     .unwrap();
 
@@ -4004,36 +5901,259 @@ mod test {
         ));
     }
 
-    /// This test tests two things:
-    /// (1) the get info RPC call
-    /// (2) whether the ConversationHttp object gets correctly updated with a peer's canonical
-    /// stacks tip height, which is sent in HTTP headers as part of the request/response
+    /// This test tests two things:
+    /// (1) the get info RPC call
+    /// (2) whether the ConversationHttp object gets correctly updated with a peer's canonical
+    /// stacks tip height, which is sent in HTTP headers as part of the request/response
+    #[test]
+    #[ignore]
+    fn test_rpc_getinfo() {
+        let peer_server_info = RefCell::new(None);
+        let client_stacks_height = 17;
+        test_rpc(
+            "test_rpc_getinfo",
+            40000,
+            40001,
+            50000,
+            50001,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let peer_info = RPCPeerInfoData::from_network(
+                    &peer_server.network,
+                    &peer_server.stacks_node.as_ref().unwrap().chainstate,
+                    &None,
+                    &Sha256Sum::zero(),
+                );
+
+                *peer_server_info.borrow_mut() = Some(peer_info);
+
+                convo_client.new_getinfo(Some(client_stacks_height))
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                assert_eq!(convo_client.canonical_stacks_tip_height, Some(1));
+                assert_eq!(
+                    convo_server.canonical_stacks_tip_height,
+                    Some(client_stacks_height)
+                );
+                match http_response {
+                    HttpResponseType::PeerInfo(response_md, peer_data) => {
+                        assert_eq!(Some((*peer_data).clone()), *peer_server_info.borrow());
+                        assert!(peer_data.node_public_key.is_some());
+                        assert!(peer_data.node_public_key_hash.is_some());
+                        assert_eq!(
+                            peer_data.node_public_key_hash,
+                            Some(Hash160::from_node_public_key(
+                                &peer_data
+                                    .node_public_key
+                                    .clone()
+                                    .unwrap()
+                                    .to_public_key()
+                                    .unwrap()
+                            ))
+                        );
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getpoxinfo() {
+        // Test v2/pox (aka GetPoxInfo) endpoint.
+        // In this test, `tip_req` is set to UseLatestAnchoredTip.
+        // Thus, the query for pox info will be against the canonical Stacks tip, which we expect to succeed.
+        let pox_server_info = RefCell::new(None);
+        test_rpc(
+            "test_rpc_getpoxinfo",
+            40002,
+            40003,
+            50002,
+            50003,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let pox_info = RPCPoxInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                )
+                .unwrap();
+                *pox_server_info.borrow_mut() = Some(pox_info);
+                convo_client.new_getpoxinfo(TipRequest::UseLatestAnchoredTip)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             convo_client,
+             convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::PoxInfo(response_md, pox_data) => {
+                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_getpoxinfo_use_latest_tip() {
+        // Test v2/pox (aka GetPoxInfo) endpoint.
+        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying for pox
+        // info against the unconfirmed state will succeed.
+        let pox_server_info = RefCell::new(None);
+        test_rpc(
+            "test_rpc_getpoxinfo_use_latest_tip",
+            40004,
+            40005,
+            50004,
+            50005,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = chainstate
+                    .unconfirmed_state
+                    .as_ref()
+                    .unwrap()
+                    .unconfirmed_chain_tip
+                    .clone();
+                let pox_info = RPCPoxInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                )
+                .unwrap();
+                *pox_server_info.borrow_mut() = Some(pox_info);
+                convo_client.new_getpoxinfo(TipRequest::UseLatestUnconfirmedTip)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::PoxInfo(response_md, pox_data) => {
+                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
-    fn test_rpc_getinfo() {
-        let peer_server_info = RefCell::new(None);
-        let client_stacks_height = 17;
+    fn test_rpc_get_stacker_info() {
+        // Test v2/pox/stackers/:principal (aka GetStackerInfo) endpoint.
+        // The answer should match what we decode from the PoX contract at the canonical tip.
+        let stacker_server_info = RefCell::new(None);
         test_rpc(
-            "test_rpc_getinfo",
-            40000,
-            40001,
-            50000,
-            50001,
+            "test_rpc_get_stacker_info",
+            40200,
+            40201,
+            50200,
+            50201,
             true,
             |ref mut peer_client,
              ref mut convo_client,
              ref mut peer_server,
              ref mut convo_server| {
-                let peer_info = RPCPeerInfoData::from_network(
-                    &peer_server.network,
-                    &peer_server.stacks_node.as_ref().unwrap().chainstate,
-                    &None,
-                    &Sha256Sum::zero(),
-                );
-
-                *peer_server_info.borrow_mut() = Some(peer_info);
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                // the test setup has this principal neither stacking nor delegating...
+                let not_stacker =
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal();
+                let not_stacker_info = RPCStackerInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                    &not_stacker,
+                )
+                .unwrap();
+                assert_eq!(not_stacker_info.locked_ustx, 0);
+                assert_eq!(not_stacker_info.stacking, None);
+                assert_eq!(not_stacker_info.delegation, None);
 
-                convo_client.new_getinfo(Some(client_stacks_height))
+                // ...and this one delegating to the first
+                let stacker = StacksAddress::from_string("STVN97YYA10MY5F6KQJHKNYJNM24C4A1AT39WRW")
+                    .unwrap()
+                    .to_account_principal();
+                let stacker_info = RPCStackerInfoData::from_db(
+                    &mut sortdb,
+                    chainstate,
+                    &stacks_block_id,
+                    &peer_client.config.burnchain,
+                    &stacker,
+                )
+                .unwrap();
+                assert_eq!(stacker_info.locked_ustx, 0);
+                assert_eq!(stacker_info.stacking, None);
+                assert_eq!(
+                    stacker_info.delegation,
+                    Some(RPCDelegationState {
+                        amount_ustx: 123456,
+                        delegated_to: not_stacker.to_string(),
+                        until_burn_height: Some(10000),
+                        pox_address: None,
+                    })
+                );
+                *stacker_server_info.borrow_mut() = Some(stacker_info);
+                convo_client.new_get_stacker_info(stacker, TipRequest::UseLatestAnchoredTip)
             },
             |ref http_request,
              ref http_response,
@@ -4042,27 +6162,9 @@ mod test {
              ref convo_client,
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
-                assert_eq!(convo_client.canonical_stacks_tip_height, Some(1));
-                assert_eq!(
-                    convo_server.canonical_stacks_tip_height,
-                    Some(client_stacks_height)
-                );
                 match http_response {
-                    HttpResponseType::PeerInfo(response_md, peer_data) => {
-                        assert_eq!(Some((*peer_data).clone()), *peer_server_info.borrow());
-                        assert!(peer_data.node_public_key.is_some());
-                        assert!(peer_data.node_public_key_hash.is_some());
-                        assert_eq!(
-                            peer_data.node_public_key_hash,
-                            Some(Hash160::from_node_public_key(
-                                &peer_data
-                                    .node_public_key
-                                    .clone()
-                                    .unwrap()
-                                    .to_public_key()
-                                    .unwrap()
-                            ))
-                        );
+                    HttpResponseType::StackerInfo(response_md, stacker_data) => {
+                        assert_eq!(Some((*stacker_data).clone()), *stacker_server_info.borrow());
                         true
                     }
                     _ => {
@@ -4076,17 +6178,17 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_getpoxinfo() {
-        // Test v2/pox (aka GetPoxInfo) endpoint.
-        // In this test, `tip_req` is set to UseLatestAnchoredTip.
-        // Thus, the query for pox info will be against the canonical Stacks tip, which we expect to succeed.
-        let pox_server_info = RefCell::new(None);
+    fn test_rpc_get_reward_set() {
+        // Test v2/pox/reward_set/:cycle (aka GetRewardSet) endpoint.
+        // Query the upcoming reward cycle, and check that the answer matches what the chainstate
+        // reports for it.
+        let reward_set_server_info = RefCell::new(None);
         test_rpc(
-            "test_rpc_getpoxinfo",
-            40002,
-            40003,
-            50002,
-            50003,
+            "test_rpc_get_reward_set",
+            40202,
+            40203,
+            50202,
+            50203,
             true,
             |ref mut peer_client,
              ref mut convo_client,
@@ -4101,26 +6203,43 @@ mod test {
                         &tip.anchored_block_hash,
                     )
                 };
-                let pox_info = RPCPoxInfoData::from_db(
+                let burnchain = &peer_client.config.burnchain;
+                let burn_tip = SortitionDB::get_canonical_burn_chain_tip(sortdb.conn()).unwrap();
+                let reward_cycle = burnchain
+                    .block_height_to_reward_cycle(burn_tip.block_height)
+                    .unwrap()
+                    + 1;
+                let reward_set = RPCRewardSetData::from_db(
                     &mut sortdb,
                     chainstate,
                     &stacks_block_id,
-                    &peer_client.config.burnchain,
+                    burnchain,
+                    reward_cycle,
+                    true,
                 )
                 .unwrap();
-                *pox_server_info.borrow_mut() = Some(pox_info);
-                convo_client.new_getpoxinfo(TipRequest::UseLatestAnchoredTip)
+                assert_eq!(reward_set.reward_cycle, reward_cycle);
+                assert!(reward_set.proofs.is_some());
+                *reward_set_server_info.borrow_mut() = Some(reward_set);
+                convo_client.new_get_reward_set(
+                    reward_cycle,
+                    TipRequest::UseLatestAnchoredTip,
+                    true,
+                )
             },
             |ref http_request,
              ref http_response,
              ref mut peer_client,
              ref mut peer_server,
-             convo_client,
-             convo_server| {
+             ref convo_client,
+             ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::PoxInfo(response_md, pox_data) => {
-                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                    HttpResponseType::RewardSet(response_md, reward_set_data) => {
+                        assert_eq!(
+                            Some((*reward_set_data).clone()),
+                            *reward_set_server_info.borrow()
+                        );
                         true
                     }
                     _ => {
@@ -4134,17 +6253,143 @@ mod test {
 
     #[test]
     #[ignore]
-    fn test_rpc_getpoxinfo_use_latest_tip() {
-        // Test v2/pox (aka GetPoxInfo) endpoint.
-        // In this test, we set `tip_req` to UseLatestUnconfirmedTip, and we expect that querying for pox
-        // info against the unconfirmed state will succeed.
-        let pox_server_info = RefCell::new(None);
+    fn test_rpc_get_missing_name_resolution() {
+        // Test v2/names/:name (aka GetNameResolution) endpoint.
+        // No names have been registered, so this should be a 404.
         test_rpc(
-            "test_rpc_getpoxinfo_use_latest_tip",
-            40004,
-            40005,
-            50004,
-            50005,
+            "test_rpc_get_missing_name_resolution",
+            40204,
+            40205,
+            50204,
+            50205,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_name_resolution(
+                    "alice".to_string(),
+                    "btc".to_string(),
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NotFound(response_md, msg) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_name_resolution_from_bns_resolution() {
+        let owner = PrincipalData::parse("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R").unwrap();
+        let zonefile_hash = Value::buff_from(vec![0x11; 20]).unwrap();
+
+        // an active name, as name-resolve returns it
+        let resolution = TupleData::from_data(vec![
+            ("owner".into(), Value::Principal(owner.clone())),
+            ("zonefile-hash".into(), zonefile_hash.clone()),
+            ("lease-started-at".into(), Value::UInt(100)),
+            (
+                "lease-ending-at".into(),
+                Value::some(Value::UInt(52660)).unwrap(),
+            ),
+        ])
+        .unwrap();
+        let data =
+            RPCNameResolutionData::from_bns_resolution("alice", "btc", "active", &resolution)
+                .unwrap();
+        assert_eq!(
+            data,
+            RPCNameResolutionData {
+                name: "alice.btc".to_string(),
+                status: "active".to_string(),
+                owner: "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R".to_string(),
+                lease_started_at: Some(100),
+                lease_ending_at: Some(52660),
+                zonefile_hash: "11".repeat(20),
+                zonefile: None,
+            }
+        );
+
+        // names in namespaces without leases never expire
+        let resolution = TupleData::from_data(vec![
+            ("owner".into(), Value::Principal(owner.clone())),
+            ("zonefile-hash".into(), zonefile_hash.clone()),
+            ("lease-started-at".into(), Value::UInt(100)),
+            ("lease-ending-at".into(), Value::none()),
+        ])
+        .unwrap();
+        let data =
+            RPCNameResolutionData::from_bns_resolution("alice", "btc", "active", &resolution)
+                .unwrap();
+        assert_eq!(data.lease_started_at, Some(100));
+        assert_eq!(data.lease_ending_at, None);
+
+        // names that aren't active have no lease information
+        let state = TupleData::from_data(vec![
+            ("owner".into(), Value::Principal(owner.clone())),
+            ("zonefile-hash".into(), zonefile_hash.clone()),
+        ])
+        .unwrap();
+        let data =
+            RPCNameResolutionData::from_bns_resolution("alice", "btc", "expired", &state).unwrap();
+        assert_eq!(data.status, "expired");
+        assert_eq!(data.lease_started_at, None);
+        assert_eq!(data.lease_ending_at, None);
+
+        // contract data that isn't in the expected form is an error, not a panic
+        assert!(
+            RPCNameResolutionData::from_bns_resolution("alice", "btc", "active", &state).is_err()
+        );
+        let bad_height = TupleData::from_data(vec![
+            ("owner".into(), Value::Principal(owner.clone())),
+            ("zonefile-hash".into(), zonefile_hash.clone()),
+            (
+                "lease-started-at".into(),
+                Value::UInt(u128::from(u64::MAX) + 1),
+            ),
+            ("lease-ending-at".into(), Value::none()),
+        ])
+        .unwrap();
+        assert!(
+            RPCNameResolutionData::from_bns_resolution("alice", "btc", "active", &bad_height)
+                .is_err()
+        );
+        let bad_owner = TupleData::from_data(vec![
+            ("owner".into(), Value::UInt(1)),
+            ("zonefile-hash".into(), zonefile_hash),
+        ])
+        .unwrap();
+        assert!(
+            RPCNameResolutionData::from_bns_resolution("alice", "btc", "expired", &bad_owner)
+                .is_err()
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_names_by_owner() {
+        // Test v2/names/owners/:principal (aka GetNamesByOwner) endpoint.
+        // The answer should match what we read from the BNS contract at the canonical tip.
+        let names_server_info = RefCell::new(None);
+        test_rpc(
+            "test_rpc_get_names_by_owner",
+            40206,
+            40207,
+            50206,
+            50207,
             true,
             |ref mut peer_client,
              ref mut convo_client,
@@ -4152,21 +6397,146 @@ mod test {
              ref mut convo_server| {
                 let mut sortdb = peer_server.sortdb.as_mut().unwrap();
                 let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
-                let stacks_block_id = chainstate
-                    .unconfirmed_state
-                    .as_ref()
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let owner = StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
                     .unwrap()
-                    .unconfirmed_chain_tip
-                    .clone();
-                let pox_info = RPCPoxInfoData::from_db(
+                    .to_account_principal();
+                let names =
+                    RPCNamesByOwnerData::from_db(&mut sortdb, chainstate, &stacks_block_id, &owner)
+                        .unwrap();
+                assert_eq!(names.names.len(), 0);
+                *names_server_info.borrow_mut() = Some(names);
+                convo_client.new_get_names_by_owner(owner, TipRequest::UseLatestAnchoredTip)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NamesByOwner(response_md, names_data) => {
+                        assert_eq!(Some((*names_data).clone()), *names_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_namespace_names() {
+        // Test v2/namespaces/:namespace/names (aka GetNamespaceNames) endpoint.
+        // The answer should match what we rebuild from the Atlas DB at the canonical tip.
+        let names_server_info = RefCell::new(None);
+        test_rpc(
+            "test_rpc_get_namespace_names",
+            40208,
+            40209,
+            50208,
+            50209,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let names = RPCNamespaceNamesData::from_db(
                     &mut sortdb,
                     chainstate,
+                    &peer_server.network.atlasdb,
                     &stacks_block_id,
-                    &peer_client.config.burnchain,
+                    "btc",
+                    0,
                 )
                 .unwrap();
-                *pox_server_info.borrow_mut() = Some(pox_info);
-                convo_client.new_getpoxinfo(TipRequest::UseLatestUnconfirmedTip)
+                assert_eq!(names.namespace, "btc");
+                assert_eq!(names.page, 0);
+                assert!(names.names.len() <= MAX_BNS_NAMES_PAGE_SIZE);
+                *names_server_info.borrow_mut() = Some(names);
+                convo_client.new_get_namespace_names(
+                    "btc".to_string(),
+                    0,
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::NamespaceNames(response_md, names_data) => {
+                        assert_eq!(Some((*names_data).clone()), *names_server_info.borrow());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_cost_voting() {
+        // Test v2/cost_voting (aka GetCostVoting) endpoint.
+        // No proposals have been made, so every cost function should be the boot cost contract's.
+        let cost_voting_server_info = RefCell::new(None);
+        test_rpc(
+            "test_rpc_get_cost_voting",
+            40210,
+            40211,
+            50210,
+            50211,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let mut sortdb = peer_server.sortdb.as_mut().unwrap();
+                let chainstate = &mut peer_server.stacks_node.as_mut().unwrap().chainstate;
+                let stacks_block_id = {
+                    let tip = chainstate.get_stacks_chain_tip(sortdb).unwrap().unwrap();
+                    StacksBlockHeader::make_index_block_hash(
+                        &tip.consensus_hash,
+                        &tip.anchored_block_hash,
+                    )
+                };
+                let report = chainstate
+                    .get_cost_voting_report(&mut sortdb, &stacks_block_id)
+                    .unwrap();
+                assert!(report.proposals.is_empty());
+                assert!(report.confirmed_overrides.is_empty());
+                assert!(report.contract_call_costs.is_empty());
+                assert_eq!(report.cost_functions.len(), ClarityCostFunction::ALL.len());
+                assert!(report.cost_functions.iter().all(|f| !f.overridden));
+                *cost_voting_server_info.borrow_mut() = Some(report);
+                convo_client.new_get_cost_voting(TipRequest::UseLatestAnchoredTip)
             },
             |ref http_request,
              ref http_response,
@@ -4176,8 +6546,8 @@ mod test {
              ref convo_server| {
                 let req_md = http_request.metadata().clone();
                 match http_response {
-                    HttpResponseType::PoxInfo(response_md, pox_data) => {
-                        assert_eq!(Some((*pox_data).clone()), *pox_server_info.borrow());
+                    HttpResponseType::CostVoting(response_md, report) => {
+                        assert_eq!(Some((*report).clone()), *cost_voting_server_info.borrow());
                         true
                     }
                     _ => {
@@ -4831,6 +7201,111 @@ mod test {
         );
     }
 
+    #[test]
+    #[ignore]
+    fn test_rpc_gettransaction_microblock() {
+        let last_txid = RefCell::new(Txid([0u8; 32]));
+        let last_mblock = RefCell::new(BlockHeaderHash([0u8; 32]));
+
+        test_rpc(
+            "test_rpc_gettransaction_microblock",
+            40160,
+            40161,
+            50160,
+            50161,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                let sortdb = peer_server.sortdb.take().unwrap();
+                Relayer::setup_unconfirmed_state(peer_server.chainstate(), &sortdb).unwrap();
+                peer_server.sortdb = Some(sortdb);
+
+                let (txid, mblock_hash) = match peer_server.chainstate().unconfirmed_state {
+                    Some(ref unconfirmed) => {
+                        let (txid, (_, mblock_hash, ..)) =
+                            unconfirmed.mined_txs.iter().next().unwrap();
+                        (txid.clone(), mblock_hash.clone())
+                    }
+                    None => {
+                        panic!("No unconfirmed state");
+                    }
+                };
+
+                *last_txid.borrow_mut() = txid.clone();
+                *last_mblock.borrow_mut() = mblock_hash.clone();
+
+                convo_client.new_gettransaction(txid)
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::TransactionReceipt(response_md, receipt) => {
+                        assert_eq!(receipt.txid, last_txid.borrow().to_hex());
+                        assert_eq!(
+                            receipt.status,
+                            TransactionStatus::Microblock {
+                                block_hash: (*last_mblock.borrow()).clone(),
+                                seq: 0
+                            }
+                        );
+                        let tx = StacksTransaction::consensus_deserialize(
+                            &mut &hex_bytes(receipt.tx.as_ref().unwrap()).unwrap()[..],
+                        )
+                        .unwrap();
+                        assert_eq!(tx.txid(), *last_txid.borrow());
+                        assert!(receipt.result.is_none());
+                        assert!(receipt.events.is_none());
+                        assert!(!receipt.txindex);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_gettransaction_not_found() {
+        test_rpc(
+            "test_rpc_gettransaction_not_found",
+            40162,
+            40163,
+            50162,
+            50163,
+            false,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_gettransaction(Txid([0x11; 32]))
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::NotFound(..) => true,
+                    _ => {
+                        error!("Invalid response: {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_missing_getblock() {
@@ -5409,6 +7884,266 @@ mod test {
         );
     }
 
+    /// The account sent two microblock transactions in the unconfirmed stream, so its anchored
+    /// and unconfirmed state should differ and both txids should be listed.
+    #[test]
+    #[ignore]
+    fn test_rpc_get_unconfirmed_account() {
+        test_rpc(
+            "test_rpc_get_unconfirmed_account",
+            40116,
+            40117,
+            50116,
+            50117,
+            true,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_unconfirmed_account(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetUnconfirmedAccount(response_md, data) => {
+                        let unconfirmed_state =
+                            peer_server.chainstate().unconfirmed_state.as_ref().unwrap();
+                        assert_eq!(data.anchored_tip, unconfirmed_state.confirmed_chain_tip);
+                        assert_eq!(
+                            data.unconfirmed_tip,
+                            unconfirmed_state.unconfirmed_chain_tip
+                        );
+                        assert_eq!(
+                            data.unconfirmed_seq,
+                            Some(unconfirmed_state.last_mblock_seq)
+                        );
+
+                        assert_eq!(data.anchored.nonce, 2);
+                        let balance =
+                            u128::from_str_radix(&data.anchored.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000);
+
+                        assert_eq!(data.unconfirmed.nonce, 4);
+                        let balance =
+                            u128::from_str_radix(&data.unconfirmed.balance[2..], 16).unwrap();
+                        assert_eq!(balance, 1000000000 - 123);
+
+                        assert_eq!(data.unconfirmed_txids.len(), 2);
+                        for txid_hex in data.unconfirmed_txids.iter() {
+                            let txid = Txid::from_hex(txid_hex).unwrap();
+                            assert!(unconfirmed_state
+                                .get_unconfirmed_transaction(&txid)
+                                .is_some());
+                        }
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    /// With no microblock stream, both sides of the response report the anchored tip.
+    #[test]
+    #[ignore]
+    fn test_rpc_get_unconfirmed_account_no_microblocks() {
+        test_rpc(
+            "test_rpc_get_unconfirmed_account_no_microblocks",
+            40118,
+            40119,
+            50118,
+            50119,
+            false,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_unconfirmed_account(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                let req_md = http_request.metadata().clone();
+                match http_response {
+                    HttpResponseType::GetUnconfirmedAccount(response_md, data) => {
+                        assert_eq!(data.anchored_tip, data.unconfirmed_tip);
+                        assert_eq!(data.unconfirmed_seq, None);
+                        assert_eq!(data.anchored, data.unconfirmed);
+                        assert_eq!(data.anchored.nonce, 2);
+                        assert!(data.unconfirmed_txids.is_empty());
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_principal_transactions() {
+        test_rpc(
+            "test_rpc_get_principal_transactions",
+            40164,
+            40165,
+            50164,
+            50165,
+            false,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_principal_transactions(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    Some(IndexCursor {
+                        block_height: 1,
+                        tx_index: 0,
+                        event_index: 0,
+                    }),
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::PrincipalTransactions(response_md, data) => {
+                        assert_eq!(data.principal, "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R");
+                        assert!(data.transactions.len() <= MAX_PRINCIPAL_ACTIVITY_PAGE_SIZE);
+                        for tx in data.transactions.iter() {
+                            assert!(tx.block_height < 1);
+                        }
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_principal_asset_events() {
+        test_rpc(
+            "test_rpc_get_principal_asset_events",
+            40166,
+            40167,
+            50166,
+            50167,
+            false,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_principal_asset_events(
+                    StacksAddress::from_string("ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R")
+                        .unwrap()
+                        .to_account_principal(),
+                    None,
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::PrincipalAssetEvents(response_md, data) => {
+                        assert_eq!(data.principal, "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R");
+                        assert!(data.events.len() <= MAX_PRINCIPAL_ACTIVITY_PAGE_SIZE);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
+    #[test]
+    #[ignore]
+    fn test_rpc_get_contract_events() {
+        test_rpc(
+            "test_rpc_get_contract_events",
+            40174,
+            40175,
+            50174,
+            50175,
+            false,
+            |ref mut peer_client,
+             ref mut convo_client,
+             ref mut peer_server,
+             ref mut convo_server| {
+                convo_client.new_get_contract_events(
+                    ContractEventFilter {
+                        contract_id: Some(
+                            QualifiedContractIdentifier::parse(
+                                "ST2DS4MSWSGJ3W9FBC6BVT0Y92S345HY8N3T6AV7R.hello-world",
+                            )
+                            .unwrap(),
+                        ),
+                        topic: Some("test".to_string()),
+                        from_height: Some(1),
+                        to_height: None,
+                    },
+                    None,
+                    TipRequest::UseLatestAnchoredTip,
+                )
+            },
+            |ref http_request,
+             ref http_response,
+             ref mut peer_client,
+             ref mut peer_server,
+             ref convo_client,
+             ref convo_server| {
+                match http_response {
+                    HttpResponseType::ContractEvents(response_md, data) => {
+                        assert!(data.events.len() <= MAX_CONTRACT_EVENTS_PAGE_SIZE);
+                        true
+                    }
+                    _ => {
+                        error!("Invalid response; {:?}", &http_response);
+                        false
+                    }
+                }
+            },
+        );
+    }
+
     #[test]
     #[ignore]
     fn test_rpc_get_data_var() {
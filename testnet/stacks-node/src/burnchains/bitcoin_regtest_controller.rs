@@ -19,6 +19,9 @@ use super::super::operations::BurnchainOpSigner;
 use super::super::Config;
 use super::{BurnchainController, BurnchainTip, Error as BurnchainControllerError};
 
+use stacks::burnchains::bitcoin::blockfile::{
+    BitcoinBlockFileIndexer, BitcoinBlockFileIndexerConfig,
+};
 use stacks::burnchains::bitcoin::indexer::{
     BitcoinIndexer, BitcoinIndexerConfig, BitcoinIndexerRuntime,
 };
@@ -66,17 +69,33 @@ use stacks::types::chainstate::BurnchainHeaderHash;
 ///  passed since the UTXO cache was last refreshed before
 ///  the cache is force-reset.
 const UTXO_CACHE_STALENESS_LIMIT: u64 = 6;
+/// The number of bitcoin blocks that can have
+///  passed since a superseded block-commit was last sent
+///  before it is no longer reported.
+const SUPERSEDED_BLOCK_COMMIT_STALENESS_LIMIT: u64 = 6;
 const DUST_UTXO_LIMIT: u64 = 5500;
+/// Estimated size of a 1-input, 1-output p2pkh transaction, used to size CPFP children
+const CPFP_CHILD_TX_ESTIM_SIZE: u64 = 192;
+/// Name of the file in the burnchain working directory that reports outstanding block-commits
+const BLOCK_COMMIT_STATUS_FILE: &str = "block_commits.json";
 
 pub struct BitcoinRegtestController {
     config: Config,
     indexer: BitcoinIndexer,
+    /// Reads burnchain blocks from local block files, if `burnchain.block_files_path` is set.
+    /// It shares the SPV headers DB with `indexer`.
+    block_file_indexer: Option<BitcoinBlockFileIndexer>,
     db: Option<SortitionDB>,
     burnchain_db: Option<BurnchainDB>,
     chain_tip: Option<BurnchainTip>,
     use_coordinator: Option<CoordinatorChannels>,
     burnchain_config: Option<Burnchain>,
     ongoing_block_commit: Option<OngoingBlockCommit>,
+    /// Block-commits we stopped tracking while they were still unconfirmed, because a newer
+    /// commit had to be sent from different UTXOs.
+    superseded_block_commits: Vec<OngoingBlockCommit>,
+    /// Cached (burn block height, fee rate) from the last fee rate estimate
+    fee_rate_estimate: Option<(u64, u64)>,
     should_keep_running: Option<Arc<AtomicBool>>,
 }
 
@@ -85,12 +104,93 @@ struct OngoingBlockCommit {
     utxos: UTXOSet,
     fees: LeaderBlockCommitFees,
     txids: Vec<Txid>,
+    /// The most recently sent version of this block-commit
+    last_tx: Transaction,
+    /// Every transaction sent on behalf of this block-commit, in order
+    attempts: Vec<BlockCommitAttempt>,
 }
 
 impl OngoingBlockCommit {
     fn sum_utxos(&self) -> u64 {
         self.utxos.total_available()
     }
+
+    /// Fee rate of the first transaction sent for this block-commit.  RBF and CPFP budgets are
+    /// relative to it.
+    fn initial_fee_rate(&self) -> u64 {
+        self.attempts
+            .first()
+            .map(|attempt| attempt.fee_rate)
+            .unwrap_or(self.fees.fee_rate)
+    }
+
+    /// The last CPFP child sent for this block-commit, if it has not been replaced since.
+    fn last_cpfp_attempt(&self) -> Option<&BlockCommitAttempt> {
+        match self.attempts.last() {
+            Some(attempt) if attempt.kind == BlockCommitAttemptKind::ChildPaysForParent => {
+                Some(attempt)
+            }
+            _ => None,
+        }
+    }
+
+    fn to_status(&self, superseded: bool) -> BlockCommitStatus {
+        BlockCommitStatus {
+            block_header_hash: self.payload.block_header_hash.to_hex(),
+            parent_block_ptr: self.payload.parent_block_ptr,
+            parent_vtxindex: self.payload.parent_vtxindex,
+            burn_fee: self.payload.burn_fee,
+            txid: self
+                .txids
+                .last()
+                .map(|txid| txid.to_hex())
+                .unwrap_or_default(),
+            replacements: self.txids.len().saturating_sub(1),
+            total_spent: self.fees.total_spent(),
+            superseded,
+            attempts: self.attempts.clone(),
+        }
+    }
+}
+
+/// How a transaction for a block-commit came about
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockCommitAttemptKind {
+    Initial,
+    ReplaceByFee,
+    ChildPaysForParent,
+}
+
+/// One transaction sent for a block-commit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockCommitAttempt {
+    pub txid: String,
+    pub kind: BlockCommitAttemptKind,
+    /// For a block-commit, its own fee rate.  For a CPFP child, the fee rate of the
+    /// parent-and-child package.
+    pub fee_rate: u64,
+    /// Absolute fee paid by this transaction
+    pub fee: u64,
+    pub tx_size: u64,
+    pub burn_block_height: u64,
+}
+
+/// Status report for an outstanding block-commit
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BlockCommitStatus {
+    pub block_header_hash: String,
+    pub parent_block_ptr: u32,
+    pub parent_vtxindex: u16,
+    pub burn_fee: u64,
+    /// The current (most recent) block-commit transaction
+    pub txid: String,
+    /// How many times the block-commit has been replaced by fee
+    pub replacements: usize,
+    pub total_spent: u64,
+    /// Whether or not a newer block-commit was sent without replacing this one
+    pub superseded: bool,
+    pub attempts: Vec<BlockCommitAttempt>,
 }
 
 #[derive(Clone)]
@@ -111,7 +211,8 @@ impl LeaderBlockCommitFees {
         payload: &LeaderBlockCommitOp,
         config: &Config,
     ) -> LeaderBlockCommitFees {
-        let mut fees = LeaderBlockCommitFees::estimated_fees_from_payload(payload, config);
+        let mut fees =
+            LeaderBlockCommitFees::estimated_fees_from_payload(payload, config, self.fee_rate);
         fees.spent_in_attempts = cmp::max(1, self.spent_in_attempts);
         fees.final_size = self.final_size;
         fees.fee_rate = self.fee_rate + config.burnchain.rbf_fee_increment;
@@ -122,6 +223,7 @@ impl LeaderBlockCommitFees {
     pub fn estimated_fees_from_payload(
         payload: &LeaderBlockCommitOp,
         config: &Config,
+        fee_rate: u64,
     ) -> LeaderBlockCommitFees {
         let sunset_fee = if payload.sunset_burn > 0 {
             cmp::max(payload.sunset_burn, DUST_UTXO_LIMIT)
@@ -133,7 +235,6 @@ impl LeaderBlockCommitFees {
         let value_per_transfer = payload.burn_fee / number_of_transfers;
         let sortition_fee = value_per_transfer * number_of_transfers;
         let spent_in_attempts = 0;
-        let fee_rate = config.burnchain.satoshis_per_byte;
         let default_tx_size = config.burnchain.block_commit_tx_estimated_size;
 
         LeaderBlockCommitFees {
@@ -252,15 +353,39 @@ impl BitcoinRegtestController {
             runtime: indexer_runtime,
         };
 
+        let block_file_indexer = config
+            .burnchain
+            .block_files_path
+            .as_ref()
+            .map(|blocks_path| {
+                info!(
+                    "Reading burnchain blocks from block files in {}",
+                    blocks_path
+                );
+                BitcoinBlockFileIndexer::new(
+                    BitcoinBlockFileIndexerConfig {
+                        blocks_path: blocks_path.clone(),
+                        spv_headers_path: indexer_config.spv_headers_path.clone(),
+                        first_block: indexer_config.first_block,
+                        magic_bytes: indexer_config.magic_bytes,
+                        epochs: indexer_config.epochs.clone(),
+                    },
+                    network_type,
+                )
+            });
+
         Self {
             use_coordinator: coordinator_channel,
             config,
             indexer: burnchain_indexer,
+            block_file_indexer,
             db: None,
             burnchain_db: None,
             chain_tip: None,
             burnchain_config,
             ongoing_block_commit: None,
+            superseded_block_commits: vec![],
+            fee_rate_estimate: None,
             should_keep_running,
         }
     }
@@ -300,11 +425,14 @@ impl BitcoinRegtestController {
             use_coordinator: None,
             config,
             indexer: burnchain_indexer,
+            block_file_indexer: None,
             db: None,
             burnchain_db: None,
             chain_tip: None,
             burnchain_config: None,
             ongoing_block_commit: None,
+            superseded_block_commits: vec![],
+            fee_rate_estimate: None,
             should_keep_running: None,
         }
     }
@@ -416,13 +544,24 @@ impl BitcoinRegtestController {
             if !self.should_keep_running() {
                 return Err(BurnchainControllerError::CoordinatorClosed);
             }
-            match burnchain.sync_with_indexer(
-                &mut self.indexer,
-                coordinator_comms.clone(),
-                target_block_height_opt,
-                Some(burnchain.pox_constants.reward_cycle_length as u64),
-                self.should_keep_running.clone(),
-            ) {
+            let max_blocks_opt = Some(burnchain.pox_constants.reward_cycle_length as u64);
+            let sync_result = match self.block_file_indexer {
+                Some(ref mut block_file_indexer) => burnchain.sync_with_indexer(
+                    block_file_indexer,
+                    coordinator_comms.clone(),
+                    target_block_height_opt,
+                    max_blocks_opt,
+                    self.should_keep_running.clone(),
+                ),
+                None => burnchain.sync_with_indexer(
+                    &mut self.indexer,
+                    coordinator_comms.clone(),
+                    target_block_height_opt,
+                    max_blocks_opt,
+                    self.should_keep_running.clone(),
+                ),
+            };
+            match sync_result {
                 Ok(x) => {
                     increment_btc_blocks_received_counter();
 
@@ -933,6 +1072,68 @@ impl BitcoinRegtestController {
         Some(tx)
     }
 
+    /// Get the height of the highest burnchain header we know about
+    fn get_burn_block_height(&self) -> u64 {
+        self.get_headers_height().saturating_sub(1)
+    }
+
+    /// Get the fee rate to use for a new block-commit.
+    /// If `fee_rate_lookback` is set, this is the median of the median fee rates of that many
+    /// recent burnchain blocks, capped at the highest fee rate RBF would be allowed to reach
+    /// from `satoshis_per_byte`.  Otherwise (or if bitcoind can't tell us), it's
+    /// `satoshis_per_byte`.
+    fn get_block_commit_fee_rate(&mut self) -> u64 {
+        let lookback = self.config.burnchain.fee_rate_lookback;
+        let default_fee_rate = self.config.burnchain.satoshis_per_byte;
+        if lookback == 0 {
+            return default_fee_rate;
+        }
+
+        let burn_block_height = self.get_burn_block_height();
+        if let Some((estimate_height, fee_rate)) = self.fee_rate_estimate.as_ref() {
+            if *estimate_height == burn_block_height {
+                return *fee_rate;
+            }
+        }
+
+        let mut fee_rates = vec![];
+        for height in burn_block_height.saturating_sub(lookback - 1)..=burn_block_height {
+            match BitcoinRPCRequest::get_block_median_fee_rate(&self.config, height) {
+                Ok(Some(fee_rate)) => {
+                    fee_rates.push(fee_rate);
+                }
+                Ok(None) => {}
+                Err(e) => {
+                    warn!(
+                        "Bitcoin RPC failure: could not get fee rate of block {}: {:?}",
+                        height, &e
+                    );
+                }
+            }
+        }
+
+        if fee_rates.len() == 0 {
+            debug!(
+                "No recent fee rates available; using default of {} sats/vB",
+                default_fee_rate
+            );
+            return default_fee_rate;
+        }
+
+        fee_rates.sort();
+        let max_fee_rate = default_fee_rate * self.config.burnchain.max_rbf / 100;
+        let fee_rate = cmp::max(1, cmp::min(fee_rates[fee_rates.len() / 2], max_fee_rate));
+
+        debug!(
+            "Block-commit fee rate is {} sats/vB, from {} burnchain blocks up to {}",
+            fee_rate,
+            fee_rates.len(),
+            burn_block_height
+        );
+        self.fee_rate_estimate = Some((burn_block_height, fee_rate));
+        fee_rate
+    }
+
     fn send_block_commit_operation(
         &mut self,
         payload: LeaderBlockCommitOp,
@@ -941,10 +1142,14 @@ impl BitcoinRegtestController {
         utxos_to_exclude: Option<UTXOSet>,
         previous_fees: Option<LeaderBlockCommitFees>,
         previous_txids: &Vec<Txid>,
+        previous_attempts: &Vec<BlockCommitAttempt>,
     ) -> Option<Transaction> {
         let mut estimated_fees = match previous_fees {
             Some(fees) => fees.fees_from_previous_tx(&payload, &self.config),
-            None => LeaderBlockCommitFees::estimated_fees_from_payload(&payload, &self.config),
+            None => {
+                let fee_rate = self.get_block_commit_fee_rate();
+                LeaderBlockCommitFees::estimated_fees_from_payload(&payload, &self.config, fee_rate)
+            }
         };
 
         let public_key = signer.get_public_key();
@@ -1004,11 +1209,28 @@ impl BitcoinRegtestController {
         let txid = Txid::from_bytes(&txid[..]).unwrap();
         let mut txids = previous_txids.clone();
         txids.push(txid.clone());
+
+        let mut attempts = previous_attempts.clone();
+        attempts.push(BlockCommitAttempt {
+            txid: txid.to_hex(),
+            kind: if estimated_fees.is_rbf_enabled {
+                BlockCommitAttemptKind::ReplaceByFee
+            } else {
+                BlockCommitAttemptKind::Initial
+            },
+            fee_rate,
+            fee: tx_fee(&tx, &utxos),
+            tx_size,
+            burn_block_height: self.get_burn_block_height(),
+        });
+
         let ongoing_block_commit = OngoingBlockCommit {
             payload,
             utxos,
             fees: estimated_fees,
             txids,
+            last_tx: tx.clone(),
+            attempts,
         };
 
         info!(
@@ -1027,7 +1249,220 @@ impl BitcoinRegtestController {
         Some(tx)
     }
 
+    /// Try to get a stuck block-commit mined by spending its change output in a child
+    /// transaction that pays for both (child-pays-for-parent).  Each bump raises the package
+    /// fee rate by `rbf_fee_increment`, replacing the previous child if there is one, until the
+    /// package fee rate would exceed `max_cpfp` percent of the commit's initial fee rate.
+    /// Returns the child transaction to send.
+    fn build_cpfp_tx(
+        &mut self,
+        ongoing_op: &mut OngoingBlockCommit,
+        signer: &mut BurnchainOpSigner,
+        burn_block_height: u64,
+    ) -> Option<Transaction> {
+        let max_cpfp = self.config.burnchain.max_cpfp;
+        if max_cpfp == 0 {
+            return None;
+        }
+
+        let public_key = signer.get_public_key();
+        let change_address_hash = Hash160::from_data(&public_key.to_bytes());
+        let change_script = BitcoinAddress::to_p2pkh_tx_out(&change_address_hash, 0).script_pubkey;
+
+        // the change output is the one output after the consensus output and the commit
+        // outputs, if it was created at all.
+        let parent_tx = &ongoing_op.last_tx;
+        let change_vout = 1 + ongoing_op.payload.commit_outs.len();
+        let change_output = match parent_tx.output.get(change_vout) {
+            Some(output) if output.script_pubkey == change_script => output.clone(),
+            _ => {
+                info!(
+                    "Block-commit {} has no change output to spend; cannot CPFP",
+                    parent_tx.txid()
+                );
+                return None;
+            }
+        };
+
+        let parent_size = SerializedTx::new(parent_tx.clone()).bytes.len() as u64;
+        let parent_fee = tx_fee(parent_tx, &ongoing_op.utxos);
+
+        let previous_rate = match ongoing_op.last_cpfp_attempt() {
+            Some(attempt) => attempt.fee_rate,
+            None => ongoing_op.fees.fee_rate,
+        };
+        let package_fee_rate = previous_rate + self.config.burnchain.rbf_fee_increment;
+        if package_fee_rate > ongoing_op.initial_fee_rate() * max_cpfp / 100 {
+            warn!(
+                "CPFP'd block commits reached {}% satoshi per byte fee rate, not resubmitting",
+                max_cpfp
+            );
+            return None;
+        }
+
+        let mut change_utxos = UTXOSet {
+            bhh: ongoing_op.utxos.bhh.clone(),
+            utxos: vec![UTXO {
+                txid: parent_tx.txid(),
+                vout: change_vout as u32,
+                script_pub_key: change_output.script_pubkey.clone(),
+                amount: change_output.value,
+                confirmations: 0,
+            }],
+        };
+
+        let child_fee = |child_size: u64| {
+            let package_fee = package_fee_rate * (parent_size + child_size);
+            // the child must pay at least for itself
+            cmp::max(package_fee.saturating_sub(parent_fee), child_size)
+        };
+
+        let child_size = {
+            // serialize once with an estimated size to find out the actual size
+            let mut child_tx = Transaction {
+                input: vec![],
+                output: vec![],
+                version: 1,
+                lock_time: 0,
+            };
+            let mut utxos_cloned = change_utxos.clone();
+            self.serialize_tx(
+                &mut child_tx,
+                child_fee(CPFP_CHILD_TX_ESTIM_SIZE),
+                &mut utxos_cloned,
+                signer,
+            );
+            SerializedTx::new(child_tx).bytes.len() as u64
+        };
+
+        let fee = child_fee(child_size);
+        if change_output.value < fee + DUST_UTXO_LIMIT {
+            info!(
+                "Change output of block-commit {} ({} sats) is too small to pay a CPFP fee of {}",
+                parent_tx.txid(),
+                change_output.value,
+                fee
+            );
+            return None;
+        }
+
+        let mut child_tx = Transaction {
+            input: vec![],
+            output: vec![],
+            version: 1,
+            lock_time: 0,
+        };
+        if !self.serialize_tx(&mut child_tx, fee, &mut change_utxos, signer) {
+            return None;
+        }
+        signer.dispose();
+
+        let mut txid = child_tx.txid().as_bytes().to_vec();
+        txid.reverse();
+        let txid = Txid::from_bytes(&txid[..]).unwrap();
+
+        info!(
+            "Miner node: submitting CPFP child of leader_block_commit (txid: {}, parent: {}, fee: {}, package fee_rate: {})",
+            txid.to_hex(),
+            parent_tx.txid(),
+            fee,
+            package_fee_rate,
+        );
+
+        ongoing_op.attempts.push(BlockCommitAttempt {
+            txid: txid.to_hex(),
+            kind: BlockCommitAttemptKind::ChildPaysForParent,
+            fee_rate: package_fee_rate,
+            fee,
+            tx_size: child_size,
+            burn_block_height,
+        });
+
+        increment_btc_ops_sent_counter();
+
+        Some(child_tx)
+    }
+
+    /// Get the status of every block-commit that we have sent but not yet seen mined
+    pub fn get_block_commit_status(&self) -> Vec<BlockCommitStatus> {
+        let mut status: Vec<_> = self
+            .superseded_block_commits
+            .iter()
+            .map(|op| op.to_status(true))
+            .collect();
+        if let Some(ongoing_op) = self.ongoing_block_commit.as_ref() {
+            status.push(ongoing_op.to_status(false));
+        }
+        status
+    }
+
+    /// Write out the block-commit status to the burnchain working directory, so operators can
+    /// inspect it.
+    fn store_block_commit_status(&self) {
+        let path = format!(
+            "{}/{}",
+            self.config.get_burnchain_path_str(),
+            BLOCK_COMMIT_STATUS_FILE
+        );
+        let status = self.get_block_commit_status();
+        let res = serde_json::to_vec_pretty(&status)
+            .map_err(|e| e.to_string())
+            .and_then(|bytes| std::fs::write(&path, bytes).map_err(|e| e.to_string()));
+        if let Err(e) = res {
+            warn!("Failed to store block-commit status to {}: {}", &path, &e);
+        }
+    }
+
+    /// Stop reporting superseded block-commits once they are mined, or once their UTXOs are
+    /// no longer usable (i.e. they can no longer be mined).
+    fn prune_superseded_block_commits(&mut self) {
+        if self.superseded_block_commits.len() == 0 {
+            return;
+        }
+        let burnchain_db = match self.burnchain_db.as_ref() {
+            Some(burnchain_db) => burnchain_db,
+            None => {
+                return;
+            }
+        };
+        let burn_block_height = self.get_burn_block_height();
+        self.superseded_block_commits.retain(|op| {
+            let mined = op
+                .txids
+                .iter()
+                .any(|txid| burnchain_db.get_burnchain_op(txid).is_some());
+            let stale = op
+                .attempts
+                .last()
+                .map(|attempt| {
+                    attempt.burn_block_height + SUPERSEDED_BLOCK_COMMIT_STALENESS_LIMIT
+                        < burn_block_height
+                })
+                .unwrap_or(true);
+            !mined && !stale
+        });
+    }
+
+    /// Whether or not the ongoing block-commit's fee rate has gone past `max_rbf` percent of
+    /// `satoshis_per_byte`, so that it may no longer be replaced by fee.
+    fn is_rbf_budget_spent(&self, ongoing_op: &OngoingBlockCommit) -> bool {
+        ongoing_op.fees.fee_rate
+            > (self.config.burnchain.satoshis_per_byte * self.config.burnchain.max_rbf / 100)
+    }
+
     fn build_leader_block_commit_tx(
+        &mut self,
+        payload: LeaderBlockCommitOp,
+        signer: &mut BurnchainOpSigner,
+        attempt: u64,
+    ) -> Option<Transaction> {
+        let res = self.build_leader_block_commit_tx_inner(payload, signer, attempt);
+        self.prune_superseded_block_commits();
+        self.store_block_commit_status();
+        res
+    }
+
+    fn build_leader_block_commit_tx_inner(
         &mut self,
         payload: LeaderBlockCommitOp,
         signer: &mut BurnchainOpSigner,
@@ -1036,11 +1471,19 @@ impl BitcoinRegtestController {
         // Are we currently tracking an operation?
         if self.ongoing_block_commit.is_none() {
             // Good to go, let's build the transaction and send it.
-            let res = self.send_block_commit_operation(payload, signer, None, None, None, &vec![]);
+            let res = self.send_block_commit_operation(
+                payload,
+                signer,
+                None,
+                None,
+                None,
+                &vec![],
+                &vec![],
+            );
             return res;
         }
 
-        let ongoing_op = self.ongoing_block_commit.take().unwrap();
+        let mut ongoing_op = self.ongoing_block_commit.take().unwrap();
 
         let _ = self.sortdb_mut();
         let burnchain_db = self.burnchain_db.as_ref().expect("BurnchainDB not opened");
@@ -1050,8 +1493,15 @@ impl BitcoinRegtestController {
             if mined_op.is_some() {
                 // Good to go, the transaction in progress was mined
                 debug!("Was able to retrieve ongoing TXID - {}", txid);
-                let res =
-                    self.send_block_commit_operation(payload, signer, None, None, None, &vec![]);
+                let res = self.send_block_commit_operation(
+                    payload,
+                    signer,
+                    None,
+                    None,
+                    None,
+                    &vec![],
+                    &vec![],
+                );
                 return res;
             } else {
                 debug!("Was unable to retrieve ongoing TXID - {}", txid);
@@ -1080,20 +1530,35 @@ impl BitcoinRegtestController {
                 "Possible presence of fork or stale UTXO cache, invalidating cached set of UTXOs.";
                 "cached_burn_block_hash" => %ongoing_op.utxos.bhh,
             );
-            let res = self.send_block_commit_operation(payload, signer, None, None, None, &vec![]);
+            let res = self.send_block_commit_operation(
+                payload,
+                signer,
+                None,
+                None,
+                None,
+                &vec![],
+                &vec![],
+            );
             return res;
         }
 
-        // Stop as soon as the fee_rate is ${self.config.burnchain.max_rbf} percent higher, stop RBF
-        if ongoing_op.fees.fee_rate
-            > (self.config.burnchain.satoshis_per_byte * self.config.burnchain.max_rbf / 100)
-        {
+        // Stop as soon as the fee_rate is ${self.config.burnchain.max_rbf} percent higher, stop RBF.
+        // If the incoming operation is the same as the ongoing one, try to get the ongoing one
+        // mined by spending its change output instead.  Otherwise it is outdated, and not worth
+        // paying more for.
+        if self.is_rbf_budget_spent(&ongoing_op) {
             warn!(
                 "RBF'd block commits reached {}% satoshi per byte fee rate, not resubmitting",
                 self.config.burnchain.max_rbf
             );
+            let res = if payload == ongoing_op.payload {
+                let burn_block_height = self.get_burn_block_height();
+                self.build_cpfp_tx(&mut ongoing_op, signer, burn_block_height)
+            } else {
+                None
+            };
             self.ongoing_block_commit = Some(ongoing_op);
-            return None;
+            return res;
         }
 
         // An ongoing operation is in the mempool and we received a new block. The desired behaviour is the following:
@@ -1112,7 +1577,8 @@ impl BitcoinRegtestController {
         }
 
         // Let's proceed and early return 2) i)
-        let res = if ongoing_op.fees.estimated_amount_required() > ongoing_op.sum_utxos() {
+        let can_rbf = ongoing_op.fees.estimated_amount_required() <= ongoing_op.sum_utxos();
+        let res = if !can_rbf {
             // Try to build and submit op, excluding UTXOs currently used
             info!("Attempt to submit another leader_block_commit, despite an ongoing (outdated) commit");
             self.send_block_commit_operation(
@@ -1122,6 +1588,7 @@ impl BitcoinRegtestController {
                 Some(ongoing_op.utxos.clone()),
                 None,
                 &vec![],
+                &vec![],
             )
         } else {
             // Case 2) ii): Attempt to RBF
@@ -1133,11 +1600,15 @@ impl BitcoinRegtestController {
                 None,
                 Some(ongoing_op.fees.clone()),
                 &ongoing_op.txids,
+                &ongoing_op.attempts,
             )
         };
 
         if res.is_none() {
             self.ongoing_block_commit = Some(ongoing_op);
+        } else if !can_rbf {
+            // the old commit is still in the mempool, and may yet be mined
+            self.superseded_block_commits.push(ongoing_op);
         }
 
         res
@@ -1295,12 +1766,15 @@ impl BitcoinRegtestController {
 
             let script_pub_key = utxo.script_pub_key.clone();
             let sig_hash_all = 0x01;
-            let sig_hash = tx.signature_hash(i, &script_pub_key, sig_hash_all);
 
             let sig1_der = {
-                let message = signer
-                    .sign_message(sig_hash.as_bytes())
-                    .expect("Unable to sign message");
+                let message = match signer.sign_transaction_input(&tx, i, &script_pub_key) {
+                    Some(message) => message,
+                    None => {
+                        warn!("Unable to sign input {} of {}", i, tx.txid());
+                        return false;
+                    }
+                };
                 message
                     .to_secp256k1_recoverable()
                     .expect("Unable to get recoverable signature")
@@ -1615,6 +2089,12 @@ impl BurnchainController for BitcoinRegtestController {
     }
 }
 
+/// Compute the fee paid by a transaction that spends exactly the given UTXOs
+fn tx_fee(tx: &Transaction, utxos: &UTXOSet) -> u64 {
+    let spent: u64 = tx.output.iter().map(|output| output.value).sum();
+    utxos.total_available().saturating_sub(spent)
+}
+
 #[derive(Debug, Clone)]
 pub struct UTXOSet {
     bhh: BurnchainHeaderHash,
@@ -1957,6 +2437,47 @@ impl BitcoinRPCRequest {
         Ok(())
     }
 
+    /// Calls `getblockstats` for the block at the given height, and returns the median fee rate
+    /// (in sats/vB) paid by its transactions.  Returns None if the block has no transactions
+    /// besides its coinbase.
+    pub fn get_block_median_fee_rate(config: &Config, height: u64) -> RPCResult<Option<u64>> {
+        let payload = BitcoinRPCRequest {
+            method: "getblockstats".to_string(),
+            params: vec![height.into(), json!(["feerate_percentiles", "txs"])],
+            id: "stacks".to_string(),
+            jsonrpc: "2.0".to_string(),
+        };
+
+        let res = BitcoinRPCRequest::send(&config, payload)?;
+        let stats = res
+            .get("result")
+            .ok_or(RPCError::Parsing("Failed to get block stats".to_string()))?;
+        BitcoinRPCRequest::parse_block_median_fee_rate(stats)
+    }
+
+    /// Get the median fee rate out of the result of a `getblockstats` call
+    fn parse_block_median_fee_rate(stats: &serde_json::Value) -> RPCResult<Option<u64>> {
+        let num_txs = stats
+            .get("txs")
+            .and_then(|txs| txs.as_u64())
+            .ok_or(RPCError::Parsing(
+                "Failed to get block tx count".to_string(),
+            ))?;
+        if num_txs <= 1 {
+            return Ok(None);
+        }
+
+        // feerate_percentiles holds the 10th, 25th, 50th, 75th and 90th percentiles
+        let median = stats
+            .get("feerate_percentiles")
+            .and_then(|percentiles| percentiles.get(2))
+            .and_then(|median| median.as_u64())
+            .ok_or(RPCError::Parsing(
+                "Failed to get block fee rate percentiles".to_string(),
+            ))?;
+        Ok(Some(median))
+    }
+
     /// Calls `listwallets` method through RPC call and returns wallet names as a vector of Strings
     pub fn list_wallets(config: &Config) -> RPCResult<Vec<String>> {
         let payload = BitcoinRPCRequest {
@@ -2075,3 +2596,313 @@ impl BitcoinRPCRequest {
         Ok(payload)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    use stacks::address::AddressHashMode;
+    use stacks::burnchains::BurnchainSigner;
+    use stacks::types::chainstate::{BlockHeaderHash, StacksAddress, VRFSeed};
+    use stacks::util::secp256k1::Secp256k1PrivateKey;
+
+    fn make_test_config(test_name: &str) -> Config {
+        let mut config = Config::default();
+        config.node.working_dir = format!("/tmp/stacks-node-tests/{}", test_name);
+        if fs::metadata(&config.node.working_dir).is_ok() {
+            fs::remove_dir_all(&config.node.working_dir).unwrap();
+        }
+        config
+    }
+
+    /// Make a block-commit that was sent from a single UTXO at `fee_rate`.  If `change` is
+    /// nonzero, the block-commit pays that much back to `secret_key`.
+    fn make_ongoing_block_commit(
+        config: &Config,
+        secret_key: &Secp256k1PrivateKey,
+        fee_rate: u64,
+        change: u64,
+    ) -> OngoingBlockCommit {
+        let public_key = Secp256k1PublicKey::from_private(secret_key);
+        let payload = LeaderBlockCommitOp {
+            sunset_burn: 0,
+            block_header_hash: BlockHeaderHash([0x11; 32]),
+            burn_fee: 20000,
+            input: (Txid([0; 32]), 0),
+            apparent_sender: BurnchainSigner {
+                hash_mode: AddressHashMode::SerializeP2PKH,
+                num_sigs: 1,
+                public_keys: vec![public_key.clone()],
+            },
+            key_block_ptr: 1,
+            key_vtxindex: 1,
+            memo: vec![0x05],
+            new_seed: VRFSeed([0x22; 32]),
+            parent_block_ptr: 2,
+            parent_vtxindex: 1,
+            vtxindex: 0,
+            txid: Txid([0u8; 32]),
+            block_height: 0,
+            burn_header_hash: BurnchainHeaderHash::zero(),
+            burn_parent_modulus: 0,
+            commit_outs: vec![StacksAddress::burn_address(false)],
+        };
+        let fees = LeaderBlockCommitFees::estimated_fees_from_payload(&payload, config, fee_rate);
+
+        let mut output = vec![TxOut {
+            value: 0,
+            script_pubkey: Builder::new()
+                .push_opcode(opcodes::All::OP_RETURN)
+                .push_slice(&[0x58, 0x32])
+                .into_script(),
+        }];
+        for commit_to in payload.commit_outs.iter() {
+            output.push(commit_to.to_bitcoin_tx_out(fees.amount_per_output()));
+        }
+        let change_address_hash = Hash160::from_data(&public_key.to_bytes());
+        if change > 0 {
+            output.push(BitcoinAddress::to_p2pkh_tx_out(
+                &change_address_hash,
+                change,
+            ));
+        }
+
+        let input_txid = Sha256dHash([0x33; 32]);
+        let last_tx = Transaction {
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: input_txid.clone(),
+                    vout: 0,
+                },
+                script_sig: Script::new(),
+                sequence: 0xFFFFFFFD,
+                witness: vec![],
+            }],
+            output,
+            version: 1,
+            lock_time: 0,
+        };
+
+        let tx_size = fees.default_tx_size;
+        let fee = fee_rate * tx_size;
+        let spent: u64 = last_tx.output.iter().map(|output| output.value).sum();
+        let utxos = UTXOSet {
+            bhh: BurnchainHeaderHash([0x44; 32]),
+            utxos: vec![UTXO {
+                txid: input_txid,
+                vout: 0,
+                script_pub_key: BitcoinAddress::to_p2pkh_tx_out(&change_address_hash, 0)
+                    .script_pubkey,
+                amount: spent + fee,
+                confirmations: 1,
+            }],
+        };
+
+        let txid = Txid([0x55; 32]);
+        OngoingBlockCommit {
+            payload,
+            utxos,
+            fees,
+            txids: vec![txid.clone()],
+            last_tx,
+            attempts: vec![BlockCommitAttempt {
+                txid: txid.to_hex(),
+                kind: BlockCommitAttemptKind::Initial,
+                fee_rate,
+                fee,
+                tx_size,
+                burn_block_height: 100,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_build_cpfp_tx() {
+        let mut config = make_test_config("test_build_cpfp_tx");
+        config.burnchain.rbf_fee_increment = 5;
+        config.burnchain.max_cpfp = 200;
+        let mut controller = BitcoinRegtestController::new_dummy(config.clone());
+
+        let secret_key = Secp256k1PrivateKey::new();
+        let mut ongoing_op = make_ongoing_block_commit(&config, &secret_key, 10, 1_000_000);
+        let parent_txid = ongoing_op.last_tx.txid();
+        let parent_size = SerializedTx::new(ongoing_op.last_tx.clone()).bytes.len() as u64;
+        let parent_fee = tx_fee(&ongoing_op.last_tx, &ongoing_op.utxos);
+        assert_eq!(
+            parent_fee,
+            10 * config.burnchain.block_commit_tx_estimated_size
+        );
+
+        // each child replaces the last one, raising the package fee rate by rbf_fee_increment
+        // until it would exceed max_cpfp percent of the initial fee rate.
+        let mut fee_rates = vec![];
+        loop {
+            let mut signer = BurnchainOpSigner::new(secret_key.clone(), false);
+            let child_tx = match controller.build_cpfp_tx(&mut ongoing_op, &mut signer, 101) {
+                Some(child_tx) => child_tx,
+                None => {
+                    break;
+                }
+            };
+
+            assert_eq!(child_tx.input.len(), 1);
+            assert_eq!(
+                child_tx.input[0].previous_output,
+                OutPoint {
+                    txid: parent_txid.clone(),
+                    vout: 2,
+                }
+            );
+
+            let attempt = ongoing_op.attempts.last().unwrap().clone();
+            assert_eq!(attempt.kind, BlockCommitAttemptKind::ChildPaysForParent);
+            assert_eq!(attempt.burn_block_height, 101);
+            assert_eq!(child_tx.output.len(), 1);
+            assert_eq!(child_tx.output[0].value + attempt.fee, 1_000_000);
+            assert!(parent_fee + attempt.fee >= attempt.fee_rate * (parent_size + attempt.tx_size));
+
+            fee_rates.push(attempt.fee_rate);
+        }
+
+        assert_eq!(fee_rates, vec![15, 20]);
+        assert_eq!(ongoing_op.attempts.len(), 3);
+    }
+
+    #[test]
+    fn test_build_cpfp_tx_not_possible() {
+        let mut config = make_test_config("test_build_cpfp_tx_not_possible");
+        config.burnchain.rbf_fee_increment = 5;
+        config.burnchain.max_cpfp = 200;
+        let mut controller = BitcoinRegtestController::new_dummy(config.clone());
+        let secret_key = Secp256k1PrivateKey::new();
+
+        // no change output to spend
+        let mut ongoing_op = make_ongoing_block_commit(&config, &secret_key, 10, 0);
+        let mut signer = BurnchainOpSigner::new(secret_key.clone(), false);
+        assert!(controller
+            .build_cpfp_tx(&mut ongoing_op, &mut signer, 101)
+            .is_none());
+        assert_eq!(ongoing_op.attempts.len(), 1);
+
+        // change output can't pay for the child without leaving dust
+        let mut ongoing_op = make_ongoing_block_commit(&config, &secret_key, 10, DUST_UTXO_LIMIT);
+        let mut signer = BurnchainOpSigner::new(secret_key.clone(), false);
+        assert!(controller
+            .build_cpfp_tx(&mut ongoing_op, &mut signer, 101)
+            .is_none());
+        assert_eq!(ongoing_op.attempts.len(), 1);
+
+        // CPFP is disabled
+        config.burnchain.max_cpfp = 0;
+        let mut controller = BitcoinRegtestController::new_dummy(config.clone());
+        let mut ongoing_op = make_ongoing_block_commit(&config, &secret_key, 10, 1_000_000);
+        let mut signer = BurnchainOpSigner::new(secret_key.clone(), false);
+        assert!(controller
+            .build_cpfp_tx(&mut ongoing_op, &mut signer, 101)
+            .is_none());
+        assert_eq!(ongoing_op.attempts.len(), 1);
+    }
+
+    #[test]
+    fn test_rbf_budget() {
+        let mut config = make_test_config("test_rbf_budget");
+        config.burnchain.satoshis_per_byte = 10;
+        config.burnchain.max_rbf = 150;
+        let controller = BitcoinRegtestController::new_dummy(config.clone());
+        let secret_key = Secp256k1PrivateKey::new();
+
+        // the budget is relative to satoshis_per_byte, even if the block-commit was first sent
+        // at a lower estimated fee rate.
+        let mut ongoing_op = make_ongoing_block_commit(&config, &secret_key, 5, 1_000_000);
+        assert!(!controller.is_rbf_budget_spent(&ongoing_op));
+
+        ongoing_op.fees.fee_rate = 15;
+        assert!(!controller.is_rbf_budget_spent(&ongoing_op));
+
+        ongoing_op.fees.fee_rate = 16;
+        assert!(controller.is_rbf_budget_spent(&ongoing_op));
+    }
+
+    #[test]
+    fn test_parse_block_median_fee_rate() {
+        let stats = json!({
+            "feerate_percentiles": [1, 2, 5, 10, 20],
+            "txs": 100
+        });
+        assert_eq!(
+            BitcoinRPCRequest::parse_block_median_fee_rate(&stats).unwrap(),
+            Some(5)
+        );
+
+        // only a coinbase
+        let stats = json!({
+            "feerate_percentiles": [0, 0, 0, 0, 0],
+            "txs": 1
+        });
+        assert_eq!(
+            BitcoinRPCRequest::parse_block_median_fee_rate(&stats).unwrap(),
+            None
+        );
+
+        let stats = json!({
+            "txs": 100
+        });
+        assert!(BitcoinRPCRequest::parse_block_median_fee_rate(&stats).is_err());
+
+        let stats = json!({
+            "feerate_percentiles": [1, 2],
+            "txs": 100
+        });
+        assert!(BitcoinRPCRequest::parse_block_median_fee_rate(&stats).is_err());
+
+        let stats = json!({
+            "feerate_percentiles": [1, 2, 5, 10, 20]
+        });
+        assert!(BitcoinRPCRequest::parse_block_median_fee_rate(&stats).is_err());
+    }
+
+    #[test]
+    fn test_store_block_commit_status() {
+        let config = make_test_config("test_store_block_commit_status");
+        fs::create_dir_all(config.get_burnchain_path_str()).unwrap();
+        let mut controller = BitcoinRegtestController::new_dummy(config.clone());
+        let secret_key = Secp256k1PrivateKey::new();
+
+        let superseded_op = make_ongoing_block_commit(&config, &secret_key, 10, 1_000_000);
+        let mut ongoing_op = make_ongoing_block_commit(&config, &secret_key, 20, 1_000_000);
+        ongoing_op.payload.block_header_hash = BlockHeaderHash([0x66; 32]);
+        controller.superseded_block_commits.push(superseded_op);
+        controller.ongoing_block_commit = Some(ongoing_op);
+
+        controller.store_block_commit_status();
+
+        let path = format!(
+            "{}/{}",
+            config.get_burnchain_path_str(),
+            BLOCK_COMMIT_STATUS_FILE
+        );
+        let status: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(
+            status,
+            serde_json::to_value(controller.get_block_commit_status()).unwrap()
+        );
+
+        let status = status.as_array().unwrap();
+        assert_eq!(status.len(), 2);
+        assert_eq!(status[0]["superseded"], json!(true));
+        assert_eq!(
+            status[0]["block_header_hash"],
+            json!(BlockHeaderHash([0x11; 32]).to_hex())
+        );
+        assert_eq!(status[1]["superseded"], json!(false));
+        assert_eq!(
+            status[1]["block_header_hash"],
+            json!(BlockHeaderHash([0x66; 32]).to_hex())
+        );
+        assert_eq!(status[1]["txid"], json!(Txid([0x55; 32]).to_hex()));
+        assert_eq!(status[1]["replacements"], json!(0));
+        assert_eq!(status[1]["attempts"][0]["kind"], json!("initial"));
+        assert_eq!(status[1]["attempts"][0]["fee_rate"], json!(20));
+    }
+}
@@ -2,6 +2,8 @@ use std::convert::TryInto;
 use std::fs;
 use std::net::{SocketAddr, ToSocketAddrs};
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
 
 use rand::RngCore;
 
@@ -11,16 +13,18 @@ use stacks::chainstate::stacks::index::marf::MARFOpenOpts;
 use stacks::chainstate::stacks::index::storage::TrieHashCalculationMode;
 use stacks::chainstate::stacks::miner::BlockBuilderSettings;
 use stacks::chainstate::stacks::MAX_BLOCK_LEN;
-use stacks::core::mempool::MemPoolWalkSettings;
+use stacks::core::mempool::{MemPoolDB, MemPoolWalkSettings};
 use stacks::core::StacksEpoch;
 use stacks::core::{
     CHAIN_ID_MAINNET, CHAIN_ID_TESTNET, PEER_VERSION_MAINNET, PEER_VERSION_TESTNET,
 };
+use stacks::cost_estimates::fee_congestion::CongestionAwareFeeRateEstimator;
 use stacks::cost_estimates::fee_medians::WeightedMedianFeeRateEstimator;
 use stacks::cost_estimates::fee_rate_fuzzer::FeeRateFuzzer;
 use stacks::cost_estimates::fee_scalar::ScalarFeeRateEstimator;
 use stacks::cost_estimates::metrics::CostMetric;
 use stacks::cost_estimates::metrics::ProportionalDotProduct;
+use stacks::cost_estimates::metrics::PROPORTION_RESOLUTION;
 use stacks::cost_estimates::CostEstimator;
 use stacks::cost_estimates::FeeEstimator;
 use stacks::cost_estimates::PessimisticEstimator;
@@ -32,9 +36,14 @@ use stacks::util::secp256k1::Secp256k1PrivateKey;
 use stacks::util::secp256k1::Secp256k1PublicKey;
 use stacks::vm::types::{AssetIdentifier, PrincipalData, QualifiedContractIdentifier};
 
+use crate::keychain::Keychain;
+use crate::signer;
+
 const DEFAULT_SATS_PER_VB: u64 = 50;
 const DEFAULT_MAX_RBF_RATE: u64 = 150; // 1.5x
 const DEFAULT_RBF_FEE_RATE_INCREMENT: u64 = 5;
+const DEFAULT_MAX_CPFP_RATE: u64 = 0; // disabled
+const DEFAULT_FEE_RATE_LOOKBACK: u64 = 0; // use satoshis_per_byte
 const LEADER_KEY_TX_ESTIM_SIZE: u64 = 290;
 const BLOCK_COMMIT_TX_ESTIM_SIZE: u64 = 350;
 const INV_REWARD_CYCLES_TESTNET: u64 = 6;
@@ -102,6 +111,23 @@ mod tests {
             "ST2TFVBMRPS5SSNP98DQKQ5JNB2B6NZM91C4K3P7B"
         );
     }
+
+    #[test]
+    fn should_load_block_files_path() {
+        let config = Config::from_config_file(ConfigFile::from_str(
+            r#"
+            [burnchain]
+            block_files_path = "/var/lib/bitcoind/blocks"
+            "#,
+        ));
+        assert_eq!(
+            config.burnchain.block_files_path,
+            Some("/var/lib/bitcoind/blocks".to_string())
+        );
+
+        let config = Config::from_config_file(ConfigFile::from_str("[burnchain]"));
+        assert_eq!(config.burnchain.block_files_path, None);
+    }
 }
 
 impl ConfigFile {
@@ -379,6 +405,22 @@ impl Config {
                         .pox_sync_sample_secs
                         .unwrap_or(default_node_config.pox_sync_sample_secs),
                     use_test_genesis_chainstate: node.use_test_genesis_chainstate,
+                    light_client: node
+                        .light_client
+                        .unwrap_or(default_node_config.light_client),
+                    light_client_peers: match node.light_client_peers {
+                        Some(peers) => peers
+                            .split(",")
+                            .map(|peer| peer.trim().to_string())
+                            .filter(|peer| peer.len() > 0)
+                            .collect(),
+                        None => default_node_config.light_client_peers,
+                    },
+                    txindex: node.txindex.unwrap_or(default_node_config.txindex),
+                    address_index: node
+                        .address_index
+                        .unwrap_or(default_node_config.address_index),
+                    event_log: node.event_log.unwrap_or(default_node_config.event_log),
                 };
                 (node_config, node.bootstrap_node, node.deny_nodes)
             }
@@ -494,10 +536,17 @@ impl Config {
                     rbf_fee_increment: burnchain
                         .rbf_fee_increment
                         .unwrap_or(default_burnchain_config.rbf_fee_increment),
+                    max_cpfp: burnchain
+                        .max_cpfp
+                        .unwrap_or(default_burnchain_config.max_cpfp),
+                    fee_rate_lookback: burnchain
+                        .fee_rate_lookback
+                        .unwrap_or(default_burnchain_config.fee_rate_lookback),
                     epochs: match burnchain.epochs {
                         Some(epochs) => Some(epochs),
                         None => default_burnchain_config.epochs,
                     },
+                    block_files_path: burnchain.block_files_path,
                 }
             }
             None => default_burnchain_config,
@@ -519,6 +568,7 @@ impl Config {
                 probability_pick_no_estimate_tx: miner
                     .probability_pick_no_estimate_tx
                     .unwrap_or(miner_default_config.probability_pick_no_estimate_tx),
+                signer_socket: miner.signer_socket.clone(),
             },
             None => miner_default_config,
         };
@@ -746,6 +796,9 @@ impl Config {
                     handshake_timeout: opts.connect_timeout.unwrap_or(5),
                     max_sockets: opts.max_sockets.unwrap_or(800) as usize,
                     antientropy_public: opts.antientropy_public.unwrap_or(true),
+                    p2p_encryption: opts.p2p_encryption.unwrap_or(false),
+                    compact_block_relay: opts.compact_block_relay.unwrap_or(false),
+                    headers_first_sync: opts.headers_first_sync.unwrap_or(false),
                     ..ConnectionOptions::default()
                 }
             }
@@ -949,9 +1002,18 @@ pub struct BurnchainConfig {
     pub leader_key_tx_estimated_size: u64,
     pub block_commit_tx_estimated_size: u64,
     pub rbf_fee_increment: u64,
+    /// Highest package fee rate a child-pays-for-parent bump of a stuck block-commit may
+    /// reach, as a percentage of the commit's initial fee rate.  0 disables CPFP.
+    pub max_cpfp: u64,
+    /// Number of recent burnchain blocks whose median fee rates are used to pick the
+    /// block-commit fee rate.  0 means always use `satoshis_per_byte`.
+    pub fee_rate_lookback: u64,
     /// Custom override for the definitions of the epochs. This will only be applied for testnet and
     /// regtest nodes.
     pub epochs: Option<Vec<StacksEpoch>>,
+    /// Read burnchain blocks from the block files in this directory (e.g. bitcoind's `blocks`
+    /// directory) instead of downloading them from `peer_host`.
+    pub block_files_path: Option<String>,
 }
 
 impl BurnchainConfig {
@@ -979,7 +1041,10 @@ impl BurnchainConfig {
             leader_key_tx_estimated_size: LEADER_KEY_TX_ESTIM_SIZE,
             block_commit_tx_estimated_size: BLOCK_COMMIT_TX_ESTIM_SIZE,
             rbf_fee_increment: DEFAULT_RBF_FEE_RATE_INCREMENT,
+            max_cpfp: DEFAULT_MAX_CPFP_RATE,
+            fee_rate_lookback: DEFAULT_FEE_RATE_LOOKBACK,
             epochs: None,
+            block_files_path: None,
         }
     }
 
@@ -1033,7 +1098,10 @@ pub struct BurnchainConfigFile {
     pub block_commit_tx_estimated_size: Option<u64>,
     pub rbf_fee_increment: Option<u64>,
     pub max_rbf: Option<u64>,
+    pub max_cpfp: Option<u64>,
+    pub fee_rate_lookback: Option<u64>,
     pub epochs: Option<Vec<StacksEpoch>>,
+    pub block_files_path: Option<String>,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -1059,6 +1127,20 @@ pub struct NodeConfig {
     pub marf_defer_hashing: bool,
     pub pox_sync_sample_secs: u64,
     pub use_test_genesis_chainstate: Option<bool>,
+    /// Follow the Stacks chain by headers only, and serve chain state reads by checking the
+    /// MARF proofs served by `light_client_peers`
+    pub light_client: bool,
+    /// RPC endpoints (`host:port`) of the full nodes a light client reads from
+    pub light_client_peers: Vec<String>,
+    /// Keep a txid index of the receipts of processed transactions, so they can be looked up
+    /// with `GET /v2/transactions/:txid`
+    pub txindex: bool,
+    /// Keep an index of the transactions and asset events that touched each principal, served by
+    /// `GET /v2/accounts/:principal/transactions` and `GET /v2/accounts/:principal/events`
+    pub address_index: bool,
+    /// Keep a log of the `print` and token events emitted by each contract, served by
+    /// `GET /v2/events`
+    pub event_log: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -1070,6 +1152,7 @@ pub enum CostEstimatorName {
 pub enum FeeEstimatorName {
     ScalarFeeRate,
     FuzzedWeightedMedianFeeRate,
+    CongestionAwareFeeRate,
 }
 
 #[derive(Clone, Debug)]
@@ -1114,6 +1197,8 @@ impl FeeEstimatorName {
             FeeEstimatorName::ScalarFeeRate
         } else if &s.to_lowercase() == "fuzzed_weighted_median_fee_rate" {
             FeeEstimatorName::FuzzedWeightedMedianFeeRate
+        } else if &s.to_lowercase() == "congestion_aware_fee_rate" {
+            FeeEstimatorName::CongestionAwareFeeRate
         } else {
             panic!(
                 "Bad fee estimator name supplied in configuration file: {}",
@@ -1142,8 +1227,8 @@ pub struct FeeEstimationConfig {
     /// If using FeeRateFuzzer, the amount of random noise, as a percentage of the base value (in
     /// [0, 1]) to add for fuzz. See comments on FeeRateFuzzer.
     pub fee_rate_fuzzer_fraction: f64,
-    /// If using WeightedMedianFeeRateEstimator, the window size to use. See comments on
-    /// WeightedMedianFeeRateEstimator.
+    /// If using WeightedMedianFeeRateEstimator or CongestionAwareFeeRateEstimator, the window
+    /// size to use. See comments on WeightedMedianFeeRateEstimator.
     pub fee_rate_window_size: u64,
 }
 
@@ -1197,6 +1282,34 @@ impl From<FeeEstimationConfigFile> for FeeEstimationConfig {
 }
 
 impl Config {
+    /// Build the node's keychain.  If an external signer is configured, the burnchain key and VRF
+    /// keys are taken from it; the signer must be reachable when this is called.
+    pub fn get_keychain(&self) -> Keychain {
+        let mut keychain = Keychain::default(self.node.seed.clone());
+        if let Some(ref socket_path) = self.miner.signer_socket {
+            #[cfg(unix)]
+            {
+                let signer = signer::UnixSocketSigner::connect(
+                    socket_path,
+                    Duration::from_millis(signer::SIGNER_REQUEST_TIMEOUT_MS),
+                )
+                .unwrap_or_else(|e| {
+                    panic!(
+                        "FATAL: failed to connect to signer at {}: {}",
+                        socket_path, &e
+                    )
+                });
+                keychain.set_external_signer(Arc::new(signer));
+            }
+            #[cfg(not(unix))]
+            panic!(
+                "FATAL: miner.signer_socket ({}) is only supported on Unix",
+                socket_path
+            );
+        }
+        keychain
+    }
+
     pub fn make_cost_estimator(&self) -> Option<Box<dyn CostEstimator>> {
         let cost_estimator: Box<dyn CostEstimator> =
             match self.estimation.cost_estimator.as_ref()? {
@@ -1228,6 +1341,15 @@ impl Config {
             FeeEstimatorName::FuzzedWeightedMedianFeeRate => self
                 .estimation
                 .make_fuzzed_weighted_median_fee_estimator(self.get_estimates_path(), metric),
+            FeeEstimatorName::CongestionAwareFeeRate => {
+                let mempool_path = MemPoolDB::db_path(&self.get_chainstate_path_str())
+                    .expect("FATAL: failed to compute mempool path");
+                self.estimation.make_congestion_aware_fee_estimator(
+                    self.get_estimates_path(),
+                    PathBuf::from(mempool_path),
+                    metric,
+                )
+            }
         };
 
         Some(fee_estimator)
@@ -1289,6 +1411,34 @@ impl FeeEstimationConfig {
             panic!("BUG: Expected to configure a weighted median fee estimator");
         }
     }
+
+    // Creates a CongestionAwareFeeRateEstimator that samples the mempool at `mempool_path`, on
+    // top of a WeightedMedianFeeRateEstimator of mined blocks.
+    pub fn make_congestion_aware_fee_estimator<CM: 'static + CostMetric>(
+        &self,
+        mut estimates_path: PathBuf,
+        mempool_path: PathBuf,
+        metric: CM,
+    ) -> Box<dyn FeeEstimator> {
+        if let Some(FeeEstimatorName::CongestionAwareFeeRate) = self.fee_estimator.as_ref() {
+            estimates_path.push("fee_congestion_aware.sqlite");
+            let underlying_estimator = WeightedMedianFeeRateEstimator::open(
+                &estimates_path,
+                metric,
+                self.fee_rate_window_size
+                    .try_into()
+                    .expect("Configured fee rate window size out of bounds."),
+            )
+            .expect("Error opening fee estimator");
+            Box::new(CongestionAwareFeeRateEstimator::new(
+                underlying_estimator,
+                &mempool_path,
+                PROPORTION_RESOLUTION,
+            ))
+        } else {
+            panic!("BUG: Expected to configure a congestion-aware fee estimator");
+        }
+    }
 }
 
 impl NodeConfig {
@@ -1332,6 +1482,11 @@ impl NodeConfig {
             marf_defer_hashing: true,
             pox_sync_sample_secs: 30,
             use_test_genesis_chainstate: None,
+            light_client: false,
+            light_client_peers: vec![],
+            txindex: false,
+            address_index: false,
+            event_log: false,
         }
     }
 
@@ -1435,6 +1590,9 @@ pub struct MinerConfig {
     pub subsequent_attempt_time_ms: u64,
     pub microblock_attempt_time_ms: u64,
     pub probability_pick_no_estimate_tx: u8,
+    /// Path to the Unix socket of an external signing daemon holding the miner's burnchain and
+    /// VRF keys.  If unset, they are derived from `node.seed`.
+    pub signer_socket: Option<String>,
 }
 
 impl MinerConfig {
@@ -1445,6 +1603,7 @@ impl MinerConfig {
             subsequent_attempt_time_ms: 30_000,
             microblock_attempt_time_ms: 30_000,
             probability_pick_no_estimate_tx: 5,
+            signer_socket: None,
         }
     }
 }
@@ -1490,6 +1649,9 @@ pub struct ConnectionOptionsFile {
     pub disable_block_download: Option<bool>,
     pub force_disconnect_interval: Option<u64>,
     pub antientropy_public: Option<bool>,
+    pub p2p_encryption: Option<bool>,
+    pub compact_block_relay: Option<bool>,
+    pub headers_first_sync: Option<bool>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -1515,6 +1677,12 @@ pub struct NodeConfigFile {
     pub marf_defer_hashing: Option<bool>,
     pub pox_sync_sample_secs: Option<u64>,
     pub use_test_genesis_chainstate: Option<bool>,
+    pub light_client: Option<bool>,
+    /// comma-separated list of `host:port` RPC endpoints
+    pub light_client_peers: Option<String>,
+    pub txindex: Option<bool>,
+    pub address_index: Option<bool>,
+    pub event_log: Option<bool>,
 }
 
 #[derive(Clone, Deserialize)]
@@ -1549,6 +1717,7 @@ pub struct MinerConfigFile {
     pub subsequent_attempt_time_ms: Option<u64>,
     pub microblock_attempt_time_ms: Option<u64>,
     pub probability_pick_no_estimate_tx: Option<u8>,
+    pub signer_socket: Option<String>,
 }
 
 #[derive(Clone, Deserialize, Default)]
@@ -1574,6 +1743,7 @@ pub enum EventKeyType {
     BurnchainBlocks,
     MinedBlocks,
     MinedMicroblocks,
+    ChainReorgs,
 }
 
 impl EventKeyType {
@@ -1598,6 +1768,10 @@ impl EventKeyType {
             return Some(EventKeyType::Microblocks);
         }
 
+        if raw_key == "reorgs" {
+            return Some(EventKeyType::ChainReorgs);
+        }
+
         let comps: Vec<_> = raw_key.split("::").collect();
         if comps.len() == 1 {
             let split: Vec<_> = comps[0].split(".").collect();
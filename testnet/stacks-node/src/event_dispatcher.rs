@@ -12,7 +12,8 @@ use http_types::{Method, Request, Url};
 use serde_json::json;
 
 use stacks::burnchains::Txid;
-use stacks::chainstate::coordinator::BlockEventDispatcher;
+use stacks::chainstate::coordinator::reorg::{ReorgBurnBlock, ReorgMicroblock, ReorgStacksBlock};
+use stacks::chainstate::coordinator::{BlockEventDispatcher, ChainReorg};
 use stacks::chainstate::stacks::address::StacksAddressExtensions;
 use stacks::chainstate::stacks::db::StacksHeaderInfo;
 use stacks::chainstate::stacks::events::{
@@ -34,6 +35,7 @@ use stacks::vm::costs::ExecutionCost;
 use stacks::vm::events::{FTEventType, NFTEventType, STXEventType};
 use stacks::vm::types::{AssetIdentifier, QualifiedContractIdentifier, Value};
 
+use super::burnchains::bitcoin_regtest_controller::BlockCommitStatus;
 use super::config::{EventKeyType, EventObserverConfig};
 use stacks::chainstate::burn::ConsensusHash;
 use stacks::chainstate::stacks::db::unconfirmed::ProcessedUnconfirmedState;
@@ -62,9 +64,11 @@ pub const PATH_MEMPOOL_TX_SUBMIT: &str = "new_mempool_tx";
 pub const PATH_MEMPOOL_TX_DROP: &str = "drop_mempool_tx";
 pub const PATH_MINED_BLOCK: &str = "mined_block";
 pub const PATH_MINED_MICROBLOCK: &str = "mined_microblock";
+pub const PATH_BLOCK_COMMIT_STATUS: &str = "block_commit_status";
 pub const PATH_BURN_BLOCK_SUBMIT: &str = "new_burn_block";
 pub const PATH_BLOCK_PROCESSED: &str = "new_block";
 pub const PATH_ATTACHMENT_PROCESSED: &str = "attachments/new";
+pub const PATH_REORG: &str = "reorg";
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct MinedBlockEvent {
@@ -86,6 +90,12 @@ pub struct MinedMicroblockEvent {
     pub anchor_block: BlockHeaderHash,
 }
 
+#[derive(Clone, Debug, Serialize)]
+pub struct BlockCommitStatusEvent {
+    pub burn_block_height: u64,
+    pub block_commits: Vec<BlockCommitStatus>,
+}
+
 impl EventObserver {
     fn send_payload(&self, payload: &serde_json::Value, path: &str) {
         let body = match serde_json::to_vec(&payload) {
@@ -192,6 +202,59 @@ impl EventObserver {
         })
     }
 
+    fn make_reorg_payload(reorg: &ChainReorg) -> serde_json::Value {
+        let serialize_blocks = |blocks: &Vec<ReorgStacksBlock>| -> Vec<serde_json::Value> {
+            blocks
+                .iter()
+                .map(|block| {
+                    json!({
+                        "index_block_hash": format!("0x{}", block.index_block_hash),
+                        "block_hash": format!("0x{}", block.block_hash),
+                        "consensus_hash": format!("0x{}", block.consensus_hash),
+                        "block_height": block.block_height,
+                    })
+                })
+                .collect()
+        };
+        let serialize_microblocks = |microblocks: &Vec<ReorgMicroblock>| -> Vec<serde_json::Value> {
+            microblocks
+                    .iter()
+                    .map(|microblock| {
+                        json!({
+                            "parent_index_block_hash": format!("0x{}", microblock.parent_index_block_hash),
+                            "microblock_hash": format!("0x{}", microblock.microblock_hash),
+                            "microblock_sequence": microblock.sequence,
+                        })
+                    })
+                    .collect()
+        };
+        let serialize_burn_blocks = |burn_blocks: &Vec<ReorgBurnBlock>| -> Vec<serde_json::Value> {
+            burn_blocks
+                .iter()
+                .map(|burn_block| {
+                    json!({
+                        "burn_block_hash": format!("0x{}", burn_block.burn_header_hash),
+                        "burn_block_height": burn_block.block_height,
+                        "consensus_hash": format!("0x{}", burn_block.consensus_hash),
+                    })
+                })
+                .collect()
+        };
+
+        json!({
+            "old_index_block_hash": format!("0x{}", reorg.old_stacks_tip),
+            "new_index_block_hash": format!("0x{}", reorg.new_stacks_tip),
+            "old_burn_block_hash": format!("0x{}", reorg.old_burn_tip),
+            "new_burn_block_hash": format!("0x{}", reorg.new_burn_tip),
+            "rolled_back_blocks": serialize_blocks(&reorg.rolled_back_blocks),
+            "applied_blocks": serialize_blocks(&reorg.applied_blocks),
+            "rolled_back_microblocks": serialize_microblocks(&reorg.rolled_back_microblocks),
+            "applied_microblocks": serialize_microblocks(&reorg.applied_microblocks),
+            "rolled_back_burn_blocks": serialize_burn_blocks(&reorg.rolled_back_burn_blocks),
+            "applied_burn_blocks": serialize_burn_blocks(&reorg.applied_burn_blocks),
+        })
+    }
+
     /// Returns tuple of (txid, success, raw_result, raw_tx, contract_interface_json)
     fn generate_payload_info_for_receipt(receipt: &StacksTransactionReceipt) -> ReceiptPayloadInfo {
         let tx = &receipt.transaction;
@@ -322,10 +385,18 @@ impl EventObserver {
         self.send_payload(payload, PATH_MINED_MICROBLOCK);
     }
 
+    fn send_block_commit_status(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_BLOCK_COMMIT_STATUS);
+    }
+
     fn send_new_burn_block(&self, payload: &serde_json::Value) {
         self.send_payload(payload, PATH_BURN_BLOCK_SUBMIT);
     }
 
+    fn send_reorg(&self, payload: &serde_json::Value) {
+        self.send_payload(payload, PATH_REORG);
+    }
+
     fn send(
         &self,
         filtered_events: Vec<(usize, &(bool, Txid, &StacksTransactionEvent))>,
@@ -399,6 +470,7 @@ pub struct EventDispatcher {
     any_event_observers_lookup: HashSet<u16>,
     miner_observers_lookup: HashSet<u16>,
     mined_microblocks_observers_lookup: HashSet<u16>,
+    reorg_observers_lookup: HashSet<u16>,
     boot_receipts: Arc<Mutex<Option<Vec<StacksTransactionReceipt>>>>,
 }
 
@@ -496,6 +568,10 @@ impl BlockEventDispatcher for EventDispatcher {
     fn dispatch_boot_receipts(&mut self, receipts: Vec<StacksTransactionReceipt>) {
         self.process_boot_receipts(receipts)
     }
+
+    fn announce_reorg(&self, reorg: &ChainReorg) {
+        self.process_reorg(reorg)
+    }
 }
 
 impl EventDispatcher {
@@ -512,6 +588,7 @@ impl EventDispatcher {
             boot_receipts: Arc::new(Mutex::new(None)),
             miner_observers_lookup: HashSet::new(),
             mined_microblocks_observers_lookup: HashSet::new(),
+            reorg_observers_lookup: HashSet::new(),
         }
     }
 
@@ -550,6 +627,29 @@ impl EventDispatcher {
         }
     }
 
+    pub fn process_reorg(&self, reorg: &ChainReorg) {
+        // lazily assemble payload only if we have observers
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| {
+                // not sent to `*` observers: ones that predate this payload would reject it,
+                // and a rejected payload is retried forever.
+                self.reorg_observers_lookup.contains(&(*obs_id as u16))
+            })
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = EventObserver::make_reorg_payload(reorg);
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_reorg(&payload);
+        }
+    }
+
     /// Iterates through tx receipts, and then the events corresponding to each receipt to
     /// generate a dispatch matrix & event vector.
     ///
@@ -884,6 +984,33 @@ impl EventDispatcher {
         }
     }
 
+    /// Report every block-commit the miner has sent but not yet seen mined
+    pub fn process_block_commit_status(
+        &self,
+        burn_block_height: u64,
+        block_commits: Vec<BlockCommitStatus>,
+    ) {
+        let interested_observers: Vec<_> = self
+            .registered_observers
+            .iter()
+            .enumerate()
+            .filter(|(obs_id, _observer)| self.miner_observers_lookup.contains(&(*obs_id as u16)))
+            .collect();
+        if interested_observers.len() < 1 {
+            return;
+        }
+
+        let payload = serde_json::to_value(BlockCommitStatusEvent {
+            burn_block_height,
+            block_commits,
+        })
+        .unwrap();
+
+        for (_, observer) in interested_observers.iter() {
+            observer.send_block_commit_status(&payload);
+        }
+    }
+
     pub fn process_dropped_mempool_txs(&self, txs: Vec<Txid>, reason: MemPoolDropReason) {
         // lazily assemble payload only if we have observers
         let interested_observers: Vec<_> = self
@@ -1007,9 +1134,121 @@ impl EventDispatcher {
                     self.mined_microblocks_observers_lookup
                         .insert(observer_index);
                 }
+                EventKeyType::ChainReorgs => {
+                    self.reorg_observers_lookup.insert(observer_index);
+                }
             }
         }
 
         self.registered_observers.push(event_observer);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc::{channel, Receiver};
+    use std::thread;
+
+    use super::*;
+
+    /// Serve HTTP requests on a local port, answering each with `status`, and report each
+    /// request's path on the returned channel.
+    fn spawn_observer(status: &'static str) -> (String, Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let endpoint = format!("127.0.0.1:{}", listener.local_addr().unwrap().port());
+        let (paths_tx, paths_rx) = channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = match stream {
+                    Ok(stream) => stream,
+                    Err(_) => continue,
+                };
+                // read the headers and the body
+                let mut request = vec![];
+                let mut buf = [0u8; 4096];
+                let (header_len, content_len) = loop {
+                    let nread = match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => n,
+                    };
+                    request.extend_from_slice(&buf[..nread]);
+                    if let Some(pos) = request.windows(4).position(|w| w == b"\r\n\r\n") {
+                        let headers = String::from_utf8_lossy(&request[..pos]).to_lowercase();
+                        let content_len = headers
+                            .lines()
+                            .find_map(|line| line.strip_prefix("content-length:"))
+                            .map(|len| len.trim().parse::<usize>().unwrap())
+                            .unwrap_or(0);
+                        break (pos + 4, content_len);
+                    }
+                };
+                while request.len() < header_len + content_len {
+                    let nread = match stream.read(&mut buf) {
+                        Ok(0) | Err(_) => return,
+                        Ok(n) => n,
+                    };
+                    request.extend_from_slice(&buf[..nread]);
+                }
+
+                let request_line = String::from_utf8_lossy(&request).to_string();
+                let path = request_line.split(' ').nth(1).unwrap_or("").to_string();
+                let _ = paths_tx.send(path);
+                let _ = stream.write_all(
+                    format!(
+                        "HTTP/1.1 {}\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                        status
+                    )
+                    .as_bytes(),
+                );
+            }
+        });
+        (endpoint, paths_rx)
+    }
+
+    fn make_reorg() -> ChainReorg {
+        ChainReorg {
+            old_stacks_tip: StacksBlockId([0x01; 32]),
+            new_stacks_tip: StacksBlockId([0x02; 32]),
+            old_burn_tip: BurnchainHeaderHash([0x03; 32]),
+            new_burn_tip: BurnchainHeaderHash([0x04; 32]),
+            rolled_back_burn_blocks: vec![],
+            applied_burn_blocks: vec![],
+            rolled_back_blocks: vec![],
+            applied_blocks: vec![],
+            rolled_back_microblocks: vec![],
+            applied_microblocks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_reorg_not_sent_to_any_event_observers() {
+        // an observer written before /reorg existed, which rejects it
+        let (any_endpoint, any_paths) = spawn_observer("404 Not Found");
+        let (reorg_endpoint, reorg_paths) = spawn_observer("200 OK");
+
+        // process_reorg() must return, instead of retrying the rejected payload forever
+        let (done_tx, done_rx) = channel();
+        thread::spawn(move || {
+            let mut dispatcher = EventDispatcher::new();
+            dispatcher.register_observer(&EventObserverConfig {
+                endpoint: any_endpoint,
+                events_keys: vec![EventKeyType::AnyEvent],
+            });
+            dispatcher.register_observer(&EventObserverConfig {
+                endpoint: reorg_endpoint,
+                events_keys: vec![EventKeyType::ChainReorgs],
+            });
+            dispatcher.process_reorg(&make_reorg());
+            done_tx.send(()).unwrap();
+        });
+        done_rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("process_reorg() did not return");
+
+        assert_eq!(reorg_paths.try_recv().unwrap(), "/reorg");
+        assert!(reorg_paths.try_recv().is_err());
+        assert!(any_paths.try_recv().is_err());
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use stacks::address::AddressHashMode;
 use stacks::burnchains::{BurnchainSigner, PrivateKey};
@@ -10,6 +11,7 @@ use stacks::util::hash::{Hash160, Sha256Sum};
 use stacks::util::vrf::{VRFPrivateKey, VRFProof, VRFPublicKey, VRF};
 
 use super::operations::BurnchainOpSigner;
+use super::signer::MinerSigner;
 
 #[derive(Clone)]
 pub struct Keychain {
@@ -21,6 +23,8 @@ pub struct Keychain {
     vrf_secret_keys: Vec<VRFPrivateKey>,
     vrf_map: HashMap<VRFPublicKey, VRFPrivateKey>,
     rotations: u64,
+    /// If set, the burnchain key and VRF keys live here instead of in this keychain
+    external_signer: Option<Arc<dyn MinerSigner>>,
 }
 
 impl Keychain {
@@ -49,9 +53,15 @@ impl Keychain {
             rotations: 0,
             vrf_secret_keys: vec![],
             vrf_map: HashMap::new(),
+            external_signer: None,
         }
     }
 
+    /// Hand the burnchain key and VRF keys over to an external signer.
+    pub fn set_external_signer(&mut self, signer: Arc<dyn MinerSigner>) {
+        self.external_signer = Some(signer);
+    }
+
     pub fn default(seed: Vec<u8>) -> Keychain {
         let mut re_hashed_seed = seed;
         let secret_key = loop {
@@ -71,7 +81,17 @@ impl Keychain {
         Keychain::new(vec![secret_key], threshold, hash_mode)
     }
 
-    pub fn rotate_vrf_keypair(&mut self, block_height: u64) -> VRFPublicKey {
+    pub fn rotate_vrf_keypair(&mut self, block_height: u64) -> Option<VRFPublicKey> {
+        if let Some(ref signer) = self.external_signer {
+            return match signer.get_vrf_public_key(block_height) {
+                Ok(pk) => Some(pk),
+                Err(e) => {
+                    warn!("External signer failed to get VRF public key: {}", &e);
+                    None
+                }
+            };
+        }
+
         let mut seed = {
             let mut secret_state = self.hashed_secret_state.to_bytes().to_vec();
             secret_state.extend_from_slice(&block_height.to_be_bytes());
@@ -90,7 +110,7 @@ impl Keychain {
 
         self.vrf_secret_keys.push(sk.clone());
         self.vrf_map.insert(pk.clone(), sk);
-        pk
+        Some(pk)
     }
 
     pub fn rotate_microblock_keypair(&mut self, burn_block_height: u64) -> StacksPrivateKey {
@@ -141,6 +161,26 @@ impl Keychain {
 
     /// Given a VRF public key, generates a VRF Proof
     pub fn generate_proof(&self, vrf_pk: &VRFPublicKey, bytes: &[u8; 32]) -> Option<VRFProof> {
+        if let Some(ref signer) = self.external_signer {
+            let proof = match signer.generate_vrf_proof(vrf_pk, bytes) {
+                Ok(proof) => proof,
+                Err(e) => {
+                    warn!("External signer failed to generate VRF proof: {}", &e);
+                    return None;
+                }
+            };
+            return match VRF::verify(vrf_pk, &proof, &bytes.to_vec()) {
+                Ok(true) => Some(proof),
+                _ => {
+                    warn!(
+                        "External signer produced an invalid VRF proof for {:?}",
+                        vrf_pk
+                    );
+                    None
+                }
+            };
+        }
+
         // Retrieve the corresponding VRF secret key
         let vrf_sk = match self.vrf_map.get(vrf_pk) {
             Some(vrf_pk) => vrf_pk,
@@ -201,6 +241,14 @@ impl Keychain {
     }
 
     pub fn get_burnchain_signer(&self) -> BurnchainSigner {
+        if let Some(ref signer) = self.external_signer {
+            return BurnchainSigner {
+                hash_mode: AddressHashMode::SerializeP2PKH,
+                num_sigs: 1,
+                public_keys: vec![signer.get_public_key()],
+            };
+        }
+
         let public_keys = self
             .secret_keys
             .iter()
@@ -241,6 +289,9 @@ impl Keychain {
     }
 
     pub fn generate_op_signer(&self) -> BurnchainOpSigner {
+        if let Some(ref signer) = self.external_signer {
+            return BurnchainOpSigner::from_external(signer.clone(), false);
+        }
         BurnchainOpSigner::new(self.secret_keys[0], false)
     }
 }
@@ -0,0 +1,802 @@
+//! Light client mode.
+//!
+//! A light node syncs the burnchain and processes sortitions like any other node, but never
+//! downloads or processes Stacks blocks.  Instead, it follows the Stacks chain by fetching block
+//! headers from a configured set of full nodes (`node.light_client_peers`), and serves account,
+//! data var and map entry reads on its RPC endpoint by asking those full nodes for the value
+//! along with a MARF proof, which it checks against the header it has for its chain tip.
+//!
+//! The chains coordinator still needs each reward cycle's PoX anchor block and reward set to
+//! process sortitions.  The anchor block is considered known once its header has been accepted,
+//! and the reward set is computed from the PoX contract state as of the anchor block, which the
+//! full nodes serve with MARF proofs.  The MARF cannot prove that a value does not exist, so a
+//! full node that claims a value is absent when another one proves it is banned, as is any full
+//! node that serves state that does not check out.
+
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use async_h1::client;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::task;
+use http_types::{Body, Method, Request, Response, StatusCode, Url};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use stacks::burnchains::Burnchain;
+use stacks::chainstate::burn::db::sortdb::SortitionDB;
+use stacks::chainstate::burn::ConsensusHash;
+use stacks::chainstate::coordinator::{
+    make_reward_set_from_registrations, Error as coord_error, RewardSetProvider,
+};
+use stacks::chainstate::stacks::db::StacksChainState;
+use stacks::chainstate::stacks::Error as ChainstateError;
+use stacks::net::light::{LightClient, ProvenRewardSet};
+use stacks::net::{
+    AccountEntryResponse, DataVarResponse, Error as NetError, ExtendedStacksHeader,
+    MapEntryResponse, RPCPeerInfoData, RPCRewardSetData, MAX_HEADERS,
+};
+use stacks::types::chainstate::{BlockHeaderHash, StacksAddress, StacksBlockId};
+use stacks::vm::types::{PrincipalData, QualifiedContractIdentifier, Value};
+use stacks::vm::ClarityName;
+
+use crate::Config;
+
+/// Handle to the light client's header chain, and the full nodes it reads from.  Shared by the
+/// header follower, the RPC proxy, and the chains coordinator's reward set provider.
+#[derive(Clone)]
+pub struct LightClientHandle {
+    light_client: Arc<Mutex<LightClient>>,
+    peers: Vec<String>,
+    /// full nodes that served us data that did not check out
+    banned_peers: Arc<Mutex<HashSet<String>>>,
+}
+
+/// Why a read could not be served
+enum LightReadError {
+    /// the request was malformed
+    BadRequest(String),
+    /// the full nodes say the value does not exist, which cannot be verified
+    NotFound,
+    /// no full node served a response we could verify
+    Unavailable(String),
+}
+
+/// Send an HTTP request to a full node's RPC endpoint, and decode its JSON response.
+/// Returns Ok(None) on a 404.
+async fn fetch_json<T: DeserializeOwned>(
+    peer: &str,
+    method: Method,
+    path: &str,
+    body: Option<String>,
+) -> Result<Option<T>, String> {
+    let url = Url::parse(&format!("http://{}{}", peer, path))
+        .map_err(|e| format!("Invalid URL for {}: {:?}", peer, &e))?;
+    let mut req = Request::new(method, url);
+    if let Some(body) = body {
+        req.append_header("Content-Type", "application/json");
+        req.set_body(body);
+    }
+
+    let stream = TcpStream::connect(peer)
+        .await
+        .map_err(|e| format!("Failed to connect to {}: {:?}", peer, &e))?;
+    let mut response = client::connect(stream, req)
+        .await
+        .map_err(|e| format!("Request to {} failed: {:?}", peer, &e))?;
+
+    if response.status() == StatusCode::NotFound {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(format!(
+            "{} replied {} to {}",
+            peer,
+            response.status(),
+            path
+        ));
+    }
+    let body = response
+        .body_string()
+        .await
+        .map_err(|e| format!("Failed to read reply from {}: {:?}", peer, &e))?;
+    serde_json::from_str(&body)
+        .map(Some)
+        .map_err(|e| format!("Invalid reply from {} to {}: {:?}", peer, path, &e))
+}
+
+impl LightClientHandle {
+    pub fn new(light_client: LightClient, peers: Vec<String>) -> LightClientHandle {
+        LightClientHandle {
+            light_client: Arc::new(Mutex::new(light_client)),
+            peers,
+            banned_peers: Arc::new(Mutex::new(HashSet::new())),
+        }
+    }
+
+    /// The full nodes we have not banned
+    fn live_peers(&self) -> Vec<String> {
+        let banned_peers = self
+            .banned_peers
+            .lock()
+            .expect("FATAL: banned peers lock poisoned");
+        self.peers
+            .iter()
+            .filter(|peer| !banned_peers.contains(*peer))
+            .cloned()
+            .collect()
+    }
+
+    /// Stop asking a full node for anything, for the rest of this process's lifetime
+    fn ban_peer(&self, peer: &str, reason: &str) {
+        warn!("Light client: banning {}: {}", peer, reason);
+        self.banned_peers
+            .lock()
+            .expect("FATAL: banned peers lock poisoned")
+            .insert(peer.to_string());
+    }
+
+    fn get_tip(&self) -> Option<StacksBlockId> {
+        self.light_client
+            .lock()
+            .expect("FATAL: light client lock poisoned")
+            .get_tip()
+            .cloned()
+    }
+
+    fn has_header(&self, index_block_hash: &StacksBlockId) -> bool {
+        self.light_client
+            .lock()
+            .expect("FATAL: light client lock poisoned")
+            .has_header(index_block_hash)
+    }
+
+    fn get_consensus_hash(&self, index_block_hash: &StacksBlockId) -> Option<ConsensusHash> {
+        self.light_client
+            .lock()
+            .expect("FATAL: light client lock poisoned")
+            .get_header(index_block_hash)
+            .map(|ext_header| ext_header.consensus_hash.clone())
+    }
+
+    /// Fetch the headers from `peer` that lead up to `target`, walking back until we reach a
+    /// header we already have, and accept them.  Returns Ok(None) if the peer does not have
+    /// `target`, and the number of new headers otherwise.
+    fn sync_headers(
+        &self,
+        sortdb: &SortitionDB,
+        peer: &str,
+        target: &StacksBlockId,
+    ) -> Result<Option<usize>, String> {
+        let mut segments = vec![];
+        let mut cursor = target.clone();
+        while !self.has_header(&cursor) {
+            let path = format!("/v2/headers/{}?tip={}", MAX_HEADERS, &cursor);
+            let headers: Vec<ExtendedStacksHeader> =
+                match task::block_on(fetch_json(peer, Method::Get, &path, None))? {
+                    Some(headers) => headers,
+                    None => return Ok(None),
+                };
+            let oldest_parent = match headers.last() {
+                Some(oldest) => oldest.parent_block_id.clone(),
+                None => return Err(format!("{} served no headers for {}", peer, &cursor)),
+            };
+            segments.push((cursor, headers));
+            cursor = oldest_parent;
+        }
+
+        // accept the oldest segment first, so each one connects to one we already accepted
+        let mut light_client = self
+            .light_client
+            .lock()
+            .expect("FATAL: light client lock poisoned");
+        let mut num_new = 0;
+        for (tip, headers) in segments.into_iter().rev() {
+            num_new += light_client
+                .accept_headers(sortdb, &tip, MAX_HEADERS as u64, headers)
+                .map_err(|e| format!("{} served invalid headers for {}: {:?}", peer, &tip, &e))?;
+        }
+        Ok(Some(num_new))
+    }
+
+    /// Ask each full node for its chain tip, and fetch headers up to it if it is ahead of ours
+    /// and we have processed its sortition.  Our own tip is re-chosen first, in case the
+    /// burnchain reorged since we last looked.
+    fn sync_peer_tips(&self, sortdb: &SortitionDB) {
+        if let Err(e) = self
+            .light_client
+            .lock()
+            .expect("FATAL: light client lock poisoned")
+            .update_tip(sortdb)
+        {
+            warn!("Light client: failed to update tip: {:?}", &e);
+        }
+
+        for peer in self.live_peers().iter() {
+            let peer_info: RPCPeerInfoData =
+                match task::block_on(fetch_json(peer, Method::Get, "/v2/info", None)) {
+                    Ok(Some(peer_info)) => peer_info,
+                    Ok(None) => {
+                        warn!("Light client: {} did not serve /v2/info", peer);
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("Light client: {}", e);
+                        continue;
+                    }
+                };
+
+            let peer_tip =
+                StacksBlockId::new(&peer_info.stacks_tip_consensus_hash, &peer_info.stacks_tip);
+            if self.has_header(&peer_tip) {
+                continue;
+            }
+            match SortitionDB::get_block_snapshot_consensus(
+                sortdb.conn(),
+                &peer_info.stacks_tip_consensus_hash,
+            ) {
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    debug!(
+                        "Light client: have not yet processed the sortition for {}'s tip {}",
+                        peer, &peer_tip
+                    );
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Light client: failed to query sortition DB: {:?}", &e);
+                    return;
+                }
+            }
+
+            match self.sync_headers(sortdb, peer, &peer_tip) {
+                Ok(Some(num_new)) => {
+                    info!(
+                        "Light client: accepted {} new header(s) from {}, up to {} (height {})",
+                        num_new, peer, &peer_tip, peer_info.stacks_tip_height
+                    );
+                }
+                Ok(None) => {
+                    debug!(
+                        "Light client: {} does not have its own tip {}",
+                        peer, &peer_tip
+                    );
+                }
+                Err(e) => {
+                    warn!("Light client: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Read a value from the full nodes at `tip`, and return the first response that `verify`
+    /// accepts.
+    async fn read_verified<T, F>(
+        &self,
+        method: Method,
+        path: &str,
+        tip: &StacksBlockId,
+        body: Option<String>,
+        verify: F,
+    ) -> Result<T, LightReadError>
+    where
+        T: DeserializeOwned,
+        F: Fn(&LightClient, &T) -> Result<(), NetError>,
+    {
+        let path = format!("{}?proof=1&tip={}", path, tip);
+        let mut not_found = false;
+        let mut last_error = "No light client peers available".to_string();
+        for peer in self.live_peers().iter() {
+            let response: T = match fetch_json(peer, method, &path, body.clone()).await {
+                Ok(Some(response)) => response,
+                Ok(None) => {
+                    not_found = true;
+                    continue;
+                }
+                Err(e) => {
+                    warn!("Light client: {}", &e);
+                    last_error = e;
+                    continue;
+                }
+            };
+
+            let result = {
+                let light_client = self
+                    .light_client
+                    .lock()
+                    .expect("FATAL: light client lock poisoned");
+                verify(&light_client, &response)
+            };
+            match result {
+                Ok(()) => {
+                    return Ok(response);
+                }
+                Err(NetError::NotFoundError) => {
+                    not_found = true;
+                }
+                Err(e) => {
+                    warn!(
+                        "Light client: {} served an unverifiable reply to {}: {:?}",
+                        peer, &path, &e
+                    );
+                    last_error = format!("{} served an unverifiable reply", peer);
+                }
+            }
+        }
+
+        if not_found {
+            Err(LightReadError::NotFound)
+        } else {
+            Err(LightReadError::Unavailable(last_error))
+        }
+    }
+
+    async fn read_account(
+        &self,
+        tip: &StacksBlockId,
+        principal: &str,
+    ) -> Result<AccountEntryResponse, LightReadError> {
+        let account = PrincipalData::parse(principal)
+            .map_err(|_| LightReadError::BadRequest("Invalid principal".into()))?;
+        self.read_verified(
+            Method::Get,
+            &format!("/v2/accounts/{}", principal),
+            tip,
+            None,
+            |light_client, entry| light_client.verify_account(tip, &account, entry),
+        )
+        .await
+    }
+
+    async fn read_data_var(
+        &self,
+        tip: &StacksBlockId,
+        address: &str,
+        contract: &str,
+        var: &str,
+    ) -> Result<DataVarResponse, LightReadError> {
+        let contract_identifier =
+            QualifiedContractIdentifier::parse(&format!("{}.{}", address, contract))
+                .map_err(|_| LightReadError::BadRequest("Invalid contract".into()))?;
+        let var_name = ClarityName::try_from(var.to_string())
+            .map_err(|_| LightReadError::BadRequest("Invalid data var name".into()))?;
+        self.read_verified(
+            Method::Get,
+            &format!("/v2/data_var/{}/{}/{}", address, contract, var),
+            tip,
+            None,
+            |light_client, entry| {
+                light_client.verify_data_var(tip, &contract_identifier, &var_name, entry)
+            },
+        )
+        .await
+    }
+
+    async fn read_map_entry(
+        &self,
+        tip: &StacksBlockId,
+        address: &str,
+        contract: &str,
+        map: &str,
+        body: String,
+    ) -> Result<MapEntryResponse, LightReadError> {
+        let contract_identifier =
+            QualifiedContractIdentifier::parse(&format!("{}.{}", address, contract))
+                .map_err(|_| LightReadError::BadRequest("Invalid contract".into()))?;
+        let map_name = ClarityName::try_from(map.to_string())
+            .map_err(|_| LightReadError::BadRequest("Invalid map name".into()))?;
+        let key_hex: String = serde_json::from_str(&body)
+            .map_err(|_| LightReadError::BadRequest("Failed to parse JSON body".into()))?;
+        let key = Value::try_deserialize_hex_untyped(&key_hex)
+            .map_err(|_| LightReadError::BadRequest("Failed to deserialize key value".into()))?;
+        self.read_verified(
+            Method::Post,
+            &format!("/v2/map_entry/{}/{}/{}", address, contract, map),
+            tip,
+            Some(body),
+            |light_client, entry| {
+                light_client.verify_map_entry(tip, &contract_identifier, &map_name, &key, entry)
+            },
+        )
+        .await
+    }
+}
+
+fn make_json_response<T: Serialize>(result: Result<T, LightReadError>) -> Response {
+    let (status, body) = match result {
+        Ok(data) => match serde_json::to_string(&data) {
+            Ok(body) => (StatusCode::Ok, body),
+            Err(e) => (
+                StatusCode::InternalServerError,
+                format!("Failed to serialize reply: {:?}", &e),
+            ),
+        },
+        Err(LightReadError::BadRequest(msg)) => (StatusCode::BadRequest, msg),
+        Err(LightReadError::NotFound) => (
+            StatusCode::NotFound,
+            "Not found, or no proof of it is available".to_string(),
+        ),
+        Err(LightReadError::Unavailable(msg)) => (StatusCode::BadGateway, msg),
+    };
+
+    let mut response = Response::new(status);
+    if status == StatusCode::Ok {
+        response.append_header("Content-Type", "application/json");
+    } else {
+        response.append_header("Content-Type", "text/plain");
+    }
+    response.set_body(Body::from(body));
+    response
+}
+
+async fn handle_request(
+    handle: LightClientHandle,
+    mut req: Request,
+) -> http_types::Result<Response> {
+    let path = req.url().path().to_string();
+    let segments: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    let tip = match handle.get_tip() {
+        Some(tip) => tip,
+        None => {
+            let mut response = Response::new(StatusCode::ServiceUnavailable);
+            response.set_body(Body::from("Light client has not synced any headers yet"));
+            return Ok(response);
+        }
+    };
+
+    let response = match (req.method(), segments.as_slice()) {
+        (Method::Get, ["v2", "accounts", principal]) => {
+            make_json_response(handle.read_account(&tip, principal).await)
+        }
+        (Method::Get, ["v2", "data_var", address, contract, var]) => {
+            make_json_response(handle.read_data_var(&tip, address, contract, var).await)
+        }
+        (Method::Post, ["v2", "map_entry", address, contract, map]) => {
+            let body = req.body_string().await?;
+            make_json_response(
+                handle
+                    .read_map_entry(&tip, address, contract, map, body)
+                    .await,
+            )
+        }
+        _ => {
+            let mut response = Response::new(StatusCode::NotFound);
+            response.set_body(Body::from("Not served by a light client"));
+            response
+        }
+    };
+    Ok(response)
+}
+
+/// Serve chain state reads on `bind_address` until the process exits
+fn serve_light_client_rpc(bind_address: String, handle: LightClientHandle) {
+    task::block_on(async {
+        let listener = TcpListener::bind(&bind_address)
+            .await
+            .expect("Light client: unable to bind RPC address");
+        info!("Light client: serving RPC on {}", &bind_address);
+
+        let mut incoming = listener.incoming();
+        while let Some(stream) = incoming.next().await {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(err) => {
+                    error!("Light client: unable to accept RPC connection - {:?}", err);
+                    continue;
+                }
+            };
+            let handle = handle.clone();
+            task::spawn(async move {
+                if let Err(err) =
+                    async_h1::accept(stream, |req| handle_request(handle.clone(), req)).await
+                {
+                    debug!("Light client: RPC connection error: {}", err);
+                }
+            });
+        }
+    });
+}
+
+/// How long the reward set provider waits before asking the full nodes again
+const LIGHT_CLIENT_RETRY_SECS: u64 = 10;
+
+/// How many times the reward set provider asks the full nodes before giving up.  The chains
+/// coordinator tries again when the next burnchain block arrives.
+const LIGHT_CLIENT_MAX_ATTEMPTS: u64 = 6;
+
+/// The light client's reward set provider.  See the module documentation.
+pub struct LightRewardSetProvider {
+    handle: LightClientHandle,
+    should_keep_running: Arc<AtomicBool>,
+}
+
+impl LightRewardSetProvider {
+    pub fn new(
+        handle: LightClientHandle,
+        should_keep_running: Arc<AtomicBool>,
+    ) -> LightRewardSetProvider {
+        LightRewardSetProvider {
+            handle,
+            should_keep_running,
+        }
+    }
+
+    /// Run `f` until it succeeds, until it has failed `LIGHT_CLIENT_MAX_ATTEMPTS` times, until
+    /// every full node is banned, or until the node is shutting down.
+    fn retry<T, F>(&self, f: F) -> Result<T, coord_error>
+    where
+        F: Fn() -> Result<T, String>,
+    {
+        let mut attempts = 0;
+        loop {
+            match f() {
+                Ok(result) => {
+                    return Ok(result);
+                }
+                Err(msg) => {
+                    warn!("Light client: {}", &msg);
+                    attempts += 1;
+                    if attempts >= LIGHT_CLIENT_MAX_ATTEMPTS
+                        || self.handle.live_peers().is_empty()
+                        || !self.should_keep_running.load(Ordering::SeqCst)
+                    {
+                        return Err(coord_error::ChainstateError(ChainstateError::NetError(
+                            NetError::ConnectionError,
+                        )));
+                    }
+                    thread::sleep(Duration::from_secs(LIGHT_CLIENT_RETRY_SECS));
+                }
+            }
+        }
+    }
+
+    /// The PoX contracts active at the anchor block, i.e. in the epoch of its sortition
+    fn get_pox_contracts(
+        &self,
+        sortdb: &SortitionDB,
+        anchor_block_id: &StacksBlockId,
+    ) -> Result<Vec<&'static str>, String> {
+        let consensus_hash = self
+            .handle
+            .get_consensus_hash(anchor_block_id)
+            .ok_or_else(|| format!("no header for anchor block {}", anchor_block_id))?;
+        let sn = SortitionDB::get_block_snapshot_consensus(sortdb.conn(), &consensus_hash)
+            .map_err(|e| format!("failed to query sortition DB: {:?}", &e))?
+            .ok_or_else(|| format!("no sortition for anchor block {}", anchor_block_id))?;
+        let epoch = SortitionDB::get_stacks_epoch(sortdb.conn(), sn.block_height)
+            .map_err(|e| format!("failed to query sortition DB: {:?}", &e))?
+            .ok_or_else(|| format!("no epoch for burn height {}", sn.block_height))?;
+        Ok(StacksChainState::pox_contracts_in_epoch(epoch.epoch_id))
+    }
+
+    /// Ask every full node for the PoX state the reward set is computed from as of the anchor
+    /// block, and check it against the anchor block's header.  Full nodes that serve state that
+    /// does not check out, or that claim a value is absent when another full node proved it, are
+    /// banned.
+    fn fetch_reward_set(
+        &self,
+        sortdb: &SortitionDB,
+        mainnet: bool,
+        reward_cycle: u64,
+        anchor_block_id: &StacksBlockId,
+    ) -> Result<ProvenRewardSet, String> {
+        let pox_contracts = self.get_pox_contracts(sortdb, anchor_block_id)?;
+        let path = format!(
+            "/v2/pox/reward_set/{}?tip={}",
+            reward_cycle, anchor_block_id
+        );
+
+        let mut reward_sets = vec![];
+        for peer in self.handle.live_peers().into_iter() {
+            let data: RPCRewardSetData =
+                match task::block_on(fetch_json(&peer, Method::Get, &path, None)) {
+                    Ok(Some(data)) => data,
+                    Ok(None) => {
+                        warn!(
+                            "Light client: {} does not have anchor block {}",
+                            &peer, anchor_block_id
+                        );
+                        continue;
+                    }
+                    Err(e) => {
+                        warn!("Light client: {}", e);
+                        continue;
+                    }
+                };
+            let proofs = match data.proofs {
+                Some(proofs) => proofs,
+                None => {
+                    warn!(
+                        "Light client: {} served the reward set without proofs",
+                        &peer
+                    );
+                    continue;
+                }
+            };
+
+            let result = self
+                .handle
+                .light_client
+                .lock()
+                .expect("FATAL: light client lock poisoned")
+                .verify_reward_set(
+                    anchor_block_id,
+                    mainnet,
+                    &pox_contracts,
+                    reward_cycle,
+                    &proofs,
+                );
+            match result {
+                Ok(reward_set) => {
+                    reward_sets.push((peer, reward_set));
+                }
+                Err(e) => {
+                    self.handle.ban_peer(
+                        &peer,
+                        &format!(
+                            "served an invalid reward set for reward cycle {}: {:?}",
+                            reward_cycle, &e
+                        ),
+                    );
+                }
+            }
+        }
+
+        let proven_keys: HashSet<&String> = reward_sets
+            .iter()
+            .flat_map(|(_, reward_set)| reward_set.proven_keys.iter())
+            .collect();
+        let mut agreed = None;
+        for (peer, reward_set) in reward_sets.iter() {
+            match reward_set
+                .absent_keys
+                .iter()
+                .find(|key| proven_keys.contains(key))
+            {
+                Some(key) => {
+                    self.handle.ban_peer(
+                        peer,
+                        &format!(
+                            "claimed '{}' is absent from the reward set for reward cycle {}",
+                            key, reward_cycle
+                        ),
+                    );
+                }
+                None => {
+                    agreed.get_or_insert_with(|| reward_set.clone());
+                }
+            }
+        }
+
+        agreed.ok_or_else(|| {
+            format!(
+                "no peer served a verifiable reward set for reward cycle {}",
+                reward_cycle
+            )
+        })
+    }
+
+    /// Fetch the anchor block's header from any full node that has it.  Returns false if every
+    /// full node says it does not have it.
+    fn fetch_anchor_header(
+        &self,
+        sortdb: &SortitionDB,
+        anchor_block_id: &StacksBlockId,
+    ) -> Result<bool, String> {
+        let peers = self.handle.live_peers();
+        let mut num_not_found = 0;
+        for peer in peers.iter() {
+            match self.handle.sync_headers(sortdb, peer, anchor_block_id) {
+                Ok(Some(_)) => {
+                    return Ok(true);
+                }
+                Ok(None) => {
+                    num_not_found += 1;
+                }
+                Err(e) => {
+                    warn!("Light client: {}", e);
+                }
+            }
+        }
+
+        if num_not_found == peers.len() {
+            Ok(false)
+        } else {
+            Err(format!(
+                "could not fetch the header of anchor block {}",
+                anchor_block_id
+            ))
+        }
+    }
+}
+
+impl RewardSetProvider for LightRewardSetProvider {
+    fn get_reward_set(
+        &self,
+        current_burn_height: u64,
+        _chainstate: &mut StacksChainState,
+        burnchain: &Burnchain,
+        sortdb: &SortitionDB,
+        block_id: &StacksBlockId,
+    ) -> Result<Vec<StacksAddress>, coord_error> {
+        let reward_cycle = burnchain
+            .block_height_to_reward_cycle(current_burn_height)
+            .ok_or_else(|| coord_error::ChainstateError(ChainstateError::PoxNoRewardCycle))?;
+
+        let reward_set = self.retry(|| {
+            self.fetch_reward_set(sortdb, burnchain.is_mainnet(), reward_cycle, block_id)
+        })?;
+
+        Ok(make_reward_set_from_registrations(
+            burnchain,
+            current_burn_height,
+            reward_set.registered_addrs,
+            reward_set.liquid_ustx,
+        ))
+    }
+
+    /// The anchor block is known if we have (or can fetch) its header.  It is unknown only if
+    /// every full node says it does not have it.
+    fn is_anchor_block_known(
+        &self,
+        _chainstate: &StacksChainState,
+        sortdb: &SortitionDB,
+        consensus_hash: &ConsensusHash,
+        stacks_block_hash: &BlockHeaderHash,
+    ) -> Result<bool, coord_error> {
+        let anchor_block_id = StacksBlockId::new(consensus_hash, stacks_block_hash);
+        if self.handle.has_header(&anchor_block_id) {
+            return Ok(true);
+        }
+        self.retry(|| self.fetch_anchor_header(sortdb, &anchor_block_id))
+    }
+}
+
+/// The light client's background threads: one follows the full nodes' headers, and one serves
+/// chain state reads.
+pub struct LightNode {
+    header_thread_handle: JoinHandle<()>,
+}
+
+impl LightNode {
+    pub fn spawn(
+        config: &Config,
+        handle: LightClientHandle,
+        should_keep_running: Arc<AtomicBool>,
+    ) -> LightNode {
+        let rpc_bind = config.node.rpc_bind.clone();
+        let rpc_handle = handle.clone();
+        thread::Builder::new()
+            .name("light-client-rpc".to_string())
+            .spawn(move || serve_light_client_rpc(rpc_bind, rpc_handle))
+            .expect("FATAL: failed to start light client RPC thread");
+
+        let sortdb_path = config.get_burn_db_file_path();
+        let poll_secs = config.burnchain.poll_time_secs;
+        let header_thread_handle = thread::Builder::new()
+            .name("light-client-headers".to_string())
+            .spawn(move || {
+                let sortdb = SortitionDB::open(&sortdb_path, false)
+                    .expect("FATAL: failed to open sortition DB");
+                while should_keep_running.load(Ordering::SeqCst) {
+                    handle.sync_peer_tips(&sortdb);
+                    thread::sleep(Duration::from_secs(poll_secs));
+                }
+            })
+            .expect("FATAL: failed to start light client header thread");
+
+        LightNode {
+            header_thread_handle,
+        }
+    }
+
+    pub fn join(self) {
+        self.header_thread_handle.join().unwrap();
+    }
+}
@@ -27,10 +27,12 @@ pub mod config;
 pub mod event_dispatcher;
 pub mod genesis_data;
 pub mod keychain;
+pub mod light_node;
 pub mod neon_node;
 pub mod node;
 pub mod operations;
 pub mod run_loop;
+pub mod signer;
 pub mod syncctl;
 pub mod tenure;
 
@@ -137,11 +139,17 @@ fn main() {
             let keychain = Keychain::default(seed);
             println!(
                 "Hex formatted secret key: {}",
-                keychain.generate_op_signer().get_sk_as_hex()
+                keychain
+                    .generate_op_signer()
+                    .get_sk_as_hex()
+                    .expect("FATAL: a keychain made from a seed has a secret key")
             );
             println!(
                 "WIF formatted secret key: {}",
-                keychain.generate_op_signer().get_sk_as_wif()
+                keychain
+                    .generate_op_signer()
+                    .get_sk_as_wif()
+                    .expect("FATAL: a keychain made from a seed has a secret key")
             );
             return;
         }
@@ -169,8 +177,13 @@ fn main() {
         || conf.burnchain.mode == "krypton"
         || conf.burnchain.mode == "mainnet"
     {
+        let light_client = conf.node.light_client;
         let mut run_loop = neon::RunLoop::new(conf);
-        run_loop.start(None, mine_start.unwrap_or(0));
+        if light_client {
+            run_loop.start_light(None);
+        } else {
+            run_loop.start(None, mine_start.unwrap_or(0));
+        }
     } else {
         println!("Burnchain mode '{}' not supported", conf.burnchain.mode);
     }
@@ -34,7 +34,7 @@ use stacks::chainstate::stacks::{
 use stacks::codec::StacksMessageCodec;
 use stacks::core::mempool::MemPoolDB;
 use stacks::core::FIRST_BURNCHAIN_CONSENSUS_HASH;
-use stacks::core::STACKS_EPOCH_2_05_MARKER;
+use stacks::core::STACKS_EPOCH_2_1_MARKER;
 use stacks::cost_estimates::metrics::UnitMetric;
 use stacks::cost_estimates::UnitEstimator;
 use stacks::monitoring::{increment_stx_blocks_mined_counter, update_active_miners_count_gauge};
@@ -271,7 +271,13 @@ fn rotate_vrf_and_register(
     burn_block: &BlockSnapshot,
     btc_controller: &mut BitcoinRegtestController,
 ) -> bool {
-    let vrf_pk = keychain.rotate_vrf_keypair(burn_block.block_height);
+    let vrf_pk = match keychain.rotate_vrf_keypair(burn_block.block_height) {
+        Some(vrf_pk) => vrf_pk,
+        None => {
+            warn!("Failed to obtain a VRF public key to register");
+            return false;
+        }
+    };
     let burnchain_tip_consensus_hash = &burn_block.consensus_hash;
     let op = inner_generate_leader_key_register_op(
         keychain.get_address(is_mainnet),
@@ -307,7 +313,7 @@ fn inner_generate_block_commit_op(
         apparent_sender: sender,
         key_block_ptr: key.block_height as u32,
         key_vtxindex: key.op_vtxindex as u16,
-        memo: vec![STACKS_EPOCH_2_05_MARKER],
+        memo: vec![STACKS_EPOCH_2_1_MARKER],
         new_seed: vrf_seed,
         parent_block_ptr,
         parent_vtxindex,
@@ -1027,7 +1033,7 @@ fn spawn_miner_relayer(
                                     bd
                                 };
 
-                                if let Err(e) = relayer.advertize_blocks(blocks_available, block_data) {
+                                if let Err(e) = relayer.advertize_blocks(blocks_available, block_data, &mem_pool) {
                                     warn!("Failed to advertise new block: {}", e);
                                 }
 
@@ -1237,7 +1243,7 @@ impl StacksNode {
         let miner = runloop.is_miner();
         let burnchain = runloop.get_burnchain();
         let atlas_config = AtlasConfig::default(config.is_mainnet());
-        let mut keychain = Keychain::default(config.node.seed.clone());
+        let mut keychain = config.get_keychain();
 
         // we can call _open_ here rather than _connect_, since connect is first called in
         //   make_genesis_block
@@ -1352,14 +1358,19 @@ impl StacksNode {
             tx.commit().unwrap();
         }
 
-        // update services to indicate we can support mempool sync
+        // update services to indicate we can support mempool sync (and, if configured, the
+        // encrypted transport and compact block relay)
         {
+            let mut services = (ServiceFlags::RPC as u16) | (ServiceFlags::RELAY as u16);
+            if config.connection_options.p2p_encryption {
+                services |= ServiceFlags::ENCRYPTION as u16;
+            }
+            if config.connection_options.compact_block_relay {
+                services |= ServiceFlags::COMPACT_BLOCKS as u16;
+            }
+
             let mut tx = peerdb.tx_begin().unwrap();
-            PeerDB::set_local_services(
-                &mut tx,
-                (ServiceFlags::RPC as u16) | (ServiceFlags::RELAY as u16),
-            )
-            .unwrap();
+            PeerDB::set_local_services(&mut tx, services).unwrap();
             tx.commit().unwrap();
         }
 
@@ -1419,7 +1430,9 @@ impl StacksNode {
 
         let leader_key_registration_state = if config.node.mock_mining {
             // mock mining, pretend to have a registered key
-            let vrf_public_key = keychain.rotate_vrf_keypair(1);
+            let vrf_public_key = keychain
+                .rotate_vrf_keypair(1)
+                .expect("FATAL: failed to obtain a VRF public key for mock mining");
             LeaderKeyRegistrationState::Active(RegisteredKey {
                 block_height: 1,
                 op_vtxindex: 1,
@@ -2114,6 +2127,10 @@ impl StacksNode {
         );
 
         let res = bitcoin_controller.submit_operation(op, &mut op_signer, attempt);
+        event_dispatcher.process_block_commit_status(
+            burn_block.block_height,
+            bitcoin_controller.get_block_commit_status(),
+        );
         if !res {
             if !config.node.mock_mining {
                 warn!("Failed to submit Bitcoin transaction");
@@ -287,7 +287,7 @@ impl Node {
             USE_TEST_GENESIS_CHAINSTATE
         };
 
-        let keychain = Keychain::default(config.node.seed.clone());
+        let keychain = config.get_keychain();
 
         let initial_balances = config
             .initial_balances
@@ -378,7 +378,7 @@ impl Node {
     ) -> Node {
         let burnchain_tip = burnchain_controller.get_chain_tip();
 
-        let keychain = Keychain::default(config.node.seed.clone());
+        let keychain = config.get_keychain();
 
         let mut event_dispatcher = EventDispatcher::new();
 
@@ -566,7 +566,8 @@ impl Node {
         let burnchain_tip = burnchain_controller.get_chain_tip();
         let vrf_pk = self
             .keychain
-            .rotate_vrf_keypair(burnchain_tip.block_snapshot.block_height);
+            .rotate_vrf_keypair(burnchain_tip.block_snapshot.block_height)
+            .expect("FATAL: failed to obtain a VRF public key");
         let consensus_hash = burnchain_tip.block_snapshot.consensus_hash;
         let key_reg_op = self.generate_leader_key_register_op(vrf_pk, &consensus_hash);
         let mut op_signer = self.keychain.generate_op_signer();
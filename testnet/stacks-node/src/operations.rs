@@ -1,8 +1,20 @@
+use std::sync::Arc;
+
 use stacks::util::secp256k1::{MessageSignature, Secp256k1PrivateKey, Secp256k1PublicKey};
 use stacks::{burnchains::PrivateKey, util::hash::hex_bytes};
+use stacks_common::deps_common::bitcoin::blockdata::script::Script;
+use stacks_common::deps_common::bitcoin::blockdata::transaction::Transaction;
+
+use crate::signer::{transaction_input_sighash, Error as SignerError, MinerSigner};
+
+/// Where the burnchain key lives
+enum OpSignerKey {
+    Local(Secp256k1PrivateKey),
+    External(Arc<dyn MinerSigner>),
+}
 
 pub struct BurnchainOpSigner {
-    secret_key: Secp256k1PrivateKey,
+    key: OpSignerKey,
     is_one_off: bool,
     is_disposed: bool,
     usages: u8,
@@ -11,37 +23,83 @@ pub struct BurnchainOpSigner {
 impl BurnchainOpSigner {
     pub fn new(secret_key: Secp256k1PrivateKey, is_one_off: bool) -> BurnchainOpSigner {
         BurnchainOpSigner {
-            secret_key,
+            key: OpSignerKey::Local(secret_key),
             usages: 0,
             is_one_off,
             is_disposed: false,
         }
     }
 
-    pub fn get_sk_as_wif(&self) -> String {
-        let hex_encoded = self.secret_key.to_hex();
+    /// Make an op signer whose signatures come from `signer`
+    pub fn from_external(signer: Arc<dyn MinerSigner>, is_one_off: bool) -> BurnchainOpSigner {
+        BurnchainOpSigner {
+            key: OpSignerKey::External(signer),
+            usages: 0,
+            is_one_off,
+            is_disposed: false,
+        }
+    }
+
+    fn secret_key(&self) -> Result<&Secp256k1PrivateKey, SignerError> {
+        match self.key {
+            OpSignerKey::Local(ref secret_key) => Ok(secret_key),
+            OpSignerKey::External(_) => Err(SignerError::NoSecretKey),
+        }
+    }
+
+    pub fn get_sk_as_wif(&self) -> Result<String, SignerError> {
+        let hex_encoded = self.secret_key()?.to_hex();
         let mut as_bytes = hex_bytes(&hex_encoded).unwrap();
         as_bytes.insert(0, 0x80);
-        stacks::address::b58::check_encode_slice(&as_bytes)
+        Ok(stacks::address::b58::check_encode_slice(&as_bytes))
     }
 
-    pub fn get_sk_as_hex(&self) -> String {
-        self.secret_key.to_hex()
+    pub fn get_sk_as_hex(&self) -> Result<String, SignerError> {
+        Ok(self.secret_key()?.to_hex())
     }
 
     pub fn get_public_key(&mut self) -> Secp256k1PublicKey {
-        let public_key = Secp256k1PublicKey::from_private(&self.secret_key);
-        public_key
+        match self.key {
+            OpSignerKey::Local(ref secret_key) => Secp256k1PublicKey::from_private(secret_key),
+            OpSignerKey::External(ref signer) => signer.get_public_key(),
+        }
     }
 
-    pub fn sign_message(&mut self, hash: &[u8]) -> Option<MessageSignature> {
+    /// Sign input `input_index` of `tx`, which spends an output locked by `script_pub_key`,
+    /// with SIGHASH_ALL.
+    pub fn sign_transaction_input(
+        &mut self,
+        tx: &Transaction,
+        input_index: usize,
+        script_pub_key: &Script,
+    ) -> Option<MessageSignature> {
         if self.is_disposed {
             return None;
         }
 
-        let signature = match self.secret_key.sign(hash) {
-            Ok(r) => r,
-            _ => return None,
+        let signature = match self.key {
+            OpSignerKey::Local(ref secret_key) => {
+                let sig_hash = match transaction_input_sighash(tx, input_index, script_pub_key) {
+                    Ok(sig_hash) => sig_hash,
+                    Err(e) => {
+                        warn!("Unable to compute sighash: {}", &e);
+                        return None;
+                    }
+                };
+                match secret_key.sign(sig_hash.as_bytes()) {
+                    Ok(r) => r,
+                    _ => return None,
+                }
+            }
+            OpSignerKey::External(ref signer) => {
+                match signer.sign_transaction_input(tx, input_index, script_pub_key) {
+                    Ok(r) => r,
+                    Err(e) => {
+                        warn!("External signer failed to sign transaction input: {}", &e);
+                        return None;
+                    }
+                }
+            }
         };
         self.usages += 1;
 
@@ -72,7 +130,7 @@ mod test {
         for (secret_key, expected_wif) in examples.iter() {
             let secp_k = Secp256k1PrivateKey::from_hex(secret_key).unwrap();
             let op_signer = BurnchainOpSigner::new(secp_k, false);
-            assert_eq!(expected_wif, &op_signer.get_sk_as_wif());
+            assert_eq!(expected_wif, &op_signer.get_sk_as_wif().unwrap());
         }
     }
 }
@@ -9,6 +9,7 @@ use std::sync::mpsc::Receiver;
 use std::sync::Arc;
 use std::thread;
 use std::thread::JoinHandle;
+use std::time::Duration;
 
 use std::collections::HashSet;
 
@@ -22,13 +23,16 @@ use stacks::chainstate::burn::db::sortdb::SortitionDB;
 use stacks::chainstate::coordinator::comm::{CoordinatorChannels, CoordinatorReceivers};
 use stacks::chainstate::coordinator::{
     migrate_chainstate_dbs, BlockEventDispatcher, ChainsCoordinator, CoordinatorCommunication,
-    Error as coord_error,
+    Error as coord_error, OnChainRewardSetProvider, RewardSetProvider,
 };
 use stacks::chainstate::stacks::db::{ChainStateBootData, StacksChainState};
+use stacks::core::FIRST_STACKS_BLOCK_ID;
 use stacks::net::atlas::{AtlasConfig, Attachment, AttachmentInstance, ATTACHMENTS_CHANNEL_SIZE};
+use stacks::net::light::LightClient;
 use stacks::util_lib::db::Error as db_error;
 use stx_genesis::GenesisData;
 
+use crate::light_node::{LightClientHandle, LightNode, LightRewardSetProvider};
 use crate::monitoring::start_serving_monitoring_metrics;
 use crate::neon_node::StacksNode;
 use crate::node::use_test_genesis_chainstate;
@@ -271,7 +275,7 @@ impl RunLoop {
     /// If there's a network error, then assume that we're not a miner.
     fn check_is_miner(&mut self, burnchain: &mut BitcoinRegtestController) -> bool {
         if self.config.node.miner {
-            let keychain = Keychain::default(self.config.node.seed.clone());
+            let keychain = self.config.get_keychain();
             let node_address = Keychain::address_from_burnchain_signer(
                 &keychain.get_burnchain_signer(),
                 self.config.is_mainnet(),
@@ -385,14 +389,8 @@ impl RunLoop {
         burnchain_controller
     }
 
-    /// Instantiate the Stacks chain state and start the chains coordinator thread.
-    /// Returns the coordinator thread handle, and the receiving end of the coordinator's atlas
-    /// attachment channel.
-    fn spawn_chains_coordinator(
-        &mut self,
-        burnchain_config: &Burnchain,
-        coordinator_receivers: CoordinatorReceivers,
-    ) -> (JoinHandle<()>, Receiver<HashSet<AttachmentInstance>>) {
+    /// Instantiate the Stacks chain state, booting it if it does not exist yet.
+    fn boot_chainstate(&mut self, burnchain_config: &Burnchain) -> StacksChainState {
         let use_test_genesis_data = use_test_genesis_chainstate(&self.config);
 
         // load up genesis balances
@@ -432,7 +430,7 @@ impl RunLoop {
             get_bulk_initial_names: Some(Box::new(move || get_names(use_test_genesis_data))),
         };
 
-        let (chain_state_db, receipts) = StacksChainState::open_and_exec(
+        let (mut chain_state_db, receipts) = StacksChainState::open_and_exec(
             self.config.is_mainnet(),
             self.config.burnchain.chain_id,
             &self.config.get_chainstate_path_str(),
@@ -440,8 +438,23 @@ impl RunLoop {
             Some(self.config.node.get_marf_opts()),
         )
         .unwrap();
+        chain_state_db.txindex = self.config.node.txindex;
+        chain_state_db.address_index = self.config.node.address_index;
+        chain_state_db.event_log = self.config.node.event_log;
         self.event_dispatcher.dispatch_boot_receipts(receipts);
+        chain_state_db
+    }
 
+    /// Start the chains coordinator thread, which computes reward sets with the given provider.
+    /// Returns the coordinator thread handle, and the receiving end of the coordinator's atlas
+    /// attachment channel.
+    fn spawn_chains_coordinator<U: RewardSetProvider + Send + 'static>(
+        &mut self,
+        burnchain_config: &Burnchain,
+        coordinator_receivers: CoordinatorReceivers,
+        chain_state_db: StacksChainState,
+        reward_set_provider: U,
+    ) -> (JoinHandle<()>, Receiver<HashSet<AttachmentInstance>>) {
         // NOTE: re-instantiate AtlasConfig so we don't have to keep the genesis attachments around
         let moved_atlas_config = AtlasConfig::default(self.config.is_mainnet());
         let moved_config = self.config.clone();
@@ -455,7 +468,7 @@ impl RunLoop {
                 let mut cost_estimator = moved_config.make_cost_estimator();
                 let mut fee_estimator = moved_config.make_fee_estimator();
 
-                ChainsCoordinator::run(
+                ChainsCoordinator::run_with_reward_set_provider(
                     chain_state_db,
                     moved_burnchain_config,
                     attachments_tx,
@@ -464,6 +477,7 @@ impl RunLoop {
                     moved_atlas_config,
                     cost_estimator.as_deref_mut(),
                     fee_estimator.as_deref_mut(),
+                    reward_set_provider,
                 );
             })
             .expect("FATAL: failed to start chains coordinator thread");
@@ -539,8 +553,13 @@ impl RunLoop {
         self.is_miner = Some(is_miner);
 
         // have headers; boot up the chains coordinator and instantiate the chain state
-        let (coordinator_thread_handle, attachments_rx) =
-            self.spawn_chains_coordinator(&burnchain_config, coordinator_receivers);
+        let chain_state_db = self.boot_chainstate(&burnchain_config);
+        let (coordinator_thread_handle, attachments_rx) = self.spawn_chains_coordinator(
+            &burnchain_config,
+            coordinator_receivers,
+            chain_state_db,
+            OnChainRewardSetProvider(),
+        );
         self.instantiate_pox_watchdog();
 
         // We announce a new burn block so that the chains coordinator
@@ -758,4 +777,97 @@ impl RunLoop {
             }
         }
     }
+
+    /// Starts the node runloop in light client mode (see `light_node`).
+    ///
+    /// This function will block by looping infinitely.  It keeps the burnchain and sortitions in
+    /// sync, and leaves following the Stacks chain and serving reads to the light client's
+    /// threads.  It never mines.
+    pub fn start_light(&mut self, burnchain_opt: Option<Burnchain>) {
+        if self.config.node.light_client_peers.len() == 0 {
+            error!("FATAL: light client mode requires at least one node in `light_client_peers`");
+            panic!();
+        }
+
+        let (coordinator_receivers, coordinator_senders) = self
+            .coordinator_channels
+            .take()
+            .expect("Run loop already started, can only start once after initialization.");
+
+        self.setup_termination_handler();
+        let mut burnchain =
+            self.instantiate_burnchain_state(burnchain_opt, coordinator_senders.clone());
+
+        let burnchain_config = burnchain.get_burnchain();
+        self.burnchain = Some(burnchain_config.clone());
+        self.is_miner = Some(false);
+
+        // the light client checks MARF proofs against the boot block's state, so it needs the
+        // (booted) chain state's genesis root hash
+        let mut chain_state_db = self.boot_chainstate(&burnchain_config);
+        let boot_state_root = chain_state_db
+            .with_clarity_marf(|marf| marf.get_root_hash_at(&FIRST_STACKS_BLOCK_ID))
+            .expect("FATAL: failed to load the boot block's state root");
+        let light_client_handle = LightClientHandle::new(
+            LightClient::new(boot_state_root),
+            self.config.node.light_client_peers.clone(),
+        );
+
+        let (coordinator_thread_handle, _attachments_rx) = self.spawn_chains_coordinator(
+            &burnchain_config,
+            coordinator_receivers,
+            chain_state_db,
+            LightRewardSetProvider::new(
+                light_client_handle.clone(),
+                self.should_keep_running.clone(),
+            ),
+        );
+        coordinator_senders.announce_new_burn_block();
+
+        let light_node = LightNode::spawn(
+            &self.config,
+            light_client_handle,
+            self.should_keep_running.clone(),
+        );
+        self.start_prometheus();
+
+        let mut burnchain_height = burnchain
+            .wait_for_sortitions(None)
+            .expect("Unable to get burnchain tip")
+            .block_snapshot
+            .block_height;
+
+        debug!(
+            "Begin light client runloop starting at burnchain block {}",
+            burnchain_height
+        );
+
+        loop {
+            if !self.should_keep_running.load(Ordering::SeqCst) {
+                info!("Terminating light client");
+                info!("Terminating chains-coordinator");
+
+                coordinator_senders.stop_chains_coordinator();
+                coordinator_thread_handle.join().unwrap();
+                light_node.join();
+
+                info!("Exiting stacks-node");
+                break;
+            }
+
+            let (_, tip_burnchain_height) = match burnchain.sync(Some(burnchain_height + 1)) {
+                Ok(x) => x,
+                Err(e) => {
+                    warn!("Burnchain controller stopped: {}", e);
+                    continue;
+                }
+            };
+
+            if burnchain_height >= tip_burnchain_height {
+                // caught up; wait for the next burnchain block
+                thread::sleep(Duration::from_secs(self.config.burnchain.poll_time_secs));
+            }
+            burnchain_height = cmp::min(burnchain_height + 1, tip_burnchain_height);
+        }
+    }
 }
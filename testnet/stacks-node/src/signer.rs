@@ -0,0 +1,900 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Miner key signers.
+//!
+//! A `MinerSigner` holds the miner's burnchain key and VRF keys, and performs every operation
+//! that needs them on the node's behalf.  `LocalSigner` keeps the keys in-process, derived from a
+//! seed exactly like `Keychain` does.  `UnixSocketSigner` forwards each request to an external
+//! signing daemon over a local Unix socket, so the keys never enter the node's process.
+//!
+//! The wire protocol is one JSON-encoded `SignerRequest` per connection, answered by one
+//! JSON-encoded `SignerResponse`, each terminated by a newline.  All binary values are hex-encoded.
+//! Burnchain signatures are requested for a transaction input, not a bare hash, so the signer
+//! computes the sighash itself and its `SignerPolicy` can see what it is signing.
+
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use std::error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+#[cfg(unix)]
+use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+#[cfg(unix)]
+use std::os::unix::net::{UnixListener, UnixStream};
+
+use stacks::burnchains::PublicKey;
+use stacks::util::hash::{hex_bytes, to_hex};
+use stacks::util::secp256k1::{MessageSignature, Secp256k1PublicKey};
+use stacks::util::vrf::{VRFProof, VRFPublicKey};
+use stacks_common::deps_common::bitcoin::blockdata::script::Script;
+use stacks_common::deps_common::bitcoin::blockdata::transaction::Transaction;
+use stacks_common::deps_common::bitcoin::network::serialize::{deserialize, serialize_hex};
+use stacks_common::deps_common::bitcoin::util::hash::Sha256dHash;
+
+use crate::keychain::Keychain;
+
+/// How long to wait on the external signer before giving up on a request.  Generous, since a
+/// signer may want a human to approve the request.
+pub const SIGNER_REQUEST_TIMEOUT_MS: u64 = 30_000;
+
+/// The only sighash type the node signs burnchain transaction inputs with
+pub const SIGHASH_ALL: u32 = 0x01;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Could not talk to the signer
+    Io(io::Error),
+    /// The signer's reply could not be decoded, or was not the reply we asked for
+    Protocol(String),
+    /// The signer refused the request
+    Rejected(String),
+    /// The burnchain secret key is held by an external signer
+    NoSecretKey,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Io(ref e) => fmt::Display::fmt(e, f),
+            Error::Protocol(ref s) => write!(f, "Signer protocol error: {}", s),
+            Error::Rejected(ref s) => write!(f, "Signer rejected request: {}", s),
+            Error::NoSecretKey => write!(f, "Secret key is held by an external signer"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Error::Io(ref e) => Some(e),
+            Error::Protocol(_) => None,
+            Error::Rejected(_) => None,
+            Error::NoSecretKey => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+/// Something that holds the miner's burnchain and VRF secret keys.
+pub trait MinerSigner: Send + Sync {
+    /// The public key of the burnchain key.  Implementations should cache this, since the node
+    /// asks for it often.
+    fn get_public_key(&self) -> Secp256k1PublicKey;
+
+    /// Sign input `input_index` of the burnchain transaction `tx`, which spends an output locked
+    /// by `script_pub_key`, with SIGHASH_ALL.
+    fn sign_transaction_input(
+        &self,
+        tx: &Transaction,
+        input_index: usize,
+        script_pub_key: &Script,
+    ) -> Result<MessageSignature, Error>;
+
+    /// Get (and remember) the VRF public key for a leader key registered at `block_height`.
+    fn get_vrf_public_key(&self, block_height: u64) -> Result<VRFPublicKey, Error>;
+
+    /// Prove `message` with the VRF secret key that goes with `vrf_pk`.
+    fn generate_vrf_proof(&self, vrf_pk: &VRFPublicKey, message: &[u8]) -> Result<VRFProof, Error>;
+}
+
+/// Compute the SIGHASH_ALL sighash of input `input_index` of `tx`.
+pub fn transaction_input_sighash(
+    tx: &Transaction,
+    input_index: usize,
+    script_pub_key: &Script,
+) -> Result<Sha256dHash, Error> {
+    if input_index >= tx.input.len() {
+        return Err(Error::Protocol(format!(
+            "no input {} in a transaction with {} inputs",
+            input_index,
+            tx.input.len()
+        )));
+    }
+    Ok(tx.signature_hash(input_index, script_pub_key, SIGHASH_ALL))
+}
+
+/// A signer that keeps the keys in-process, derived from a seed the same way the node's
+/// `Keychain` derives them.  The reference signing daemon uses this.
+pub struct LocalSigner {
+    keychain: Mutex<Keychain>,
+    public_key: Secp256k1PublicKey,
+    /// where to record the burnchain heights VRF keys are derived at, so the same keys can be
+    /// derived again after a restart
+    vrf_heights_path: Option<PathBuf>,
+}
+
+impl LocalSigner {
+    pub fn new(seed: Vec<u8>) -> LocalSigner {
+        let keychain = Keychain::default(seed);
+        let public_key = keychain.generate_op_signer().get_public_key();
+        LocalSigner {
+            keychain: Mutex::new(keychain),
+            public_key,
+            vrf_heights_path: None,
+        }
+    }
+
+    /// Like `new()`, but record the burnchain height of each VRF key we hand out in the file at
+    /// `vrf_heights_path`, and derive the VRF keys already recorded there.  Only heights are
+    /// recorded; the keys themselves are derived from the seed.
+    pub fn with_vrf_heights_file<P: AsRef<Path>>(
+        seed: Vec<u8>,
+        vrf_heights_path: P,
+    ) -> Result<LocalSigner, Error> {
+        let mut signer = LocalSigner::new(seed);
+        let vrf_heights_path = vrf_heights_path.as_ref().to_path_buf();
+        match fs::read_to_string(&vrf_heights_path) {
+            Ok(contents) => {
+                let mut keychain = signer
+                    .keychain
+                    .lock()
+                    .expect("FATAL: keychain lock poisoned");
+                for line in contents.lines().map(|line| line.trim()) {
+                    if line.is_empty() {
+                        continue;
+                    }
+                    let block_height: u64 = line.parse().map_err(|_| {
+                        io::Error::new(
+                            io::ErrorKind::InvalidData,
+                            format!(
+                                "bad VRF key height '{}' in {}",
+                                line,
+                                vrf_heights_path.display()
+                            ),
+                        )
+                    })?;
+                    keychain.rotate_vrf_keypair(block_height);
+                }
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+            Err(e) => {
+                return Err(e.into());
+            }
+        }
+        signer.vrf_heights_path = Some(vrf_heights_path);
+        Ok(signer)
+    }
+}
+
+impl MinerSigner for LocalSigner {
+    fn get_public_key(&self) -> Secp256k1PublicKey {
+        self.public_key
+    }
+
+    fn sign_transaction_input(
+        &self,
+        tx: &Transaction,
+        input_index: usize,
+        script_pub_key: &Script,
+    ) -> Result<MessageSignature, Error> {
+        let keychain = self.keychain.lock().expect("FATAL: keychain lock poisoned");
+        keychain
+            .generate_op_signer()
+            .sign_transaction_input(tx, input_index, script_pub_key)
+            .ok_or_else(|| Error::Rejected("failed to sign transaction input".to_string()))
+    }
+
+    fn get_vrf_public_key(&self, block_height: u64) -> Result<VRFPublicKey, Error> {
+        let mut keychain = self.keychain.lock().expect("FATAL: keychain lock poisoned");
+        let vrf_pk = keychain
+            .rotate_vrf_keypair(block_height)
+            .ok_or_else(|| Error::Rejected("failed to derive VRF key".to_string()))?;
+
+        // the node will ask us to prove with this key later, possibly after we restart
+        if let Some(ref vrf_heights_path) = self.vrf_heights_path {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(vrf_heights_path)?;
+            writeln!(file, "{}", block_height)?;
+            file.sync_all()?;
+        }
+        Ok(vrf_pk)
+    }
+
+    fn generate_vrf_proof(&self, vrf_pk: &VRFPublicKey, message: &[u8]) -> Result<VRFProof, Error> {
+        let message = <&[u8; 32]>::try_from(message)
+            .map_err(|_| Error::Rejected("VRF message must be 32 bytes".to_string()))?;
+        let keychain = self.keychain.lock().expect("FATAL: keychain lock poisoned");
+        keychain
+            .generate_proof(vrf_pk, message)
+            .ok_or_else(|| Error::Rejected(format!("no VRF secret key for {}", vrf_pk.to_hex())))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", rename_all = "snake_case")]
+pub enum SignerRequest {
+    GetPublicKey,
+    SignTransactionInput {
+        /// the consensus-serialized transaction
+        transaction: String,
+        input_index: u32,
+        /// the script of the output the input spends
+        script_pub_key: String,
+    },
+    GetVrfPublicKey {
+        block_height: u64,
+    },
+    VrfProve {
+        vrf_public_key: String,
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "result", rename_all = "snake_case")]
+pub enum SignerResponse {
+    PublicKey { public_key: String },
+    Signature { signature: String },
+    VrfPublicKey { vrf_public_key: String },
+    VrfProof { proof: String },
+    Error { message: String },
+}
+
+impl SignerRequest {
+    /// Make a request to sign input `input_index` of `tx`
+    pub fn sign_transaction_input(
+        tx: &Transaction,
+        input_index: usize,
+        script_pub_key: &Script,
+    ) -> Result<SignerRequest, Error> {
+        let transaction = serialize_hex(tx)
+            .map_err(|e| Error::Protocol(format!("failed to encode transaction: {:?}", &e)))?;
+        let input_index = u32::try_from(input_index)
+            .map_err(|_| Error::Protocol(format!("input index {} is too big", input_index)))?;
+        Ok(SignerRequest::SignTransactionInput {
+            transaction,
+            input_index,
+            script_pub_key: to_hex(script_pub_key.as_bytes()),
+        })
+    }
+
+    /// Decode a `SignTransactionInput` request's transaction, input index, and script.  Returns
+    /// Ok(None) for other requests.
+    pub fn decode_transaction_input(&self) -> Result<Option<(Transaction, usize, Script)>, Error> {
+        match self {
+            SignerRequest::SignTransactionInput {
+                transaction,
+                input_index,
+                script_pub_key,
+            } => {
+                let tx_bytes = hex_bytes(transaction)
+                    .map_err(|_| Error::Protocol("transaction is not hex".to_string()))?;
+                let tx: Transaction = deserialize(&tx_bytes)
+                    .map_err(|e| Error::Protocol(format!("bad transaction: {:?}", &e)))?;
+                let script_pub_key = hex_bytes(script_pub_key)
+                    .map_err(|_| Error::Protocol("script is not hex".to_string()))?;
+                Ok(Some((
+                    tx,
+                    *input_index as usize,
+                    Script::from(script_pub_key),
+                )))
+            }
+            _ => Ok(None),
+        }
+    }
+}
+
+/// A check the signing daemon runs on each request before it signs anything.  Policies that
+/// care what a burnchain signature is for can decode the transaction being signed with
+/// `SignerRequest::decode_transaction_input()`.
+pub trait SignerPolicy: Send {
+    fn check(&mut self, request: &SignerRequest) -> Result<(), String>;
+}
+
+/// Sign everything.
+pub struct AllowAllPolicy;
+
+impl SignerPolicy for AllowAllPolicy {
+    fn check(&mut self, _request: &SignerRequest) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Refuse to produce more than `max_signatures` burnchain signatures in any `window`.  This
+/// bounds how much BTC a compromised node can spend before someone notices.
+pub struct RateLimitPolicy {
+    max_signatures: usize,
+    window: Duration,
+    recent: VecDeque<Instant>,
+}
+
+impl RateLimitPolicy {
+    pub fn new(max_signatures: usize, window: Duration) -> RateLimitPolicy {
+        RateLimitPolicy {
+            max_signatures,
+            window,
+            recent: VecDeque::new(),
+        }
+    }
+}
+
+impl SignerPolicy for RateLimitPolicy {
+    fn check(&mut self, request: &SignerRequest) -> Result<(), String> {
+        if let SignerRequest::SignTransactionInput { .. } = request {
+            let now = Instant::now();
+            while let Some(oldest) = self.recent.front() {
+                if now.duration_since(*oldest) < self.window {
+                    break;
+                }
+                self.recent.pop_front();
+            }
+            if self.recent.len() >= self.max_signatures {
+                return Err(format!(
+                    "signed {} messages in the last {}s",
+                    self.recent.len(),
+                    self.window.as_secs()
+                ));
+            }
+            self.recent.push_back(now);
+        }
+        Ok(())
+    }
+}
+
+/// Answer a single request with the given signer, subject to the given policy.
+pub fn handle_request(
+    signer: &dyn MinerSigner,
+    policy: &mut dyn SignerPolicy,
+    request: SignerRequest,
+) -> SignerResponse {
+    if let Err(message) = policy.check(&request) {
+        return SignerResponse::Error { message };
+    }
+
+    let result = match request {
+        SignerRequest::GetPublicKey => Ok(SignerResponse::PublicKey {
+            public_key: signer.get_public_key().to_hex(),
+        }),
+        SignerRequest::SignTransactionInput { .. } => request
+            .decode_transaction_input()
+            .and_then(|decoded| {
+                decoded.ok_or_else(|| Error::Protocol("not a transaction input".to_string()))
+            })
+            .and_then(|(tx, input_index, script_pub_key)| {
+                signer.sign_transaction_input(&tx, input_index, &script_pub_key)
+            })
+            .map(|sig| SignerResponse::Signature {
+                signature: sig.to_hex(),
+            }),
+        SignerRequest::GetVrfPublicKey { block_height } => signer
+            .get_vrf_public_key(block_height)
+            .map(|vrf_pk| SignerResponse::VrfPublicKey {
+                vrf_public_key: vrf_pk.to_hex(),
+            }),
+        SignerRequest::VrfProve {
+            vrf_public_key,
+            message,
+        } => match (VRFPublicKey::from_hex(&vrf_public_key), hex_bytes(&message)) {
+            (Some(vrf_pk), Ok(message)) => {
+                signer
+                    .generate_vrf_proof(&vrf_pk, &message)
+                    .map(|proof| SignerResponse::VrfProof {
+                        proof: proof.to_hex(),
+                    })
+            }
+            _ => Err(Error::Protocol("malformed VRF request".to_string())),
+        },
+    };
+
+    result.unwrap_or_else(|e| SignerResponse::Error {
+        message: e.to_string(),
+    })
+}
+
+/// A signer that forwards every request to an external signing daemon listening on a local Unix
+/// socket.  Each request uses a fresh connection, so the daemon can be restarted under a running
+/// node.
+#[cfg(unix)]
+pub struct UnixSocketSigner {
+    socket_path: PathBuf,
+    timeout: Duration,
+    public_key: Secp256k1PublicKey,
+}
+
+#[cfg(unix)]
+impl UnixSocketSigner {
+    /// Connect to the daemon at `socket_path` and fetch the burnchain public key.
+    pub fn connect<P: AsRef<Path>>(
+        socket_path: P,
+        timeout: Duration,
+    ) -> Result<UnixSocketSigner, Error> {
+        let socket_path = socket_path.as_ref().to_path_buf();
+        let public_key = match Self::request(&socket_path, timeout, &SignerRequest::GetPublicKey)? {
+            SignerResponse::PublicKey { public_key } => {
+                Secp256k1PublicKey::from_hex(&public_key)
+                    .map_err(|e| Error::Protocol(format!("bad public key: {}", e)))?
+            }
+            resp => {
+                return Err(Error::Protocol(format!("unexpected reply {:?}", &resp)));
+            }
+        };
+
+        Ok(UnixSocketSigner {
+            socket_path,
+            timeout,
+            public_key,
+        })
+    }
+
+    fn request(
+        socket_path: &Path,
+        timeout: Duration,
+        request: &SignerRequest,
+    ) -> Result<SignerResponse, Error> {
+        let mut stream = UnixStream::connect(socket_path)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let mut line = serde_json::to_string(request)
+            .map_err(|e| Error::Protocol(format!("failed to encode request: {:?}", &e)))?;
+        line.push('\n');
+        stream.write_all(line.as_bytes())?;
+        stream.flush()?;
+
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+        match serde_json::from_str(&reply)
+            .map_err(|e| Error::Protocol(format!("failed to decode reply: {:?}", &e)))?
+        {
+            SignerResponse::Error { message } => Err(Error::Rejected(message)),
+            resp => Ok(resp),
+        }
+    }
+
+    fn send(&self, request: &SignerRequest) -> Result<SignerResponse, Error> {
+        Self::request(&self.socket_path, self.timeout, request)
+    }
+}
+
+#[cfg(unix)]
+impl MinerSigner for UnixSocketSigner {
+    fn get_public_key(&self) -> Secp256k1PublicKey {
+        self.public_key
+    }
+
+    fn sign_transaction_input(
+        &self,
+        tx: &Transaction,
+        input_index: usize,
+        script_pub_key: &Script,
+    ) -> Result<MessageSignature, Error> {
+        let sighash = transaction_input_sighash(tx, input_index, script_pub_key)?;
+        let request = SignerRequest::sign_transaction_input(tx, input_index, script_pub_key)?;
+        let signature = match self.send(&request)? {
+            SignerResponse::Signature { signature } => MessageSignature::from_hex(&signature)
+                .map_err(|e| Error::Protocol(format!("bad signature: {:?}", &e)))?,
+            resp => {
+                return Err(Error::Protocol(format!("unexpected reply {:?}", &resp)));
+            }
+        };
+
+        // don't hand the burnchain a signature it will reject
+        match self.public_key.verify(sighash.as_bytes(), &signature) {
+            Ok(true) => Ok(signature),
+            _ => Err(Error::Protocol(
+                "signature does not match the signer's public key".to_string(),
+            )),
+        }
+    }
+
+    fn get_vrf_public_key(&self, block_height: u64) -> Result<VRFPublicKey, Error> {
+        match self.send(&SignerRequest::GetVrfPublicKey { block_height })? {
+            SignerResponse::VrfPublicKey { vrf_public_key } => {
+                VRFPublicKey::from_hex(&vrf_public_key)
+                    .ok_or_else(|| Error::Protocol("bad VRF public key".to_string()))
+            }
+            resp => Err(Error::Protocol(format!("unexpected reply {:?}", &resp))),
+        }
+    }
+
+    fn generate_vrf_proof(&self, vrf_pk: &VRFPublicKey, message: &[u8]) -> Result<VRFProof, Error> {
+        let request = SignerRequest::VrfProve {
+            vrf_public_key: vrf_pk.to_hex(),
+            message: to_hex(message),
+        };
+        match self.send(&request)? {
+            SignerResponse::VrfProof { proof } => VRFProof::from_hex(&proof)
+                .ok_or_else(|| Error::Protocol("bad VRF proof".to_string())),
+            resp => Err(Error::Protocol(format!("unexpected reply {:?}", &resp))),
+        }
+    }
+}
+
+/// The server side of the Unix socket protocol, as run by a signing daemon.
+#[cfg(unix)]
+pub struct SignerServer {
+    listener: UnixListener,
+    signer: Box<dyn MinerSigner>,
+    policy: Box<dyn SignerPolicy>,
+}
+
+#[cfg(unix)]
+impl SignerServer {
+    /// Listen on `socket_path`, replacing a stale socket file if there is one.  Only the owner of
+    /// the daemon may connect.  The socket can only be restricted to its owner once it exists,
+    /// so its directory must be accessible only by its owner too; the directory is created with
+    /// mode 0700 if it does not exist, and binding fails if others can access it.
+    pub fn bind<P: AsRef<Path>>(
+        socket_path: P,
+        signer: Box<dyn MinerSigner>,
+        policy: Box<dyn SignerPolicy>,
+    ) -> Result<SignerServer, Error> {
+        let socket_path = socket_path.as_ref();
+        let socket_dir = match socket_path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        if !socket_dir.exists() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(socket_dir)?;
+        }
+        let dir_mode = fs::metadata(socket_dir)?.permissions().mode();
+        if dir_mode & 0o077 != 0 {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                format!(
+                    "{} is accessible by other users (mode {:o}); the signer socket must be in a directory with mode 0700",
+                    socket_dir.display(),
+                    dir_mode & 0o777
+                ),
+            )));
+        }
+
+        if socket_path.exists() {
+            fs::remove_file(socket_path)?;
+        }
+        let listener = UnixListener::bind(socket_path)?;
+        fs::set_permissions(socket_path, fs::Permissions::from_mode(0o600))?;
+        Ok(SignerServer {
+            listener,
+            signer,
+            policy,
+        })
+    }
+
+    /// Accept one connection and answer its request.
+    pub fn serve_one(&mut self) -> Result<(), Error> {
+        let (stream, _) = self.listener.accept()?;
+        stream.set_read_timeout(Some(Duration::from_millis(SIGNER_REQUEST_TIMEOUT_MS)))?;
+
+        let mut line = String::new();
+        let mut reader = BufReader::new(stream);
+        reader.read_line(&mut line)?;
+
+        let response = match serde_json::from_str::<SignerRequest>(&line) {
+            Ok(request) => {
+                debug!("Signer request: {:?}", &request);
+                let response = handle_request(self.signer.as_ref(), self.policy.as_mut(), request);
+                if let SignerResponse::Error { ref message } = response {
+                    warn!("Signer refused request: {}", message);
+                }
+                response
+            }
+            Err(e) => SignerResponse::Error {
+                message: format!("malformed request: {}", e),
+            },
+        };
+
+        let mut reply = serde_json::to_string(&response)
+            .map_err(|e| Error::Protocol(format!("failed to encode reply: {:?}", &e)))?;
+        reply.push('\n');
+        let mut stream = reader.into_inner();
+        stream.write_all(reply.as_bytes())?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    /// Answer requests until the process is killed.
+    pub fn serve_forever(&mut self) {
+        loop {
+            if let Err(e) = self.serve_one() {
+                warn!("Failed to serve signer request: {}", &e);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod test {
+    use super::*;
+
+    use std::sync::Arc;
+    use std::thread;
+
+    use stacks::util::vrf::VRF;
+    use stacks_common::deps_common::bitcoin::blockdata::transaction::{OutPoint, TxIn, TxOut};
+
+    fn test_socket_path(name: &str) -> PathBuf {
+        std::env::temp_dir()
+            .join(format!("{}-{}", name, rand::random::<u64>()))
+            .join("signer.sock")
+    }
+
+    fn spawn_server(name: &str, policy: Box<dyn SignerPolicy>, requests: usize) -> PathBuf {
+        let socket_path = test_socket_path(name);
+        let mut server = SignerServer::bind(
+            &socket_path,
+            Box::new(LocalSigner::new(vec![1, 2, 3])),
+            policy,
+        )
+        .unwrap();
+        thread::spawn(move || {
+            for _ in 0..requests {
+                server.serve_one().unwrap();
+            }
+        });
+        socket_path
+    }
+
+    fn test_transaction() -> (Transaction, Script) {
+        let tx = Transaction {
+            version: 1,
+            lock_time: 0,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Sha256dHash([0x44; 32]),
+                    vout: 1,
+                },
+                script_sig: Script::new(),
+                sequence: 0xfffffffd,
+                witness: vec![],
+            }],
+            output: vec![TxOut {
+                value: 12345,
+                script_pubkey: Script::from(vec![0x6a, 0x01, 0x02]),
+            }],
+        };
+        (tx, Script::from(vec![0x76, 0xa9, 0x14]))
+    }
+
+    #[test]
+    fn test_unix_socket_signer() {
+        // connect, sign, VRF key, VRF proof, unknown VRF key
+        let socket_path = spawn_server("test-unix-socket-signer", Box::new(AllowAllPolicy), 5);
+        let signer = UnixSocketSigner::connect(
+            &socket_path,
+            Duration::from_millis(SIGNER_REQUEST_TIMEOUT_MS),
+        )
+        .unwrap();
+
+        // same keys as a keychain with the same seed
+        let mut local = Keychain::default(vec![1, 2, 3]);
+        assert_eq!(
+            signer.get_public_key(),
+            local.generate_op_signer().get_public_key()
+        );
+
+        let (tx, script_pub_key) = test_transaction();
+        let sighash = transaction_input_sighash(&tx, 0, &script_pub_key).unwrap();
+        let sig = signer
+            .sign_transaction_input(&tx, 0, &script_pub_key)
+            .unwrap();
+        assert!(signer
+            .get_public_key()
+            .verify(sighash.as_bytes(), &sig)
+            .unwrap());
+
+        // no such input, caught before anything is sent
+        match signer.sign_transaction_input(&tx, 1, &script_pub_key) {
+            Err(Error::Protocol(_)) => {}
+            x => panic!("expected protocol error, got {:?}", &x),
+        }
+
+        let hash = [0x11u8; 32];
+        let vrf_pk = signer.get_vrf_public_key(100).unwrap();
+        assert_eq!(vrf_pk, local.rotate_vrf_keypair(100).unwrap());
+
+        let proof = signer.generate_vrf_proof(&vrf_pk, &hash).unwrap();
+        assert!(VRF::verify(&vrf_pk, &proof, &hash.to_vec()).unwrap());
+
+        let other_vrf_pk = local.rotate_vrf_keypair(101).unwrap();
+        match signer.generate_vrf_proof(&other_vrf_pk, &hash) {
+            Err(Error::Rejected(_)) => {}
+            x => panic!("expected rejection, got {:?}", &x),
+        }
+
+        fs::remove_dir_all(socket_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_keychain_with_external_signer() {
+        let socket_path =
+            spawn_server("test-keychain-external-signer", Box::new(AllowAllPolicy), 4);
+        let signer = UnixSocketSigner::connect(
+            &socket_path,
+            Duration::from_millis(SIGNER_REQUEST_TIMEOUT_MS),
+        )
+        .unwrap();
+
+        // the keychain's own seed is unrelated to the signer's
+        let mut keychain = Keychain::default(vec![4, 5, 6]);
+        keychain.set_external_signer(Arc::new(signer));
+        let local = Keychain::default(vec![1, 2, 3]);
+
+        assert_eq!(
+            keychain.get_burnchain_signer(),
+            local.get_burnchain_signer()
+        );
+
+        let mut op_signer = keychain.generate_op_signer();
+        let (tx, script_pub_key) = test_transaction();
+        let sighash = transaction_input_sighash(&tx, 0, &script_pub_key).unwrap();
+        let sig = op_signer
+            .sign_transaction_input(&tx, 0, &script_pub_key)
+            .unwrap();
+        assert!(op_signer
+            .get_public_key()
+            .verify(sighash.as_bytes(), &sig)
+            .unwrap());
+
+        // the signature is the one the keychain would have made itself
+        let local_sig = local
+            .generate_op_signer()
+            .sign_transaction_input(&tx, 0, &script_pub_key)
+            .unwrap();
+        assert_eq!(sig, local_sig);
+
+        // the secret key is not ours to give out
+        match op_signer.get_sk_as_hex() {
+            Err(Error::NoSecretKey) => {}
+            x => panic!("expected no secret key, got {:?}", &x),
+        }
+
+        let hash = [0x22u8; 32];
+        let vrf_pk = keychain.rotate_vrf_keypair(200).unwrap();
+        let proof = keychain.generate_proof(&vrf_pk, &hash).unwrap();
+        assert!(VRF::verify(&vrf_pk, &proof, &hash.to_vec()).unwrap());
+
+        fs::remove_dir_all(socket_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_rate_limit_policy() {
+        let socket_path = spawn_server(
+            "test-rate-limit-policy",
+            Box::new(RateLimitPolicy::new(2, Duration::from_secs(3600))),
+            5,
+        );
+        let signer = UnixSocketSigner::connect(
+            &socket_path,
+            Duration::from_millis(SIGNER_REQUEST_TIMEOUT_MS),
+        )
+        .unwrap();
+
+        let (tx, script_pub_key) = test_transaction();
+        signer
+            .sign_transaction_input(&tx, 0, &script_pub_key)
+            .unwrap();
+        signer
+            .sign_transaction_input(&tx, 0, &script_pub_key)
+            .unwrap();
+        match signer.sign_transaction_input(&tx, 0, &script_pub_key) {
+            Err(Error::Rejected(_)) => {}
+            x => panic!("expected rejection, got {:?}", &x),
+        }
+
+        // only signatures are rate-limited
+        signer.get_vrf_public_key(300).unwrap();
+
+        fs::remove_dir_all(socket_path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_decode_transaction_input() {
+        let (tx, script_pub_key) = test_transaction();
+        let request = SignerRequest::sign_transaction_input(&tx, 0, &script_pub_key).unwrap();
+
+        // survives the trip over the wire
+        let request: SignerRequest =
+            serde_json::from_str(&serde_json::to_string(&request).unwrap()).unwrap();
+        let (decoded_tx, input_index, decoded_script) =
+            request.decode_transaction_input().unwrap().unwrap();
+        assert_eq!(decoded_tx, tx);
+        assert_eq!(input_index, 0);
+        assert_eq!(decoded_script, script_pub_key);
+
+        assert!(SignerRequest::GetPublicKey
+            .decode_transaction_input()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_vrf_keys_survive_restart() {
+        let dir = std::env::temp_dir().join(format!(
+            "test-vrf-keys-survive-restart-{}",
+            rand::random::<u64>()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let vrf_heights_path = dir.join("vrf-heights");
+        let hash = [0x55u8; 32];
+
+        let signer = LocalSigner::with_vrf_heights_file(vec![1, 2, 3], &vrf_heights_path).unwrap();
+        let vrf_pk = signer.get_vrf_public_key(400).unwrap();
+        drop(signer);
+
+        // a fresh signer without the file has forgotten the key...
+        match LocalSigner::new(vec![1, 2, 3]).generate_vrf_proof(&vrf_pk, &hash) {
+            Err(Error::Rejected(_)) => {}
+            x => panic!("expected rejection, got {:?}", &x),
+        }
+
+        // ...but one with the file still proves with it
+        let signer = LocalSigner::with_vrf_heights_file(vec![1, 2, 3], &vrf_heights_path).unwrap();
+        let proof = signer.generate_vrf_proof(&vrf_pk, &hash).unwrap();
+        assert!(VRF::verify(&vrf_pk, &proof, &hash.to_vec()).unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_bind_refuses_shared_directory() {
+        let dir = std::env::temp_dir().join(format!(
+            "test-bind-refuses-shared-directory-{}",
+            rand::random::<u64>()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        fs::set_permissions(&dir, fs::Permissions::from_mode(0o755)).unwrap();
+
+        match SignerServer::bind(
+            dir.join("signer.sock"),
+            Box::new(LocalSigner::new(vec![1, 2, 3])),
+            Box::new(AllowAllPolicy),
+        ) {
+            Err(Error::Io(ref e)) if e.kind() == io::ErrorKind::PermissionDenied => {}
+            Err(e) => panic!("expected permission denied, got {:?}", &e),
+            Ok(_) => panic!("bound a socket in a shared directory"),
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
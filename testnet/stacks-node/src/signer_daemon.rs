@@ -0,0 +1,151 @@
+// Copyright (C) 2013-2020 Blockstack PBC, a public benefit corporation
+// Copyright (C) 2020-2022 Stacks Open Internet Foundation
+//
+// This program is free software: you can redistribute it and/or modify
+// it under the terms of the GNU General Public License as published by
+// the Free Software Foundation, either version 3 of the License, or
+// (at your option) any later version.
+//
+// This program is distributed in the hope that it will be useful,
+// but WITHOUT ANY WARRANTY; without even the implied warranty of
+// MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.  See the
+// GNU General Public License for more details.
+//
+// You should have received a copy of the GNU General Public License
+// along with this program.  If not, see <http://www.gnu.org/licenses/>.
+
+//! Reference signing daemon for a stacks-node miner.  It holds the miner's burnchain and VRF
+//! keys, derived from a seed file exactly as the node would derive them from `node.seed`, and
+//! answers signing requests on a Unix socket.
+
+// this binary only uses part of the modules it shares with stacks-node
+#![allow(dead_code)]
+
+extern crate rand;
+extern crate serde;
+
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate stacks_common;
+
+extern crate stacks;
+
+#[allow(unused_imports)]
+#[macro_use(o, slog_log, slog_trace, slog_debug, slog_info, slog_warn, slog_error)]
+extern crate slog;
+
+#[path = "keychain.rs"]
+mod keychain;
+#[path = "operations.rs"]
+mod operations;
+#[path = "signer.rs"]
+mod signer;
+
+use std::env;
+use std::fs;
+use std::process;
+use std::time::Duration;
+
+use pico_args::Arguments;
+
+use stacks::util::hash::hex_bytes;
+
+use crate::signer::{AllowAllPolicy, LocalSigner, MinerSigner, RateLimitPolicy, SignerPolicy};
+
+fn print_help() {
+    let argv: Vec<_> = env::args().collect();
+
+    eprintln!(
+        "\
+{} --socket=<path> --seed-file=<path> [OPTIONS]
+Hold a stacks-node miner's burnchain and VRF keys, and sign for the node over a Unix socket.
+Point the node at the socket with `signer_socket` in the `[miner]` section of its config.
+
+ARGUMENTS:
+
+\t\t--socket=<path>: path of the Unix socket to listen on.  Only this user may connect to it.
+\t\t\tIts directory must have mode 0700, and is created that way if it does not exist.
+\t\t--seed-file=<path>: file holding the hex-encoded seed, i.e. what was the node's `node.seed`.
+
+OPTIONAL ARGUMENTS:
+
+\t\t--max-signatures=<count>: refuse to produce more than <count> burnchain signatures per window.
+\t\t--window-secs=<secs>: length of the --max-signatures window (default 3600).
+\t\t--vrf-heights-file=<path>: file recording the burnchain heights of the VRF keys handed out,
+\t\t\tso they survive a restart (default <seed-file>.vrf-heights).
+
+",
+        argv[0]
+    );
+}
+
+#[cfg(unix)]
+fn main() {
+    let mut args = Arguments::from_env();
+    if args.contains(["-h", "--help"]) {
+        print_help();
+        return;
+    }
+
+    let socket_path: String = args.value_from_str("--socket").unwrap_or_else(|_| {
+        print_help();
+        process::exit(1);
+    });
+    let seed_path: String = args.value_from_str("--seed-file").unwrap_or_else(|_| {
+        print_help();
+        process::exit(1);
+    });
+    let max_signatures: Option<usize> = args
+        .opt_value_from_str("--max-signatures")
+        .expect("Failed to parse --max-signatures argument");
+    let window_secs: u64 = args
+        .opt_value_from_str("--window-secs")
+        .expect("Failed to parse --window-secs argument")
+        .unwrap_or(3600);
+    let vrf_heights_path: String = args
+        .opt_value_from_str("--vrf-heights-file")
+        .expect("Failed to parse --vrf-heights-file argument")
+        .unwrap_or_else(|| format!("{}.vrf-heights", &seed_path));
+    args.finish().unwrap();
+
+    let seed_hex = fs::read_to_string(&seed_path)
+        .unwrap_or_else(|e| panic!("Failed to read seed file {}: {:?}", &seed_path, &e));
+    let seed = hex_bytes(seed_hex.trim()).expect("Seed should be a hex encoded string");
+
+    let signer = LocalSigner::with_vrf_heights_file(seed, &vrf_heights_path).unwrap_or_else(|e| {
+        panic!(
+            "Failed to load VRF key heights from {}: {}",
+            &vrf_heights_path, &e
+        )
+    });
+    info!(
+        "Signing for burnchain public key {}",
+        signer.get_public_key().to_hex()
+    );
+
+    let policy: Box<dyn SignerPolicy> = match max_signatures {
+        Some(max_signatures) => {
+            info!(
+                "Signing at most {} messages every {}s",
+                max_signatures, window_secs
+            );
+            Box::new(RateLimitPolicy::new(
+                max_signatures,
+                Duration::from_secs(window_secs),
+            ))
+        }
+        None => Box::new(AllowAllPolicy),
+    };
+
+    let mut server = signer::SignerServer::bind(&socket_path, Box::new(signer), policy)
+        .unwrap_or_else(|e| panic!("Failed to listen on {}: {}", &socket_path, &e));
+    info!("Listening on {}", &socket_path);
+    server.serve_forever();
+}
+
+#[cfg(not(unix))]
+fn main() {
+    eprintln!("stacks-signer is only supported on Unix");
+    process::exit(1);
+}
@@ -206,7 +206,11 @@ pub fn make_contract_publish(
     let name = ContractName::from(contract_name);
     let code_body = StacksString::from_string(&contract_content.to_string()).unwrap();
 
-    let payload = TransactionSmartContract { name, code_body };
+    let payload = TransactionSmartContract {
+        name,
+        code_body,
+        clarity_version: None,
+    };
 
     serialize_sign_standard_single_sig_tx(payload.into(), sender, nonce, tx_fee)
 }
@@ -221,7 +225,11 @@ pub fn make_contract_publish_microblock_only(
     let name = ContractName::from(contract_name);
     let code_body = StacksString::from_string(&contract_content.to_string()).unwrap();
 
-    let payload = TransactionSmartContract { name, code_body };
+    let payload = TransactionSmartContract {
+        name,
+        code_body,
+        clarity_version: None,
+    };
 
     serialize_sign_standard_single_sig_tx_anchor_mode(
         payload.into(),
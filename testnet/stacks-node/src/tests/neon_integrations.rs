@@ -139,6 +139,8 @@ pub mod test_observer {
         pub static ref MEMTXS: Mutex<Vec<String>> = Mutex::new(Vec::new());
         pub static ref MEMTXS_DROPPED: Mutex<Vec<(String, String)>> = Mutex::new(Vec::new());
         pub static ref ATTACHMENTS: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
+        pub static ref REORGS: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
+        pub static ref BLOCK_COMMIT_STATUS: Mutex<Vec<serde_json::Value>> = Mutex::new(Vec::new());
     }
 
     async fn handle_burn_block(
@@ -208,6 +210,14 @@ pub mod test_observer {
         Ok(warp::http::StatusCode::OK)
     }
 
+    async fn handle_block_commit_status(
+        status: serde_json::Value,
+    ) -> Result<impl warp::Reply, Infallible> {
+        let mut block_commit_status = BLOCK_COMMIT_STATUS.lock().unwrap();
+        block_commit_status.push(status);
+        Ok(warp::http::StatusCode::OK)
+    }
+
     async fn handle_mempool_txs(txs: serde_json::Value) -> Result<impl warp::Reply, Infallible> {
         let new_rawtxs = txs
             .as_array()
@@ -251,6 +261,12 @@ pub mod test_observer {
         Ok(warp::http::StatusCode::OK)
     }
 
+    async fn handle_reorg(reorg: serde_json::Value) -> Result<impl warp::Reply, Infallible> {
+        let mut reorgs = REORGS.lock().unwrap();
+        reorgs.push(reorg);
+        Ok(warp::http::StatusCode::OK)
+    }
+
     pub fn get_memtxs() -> Vec<String> {
         MEMTXS.lock().unwrap().clone()
     }
@@ -283,6 +299,14 @@ pub mod test_observer {
         MINED_MICROBLOCKS.lock().unwrap().clone()
     }
 
+    pub fn get_reorgs() -> Vec<serde_json::Value> {
+        REORGS.lock().unwrap().clone()
+    }
+
+    pub fn get_block_commit_status() -> Vec<serde_json::Value> {
+        BLOCK_COMMIT_STATUS.lock().unwrap().clone()
+    }
+
     /// each path here should correspond to one of the paths listed in `event_dispatcher.rs`
     async fn serve() {
         let new_blocks = warp::path!("new_block")
@@ -317,6 +341,14 @@ pub mod test_observer {
             .and(warp::post())
             .and(warp::body::json())
             .and_then(handle_mined_microblock);
+        let reorgs = warp::path!("reorg")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(handle_reorg);
+        let block_commit_status = warp::path!("block_commit_status")
+            .and(warp::post())
+            .and(warp::body::json())
+            .and_then(handle_block_commit_status);
 
         info!("Spawning warp server");
         warp::serve(
@@ -327,7 +359,9 @@ pub mod test_observer {
                 .or(new_attachments)
                 .or(new_microblocks)
                 .or(mined_blocks)
-                .or(mined_microblocks),
+                .or(mined_microblocks)
+                .or(reorgs)
+                .or(block_commit_status),
         )
         .run(([127, 0, 0, 1], EVENT_OBSERVER_PORT))
         .await
@@ -348,6 +382,8 @@ pub mod test_observer {
         MEMTXS.lock().unwrap().clear();
         MEMTXS_DROPPED.lock().unwrap().clear();
         MINED_BLOCKS.lock().unwrap().clear();
+        REORGS.lock().unwrap().clear();
+        BLOCK_COMMIT_STATUS.lock().unwrap().clear();
     }
 }
 